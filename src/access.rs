@@ -0,0 +1,143 @@
+//! Access scoping for index storage on multi-user machines.
+//!
+//! Index directories are created private to their user (mode 0700, see
+//! [`create_private_dir`]), and an index that belongs to another user is
+//! refused ([`check_owner`]) rather than opened and left with WAL files its
+//! owner cannot write.  When the central store under `~/.wonk/repos` cannot
+//! be written, for example on a build server with a shared or read-only
+//! home, indexes are kept in the repository's `.wonk/` instead (see
+//! [`central_store_writable`] and [`crate::db::index_path_for`]).
+
+use std::ffi::CString;
+use std::fs::{self, DirBuilder};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::errors::AccessError;
+
+/// Mode of directories holding indexes: usable by their owner only.
+const PRIVATE_DIR_MODE: u32 = 0o700;
+
+/// Effective user ID of this process.
+fn current_uid() -> u32 {
+    // SAFETY: `geteuid` has no preconditions and cannot fail.
+    unsafe { libc::geteuid() }
+}
+
+/// Create `dir` and any missing parents with mode 0700.  An existing `dir`
+/// owned by the current user is narrowed to 0700 as well, so indexes built
+/// before it was private stop being readable by others.
+pub fn create_private_dir(dir: &Path) -> Result<()> {
+    DirBuilder::new()
+        .recursive(true)
+        .mode(PRIVATE_DIR_MODE)
+        .create(dir)
+        .with_context(|| format!("creating index directory {}", dir.display()))?;
+    let meta = fs::metadata(dir).with_context(|| format!("reading {}", dir.display()))?;
+    if meta.uid() == current_uid() && meta.mode() & 0o077 != 0 {
+        fs::set_permissions(dir, fs::Permissions::from_mode(PRIVATE_DIR_MODE))
+            .with_context(|| format!("restricting permissions of {}", dir.display()))?;
+    }
+    Ok(())
+}
+
+/// Refuse the index at `path` when it belongs to another user, or sits in
+/// another user's private directory.  Passes when `path` does not exist yet.
+pub fn check_owner(path: &Path) -> Result<(), AccessError> {
+    let owner = match fs::metadata(path) {
+        Ok(meta) => meta.uid(),
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+            let dir = path.parent().unwrap_or(path);
+            fs::metadata(dir).map_or(u32::MAX, |m| m.uid())
+        }
+        Err(_) => return Ok(()),
+    };
+    if owner == current_uid() {
+        return Ok(());
+    }
+    Err(AccessError::ForeignIndex {
+        path: path.display().to_string(),
+        owner,
+    })
+}
+
+/// Returns `true` when `path` exists and belongs to the current user.
+pub fn is_own(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|m| m.uid() == current_uid())
+}
+
+/// Returns `true` when indexes can be created under `store`: it belongs to
+/// the current user if it exists, and otherwise its nearest existing
+/// ancestor is a directory the current user can write to.
+pub fn central_store_writable(store: &Path) -> bool {
+    if store.exists() {
+        return is_own(store) && writable_dir(store);
+    }
+    store
+        .ancestors()
+        .find(|p| p.exists())
+        .is_some_and(writable_dir)
+}
+
+/// Returns `true` when `dir` is a directory the current user may create
+/// entries in.
+fn writable_dir(dir: &Path) -> bool {
+    if !dir.is_dir() {
+        return false;
+    }
+    let Ok(c_path) = CString::new(dir.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // SAFETY: `c_path` is a valid NUL-terminated string.
+    unsafe { libc::access(c_path.as_ptr(), libc::W_OK | libc::X_OK) == 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mode(path: &Path) -> u32 {
+        fs::metadata(path).unwrap().mode() & 0o777
+    }
+
+    #[test]
+    fn private_dirs_are_created_and_narrowed() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path().join(".wonk/repos/abc");
+        create_private_dir(&store).unwrap();
+        assert_eq!(mode(&store), 0o700);
+        assert_eq!(mode(&dir.path().join(".wonk")), 0o700);
+
+        let open = dir.path().join("open");
+        fs::create_dir(&open).unwrap();
+        fs::set_permissions(&open, fs::Permissions::from_mode(0o755)).unwrap();
+        create_private_dir(&open).unwrap();
+        assert_eq!(mode(&open), 0o700);
+    }
+
+    #[test]
+    fn own_indexes_pass_the_owner_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = dir.path().join("index.db");
+        assert!(check_owner(&index).is_ok(), "missing index passes");
+        fs::write(&index, b"").unwrap();
+        assert!(check_owner(&index).is_ok());
+        assert!(is_own(&index));
+    }
+
+    #[test]
+    fn store_writability() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(central_store_writable(&dir.path().join(".wonk/repos")));
+        create_private_dir(&dir.path().join(".wonk/repos")).unwrap();
+        assert!(central_store_writable(&dir.path().join(".wonk/repos")));
+
+        // A home that is a file (or otherwise not a directory) cannot hold it.
+        let file = dir.path().join("home");
+        fs::write(&file, b"").unwrap();
+        assert!(!central_store_writable(&file.join(".wonk/repos")));
+    }
+}
@@ -0,0 +1,500 @@
+//! Archive input for `wonk init --archive`.
+//!
+//! Reads the members of a tarball (`.tar`, `.tar.gz`/`.tgz`), a zip file, or
+//! the `HEAD` tree of a bare git repository into memory so they can be
+//! indexed without unpacking to disk.  The format is detected from the
+//! leading bytes, not the file name.  Leading directories (such as the
+//! `repo-<sha>/` wrapper of GitHub source tarballs) can be dropped with
+//! `--strip-components`, as with `tar`.
+
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+
+/// Members larger than this are skipped: they are not worth parsing, and the
+/// cap bounds memory for hostile archives.
+const MAX_MEMBER_BYTES: u64 = 16 * 1024 * 1024;
+
+const TAR_BLOCK: usize = 512;
+
+/// A regular file read from an archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// Path relative to the archive root.
+    pub path: PathBuf,
+    pub contents: Vec<u8>,
+}
+
+/// Read every regular file in the archive or bare git repository at `path`,
+/// dropping the first `strip` path components (and members that have no
+/// more).
+pub fn read(path: &Path, strip: usize) -> Result<Vec<ArchiveEntry>> {
+    let entries = if path.is_dir() {
+        if !is_bare_repo(path) {
+            bail!(
+                "{} is a directory, not an archive or bare git repository",
+                path.display()
+            );
+        }
+        read_bare_repo(path)?
+    } else {
+        let bytes = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+        read_bytes(&bytes).with_context(|| format!("reading archive {}", path.display()))?
+    };
+    Ok(strip_components(entries, strip))
+}
+
+/// Parse an in-memory archive, detecting gzip, zip, or tar.
+fn read_bytes(bytes: &[u8]) -> Result<Vec<ArchiveEntry>> {
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        read_tar(flate2::read::MultiGzDecoder::new(bytes))
+    } else if bytes.starts_with(b"PK\x03\x04") || bytes.starts_with(b"PK\x05\x06") {
+        read_zip(bytes)
+    } else if bytes.len() >= 263 && &bytes[257..262] == b"ustar" {
+        read_tar(bytes)
+    } else {
+        bail!("unrecognised archive format (expected .tar, .tar.gz, or .zip)")
+    }
+}
+
+fn is_bare_repo(path: &Path) -> bool {
+    path.join("HEAD").is_file() && path.join("objects").is_dir() && !path.join(".git").exists()
+}
+
+/// The `HEAD` tree of a bare repository, via `git archive`.
+fn read_bare_repo(path: &Path) -> Result<Vec<ArchiveEntry>> {
+    let output = Command::new("git")
+        .arg("--git-dir")
+        .arg(path)
+        .args(["archive", "--format=tar", "HEAD"])
+        .output()
+        .context("running git archive (is git installed?)")?;
+    if !output.status.success() {
+        bail!(
+            "git archive failed for {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    read_tar(output.stdout.as_slice())
+}
+
+/// Normalise a member name; `None` for absolute paths or `..` components.
+fn member_path(name: &str) -> Option<PathBuf> {
+    let mut path = PathBuf::new();
+    for component in Path::new(name).components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!path.as_os_str().is_empty()).then_some(path)
+}
+
+// ---------------------------------------------------------------------------
+// tar
+// ---------------------------------------------------------------------------
+
+/// Parse a ustar/pax/GNU tar stream, keeping regular files.
+fn read_tar(mut reader: impl Read) -> Result<Vec<ArchiveEntry>> {
+    let mut entries = Vec::new();
+    // Name from a preceding pax `x` or GNU `L` header.
+    let mut long_name: Option<String> = None;
+    let mut header = [0u8; TAR_BLOCK];
+
+    loop {
+        if !read_block(&mut reader, &mut header)? || header.iter().all(|&b| b == 0) {
+            break;
+        }
+        let size = tar_size(&header[124..136])?;
+        let padded = size.div_ceil(TAR_BLOCK as u64) * TAR_BLOCK as u64;
+        let typeflag = header[156];
+
+        match typeflag {
+            b'x' | b'L' => {
+                let data = read_member(&mut reader, size, padded)?;
+                long_name = if typeflag == b'L' {
+                    Some(c_string(&data))
+                } else {
+                    pax_path(&data).or(long_name)
+                };
+            }
+            b'0' | 0 if size <= MAX_MEMBER_BYTES => {
+                let name = long_name.take().unwrap_or_else(|| ustar_name(&header));
+                let contents = read_member(&mut reader, size, padded)?;
+                if let Some(path) = member_path(&name) {
+                    entries.push(ArchiveEntry { path, contents });
+                }
+            }
+            _ => {
+                // Directories, links, pax global headers, oversized files.
+                long_name = None;
+                skip(&mut reader, padded)?;
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Fill `block`; `false` at a clean end of stream.
+fn read_block(reader: &mut impl Read, block: &mut [u8; TAR_BLOCK]) -> Result<bool> {
+    let mut filled = 0;
+    while filled < TAR_BLOCK {
+        let n = reader.read(&mut block[filled..]).context("reading tar")?;
+        if n == 0 {
+            if filled == 0 {
+                return Ok(false);
+            }
+            bail!("truncated tar header");
+        }
+        filled += n;
+    }
+    Ok(true)
+}
+
+/// Read `size` bytes of member data and skip the padding up to `padded`.
+fn read_member(reader: &mut impl Read, size: u64, padded: u64) -> Result<Vec<u8>> {
+    let mut data = Vec::with_capacity(size as usize);
+    reader
+        .by_ref()
+        .take(size)
+        .read_to_end(&mut data)
+        .context("reading tar member")?;
+    if data.len() as u64 != size {
+        bail!("truncated tar member");
+    }
+    skip(reader, padded - size)?;
+    Ok(data)
+}
+
+fn skip(reader: &mut impl Read, n: u64) -> Result<()> {
+    let skipped = std::io::copy(&mut reader.by_ref().take(n), &mut std::io::sink())?;
+    if skipped != n {
+        bail!("truncated tar member");
+    }
+    Ok(())
+}
+
+/// Octal size field (NUL/space terminated).
+fn tar_size(field: &[u8]) -> Result<u64> {
+    if field[0] & 0x80 != 0 {
+        bail!("tar members over 8 GiB are not supported");
+    }
+    let text = std::str::from_utf8(field).unwrap_or_default();
+    let digits = text.trim_matches(|c: char| c == '\0' || c == ' ');
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(digits, 8).with_context(|| format!("invalid tar size {digits:?}"))
+}
+
+fn c_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+/// `prefix/name` from a ustar header.
+fn ustar_name(header: &[u8; TAR_BLOCK]) -> String {
+    let name = c_string(&header[0..100]);
+    let prefix = if &header[257..262] == b"ustar" {
+        c_string(&header[345..500])
+    } else {
+        String::new()
+    };
+    if prefix.is_empty() {
+        name
+    } else {
+        format!("{prefix}/{name}")
+    }
+}
+
+/// The `path` record of a pax extended header (`"<len> path=<value>\n"`).
+fn pax_path(data: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(data);
+    text.lines().find_map(|record| {
+        let (_, kv) = record.split_once(' ')?;
+        kv.strip_prefix("path=").map(str::to_string)
+    })
+}
+
+// ---------------------------------------------------------------------------
+// zip
+// ---------------------------------------------------------------------------
+
+const ZIP_END_SIG: u32 = 0x0605_4b50;
+const ZIP_CENTRAL_SIG: u32 = 0x0201_4b50;
+const ZIP_LOCAL_SIG: u32 = 0x0403_4b50;
+
+fn u16_at(bytes: &[u8], at: usize) -> Result<u16> {
+    bytes
+        .get(at..at + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .context("truncated zip")
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Result<u32> {
+    bytes
+        .get(at..at + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .context("truncated zip")
+}
+
+/// Parse a zip file through its central directory, keeping stored and
+/// deflated regular files.
+fn read_zip(bytes: &[u8]) -> Result<Vec<ArchiveEntry>> {
+    // The end-of-central-directory record is the last 22 bytes plus an
+    // optional comment of up to 64 KiB.
+    let end = (0..=bytes.len().saturating_sub(22))
+        .rev()
+        .take(22 + usize::from(u16::MAX))
+        .find(|&at| u32_at(bytes, at).ok() == Some(ZIP_END_SIG))
+        .context("zip end of central directory not found")?;
+    let count = usize::from(u16_at(bytes, end + 10)?);
+    let mut at = u32_at(bytes, end + 16)? as usize;
+    if count == usize::from(u16::MAX) || at == u32::MAX as usize {
+        bail!("zip64 archives are not supported");
+    }
+
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        if u32_at(bytes, at)? != ZIP_CENTRAL_SIG {
+            bail!("corrupt zip central directory");
+        }
+        let method = u16_at(bytes, at + 10)?;
+        let compressed = u64::from(u32_at(bytes, at + 20)?);
+        let size = u64::from(u32_at(bytes, at + 24)?);
+        let name_len = usize::from(u16_at(bytes, at + 28)?);
+        let extra_len = usize::from(u16_at(bytes, at + 30)?);
+        let comment_len = usize::from(u16_at(bytes, at + 32)?);
+        let local = u32_at(bytes, at + 42)? as usize;
+        let name = bytes
+            .get(at + 46..at + 46 + name_len)
+            .context("truncated zip")?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        at += 46 + name_len + extra_len + comment_len;
+
+        if name.ends_with('/') || size > MAX_MEMBER_BYTES {
+            continue;
+        }
+        let Some(path) = member_path(&name) else {
+            continue;
+        };
+        if u32_at(bytes, local)? != ZIP_LOCAL_SIG {
+            bail!("corrupt zip local header for {name}");
+        }
+        let data_start = local
+            + 30
+            + usize::from(u16_at(bytes, local + 26)?)
+            + usize::from(u16_at(bytes, local + 28)?);
+        let data = bytes
+            .get(data_start..data_start + compressed as usize)
+            .context("truncated zip")?;
+        let contents = match method {
+            0 => data.to_vec(),
+            8 => {
+                let mut out = Vec::with_capacity(size as usize);
+                flate2::read::DeflateDecoder::new(data)
+                    .take(MAX_MEMBER_BYTES + 1)
+                    .read_to_end(&mut out)
+                    .with_context(|| format!("inflating {name}"))?;
+                out
+            }
+            // Other compression methods are rare for source archives.
+            _ => continue,
+        };
+        if contents.len() as u64 == size {
+            entries.push(ArchiveEntry { path, contents });
+        }
+    }
+    Ok(entries)
+}
+
+// ---------------------------------------------------------------------------
+// Paths
+// ---------------------------------------------------------------------------
+
+fn strip_components(entries: Vec<ArchiveEntry>, strip: usize) -> Vec<ArchiveEntry> {
+    if strip == 0 {
+        return entries;
+    }
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            let path: PathBuf = entry.path.components().skip(strip).collect();
+            (!path.as_os_str().is_empty()).then_some(ArchiveEntry {
+                path,
+                contents: entry.contents,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// A ustar header for a regular file (or `typeflag`) with `data`.
+    fn tar_member(name: &str, typeflag: u8, data: &[u8]) -> Vec<u8> {
+        let mut header = [0u8; TAR_BLOCK];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        header[100..108].copy_from_slice(b"0000644\0");
+        header[124..136].copy_from_slice(format!("{:011o}\0", data.len()).as_bytes());
+        header[156] = typeflag;
+        header[257..263].copy_from_slice(b"ustar\0");
+        let mut out = header.to_vec();
+        out.extend_from_slice(data);
+        out.resize(out.len().div_ceil(TAR_BLOCK) * TAR_BLOCK, 0);
+        out
+    }
+
+    fn tar(members: &[Vec<u8>]) -> Vec<u8> {
+        let mut out: Vec<u8> = members.concat();
+        out.extend_from_slice(&[0u8; TAR_BLOCK * 2]);
+        out
+    }
+
+    fn zip_stored(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut central = Vec::new();
+        for (name, data) in files {
+            let offset = out.len() as u32;
+            out.extend_from_slice(&ZIP_LOCAL_SIG.to_le_bytes());
+            out.extend_from_slice(&[20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            out.extend_from_slice(&0u16.to_le_bytes());
+            out.extend_from_slice(name.as_bytes());
+            out.extend_from_slice(data);
+
+            central.extend_from_slice(&ZIP_CENTRAL_SIG.to_le_bytes());
+            central.extend_from_slice(&[20, 0, 20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            central.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            central.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            central.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            central.extend_from_slice(&[0; 12]);
+            central.extend_from_slice(&offset.to_le_bytes());
+            central.extend_from_slice(name.as_bytes());
+        }
+        let central_at = out.len() as u32;
+        out.extend_from_slice(&central);
+        out.extend_from_slice(&ZIP_END_SIG.to_le_bytes());
+        out.extend_from_slice(&[0, 0, 0, 0]);
+        out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+        out.extend_from_slice(&central_at.to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes());
+        out
+    }
+
+    fn paths(entries: &[ArchiveEntry]) -> Vec<String> {
+        entries
+            .iter()
+            .map(|e| e.path.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn reads_tar_and_gzip_members() {
+        let bytes = tar(&[
+            tar_member("src/", b'5', b""),
+            tar_member("src/lib.rs", b'0', b"fn a() {}\n"),
+            tar_member("README.md", b'0', b"# hi\n"),
+        ]);
+        let entries = read_bytes(&bytes).unwrap();
+        assert_eq!(paths(&entries), vec!["src/lib.rs", "README.md"]);
+        assert_eq!(entries[0].contents, b"fn a() {}\n");
+
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        gz.write_all(&bytes).unwrap();
+        let entries = read_bytes(&gz.finish().unwrap()).unwrap();
+        assert_eq!(paths(&entries), vec!["src/lib.rs", "README.md"]);
+    }
+
+    #[test]
+    fn tar_long_names_and_unsafe_paths() {
+        let long = format!("{}/main.py", "d".repeat(120));
+        let pax = format!("{} path={long}\n", long.len() + 12);
+        let bytes = tar(&[
+            tar_member("pax_global_header", b'g', b"52 comment=abc\n"),
+            tar_member("PaxHeader", b'x', pax.as_bytes()),
+            tar_member("truncated", b'0', b"x = 1\n"),
+            tar_member("../escape.rs", b'0', b"fn evil() {}\n"),
+            tar_member("/abs.rs", b'0', b"fn evil() {}\n"),
+        ]);
+        let entries = read_bytes(&bytes).unwrap();
+        assert_eq!(paths(&entries), vec![long]);
+    }
+
+    #[test]
+    fn reads_zip_members() {
+        let bytes = zip_stored(&[("pkg/", b""), ("pkg/a.go", b"package a\n")]);
+        let entries = read_bytes(&bytes).unwrap();
+        assert_eq!(paths(&entries), vec!["pkg/a.go"]);
+        assert_eq!(entries[0].contents, b"package a\n");
+    }
+
+    #[test]
+    fn rejects_unknown_formats() {
+        let err = read_bytes(b"just some text").unwrap_err();
+        assert!(err.to_string().contains("unrecognised archive format"));
+    }
+
+    #[test]
+    fn strip_components_drops_leading_directories() {
+        let entry = |p: &str| ArchiveEntry {
+            path: PathBuf::from(p),
+            contents: Vec::new(),
+        };
+        let entries = vec![
+            entry("repo-abc/src/a.rs"),
+            entry("repo-abc/b.rs"),
+            entry("top.rs"),
+        ];
+        assert_eq!(
+            paths(&strip_components(entries.clone(), 0)),
+            vec!["repo-abc/src/a.rs", "repo-abc/b.rs", "top.rs"]
+        );
+        assert_eq!(
+            paths(&strip_components(entries, 1)),
+            vec!["src/a.rs", "b.rs"]
+        );
+    }
+
+    #[test]
+    fn reads_bare_git_repository() {
+        let has_git = Command::new("git").arg("--version").output().is_ok();
+        if !has_git {
+            return;
+        }
+        let dir = tempfile::TempDir::new().unwrap();
+        let work = dir.path().join("work");
+        std::fs::create_dir_all(work.join("src")).unwrap();
+        std::fs::write(work.join("src/lib.rs"), "pub fn f() {}\n").unwrap();
+        let git = |args: &[&str], cwd: &Path| {
+            let status = Command::new("git")
+                .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+                .args(args)
+                .current_dir(cwd)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {args:?}");
+        };
+        git(&["init", "-q"], &work);
+        git(&["add", "."], &work);
+        git(&["commit", "-qm", "init"], &work);
+        git(&["clone", "-q", "--bare", "work", "bare.git"], dir.path());
+
+        let entries = read(&dir.path().join("bare.git"), 0).unwrap();
+        assert_eq!(paths(&entries), vec!["src/lib.rs"]);
+
+        let err = read(&work, 0).unwrap_err();
+        assert!(err.to_string().contains("not an archive"));
+    }
+}
@@ -3,6 +3,16 @@
 //! Provides a simple heuristic (~4 characters per token) for estimating token
 //! counts and a [`TokenBudget`] struct that tracks cumulative consumption,
 //! allowing callers to stop emitting results once a budget is exhausted.
+//! [`distribute`] splits a budget across result groups by weight, so ranked
+//! output keeps room for its most important categories.  A [`SessionBudget`]
+//! file (`--budget-file`) carries a budget across the commands of one agent
+//! session.
+
+use std::os::fd::AsRawFd;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 
 /// Estimate the number of tokens in `text` using the ~4 chars/token heuristic.
 ///
@@ -23,6 +33,9 @@ pub struct TokenBudget {
     limit: usize,
     used: usize,
     skip: usize,
+    /// Consumption stops here when it is below `limit` (see
+    /// [`set_ceiling`](Self::set_ceiling)).
+    ceiling: Option<usize>,
 }
 
 impl TokenBudget {
@@ -32,6 +45,7 @@ impl TokenBudget {
             limit,
             used: 0,
             skip: 0,
+            ceiling: None,
         }
     }
 
@@ -42,6 +56,7 @@ impl TokenBudget {
             limit,
             used: 0,
             skip,
+            ceiling: None,
         }
     }
 
@@ -71,21 +86,7 @@ impl TokenBudget {
     ///
     /// When skip > 0, deducts from skip and returns `false` (skipped).
     pub fn try_consume(&mut self, text: &str) -> bool {
-        let tokens = estimate_tokens(text);
-        if self.skip > 0 {
-            if tokens <= self.skip {
-                self.skip -= tokens;
-            } else {
-                self.skip = 0;
-            }
-            return false;
-        }
-        if tokens + self.used <= self.limit {
-            self.used += tokens;
-            true
-        } else {
-            false
-        }
+        self.consume(estimate_tokens(text))
     }
 
     /// Try to consume tokens estimated from a byte length. Same semantics as
@@ -93,7 +94,17 @@ impl TokenBudget {
     /// eliminating the need for `String::from_utf8_lossy` when working with
     /// raw byte buffers.
     pub fn try_consume_bytes(&mut self, byte_len: usize) -> bool {
-        let tokens = estimate_tokens_from_len(byte_len);
+        self.consume(estimate_tokens_from_len(byte_len))
+    }
+
+    /// Cap consumption at `ceiling` tokens in total, below the limit, until
+    /// it is cleared with `None`.  Used to hold back part of the budget for
+    /// results emitted later.
+    pub fn set_ceiling(&mut self, ceiling: Option<usize>) {
+        self.ceiling = ceiling;
+    }
+
+    fn consume(&mut self, tokens: usize) -> bool {
         if self.skip > 0 {
             if tokens <= self.skip {
                 self.skip -= tokens;
@@ -102,7 +113,8 @@ impl TokenBudget {
             }
             return false;
         }
-        if tokens + self.used <= self.limit {
+        let cap = self.ceiling.map_or(self.limit, |c| c.min(self.limit));
+        if tokens + self.used <= cap {
             self.used += tokens;
             true
         } else {
@@ -111,6 +123,151 @@ impl TokenBudget {
     }
 }
 
+/// Split `total` tokens across groups given as `(weight, demand)`, where
+/// `demand` is the tokens a group needs to be shown in full.
+///
+/// Each group gets a share of `total` in proportion to its weight, but never
+/// more than its demand; what a group leaves unused is shared among the
+/// others by weight, until every group is satisfied or the budget is spent.
+/// Groups of weight 0 only get what is left after all others.
+pub fn distribute(total: usize, groups: &[(u32, usize)]) -> Vec<usize> {
+    let mut alloc = vec![0usize; groups.len()];
+    let mut left = total;
+    let mut open: Vec<usize> = (0..groups.len())
+        .filter(|&i| groups[i].0 > 0 && groups[i].1 > 0)
+        .collect();
+    while !open.is_empty() && left > 0 {
+        let weights: u64 = open.iter().map(|&i| u64::from(groups[i].0)).sum();
+        let share = |i: usize| (left as u64 * u64::from(groups[i].0) / weights) as usize;
+        let (satisfied, unsatisfied): (Vec<usize>, Vec<usize>) =
+            open.iter().partition(|&&i| groups[i].1 <= share(i));
+        if satisfied.is_empty() {
+            for &i in &unsatisfied {
+                alloc[i] = share(i);
+            }
+            left -= unsatisfied.iter().map(|&i| alloc[i]).sum::<usize>();
+            break;
+        }
+        for i in satisfied {
+            alloc[i] = groups[i].1;
+            left -= groups[i].1;
+        }
+        open = unsatisfied;
+    }
+    for (i, &(weight, demand)) in groups.iter().enumerate() {
+        if weight == 0 && left > 0 {
+            alloc[i] = demand.min(left);
+            left -= alloc[i];
+        }
+    }
+    alloc
+}
+
+/// Tokens a new session budget file allows until `wonk budget reset
+/// --limit` sets another limit.
+pub const DEFAULT_SESSION_LIMIT: usize = 50_000;
+
+/// A token budget shared by every query of one agent session, stored as
+/// JSON at the path given with `--budget-file`.
+///
+/// Each query is capped at what is left and charged what it printed once it
+/// is done; when nothing is left, queries are refused until the file is
+/// reset.  Only results counted by the query's [`TokenBudget`] are charged:
+/// headers written straight to the output and hints or warnings on stderr
+/// are not.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionBudget {
+    /// Tokens the whole session may use.
+    pub limit: usize,
+    /// Tokens used so far.
+    pub used: usize,
+    /// Number of queries charged so far.
+    #[serde(default)]
+    pub commands: usize,
+}
+
+impl SessionBudget {
+    /// An unused budget of `limit` tokens.
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            used: 0,
+            commands: 0,
+        }
+    }
+
+    /// Read the budget at `path`; a missing file is a fresh budget of
+    /// [`DEFAULT_SESSION_LIMIT`] tokens.
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json)
+                .with_context(|| format!("reading budget file {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Ok(Self::new(DEFAULT_SESSION_LIMIT))
+            }
+            Err(e) => Err(e).with_context(|| format!("reading budget file {}", path.display())),
+        }
+    }
+
+    /// Write the budget to `path`, replacing it in one step so concurrent
+    /// readers never see a partial file.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension(format!("tmp.{}", std::process::id()));
+        let mut json = serde_json::to_string_pretty(self)?;
+        json.push('\n');
+        std::fs::write(&tmp, json).with_context(|| format!("writing {}", tmp.display()))?;
+        std::fs::rename(&tmp, path).with_context(|| format!("writing {}", path.display()))
+    }
+
+    /// Tokens left before queries are refused.
+    pub fn remaining(&self) -> usize {
+        self.limit.saturating_sub(self.used)
+    }
+
+    /// Charge one query's `tokens` to the budget at `path`.  The file is
+    /// re-read and written back under an exclusive lock, so charges from
+    /// queries running alongside this one are all kept.
+    pub fn charge(path: &Path, tokens: usize) -> Result<Self> {
+        let _lock = lock(path)?;
+        let mut budget = Self::load(path)?;
+        budget.used += tokens;
+        budget.commands += 1;
+        budget.save(path)?;
+        Ok(budget)
+    }
+
+    /// Start the budget at `path` over with `limit` tokens, or its current
+    /// limit when `None`.  A malformed file is replaced rather than kept.
+    pub fn reset(path: &Path, limit: Option<usize>) -> Result<Self> {
+        let _lock = lock(path)?;
+        let limit =
+            limit.unwrap_or_else(|| Self::load(path).map_or(DEFAULT_SESSION_LIMIT, |b| b.limit));
+        let budget = Self::new(limit);
+        budget.save(path)?;
+        Ok(budget)
+    }
+}
+
+/// Take an exclusive `flock` on `<path>.lock`, released when the returned
+/// file is closed.  The budget file itself is replaced on every save, so it
+/// cannot carry the lock.
+fn lock(path: &Path) -> Result<std::fs::File> {
+    let mut lock_path = path.as_os_str().to_owned();
+    lock_path.push(".lock");
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("opening {}", Path::new(&lock_path).display()))?;
+    // SAFETY: flock only operates on the open descriptor owned by `file`.
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+        return Err(std::io::Error::last_os_error())
+            .with_context(|| format!("locking {}", Path::new(&lock_path).display()));
+    }
+    Ok(file)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -201,4 +358,113 @@ mod tests {
         assert!(budget.try_consume(""));
         assert_eq!(budget.used(), 0);
     }
+
+    #[test]
+    fn ceiling_holds_back_the_rest_of_the_budget() {
+        let mut budget = TokenBudget::new(10);
+        budget.set_ceiling(Some(2));
+        assert!(budget.try_consume("abcdefgh")); // 2 tokens
+        assert!(!budget.try_consume("abcd"));
+        budget.set_ceiling(None);
+        assert!(budget.try_consume("abcd"));
+        assert_eq!(budget.used(), 3);
+    }
+
+    // -- distribute ----------------------------------------------------------
+
+    #[test]
+    fn distribute_splits_by_weight_when_every_group_overflows() {
+        assert_eq!(
+            distribute(100, &[(4, 500), (2, 500), (1, 500)]),
+            [57, 28, 14]
+        );
+    }
+
+    #[test]
+    fn distribute_passes_unused_share_to_other_groups() {
+        // The second group needs less than its share; the others split the rest.
+        assert_eq!(
+            distribute(100, &[(4, 500), (4, 10), (2, 500)]),
+            [60, 10, 30]
+        );
+        // Everything fits.
+        assert_eq!(distribute(100, &[(4, 20), (1, 30)]), [20, 30]);
+    }
+
+    #[test]
+    fn distribute_gives_zero_weights_the_leftovers() {
+        assert_eq!(distribute(100, &[(0, 50), (1, 70)]), [30, 70]);
+        assert_eq!(distribute(100, &[(0, 50), (1, 200)]), [0, 100]);
+        assert_eq!(distribute(0, &[(1, 5)]), [0]);
+    }
+
+    // -- SessionBudget -------------------------------------------------------
+
+    #[test]
+    fn session_budget_accumulates_across_charges() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        assert_eq!(
+            SessionBudget::load(&path).unwrap(),
+            SessionBudget::new(DEFAULT_SESSION_LIMIT)
+        );
+
+        SessionBudget::new(100).save(&path).unwrap();
+        SessionBudget::charge(&path, 30).unwrap();
+        let budget = SessionBudget::charge(&path, 90).unwrap();
+        assert_eq!(budget.used, 120);
+        assert_eq!(budget.commands, 2);
+        assert_eq!(budget.remaining(), 0);
+        assert_eq!(SessionBudget::load(&path).unwrap(), budget);
+    }
+
+    #[test]
+    fn session_budget_keeps_concurrent_charges() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        SessionBudget::new(100_000).save(&path).unwrap();
+
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for _ in 0..25 {
+                        SessionBudget::charge(&path, 3).unwrap();
+                    }
+                });
+            }
+        });
+        let budget = SessionBudget::load(&path).unwrap();
+        assert_eq!(budget.commands, 200);
+        assert_eq!(budget.used, 600);
+    }
+
+    #[test]
+    fn session_budget_reset_keeps_the_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        SessionBudget::new(100).save(&path).unwrap();
+        SessionBudget::charge(&path, 30).unwrap();
+        assert_eq!(
+            SessionBudget::reset(&path, None).unwrap(),
+            SessionBudget::new(100)
+        );
+        assert_eq!(
+            SessionBudget::reset(&path, Some(7)).unwrap(),
+            SessionBudget::new(7)
+        );
+        std::fs::write(&path, "not json").unwrap();
+        assert_eq!(
+            SessionBudget::reset(&path, None).unwrap(),
+            SessionBudget::new(DEFAULT_SESSION_LIMIT)
+        );
+    }
+
+    #[test]
+    fn session_budget_rejects_malformed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        std::fs::write(&path, "not json").unwrap();
+        let err = SessionBudget::load(&path).unwrap_err();
+        assert!(format!("{err:#}").contains("reading budget file"));
+    }
 }
@@ -11,7 +11,9 @@ use std::str::FromStr;
 use anyhow::Result;
 use rusqlite::{Connection, OptionalExtension};
 
-use crate::types::{CallPathHop, CalleeResult, CallerResult, SymbolKind};
+use crate::errors::DbError;
+use crate::resolve::{Definition, Resolver};
+use crate::types::{CallPathHop, CalleeResult, CallerResult, Reference, ReferenceKind, SymbolKind};
 
 /// Maximum allowed depth for transitive expansion.
 pub const MAX_DEPTH_CAP: usize = 10;
@@ -82,7 +84,8 @@ fn find_symbol_ids_with_scope(
 
 /// Find all callers of the given symbol name, with BFS transitive expansion.
 ///
-/// At depth 1, returns direct callers (functions whose body references `name`).
+/// At depth 1, returns direct callers (functions whose body calls `name`, or
+/// renders it as a JSX component); type annotations and imports do not count.
 /// At depth N > 1, also returns callers of callers up to N levels.
 /// File-scope call sites (where `caller_id` is NULL) are returned with
 /// `caller_name` set to `"<module>"`.
@@ -127,7 +130,7 @@ pub fn callers(
         "SELECT DISTINCT s.name, s.kind, s.file, s.line, s.signature, r.file AS ref_file, r.confidence \
          FROM \"references\" r \
          JOIN symbols s ON r.caller_id = s.id \
-         WHERE r.name = ?1 AND r.confidence >= ?2 \
+         WHERE r.name = ?1 AND r.confidence >= ?2 AND (r.kind IS NULL OR r.kind IN ('call', 'component')) \
          AND (?3 = '' OR r.target_id IN (SELECT id FROM symbols WHERE name = ?1 AND file LIKE '%' || ?3 || '%' ESCAPE '\\')) \
          AND (?4 = '' OR s.file LIKE '%' || ?4 || '%' ESCAPE '\\')",
     )?;
@@ -137,14 +140,14 @@ pub fn callers(
         "SELECT DISTINCT s.name, s.kind, s.file, s.line, s.signature, r.file AS ref_file, r.confidence \
          FROM \"references\" r \
          JOIN symbols s ON r.caller_id = s.id \
-         WHERE r.name = ?1 AND r.confidence >= ?2",
+         WHERE r.name = ?1 AND r.confidence >= ?2 AND (r.kind IS NULL OR r.kind IN ('call', 'component'))",
     )?;
 
     // Filtered module-scope statement for depth 1.
     let mut stmt_module_filtered = conn.prepare(
         "SELECT DISTINCT r.file, r.line, r.confidence \
          FROM \"references\" r \
-         WHERE r.name = ?1 AND r.caller_id IS NULL AND r.confidence >= ?2 \
+         WHERE r.name = ?1 AND r.caller_id IS NULL AND r.confidence >= ?2 AND (r.kind IS NULL OR r.kind IN ('call', 'component')) \
          AND (?3 = '' OR r.target_id IN (SELECT id FROM symbols WHERE name = ?1 AND file LIKE '%' || ?3 || '%' ESCAPE '\\')) \
          AND (?4 = '' OR r.file LIKE '%' || ?4 || '%' ESCAPE '\\')",
     )?;
@@ -153,7 +156,7 @@ pub fn callers(
     let mut stmt_module_plain = conn.prepare(
         "SELECT DISTINCT r.file, r.line, r.confidence \
          FROM \"references\" r \
-         WHERE r.name = ?1 AND r.caller_id IS NULL AND r.confidence >= ?2",
+         WHERE r.name = ?1 AND r.caller_id IS NULL AND r.confidence >= ?2 AND (r.kind IS NULL OR r.kind IN ('call', 'component'))",
     )?;
 
     let has_filters = !ref_file_pat.is_empty() || !callers_file_pat.is_empty();
@@ -290,8 +293,9 @@ pub fn callers(
 
 /// Find all callees of the given symbol name, with BFS transitive expansion.
 ///
-/// At depth 1, returns direct callees (symbols referenced within the body of
-/// functions named `name`). At depth N > 1, also returns callees of callees.
+/// At depth 1, returns direct callees (symbols called or rendered as JSX
+/// components within the body of functions named `name`). At depth N > 1,
+/// also returns callees of callees.
 ///
 /// `reference_file` disambiguates which source symbol `name` refers to.
 /// `callees_file` filters the returned callees by file (via `target_id`).
@@ -325,7 +329,7 @@ pub fn callees(
         "SELECT DISTINCT r.name, r.file, r.line, r.context, s.file AS source_file, r.confidence \
          FROM \"references\" r \
          JOIN symbols s ON s.id = r.caller_id \
-         WHERE s.name = ?1 AND r.confidence >= ?2 \
+         WHERE s.name = ?1 AND r.confidence >= ?2 AND (r.kind IS NULL OR r.kind IN ('call', 'component')) \
          AND (?3 = '' OR s.file LIKE '%' || ?3 || '%' ESCAPE '\\') \
          AND (?4 = '' OR r.target_id IN (SELECT id FROM symbols WHERE name = r.name AND file LIKE '%' || ?4 || '%' ESCAPE '\\'))",
     )?;
@@ -335,7 +339,7 @@ pub fn callees(
         "SELECT DISTINCT r.name, r.file, r.line, r.context, s.file AS source_file, r.confidence \
          FROM \"references\" r \
          JOIN symbols s ON s.id = r.caller_id \
-         WHERE s.name = ?1 AND r.confidence >= ?2",
+         WHERE s.name = ?1 AND r.confidence >= ?2 AND (r.kind IS NULL OR r.kind IN ('call', 'component'))",
     )?;
 
     let has_filters = !ref_file_pat.is_empty() || !callees_file_pat.is_empty();
@@ -413,6 +417,34 @@ pub fn callees(
     Ok(results)
 }
 
+/// Resolve each of `callees` to the definition it most likely calls (see
+/// [`Resolver`]), `None` where none is indexed or the candidates cannot be
+/// told apart.
+pub fn resolve_callees(
+    conn: &Connection,
+    callees: &[CalleeResult],
+) -> Result<Vec<Option<Definition>>, DbError> {
+    let mut resolver = Resolver::new(conn);
+    callees
+        .iter()
+        .map(|cr| {
+            resolver.resolve(&Reference {
+                name: cr.callee_name.clone(),
+                kind: ReferenceKind::Call,
+                file: cr.file.clone(),
+                line: cr.line,
+                col: 0,
+                end_col: None,
+                start_byte: None,
+                end_byte: None,
+                context: cr.context.clone(),
+                caller_name: None,
+                confidence: cr.confidence,
+            })
+        })
+        .collect()
+}
+
 /// Find the shortest call chain from `from` to `to` via BFS callee expansion.
 ///
 /// Returns `Some(path)` where `path` is a `Vec<CallPathHop>` representing the
@@ -674,6 +706,36 @@ fn bar() {
         assert_eq!(results[0].depth, 1);
     }
 
+    #[test]
+    fn callers_ignore_type_references() {
+        // Both functions mention Config, but only build calls it.
+        let source = r#"
+struct Config;
+
+fn load(c: Config) -> Config {
+    c
+}
+
+fn build() {
+    let c = Config();
+    load(c);
+}
+"#;
+        let (_dir, conn) = make_indexed_repo(source);
+        let kinds: Vec<String> = conn
+            .prepare("SELECT DISTINCT kind FROM \"references\" WHERE name = 'Config' ORDER BY kind")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(kinds, ["call", "type"]);
+
+        let results = callers(&conn, "Config", 1, None, None, None).unwrap();
+        let names: Vec<&str> = results.iter().map(|r| r.caller_name.as_str()).collect();
+        assert_eq!(names, ["build"]);
+    }
+
     #[test]
     fn callers_empty() {
         // No one calls standalone_fn.
@@ -760,6 +822,27 @@ fn baz() { }
         assert!(names.contains(&"baz"), "baz should be a callee of foo");
     }
 
+    #[test]
+    fn callees_are_calls_resolved_to_definitions() {
+        let source = r#"
+struct Config;
+
+fn load(c: Config) -> Config {
+    parse(c)
+}
+
+fn parse(c: Config) -> Config { c }
+"#;
+        let (_dir, conn) = make_indexed_repo(source);
+        let results = callees(&conn, "load", 1, None, None, None).unwrap();
+        let names: Vec<&str> = results.iter().map(|r| r.callee_name.as_str()).collect();
+        assert_eq!(names, ["parse"]);
+
+        let definitions = resolve_callees(&conn, &results).unwrap();
+        let def = definitions[0].as_ref().unwrap();
+        assert_eq!((def.file.as_str(), def.line), ("src/lib.rs", 8));
+    }
+
     #[test]
     fn callees_empty() {
         // leaf_fn calls nothing.
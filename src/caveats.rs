@@ -0,0 +1,121 @@
+//! Reasons the answer to a query may be incomplete.
+//!
+//! Query code [`record`]s a caveat when it answers from less than a complete
+//! index: the grep heuristics of [`crate::router::QueryRouter`], an index
+//! whose build was interrupted, or one that left out languages whose grammar
+//! failed to load.  Once the command has printed its results,
+//! the router reports each caveat as a warning (a `{"type":"warning"}`
+//! record in structured output), and with `--strict` the process exits with
+//! [`crate::errors::EXIT_INCOMPLETE`] so an agent does not trust the answer
+//! blindly.
+//!
+//! Result files modified since they were indexed are recorded separately
+//! with [`record_stale_file`]: the answer is complete but may be outdated,
+//! which the router reports as a hint to run `wonk update`.
+
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+
+/// Caveats recorded so far, without duplicates.
+static CAVEATS: Mutex<Vec<Caveat>> = Mutex::new(Vec::new());
+
+/// Result files found modified since they were indexed.
+static STALE_FILES: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// Why a query's results may be incomplete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Caveat {
+    /// Results came from grep heuristics instead of the index.
+    GrepFallback,
+    /// The index build was interrupted or the index is built lazily, so
+    /// some files are not indexed.
+    PartialIndex,
+    /// A grammar failed to load, so files in that language are not indexed.
+    DisabledLanguages,
+}
+
+impl Caveat {
+    /// Stable identifier, the `code` of the warning record.
+    pub fn code(self) -> &'static str {
+        match self {
+            Caveat::GrepFallback => "grep_fallback",
+            Caveat::PartialIndex => "partial_index",
+            Caveat::DisabledLanguages => "disabled_languages",
+        }
+    }
+
+    /// Human-readable explanation.
+    pub fn message(self) -> &'static str {
+        match self {
+            Caveat::GrepFallback => {
+                "results come from a grep fallback, not the index, and may be incomplete or imprecise"
+            }
+            Caveat::PartialIndex => {
+                "the index is partial (its build was interrupted or it is built lazily) and results may be incomplete; run `wonk update` to finish it"
+            }
+            Caveat::DisabledLanguages => {
+                "some grammars failed to load, so files in those languages are not indexed and results may be missing them; see `wonk status`"
+            }
+        }
+    }
+}
+
+/// Note that the current answer is subject to `caveat`.
+pub fn record(caveat: Caveat) {
+    if let Ok(mut caveats) = CAVEATS.lock()
+        && !caveats.contains(&caveat)
+    {
+        caveats.push(caveat);
+    }
+}
+
+/// Caveats recorded so far, in the order they were first recorded.
+pub fn recorded() -> Vec<Caveat> {
+    CAVEATS.lock().map(|c| c.clone()).unwrap_or_default()
+}
+
+/// Note that results came from `file`, which changed after it was indexed.
+pub fn record_stale_file(file: &str) {
+    if let Ok(mut files) = STALE_FILES.lock() {
+        files.insert(file.to_string());
+    }
+}
+
+/// Stale result files recorded so far, sorted and without duplicates.
+pub fn stale_files() -> Vec<String> {
+    STALE_FILES
+        .lock()
+        .map(|f| f.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caveats_are_recorded_once() {
+        record(Caveat::GrepFallback);
+        record(Caveat::GrepFallback);
+        let recorded = recorded();
+        assert_eq!(
+            recorded
+                .iter()
+                .filter(|c| **c == Caveat::GrepFallback)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn stale_files_are_counted_once() {
+        record_stale_file("caveats_test/a.rs");
+        record_stale_file("caveats_test/a.rs");
+        record_stale_file("caveats_test/b.rs");
+        let recorded: Vec<String> = stale_files()
+            .into_iter()
+            .filter(|f| f.starts_with("caveats_test/"))
+            .collect();
+        assert_eq!(recorded, ["caveats_test/a.rs", "caveats_test/b.rs"]);
+    }
+}
@@ -1,6 +1,4 @@
-use std::io::IsTerminal;
-
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
 
 use crate::output::OutputFormat;
 
@@ -8,7 +6,7 @@ use crate::output::OutputFormat;
 #[derive(Parser, Debug)]
 #[command(name = "wonk", version, about)]
 pub struct Cli {
-    /// Output format: grep (default), json, or toon
+    /// Output format: grep (default), json, toon, or github (Actions annotations)
     #[arg(long, global = true, value_enum)]
     pub format: Option<OutputFormat>,
 
@@ -16,10 +14,24 @@ pub struct Cli {
     #[arg(short = 'q', long, global = true)]
     pub quiet: bool,
 
+    /// Use ASCII instead of Unicode dashes, arrows, and bars
+    #[arg(long, global = true)]
+    pub ascii: bool,
+
+    /// Do not pipe long output on a terminal through $PAGER (also
+    /// `[output].pager = false`)
+    #[arg(long, global = true)]
+    pub no_pager: bool,
+
     /// Limit output to approximately N tokens (higher-ranked results preserved)
     #[arg(long, global = true)]
     pub budget: Option<usize>,
 
+    /// Share a token budget across the queries of a session, tracked in
+    /// PATH (default: $WONK_BUDGET_FILE); see `wonk budget`
+    #[arg(long, global = true, value_name = "PATH")]
+    pub budget_file: Option<String>,
+
     /// Page number for paginated output (1-indexed, requires --budget)
     #[arg(long, global = true)]
     pub page: Option<usize>,
@@ -28,6 +40,62 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub include_tests: bool,
 
+    /// Exclude test, doc, example, and benchmark files from results (the default)
+    #[arg(long, global = true, conflicts_with_all = ["include_tests", "tests_only"])]
+    pub no_tests: bool,
+
+    /// Show only results from test files (search/sym/ref)
+    #[arg(long, global = true, conflicts_with = "include_tests")]
+    pub tests_only: bool,
+
+    /// Drop sym/ref/rdeps results located in generated code
+    #[arg(long, global = true)]
+    pub no_generated: bool,
+
+    /// Drop sym/ref/rdeps results located in git submodules (indexed with
+    /// `[index].submodules`)
+    #[arg(long, global = true)]
+    pub no_submodules: bool,
+
+    /// Limit search/sym/ref to the current directory's subtree (default:
+    /// `[search].cwd_scope`)
+    #[arg(long, global = true)]
+    pub cwd_scope: bool,
+
+    /// Query the whole repository even when `[search].cwd_scope` is set
+    #[arg(long, global = true, conflicts_with = "cwd_scope")]
+    pub no_cwd_scope: bool,
+
+    /// Show a sample of N results spread across directories and files
+    /// instead of all of them (search/sym/ref)
+    #[arg(long, global = true, value_name = "N")]
+    pub sample: Option<usize>,
+
+    /// Recheck sym/sig/ref results against files on disk, fixing drifted lines
+    #[arg(long, global = true)]
+    pub verify: bool,
+
+    /// Exit with status 3 when results may be incomplete (grep fallback or
+    /// a partial index)
+    #[arg(long, global = true)]
+    pub strict: bool,
+
+    /// Stop slow searches and index queries after SECS seconds, returning partial results
+    #[arg(long, global = true, value_name = "SECS", value_parser = parse_timeout)]
+    pub timeout: Option<f64>,
+
+    /// Parse files on at most N threads when building the index (overrides `[index].jobs`)
+    #[arg(short = 'j', long, global = true, value_name = "N")]
+    pub jobs: Option<usize>,
+
+    /// Do not start a background daemon, whatever `[daemon].auto_start` says
+    #[arg(long, global = true)]
+    pub no_daemon: bool,
+
+    /// Apply the `[profile.NAME]` config section (default: $WONK_PROFILE)
+    #[arg(long, global = true, value_name = "NAME")]
+    pub config_profile: Option<String>,
+
     #[command(subcommand)]
     pub command: Command,
 }
@@ -59,7 +127,13 @@ pub enum Command {
     Update(UpdateArgs),
 
     /// Show indexing status for the current repository
-    Status,
+    Status(StatusArgs),
+
+    /// Show which index queries read, why, and when it was built
+    WhichIndex,
+
+    /// Show files, lines, symbols, and references per indexed language
+    Langs,
 
     /// Manage the background daemon
     Daemon(DaemonArgs),
@@ -67,6 +141,12 @@ pub enum Command {
     /// Manage tracked repositories
     Repos(ReposArgs),
 
+    /// Query every repository indexed in the central store at once
+    Global(GlobalArgs),
+
+    /// Show or reset the session token budget (`--budget-file`)
+    Budget(BudgetArgs),
+
     /// Run MCP (Model Context Protocol) server
     Mcp(McpArgs),
 
@@ -82,10 +162,11 @@ pub enum Command {
     /// Show full source body of a symbol
     Show(ShowArgs),
 
-    /// Find all callers of a symbol (functions whose bodies reference it)
+    /// Find all callers of a symbol (functions whose bodies call it)
     Callers(CallersArgs),
 
-    /// Find all callees of a symbol (symbols referenced within its body)
+    /// Find all callees of a symbol (calls made within its body)
+    #[command(visible_alias = "calls")]
     Callees(CalleesArgs),
 
     /// Find a call chain between two symbols
@@ -94,6 +175,14 @@ pub enum Command {
     /// Show a structural summary of a file or directory
     Summary(SummaryArgs),
 
+    /// Describe one file from the index: language, size, role, imports,
+    /// exports, and its most-used symbols
+    Describe(DescribeArgs),
+
+    /// Show what surrounds a location: enclosing symbol, siblings,
+    /// references out of it, and the file's deps and rdeps
+    Near(NearArgs),
+
     /// Detect entry points and trace execution flows
     Flows(FlowsArgs),
 
@@ -105,12 +194,67 @@ pub enum Command {
 
     /// Aggregate full context for a symbol: definition, callers, callees, importers, flows, children
     Context(ContextArgs),
+
+    /// Everything about a name in one report: definitions, signatures,
+    /// usages, and other mentions, each line shown once
+    Find(FindArgs),
+
+    /// Report async call boundaries or unsafe code across the repository
+    Report(ReportArgs),
+
+    /// Save a query's results to a named snapshot
+    Snapshot(SnapshotArgs),
+
+    /// Diff a query's current results against a saved snapshot
+    Compare(CompareArgs),
+
+    /// Check the `[gate]` thresholds and baselines from config (for CI)
+    Gate(GateArgs),
+
+    /// Show task-oriented recipes (find callers, build a context pack, ...)
+    Examples(ExamplesArgs),
+
+    /// Extract symbols and references from one file (or stdin) as JSON
+    Parse(ParseArgs),
+
+    /// Print the tree-sitter syntax tree wonk sees for a file (or stdin)
+    TreeDump(TreeDumpArgs),
+
+    /// Print JSON Schema definitions of the `--format json` records
+    Schema(SchemaArgs),
+
+    /// Collect version, index, and crash-report details for a bug report
+    Diag(DiagArgs),
+
+    /// Manage the wonk installation itself
+    #[cfg(feature = "self-update")]
+    #[command(name = "self")]
+    SelfCmd(SelfArgs),
+
+    /// Generate man pages and a markdown CLI reference (for packagers)
+    #[command(hide = true)]
+    GenDocs(GenDocsArgs),
+
+    /// Check extraction against golden fixtures (for contributors)
+    #[command(hide = true)]
+    Golden(GoldenArgs),
 }
 
 #[derive(clap::Args, Debug)]
 pub struct SearchArgs {
     /// The search pattern
-    pub pattern: String,
+    #[arg(required_unless_present_any = ["patterns", "patterns_file"])]
+    pub pattern: Option<String>,
+
+    /// Search for several patterns in one pass, reporting which matched
+    /// (repeatable; replaces PATTERN)
+    #[arg(short = 'e', long = "pattern", value_name = "PATTERN", conflicts_with_all = ["pattern", "semantic"])]
+    pub patterns: Vec<String>,
+
+    /// Read patterns from a file (one per line, `#` comments); combined with
+    /// any `-e` patterns
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["pattern", "semantic"])]
+    pub patterns_file: Option<String>,
 
     /// Treat the pattern as a regular expression
     #[arg(long)]
@@ -132,6 +276,33 @@ pub struct SearchArgs {
     #[arg(long, conflicts_with = "raw")]
     pub semantic: bool,
 
+    /// Emit ranked results grouped by category, one object per group (JSON/TOON; implies --smart)
+    #[arg(long, conflicts_with_all = ["raw", "semantic"])]
+    pub group_json: bool,
+
+    /// Write results as each file is searched instead of after the whole search (ranked per file)
+    #[arg(long, conflicts_with_all = ["semantic", "group_json"])]
+    pub stream: bool,
+
+    /// Group hits by directory with per-directory counts, showing only the
+    /// top hits of each (`dir` is the only grouping)
+    #[arg(long, value_name = "BY", value_parser = ["dir"], conflicts_with_all = ["semantic", "group_json", "stream", "patterns", "patterns_file"])]
+    pub cluster: Option<String>,
+
+    /// Hits shown per cluster with --cluster (default: `[search].cluster_top`)
+    #[arg(long, value_name = "K", requires = "cluster")]
+    pub cluster_top: Option<usize>,
+
+    /// Show hits in recently changed files first (by last commit, or mtime
+    /// for uncommitted files; see `[search].recent_source`)
+    #[arg(long, conflicts_with_all = ["semantic", "stream"])]
+    pub recent: bool,
+
+    /// List ranked hits in files matching this glob first, on top of
+    /// `[ranking].boost`; repeatable
+    #[arg(long, value_name = "GLOB")]
+    pub boost: Vec<String>,
+
     /// Restrict search to files matching this path (substring match)
     #[arg(short = 'f', long)]
     pub file: Option<String>,
@@ -144,7 +315,14 @@ pub struct SearchArgs {
 #[derive(clap::Args, Debug)]
 pub struct SymArgs {
     /// Symbol name to look up
-    pub name: String,
+    #[arg(required_unless_present = "stdin_names")]
+    pub name: Option<String>,
+
+    /// Read symbol names from stdin (one per line, `#` comments) and look
+    /// them all up at once; matching is exact and results are tagged with
+    /// the queried name
+    #[arg(long, conflicts_with_all = ["name", "id", "exact"])]
+    pub stdin_names: bool,
 
     /// Filter by symbol kind (e.g. function, class, variable)
     #[arg(long)]
@@ -161,12 +339,48 @@ pub struct SymArgs {
     /// Limit the number of results returned
     #[arg(long)]
     pub limit: Option<usize>,
+
+    /// Treat NAME as a stable symbol ID (the `id` field of JSON output)
+    #[arg(long)]
+    pub id: bool,
+
+    /// Only symbols whose C/C++ preprocessor condition contains TEXT
+    /// (`none` for symbols outside any `#if`)
+    #[arg(long, value_name = "TEXT")]
+    pub condition: Option<String>,
+
+    /// Only symbols with this visibility (public, private, protected,
+    /// internal, package, or a Rust restriction such as pub(crate))
+    #[arg(long)]
+    pub visibility: Option<String>,
+
+    /// Only symbols carrying this modifier (static, async, unsafe, abstract,
+    /// final, const, readonly, virtual, override, ...); repeatable
+    #[arg(long = "modifier", value_name = "MODIFIER")]
+    pub modifiers: Vec<String>,
+
+    /// Only generic symbols matching this: a parameter count (`2`), a
+    /// parameter name (`T`), or a bounded parameter (`T: Read`, `: Read`
+    /// for any name); repeatable
+    #[arg(long = "generic", value_name = "PARAM")]
+    pub generics: Vec<String>,
+
+    /// List symbols in files matching this glob first, on top of
+    /// `[ranking].boost`; repeatable
+    #[arg(long, value_name = "GLOB")]
+    pub boost: Vec<String>,
 }
 
 #[derive(clap::Args, Debug)]
 pub struct RefArgs {
     /// Symbol name to find references for
-    pub name: String,
+    #[arg(required_unless_present = "patterns_file")]
+    pub name: Option<String>,
+
+    /// Read symbol names from a file (one per line, `#` comments) and report
+    /// references for each
+    #[arg(long, value_name = "FILE", conflicts_with = "name")]
+    pub patterns_file: Option<String>,
 
     /// Output mode: full (default) or files (unique file paths only)
     #[arg(long, default_value = "full")]
@@ -176,6 +390,18 @@ pub struct RefArgs {
     #[arg(short = 'f', long)]
     pub file: Option<String>,
 
+    /// Attach the likely definition (file/line) to each reference
+    #[arg(long)]
+    pub resolve: bool,
+
+    /// Match by bare name only, keeping references that cannot see the symbol
+    #[arg(long)]
+    pub loose: bool,
+
+    /// Summarize hits in generated code as one entry per generator
+    #[arg(long)]
+    pub collapse_generated: bool,
+
     /// Restrict search to these paths (use -- before paths)
     #[arg(last = true)]
     pub paths: Vec<String>,
@@ -197,6 +423,14 @@ pub struct DepsArgs {
 pub struct RdepsArgs {
     /// File to show reverse dependencies for
     pub file: String,
+
+    /// Summarize dependents in generated code as one entry per generator
+    #[arg(long)]
+    pub collapse_generated: bool,
+
+    /// Also match unresolved imports by file stem (may include false positives)
+    #[arg(long)]
+    pub fuzzy: bool,
 }
 
 #[derive(clap::Args, Debug)]
@@ -204,10 +438,35 @@ pub struct InitArgs {
     /// Use a local (project-specific) index instead of the shared index
     #[arg(long)]
     pub local: bool,
+
+    /// Rebuild from scratch and report the slowest files and languages
+    #[arg(long)]
+    pub profile: bool,
+
+    /// Index a tarball, zip file, or bare git repository into .wonk/ here
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["local", "profile"])]
+    pub archive: Option<String>,
+
+    /// Make DIR a repository root (marked with `.wonk-root`) and index it,
+    /// for directories outside git such as code drops and SVN checkouts
+    #[arg(long, value_name = "DIR", conflicts_with = "archive")]
+    pub root: Option<String>,
+
+    /// Drop this many leading path components from archive members
+    #[arg(long, value_name = "N", default_value_t = 0, requires = "archive")]
+    pub strip_components: usize,
+
+    /// Build even when the disk looks too full for the estimated index size
+    #[arg(long)]
+    pub skip_space_check: bool,
 }
 
 #[derive(clap::Args, Debug)]
 pub struct UpdateArgs {
+    /// Files or directories to refresh instead of the whole repository
+    #[arg(value_name = "PATH", conflicts_with = "force")]
+    pub paths: Vec<String>,
+
     /// Force a full rebuild even if the index appears current
     #[arg(long)]
     pub force: bool,
@@ -215,6 +474,17 @@ pub struct UpdateArgs {
     /// Skip embedding generation (useful for quick structural-only updates)
     #[arg(long)]
     pub skip_embed: bool,
+
+    /// Build even when the disk looks too full for the estimated index size
+    #[arg(long)]
+    pub skip_space_check: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct StatusArgs {
+    /// Print the report as JSON (same as `--format json`)
+    #[arg(long)]
+    pub json: bool,
 }
 
 #[derive(clap::Args, Debug)]
@@ -252,8 +522,292 @@ pub struct ReposArgs {
 pub enum ReposCommand {
     /// List all tracked repositories
     List,
-    /// Remove stale repositories from the index
-    Clean,
+    /// Remove central indexes whose repository no longer exists
+    Clean(ReposCleanArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ReposCleanArgs {
+    /// Also remove indexes not updated for this long (e.g. 30d, 12h, 2w)
+    #[arg(long, value_name = "AGE", value_parser = parse_age)]
+    pub older_than: Option<std::time::Duration>,
+
+    /// Remove every central index
+    #[arg(long, conflicts_with = "older_than")]
+    pub all: bool,
+
+    /// Also remove indexes whose meta.json is missing or unreadable, so the
+    /// repository they belong to is unknown
+    #[arg(long)]
+    pub unknown: bool,
+
+    /// List what would be removed without removing it
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Parse an age such as `30d`: a whole number followed by `s`, `m`, `h`,
+/// `d`, or `w`.
+fn parse_age(s: &str) -> Result<std::time::Duration, String> {
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (count, unit) = s.split_at(split);
+    let count: u64 = count
+        .parse()
+        .map_err(|_| format!("expected a number followed by s, m, h, d, or w, got {s:?}"))?;
+    let secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => {
+            return Err(format!(
+                "unknown unit {unit:?} in {s:?}; use s, m, h, d, or w"
+            ));
+        }
+    };
+    Ok(std::time::Duration::from_secs(count.saturating_mul(secs)))
+}
+
+/// Parse a `--timeout` in seconds: a finite, non-negative number small
+/// enough to be a [`std::time::Duration`].
+fn parse_timeout(s: &str) -> Result<f64, String> {
+    let secs: f64 = s
+        .parse()
+        .map_err(|_| format!("expected a number of seconds, got {s:?}"))?;
+    std::time::Duration::try_from_secs_f64(secs)
+        .map(|_| secs)
+        .map_err(|_| format!("{s:?} is not a usable number of seconds"))
+}
+
+#[derive(clap::Args, Debug)]
+pub struct GlobalArgs {
+    #[command(subcommand)]
+    pub command: GlobalCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum GlobalCommand {
+    /// Look up symbols in every indexed repository
+    Sym(GlobalSymArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct GlobalSymArgs {
+    /// Symbol name to look up
+    pub name: String,
+
+    /// Filter by symbol kind (e.g. function, class, variable)
+    #[arg(long)]
+    pub kind: Option<String>,
+
+    /// Require an exact match on the symbol name
+    #[arg(long)]
+    pub exact: bool,
+
+    /// Only search repositories with this name (repeatable)
+    #[arg(long = "repo", value_name = "NAME")]
+    pub repos: Vec<String>,
+
+    /// Limit the number of results returned
+    #[arg(long)]
+    pub limit: Option<usize>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct BudgetArgs {
+    #[command(subcommand)]
+    pub command: BudgetCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BudgetCommand {
+    /// Show the tokens used and left in the session budget
+    Status,
+    /// Start the session budget over, optionally with a new limit
+    Reset {
+        /// Tokens the session may use (default: keep the current limit)
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ReportArgs {
+    #[command(subcommand)]
+    pub command: ReportCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ReportCommand {
+    /// List async functions and their non-async callers
+    Async(ReportPathArgs),
+    /// List unsafe functions and unsafe blocks with their enclosing symbols
+    Unsafe(ReportPathArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ReportPathArgs {
+    /// Restrict the report to files under this path
+    pub path: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SnapshotArgs {
+    /// Snapshot name (stored as .wonk/snapshots/NAME.json)
+    pub name: String,
+
+    /// The query to record, after `--` (e.g. `-- ref old_api`)
+    #[arg(last = true, required = true, value_name = "QUERY")]
+    pub query: Vec<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct CompareArgs {
+    /// Snapshot to compare against
+    pub name: String,
+
+    /// Ignore this record field when matching entries, e.g. `line` (repeatable)
+    #[arg(long, value_name = "FIELD")]
+    pub ignore: Vec<String>,
+
+    /// Ignore line, column, and byte offsets, so moved code is not a change
+    #[arg(long)]
+    pub ignore_positions: bool,
+
+    /// Exit with status 1 when entries were added since the snapshot
+    #[arg(long)]
+    pub check: bool,
+
+    /// Replace the snapshot with the current results after comparing
+    #[arg(long)]
+    pub update: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct GateArgs {
+    /// Rewrite the baselines of `no_new_findings_of` queries from the current results
+    #[arg(long)]
+    pub update_baseline: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ExamplesArgs {
+    /// Recipe to show (or a unique prefix); lists all recipes when omitted
+    pub topic: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SchemaArgs {
+    /// Record type to print on its own (e.g. SymbolOutput); prints the
+    /// bundle of all records when omitted
+    pub record: Option<String>,
+
+    /// List the record type names instead
+    #[arg(long, conflicts_with = "record")]
+    pub list: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ParseArgs {
+    /// Source file to parse, or `-` to read from stdin
+    pub file: String,
+
+    /// Language of the source (name or extension, e.g. rust, py, c++);
+    /// detected from the path's extension when omitted
+    #[arg(long, short = 'l')]
+    pub lang: Option<String>,
+
+    /// Path to record as the file of each result (default: FILE, or
+    /// `<stdin>`)
+    #[arg(long, value_name = "PATH")]
+    pub path: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct TreeDumpArgs {
+    /// Source file to parse, or `-` to read from stdin
+    pub file: String,
+
+    /// Language of the source (name or extension, e.g. rust, py, c++);
+    /// detected from the path's extension when omitted
+    #[arg(long, short = 'l')]
+    pub lang: Option<String>,
+
+    /// Path to show in the output (default: FILE, or `<stdin>`)
+    #[arg(long, value_name = "PATH")]
+    pub path: Option<String>,
+
+    /// Only show nodes of this kind and their subtrees (repeatable)
+    #[arg(long, short = 'k', value_name = "KIND")]
+    pub kind: Vec<String>,
+
+    /// Print s-expressions instead of one node per line
+    #[arg(long)]
+    pub sexp: bool,
+
+    /// Include anonymous nodes (punctuation and keywords)
+    #[arg(long, conflicts_with = "sexp")]
+    pub all: bool,
+
+    /// Only show nodes up to N levels below the root (or a --kind match)
+    #[arg(long, value_name = "N", conflicts_with = "sexp")]
+    pub depth: Option<usize>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct DiagArgs {
+    /// Include every crash report instead of only the most recent
+    #[arg(long)]
+    pub all: bool,
+    /// Write the bundle to FILE instead of stdout
+    #[arg(long, short = 'o', value_name = "FILE")]
+    pub output: Option<String>,
+}
+
+#[cfg(feature = "self-update")]
+#[derive(clap::Args, Debug)]
+pub struct SelfArgs {
+    #[command(subcommand)]
+    pub command: SelfCommand,
+}
+
+#[cfg(feature = "self-update")]
+#[derive(Subcommand, Debug)]
+pub enum SelfCommand {
+    /// Replace this binary with the latest release from GitHub
+    Update(SelfUpdateArgs),
+}
+
+#[cfg(feature = "self-update")]
+#[derive(clap::Args, Debug)]
+pub struct SelfUpdateArgs {
+    /// Only report whether a newer release exists; never fails when offline
+    #[arg(long)]
+    pub check: bool,
+    /// Release channel to follow: stable or prerelease (overrides `[update].channel`)
+    #[arg(long, value_name = "CHANNEL")]
+    pub channel: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct GenDocsArgs {
+    /// Write one man page per command into DIR
+    #[arg(long, value_name = "DIR", required_unless_present = "markdown")]
+    pub man: Option<String>,
+    /// Write one markdown page per command into DIR
+    #[arg(long, value_name = "DIR")]
+    pub markdown: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct GoldenArgs {
+    /// Directory of fixtures and `.yaml` expectations
+    #[arg(default_value = crate::golden::DEFAULT_DIR)]
+    pub dir: String,
+    /// Rewrite expectations that are missing or differ
+    #[arg(long)]
+    pub bless: bool,
 }
 
 #[derive(clap::Args, Debug)]
@@ -395,6 +949,32 @@ pub struct SummaryArgs {
     /// Show full recursive hierarchy (unlimited depth)
     #[arg(long, conflicts_with = "depth")]
     pub recursive: bool,
+
+    /// List per file (language, symbol count, top-level symbols) instead of
+    /// aggregated metrics; with `--detail rich`, group every symbol under its file
+    #[arg(long, conflicts_with_all = ["depth", "recursive"])]
+    pub files: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct DescribeArgs {
+    /// File to describe (relative to the repository root)
+    pub file: String,
+
+    /// Maximum number of symbols to list
+    #[arg(long, default_value_t = 10)]
+    pub limit: usize,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct NearArgs {
+    /// Location as FILE:LINE (file relative to the repository root)
+    #[arg(value_name = "FILE:LINE")]
+    pub location: String,
+
+    /// Number of sibling symbols to list on each side of the location
+    #[arg(long, default_value_t = 3)]
+    pub radius: usize,
 }
 
 #[derive(clap::Args, Debug)]
@@ -455,61 +1035,360 @@ pub struct ChangesArgs {
     #[arg(long)]
     pub blast: bool,
 
-    /// Identify execution flows affected by changed symbols
-    #[arg(long)]
-    pub flows: bool,
+    /// Identify execution flows affected by changed symbols
+    #[arg(long)]
+    pub flows: bool,
+
+    /// Minimum confidence threshold (0.0-1.0) for blast/flow edges
+    #[arg(long)]
+    pub min_confidence: Option<f64>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ContextArgs {
+    /// Symbol name to look up
+    pub name: String,
+
+    /// Restrict to symbols in this file
+    #[arg(long)]
+    pub file: Option<String>,
+
+    /// Filter by symbol kind (e.g. function, class)
+    #[arg(long)]
+    pub kind: Option<String>,
+
+    /// Minimum confidence threshold (0.0-1.0) to filter edges
+    #[arg(long)]
+    pub min_confidence: Option<f64>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct FindArgs {
+    /// Name to look up
+    pub name: String,
+
+    /// Maximum results per section
+    #[arg(long)]
+    pub limit: Option<usize>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct McpArgs {
+    #[command(subcommand)]
+    pub command: McpCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum McpCommand {
+    /// Start the MCP server (stdio transport)
+    Serve,
+}
+
+/// Build the clap command, attaching each subcommand's examples from
+/// [`crate::examples`] as after-help.
+pub fn command() -> clap::Command {
+    let mut cmd = Cli::command().after_help("Run `wonk examples` for task-oriented recipes.");
+    let names: Vec<String> = cmd
+        .get_subcommands()
+        .map(|sub| sub.get_name().to_string())
+        .collect();
+    for name in names {
+        if let Some(help) = crate::examples::after_help(&name) {
+            cmd = cmd.mut_subcommand(name, |sub| sub.after_help(help));
+        }
+    }
+    cmd
+}
+
+/// Token budget applied when stdout is piped and neither `--budget` nor the
+/// selected profile sets one.
+pub const AUTO_BUDGET: usize = 2000;
+
+pub fn parse() -> Cli {
+    let mut cli = Cli::from_arg_matches(&command().get_matches()).unwrap_or_else(|err| err.exit());
+
+    if cli.config_profile.is_none() {
+        cli.config_profile = std::env::var("WONK_PROFILE")
+            .ok()
+            .filter(|name| !name.is_empty());
+    }
+    if cli.budget_file.is_none() {
+        cli.budget_file = std::env::var("WONK_BUDGET_FILE")
+            .ok()
+            .filter(|path| !path.is_empty());
+    }
+
+    cli
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn parse_examples_topic() {
+        let cli = Cli::try_parse_from(["wonk", "examples", "callers"]).unwrap();
+        match cli.command {
+            Command::Examples(args) => assert_eq!(args.topic.as_deref(), Some("callers")),
+            _ => panic!("expected Command::Examples"),
+        }
+    }
+
+    #[test]
+    fn command_attaches_examples_to_help() {
+        let mut cmd = command();
+        let help = cmd
+            .find_subcommand_mut("callers")
+            .unwrap()
+            .render_long_help()
+            .to_string();
+        assert!(help.contains("Examples:"));
+        assert!(help.contains("wonk callers parse_config"));
+    }
+
+    #[test]
+    fn parse_gen_docs_requires_a_target() {
+        assert!(Cli::try_parse_from(["wonk", "gen-docs"]).is_err());
+        let cli = Cli::try_parse_from(["wonk", "gen-docs", "--markdown", "out"]).unwrap();
+        match cli.command {
+            Command::GenDocs(args) => {
+                assert!(args.man.is_none());
+                assert_eq!(args.markdown.as_deref(), Some("out"));
+            }
+            _ => panic!("expected Command::GenDocs"),
+        }
+    }
+
+    #[test]
+    fn parse_diag_output() {
+        let cli = Cli::try_parse_from(["wonk", "diag", "--all", "-o", "diag.txt"]).unwrap();
+        match cli.command {
+            Command::Diag(args) => {
+                assert!(args.all);
+                assert_eq!(args.output.as_deref(), Some("diag.txt"));
+            }
+            _ => panic!("expected Command::Diag"),
+        }
+    }
+
+    #[cfg(feature = "self-update")]
+    #[test]
+    fn parse_self_update_check() {
+        let cli = Cli::try_parse_from(["wonk", "self", "update", "--check"]).unwrap();
+        match cli.command {
+            Command::SelfCmd(SelfArgs {
+                command: SelfCommand::Update(args),
+            }) => {
+                assert!(args.check);
+                assert!(args.channel.is_none());
+            }
+            _ => panic!("expected Command::SelfCmd"),
+        }
+    }
+
+    #[test]
+    fn parse_global_no_daemon() {
+        let cli = Cli::try_parse_from(["wonk", "sym", "main", "--no-daemon"]).unwrap();
+        assert!(cli.no_daemon);
+        assert!(!Cli::try_parse_from(["wonk", "langs"]).unwrap().no_daemon);
+    }
+
+    #[test]
+    fn parse_global_jobs() {
+        let cli = Cli::try_parse_from(["wonk", "init", "-j", "2"]).unwrap();
+        assert_eq!(cli.jobs, Some(2));
+        let cli = Cli::try_parse_from(["wonk", "update", "--jobs", "4", "--force"]).unwrap();
+        assert_eq!(cli.jobs, Some(4));
+    }
+
+    #[test]
+    fn parse_init_archive() {
+        let cli = Cli::try_parse_from([
+            "wonk",
+            "init",
+            "--archive",
+            "repo.tar.gz",
+            "--strip-components",
+            "1",
+        ])
+        .unwrap();
+        match cli.command {
+            Command::Init(args) => {
+                assert_eq!(args.archive.as_deref(), Some("repo.tar.gz"));
+                assert_eq!(args.strip_components, 1);
+            }
+            _ => panic!("expected Command::Init"),
+        }
+        assert!(Cli::try_parse_from(["wonk", "init", "--strip-components", "1"]).is_err());
+        assert!(Cli::try_parse_from(["wonk", "init", "--archive", "a.zip", "--local"]).is_err());
+    }
+
+    #[test]
+    fn parse_init_root() {
+        let cli = Cli::try_parse_from(["wonk", "init", "--root", "."]).unwrap();
+        match cli.command {
+            Command::Init(args) => assert_eq!(args.root.as_deref(), Some(".")),
+            _ => panic!("expected Command::Init"),
+        }
+        assert!(
+            Cli::try_parse_from(["wonk", "init", "--root", ".", "--archive", "a.zip"]).is_err()
+        );
+    }
+
+    #[test]
+    fn parse_parse_stdin() {
+        let cli = Cli::try_parse_from(["wonk", "parse", "--lang", "rust", "-"]).unwrap();
+        match cli.command {
+            Command::Parse(args) => {
+                assert_eq!(args.file, "-");
+                assert_eq!(args.lang.as_deref(), Some("rust"));
+                assert!(args.path.is_none());
+            }
+            _ => panic!("expected Command::Parse"),
+        }
+        assert!(Cli::try_parse_from(["wonk", "parse"]).is_err());
+    }
 
-    /// Minimum confidence threshold (0.0-1.0) for blast/flow edges
-    #[arg(long)]
-    pub min_confidence: Option<f64>,
-}
+    #[test]
+    fn parse_tree_dump() {
+        let cli = Cli::try_parse_from([
+            "wonk",
+            "tree-dump",
+            "src/lib.rs",
+            "-k",
+            "call_expression",
+            "--kind",
+            "macro_invocation",
+            "--depth",
+            "2",
+        ])
+        .unwrap();
+        match cli.command {
+            Command::TreeDump(args) => {
+                assert_eq!(args.file, "src/lib.rs");
+                assert_eq!(args.kind, vec!["call_expression", "macro_invocation"]);
+                assert_eq!(args.depth, Some(2));
+                assert!(!args.sexp);
+            }
+            _ => panic!("expected Command::TreeDump"),
+        }
+        assert!(Cli::try_parse_from(["wonk", "tree-dump", "a.rs", "--sexp", "--all"]).is_err());
+    }
 
-#[derive(clap::Args, Debug)]
-pub struct ContextArgs {
-    /// Symbol name to look up
-    pub name: String,
+    #[test]
+    fn parse_snapshot_and_compare() {
+        let cli = Cli::try_parse_from([
+            "wonk",
+            "snapshot",
+            "deprecated",
+            "--",
+            "ref",
+            "old_api",
+            "-f",
+            "src",
+        ])
+        .unwrap();
+        match cli.command {
+            Command::Snapshot(args) => {
+                assert_eq!(args.name, "deprecated");
+                assert_eq!(args.query, vec!["ref", "old_api", "-f", "src"]);
+            }
+            _ => panic!("expected Command::Snapshot"),
+        }
+        assert!(Cli::try_parse_from(["wonk", "snapshot", "deprecated"]).is_err());
 
-    /// Restrict to symbols in this file
-    #[arg(long)]
-    pub file: Option<String>,
+        let cli = Cli::try_parse_from([
+            "wonk",
+            "compare",
+            "deprecated",
+            "--ignore",
+            "line",
+            "--ignore",
+            "col",
+            "--check",
+        ])
+        .unwrap();
+        match cli.command {
+            Command::Compare(args) => {
+                assert_eq!(args.ignore, vec!["line", "col"]);
+                assert!(args.check && !args.update);
+            }
+            _ => panic!("expected Command::Compare"),
+        }
+    }
 
-    /// Filter by symbol kind (e.g. function, class)
-    #[arg(long)]
-    pub kind: Option<String>,
+    #[test]
+    fn parse_gate() {
+        let cli = Cli::try_parse_from(["wonk", "gate", "--update-baseline"]).unwrap();
+        match cli.command {
+            Command::Gate(args) => assert!(args.update_baseline),
+            _ => panic!("expected Command::Gate"),
+        }
+    }
 
-    /// Minimum confidence threshold (0.0-1.0) to filter edges
-    #[arg(long)]
-    pub min_confidence: Option<f64>,
-}
+    #[test]
+    fn parse_golden_is_hidden_with_default_dir() {
+        let cli = Cli::try_parse_from(["wonk", "golden", "--bless"]).unwrap();
+        match cli.command {
+            Command::Golden(args) => {
+                assert_eq!(args.dir, "tests/golden");
+                assert!(args.bless);
+            }
+            _ => panic!("expected Command::Golden"),
+        }
+        let cmd = command();
+        assert!(cmd.find_subcommand("golden").unwrap().is_hide_set());
+    }
 
-#[derive(clap::Args, Debug)]
-pub struct McpArgs {
-    #[command(subcommand)]
-    pub command: McpCommand,
-}
+    #[test]
+    fn parse_global_profile() {
+        let cli =
+            Cli::try_parse_from(["wonk", "sym", "main", "--config-profile", "agent"]).unwrap();
+        assert_eq!(cli.config_profile.as_deref(), Some("agent"));
+        let cli = Cli::try_parse_from(["wonk", "init", "--profile"]).unwrap();
+        assert!(cli.config_profile.is_none());
+    }
 
-#[derive(Subcommand, Debug)]
-pub enum McpCommand {
-    /// Start the MCP server (stdio transport)
-    Serve,
-}
+    #[test]
+    fn parse_global_no_pager() {
+        let cli = Cli::try_parse_from(["wonk", "sym", "Foo", "--no-pager"]).unwrap();
+        assert!(cli.no_pager);
+        assert!(!Cli::try_parse_from(["wonk", "langs"]).unwrap().no_pager);
+    }
 
-pub fn parse() -> Cli {
-    let mut cli = Cli::parse();
+    #[test]
+    fn parse_global_ascii() {
+        let cli = Cli::try_parse_from(["wonk", "deps", "src/main.rs", "--ascii"]).unwrap();
+        assert!(cli.ascii);
+        assert!(!Cli::try_parse_from(["wonk", "langs"]).unwrap().ascii);
+    }
 
-    // Auto-budget: when stdout is piped (not a TTY) and no explicit --budget
-    // was given, apply a default to keep output bounded.
-    if cli.budget.is_none() && !std::io::stdout().is_terminal() {
-        cli.budget = Some(2000);
+    #[test]
+    fn parse_which_index() {
+        let cli = Cli::try_parse_from(["wonk", "which-index"]).unwrap();
+        assert!(matches!(cli.command, Command::WhichIndex));
     }
 
-    cli
-}
+    #[test]
+    fn parse_global_strict() {
+        let cli = Cli::try_parse_from(["wonk", "ref", "main", "--strict"]).unwrap();
+        assert!(cli.strict);
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use clap::Parser;
+    #[test]
+    fn parse_global_timeout() {
+        let cli = Cli::try_parse_from(["wonk", "search", "foo", "--timeout", "2.5"]).unwrap();
+        assert_eq!(cli.timeout, Some(2.5));
+        let cli = Cli::try_parse_from(["wonk", "search", "foo"]).unwrap();
+        assert!(cli.timeout.is_none());
+        for bad in ["nan", "inf", "-1", "1e30", "soon"] {
+            assert!(
+                Cli::try_parse_from(["wonk", "search", "foo", "--timeout", bad]).is_err(),
+                "{bad} is rejected"
+            );
+        }
+    }
 
     #[test]
     fn parse_ask_basic_query() {
@@ -556,12 +1435,83 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn parse_search_multiple_patterns() {
+        let cli = Cli::try_parse_from(["wonk", "search", "-e", "old_api", "-e", "legacy"]).unwrap();
+        match cli.command {
+            Command::Search(args) => {
+                assert!(args.pattern.is_none());
+                assert_eq!(args.patterns, ["old_api", "legacy"]);
+            }
+            _ => panic!("expected Command::Search"),
+        }
+        assert!(Cli::try_parse_from(["wonk", "search"]).is_err());
+        assert!(Cli::try_parse_from(["wonk", "search", "-e", "a", "b"]).is_err());
+    }
+
+    #[test]
+    fn parse_global_sample() {
+        let cli = Cli::try_parse_from(["wonk", "ref", "log", "--sample", "30"]).unwrap();
+        assert_eq!(cli.sample, Some(30));
+        let cli = Cli::try_parse_from(["wonk", "search", "log"]).unwrap();
+        assert_eq!(cli.sample, None);
+    }
+
+    #[test]
+    fn parse_search_cluster() {
+        let cli = Cli::try_parse_from([
+            "wonk",
+            "search",
+            "log",
+            "--cluster",
+            "dir",
+            "--cluster-top",
+            "5",
+        ])
+        .unwrap();
+        match cli.command {
+            Command::Search(args) => {
+                assert_eq!(args.cluster.as_deref(), Some("dir"));
+                assert_eq!(args.cluster_top, Some(5));
+            }
+            _ => panic!("expected Search command"),
+        }
+        assert!(Cli::try_parse_from(["wonk", "search", "log", "--cluster", "file"]).is_err());
+        assert!(Cli::try_parse_from(["wonk", "search", "log", "--cluster-top", "5"]).is_err());
+        assert!(
+            Cli::try_parse_from(["wonk", "search", "log", "--cluster", "dir", "--stream"]).is_err()
+        );
+    }
+
+    #[test]
+    fn parse_boost_is_repeatable() {
+        let cli = Cli::try_parse_from([
+            "wonk",
+            "search",
+            "log",
+            "--boost",
+            "src/core/**",
+            "--boost",
+            "lib/",
+        ])
+        .unwrap();
+        match cli.command {
+            Command::Search(args) => assert_eq!(args.boost, ["src/core/**", "lib/"]),
+            _ => panic!("expected Search command"),
+        }
+        let cli = Cli::try_parse_from(["wonk", "sym", "open", "--boost", "src/**"]).unwrap();
+        match cli.command {
+            Command::Sym(args) => assert_eq!(args.boost, ["src/**"]),
+            _ => panic!("expected Sym command"),
+        }
+    }
+
     #[test]
     fn parse_search_semantic_flag() {
         let cli = Cli::try_parse_from(["wonk", "search", "--semantic", "verifyToken"]).unwrap();
         match cli.command {
             Command::Search(args) => {
-                assert_eq!(args.pattern, "verifyToken");
+                assert_eq!(args.pattern.as_deref(), Some("verifyToken"));
                 assert!(args.semantic);
                 assert!(!args.raw);
             }
@@ -569,6 +1519,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_search_group_json_flag() {
+        let cli = Cli::try_parse_from(["wonk", "search", "--group-json", "verifyToken"]).unwrap();
+        match cli.command {
+            Command::Search(args) => assert!(args.group_json),
+            _ => panic!("expected Command::Search"),
+        }
+        assert!(Cli::try_parse_from(["wonk", "search", "--group-json", "--raw", "x"]).is_err());
+    }
+
+    #[test]
+    fn parse_search_stream_flag() {
+        let cli = Cli::try_parse_from(["wonk", "search", "--stream", "foo"]).unwrap();
+        match cli.command {
+            Command::Search(args) => assert!(args.stream),
+            _ => panic!("expected Command::Search"),
+        }
+        assert!(Cli::try_parse_from(["wonk", "search", "--stream", "--semantic", "x"]).is_err());
+    }
+
     #[test]
     fn parse_search_semantic_default_false() {
         let cli = Cli::try_parse_from(["wonk", "search", "verifyToken"]).unwrap();
@@ -777,6 +1747,113 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_langs() {
+        let cli = Cli::try_parse_from(["wonk", "langs"]).unwrap();
+        assert!(matches!(cli.command, Command::Langs));
+    }
+
+    #[test]
+    fn parse_global_sym() {
+        let cli = Cli::try_parse_from([
+            "wonk", "global", "sym", "Invoice", "--exact", "--repo", "client", "--repo", "billing",
+        ])
+        .unwrap();
+        match cli.command {
+            Command::Global(GlobalArgs {
+                command: GlobalCommand::Sym(args),
+            }) => {
+                assert_eq!(args.name, "Invoice");
+                assert!(args.exact);
+                assert_eq!(args.repos, ["client", "billing"]);
+            }
+            _ => panic!("expected Command::Global"),
+        }
+    }
+
+    #[test]
+    fn parse_budget_file_and_reset() {
+        let cli = Cli::try_parse_from([
+            "wonk",
+            "--budget-file",
+            "/tmp/session.json",
+            "budget",
+            "reset",
+            "--limit",
+            "8000",
+        ])
+        .unwrap();
+        assert_eq!(cli.budget_file.as_deref(), Some("/tmp/session.json"));
+        assert!(matches!(
+            cli.command,
+            Command::Budget(BudgetArgs {
+                command: BudgetCommand::Reset { limit: Some(8000) }
+            })
+        ));
+        let cli = Cli::try_parse_from(["wonk", "sym", "main", "--budget-file", "s.json"]).unwrap();
+        assert_eq!(cli.budget_file.as_deref(), Some("s.json"));
+    }
+
+    #[test]
+    fn parse_repos_clean_older_than() {
+        let cli = Cli::try_parse_from(["wonk", "repos", "clean", "--older-than", "30d"]).unwrap();
+        match cli.command {
+            Command::Repos(ReposArgs {
+                command: ReposCommand::Clean(args),
+            }) => {
+                assert_eq!(
+                    args.older_than,
+                    Some(std::time::Duration::from_secs(30 * 86400))
+                );
+                assert!(!args.all);
+                assert!(!args.unknown);
+            }
+            _ => panic!("expected Command::Repos"),
+        }
+        assert_eq!(
+            parse_age("2w"),
+            Ok(std::time::Duration::from_secs(14 * 86400))
+        );
+        assert!(parse_age("30").is_err());
+        assert!(parse_age("d").is_err());
+        assert!(
+            Cli::try_parse_from(["wonk", "repos", "clean", "--all", "--older-than", "1d"]).is_err()
+        );
+    }
+
+    #[test]
+    fn parse_find() {
+        let cli = Cli::try_parse_from(["wonk", "find", "parse_config", "--limit", "5"]).unwrap();
+        match cli.command {
+            Command::Find(args) => {
+                assert_eq!(args.name, "parse_config");
+                assert_eq!(args.limit, Some(5));
+            }
+            _ => panic!("expected Command::Find"),
+        }
+    }
+
+    #[test]
+    fn parse_status_json() {
+        let cli = Cli::try_parse_from(["wonk", "status", "--json"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Status(StatusArgs { json: true })
+        ));
+        let cli = Cli::try_parse_from(["wonk", "status"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Status(StatusArgs { json: false })
+        ));
+    }
+
+    #[test]
+    fn parse_langs_with_global_format() {
+        let cli = Cli::try_parse_from(["wonk", "--format", "json", "langs"]).unwrap();
+        assert_eq!(cli.format, Some(OutputFormat::Json));
+        assert!(matches!(cli.command, Command::Langs));
+    }
+
     // -- Callers/Callees tests -----------------------------------------------
 
     #[test]
@@ -833,6 +1910,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_calls_is_callees() {
+        let cli = Cli::try_parse_from(["wonk", "calls", "--depth", "2", "main"]).unwrap();
+        match cli.command {
+            Command::Callees(args) => {
+                assert_eq!(args.name, "main");
+                assert_eq!(args.depth, 2);
+            }
+            _ => panic!("expected Command::Callees"),
+        }
+    }
+
     #[test]
     fn parse_callees_requires_name() {
         let result = Cli::try_parse_from(["wonk", "callees"]);
@@ -1109,6 +2198,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_summary_files() {
+        let cli = Cli::try_parse_from(["wonk", "summary", "--files", "src/"]).unwrap();
+        match cli.command {
+            Command::Summary(args) => assert!(args.files),
+            _ => panic!("expected Command::Summary"),
+        }
+        assert!(
+            Cli::try_parse_from(["wonk", "summary", "--files", "--depth", "1", "src/"]).is_err()
+        );
+        assert!(
+            Cli::try_parse_from(["wonk", "summary", "--files", "--detail", "rich", "src/"]).is_ok()
+        );
+    }
+
     #[test]
     fn parse_summary_recursive() {
         let cli = Cli::try_parse_from(["wonk", "summary", "--recursive", "src/"]).unwrap();
@@ -1300,6 +2404,131 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_ref_resolve() {
+        let cli = Cli::try_parse_from(["wonk", "ref", "--resolve", "foo"]).unwrap();
+        match cli.command {
+            Command::Ref(args) => {
+                assert_eq!(args.name.as_deref(), Some("foo"));
+                assert!(args.resolve);
+                assert!(!args.loose);
+            }
+            _ => panic!("expected Ref command"),
+        }
+    }
+
+    #[test]
+    fn parse_patterns_file() {
+        let cli =
+            Cli::try_parse_from(["wonk", "ref", "--patterns-file", "deprecated.txt"]).unwrap();
+        match cli.command {
+            Command::Ref(args) => {
+                assert!(args.name.is_none());
+                assert_eq!(args.patterns_file.as_deref(), Some("deprecated.txt"));
+            }
+            _ => panic!("expected Ref command"),
+        }
+        assert!(Cli::try_parse_from(["wonk", "ref", "--patterns-file", "p.txt", "foo"]).is_err());
+
+        let cli =
+            Cli::try_parse_from(["wonk", "search", "-e", "extra", "--patterns-file", "p.txt"])
+                .unwrap();
+        match cli.command {
+            Command::Search(args) => {
+                assert_eq!(args.patterns, ["extra"]);
+                assert_eq!(args.patterns_file.as_deref(), Some("p.txt"));
+            }
+            _ => panic!("expected Search command"),
+        }
+    }
+
+    #[test]
+    fn parse_describe() {
+        let cli = Cli::try_parse_from(["wonk", "describe", "src/db.rs", "--limit", "3"]).unwrap();
+        match cli.command {
+            Command::Describe(args) => {
+                assert_eq!(args.file, "src/db.rs");
+                assert_eq!(args.limit, 3);
+            }
+            _ => panic!("expected Describe command"),
+        }
+        assert!(Cli::try_parse_from(["wonk", "describe"]).is_err());
+    }
+
+    #[test]
+    fn parse_near() {
+        let cli = Cli::try_parse_from(["wonk", "near", "src/db.rs:42", "--radius", "5"]).unwrap();
+        match cli.command {
+            Command::Near(args) => {
+                assert_eq!(args.location, "src/db.rs:42");
+                assert_eq!(args.radius, 5);
+            }
+            _ => panic!("expected Near command"),
+        }
+    }
+
+    #[test]
+    fn parse_sym_stdin_names() {
+        let cli =
+            Cli::try_parse_from(["wonk", "sym", "--stdin-names", "--kind", "function"]).unwrap();
+        match cli.command {
+            Command::Sym(args) => {
+                assert!(args.stdin_names);
+                assert!(args.name.is_none());
+                assert_eq!(args.kind.as_deref(), Some("function"));
+            }
+            _ => panic!("expected Sym command"),
+        }
+        assert!(Cli::try_parse_from(["wonk", "sym"]).is_err());
+        assert!(Cli::try_parse_from(["wonk", "sym", "--stdin-names", "foo"]).is_err());
+        assert!(Cli::try_parse_from(["wonk", "sym", "--stdin-names", "--id"]).is_err());
+    }
+
+    #[test]
+    fn parse_test_filter_flags() {
+        let cli = Cli::try_parse_from(["wonk", "sym", "--tests-only", "main"]).unwrap();
+        assert!(cli.tests_only);
+        let cli = Cli::try_parse_from(["wonk", "ref", "--no-tests", "main"]).unwrap();
+        assert!(cli.no_tests);
+        assert!(
+            Cli::try_parse_from(["wonk", "sym", "--tests-only", "--include-tests", "x"]).is_err()
+        );
+        assert!(Cli::try_parse_from(["wonk", "sym", "--no-tests", "--tests-only", "x"]).is_err());
+    }
+
+    #[test]
+    fn parse_generated_flags() {
+        let cli =
+            Cli::try_parse_from(["wonk", "rdeps", "--collapse-generated", "src/api.ts"]).unwrap();
+        match cli.command {
+            Command::Rdeps(args) => assert!(args.collapse_generated),
+            _ => panic!("expected Rdeps command"),
+        }
+        let cli = Cli::try_parse_from(["wonk", "ref", "--no-generated", "foo"]).unwrap();
+        assert!(cli.no_generated);
+        let cli = Cli::try_parse_from(["wonk", "sym", "--no-submodules", "foo"]).unwrap();
+        assert!(cli.no_submodules);
+    }
+
+    #[test]
+    fn parse_cwd_scope_flags() {
+        let cli = Cli::try_parse_from(["wonk", "ref", "--cwd-scope", "main"]).unwrap();
+        assert!(cli.cwd_scope);
+        let cli = Cli::try_parse_from(["wonk", "sym", "main", "--no-cwd-scope"]).unwrap();
+        assert!(cli.no_cwd_scope && !cli.cwd_scope);
+        assert!(
+            Cli::try_parse_from(["wonk", "sym", "--cwd-scope", "--no-cwd-scope", "main"]).is_err()
+        );
+    }
+
+    #[test]
+    fn parse_global_verify_flag() {
+        let cli = Cli::try_parse_from(["wonk", "sym", "--verify", "main"]).unwrap();
+        assert!(cli.verify);
+        let cli = Cli::try_parse_from(["wonk", "sym", "main"]).unwrap();
+        assert!(!cli.verify);
+    }
+
     #[test]
     fn parse_blast_with_include_tests() {
         let cli =
@@ -1546,4 +2775,25 @@ mod tests {
             _ => panic!("expected Command::Context"),
         }
     }
+
+    #[test]
+    fn parse_report_subcommands() {
+        let cli = Cli::try_parse_from(["wonk", "report", "async"]).unwrap();
+        match cli.command {
+            Command::Report(ReportArgs {
+                command: ReportCommand::Async(args),
+            }) => assert!(args.path.is_none()),
+            _ => panic!("expected Command::Report async"),
+        }
+
+        let cli = Cli::try_parse_from(["wonk", "report", "unsafe", "src/ffi"]).unwrap();
+        match cli.command {
+            Command::Report(ReportArgs {
+                command: ReportCommand::Unsafe(args),
+            }) => assert_eq!(args.path.as_deref(), Some("src/ffi")),
+            _ => panic!("expected Command::Report unsafe"),
+        }
+
+        assert!(Cli::try_parse_from(["wonk", "report"]).is_err());
+    }
 }
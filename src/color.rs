@@ -30,6 +30,11 @@ pub const LINE_NO: &str = "\x1b[32m";
 pub const MATCH: &str = "\x1b[1m\x1b[4m\x1b[31m";
 /// Separators (colons): cyan.
 pub const SEP: &str = "\x1b[36m";
+/// Records added since a snapshot: green.  Rendered after a `+` marker, so
+/// the state never depends on color alone.
+pub const ADDED: &str = "\x1b[32m";
+/// Records removed since a snapshot: red, after a `-` marker.
+pub const REMOVED: &str = "\x1b[31m";
 
 // ---------------------------------------------------------------------------
 // Color resolution
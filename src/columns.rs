@@ -0,0 +1,148 @@
+//! Column units for reported positions.
+//!
+//! Tree-sitter and the search engine report columns as byte offsets, which
+//! editors disagree with on lines containing multibyte characters: LSP
+//! clients count UTF-16 code units, most others count characters.
+//! `[output].col_unit` selects the unit; [`ColumnMapper`] converts byte
+//! columns against the source line, reading each file at most once.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Unit in which output columns are counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColUnit {
+    /// UTF-8 bytes, as stored in the index (the default).
+    #[default]
+    Byte,
+    /// UTF-16 code units, as used by LSP and most JavaScript tooling.
+    Utf16,
+    /// Unicode scalar values.
+    Char,
+}
+
+impl FromStr for ColUnit {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "byte" | "bytes" | "utf8" | "utf-8" => Ok(Self::Byte),
+            "utf16" | "utf-16" => Ok(Self::Utf16),
+            "char" | "chars" | "codepoint" => Ok(Self::Char),
+            _ => Err(format!(
+                "unknown column unit '{s}' (expected: byte, utf16, char)"
+            )),
+        }
+    }
+}
+
+/// Convert a 0-based byte column within `line` to `unit`.
+///
+/// Offsets past the end of the line or inside a multibyte character are
+/// clamped to the preceding character boundary.
+pub fn convert_col(line: &str, byte_col: usize, unit: ColUnit) -> usize {
+    if unit == ColUnit::Byte {
+        return byte_col;
+    }
+    let mut end = byte_col.min(line.len());
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    let prefix = &line[..end];
+    match unit {
+        ColUnit::Byte => end,
+        ColUnit::Utf16 => prefix.encode_utf16().count(),
+        ColUnit::Char => prefix.chars().count(),
+    }
+}
+
+/// Converts byte columns of indexed positions to the configured unit,
+/// caching the lines of each file it reads.
+#[derive(Debug)]
+pub struct ColumnMapper {
+    unit: ColUnit,
+    root: PathBuf,
+    files: HashMap<String, Option<Vec<String>>>,
+}
+
+impl ColumnMapper {
+    /// Create a mapper resolving relative paths against `root`.
+    pub fn new(unit: ColUnit, root: &Path) -> Self {
+        Self {
+            unit,
+            root: root.to_path_buf(),
+            files: HashMap::new(),
+        }
+    }
+
+    /// The unit columns are converted to.
+    pub fn unit(&self) -> ColUnit {
+        self.unit
+    }
+
+    /// Convert the 0-based byte column on 1-based `line` of `file`.
+    ///
+    /// Columns are returned unchanged when the file or line cannot be read.
+    pub fn map(&mut self, file: &str, line: usize, byte_col: usize) -> usize {
+        if self.unit == ColUnit::Byte || byte_col == 0 {
+            return byte_col;
+        }
+        let root = &self.root;
+        let lines = self.files.entry(file.to_string()).or_insert_with(|| {
+            let path = root.join(file);
+            std::fs::read_to_string(&path)
+                .or_else(|_| std::fs::read_to_string(file))
+                .ok()
+                .map(|text| text.lines().map(str::to_string).collect())
+        });
+        match lines.as_ref().and_then(|l| l.get(line.wrapping_sub(1))) {
+            Some(text) => convert_col(text, byte_col, self.unit),
+            None => byte_col,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn parse_col_unit() {
+        assert_eq!("byte".parse::<ColUnit>().unwrap(), ColUnit::Byte);
+        assert_eq!("UTF-16".parse::<ColUnit>().unwrap(), ColUnit::Utf16);
+        assert_eq!("char".parse::<ColUnit>().unwrap(), ColUnit::Char);
+        assert!("columns".parse::<ColUnit>().is_err());
+    }
+
+    #[test]
+    fn convert_col_counts_units_before_offset() {
+        // "é" is 2 bytes / 1 UTF-16 unit; "😀" is 4 bytes / 2 UTF-16 units.
+        let line = "é😀 = x";
+        let byte_col = line.find('=').unwrap();
+        assert_eq!(byte_col, 7);
+        assert_eq!(convert_col(line, byte_col, ColUnit::Byte), 7);
+        assert_eq!(convert_col(line, byte_col, ColUnit::Utf16), 4);
+        assert_eq!(convert_col(line, byte_col, ColUnit::Char), 3);
+    }
+
+    #[test]
+    fn convert_col_clamps_offsets() {
+        assert_eq!(convert_col("é", 1, ColUnit::Char), 0);
+        assert_eq!(convert_col("ab", 10, ColUnit::Utf16), 2);
+    }
+
+    #[test]
+    fn mapper_reads_lines_relative_to_root() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.ts"), "let x = 1;\nconst ñame = 2;\n").unwrap();
+        let mut mapper = ColumnMapper::new(ColUnit::Char, dir.path());
+        // `=` on line 2 is at byte 12, char 11.
+        assert_eq!(mapper.map("a.ts", 2, 12), 11);
+        assert_eq!(mapper.map("a.ts", 1, 6), 6);
+        // Missing files and lines leave the column unchanged.
+        assert_eq!(mapper.map("missing.ts", 1, 5), 5);
+        assert_eq!(mapper.map("a.ts", 9, 5), 5);
+    }
+}
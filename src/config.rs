@@ -4,13 +4,16 @@
 //! 1. Built-in defaults
 //! 2. Global config from `~/.wonk/config.toml`
 //! 3. Per-repo config from `<repo_root>/.wonk/config.toml`
+//! 4. The selected `[profile.<name>]` (from `--config-profile` or `WONK_PROFILE`),
+//!    first as defined globally, then as defined in the repo
 //!
 //! Each layer only overrides fields it explicitly sets; absent fields
 //! are left at their previous value.
 
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use serde::Deserialize;
 
 // ---------------------------------------------------------------------------
@@ -26,6 +29,13 @@ pub struct Config {
     pub ignore: IgnoreConfig,
     pub llm: LlmConfig,
     pub search: SearchConfig,
+    pub fallback: FallbackConfig,
+    pub ranking: RankingConfig,
+    pub budget: BudgetConfig,
+    pub ruby: RubyConfig,
+    pub update: UpdateConfig,
+    pub gate: GateConfig,
+    pub profile: ProfileConfig,
 }
 
 /// Daemon-related settings.
@@ -33,6 +43,16 @@ pub struct Config {
 pub struct DaemonConfig {
     /// Debounce interval in milliseconds for file-change events.
     pub debounce_ms: u64,
+    /// Extra glob patterns the file watcher ignores, on top of
+    /// `[ignore].patterns`.
+    pub ignore: Vec<String>,
+    /// Changed files in one burst above which the daemon stops reindexing
+    /// file by file and runs a single incremental update once the burst
+    /// settles (0 disables).
+    pub flood_threshold: usize,
+    /// When commands start the daemon on their own, parsed from `"never"`,
+    /// `"auto"`, or `"always"`.
+    pub auto_start: crate::daemon::AutoStart,
 }
 
 /// Indexing settings.
@@ -42,6 +62,29 @@ pub struct IndexConfig {
     pub max_file_size_kb: u64,
     /// Extra file extensions to index beyond the built-in set.
     pub additional_extensions: Vec<String>,
+    /// Maximum number of modified files reindexed on the fly per query
+    /// (0 disables query-time refresh).
+    pub query_refresh_limit: usize,
+    /// Threads used to parse files during index builds (0 = one per core).
+    pub jobs: usize,
+    /// Niceness (0-19) of index builds and the daemon; 0 keeps the normal
+    /// priority.
+    pub nice: i32,
+    /// How queries open the index: `"sqlite"` or `"memory"` (see
+    /// [`crate::storage::Backend`]).
+    pub backend: String,
+    /// How linked git worktrees are indexed: `"separate"` or `"shared"`
+    /// (see [`crate::db::WorktreeIndex`]).
+    pub worktrees: String,
+    /// Descend into checked-out git submodules and index their files, tagged
+    /// with the submodule they belong to.
+    pub submodules: bool,
+    /// What central indexes are keyed by: `"path"` or `"git"` (see
+    /// [`crate::db::IndexKey`]).
+    pub key: String,
+    /// How a query in a repository without an index builds one: `"full"` or
+    /// `"lazy"` (see [`crate::pipeline::AutoInit`]).
+    pub auto_init: String,
 }
 
 /// Output / display settings.
@@ -51,6 +94,12 @@ pub struct OutputConfig {
     pub default_format: String,
     /// Color mode: `"auto"`, `"always"`, or `"never"`.
     pub color: String,
+    /// Unit for result columns: `"byte"`, `"utf16"`, or `"char"`.
+    pub col_unit: String,
+    /// Replace Unicode dashes, arrows, and bars with ASCII.
+    pub ascii: bool,
+    /// Page long human-readable output on a terminal through `$PAGER`.
+    pub pager: bool,
 }
 
 /// Ignore / exclusion settings.
@@ -78,6 +127,99 @@ pub struct SearchConfig {
     /// structural and semantic result lists. Higher values produce more
     /// even blending. Default: 60.0 (standard RRF constant).
     pub rrf_k: f32,
+    /// Result categories shown first in ranked output, in this order.
+    pub category_order: Vec<String>,
+    /// Custom header labels keyed by category name (`collapsed` names the
+    /// group of collapsed categories).
+    pub category_labels: HashMap<String, String>,
+    /// Categories with fewer results are merged into one trailing group
+    /// (0 disables collapsing).
+    pub collapse_below: usize,
+    /// Results shown per directory with `search --cluster dir`.
+    pub cluster_top: usize,
+    /// How `search --recent` dates files: `"git"` (last commit, mtime for
+    /// uncommitted files) or `"mtime"`.
+    pub recent_source: String,
+    /// Limit `search`, `sym`, and `ref` to the current directory's subtree
+    /// by default (`--cwd-scope` / `--no-cwd-scope` override).
+    pub cwd_scope: bool,
+}
+
+/// Grep fallback settings, one `[fallback.<language>]` table per language.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FallbackConfig {
+    /// Keyed by language name (`rust`, `c++`) or file extension; an
+    /// extension wonk does not parse, such as a DSL's, works too.
+    pub languages: BTreeMap<String, FallbackLanguageConfig>,
+}
+
+/// Grep fallback settings for one language.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FallbackLanguageConfig {
+    /// Keywords that introduce a definition in this language's files
+    /// (`"fn"`, `"pub fn"`, `"defn"`), replacing the built-in ones when set.
+    pub symbol_keywords: Option<Vec<String>>,
+}
+
+/// Result ranking settings.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RankingConfig {
+    /// Gitignore-style globs (repo-relative) whose files are listed first in
+    /// ranked search and `sym` output; `--boost` adds more.
+    pub boost: Vec<String>,
+}
+
+/// Token budget settings.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BudgetConfig {
+    /// Relative share of a `--budget` each category gets in ranked search
+    /// output, keyed by category name (`collapsed` names the group of
+    /// collapsed categories), over the defaults in
+    /// [`crate::ranker::CategoryWeights`].
+    pub category_weights: HashMap<String, u32>,
+}
+
+/// Ruby-specific settings.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RubyConfig {
+    /// Apply Rails autoloading conventions: constants used in a file count
+    /// as dependencies on the `app/**` (or `lib/`) file that defines them.
+    pub rails: bool,
+}
+
+/// Self-update settings (`wonk self update`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpdateConfig {
+    /// Release channel: `"stable"` or `"prerelease"`.
+    pub channel: String,
+}
+
+/// CI gate settings (`wonk gate`).
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GateConfig {
+    /// Named queries, as `wonk` arguments (`todo = ["search", "TODO"]`).
+    pub queries: BTreeMap<String, Vec<String>>,
+    /// Largest allowed result count per query, from `max_<query> = N`.
+    pub max: BTreeMap<String, usize>,
+    /// Queries whose results may not gain entries over their baseline.
+    pub no_new_findings_of: Vec<String>,
+}
+
+/// Settings of the selected profile that are defaults for global flags
+/// rather than config keys.  Command-line flags still take precedence.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ProfileConfig {
+    /// Name of the selected profile (`None` when no profile is in use).
+    pub name: Option<String>,
+    /// Token budget when `--budget` is not given (`Some(0)` disables the
+    /// automatic budget for piped output).
+    pub budget: Option<usize>,
+    /// Include test files, as `--include-tests`.
+    pub include_tests: bool,
+    /// Drop generated code, as `--no-generated`.
+    pub no_generated: bool,
+    /// Recheck results on disk, as `--verify`.
+    pub verify: bool,
 }
 
 // ---------------------------------------------------------------------------
@@ -86,7 +228,12 @@ pub struct SearchConfig {
 
 impl Default for DaemonConfig {
     fn default() -> Self {
-        Self { debounce_ms: 500 }
+        Self {
+            debounce_ms: 500,
+            ignore: Vec::new(),
+            flood_threshold: 1000,
+            auto_start: crate::daemon::AutoStart::default(),
+        }
     }
 }
 
@@ -95,6 +242,14 @@ impl Default for IndexConfig {
         Self {
             max_file_size_kb: 1024,
             additional_extensions: Vec::new(),
+            query_refresh_limit: 16,
+            jobs: 0,
+            nice: 0,
+            backend: "sqlite".to_string(),
+            worktrees: "separate".to_string(),
+            submodules: false,
+            key: "path".to_string(),
+            auto_init: "full".to_string(),
         }
     }
 }
@@ -104,6 +259,9 @@ impl Default for OutputConfig {
         Self {
             default_format: "grep".to_string(),
             color: "auto".to_string(),
+            col_unit: "byte".to_string(),
+            ascii: false,
+            pager: true,
         }
     }
 }
@@ -119,7 +277,23 @@ impl Default for LlmConfig {
 
 impl Default for SearchConfig {
     fn default() -> Self {
-        Self { rrf_k: 60.0 }
+        Self {
+            rrf_k: 60.0,
+            category_order: Vec::new(),
+            category_labels: HashMap::new(),
+            collapse_below: 0,
+            cluster_top: 3,
+            recent_source: "git".to_string(),
+            cwd_scope: false,
+        }
+    }
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            channel: "stable".to_string(),
+        }
     }
 }
 
@@ -139,12 +313,49 @@ struct ConfigOverlay {
     ignore: Option<IgnoreOverlay>,
     llm: Option<LlmOverlay>,
     search: Option<SearchOverlay>,
+    fallback: Option<HashMap<String, FallbackLanguageOverlay>>,
+    ranking: Option<RankingOverlay>,
+    budget: Option<BudgetOverlay>,
+    ruby: Option<RubyOverlay>,
+    update: Option<UpdateOverlay>,
+    gate: Option<GateOverlay>,
+    profile: Option<HashMap<String, ProfileOverlay>>,
+}
+
+impl ConfigOverlay {
+    /// Post-process fields serde cannot check on its own.
+    fn resolve(&mut self) -> Result<()> {
+        if let Some(gate) = &mut self.gate {
+            gate.resolve_limits()?;
+        }
+        for profile in self.profile.iter_mut().flat_map(HashMap::values_mut) {
+            profile.sections.resolve()?;
+        }
+        Ok(())
+    }
+}
+
+/// One `[profile.<name>]` table: global-flag defaults plus any config
+/// sections (`[profile.<name>.search]`, ...) to apply over the regular ones.
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+struct ProfileOverlay {
+    format: Option<String>,
+    budget: Option<usize>,
+    include_tests: Option<bool>,
+    no_generated: Option<bool>,
+    verify: Option<bool>,
+    #[serde(flatten)]
+    sections: ConfigOverlay,
 }
 
 #[derive(Debug, Deserialize, Default)]
 #[serde(default)]
 struct DaemonOverlay {
     debounce_ms: Option<u64>,
+    ignore: Option<Vec<String>>,
+    flood_threshold: Option<usize>,
+    auto_start: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -152,6 +363,14 @@ struct DaemonOverlay {
 struct IndexOverlay {
     max_file_size_kb: Option<u64>,
     additional_extensions: Option<Vec<String>>,
+    query_refresh_limit: Option<usize>,
+    jobs: Option<usize>,
+    nice: Option<i32>,
+    backend: Option<String>,
+    worktrees: Option<String>,
+    submodules: Option<bool>,
+    key: Option<String>,
+    auto_init: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -159,6 +378,9 @@ struct IndexOverlay {
 struct OutputOverlay {
     default_format: Option<String>,
     color: Option<String>,
+    col_unit: Option<String>,
+    ascii: Option<bool>,
+    pager: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -178,6 +400,72 @@ struct LlmOverlay {
 #[serde(default)]
 struct SearchOverlay {
     rrf_k: Option<f32>,
+    category_order: Option<Vec<String>>,
+    category_labels: Option<HashMap<String, String>>,
+    collapse_below: Option<usize>,
+    cluster_top: Option<usize>,
+    recent_source: Option<String>,
+    cwd_scope: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+struct FallbackLanguageOverlay {
+    symbol_keywords: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+struct RankingOverlay {
+    boost: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+struct BudgetOverlay {
+    category_weights: Option<HashMap<String, u32>>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+struct RubyOverlay {
+    rails: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+struct UpdateOverlay {
+    channel: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+struct GateOverlay {
+    queries: Option<HashMap<String, Vec<String>>>,
+    no_new_findings_of: Option<Vec<String>>,
+    /// Every other key; `max_<query>` entries become [`Self::max`].
+    #[serde(flatten)]
+    other: HashMap<String, toml::Value>,
+    #[serde(skip)]
+    max: HashMap<String, usize>,
+}
+
+impl GateOverlay {
+    /// Collect the `max_<query> = N` thresholds, rejecting values that are
+    /// not non-negative integers.
+    fn resolve_limits(&mut self) -> Result<()> {
+        for (key, value) in &self.other {
+            let Some(name) = key.strip_prefix("max_") else {
+                continue;
+            };
+            let limit = value
+                .as_integer()
+                .and_then(|n| usize::try_from(n).ok())
+                .with_context(|| format!("gate.{key} must be a non-negative integer"))?;
+            self.max.insert(name.to_string(), limit);
+        }
+        Ok(())
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -188,10 +476,23 @@ impl Config {
     /// Apply an overlay on top of this config, replacing only the fields
     /// that are `Some` in the overlay.
     fn apply_overlay(&mut self, overlay: ConfigOverlay) {
-        if let Some(d) = overlay.daemon
-            && let Some(v) = d.debounce_ms
-        {
-            self.daemon.debounce_ms = v;
+        if let Some(d) = overlay.daemon {
+            if let Some(v) = d.debounce_ms {
+                self.daemon.debounce_ms = v;
+            }
+            if let Some(v) = d.ignore {
+                self.daemon.ignore = v;
+            }
+            if let Some(v) = d.flood_threshold {
+                self.daemon.flood_threshold = v;
+            }
+            if let Some(v) = d.auto_start {
+                static WARNED: std::sync::Once = std::sync::Once::new();
+                self.daemon.auto_start = v.parse().unwrap_or_else(|e: String| {
+                    WARNED.call_once(|| crate::output::print_warning(&format!("{e}; using auto")));
+                    crate::daemon::AutoStart::default()
+                });
+            }
         }
         if let Some(idx) = overlay.index {
             if let Some(v) = idx.max_file_size_kb {
@@ -200,6 +501,30 @@ impl Config {
             if let Some(v) = idx.additional_extensions {
                 self.index.additional_extensions = v;
             }
+            if let Some(v) = idx.query_refresh_limit {
+                self.index.query_refresh_limit = v;
+            }
+            if let Some(v) = idx.jobs {
+                self.index.jobs = v;
+            }
+            if let Some(v) = idx.nice {
+                self.index.nice = v;
+            }
+            if let Some(v) = idx.backend {
+                self.index.backend = v;
+            }
+            if let Some(v) = idx.worktrees {
+                self.index.worktrees = v;
+            }
+            if let Some(v) = idx.submodules {
+                self.index.submodules = v;
+            }
+            if let Some(v) = idx.key {
+                self.index.key = v;
+            }
+            if let Some(v) = idx.auto_init {
+                self.index.auto_init = v;
+            }
         }
         if let Some(out) = overlay.output {
             if let Some(v) = out.default_format {
@@ -208,6 +533,15 @@ impl Config {
             if let Some(v) = out.color {
                 self.output.color = v;
             }
+            if let Some(v) = out.col_unit {
+                self.output.col_unit = v;
+            }
+            if let Some(v) = out.ascii {
+                self.output.ascii = v;
+            }
+            if let Some(v) = out.pager {
+                self.output.pager = v;
+            }
         }
         if let Some(ign) = overlay.ignore
             && let Some(v) = ign.patterns
@@ -222,12 +556,85 @@ impl Config {
                 self.llm.generate_url = v;
             }
         }
-        if let Some(s) = overlay.search
-            && let Some(v) = s.rrf_k
+        if let Some(s) = overlay.search {
+            if let Some(v) = s.rrf_k {
+                self.search.rrf_k = v;
+            }
+            if let Some(v) = s.category_order {
+                self.search.category_order = v;
+            }
+            if let Some(v) = s.category_labels {
+                self.search.category_labels.extend(v);
+            }
+            if let Some(v) = s.collapse_below {
+                self.search.collapse_below = v;
+            }
+            if let Some(v) = s.cluster_top {
+                self.search.cluster_top = v;
+            }
+            if let Some(v) = s.recent_source {
+                self.search.recent_source = v;
+            }
+            if let Some(v) = s.cwd_scope {
+                self.search.cwd_scope = v;
+            }
+        }
+        for (language, f) in overlay.fallback.unwrap_or_default() {
+            let entry = self.fallback.languages.entry(language).or_default();
+            if let Some(v) = f.symbol_keywords {
+                entry.symbol_keywords = Some(v);
+            }
+        }
+        if let Some(r) = overlay.ranking
+            && let Some(v) = r.boost
+        {
+            self.ranking.boost = v;
+        }
+        if let Some(b) = overlay.budget
+            && let Some(v) = b.category_weights
+        {
+            self.budget.category_weights.extend(v);
+        }
+        if let Some(r) = overlay.ruby
+            && let Some(v) = r.rails
+        {
+            self.ruby.rails = v;
+        }
+        if let Some(u) = overlay.update
+            && let Some(v) = u.channel
         {
-            self.search.rrf_k = v;
+            self.update.channel = v;
+        }
+        if let Some(g) = overlay.gate {
+            if let Some(v) = g.queries {
+                self.gate.queries.extend(v);
+            }
+            if let Some(v) = g.no_new_findings_of {
+                self.gate.no_new_findings_of = v;
+            }
+            self.gate.max.extend(g.max);
         }
     }
+
+    /// Apply one layer's definition of the selected profile.
+    fn apply_profile(&mut self, profile: ProfileOverlay) {
+        if let Some(v) = profile.format {
+            self.output.default_format = v;
+        }
+        if let Some(v) = profile.budget {
+            self.profile.budget = Some(v);
+        }
+        if let Some(v) = profile.include_tests {
+            self.profile.include_tests = v;
+        }
+        if let Some(v) = profile.no_generated {
+            self.profile.no_generated = v;
+        }
+        if let Some(v) = profile.verify {
+            self.profile.verify = v;
+        }
+        self.apply_overlay(profile.sections);
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -243,8 +650,12 @@ fn home_dir() -> Option<PathBuf> {
 /// Parse a TOML string into a [`ConfigOverlay`], producing a clear error
 /// message on malformed input.
 fn parse_overlay(contents: &str, path: &Path) -> Result<ConfigOverlay> {
-    toml::from_str(contents)
-        .with_context(|| format!("failed to parse config file: {}", path.display()))
+    let mut overlay: ConfigOverlay = toml::from_str(contents)
+        .with_context(|| format!("failed to parse config file: {}", path.display()))?;
+    overlay
+        .resolve()
+        .with_context(|| format!("failed to parse config file: {}", path.display()))?;
+    Ok(overlay)
 }
 
 /// Try to read a config file and parse it as an overlay.
@@ -275,18 +686,43 @@ impl Config {
         Self::load_with_global_dir(global_dir.as_deref(), repo_root)
     }
 
+    /// Load configuration like [`Config::load`], then apply the profile
+    /// `name`.  Fails when neither config file defines it.
+    pub fn load_with_profile(repo_root: Option<&Path>, name: &str) -> Result<Config> {
+        let global_dir = home_dir().map(|h| h.join(".wonk"));
+        Self::load_layers(global_dir.as_deref(), repo_root, Some(name))
+    }
+
     /// Internal: load config with an explicit global config directory.
     ///
     /// This allows tests to supply a temporary directory instead of the
     /// real `~/.wonk` without mutating environment variables.
     fn load_with_global_dir(global_dir: Option<&Path>, repo_root: Option<&Path>) -> Result<Config> {
+        Self::load_layers(global_dir, repo_root, None)
+    }
+
+    fn load_layers(
+        global_dir: Option<&Path>,
+        repo_root: Option<&Path>,
+        profile: Option<&str>,
+    ) -> Result<Config> {
         let mut config = Config::default();
+        let mut defined: Vec<String> = Vec::new();
+        let mut selected: Vec<ProfileOverlay> = Vec::new();
+        let mut apply = |config: &mut Config, mut overlay: ConfigOverlay| {
+            let mut profiles = overlay.profile.take().unwrap_or_default();
+            config.apply_overlay(overlay);
+            defined.extend(profiles.keys().cloned());
+            if let Some(p) = profile.and_then(|name| profiles.remove(name)) {
+                selected.push(p);
+            }
+        };
 
         // Layer 2: global config
         if let Some(dir) = global_dir {
             let global_path = dir.join("config.toml");
             if let Some(overlay) = load_overlay(&global_path)? {
-                config.apply_overlay(overlay);
+                apply(&mut config, overlay);
             }
         }
 
@@ -294,7 +730,26 @@ impl Config {
         if let Some(root) = repo_root {
             let repo_config_path = root.join(".wonk").join("config.toml");
             if let Some(overlay) = load_overlay(&repo_config_path)? {
-                config.apply_overlay(overlay);
+                apply(&mut config, overlay);
+            }
+        }
+
+        // Layer 4: the selected profile, over everything else
+        if let Some(name) = profile {
+            if selected.is_empty() {
+                defined.sort();
+                defined.dedup();
+                if defined.is_empty() {
+                    bail!("unknown profile {name:?}: no [profile.<name>] sections are configured");
+                }
+                bail!(
+                    "unknown profile {name:?} (configured: {})",
+                    defined.join(", ")
+                );
+            }
+            config.profile.name = Some(name.to_string());
+            for p in selected {
+                config.apply_profile(p);
             }
         }
 
@@ -370,11 +825,175 @@ mod tests {
         assert_eq!(config.daemon.debounce_ms, 500);
         assert_eq!(config.index.max_file_size_kb, 1024);
         assert!(config.index.additional_extensions.is_empty());
+        assert_eq!(config.index.query_refresh_limit, 16);
+        assert_eq!(config.index.jobs, 0);
+        assert_eq!(config.index.nice, 0);
+        assert_eq!(config.index.backend, "sqlite");
+        assert_eq!(config.index.worktrees, "separate");
+        assert!(!config.index.submodules);
+        assert_eq!(config.index.key, "path");
+        assert_eq!(config.index.auto_init, "full");
         assert_eq!(config.output.default_format, "grep");
         assert_eq!(config.output.color, "auto");
+        assert_eq!(config.output.col_unit, "byte");
         assert!(config.ignore.patterns.is_empty());
     }
 
+    #[test]
+    fn output_col_unit_is_configurable() {
+        let env = TestEnv::new();
+        env.write_global_config(
+            r#"
+[output]
+col_unit = "utf16"
+"#,
+        );
+
+        let config = env.load().unwrap();
+        assert_eq!(config.output.col_unit, "utf16");
+        assert_eq!(config.output.default_format, "grep");
+        assert!(!config.output.ascii);
+        assert!(config.output.pager);
+    }
+
+    #[test]
+    fn output_ascii_is_configurable() {
+        let env = TestEnv::new();
+        env.write_global_config(
+            r#"
+[output]
+ascii = true
+"#,
+        );
+
+        assert!(env.load().unwrap().output.ascii);
+    }
+
+    #[test]
+    fn output_pager_can_be_disabled() {
+        let env = TestEnv::new();
+        env.write_global_config(
+            r#"
+[output]
+pager = false
+"#,
+        );
+
+        assert!(!env.load().unwrap().output.pager);
+    }
+
+    #[test]
+    fn search_category_layout_is_configurable() {
+        let mut env = TestEnv::new();
+        env.write_global_config(
+            r#"
+[search]
+category_order = ["test", "definition"]
+
+[search.category_labels]
+test = "specs"
+collapsed = "misc"
+"#,
+        );
+        env.create_repo();
+        env.write_repo_config(
+            r#"
+[search]
+collapse_below = 2
+cluster_top = 5
+recent_source = "mtime"
+cwd_scope = true
+
+[search.category_labels]
+comment = "notes"
+"#,
+        );
+
+        let config = env.load().unwrap();
+        assert_eq!(config.search.category_order, ["test", "definition"]);
+        assert_eq!(config.search.collapse_below, 2);
+        assert_eq!(config.search.cluster_top, 5);
+        assert_eq!(config.search.recent_source, "mtime");
+        assert!(config.search.cwd_scope);
+        // Labels merge across layers key by key.
+        assert_eq!(config.search.category_labels.len(), 3);
+        assert_eq!(config.search.category_labels["test"], "specs");
+        assert_eq!(config.search.category_labels["comment"], "notes");
+        assert_eq!(config.search.rrf_k, 60.0);
+    }
+
+    #[test]
+    fn ranking_boost_repo_replaces_global() {
+        let mut env = TestEnv::new();
+        env.write_global_config(
+            r#"
+[ranking]
+boost = ["lib/**"]
+"#,
+        );
+        assert_eq!(env.load().unwrap().ranking.boost, ["lib/**"]);
+
+        env.create_repo();
+        env.write_repo_config(
+            r#"
+[ranking]
+boost = ["src/core/**", "crates/domain/"]
+"#,
+        );
+        let config = env.load().unwrap();
+        assert_eq!(config.ranking.boost, ["src/core/**", "crates/domain/"]);
+    }
+
+    #[test]
+    fn budget_category_weights_merge_across_layers() {
+        let mut env = TestEnv::new();
+        assert!(env.load().unwrap().budget.category_weights.is_empty());
+        env.write_global_config(
+            r#"
+[budget.category_weights]
+definition = 6
+test = 0
+"#,
+        );
+        env.create_repo();
+        env.write_repo_config(
+            r#"
+[budget]
+category_weights = { test = 2 }
+"#,
+        );
+        let config = env.load().unwrap();
+        assert_eq!(config.budget.category_weights.len(), 2);
+        assert_eq!(config.budget.category_weights["definition"], 6);
+        assert_eq!(config.budget.category_weights["test"], 2);
+    }
+
+    #[test]
+    fn fallback_keywords_merge_per_language() {
+        let mut env = TestEnv::new();
+        assert!(env.load().unwrap().fallback.languages.is_empty());
+        env.write_global_config(
+            r#"
+[fallback.rust]
+symbol_keywords = ["fn", "struct"]
+
+[fallback.clj]
+symbol_keywords = ["defn", "defmacro"]
+"#,
+        );
+        env.create_repo();
+        env.write_repo_config(
+            r#"
+[fallback.rust]
+symbol_keywords = ["fn", "struct", "define_op!"]
+"#,
+        );
+        let config = env.load().unwrap();
+        let keywords = |lang: &str| config.fallback.languages[lang].symbol_keywords.clone();
+        assert_eq!(keywords("rust").unwrap(), ["fn", "struct", "define_op!"]);
+        assert_eq!(keywords("clj").unwrap(), ["defn", "defmacro"]);
+    }
+
     #[test]
     fn global_config_overrides_defaults() {
         let env = TestEnv::new();
@@ -397,6 +1016,34 @@ default_format = "json"
         assert_eq!(config.output.color, "auto");
     }
 
+    #[test]
+    fn daemon_ignore_patterns_load() {
+        let env = TestEnv::new();
+        env.write_global_config(
+            r#"
+[daemon]
+ignore = ["*.log", "generated/"]
+flood_threshold = 200
+auto_start = "never"
+"#,
+        );
+
+        let config = env.load().unwrap();
+        assert_eq!(config.daemon.ignore, ["*.log", "generated/"]);
+        assert_eq!(config.daemon.flood_threshold, 200);
+        assert_eq!(config.daemon.auto_start, crate::daemon::AutoStart::Never);
+        assert_eq!(config.daemon.debounce_ms, 500);
+    }
+
+    #[test]
+    fn unknown_daemon_auto_start_falls_back_to_auto() {
+        let env = TestEnv::new();
+        env.write_global_config("[daemon]\nauto_start = \"sometimes\"\n");
+
+        let config = env.load().unwrap();
+        assert_eq!(config.daemon.auto_start, crate::daemon::AutoStart::Auto);
+    }
+
     #[test]
     fn repo_config_overrides_global() {
         let mut env = TestEnv::new();
@@ -419,6 +1066,14 @@ debounce_ms = 100
 [index]
 max_file_size_kb = 512
 additional_extensions = ["toml", "yaml"]
+query_refresh_limit = 0
+jobs = 2
+nice = 10
+backend = "memory"
+worktrees = "shared"
+submodules = true
+key = "git"
+auto_init = "lazy"
 "#,
         );
 
@@ -431,6 +1086,14 @@ additional_extensions = ["toml", "yaml"]
             config.index.additional_extensions,
             vec!["toml".to_string(), "yaml".to_string()]
         );
+        assert_eq!(config.index.query_refresh_limit, 0);
+        assert_eq!(config.index.jobs, 2);
+        assert_eq!(config.index.nice, 10);
+        assert_eq!(config.index.backend, "memory");
+        assert_eq!(config.index.worktrees, "shared");
+        assert!(config.index.submodules);
+        assert_eq!(config.index.key, "git");
+        assert_eq!(config.index.auto_init, "lazy");
         // Global value not overridden by repo should still be present:
         assert_eq!(config.output.color, "always");
         // Default not touched by either layer:
@@ -786,4 +1449,154 @@ rrf_k = 80.0
         let config = Config::load_with_global_dir(Some(&env.global_path), Some(&repo)).unwrap();
         assert!((config.search.rrf_k - 80.0).abs() < f32::EPSILON);
     }
+
+    // -- Ruby config tests ----------------------------------------------------
+
+    #[test]
+    fn ruby_rails_defaults_off_and_repo_enables_it() {
+        let mut env = TestEnv::new();
+        assert!(!env.load().unwrap().ruby.rails);
+
+        let repo = env.create_repo();
+        env.write_repo_config(
+            r#"
+[ruby]
+rails = true
+"#,
+        );
+
+        let config = Config::load_with_global_dir(Some(&env.global_path), Some(&repo)).unwrap();
+        assert!(config.ruby.rails);
+    }
+
+    // -- Update config tests --------------------------------------------------
+
+    #[test]
+    fn update_channel_defaults_to_stable_and_is_overridable() {
+        let env = TestEnv::new();
+        assert_eq!(env.load().unwrap().update.channel, "stable");
+
+        env.write_global_config(
+            r#"
+[update]
+channel = "prerelease"
+"#,
+        );
+        assert_eq!(env.load().unwrap().update.channel, "prerelease");
+    }
+
+    // -- Gate config tests ----------------------------------------------------
+
+    #[test]
+    fn gate_queries_and_limits_merge_across_layers() {
+        let mut env = TestEnv::new();
+        assert_eq!(env.load().unwrap().gate, GateConfig::default());
+
+        env.write_global_config(
+            r#"
+[gate]
+max_todo = 10
+
+[gate.queries]
+todo = ["search", "TODO"]
+"#,
+        );
+        env.create_repo();
+        env.write_repo_config(
+            r#"
+[gate]
+max_unwrap_calls = 50
+no_new_findings_of = ["deprecated"]
+note = "ignored"
+
+[gate.queries]
+unwrap_calls = ["ref", "unwrap"]
+deprecated = ["ref", "old_api"]
+"#,
+        );
+
+        let gate = env.load().unwrap().gate;
+        assert_eq!(gate.queries.len(), 3);
+        assert_eq!(gate.queries["todo"], vec!["search", "TODO"]);
+        assert_eq!(gate.max["todo"], 10);
+        assert_eq!(gate.max["unwrap_calls"], 50);
+        assert_eq!(gate.no_new_findings_of, vec!["deprecated"]);
+    }
+
+    #[test]
+    fn gate_limit_must_be_a_count() {
+        let env = TestEnv::new();
+        env.write_global_config("[gate]\nmax_todo = -1\n");
+        let err = format!("{:#}", env.load().unwrap_err());
+        assert!(err.contains("gate.max_todo"), "{err}");
+    }
+
+    // -- Profile tests --------------------------------------------------------
+
+    #[test]
+    fn profile_overrides_sections_and_sets_flag_defaults() {
+        let mut env = TestEnv::new();
+        env.write_global_config(
+            r#"
+[profile.agent]
+format = "json"
+budget = 4000
+
+[profile.agent.search]
+rrf_k = 30.0
+"#,
+        );
+        let repo = env.create_repo();
+        env.write_repo_config(
+            r#"
+[search]
+rrf_k = 45.0
+collapse_below = 2
+
+[profile.agent]
+no_generated = true
+
+[profile.ci]
+format = "github"
+budget = 0
+include_tests = true
+"#,
+        );
+
+        let plain = env.load().unwrap();
+        assert_eq!(plain.profile, ProfileConfig::default());
+        assert_eq!(plain.search.rrf_k, 45.0);
+
+        let agent =
+            Config::load_layers(Some(&env.global_path), Some(&repo), Some("agent")).unwrap();
+        assert_eq!(agent.profile.name.as_deref(), Some("agent"));
+        assert_eq!(agent.output.default_format, "json");
+        assert_eq!(agent.profile.budget, Some(4000));
+        assert!(agent.profile.no_generated && !agent.profile.include_tests);
+        assert_eq!(agent.search.rrf_k, 30.0);
+        assert_eq!(agent.search.collapse_below, 2);
+
+        let ci = Config::load_layers(Some(&env.global_path), Some(&repo), Some("ci")).unwrap();
+        assert_eq!(ci.output.default_format, "github");
+        assert_eq!(ci.profile.budget, Some(0));
+        assert!(ci.profile.include_tests);
+        assert_eq!(ci.search.rrf_k, 45.0);
+    }
+
+    #[test]
+    fn unknown_profile_lists_configured_ones() {
+        let env = TestEnv::new();
+        let err = Config::load_layers(Some(&env.global_path), None, Some("ci")).unwrap_err();
+        assert!(
+            err.to_string().contains("no [profile.<name>] sections"),
+            "{err}"
+        );
+
+        env.write_global_config("[profile.human]\n[profile.agent]\n");
+        let err = Config::load_layers(Some(&env.global_path), None, Some("ci")).unwrap_err();
+        assert!(
+            err.to_string().contains("configured: agent, human"),
+            "{err}"
+        );
+    }
 }
@@ -0,0 +1,487 @@
+//! Crash reports for panics, and the bundle printed by `wonk diag`.
+//!
+//! [`install_panic_hook`] replaces the default panic output with a short
+//! notice and writes a plain-text report to `~/.wonk/crash/`: the wonk
+//! version and platform, the command line with argument values redacted
+//! (see [`redact_args`]), the panic message with quoted text and details
+//! redacted (see [`redact_panic_message`]), size statistics for the current
+//! repository's index, and a backtrace.  `wonk diag` collects the same environment
+//! details plus the most recent reports so they can be attached to an issue.
+
+use std::backtrace::Backtrace;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+
+use crate::db;
+
+/// Where users are asked to file crash reports.
+pub const ISSUES_URL: &str = "https://github.com/etr/wonk/issues";
+
+/// Placeholder for a command-line value left out of reports.
+const REDACTED: &str = "<redacted>";
+
+/// Directory holding crash reports: `~/.wonk/crash`.
+pub fn crash_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".wonk").join("crash"))
+}
+
+/// Platform as `<os>-<arch>`, e.g. `linux-x86_64`.
+pub fn platform() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// Size of the current repository's index, without paths or contents.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, schemars::JsonSchema)]
+pub struct RepoStats {
+    /// `"local"` (`.wonk/index.db`) or `"central"` (`~/.wonk/repos/`).
+    pub location: String,
+    pub index_bytes: u64,
+    pub files: i64,
+    pub symbols: i64,
+    pub references: i64,
+    pub languages: Vec<String>,
+    /// Version of wonk that built the index, when recorded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub built_by: Option<String>,
+    pub partial: bool,
+}
+
+impl RepoStats {
+    /// Collect stats for the repository containing `dir`, or `None` when it
+    /// has no index.  Opens the index read-only and never fails: counts that
+    /// cannot be read are left at zero.
+    pub fn collect(dir: &Path) -> Option<Self> {
+        let root = db::find_repo_root(dir).ok()?;
+        let index = db::find_existing_index(&root)?;
+        let location = if index == db::local_index_path(&root) {
+            "local"
+        } else {
+            "central"
+        };
+        let mut stats = RepoStats {
+            location: location.to_string(),
+            index_bytes: std::fs::metadata(&index).map(|m| m.len()).unwrap_or(0),
+            ..Default::default()
+        };
+        if let Ok(meta) = db::read_meta(&index) {
+            stats.languages = meta.languages;
+            stats.built_by = meta.wonk_version;
+            stats.partial = meta.partial;
+        }
+        if let Ok(conn) = Connection::open_with_flags(&index, OpenFlags::SQLITE_OPEN_READ_ONLY) {
+            let count = |table: &str| {
+                conn.query_row(&format!("SELECT COUNT(*) FROM \"{table}\""), [], |row| {
+                    row.get(0)
+                })
+                .unwrap_or(0)
+            };
+            stats.files = count("files");
+            stats.symbols = count("symbols");
+            stats.references = count("references");
+        }
+        Some(stats)
+    }
+
+    /// One-line summary, e.g. `central, 12.3 MiB, 120 files, ...`.
+    pub fn summary(&self) -> String {
+        let mut line = format!(
+            "{}, {:.1} MiB, {} files, {} symbols, {} references",
+            self.location,
+            self.index_bytes as f64 / (1024.0 * 1024.0),
+            self.files,
+            self.symbols,
+            self.references
+        );
+        if let Some(version) = &self.built_by {
+            let _ = write!(line, ", built by {version}");
+        }
+        if self.partial {
+            line.push_str(", partial");
+        }
+        line
+    }
+}
+
+/// Redact `args` (without the program name) for a report.
+///
+/// Subcommand names and flags are kept; a value is kept only when it is a
+/// number or one of its option's possible values (`--format json`).
+/// Everything else — patterns, symbol names, paths, URLs — becomes
+/// `<redacted>`, since it may name private code.
+pub fn redact_args(args: &[String]) -> Vec<String> {
+    let mut cmd = crate::cli::command();
+    cmd.build();
+    let mut current = &cmd;
+    let mut out = vec!["wonk".to_string()];
+    let mut in_subcommands = true;
+    let mut prev_flag: Option<&clap::Arg> = None;
+
+    for arg in args {
+        if in_subcommands && let Some(sub) = current.find_subcommand(arg) {
+            out.push(arg.clone());
+            current = sub;
+            continue;
+        }
+        if arg.starts_with('-') && arg.len() > 1 && arg.parse::<f64>().is_err() {
+            in_subcommands = false;
+            let (flag, value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag, Some(value)),
+                None => (arg.as_str(), None),
+            };
+            let spec = find_flag(current, flag);
+            match value {
+                Some(value) => out.push(format!("{flag}={}", redact_value(spec, value))),
+                None => out.push(arg.clone()),
+            }
+            prev_flag = spec.filter(|a| value.is_none() && a.get_action().takes_values());
+            continue;
+        }
+        in_subcommands = false;
+        out.push(redact_value(prev_flag.take(), arg));
+    }
+    out
+}
+
+/// The argument of `cmd` matching `--long` or `-s`.
+fn find_flag<'a>(cmd: &'a clap::Command, flag: &str) -> Option<&'a clap::Arg> {
+    if let Some(long) = flag.strip_prefix("--") {
+        cmd.get_arguments().find(|a| a.get_long() == Some(long))
+    } else {
+        let short = flag.strip_prefix('-')?.chars().next()?;
+        cmd.get_arguments().find(|a| a.get_short() == Some(short))
+    }
+}
+
+fn redact_value(spec: Option<&clap::Arg>, value: &str) -> String {
+    let allowed = value.parse::<f64>().is_ok()
+        || spec.is_some_and(|a| {
+            a.get_possible_values()
+                .iter()
+                .any(|v| v.matches(value, false))
+        });
+    if allowed {
+        value.to_string()
+    } else {
+        REDACTED.to_string()
+    }
+}
+
+/// Names std panics quote that say what failed rather than what was being
+/// worked on, kept by [`redact_panic_message`].
+const STD_PANIC_NAMES: &[&str] = &[
+    "Result::unwrap()",
+    "Result::unwrap_err()",
+    "Option::unwrap()",
+    "Err",
+    "Ok",
+    "None",
+];
+
+/// Reduce a panic message to its class for a report.
+///
+/// Std panics quote what they were working on: string-slicing panics the
+/// sliced text (source code, here), I/O errors a path.  Anything after the
+/// first `": "` (an error value's details) is dropped, and quoted text
+/// (`` `...` ``, `"..."`, `'...'`) becomes `<redacted>` unless it is one of
+/// [`STD_PANIC_NAMES`].  Positions and lengths are kept.
+pub fn redact_panic_message(message: &str) -> String {
+    let (head, details) = match message.split_once(": ") {
+        Some((head, _)) => (head, true),
+        None => (message, false),
+    };
+    let mut out = String::with_capacity(head.len());
+    let mut rest = head;
+    while let Some(start) = rest.find(['`', '"', '\'']) {
+        let quote = rest[start..].chars().next().unwrap_or('`');
+        // An apostrophe inside a word (`can't`) does not open a quote.
+        let in_word = quote == '\''
+            && rest[..start]
+                .chars()
+                .next_back()
+                .is_some_and(char::is_alphanumeric);
+        let close = rest[start + 1..].find(quote).map(|i| start + 1 + i);
+        match close {
+            Some(end) if !in_word => {
+                out.push_str(&rest[..start]);
+                let quoted = &rest[start + 1..end];
+                if STD_PANIC_NAMES.contains(&quoted) {
+                    out.push_str(&rest[start..=end]);
+                } else {
+                    out.push_str(REDACTED);
+                }
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                out.push_str(&rest[..=start]);
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    if details {
+        out.push_str(": ");
+        out.push_str(REDACTED);
+    }
+    out
+}
+
+/// Render a crash report.  The panic `message` is redacted with
+/// [`redact_panic_message`].
+pub fn render_report(
+    message: &str,
+    location: Option<String>,
+    command: &[String],
+    repo: Option<&RepoStats>,
+    backtrace: &str,
+) -> String {
+    let mut report = String::new();
+    let _ = writeln!(report, "wonk crash report");
+    let _ = writeln!(report, "version: {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(report, "platform: {}", platform());
+    let _ = writeln!(report, "time: {}", unix_now());
+    let _ = writeln!(
+        report,
+        "thread: {}",
+        std::thread::current().name().unwrap_or("<unnamed>")
+    );
+    let _ = writeln!(report, "command: {}", command.join(" "));
+    let _ = writeln!(report, "panic: {}", redact_panic_message(message));
+    if let Some(location) = location {
+        let _ = writeln!(report, "location: {location}");
+    }
+    match repo {
+        Some(stats) => {
+            let _ = writeln!(report, "index: {}", stats.summary());
+            let _ = writeln!(report, "languages: {}", stats.languages.join(", "));
+        }
+        None => {
+            let _ = writeln!(report, "index: none");
+        }
+    }
+    let _ = writeln!(report, "\nbacktrace:\n{}", backtrace.trim_end());
+    report
+}
+
+/// Write `report` into `dir` as `crash-<unix time>-<pid>.txt`.
+pub fn write_report(dir: &Path, report: &str) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    let path = dir.join(format!("crash-{}-{}.txt", unix_now(), std::process::id()));
+    std::fs::write(&path, report).with_context(|| format!("writing {}", path.display()))?;
+    Ok(path)
+}
+
+/// Crash reports in `dir`, newest first.
+pub fn list_reports(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut reports: Vec<(SystemTime, PathBuf)> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("crash-") && n.ends_with(".txt"))
+        })
+        .map(|p| {
+            let modified = std::fs::metadata(&p)
+                .and_then(|m| m.modified())
+                .unwrap_or(UNIX_EPOCH);
+            (modified, p)
+        })
+        .collect();
+    reports.sort_by(|a, b| b.cmp(a));
+    reports.into_iter().map(|(_, p)| p).collect()
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Write a crash report on panic instead of only printing the message.
+///
+/// Falls back to the default hook when the report cannot be written.
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload_as_str()
+            .unwrap_or("<non-string panic payload>");
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let repo = std::env::current_dir()
+            .ok()
+            .and_then(|cwd| RepoStats::collect(&cwd));
+        let backtrace = Backtrace::force_capture().to_string();
+        let report = render_report(
+            message,
+            location,
+            &redact_args(&args),
+            repo.as_ref(),
+            &backtrace,
+        );
+
+        match crash_dir().map(|dir| write_report(&dir, &report)) {
+            Some(Ok(path)) => {
+                eprintln!("error: wonk crashed: {message}");
+                eprintln!("A crash report was written to {}", path.display());
+                eprintln!(
+                    "Please run `wonk diag` and attach its output to an issue at {ISSUES_URL}"
+                );
+            }
+            _ => default_hook(info),
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn redact_keeps_commands_flags_and_safe_values() {
+        assert_eq!(
+            redact_args(&args(
+                "search secret_fn --format json -i --budget 500 -- src/private"
+            )),
+            args("wonk search <redacted> --format json -i --budget 500 -- <redacted>")
+        );
+        assert_eq!(
+            redact_args(&args("daemon stop --all")),
+            args("wonk daemon stop --all")
+        );
+        assert_eq!(
+            redact_args(&args("sym Config --kind=struct --file=src/a.rs")),
+            args("wonk sym <redacted> --kind=<redacted> --file=<redacted>")
+        );
+    }
+
+    #[test]
+    fn redact_treats_subcommand_names_as_values_after_the_first_argument() {
+        assert_eq!(
+            redact_args(&args("search update")),
+            args("wonk search <redacted>")
+        );
+    }
+
+    #[test]
+    fn panic_messages_lose_quoted_text_and_details() {
+        let sliced = std::panic::catch_unwind(|| {
+            let source = String::from("fn caf\u{e9}_secret() {}");
+            source[..7].len()
+        })
+        .unwrap_err();
+        let message = sliced.downcast_ref::<String>().unwrap();
+        assert!(message.contains("secret"), "{message}");
+        let redacted = redact_panic_message(message);
+        assert!(!redacted.contains("secret"), "{redacted}");
+        assert!(
+            redacted.contains("byte index 7 is not a char boundary; it is inside <redacted>"),
+            "{redacted}"
+        );
+
+        assert_eq!(
+            redact_panic_message(
+                "called `Result::unwrap()` on an `Err` value: Os { code: 2, kind: NotFound, \
+                 message: \"/home/me/private/src/a.rs\" }"
+            ),
+            "called `Result::unwrap()` on an `Err` value: <redacted>"
+        );
+        assert_eq!(
+            redact_panic_message("can't read \"/home/me/notes.txt\""),
+            "can't read <redacted>"
+        );
+        assert_eq!(
+            redact_panic_message("attempt to subtract with overflow"),
+            "attempt to subtract with overflow"
+        );
+    }
+
+    #[test]
+    fn report_contains_environment_and_backtrace() {
+        let stats = RepoStats {
+            location: "local".into(),
+            index_bytes: 2 * 1024 * 1024,
+            files: 10,
+            symbols: 20,
+            references: 30,
+            languages: vec!["rust".into()],
+            built_by: Some("4.14.1".into()),
+            partial: false,
+        };
+        let report = render_report(
+            "boom at `secret_fn`",
+            Some("src/x.rs:1:2".into()),
+            &args("wonk status"),
+            Some(&stats),
+            "0: main\n",
+        );
+        assert!(report.starts_with("wonk crash report\n"));
+        assert!(report.contains(&format!("version: {}\n", env!("CARGO_PKG_VERSION"))));
+        assert!(report.contains("command: wonk status\n"));
+        assert!(report.contains("panic: boom at <redacted>\nlocation: src/x.rs:1:2\n"));
+        assert!(report.contains(
+            "index: local, 2.0 MiB, 10 files, 20 symbols, 30 references, built by 4.14.1\n"
+        ));
+        assert!(report.ends_with("backtrace:\n0: main\n"));
+    }
+
+    #[test]
+    fn reports_are_written_and_listed_newest_first() {
+        let dir = TempDir::new().unwrap();
+        assert!(list_reports(dir.path()).is_empty());
+
+        let old = dir.path().join("crash-1-1.txt");
+        std::fs::write(&old, "old").unwrap();
+        let past = SystemTime::now() - std::time::Duration::from_secs(60);
+        std::fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(past)
+            .unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let new = write_report(dir.path(), "new").unwrap();
+        assert_eq!(list_reports(dir.path()), vec![new, old]);
+    }
+
+    #[test]
+    fn repo_stats_count_index_rows() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        std::fs::create_dir(root.join(".git")).unwrap();
+        std::fs::write(
+            root.join("lib.rs"),
+            "fn helper() {}\nfn main() { helper(); }\n",
+        )
+        .unwrap();
+        crate::pipeline::build_index(root, true).unwrap();
+
+        let stats = RepoStats::collect(root).unwrap();
+        assert_eq!(stats.location, "local");
+        assert_eq!(stats.files, 1);
+        assert!(stats.symbols >= 2);
+        assert!(stats.index_bytes > 0);
+        assert_eq!(stats.languages, vec!["Rust".to_string()]);
+
+        let empty = TempDir::new().unwrap();
+        std::fs::create_dir(empty.path().join(".git")).unwrap();
+        assert!(RepoStats::collect(empty.path()).is_none());
+    }
+}
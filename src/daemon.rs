@@ -8,6 +8,7 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
@@ -46,9 +47,16 @@ pub struct DaemonInfo {
     pub state: Option<String>,
     pub uptime_start: Option<String>,
     pub last_activity: Option<String>,
+    /// Files whose index data changed in the last reindex.
+    pub last_reindex_files: Option<String>,
+    /// File events handled since the daemon started.
+    pub events_processed: Option<String>,
     pub files_queued: Option<String>,
     pub last_error: Option<String>,
     pub heartbeat: Option<String>,
+    pub last_event: Option<String>,
+    pub last_flood: Option<String>,
+    pub last_flood_files: Option<String>,
     pub embedding_last_activity: Option<String>,
     pub embedding_files_count: Option<String>,
     pub embedding_build_requested: Option<String>,
@@ -96,6 +104,18 @@ pub fn update_activity(conn: &Connection) -> Result<()> {
     write_status(conn, "last_activity", &now_epoch().to_string())
 }
 
+/// Record a reindex that changed `files` files: updates `last_activity`
+/// and `last_reindex_files`.
+pub fn record_reindex(conn: &Connection, files: usize) -> Result<()> {
+    update_activity(conn)?;
+    write_status(conn, "last_reindex_files", &files.to_string())
+}
+
+/// Record the number of file events handled since startup.
+pub fn record_events_processed(conn: &Connection, count: u64) -> Result<()> {
+    write_status(conn, "events_processed", &count.to_string())
+}
+
 /// Update the `files_queued` count (call when processing batches).
 pub fn update_queue_depth(conn: &Connection, count: usize) -> Result<()> {
     write_status(conn, "files_queued", &count.to_string())
@@ -106,11 +126,24 @@ pub fn write_error(conn: &Connection, error_msg: &str) -> Result<()> {
     write_status(conn, "last_error", error_msg)
 }
 
-/// Update the heartbeat timestamp (call periodically, e.g. every 30s).
+/// Record that a burst of `files` changed files was handled with one
+/// incremental update instead of file by file.
+pub fn record_flood(conn: &Connection, files: usize) -> Result<()> {
+    write_status(conn, "last_flood", &now_epoch().to_string())?;
+    write_status(conn, "last_flood_files", &files.to_string())?;
+    Ok(())
+}
+
+/// Update the heartbeat timestamp (call every [`HEARTBEAT_INTERVAL`]).
 pub fn write_heartbeat(conn: &Connection) -> Result<()> {
     write_status(conn, "heartbeat", &now_epoch().to_string())
 }
 
+/// Record the path of the last file event the daemon processed.
+pub fn record_last_event(conn: &Connection, path: &str) -> Result<()> {
+    write_status(conn, "last_event", path)
+}
+
 /// Update embedding activity status (call after each embedding re-index).
 pub fn update_embedding_activity(conn: &Connection, files_count: usize) -> Result<()> {
     write_status(conn, "embedding_last_activity", &now_epoch().to_string())?;
@@ -178,15 +211,153 @@ pub fn read_all_status(conn: &Connection) -> Result<DaemonInfo> {
         state: map.remove("state"),
         uptime_start: map.remove("uptime_start"),
         last_activity: map.remove("last_activity"),
+        last_reindex_files: map.remove("last_reindex_files"),
+        events_processed: map.remove("events_processed"),
         files_queued: map.remove("files_queued"),
         last_error: map.remove("last_error"),
         heartbeat: map.remove("heartbeat"),
+        last_event: map.remove("last_event"),
+        last_flood: map.remove("last_flood"),
+        last_flood_files: map.remove("last_flood_files"),
         embedding_last_activity: map.remove("embedding_last_activity"),
         embedding_files_count: map.remove("embedding_files_count"),
         embedding_build_requested: map.remove("embedding_build_requested"),
     })
 }
 
+// ---------------------------------------------------------------------------
+// Health
+// ---------------------------------------------------------------------------
+
+/// How often the event loop refreshes the heartbeat, even when idle.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A running daemon whose heartbeat is older than this is considered stuck.
+pub const HEARTBEAT_STALE_SECS: i64 = 60;
+
+/// Liveness of a repository's daemon, as reported by `wonk status`.
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
+pub struct DaemonHealth {
+    pub pid: u32,
+    pub state: String,
+    /// Seconds since the last heartbeat.
+    pub heartbeat_age: Option<i64>,
+    /// Path of the last file event processed.
+    pub last_event: Option<String>,
+    pub files_queued: usize,
+    /// The process is alive but has stopped writing heartbeats.
+    pub stuck: bool,
+}
+
+/// Seconds since the daemon last wrote its heartbeat.
+pub fn heartbeat_age(info: &DaemonInfo) -> Option<i64> {
+    let beat = info.heartbeat.as_ref()?.parse::<i64>().ok()?;
+    Some((now_epoch() - beat).max(0))
+}
+
+/// Whether a daemon with this status has stopped writing heartbeats.
+///
+/// A catch-up update after a flood blocks the event loop by design, so a
+/// daemon in the `catching_up` state is never reported as stuck.
+pub fn is_stuck(info: &DaemonInfo) -> bool {
+    info.state.as_deref() != Some("catching_up")
+        && heartbeat_age(info).is_some_and(|age| age > HEARTBEAT_STALE_SECS)
+}
+
+/// Read the health of the daemon recorded in `conn`, or `None` when no
+/// daemon process is alive.
+pub fn read_health(conn: &Connection) -> Option<DaemonHealth> {
+    let info = read_all_status(conn).ok()?;
+    let pid = info.pid.as_ref()?.parse::<u32>().ok()?;
+    if !process_alive(pid) {
+        return None;
+    }
+    Some(DaemonHealth {
+        pid,
+        stuck: is_stuck(&info),
+        heartbeat_age: heartbeat_age(&info),
+        state: info.state.unwrap_or_else(|| "running".to_string()),
+        last_event: info.last_event,
+        files_queued: info.files_queued.and_then(|n| n.parse().ok()).unwrap_or(0),
+    })
+}
+
+/// Kill a daemon that is alive but no longer writes heartbeats, so a new
+/// one can take its place. Returns whether a daemon was killed.
+///
+/// A stuck event loop never checks its shutdown flag, so this sends
+/// SIGKILL rather than SIGTERM and clears the status the daemon left behind.
+fn kill_if_stuck(index_dir: &Path, index_path: &Path) -> Result<bool> {
+    let Ok(contents) = fs::read_to_string(pid_file_path(index_dir)) else {
+        return Ok(false);
+    };
+    let Ok(pid) = contents.trim().parse::<u32>() else {
+        return Ok(false);
+    };
+    let conn = db::open(index_path)?;
+    if !process_alive(pid) || !is_stuck(&read_all_status(&conn)?) {
+        return Ok(false);
+    }
+
+    // SAFETY: sending SIGKILL is a standard POSIX operation.
+    unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) };
+    for _ in 0..25 {
+        if !process_alive(pid) {
+            break;
+        }
+        thread::sleep(Duration::from_millis(200));
+    }
+    clear_status(&conn)?;
+    remove_pid(index_dir)?;
+    Ok(true)
+}
+
+// ---------------------------------------------------------------------------
+// Auto-start policy
+// ---------------------------------------------------------------------------
+
+/// When commands start the daemon on their own, from `[daemon].auto_start`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AutoStart {
+    /// Only `wonk daemon start` starts it.
+    Never,
+    /// After a query builds a missing index, except in CI (`$CI` is set).
+    #[default]
+    Auto,
+    /// Whenever a query runs in an indexed repository with no daemon.
+    Always,
+}
+
+impl AutoStart {
+    pub const NAMES: &[&str] = &["never", "auto", "always"];
+
+    /// Whether to start a daemon: right after auto-init built the index
+    /// (`after_init`), or before any other query when `Always`.
+    pub fn wants_start(self, after_init: bool, in_ci: bool) -> bool {
+        match self {
+            AutoStart::Never => false,
+            AutoStart::Auto => after_init && !in_ci,
+            AutoStart::Always => true,
+        }
+    }
+}
+
+impl FromStr for AutoStart {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "never" => Ok(AutoStart::Never),
+            "auto" => Ok(AutoStart::Auto),
+            "always" => Ok(AutoStart::Always),
+            other => Err(format!(
+                "unknown [daemon].auto_start {other:?} (available: {})",
+                AutoStart::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // PID file path
 // ---------------------------------------------------------------------------
@@ -245,8 +416,7 @@ pub fn check_stale_pid(index_dir: &Path) -> Result<()> {
 /// Write the current process's PID to `daemon.pid`.
 pub fn write_pid(index_dir: &Path) -> Result<()> {
     let pid_path = pid_file_path(index_dir);
-    fs::create_dir_all(index_dir)
-        .with_context(|| format!("creating index directory {}", index_dir.display()))?;
+    crate::access::create_private_dir(index_dir)?;
     fs::write(&pid_path, format!("{}\n", process::id()))
         .with_context(|| format!("writing PID file {}", pid_path.display()))?;
     Ok(())
@@ -355,6 +525,10 @@ pub(crate) fn coalesce_file_batches(
 // Spawn daemon (main entry point)
 // ---------------------------------------------------------------------------
 
+/// How long a flood of file events must pause before the daemon runs its
+/// catch-up update.
+const FLOOD_SETTLE: Duration = Duration::from_secs(2);
+
 /// Spawn the daemon for the given repository.
 ///
 /// This function:
@@ -373,8 +547,12 @@ pub fn spawn_daemon(repo_root: &Path, local: bool) -> Result<()> {
         .expect("index.db must have a parent directory")
         .to_path_buf();
 
-    // Remove stale PID files from crashed daemons.
+    // Remove stale PID files from crashed daemons, and replace a daemon
+    // that is alive but stuck.
     check_stale_pid(&index_dir)?;
+    if index_path.exists() {
+        kill_if_stuck(&index_dir, &index_path)?;
+    }
 
     // Enforce single instance.
     if is_running(&index_dir) {
@@ -391,6 +569,11 @@ pub fn spawn_daemon(repo_root: &Path, local: bool) -> Result<()> {
     // Write PID file (we are now the daemon process).
     write_pid(&index_dir)?;
 
+    // Background reindexing yields the CPU to foreground work; threads
+    // spawned below inherit the priority.
+    let config = crate::config::Config::load(Some(repo_root)).unwrap_or_default();
+    pipeline::lower_priority(config.index.nice);
+
     // Open the database so we can write status.
     let conn = db::open(&index_path)?;
 
@@ -474,23 +657,70 @@ pub fn spawn_daemon(repo_root: &Path, local: bool) -> Result<()> {
         .context("spawning embedding worker thread")?;
 
     // --- File watcher event loop ---
-    // Build ignore rules from .gitignore, .wonkignore, and config patterns.
-    let config = crate::config::Config::load(Some(repo_root)).unwrap_or_default();
-    let ignore_matcher = Arc::new(watcher::IgnoreMatcher::build(
-        repo_root,
-        &config.ignore.patterns,
-    ));
+    // Build ignore rules from .gitignore, .wonkignore, and config patterns;
+    // `[daemon].ignore` only affects the watcher.
+    let patterns: Vec<String> = config
+        .ignore
+        .patterns
+        .iter()
+        .chain(&config.daemon.ignore)
+        .cloned()
+        .collect();
+    let ignore_matcher = Arc::new(watcher::IgnoreMatcher::build(repo_root, &patterns));
     let (_watcher, rx) =
         FileWatcher::new(repo_root, 500, ignore_matcher).context("starting file watcher")?;
 
     let repo_root_buf = repo_root.to_path_buf();
-    watcher::run_event_loop(&rx, &shutdown, |events| {
+    let flood_threshold = config.daemon.flood_threshold;
+    let on_tick = || {
+        write_heartbeat(&conn).ok();
+    };
+    let mut events_processed = 0u64;
+    watcher::run_event_loop_with_tick(&rx, &shutdown, HEARTBEAT_INTERVAL, on_tick, |events| {
+        let mut events = watcher::coalesce_events(events, &rx);
         update_queue_depth(&conn, events.len()).ok();
+        if let Some(last) = events.last()
+            && let Ok(rel) = last.path().strip_prefix(&repo_root_buf)
+        {
+            record_last_event(&conn, &rel.to_string_lossy()).ok();
+        }
+
+        // A checkout or rebase touching thousands of files: let it finish,
+        // then catch up with one incremental update instead of reindexing
+        // file by file.
+        if flood_threshold > 0 && events.len() >= flood_threshold {
+            write_status(&conn, "state", "catching_up").ok();
+            events.extend(watcher::wait_for_quiet(&rx, &shutdown, FLOOD_SETTLE));
+            // A path can be in both the flood and the batches that followed.
+            let events = watcher::dedup_events(events);
+            record_flood(&conn, events.len()).ok();
+            events_processed += events.len() as u64;
+            record_events_processed(&conn, events_processed).ok();
+            match pipeline::incremental_update(&repo_root_buf, local) {
+                Ok(_) => {
+                    let changed: Vec<String> = events
+                        .iter()
+                        .filter_map(|e| e.path().strip_prefix(&repo_root_buf).ok())
+                        .map(|p| p.to_string_lossy().into_owned())
+                        .collect();
+                    record_reindex(&conn, changed.len()).ok();
+                    let _ = embed_tx.send(changed);
+                }
+                Err(e) => {
+                    write_error(&conn, &format!("catch-up update: {e:#}")).ok();
+                }
+            }
+            write_status(&conn, "state", "running").ok();
+            update_queue_depth(&conn, 0).ok();
+            return;
+        }
 
-        match pipeline::process_events(&conn, events, &repo_root_buf) {
+        events_processed += events.len() as u64;
+        record_events_processed(&conn, events_processed).ok();
+        match pipeline::process_events(&conn, &events, &repo_root_buf) {
             Ok(result) => {
                 if result.updated_count > 0 {
-                    update_activity(&conn).ok();
+                    record_reindex(&conn, result.updated_count).ok();
                 }
                 // Send changed files to embedding worker (non-blocking).
                 if !result.changed_files.is_empty() {
@@ -596,7 +826,7 @@ pub fn daemon_status(repo_root: &Path, local: bool) -> Result<Option<u32>> {
 // ---------------------------------------------------------------------------
 
 /// A discovered daemon entry with its metadata.
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
 pub struct DaemonEntry {
     pub pid: u32,
     pub repo_path: String,
@@ -878,6 +1108,20 @@ mod tests {
         check_stale_pid(dir.path()).unwrap();
     }
 
+    #[test]
+    fn test_auto_start_policy() {
+        assert_eq!("never".parse(), Ok(AutoStart::Never));
+        assert_eq!("always".parse(), Ok(AutoStart::Always));
+        assert!("sometimes".parse::<AutoStart>().is_err());
+        assert_eq!(AutoStart::default(), AutoStart::Auto);
+
+        assert!(AutoStart::Auto.wants_start(true, false));
+        assert!(!AutoStart::Auto.wants_start(true, true));
+        assert!(!AutoStart::Auto.wants_start(false, false));
+        assert!(AutoStart::Always.wants_start(false, true));
+        assert!(!AutoStart::Never.wants_start(true, false));
+    }
+
     #[test]
     fn test_process_alive_current() {
         assert!(process_alive(process::id()));
@@ -1004,6 +1248,17 @@ mod tests {
         assert!(ts > 0);
     }
 
+    #[test]
+    fn test_record_reindex_and_events() {
+        let conn = open_test_db();
+        record_reindex(&conn, 3).unwrap();
+        record_events_processed(&conn, 12).unwrap();
+        let info = read_all_status(&conn).unwrap();
+        assert!(info.last_activity.is_some());
+        assert_eq!(info.last_reindex_files.as_deref(), Some("3"));
+        assert_eq!(info.events_processed.as_deref(), Some("12"));
+    }
+
     #[test]
     fn test_update_queue_depth() {
         let conn = open_test_db();
@@ -1043,6 +1298,43 @@ mod tests {
         assert!(ts > 0);
     }
 
+    #[test]
+    fn test_is_stuck_on_stale_heartbeat() {
+        let conn = open_test_db();
+        write_startup_status(&conn, process::id()).unwrap();
+        assert!(!is_stuck(&read_all_status(&conn).unwrap()));
+
+        let stale = now_epoch() - HEARTBEAT_STALE_SECS - 5;
+        write_status(&conn, "heartbeat", &stale.to_string()).unwrap();
+        let info = read_all_status(&conn).unwrap();
+        assert!(heartbeat_age(&info).unwrap() > HEARTBEAT_STALE_SECS);
+        assert!(is_stuck(&info));
+
+        // A catch-up update blocks the loop on purpose.
+        write_status(&conn, "state", "catching_up").unwrap();
+        assert!(!is_stuck(&read_all_status(&conn).unwrap()));
+    }
+
+    #[test]
+    fn test_read_health() {
+        let conn = open_test_db();
+        assert!(read_health(&conn).is_none());
+
+        write_startup_status(&conn, process::id()).unwrap();
+        record_last_event(&conn, "src/main.rs").unwrap();
+        update_queue_depth(&conn, 3).unwrap();
+        let health = read_health(&conn).unwrap();
+        assert_eq!(health.pid, process::id());
+        assert_eq!(health.state, "running");
+        assert_eq!(health.last_event.as_deref(), Some("src/main.rs"));
+        assert_eq!(health.files_queued, 3);
+        assert!(!health.stuck);
+
+        // A dead process has no health to report.
+        write_status(&conn, "pid", "4294967").unwrap();
+        assert!(read_health(&conn).is_none());
+    }
+
     #[test]
     fn test_clear_status() {
         let conn = open_test_db();
@@ -1097,6 +1389,16 @@ mod tests {
         assert!(info.heartbeat.is_some());
     }
 
+    #[test]
+    fn test_record_flood() {
+        let conn = open_test_db();
+        record_flood(&conn, 50_000).unwrap();
+
+        let info = read_all_status(&conn).unwrap();
+        assert!(info.last_flood.is_some());
+        assert_eq!(info.last_flood_files, Some("50000".to_string()));
+    }
+
     #[test]
     fn test_startup_then_clear_lifecycle() {
         let conn = open_test_db();
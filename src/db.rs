@@ -5,16 +5,26 @@
 
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Once;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result, bail};
 use rusqlite::Connection;
 use sha2::{Digest, Sha256};
 
+use crate::access;
+use crate::config::Config;
+use crate::vcs::Vcs;
+
 // ---------------------------------------------------------------------------
 // Schema SQL
 // ---------------------------------------------------------------------------
 
+/// Version of the schema [`open`] brings an index up to, stored in SQLite's
+/// `user_version`.  Bump it whenever `apply_schema` gains a migration.
+pub const SCHEMA_VERSION: i64 = 1;
+
 const SCHEMA_SQL: &str = r#"
 CREATE TABLE IF NOT EXISTS symbols (
     id INTEGER PRIMARY KEY,
@@ -38,7 +48,8 @@ CREATE TABLE IF NOT EXISTS "references" (
     context TEXT,
     caller_id INTEGER REFERENCES symbols(id) ON DELETE SET NULL,
     confidence REAL DEFAULT 0.5,
-    target_id INTEGER REFERENCES symbols(id) ON DELETE SET NULL
+    target_id INTEGER REFERENCES symbols(id) ON DELETE SET NULL,
+    kind TEXT
 );
 
 CREATE TABLE IF NOT EXISTS files (
@@ -110,6 +121,15 @@ CREATE TABLE IF NOT EXISTS summaries (
 );
 "#;
 
+/// Single-row counter advanced by every write that changes indexed content,
+/// so a result can be tied to the state of the index it came from.
+const GENERATION_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS index_generation (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    value INTEGER NOT NULL
+);
+"#;
+
 const FTS_SQL: &str = r#"
 CREATE VIRTUAL TABLE IF NOT EXISTS symbols_fts USING fts5(
     name, kind, file, content=symbols, content_rowid=id
@@ -138,6 +158,35 @@ CREATE TRIGGER IF NOT EXISTS symbols_au AFTER UPDATE ON symbols BEGIN
 END;
 "#;
 
+/// Trigram index over symbol names.  FTS5's trigram tokenizer lets SQLite
+/// answer `name LIKE '%abc%'` from the index instead of scanning every row,
+/// which keeps substring `wonk sym` lookups fast on very large indexes.
+const NAME_TRIGRAM_SQL: &str = r#"
+CREATE VIRTUAL TABLE IF NOT EXISTS symbol_names USING fts5(
+    name, content=symbols, content_rowid=id, tokenize='trigram'
+);
+
+CREATE TRIGGER IF NOT EXISTS symbol_names_ai AFTER INSERT ON symbols BEGIN
+    INSERT INTO symbol_names(rowid, name) VALUES (new.id, new.name);
+END;
+
+CREATE TRIGGER IF NOT EXISTS symbol_names_bd BEFORE DELETE ON symbols BEGIN
+    INSERT INTO symbol_names(symbol_names, rowid, name) VALUES ('delete', old.id, old.name);
+END;
+
+CREATE TRIGGER IF NOT EXISTS symbol_names_bu BEFORE UPDATE ON symbols BEGIN
+    INSERT INTO symbol_names(symbol_names, rowid, name) VALUES ('delete', old.id, old.name);
+END;
+
+CREATE TRIGGER IF NOT EXISTS symbol_names_au AFTER UPDATE ON symbols BEGIN
+    INSERT INTO symbol_names(rowid, name) VALUES (new.id, new.name);
+END;
+"#;
+
+/// Shortest substring the trigram index can answer; shorter patterns fall
+/// back to a plain `LIKE` scan.
+pub const NAME_TRIGRAM_MIN_LEN: usize = 3;
+
 // ---------------------------------------------------------------------------
 // Connection management
 // ---------------------------------------------------------------------------
@@ -145,17 +194,18 @@ END;
 /// Open (or create) a SQLite database at `path`, apply the full schema, and
 /// set pragmas suitable for concurrent access.
 pub fn open(path: &Path) -> Result<Connection> {
-    // Ensure parent directory exists.
+    // Ensure parent directory exists, private to the current user.
     if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .with_context(|| format!("creating index directory {}", parent.display()))?;
+        access::create_private_dir(parent)?;
     }
+    access::check_owner(path)?;
 
     let conn =
         Connection::open(path).with_context(|| format!("opening database {}", path.display()))?;
 
     apply_pragmas(&conn)?;
     apply_schema(&conn)?;
+    crate::deadline::watch(&conn);
 
     Ok(conn)
 }
@@ -166,10 +216,33 @@ pub fn open_existing(path: &Path) -> Result<Connection> {
     if !path.exists() {
         bail!("index not found at {}", path.display());
     }
+    access::check_owner(path)?;
     let conn =
         Connection::open(path).with_context(|| format!("opening database {}", path.display()))?;
 
     apply_pragmas(&conn)?;
+    crate::deadline::watch(&conn);
+    Ok(conn)
+}
+
+/// Copy the existing database at `path` into a new in-memory database.
+///
+/// The copy is a consistent snapshot of the index; later writes to either
+/// side are not seen by the other.
+pub fn open_in_memory_copy(path: &Path) -> Result<Connection> {
+    if !path.exists() {
+        bail!("index not found at {}", path.display());
+    }
+    let mut conn = Connection::open_in_memory().context("opening in-memory database")?;
+    conn.restore(
+        rusqlite::MAIN_DB,
+        path,
+        None::<fn(rusqlite::backup::Progress)>,
+    )
+    .with_context(|| format!("copying {} into memory", path.display()))?;
+    conn.execute_batch("PRAGMA foreign_keys = ON;")
+        .context("setting database pragmas")?;
+    crate::deadline::watch(&conn);
     Ok(conn)
 }
 
@@ -192,19 +265,42 @@ fn apply_schema(conn: &Connection) -> Result<()> {
     ensure_confidence_column(conn)?;
     ensure_doc_comment_column(conn)?;
     ensure_target_id_column(conn)?;
+    ensure_reference_kind_column(conn)?;
+    ensure_stable_id_column(conn)?;
+    ensure_is_test_column(conn)?;
+    ensure_generated_by_column(conn)?;
+    ensure_submodule_column(conn)?;
+    ensure_unresolvable_column(conn)?;
+    ensure_resolved_file_column(conn)?;
+    ensure_condition_column(conn)?;
+    ensure_modifier_columns(conn)?;
+    ensure_generics_column(conn)?;
+    ensure_range_columns(conn)?;
     conn.execute_batch(TYPE_EDGES_SQL)
         .context("creating type_edges table")?;
     conn.execute_batch(EMBEDDINGS_SQL)
         .context("creating embeddings table")?;
     conn.execute_batch(SUMMARIES_SQL)
         .context("creating summaries table")?;
+    conn.execute_batch(GENERATION_SQL)
+        .context("creating index_generation table")?;
     conn.execute_batch(FTS_SQL)
         .context("creating FTS5 virtual table")?;
     conn.execute_batch(TRIGGERS_SQL)
         .context("creating FTS5 sync triggers")?;
+    ensure_symbol_name_index(conn)?;
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)
+        .context("recording schema version")?;
     Ok(())
 }
 
+/// The schema version recorded in the index, or 0 for an index last opened
+/// by a wonk that predates versioning.
+pub fn schema_version(conn: &Connection) -> i64 {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+        .unwrap_or(0)
+}
+
 /// Ensure the `embeddings` table exists, creating it if missing.
 ///
 /// This handles schema migration for V1 indexes that were created before
@@ -283,6 +379,256 @@ pub fn ensure_doc_comment_column(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+/// Ensure the `stable_id` column exists on the `symbols` table.
+///
+/// Handles schema migration for indexes built before stable symbol IDs
+/// were added.  Existing rows keep a NULL ID until their file is reindexed.
+pub fn ensure_stable_id_column(conn: &Connection) -> Result<()> {
+    let has_column: bool = conn
+        .prepare("PRAGMA table_info(symbols)")?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|r| r.ok())
+        .any(|name| name == "stable_id");
+
+    if !has_column {
+        conn.execute_batch("ALTER TABLE symbols ADD COLUMN stable_id TEXT;")
+            .context("adding stable_id column to symbols table")?;
+    }
+
+    conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_symbols_stable_id ON symbols(stable_id);")
+        .context("creating stable_id index")?;
+
+    Ok(())
+}
+
+/// Ensure the `is_test` column exists on the `files` table.
+///
+/// Handles schema migration for indexes built before test-file detection
+/// was stored.  Existing rows default to 0 until their file is reindexed.
+pub fn ensure_is_test_column(conn: &Connection) -> Result<()> {
+    let has_column: bool = conn
+        .prepare("PRAGMA table_info(files)")?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|r| r.ok())
+        .any(|name| name == "is_test");
+
+    if !has_column {
+        conn.execute_batch("ALTER TABLE files ADD COLUMN is_test INTEGER NOT NULL DEFAULT 0;")
+            .context("adding is_test column to files table")?;
+    }
+
+    Ok(())
+}
+
+/// Ensure the `generated_by` column exists on the `files` table.
+///
+/// Handles schema migration for indexes built before generated-code
+/// provenance was recorded.
+pub fn ensure_generated_by_column(conn: &Connection) -> Result<()> {
+    let has_column: bool = conn
+        .prepare("PRAGMA table_info(files)")?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|r| r.ok())
+        .any(|name| name == "generated_by");
+
+    if !has_column {
+        conn.execute_batch("ALTER TABLE files ADD COLUMN generated_by TEXT;")
+            .context("adding generated_by column to files table")?;
+    }
+
+    Ok(())
+}
+
+/// Ensure the `submodule` column exists on the `files` table.
+///
+/// Handles schema migration for indexes built before submodule files were
+/// tagged.
+pub fn ensure_submodule_column(conn: &Connection) -> Result<()> {
+    let has_column: bool = conn
+        .prepare("PRAGMA table_info(files)")?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|r| r.ok())
+        .any(|name| name == "submodule");
+
+    if !has_column {
+        conn.execute_batch("ALTER TABLE files ADD COLUMN submodule TEXT;")
+            .context("adding submodule column to files table")?;
+    }
+
+    Ok(())
+}
+
+/// Ensure the `condition` column exists on the `symbols` table.
+///
+/// Handles schema migration for indexes built before preprocessor
+/// conditions were recorded.  Existing rows stay unconditional until their
+/// file is reindexed.
+pub fn ensure_condition_column(conn: &Connection) -> Result<()> {
+    let has_column: bool = conn
+        .prepare("PRAGMA table_info(symbols)")?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|r| r.ok())
+        .any(|name| name == "condition");
+
+    if !has_column {
+        conn.execute_batch("ALTER TABLE symbols ADD COLUMN condition TEXT;")
+            .context("adding condition column to symbols table")?;
+    }
+
+    Ok(())
+}
+
+/// Ensure the `visibility` and `modifiers` columns exist on the `symbols`
+/// table.
+///
+/// Handles schema migration for indexes built before visibility and
+/// modifier keywords were extracted.  `modifiers` holds space-separated
+/// keywords.
+pub fn ensure_modifier_columns(conn: &Connection) -> Result<()> {
+    let columns: Vec<String> = conn
+        .prepare("PRAGMA table_info(symbols)")?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    for column in ["visibility", "modifiers"] {
+        if !columns.iter().any(|c| c == column) {
+            conn.execute_batch(&format!("ALTER TABLE symbols ADD COLUMN {column} TEXT;"))
+                .with_context(|| format!("adding {column} column to symbols table"))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Ensure the `generics` column exists on the `symbols` table.
+///
+/// Handles schema migration for indexes built before generic parameters
+/// were extracted.  The column holds one normalised parameter per line.
+pub fn ensure_generics_column(conn: &Connection) -> Result<()> {
+    let has_column: bool = conn
+        .prepare("PRAGMA table_info(symbols)")?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|r| r.ok())
+        .any(|name| name == "generics");
+
+    if !has_column {
+        conn.execute_batch("ALTER TABLE symbols ADD COLUMN generics TEXT;")
+            .context("adding generics column to symbols table")?;
+    }
+
+    Ok(())
+}
+
+/// Ensure the `end_col`, `start_byte` and `end_byte` columns exist on the
+/// `symbols` and `references` tables.
+///
+/// Handles schema migration for indexes built before source ranges were
+/// recorded; existing rows keep NULL ranges until their files are re-indexed.
+pub fn ensure_range_columns(conn: &Connection) -> Result<()> {
+    for table in ["symbols", "references"] {
+        let existing: Vec<String> = conn
+            .prepare(&format!("PRAGMA table_info(\"{table}\")"))?
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(|r| r.ok())
+            .collect();
+        for column in ["end_col", "start_byte", "end_byte"] {
+            if !existing.iter().any(|name| name == column) {
+                conn.execute_batch(&format!(
+                    "ALTER TABLE \"{table}\" ADD COLUMN {column} INTEGER;"
+                ))
+                .with_context(|| format!("adding {column} column to {table} table"))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Ensure the `unresolvable` column exists on the `file_imports` table.
+///
+/// Handles schema migration for indexes built before imports that did not
+/// resolve were marked, so they are not retried on every update (see
+/// [`crate::imports::resolve_pending`]).
+pub fn ensure_unresolvable_column(conn: &Connection) -> Result<()> {
+    let has_column: bool = conn
+        .prepare("PRAGMA table_info(file_imports)")?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|r| r.ok())
+        .any(|name| name == "unresolvable");
+
+    if !has_column {
+        conn.execute_batch(
+            "ALTER TABLE file_imports ADD COLUMN unresolvable INTEGER NOT NULL DEFAULT 0;",
+        )
+        .context("adding unresolvable column to file_imports table")?;
+    }
+
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_file_imports_unresolvable \
+         ON file_imports(unresolvable) WHERE unresolvable = 1;",
+    )
+    .context("creating unresolvable index")?;
+
+    Ok(())
+}
+
+/// Ensure the `resolved_file` column exists on the `file_imports` table.
+///
+/// Handles schema migration for indexes built before import paths were
+/// resolved to files; existing imports are resolved when the column is added.
+pub fn ensure_resolved_file_column(conn: &Connection) -> Result<()> {
+    let has_column: bool = conn
+        .prepare("PRAGMA table_info(file_imports)")?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|r| r.ok())
+        .any(|name| name == "resolved_file");
+
+    if !has_column {
+        conn.execute_batch("ALTER TABLE file_imports ADD COLUMN resolved_file TEXT;")
+            .context("adding resolved_file column to file_imports table")?;
+        // The repo root (for build files such as go.mod) comes from meta.json.
+        let repo_root = conn
+            .path()
+            .and_then(|p| read_meta(Path::new(p)).ok())
+            .map(|meta| PathBuf::from(meta.repo_path));
+        crate::imports::resolve_pending(conn, repo_root.as_deref())
+            .context("resolving existing imports")?;
+    }
+
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_file_imports_resolved ON file_imports(resolved_file);",
+    )
+    .context("creating resolved_file index")?;
+
+    Ok(())
+}
+
+/// Ensure the trigram index over symbol names exists.
+///
+/// Handles schema migration for indexes built before the trigram index was
+/// added: the table is created and back-filled from `symbols` once.
+pub fn ensure_symbol_name_index(conn: &Connection) -> Result<()> {
+    let existed = has_symbol_name_index(conn);
+    conn.execute_batch(NAME_TRIGRAM_SQL)
+        .context("creating symbol name trigram index")?;
+    if !existed {
+        conn.execute_batch("INSERT INTO symbol_names(symbol_names) VALUES ('rebuild');")
+            .context("populating symbol name trigram index")?;
+    }
+    Ok(())
+}
+
+/// Whether the index has the symbol name trigram table.
+pub fn has_symbol_name_index(conn: &Connection) -> bool {
+    conn.query_row(
+        "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'symbol_names'",
+        [],
+        |_| Ok(()),
+    )
+    .is_ok()
+}
+
 /// Ensure the `caller_id` column exists on the `references` table.
 ///
 /// Handles schema migration for pre-V3 indexes that lack the call graph
@@ -336,12 +682,52 @@ pub fn ensure_target_id_column(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+/// Ensure the `kind` column exists on the `references` table.
+///
+/// Handles schema migration for indexes built before reference kinds were
+/// stored.  Existing rows keep a NULL kind, read as a call, until their file
+/// is reindexed.
+pub fn ensure_reference_kind_column(conn: &Connection) -> Result<()> {
+    let has_column: bool = conn
+        .prepare("PRAGMA table_info(\"references\")")?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|r| r.ok())
+        .any(|name| name == "kind");
+
+    if !has_column {
+        conn.execute_batch("ALTER TABLE \"references\" ADD COLUMN kind TEXT;")
+            .context("adding kind column to references table")?;
+    }
+
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Repo root discovery
 // ---------------------------------------------------------------------------
 
-/// Walk upwards from `start` looking for a `.git` directory or `.wonk`
-/// directory.  Returns the directory that contains the marker.
+/// Marker file that `wonk init --root` leaves in a directory to make it a
+/// repository root without version control (code drops, vendored exports,
+/// SVN checkouts).
+pub const ROOT_MARKER: &str = ".wonk-root";
+
+/// Returns `true` when `dir` holds a repository root marker: a git,
+/// Mercurial, or Jujutsu repository (`.git`, `.hg`, `.jj`), `.wonk`, or
+/// [`ROOT_MARKER`].
+pub fn is_repo_root(dir: &Path) -> bool {
+    Vcs::MARKERS
+        .iter()
+        .map(|(marker, _)| *marker)
+        .chain([".wonk", ROOT_MARKER])
+        .any(|marker| dir.join(marker).exists())
+}
+
+/// Walk upwards from `start` looking for a repository marker (see
+/// [`is_repo_root`]).  Returns the directory that contains the marker.
+///
+/// A linked git worktree is its own root unless `[index].worktrees` is
+/// `"shared"`, in which case the main worktree is returned so that every
+/// checkout of the repository uses one index (see [`main_worktree_root`]).
 pub fn find_repo_root(start: &Path) -> Result<PathBuf> {
     let mut current = start.to_path_buf();
     // Canonicalize so we don't get stuck in symlink loops, but tolerate
@@ -350,18 +736,101 @@ pub fn find_repo_root(start: &Path) -> Result<PathBuf> {
         current = canon;
     }
     loop {
-        if current.join(".git").exists() || current.join(".wonk").exists() {
+        if is_repo_root(&current) {
+            if current.join(".git").is_file()
+                && worktree_index(&current) == WorktreeIndex::Shared
+                && let Some(main) = main_worktree_root(&current)
+            {
+                return Ok(main);
+            }
             return Ok(current);
         }
         if !current.pop() {
             bail!(
-                "could not find repository root (no .git or .wonk) starting from {}",
+                "could not find repository root (no .git, .hg, .jj, .wonk, or {ROOT_MARKER}) starting from {}; run `wonk init --root .` to index a directory outside version control",
                 start.display()
             );
         }
     }
 }
 
+/// How linked git worktrees are indexed, from `[index].worktrees`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WorktreeIndex {
+    /// Each worktree has an index of its own checkout.
+    #[default]
+    Separate,
+    /// Worktrees use the main worktree's index.
+    Shared,
+}
+
+impl WorktreeIndex {
+    pub const NAMES: &[&str] = &["separate", "shared"];
+}
+
+impl FromStr for WorktreeIndex {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "separate" => Ok(WorktreeIndex::Separate),
+            "shared" => Ok(WorktreeIndex::Shared),
+            other => Err(format!(
+                "unknown [index].worktrees {other:?} (available: {})",
+                WorktreeIndex::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// The `[index].worktrees` policy configured for the checkout at `root`.
+/// An unknown value is reported once and treated as the default.
+fn worktree_index(root: &Path) -> WorktreeIndex {
+    static WARNED: Once = Once::new();
+    let Ok(config) = Config::load(Some(root)) else {
+        return WorktreeIndex::default();
+    };
+    config.index.worktrees.parse().unwrap_or_else(|e: String| {
+        WARNED.call_once(|| crate::output::print_warning(&format!("{e}; using separate")));
+        WorktreeIndex::default()
+    })
+}
+
+/// If `root` is a linked git worktree (its `.git` is a file pointing into
+/// another repository's `.git/worktrees/`), return the main worktree's root.
+/// Returns `None` for main worktrees, bare repositories, and submodules.
+pub fn main_worktree_root(root: &Path) -> Option<PathBuf> {
+    let dot_git = fs::read_to_string(root.join(".git")).ok()?;
+    let git_dir = dot_git
+        .lines()
+        .find_map(|l| l.strip_prefix("gitdir:"))?
+        .trim();
+    let git_dir = root.join(git_dir);
+    // Only worktrees have a `commondir`; submodules' git dirs do not.
+    let common = fs::read_to_string(git_dir.join("commondir")).ok()?;
+    let common = fs::canonicalize(git_dir.join(common.trim())).ok()?;
+    if common.file_name()? != ".git" {
+        return None;
+    }
+    common.parent().map(Path::to_path_buf)
+}
+
+/// Paths (relative to `repo_root`) of the git submodules listed in its
+/// `.gitmodules` that are checked out.
+pub fn submodule_paths(repo_root: &Path) -> Vec<String> {
+    let Ok(gitmodules) = fs::read_to_string(repo_root.join(".gitmodules")) else {
+        return Vec::new();
+    };
+    gitmodules
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            (key.trim() == "path").then(|| value.trim().trim_end_matches('/').to_string())
+        })
+        .filter(|path| !path.is_empty() && repo_root.join(path).join(".git").exists())
+        .collect()
+}
+
 // ---------------------------------------------------------------------------
 // Index path computation
 // ---------------------------------------------------------------------------
@@ -381,12 +850,98 @@ fn hex_encode_short(bytes: &[u8]) -> String {
     bytes.iter().take(8).map(|b| format!("{b:02x}")).collect()
 }
 
+/// What central indexes are keyed by, from `[index].key`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IndexKey {
+    /// The repository root's canonical path.
+    #[default]
+    Path,
+    /// The repository's origin URL and root commit, so one repository maps
+    /// to one index whatever path (bind mount, copy, clone) it is reached by.
+    Git,
+}
+
+impl IndexKey {
+    pub const NAMES: &[&str] = &["path", "git"];
+}
+
+impl FromStr for IndexKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "path" => Ok(IndexKey::Path),
+            "git" => Ok(IndexKey::Git),
+            other => Err(format!(
+                "unknown [index].key {other:?} (available: {})",
+                IndexKey::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// The string the central index of `repo_path` is keyed by (see
+/// [`IndexKey`]).  Paths are canonicalized, so a repository reached through
+/// a symlinked home or directory keeps one index; under `"git"`, a
+/// repository without commits falls back to its path.
+pub fn repo_key(repo_path: &Path) -> String {
+    static WARNED: Once = Once::new();
+    let canonical = fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+    let key = Config::load(Some(&canonical))
+        .map(|c| {
+            c.index.key.parse().unwrap_or_else(|e: String| {
+                WARNED.call_once(|| crate::output::print_warning(&format!("{e}; using path")));
+                IndexKey::default()
+            })
+        })
+        .unwrap_or_default();
+    if key == IndexKey::Git
+        && let Some(identity) = git_identity(&canonical)
+    {
+        return identity;
+    }
+    canonical.to_string_lossy().into_owned()
+}
+
+/// `git:<root commit>:<origin URL>` for the repository at `repo_path`, or
+/// `None` without git or commits.  The URL is left out when there is no
+/// `origin` remote, and a trailing `.git` is dropped from it.
+fn git_identity(repo_path: &Path) -> Option<String> {
+    let git = |args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(repo_path)
+            .stderr(std::process::Stdio::null())
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+    // A history with several roots (merged projects) is keyed by the
+    // smallest, which does not depend on the order git lists them in.
+    let root = git(&["rev-list", "--max-parents=0", "HEAD"])?
+        .lines()
+        .min()?
+        .to_string();
+    let remote = git(&["config", "--get", "remote.origin.url"]).unwrap_or_default();
+    let remote = remote.trim().trim_end_matches('/');
+    let remote = remote.strip_suffix(".git").unwrap_or(remote);
+    Some(format!("git:{root}:{remote}"))
+}
+
+/// The central store holding one index directory per repository:
+/// `~/.wonk/repos`.
+pub fn central_repos_dir() -> Result<PathBuf> {
+    Ok(home_dir()?.join(".wonk").join("repos"))
+}
+
 /// Where the index lives when using the **central** (default) location:
-/// `~/.wonk/repos/<hash>/index.db`
+/// `~/.wonk/repos/<hash>/index.db`, where the hash is of [`repo_key`].
 pub fn central_index_path(repo_path: &Path) -> Result<PathBuf> {
-    let home = home_dir()?;
-    let hash = repo_hash(repo_path);
-    Ok(home.join(".wonk").join("repos").join(hash).join("index.db"))
+    let hash = repo_hash(Path::new(&repo_key(repo_path)));
+    Ok(central_repos_dir()?.join(hash).join("index.db"))
 }
 
 /// Where the index lives when using the **local** location:
@@ -396,31 +951,78 @@ pub fn local_index_path(repo_root: &Path) -> PathBuf {
 }
 
 /// Resolve the index path for a given repo, respecting `local` flag.
+///
+/// Uses the local location as well when the central store cannot be
+/// written (see [`central_store_usable`]).
 pub fn index_path_for(repo_root: &Path, local: bool) -> Result<PathBuf> {
-    if local {
+    if local || !central_store_usable() {
         Ok(local_index_path(repo_root))
     } else {
         central_index_path(repo_root)
     }
 }
 
+/// Returns `true` when indexes can be kept in the central store
+/// (`~/.wonk/repos`): `$HOME` is set and the store is, or can be created
+/// as, a directory the current user owns.
+pub fn central_store_usable() -> bool {
+    central_repos_dir().is_ok_and(|store| access::central_store_writable(&store))
+}
+
 /// Check whether an index exists for the given repo root.
 ///
 /// Checks the local path first (`.wonk/index.db`), then the central path
-/// (`~/.wonk/repos/<hash>/index.db`).  Returns the path if found.
+/// (`~/.wonk/repos/<hash>/index.db`).  Returns the path if found.  Indexes
+/// that belong to another user are passed over, so a shared checkout's
+/// `.wonk/index.db` does not hide the current user's own index.
 pub fn find_existing_index(repo_root: &Path) -> Option<PathBuf> {
     let local = local_index_path(repo_root);
-    if local.exists() {
+    if access::is_own(&local) {
         return Some(local);
     }
     if let Ok(central) = central_index_path(repo_root)
-        && central.exists()
+        && access::is_own(&central)
     {
         return Some(central);
     }
     None
 }
 
+// ---------------------------------------------------------------------------
+// Index generation
+// ---------------------------------------------------------------------------
+
+/// Advance the index generation.  Call inside the transaction that changes
+/// indexed content, so the new generation becomes visible with the content.
+pub fn bump_generation(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "INSERT INTO index_generation (id, value) VALUES (1, 1) \
+         ON CONFLICT(id) DO UPDATE SET value = value + 1",
+        [],
+    )
+    .context("advancing index generation")?;
+    Ok(())
+}
+
+/// The current index generation, or 0 for an index never written since
+/// generations were introduced.
+pub fn index_generation(conn: &Connection) -> u64 {
+    conn.query_row(
+        "SELECT value FROM index_generation WHERE id = 1",
+        [],
+        |row| row.get::<_, i64>(0),
+    )
+    .map_or(0, |v| v as u64)
+}
+
+/// The content hash recorded for `path` when it was last indexed.
+pub fn indexed_hash(conn: &Connection, path: &str) -> Option<String> {
+    conn.query_row("SELECT hash FROM files WHERE path = ?1", [path], |row| {
+        row.get(0)
+    })
+    .ok()
+}
+
 // ---------------------------------------------------------------------------
 // meta.json
 // ---------------------------------------------------------------------------
@@ -433,15 +1035,30 @@ pub struct Meta {
     pub languages: Vec<String>,
     #[serde(default)]
     pub wonk_version: Option<String>,
+    /// Languages skipped because their grammar failed to load while indexing.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled_languages: Vec<String>,
+    /// The build was interrupted, or only the files queries promoted into
+    /// the index (see [`crate::pipeline::AutoInit::Lazy`]) were indexed; the
+    /// rest are missing until the next `wonk update`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool,
+    /// Git `HEAD` of the repository when the index was built or updated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// Where the sources came from when not the working tree:
+    /// `archive:<name>` for `wonk init --archive`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
 /// Write `meta.json` next to the given `index_db_path`.
+///
+/// Languages disabled during this session (see
+/// [`crate::indexer::disabled_languages`]) are recorded alongside, and the
+/// index is marked partial when the session was interrupted (see
+/// [`crate::deadline::interrupted`]).
 pub fn write_meta(index_db_path: &Path, repo_path: &Path, languages: &[String]) -> Result<()> {
-    let meta_path = index_db_path
-        .parent()
-        .expect("index.db must have a parent directory")
-        .join("meta.json");
-
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
@@ -452,26 +1069,192 @@ pub fn write_meta(index_db_path: &Path, repo_path: &Path, languages: &[String])
         created: now,
         languages: languages.to_vec(),
         wonk_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        disabled_languages: crate::indexer::disabled_languages(),
+        partial: crate::deadline::interrupted(),
+        commit: head_commit(repo_path),
+        source: None,
     };
+    save_meta(index_db_path, &meta)
+}
+
+/// Record in `meta.json` that the index at `index_db_path` was built from
+/// `archive` rather than the working tree (see [`archive_source`]).
+pub fn mark_archive(index_db_path: &Path, archive: &Path) -> Result<()> {
+    let mut meta = read_meta(index_db_path)?;
+    let name = archive.file_name().unwrap_or(archive.as_os_str());
+    meta.source = Some(format!("archive:{}", name.to_string_lossy()));
+    save_meta(index_db_path, &meta)
+}
+
+/// The `source` of the index at `index_db_path` when it was built by
+/// `wonk init --archive`.  Its directory holds no sources, so updating or
+/// rebuilding it from the working tree would empty it.
+pub fn archive_source(index_db_path: &Path) -> Option<String> {
+    read_meta(index_db_path)
+        .ok()?
+        .source
+        .filter(|s| s.starts_with("archive:"))
+}
+
+/// Mark the index at `index_db_path` partial in its `meta.json`, so it is
+/// reported as such until a full `wonk update` rewrites it.
+pub fn mark_partial(index_db_path: &Path) -> Result<()> {
+    let mut meta = read_meta(index_db_path)?;
+    meta.partial = true;
+    save_meta(index_db_path, &meta)
+}
 
-    let json = serde_json::to_string_pretty(&meta).context("serializing meta.json")?;
+/// Write `meta` to the `meta.json` next to `index_db_path`.
+fn save_meta(index_db_path: &Path, meta: &Meta) -> Result<()> {
+    let meta_path = meta_path(index_db_path)?;
+    let json = serde_json::to_string_pretty(meta).context("serializing meta.json")?;
     fs::write(&meta_path, json).with_context(|| format!("writing {}", meta_path.display()))?;
     Ok(())
 }
 
-/// Read `meta.json` from next to the given `index_db_path`.
-pub fn read_meta(index_db_path: &Path) -> Result<Meta> {
-    let meta_path = index_db_path
+/// The `meta.json` kept next to `index_db_path`.
+fn meta_path(index_db_path: &Path) -> Result<PathBuf> {
+    index_db_path
         .parent()
-        .expect("index.db must have a parent directory")
-        .join("meta.json");
+        .map(|dir| dir.join("meta.json"))
+        .with_context(|| {
+            format!(
+                "index path {} has no parent directory",
+                index_db_path.display()
+            )
+        })
+}
 
+/// The commit checked out in `repo_path`, or `None` outside a git work tree
+/// (or without `git`).
+pub fn head_commit(repo_path: &Path) -> Option<String> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(repo_path)
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    let commit = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (output.status.success() && !commit.is_empty()).then_some(commit)
+}
+
+/// Read `meta.json` from next to the given `index_db_path`.
+pub fn read_meta(index_db_path: &Path) -> Result<Meta> {
+    let meta_path = meta_path(index_db_path)?;
     let data = fs::read_to_string(&meta_path)
         .with_context(|| format!("reading {}", meta_path.display()))?;
     let meta: Meta = serde_json::from_str(&data).context("parsing meta.json")?;
     Ok(meta)
 }
 
+// ---------------------------------------------------------------------------
+// Central store cleanup
+// ---------------------------------------------------------------------------
+
+/// An index directory in the central store (`~/.wonk/repos/<hash>`).
+#[derive(Debug, Clone)]
+pub struct CentralIndex {
+    pub dir: PathBuf,
+    /// The repository indexed, from `meta.json`; `None` when unreadable.
+    pub repo_path: Option<PathBuf>,
+    /// When the index was last written.
+    pub modified: Option<SystemTime>,
+    /// Bytes taken by the directory and everything in it.
+    pub size: u64,
+}
+
+impl CentralIndex {
+    /// Whether the repository indexed is gone: its path no longer has a
+    /// root marker.  An index whose `meta.json` does not say which
+    /// repository it was is not orphaned but [unknown](Self::is_unknown).
+    pub fn is_orphaned(&self) -> bool {
+        self.repo_path
+            .as_deref()
+            .is_some_and(|path| !is_repo_root(path))
+    }
+
+    /// Whether `meta.json` is missing or unreadable, e.g. after an
+    /// interrupted build or one by an older wonk, so the repository indexed
+    /// cannot be told.
+    pub fn is_unknown(&self) -> bool {
+        self.repo_path.is_none()
+    }
+}
+
+/// Index directories in `repos_dir`, sorted by path.  Entries that are not
+/// directories, symlinks included, or hold neither `index.db` nor
+/// `meta.json` are not indexes and are left out.
+pub fn list_central_indexes(repos_dir: &Path) -> Result<Vec<CentralIndex>> {
+    let read_dir = match fs::read_dir(repos_dir) {
+        Ok(read_dir) => read_dir,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("reading {}", repos_dir.display())),
+    };
+    let mut indexes = Vec::new();
+    for entry in read_dir.flatten() {
+        let dir = entry.path();
+        let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+        if !is_dir || !(dir.join("index.db").exists() || dir.join("meta.json").exists()) {
+            continue;
+        }
+        let index_db = dir.join("index.db");
+        // Writes go to the WAL until a checkpoint, so it may be newer.
+        let modified = ["index.db", "index.db-wal", "meta.json"]
+            .iter()
+            .filter_map(|name| fs::metadata(dir.join(name)).and_then(|m| m.modified()).ok())
+            .max();
+        indexes.push(CentralIndex {
+            repo_path: read_meta(&index_db)
+                .ok()
+                .map(|m| PathBuf::from(m.repo_path)),
+            modified,
+            size: dir_size(&dir),
+            dir,
+        });
+    }
+    indexes.sort_by(|a, b| a.dir.cmp(&b.dir));
+    Ok(indexes)
+}
+
+/// Delete a central index directory, refusing anything but a real
+/// directory directly inside `repos_dir` and indexes whose daemon is still
+/// running.
+pub fn remove_central_index(repos_dir: &Path, index: &CentralIndex) -> Result<()> {
+    let is_dir = fs::symlink_metadata(&index.dir).is_ok_and(|m| m.is_dir());
+    if !is_dir || index.dir.parent() != Some(repos_dir) {
+        bail!(
+            "{} is not an index directory in {}",
+            index.dir.display(),
+            repos_dir.display()
+        );
+    }
+    if crate::daemon::is_running(&index.dir) {
+        bail!(
+            "a daemon is still running for {}; stop it with `wonk daemon stop` first",
+            index.dir.display()
+        );
+    }
+    fs::remove_dir_all(&index.dir).with_context(|| format!("removing {}", index.dir.display()))
+}
+
+/// Total size of the files under `dir`, not following symlinks.
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return 0;
+    };
+    read_dir
+        .flatten()
+        .filter_map(|entry| Some((entry.path(), entry.metadata().ok()?)))
+        .map(|(path, meta)| {
+            if meta.is_dir() {
+                dir_size(&path)
+            } else {
+                meta.len()
+            }
+        })
+        .sum()
+}
+
 // ---------------------------------------------------------------------------
 // Symbol detection
 // ---------------------------------------------------------------------------
@@ -513,6 +1296,34 @@ pub fn file_exists_in_index(conn: &Connection, path: &str) -> Result<bool> {
     Ok(count > 0)
 }
 
+/// Record in `files.submodule` which of `submodules` (paths relative to the
+/// repository root) each indexed file belongs to, clearing stale tags.
+pub fn tag_submodules(conn: &Connection, submodules: &[String]) -> Result<()> {
+    conn.execute(
+        "UPDATE files SET submodule = NULL WHERE submodule IS NOT NULL",
+        [],
+    )?;
+    for sub in submodules {
+        conn.execute(
+            "UPDATE files SET submodule = ?1 \
+             WHERE substr(path, 1, length(?1) + 1) = ?1 || '/'",
+            [sub],
+        )
+        .with_context(|| format!("tagging files of submodule {sub}"))?;
+    }
+    Ok(())
+}
+
+/// Submodule paths that have files in the index.
+pub fn indexed_submodules(conn: &Connection) -> Vec<String> {
+    conn.prepare("SELECT DISTINCT submodule FROM files WHERE submodule IS NOT NULL")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| row.get(0))?
+                .collect::<rusqlite::Result<Vec<String>>>()
+        })
+        .unwrap_or_default()
+}
+
 fn home_dir() -> Result<PathBuf> {
     // Try $HOME first.  We avoid the `dirs` crate to keep dependencies small.
     if let Ok(home) = std::env::var("HOME") {
@@ -606,6 +1417,59 @@ mod tests {
         assert_eq!(timeout, 5000);
     }
 
+    #[test]
+    fn test_symbol_name_index_tracks_symbols() {
+        let dir = TempDir::new().unwrap();
+        let conn = open(&dir.path().join("index.db")).unwrap();
+        assert!(has_symbol_name_index(&conn));
+
+        conn.execute(
+            "INSERT INTO symbols (name, kind, file, line, col, language) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params!["parseConfigFile", "function", "src/main.rs", 10, 0, "rust"],
+        )
+        .unwrap();
+        let hits = |conn: &Connection| -> i64 {
+            conn.query_row(
+                "SELECT COUNT(*) FROM symbol_names WHERE name LIKE '%config%'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(hits(&conn), 1);
+
+        conn.execute("DELETE FROM symbols", []).unwrap();
+        assert_eq!(hits(&conn), 0);
+    }
+
+    #[test]
+    fn test_symbol_name_index_backfills_old_index() {
+        let dir = TempDir::new().unwrap();
+        let conn = open(&dir.path().join("index.db")).unwrap();
+        conn.execute_batch(
+            "DROP TRIGGER symbol_names_ai; DROP TRIGGER symbol_names_bd;
+             DROP TRIGGER symbol_names_bu; DROP TRIGGER symbol_names_au;
+             DROP TABLE symbol_names;",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO symbols (name, kind, file, line, col, language) VALUES ('load_settings', 'function', 'a.rs', 1, 0, 'rust')",
+            [],
+        )
+        .unwrap();
+        assert!(!has_symbol_name_index(&conn));
+
+        ensure_symbol_name_index(&conn).unwrap();
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM symbol_names WHERE name LIKE '%settings%'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
     #[test]
     fn test_fts5_triggers_insert() {
         let dir = TempDir::new().unwrap();
@@ -729,6 +1593,30 @@ mod tests {
         assert_eq!(root, fs::canonicalize(dir.path()).unwrap());
     }
 
+    #[test]
+    fn test_find_repo_root_marker_file() {
+        let dir = TempDir::new().unwrap();
+        let drop = dir.path().join("drop");
+        fs::create_dir_all(drop.join("src/deep")).unwrap();
+        fs::write(drop.join(ROOT_MARKER), "").unwrap();
+
+        let root = find_repo_root(&drop.join("src/deep")).unwrap();
+        assert_eq!(root, fs::canonicalize(&drop).unwrap());
+    }
+
+    #[test]
+    fn test_find_repo_root_mercurial_and_jujutsu() {
+        let dir = TempDir::new().unwrap();
+        for marker in [".hg", ".jj"] {
+            let repo = dir.path().join(&marker[1..]);
+            fs::create_dir_all(repo.join(marker)).unwrap();
+            fs::create_dir_all(repo.join("src")).unwrap();
+
+            let root = find_repo_root(&repo.join("src")).unwrap();
+            assert_eq!(root, fs::canonicalize(&repo).unwrap());
+        }
+    }
+
     #[test]
     fn test_find_repo_root_fails() {
         // Use a tmpdir with no markers at all.
@@ -740,6 +1628,150 @@ mod tests {
         assert!(result.is_err());
     }
 
+    /// A main checkout at `main/` with a linked worktree at `wt/`, laid out
+    /// the way `git worktree add` leaves them.
+    fn fake_worktree(dir: &Path) -> (PathBuf, PathBuf) {
+        let main = dir.join("main");
+        let wt = dir.join("wt");
+        fs::create_dir_all(main.join(".git/worktrees/wt")).unwrap();
+        fs::write(main.join(".git/worktrees/wt/commondir"), "../..\n").unwrap();
+        fs::create_dir(&wt).unwrap();
+        fs::write(
+            wt.join(".git"),
+            format!("gitdir: {}\n", main.join(".git/worktrees/wt").display()),
+        )
+        .unwrap();
+        (
+            fs::canonicalize(main).unwrap(),
+            fs::canonicalize(wt).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_main_worktree_root() {
+        let dir = TempDir::new().unwrap();
+        let (main, wt) = fake_worktree(dir.path());
+        assert_eq!(main_worktree_root(&wt), Some(main.clone()));
+        assert_eq!(main_worktree_root(&main), None);
+
+        // Submodules point into `.git/modules/`, which has no `commondir`.
+        let sub = main.join("sub");
+        fs::create_dir_all(main.join(".git/modules/sub")).unwrap();
+        fs::create_dir(&sub).unwrap();
+        fs::write(sub.join(".git"), "gitdir: ../.git/modules/sub\n").unwrap();
+        assert_eq!(main_worktree_root(&sub), None);
+    }
+
+    #[test]
+    fn test_find_repo_root_shared_worktree() {
+        let dir = TempDir::new().unwrap();
+        let (main, wt) = fake_worktree(dir.path());
+        assert_eq!(find_repo_root(&wt).unwrap(), wt);
+
+        fs::create_dir(wt.join(".wonk")).unwrap();
+        fs::write(
+            wt.join(".wonk/config.toml"),
+            "[index]\nworktrees = \"shared\"\n",
+        )
+        .unwrap();
+        assert_eq!(find_repo_root(&wt).unwrap(), main);
+        assert_eq!("bogus".parse::<WorktreeIndex>().ok(), None);
+    }
+
+    fn git(root: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(root)
+            .env("GIT_AUTHOR_NAME", "Test")
+            .env("GIT_AUTHOR_EMAIL", "test@test.com")
+            .env("GIT_COMMITTER_NAME", "Test")
+            .env("GIT_COMMITTER_EMAIL", "test@test.com")
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    #[test]
+    fn test_repo_key_canonicalizes_symlinks() {
+        let dir = TempDir::new().unwrap();
+        let repo = dir.path().join("repo");
+        fs::create_dir(&repo).unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&repo, &link).unwrap();
+        assert_eq!(repo_key(&link), repo_key(&repo));
+        assert_eq!(
+            central_index_path(&link).unwrap(),
+            central_index_path(&repo).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_repo_key_by_git_identity() {
+        if std::process::Command::new("git")
+            .arg("--version")
+            .output()
+            .is_err()
+        {
+            return;
+        }
+        let dir = TempDir::new().unwrap();
+        let origin = dir.path().join("origin");
+        fs::create_dir(&origin).unwrap();
+        git(&origin, &["init", "-q"]);
+        fs::write(origin.join("a.rs"), "fn a() {}\n").unwrap();
+        git(&origin, &["add", "."]);
+        git(&origin, &["commit", "-qm", "init"]);
+        git(dir.path(), &["clone", "-q", "origin", "one"]);
+        git(dir.path(), &["clone", "-q", "origin", "two"]);
+        let (one, two) = (dir.path().join("one"), dir.path().join("two"));
+        assert_ne!(repo_key(&one), repo_key(&two), "keyed by path by default");
+
+        for clone in [&one, &two] {
+            fs::create_dir(clone.join(".wonk")).unwrap();
+            fs::write(clone.join(".wonk/config.toml"), "[index]\nkey = \"git\"\n").unwrap();
+        }
+        assert_eq!(repo_key(&one), repo_key(&two));
+        assert!(repo_key(&one).starts_with("git:"));
+        assert!(repo_key(&one).ends_with("/origin"));
+        assert_eq!("inode".parse::<IndexKey>().ok(), None);
+    }
+
+    #[test]
+    fn test_submodule_paths_and_tags() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join(".gitmodules"),
+            "[submodule \"lib\"]\n\tpath = deps/lib\n\turl = ../lib.git\n\
+             [submodule \"gone\"]\n\tpath = deps/gone\n",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("deps/lib")).unwrap();
+        fs::write(root.join("deps/lib/.git"), "gitdir: ../../.git/modules/lib").unwrap();
+        // `deps/gone` is not checked out.
+        assert_eq!(submodule_paths(root), vec!["deps/lib".to_string()]);
+
+        let conn = open(&root.join("index.db")).unwrap();
+        for path in ["src/a.rs", "deps/lib/b.rs", "deps/library.rs"] {
+            conn.execute(
+                "INSERT INTO files (path, hash, last_indexed) VALUES (?1, 'h', 0)",
+                [path],
+            )
+            .unwrap();
+        }
+        tag_submodules(&conn, &submodule_paths(root)).unwrap();
+        let tagged: Vec<String> = conn
+            .prepare("SELECT path FROM files WHERE submodule = 'deps/lib'")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(tagged, vec!["deps/lib/b.rs".to_string()]);
+        assert_eq!(indexed_submodules(&conn), vec!["deps/lib".to_string()]);
+    }
+
     #[test]
     fn test_repo_hash_deterministic() {
         let path = Path::new("/home/user/projects/myrepo");
@@ -771,6 +1803,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_index_generation_counts_bumps() {
+        let dir = TempDir::new().unwrap();
+        let conn = open(&dir.path().join("index.db")).unwrap();
+        assert_eq!(index_generation(&conn), 0);
+        bump_generation(&conn).unwrap();
+        bump_generation(&conn).unwrap();
+        assert_eq!(index_generation(&conn), 2);
+    }
+
     #[test]
     fn test_write_and_read_meta() {
         let dir = TempDir::new().unwrap();
@@ -784,6 +1826,38 @@ mod tests {
         assert_eq!(meta.repo_path, "/fake/repo");
         assert_eq!(meta.languages, vec!["rust", "python"]);
         assert!(meta.created > 0);
+        assert!(meta.disabled_languages.is_empty());
+        assert!(!meta.partial);
+        // Not a git work tree.
+        assert!(meta.commit.is_none());
+    }
+
+    #[test]
+    fn test_read_meta_with_disabled_languages() {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("index.db");
+        fs::write(
+            dir.path().join("meta.json"),
+            r#"{"repo_path":"/r","created":1,"languages":[],"disabled_languages":["PHP"]}"#,
+        )
+        .unwrap();
+
+        let meta = read_meta(&db_path).unwrap();
+        assert_eq!(meta.disabled_languages, vec!["PHP"]);
+        assert!(!meta.partial);
+    }
+
+    #[test]
+    fn test_read_meta_partial() {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("index.db");
+        fs::write(
+            dir.path().join("meta.json"),
+            r#"{"repo_path":"/r","created":1,"languages":[],"partial":true}"#,
+        )
+        .unwrap();
+
+        assert!(read_meta(&db_path).unwrap().partial);
     }
 
     #[test]
@@ -796,6 +1870,15 @@ mod tests {
         let _conn2 = open(&db_path).unwrap();
     }
 
+    #[test]
+    fn test_open_records_schema_version() {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("index.db");
+        Connection::open(&db_path).unwrap();
+        assert_eq!(schema_version(&open_existing(&db_path).unwrap()), 0);
+        assert_eq!(schema_version(&open(&db_path).unwrap()), SCHEMA_VERSION);
+    }
+
     #[test]
     fn test_schema_references_table() {
         let dir = TempDir::new().unwrap();
@@ -1365,6 +2448,111 @@ mod tests {
         assert!(has_caller_id);
     }
 
+    #[test]
+    fn test_ensure_stable_id_column_idempotent() {
+        let dir = TempDir::new().unwrap();
+        let conn = open(&dir.path().join("index.db")).unwrap();
+        ensure_stable_id_column(&conn).unwrap();
+        ensure_stable_id_column(&conn).unwrap();
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('symbols') WHERE name = 'stable_id'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_ensure_is_test_column_idempotent() {
+        let dir = TempDir::new().unwrap();
+        let conn = open(&dir.path().join("index.db")).unwrap();
+        ensure_is_test_column(&conn).unwrap();
+        ensure_is_test_column(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO files (path, hash, last_indexed) VALUES ('a.rs', 'h', 0)",
+            [],
+        )
+        .unwrap();
+        let is_test: i64 = conn
+            .query_row("SELECT is_test FROM files WHERE path = 'a.rs'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(is_test, 0);
+    }
+
+    #[test]
+    fn test_ensure_condition_column_idempotent() {
+        let dir = TempDir::new().unwrap();
+        let conn = open(&dir.path().join("index.db")).unwrap();
+        ensure_condition_column(&conn).unwrap();
+        ensure_condition_column(&conn).unwrap();
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('symbols') WHERE name = 'condition'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_ensure_modifier_columns_idempotent() {
+        let dir = TempDir::new().unwrap();
+        let conn = open(&dir.path().join("index.db")).unwrap();
+        ensure_modifier_columns(&conn).unwrap();
+        ensure_modifier_columns(&conn).unwrap();
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('symbols') \
+                 WHERE name IN ('visibility', 'modifiers')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_ensure_generics_column_idempotent() {
+        let dir = TempDir::new().unwrap();
+        let conn = open(&dir.path().join("index.db")).unwrap();
+        ensure_generics_column(&conn).unwrap();
+        ensure_generics_column(&conn).unwrap();
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('symbols') WHERE name = 'generics'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_ensure_range_columns_idempotent() {
+        let dir = TempDir::new().unwrap();
+        let conn = open(&dir.path().join("index.db")).unwrap();
+        ensure_range_columns(&conn).unwrap();
+        ensure_range_columns(&conn).unwrap();
+        for table in ["symbols", "references"] {
+            let count: i64 = conn
+                .query_row(
+                    &format!(
+                        "SELECT COUNT(*) FROM pragma_table_info('{table}') \
+                         WHERE name IN ('end_col', 'start_byte', 'end_byte')"
+                    ),
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(count, 3, "{table}");
+        }
+    }
+
     #[test]
     fn test_ensure_caller_id_column_idempotent() {
         let dir = TempDir::new().unwrap();
@@ -1862,6 +3050,53 @@ mod tests {
         assert!(indexes.contains(&"idx_references_target_id".to_string()));
     }
 
+    #[test]
+    fn test_list_central_indexes_finds_orphans() {
+        let dir = TempDir::new().unwrap();
+        let repos_dir = dir.path().join("repos");
+        let repo = dir.path().join("project");
+        fs::create_dir_all(repo.join(".git")).unwrap();
+        for (name, repo_path) in [("live", &repo), ("gone", &dir.path().join("deleted"))] {
+            let index_dir = repos_dir.join(name);
+            fs::create_dir_all(&index_dir).unwrap();
+            fs::write(index_dir.join("index.db"), "0123456789").unwrap();
+            write_meta(&index_dir.join("index.db"), repo_path, &[]).unwrap();
+        }
+        fs::create_dir_all(repos_dir.join("stray")).unwrap();
+        // An interrupted build: index.db without meta.json.
+        fs::create_dir_all(repos_dir.join("unread")).unwrap();
+        fs::write(repos_dir.join("unread/index.db"), "0123456789").unwrap();
+
+        let indexes = list_central_indexes(&repos_dir).unwrap();
+        let found: Vec<(String, bool, bool)> = indexes
+            .iter()
+            .map(|i| {
+                let name = i.dir.file_name().unwrap().to_string_lossy().into_owned();
+                (name, i.is_orphaned(), i.is_unknown())
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("gone".to_string(), true, false),
+                ("live".to_string(), false, false),
+                ("unread".to_string(), false, true),
+            ]
+        );
+        assert!(indexes[0].size > 10);
+        assert!(indexes[0].modified.is_some());
+
+        remove_central_index(&repos_dir, &indexes[0]).unwrap();
+        assert!(!repos_dir.join("gone").exists());
+        assert!(remove_central_index(dir.path(), &indexes[1]).is_err());
+        assert!(repos_dir.join("live").exists());
+        assert!(
+            list_central_indexes(&dir.path().join("missing"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
     #[test]
     fn test_ensure_type_edges_table_idempotent() {
         let dir = TempDir::new().unwrap();
@@ -0,0 +1,398 @@
+//! Cancellation for the global `--timeout` flag and Ctrl-C.
+//!
+//! Once a [`start`]ed deadline passes or the user interrupts the process
+//! (see [`cancel_on_interrupt`]), the grep searchers see [`expired`] and stop
+//! between files and matches, and SQLite connections opened through
+//! [`crate::db`] are interrupted.  Index builders only stop for an interrupt
+//! (see [`build_interrupted`]): `--timeout` bounds searches, not builds.  Commands then return
+//! whatever they found so far, and [`timed_out`] / [`interrupted`] tell the
+//! caller to mark the output as partial.  A command that finishes without
+//! stopping for an expired deadline is not marked, however long it took.
+
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use rusqlite::{Connection, InterruptHandle};
+
+/// How often the watchdog re-interrupts connections once cancelled.
+/// `sqlite3_interrupt` only affects statements that are running, so a query
+/// started after the first interrupt needs another one.
+const REINTERRUPT_INTERVAL: Duration = Duration::from_millis(10);
+
+const RUNNING: u8 = 0;
+const TIMED_OUT: u8 = 1;
+const INTERRUPTED: u8 = 2;
+
+/// A cancellation point: an optional deadline plus an explicit cancel.
+pub struct Deadline {
+    at: OnceLock<Instant>,
+    state: AtomicU8,
+    /// Number of live [`Shield`]s; SQL interrupts are held back while
+    /// non-zero.
+    shielded: AtomicUsize,
+    handles: Mutex<Vec<InterruptHandle>>,
+    /// Some work stopped because the token expired (see [`expired`]).
+    stopped: AtomicBool,
+}
+
+impl Deadline {
+    /// Create a token that only expires when [`cancel`](Self::cancel)led.
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            at: OnceLock::new(),
+            state: AtomicU8::new(RUNNING),
+            shielded: AtomicUsize::new(0),
+            handles: Mutex::new(Vec::new()),
+            stopped: AtomicBool::new(false),
+        })
+    }
+
+    /// Create a token that expires `timeout` from now.
+    pub fn arm(timeout: Duration) -> Arc<Self> {
+        let deadline = Self::new();
+        deadline.set_timeout(timeout);
+        deadline
+    }
+
+    /// Expire `timeout` from now.  Only the first call has an effect, and a
+    /// timeout too far out to be represented never expires.
+    fn set_timeout(self: &Arc<Self>, timeout: Duration) {
+        let Some(at) = Instant::now().checked_add(timeout) else {
+            return;
+        };
+        if self.at.set(at).is_ok() {
+            spawn_watchdog(self, timeout);
+        }
+    }
+
+    /// Expire now, recording the cause as an interrupt (also when the
+    /// deadline has already passed).
+    pub fn cancel(self: &Arc<Self>) {
+        if self.state.swap(INTERRUPTED, Ordering::Relaxed) == RUNNING {
+            spawn_watchdog(self, Duration::ZERO);
+        }
+    }
+
+    /// Returns `true` once the deadline has passed or the token was
+    /// cancelled.
+    pub fn expired(&self) -> bool {
+        if self.state.load(Ordering::Relaxed) != RUNNING {
+            return true;
+        }
+        if self.at.get().is_some_and(|at| Instant::now() >= *at) {
+            let _ = self.state.compare_exchange(
+                RUNNING,
+                TIMED_OUT,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            );
+            return true;
+        }
+        false
+    }
+
+    /// Like [`expired`](Self::expired), for a caller that stops its work when
+    /// it returns `true`: that is recorded (see [`cut_short`](Self::cut_short)).
+    pub fn should_stop(&self) -> bool {
+        let expired = self.expired();
+        if expired {
+            self.stopped.store(true, Ordering::Relaxed);
+        }
+        expired
+    }
+
+    /// Like [`should_stop`](Self::should_stop), but only once the token was
+    /// [`cancel`](Self::cancel)led, not for a passed deadline.
+    pub fn should_stop_build(&self) -> bool {
+        self.state.load(Ordering::Relaxed) == INTERRUPTED && self.should_stop()
+    }
+
+    /// Returns `true` once some work stopped for this token (see
+    /// [`should_stop`](Self::should_stop)).
+    pub fn cut_short(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    /// Interrupt `conn`'s statements once the token expires.
+    pub fn watch(&self, conn: &Connection) {
+        if let Ok(mut handles) = self.handles.lock() {
+            handles.push(conn.get_interrupt_handle());
+        }
+    }
+
+    /// Hold back SQL interrupts until the returned guard is dropped.
+    pub fn shield(self: &Arc<Self>) -> Shield {
+        self.shielded.fetch_add(1, Ordering::Relaxed);
+        Shield(Some(Arc::clone(self)))
+    }
+
+    fn interrupt_all(&self) {
+        if self.shielded.load(Ordering::Relaxed) > 0 {
+            return;
+        }
+        if let Ok(handles) = self.handles.lock() {
+            for handle in handles.iter() {
+                handle.interrupt();
+            }
+        }
+    }
+}
+
+/// Spawn a thread that waits `wait`, then keeps interrupting `deadline`'s
+/// connections for as long as it is expired and alive.
+fn spawn_watchdog(deadline: &Arc<Deadline>, wait: Duration) {
+    let watchdog: Weak<Deadline> = Arc::downgrade(deadline);
+    std::thread::spawn(move || {
+        let mut wait = wait;
+        loop {
+            std::thread::sleep(wait);
+            let Some(deadline) = watchdog.upgrade() else {
+                return;
+            };
+            if deadline.expired() {
+                deadline.interrupt_all();
+                wait = REINTERRUPT_INTERVAL;
+            } else {
+                wait = deadline.at.get().map_or(REINTERRUPT_INTERVAL, |at| {
+                    at.saturating_duration_since(Instant::now())
+                });
+            }
+        }
+    });
+}
+
+/// Holds back SQL interrupts while alive, so a write that must not be rolled
+/// back (such as committing an interrupted index build) can finish.
+/// Cooperative [`expired`] checks are unaffected.
+pub struct Shield(Option<Arc<Deadline>>);
+
+impl Drop for Shield {
+    fn drop(&mut self) {
+        if let Some(deadline) = &self.0 {
+            deadline.shielded.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
+
+static GLOBAL: OnceLock<Arc<Deadline>> = OnceLock::new();
+
+fn global() -> &'static Arc<Deadline> {
+    GLOBAL.get_or_init(Deadline::new)
+}
+
+/// Start the process-wide deadline.  Only the first call has an effect.
+pub fn start(timeout: Duration) {
+    global().set_timeout(timeout);
+}
+
+/// Cancel the process-wide token on the first SIGINT/SIGTERM instead of
+/// dying mid-write; a second signal exits immediately with status 130.
+pub fn cancel_on_interrupt() -> Result<()> {
+    use signal_hook::consts::{SIGINT, SIGTERM};
+
+    let deadline = Arc::clone(global());
+    let mut signals =
+        signal_hook::iterator::Signals::new([SIGINT, SIGTERM]).context("registering SIGINT")?;
+    std::thread::spawn(move || {
+        for _ in signals.forever() {
+            if deadline.state.load(Ordering::Relaxed) == INTERRUPTED {
+                std::process::exit(crate::errors::EXIT_INTERRUPTED);
+            }
+            deadline.cancel();
+        }
+    });
+    Ok(())
+}
+
+/// Returns `true` once the process-wide deadline has passed or the process
+/// was interrupted.  Callers stop their work when it does, so this also
+/// records that the results are partial (see [`cut_short`]).
+pub fn expired() -> bool {
+    GLOBAL.get().is_some_and(|d| d.should_stop())
+}
+
+/// Like [`expired`], for index builds: only returns `true` once the process
+/// was interrupted, so `--timeout` never leaves a partial index.
+pub fn build_interrupted() -> bool {
+    GLOBAL.get().is_some_and(|d| d.should_stop_build())
+}
+
+/// Returns `true` when some work stopped because the process-wide token
+/// expired (see [`expired`]), i.e. results are partial.
+pub fn cut_short() -> bool {
+    GLOBAL.get().is_some_and(|d| d.cut_short())
+}
+
+/// Returns `true` when work stopped because the process-wide deadline
+/// passed, i.e. results may be partial.
+pub fn timed_out() -> bool {
+    cut_short()
+        && GLOBAL
+            .get()
+            .is_some_and(|d| d.state.load(Ordering::Relaxed) == TIMED_OUT)
+}
+
+/// Returns `true` when `err` is, or was caused by, a SQLite statement
+/// interrupted by an expired token.
+pub fn is_interrupt(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause.downcast_ref::<rusqlite::Error>().is_some_and(|e| {
+            e.sqlite_error_code() == Some(rusqlite::ErrorCode::OperationInterrupted)
+        })
+    })
+}
+
+/// Returns `true` when the process was interrupted, i.e. results may be
+/// partial.
+pub fn interrupted() -> bool {
+    GLOBAL
+        .get()
+        .is_some_and(|d| d.state.load(Ordering::Relaxed) == INTERRUPTED)
+}
+
+/// Hold back process-wide SQL interrupts until the returned guard is
+/// dropped.
+pub fn shield() -> Shield {
+    GLOBAL.get().map_or(Shield(None), |d| d.shield())
+}
+
+/// Register `conn` for interruption when the process-wide token expires.
+pub fn watch(conn: &Connection) {
+    if let Some(deadline) = GLOBAL.get() {
+        deadline.watch(conn);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs an unbounded recursive query that only stops when interrupted.
+    fn run_forever(conn: &Connection) -> rusqlite::Result<i64> {
+        conn.query_row(
+            "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n) \
+             SELECT MAX(x) FROM n",
+            [],
+            |row| row.get(0),
+        )
+    }
+
+    #[test]
+    fn zero_timeout_is_expired() {
+        let deadline = Deadline::arm(Duration::ZERO);
+        assert!(deadline.expired());
+        let later = Deadline::arm(Duration::from_secs(3600));
+        assert!(!later.expired());
+        assert!(!Deadline::arm(Duration::MAX).expired());
+        assert!(!Deadline::new().expired());
+    }
+
+    #[test]
+    fn expiry_only_cuts_short_work_that_stops() {
+        let deadline = Deadline::arm(Duration::ZERO);
+        // The watchdog noticing the deadline does not make results partial.
+        assert!(deadline.expired());
+        assert!(!deadline.cut_short());
+        assert!(deadline.should_stop());
+        assert!(deadline.cut_short());
+
+        let running = Deadline::new();
+        assert!(!running.should_stop());
+        assert!(!running.cut_short());
+    }
+
+    #[test]
+    fn builds_only_stop_when_cancelled() {
+        let deadline = Deadline::arm(Duration::ZERO);
+        assert!(deadline.expired());
+        assert!(!deadline.should_stop_build());
+        assert!(!deadline.cut_short());
+
+        // An interrupt after the deadline passed still stops the build.
+        deadline.cancel();
+        assert!(deadline.should_stop_build());
+        assert!(deadline.cut_short());
+        assert_eq!(deadline.state.load(Ordering::Relaxed), INTERRUPTED);
+    }
+
+    #[test]
+    fn interrupted_statements_are_recognised_through_context() {
+        let conn = Connection::open_in_memory().unwrap();
+        let deadline = Deadline::arm(Duration::ZERO);
+        deadline.watch(&conn);
+        std::thread::sleep(Duration::from_millis(50));
+        let err = anyhow::Error::from(crate::errors::DbError::from(
+            run_forever(&conn).unwrap_err(),
+        ))
+        .context("looking up symbols");
+        assert!(is_interrupt(&err));
+        assert!(!is_interrupt(&anyhow::anyhow!(
+            "database disk image is malformed"
+        )));
+    }
+
+    #[test]
+    fn watched_connection_is_interrupted_mid_query() {
+        let conn = Connection::open_in_memory().unwrap();
+        let deadline = Deadline::arm(Duration::from_millis(50));
+        deadline.watch(&conn);
+
+        assert_eq!(
+            run_forever(&conn).unwrap_err().sqlite_error_code(),
+            Some(rusqlite::ErrorCode::OperationInterrupted)
+        );
+        assert!(deadline.expired());
+    }
+
+    #[test]
+    fn query_started_after_expiry_is_interrupted() {
+        let conn = Connection::open_in_memory().unwrap();
+        let deadline = Deadline::arm(Duration::ZERO);
+        deadline.watch(&conn);
+        std::thread::sleep(Duration::from_millis(30));
+
+        assert_eq!(
+            run_forever(&conn).unwrap_err().sqlite_error_code(),
+            Some(rusqlite::ErrorCode::OperationInterrupted)
+        );
+    }
+
+    #[test]
+    fn cancel_expires_and_interrupts() {
+        let conn = Connection::open_in_memory().unwrap();
+        let deadline = Deadline::new();
+        deadline.watch(&conn);
+        let canceller = Arc::clone(&deadline);
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(30));
+            canceller.cancel();
+        });
+
+        assert!(run_forever(&conn).is_err());
+        assert!(deadline.expired());
+        assert_eq!(deadline.state.load(Ordering::Relaxed), INTERRUPTED);
+    }
+
+    #[test]
+    fn shield_holds_back_interrupts() {
+        let conn = Connection::open_in_memory().unwrap();
+        let deadline = Deadline::arm(Duration::ZERO);
+        deadline.watch(&conn);
+        let shield = deadline.shield();
+        std::thread::sleep(Duration::from_millis(30));
+        let count: i64 = conn
+            .query_row(
+                "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n WHERE x < 200000) \
+                 SELECT COUNT(*) FROM n",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 200000);
+
+        drop(shield);
+        assert!(run_forever(&conn).is_err());
+    }
+}
@@ -0,0 +1,162 @@
+//! Compact per-file descriptions for `wonk describe`.
+//!
+//! Everything comes from the index: the `files` row gives the language, line
+//! count, and test/generated flags, `file_imports` the imports, and the
+//! file's top-level symbols the exports and the most-used definitions.  The
+//! result is small enough for an agent to triage a file without reading it.
+
+use std::path::Path;
+
+use anyhow::Result;
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::output::{DescribeOutput, DescribeSymbolOutput};
+use crate::router;
+
+/// Describe the indexed file `path` (repo-relative), listing at most
+/// `limit` symbols.  Returns `None` when the file is not indexed.
+pub fn describe_file(
+    conn: &Connection,
+    path: &str,
+    limit: usize,
+) -> Result<Option<DescribeOutput>> {
+    let path = path.trim_start_matches("./");
+    let row = conn
+        .query_row(
+            "SELECT COALESCE(language, ''), COALESCE(line_count, 0), is_test, generated_by \
+             FROM files WHERE path = ?1",
+            rusqlite::params![path],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)? as usize,
+                    row.get::<_, bool>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                ))
+            },
+        )
+        .optional()?;
+    let Some((language, lines, marked_test, generated_by)) = row else {
+        return Ok(None);
+    };
+
+    let role = if marked_test || crate::ranker::is_test_file(Path::new(path)) {
+        "test"
+    } else if generated_by.is_some() {
+        "generated"
+    } else {
+        "source"
+    };
+
+    let symbol_count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM symbols WHERE file = ?1",
+        rusqlite::params![path],
+        |row| row.get(0),
+    )?;
+
+    let mut stmt = conn.prepare_cached(
+        "SELECT name FROM symbols \
+         WHERE file = ?1 AND scope IS NULL AND visibility = 'public' ORDER BY line",
+    )?;
+    let mut exports = stmt
+        .query_map(rusqlite::params![path], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    exports.dedup();
+
+    // Top-level definitions, most referenced from other files first.
+    let mut stmt = conn.prepare_cached(
+        "SELECT s.name, s.kind, s.line, COALESCE(s.signature, ''), \
+         (SELECT COUNT(*) FROM \"references\" r WHERE r.name = s.name AND r.file != s.file) AS uses \
+         FROM symbols s WHERE s.file = ?1 AND s.scope IS NULL AND s.kind != 'variable' \
+         ORDER BY uses DESC, s.line LIMIT ?2",
+    )?;
+    let symbols = stmt
+        .query_map(rusqlite::params![path, limit as i64], |row| {
+            Ok(DescribeSymbolOutput {
+                name: row.get(0)?,
+                kind: row.get(1)?,
+                line: row.get::<_, i64>(2)? as usize,
+                signature: row.get(3)?,
+                uses: row.get::<_, i64>(4)? as usize,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(Some(DescribeOutput {
+        file: path.to_string(),
+        language,
+        lines,
+        role: role.to_string(),
+        generated_by,
+        imports: router::query_deps_db(conn, path)?,
+        exports,
+        symbol_count: symbol_count as usize,
+        symbols,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use tempfile::TempDir;
+
+    fn fixture() -> (TempDir, Connection) {
+        let dir = TempDir::new().unwrap();
+        let conn = db::open(&dir.path().join("index.db")).unwrap();
+        conn.execute_batch(
+            "INSERT INTO files (path, language, hash, last_indexed, line_count) \
+                 VALUES ('src/store.rs', 'Rust', 'h', 0, 120); \
+             INSERT INTO files (path, language, hash, last_indexed, line_count, generated_by) \
+                 VALUES ('src/api.pb.rs', 'Rust', 'h', 0, 900, 'protoc'); \
+             INSERT INTO symbols (name, kind, file, line, col, signature, language, visibility) \
+                 VALUES ('Store', 'struct', 'src/store.rs', 3, 0, 'pub struct Store', 'Rust', 'public'); \
+             INSERT INTO symbols (name, kind, file, line, col, signature, language, visibility) \
+                 VALUES ('open', 'function', 'src/store.rs', 10, 0, 'pub fn open() -> Store', 'Rust', 'public'); \
+             INSERT INTO symbols (name, kind, file, line, col, signature, language, visibility) \
+                 VALUES ('checksum', 'function', 'src/store.rs', 40, 0, 'fn checksum()', 'Rust', 'private'); \
+             INSERT INTO symbols (name, kind, file, line, col, scope, signature, language, visibility) \
+                 VALUES ('get', 'method', 'src/store.rs', 5, 4, 'Store', 'pub fn get()', 'Rust', 'public'); \
+             INSERT INTO \"references\" (name, file, line, col) VALUES ('open', 'src/main.rs', 2, 0); \
+             INSERT INTO \"references\" (name, file, line, col) VALUES ('open', 'src/cli.rs', 8, 0); \
+             INSERT INTO \"references\" (name, file, line, col) VALUES ('checksum', 'src/store.rs', 12, 0); \
+             INSERT INTO file_imports (source_file, import_path) VALUES ('src/store.rs', 'std::fs');",
+        )
+        .unwrap();
+        (dir, conn)
+    }
+
+    #[test]
+    fn describe_file_summarizes_index_facts() {
+        let (_dir, conn) = fixture();
+        let out = describe_file(&conn, "./src/store.rs", 10).unwrap().unwrap();
+        assert_eq!(out.file, "src/store.rs");
+        assert_eq!(out.language, "Rust");
+        assert_eq!(out.lines, 120);
+        assert_eq!(out.role, "source");
+        assert_eq!(out.imports, ["std::fs"]);
+        assert_eq!(out.exports, ["Store", "open"]);
+        assert_eq!(out.symbol_count, 4);
+        let names: Vec<&str> = out.symbols.iter().map(|s| s.name.as_str()).collect();
+        // Used from other files first; same-file uses do not count.
+        assert_eq!(names, ["open", "Store", "checksum"]);
+        assert_eq!(out.symbols[0].uses, 2);
+    }
+
+    #[test]
+    fn describe_file_limits_symbols_and_detects_role() {
+        let (_dir, conn) = fixture();
+        let out = describe_file(&conn, "src/store.rs", 1).unwrap().unwrap();
+        assert_eq!(out.symbols.len(), 1);
+
+        let out = describe_file(&conn, "src/api.pb.rs", 10).unwrap().unwrap();
+        assert_eq!(out.role, "generated");
+        assert_eq!(out.generated_by.as_deref(), Some("protoc"));
+
+        assert!(
+            describe_file(&conn, "src/missing.rs", 10)
+                .unwrap()
+                .is_none()
+        );
+    }
+}
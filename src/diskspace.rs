@@ -0,0 +1,208 @@
+//! Disk space guard for index builds.
+//!
+//! Before a full build, [`check`] estimates the index size from the total
+//! size of the files about to be parsed and refuses to start when the
+//! filesystem holding the index lacks headroom (`--skip-space-check`
+//! overrides, see [`skip_checks`]).  When the disk fills up anyway,
+//! [`is_disk_full`] recognises the SQLite / OS error so the build can remove
+//! its partial database ([`remove_index`]) and report a clear error.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::errors::DiskSpaceError;
+
+/// Index bytes per byte of source, measured on mixed-language repositories
+/// (symbols, references, FTS, and edges; embeddings excluded).
+const INDEX_BYTES_PER_SOURCE_BYTE: u64 = 4;
+
+/// Fixed SQLite overhead (schema, FTS tables, page slack).
+const INDEX_BASE_BYTES: u64 = 1024 * 1024;
+
+static SKIP: AtomicBool = AtomicBool::new(false);
+
+/// Disable [`check`] for the rest of the process (`--skip-space-check`).
+pub fn skip_checks() {
+    SKIP.store(true, Ordering::Relaxed);
+}
+
+/// Estimated size of an index built from `source_bytes` of source files.
+pub fn estimate_index_bytes(source_bytes: u64) -> u64 {
+    source_bytes * INDEX_BYTES_PER_SOURCE_BYTE + INDEX_BASE_BYTES
+}
+
+/// Bytes the build needs free: the estimate plus half again for the WAL and
+/// SQLite's temporary b-trees while indexes are created.
+pub fn required_bytes(estimate: u64) -> u64 {
+    estimate + estimate / 2
+}
+
+/// Bytes available to unprivileged users on the filesystem holding `path`
+/// (or its nearest existing ancestor), or `None` when it cannot be read.
+pub fn available_bytes(path: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let existing = path.ancestors().find(|p| p.exists())?;
+    let c_path = std::ffi::CString::new(existing.as_os_str().as_bytes()).ok()?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `c_path` is a valid NUL-terminated string and `stat` points to
+    // writable memory of the right size; it is only read after success.
+    let stat = unsafe {
+        if libc::statvfs(c_path.as_ptr(), stat.as_mut_ptr()) != 0 {
+            return None;
+        }
+        stat.assume_init()
+    };
+    // The field types vary by platform (u32 on some BSDs).
+    #[allow(clippy::useless_conversion)]
+    Some(u64::from(stat.f_bavail).saturating_mul(u64::from(stat.f_frsize)))
+}
+
+/// Size of the index at `index_path` including its WAL; a rebuild reuses
+/// this space.
+fn existing_index_bytes(index_path: &Path) -> u64 {
+    index_files(index_path)
+        .iter()
+        .filter_map(|p| std::fs::metadata(p).ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Refuse to build an index at `index_path` from `source_bytes` of source
+/// when its filesystem lacks headroom.  Passes when checks are skipped or
+/// free space cannot be determined.
+pub fn check(index_path: &Path, source_bytes: u64) -> Result<(), DiskSpaceError> {
+    if SKIP.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+    let dir = index_path.parent().unwrap_or(index_path);
+    let Some(free) = available_bytes(dir) else {
+        return Ok(());
+    };
+    check_headroom(
+        dir,
+        required_bytes(estimate_index_bytes(source_bytes)),
+        free + existing_index_bytes(index_path),
+    )
+}
+
+fn check_headroom(dir: &Path, needed: u64, available: u64) -> Result<(), DiskSpaceError> {
+    if available >= needed {
+        return Ok(());
+    }
+    Err(DiskSpaceError::Insufficient {
+        dir: dir.display().to_string(),
+        needed_mib: needed.div_ceil(1024 * 1024),
+        available_mib: available / (1024 * 1024),
+    })
+}
+
+/// Returns `true` when `err` was caused by a full disk (`SQLITE_FULL` or
+/// `ENOSPC`) anywhere in its chain.
+pub fn is_disk_full(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        if let Some(e) = cause.downcast_ref::<rusqlite::Error>() {
+            return e.sqlite_error_code() == Some(rusqlite::ErrorCode::DiskFull);
+        }
+        if let Some(e) = cause.downcast_ref::<std::io::Error>() {
+            return e.kind() == std::io::ErrorKind::StorageFull
+                || e.raw_os_error() == Some(libc::ENOSPC);
+        }
+        false
+    })
+}
+
+/// The database, WAL, shared-memory, and meta files of an index.
+fn index_files(index_path: &Path) -> Vec<std::path::PathBuf> {
+    let mut files: Vec<_> = ["", "-wal", "-shm"]
+        .iter()
+        .map(|suffix| {
+            let mut name = index_path.as_os_str().to_owned();
+            name.push(suffix);
+            std::path::PathBuf::from(name)
+        })
+        .collect();
+    if let Some(dir) = index_path.parent() {
+        files.push(dir.join("meta.json"));
+    }
+    files
+}
+
+/// Delete the index at `index_path` and its side files, ignoring files that
+/// do not exist.
+pub fn remove_index(index_path: &Path) {
+    for file in index_files(index_path) {
+        let _ = std::fs::remove_file(file);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn estimate_scales_with_source() {
+        assert_eq!(estimate_index_bytes(0), INDEX_BASE_BYTES);
+        assert_eq!(
+            estimate_index_bytes(10 * 1024 * 1024),
+            40 * 1024 * 1024 + INDEX_BASE_BYTES
+        );
+        assert_eq!(required_bytes(100), 150);
+    }
+
+    #[test]
+    fn headroom_check_reports_sizes() {
+        let dir = Path::new("/idx");
+        assert!(check_headroom(dir, 100, 100).is_ok());
+
+        let err = check_headroom(dir, 300 * 1024 * 1024 + 1, 120 * 1024 * 1024).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("/idx"), "{msg}");
+        assert!(msg.contains("about 301 MiB"), "{msg}");
+        assert!(msg.contains("120 MiB available"), "{msg}");
+        assert!(msg.contains("--skip-space-check"), "{msg}");
+    }
+
+    #[test]
+    fn available_bytes_uses_nearest_existing_ancestor() {
+        let dir = TempDir::new().unwrap();
+        let missing = dir.path().join("a/b/index.db");
+        assert!(available_bytes(&missing).is_some());
+        assert!(available_bytes(Path::new("")).is_none());
+    }
+
+    #[test]
+    fn disk_full_is_recognised_in_error_chains() {
+        let sqlite = rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_FULL),
+            None,
+        );
+        assert!(is_disk_full(
+            &anyhow::Error::from(sqlite).context("inserting symbols")
+        ));
+        let io = std::io::Error::from_raw_os_error(libc::ENOSPC);
+        assert!(is_disk_full(
+            &anyhow::Error::from(io).context("writing meta")
+        ));
+        assert!(!is_disk_full(&anyhow::anyhow!("something else")));
+    }
+
+    #[test]
+    fn remove_index_deletes_database_and_side_files() {
+        let dir = TempDir::new().unwrap();
+        let index = dir.path().join("index.db");
+        for name in ["index.db", "index.db-wal", "index.db-shm", "meta.json"] {
+            std::fs::write(dir.path().join(name), "x").unwrap();
+        }
+        std::fs::write(dir.path().join("daemon.pid"), "1").unwrap();
+        assert_eq!(existing_index_bytes(&index), 4);
+
+        remove_index(&index);
+        let left: Vec<String> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(left, vec!["daemon.pid"]);
+    }
+}
@@ -405,10 +405,17 @@ fn map_symbol_row(row: &rusqlite::Row) -> rusqlite::Result<SymbolRow> {
             line,
             col,
             end_line,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             scope,
             signature,
             language,
             doc_comment: None,
+            condition: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
         },
     })
 }
@@ -1293,10 +1300,17 @@ mod tests {
             line,
             col: 0,
             end_line,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             scope: scope.map(|s| s.to_string()),
             signature: format!("fn {name}()"),
             language: "Rust".to_string(),
             doc_comment: None,
+            condition: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
         }
     }
 
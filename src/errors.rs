@@ -4,6 +4,8 @@
 //! - [`DbError`] for database/index errors (enables fallback decisions)
 //! - [`SearchError`] for grep-based search failures
 //! - [`EmbeddingError`] for embedding / semantic-search failures
+//! - [`DiskSpaceError`] for index builds that run out of disk space
+//! - [`AccessError`] for indexes that belong to another user
 //! - [`WonkError`] as the unified top-level error type
 //!
 //! The [`WonkError`] type carries contextual hints and exit codes so that
@@ -21,9 +23,15 @@ use thiserror::Error;
 /// * `0` - success
 /// * `1` - general runtime error
 /// * `2` - usage / argument error (bad CLI invocation)
+/// * `3` - results may be incomplete (`--strict` only)
 pub const EXIT_SUCCESS: i32 = 0;
 pub const EXIT_ERROR: i32 = 1;
 pub const EXIT_USAGE: i32 = 2;
+/// Exit status under `--strict` when the results may be incomplete (grep
+/// fallback or a partial index).
+pub const EXIT_INCOMPLETE: i32 = 3;
+/// Exit status after Ctrl-C / SIGTERM (128 + SIGINT).
+pub const EXIT_INTERRUPTED: i32 = 130;
 
 // ---------------------------------------------------------------------------
 // Layer-specific error types
@@ -105,6 +113,49 @@ pub enum LlmError {
     QueryFailed(String),
 }
 
+/// Errors from the disk space guard around index builds.
+#[derive(Error, Debug)]
+pub enum DiskSpaceError {
+    /// The filesystem holding the index lacks room for the estimated index.
+    #[error(
+        "not enough disk space for the index in {dir}: need about {needed_mib} MiB, \
+         {available_mib} MiB available (free some space, exclude large directories \
+         with [ignore].patterns, or pass --skip-space-check to build anyway)"
+    )]
+    Insufficient {
+        dir: String,
+        needed_mib: u64,
+        available_mib: u64,
+    },
+
+    /// The disk filled up during a full build; the partial index was removed.
+    #[error(
+        "disk full while building the index in {dir}; removed the partial index \
+         (free some space and run `wonk init` again)"
+    )]
+    FullDuringBuild { dir: String },
+
+    /// The disk filled up during an incremental update; the interrupted
+    /// transaction was rolled back and the index is unchanged.
+    #[error(
+        "disk full while updating the index in {dir}; the index was left as it was \
+         (free some space and run `wonk update` again)"
+    )]
+    FullDuringUpdate { dir: String },
+}
+
+/// Errors from the access checks on index storage.
+#[derive(Error, Debug)]
+pub enum AccessError {
+    /// The index belongs to another user; opening it would leave files its
+    /// owner cannot write.
+    #[error(
+        "the index at {path} belongs to another user (uid {owner}); refusing to open it \
+         (run `wonk init` to build your own index, or set HOME to a directory you own)"
+    )]
+    ForeignIndex { path: String, owner: u32 },
+}
+
 // ---------------------------------------------------------------------------
 // Unified application error
 // ---------------------------------------------------------------------------
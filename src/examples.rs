@@ -0,0 +1,544 @@
+//! Usage examples for `--help` and `wonk examples`.
+//!
+//! [`COMMAND_EXAMPLES`] is appended to each subcommand's `--help` (see
+//! [`crate::cli::command`]) and [`RECIPES`] are the task-oriented walkthroughs
+//! printed by `wonk examples [topic]`.  Every command line is parsed by the
+//! tests, so the examples cannot drift from the CLI.
+
+/// One command line and what it does.
+#[derive(Debug)]
+pub struct Example {
+    pub command: &'static str,
+    pub description: &'static str,
+}
+
+/// A sequence of commands that accomplishes a common task.
+#[derive(Debug)]
+pub struct Recipe {
+    /// Short name used to select the recipe (`wonk examples <topic>`).
+    pub topic: &'static str,
+    pub title: &'static str,
+    pub steps: &'static [Example],
+}
+
+const fn ex(command: &'static str, description: &'static str) -> Example {
+    Example {
+        command,
+        description,
+    }
+}
+
+/// Task-oriented recipes, in the order `wonk examples` lists them.
+pub const RECIPES: &[Recipe] = &[
+    Recipe {
+        topic: "find",
+        title: "Find where something is defined",
+        steps: &[
+            ex(
+                "wonk sym Config --kind struct",
+                "Definitions by name and kind",
+            ),
+            ex(
+                "wonk search \"retry budget\"",
+                "Ranked text search: definitions first, then call sites, then the rest",
+            ),
+            ex(
+                "wonk ask \"where are retries configured\"",
+                "Semantic search by meaning (needs Ollama)",
+            ),
+        ],
+    },
+    Recipe {
+        topic: "callers",
+        title: "Find who calls a function",
+        steps: &[
+            ex("wonk callers parse_config", "Direct callers"),
+            ex(
+                "wonk callers parse_config --depth 3",
+                "Callers of callers, up to three levels",
+            ),
+            ex(
+                "wonk callpath main parse_config",
+                "One call chain from an entry point to the function",
+            ),
+            ex(
+                "wonk ref parse_config --output files",
+                "Every file that mentions it, including non-call uses",
+            ),
+        ],
+    },
+    Recipe {
+        topic: "context",
+        title: "Build a context pack for a symbol",
+        steps: &[
+            ex(
+                "wonk context parse_config --budget 4000 --format json",
+                "Definition, callers, callees, importers, and flows in one bounded document",
+            ),
+            ex("wonk show parse_config", "Full source of the symbol"),
+            ex(
+                "wonk summary src/config --detail rich",
+                "Outline of the surrounding module",
+            ),
+            ex(
+                "wonk blast parse_config --depth 2",
+                "Symbols affected if it changes",
+            ),
+        ],
+    },
+    Recipe {
+        topic: "review",
+        title: "Review a change before committing",
+        steps: &[
+            ex(
+                "wonk changes --blast",
+                "Changed symbols and their blast radius",
+            ),
+            ex(
+                "wonk changes --scope compare --base main --flows",
+                "Execution flows touched by the current branch",
+            ),
+            ex(
+                "wonk impact src/config.rs",
+                "Semantically similar code that may need the same change (needs Ollama)",
+            ),
+        ],
+    },
+    Recipe {
+        topic: "ci",
+        title: "Check index staleness in CI",
+        steps: &[
+            ex(
+                "wonk update --skip-embed -q",
+                "Bring the index up to date without calling Ollama",
+            ),
+            ex(
+                "wonk status --format json",
+                "File and symbol counts; `partial` is true after an interrupted build",
+            ),
+            ex(
+                "wonk ref parse_config --verify --format json | grep -q '\"freshness\":\"stale\"' && exit 1",
+                "Fail the job when indexed results no longer match the files on disk",
+            ),
+        ],
+    },
+];
+
+/// Examples appended to each subcommand's `--help`, keyed by subcommand name.
+pub const COMMAND_EXAMPLES: &[(&str, &[Example])] = &[
+    (
+        "search",
+        &[
+            ex(
+                "wonk search parse_config",
+                "Ranked search for an identifier",
+            ),
+            ex(
+                "wonk search --regex \"fn \\w+_config\" -- src",
+                "Regular expression, limited to src/",
+            ),
+            ex(
+                "wonk search -e TODO -e FIXME --raw",
+                "Several patterns in one unranked pass",
+            ),
+            ex(
+                "wonk search log --cluster dir",
+                "Hit counts per directory with the top hits of each",
+            ),
+            ex(
+                "wonk search timeout --recent",
+                "Hits in recently changed files first",
+            ),
+            ex(
+                "wonk search Client --boost 'src/core/**'",
+                "Hits in your own code ahead of vendored copies",
+            ),
+        ],
+    ),
+    (
+        "sym",
+        &[
+            ex("wonk sym Config --kind struct", "Structs named like Config"),
+            ex(
+                "wonk sym parse --modifier async --exact",
+                "Async symbols named exactly parse",
+            ),
+            ex(
+                "wonk sym --stdin-names --kind function",
+                "Functions for each name read from stdin, one per line",
+            ),
+        ],
+    ),
+    (
+        "ref",
+        &[
+            ex("wonk ref parse_config", "Every reference to a symbol"),
+            ex(
+                "wonk ref log --sample 30",
+                "30 references to log spread across the tree",
+            ),
+            ex(
+                "wonk ref parse_config --resolve -- src",
+                "References under src/ with their likely definitions",
+            ),
+            ex(
+                "wonk ref Invoice --cwd-scope",
+                "References under the current directory only",
+            ),
+        ],
+    ),
+    (
+        "sig",
+        &[ex(
+            "wonk sig parse_config",
+            "Signatures of every parse_config",
+        )],
+    ),
+    (
+        "deps",
+        &[ex("wonk deps src/main.rs", "Files imported by src/main.rs")],
+    ),
+    (
+        "rdeps",
+        &[ex(
+            "wonk rdeps src/config.rs",
+            "Files that import src/config.rs",
+        )],
+    ),
+    (
+        "init",
+        &[
+            ex(
+                "wonk init --local",
+                "Index the repository into .wonk/ instead of the shared cache",
+            ),
+            ex(
+                "wonk init --profile",
+                "Rebuild and list the slowest languages and files to parse",
+            ),
+        ],
+    ),
+    (
+        "update",
+        &[
+            ex("wonk update", "Re-index changed files"),
+            ex(
+                "wonk update --force --skip-embed",
+                "Full structural rebuild without embeddings",
+            ),
+        ],
+    ),
+    (
+        "which-index",
+        &[ex(
+            "wonk which-index",
+            "Which index queries read, and the commit it was built from",
+        )],
+    ),
+    (
+        "callers",
+        &[
+            ex("wonk callers parse_config", "Direct callers"),
+            ex(
+                "wonk callers parse_config --depth 3 --min-confidence 0.8",
+                "Three levels of callers, confident edges only",
+            ),
+        ],
+    ),
+    (
+        "callees",
+        &[ex(
+            "wonk callees main --depth 2",
+            "What main calls, two levels deep",
+        )],
+    ),
+    (
+        "callpath",
+        &[ex(
+            "wonk callpath main parse_config",
+            "A call chain from main to parse_config",
+        )],
+    ),
+    (
+        "show",
+        &[
+            ex("wonk show parse_config", "Full source of a symbol"),
+            ex(
+                "wonk show Config --shallow",
+                "A type's signature and member signatures, without bodies",
+            ),
+        ],
+    ),
+    (
+        "summary",
+        &[ex(
+            "wonk summary src --depth 1",
+            "Metrics for src/ and each direct subdirectory",
+        )],
+    ),
+    (
+        "describe",
+        &[ex(
+            "wonk describe src/main.rs --format json",
+            "Language, role, imports, exports, and top symbols of a file",
+        )],
+    ),
+    (
+        "near",
+        &[ex(
+            "wonk near src/main.rs:42 --radius 2",
+            "Enclosing symbol, siblings, and references around a line",
+        )],
+    ),
+    (
+        "flows",
+        &[
+            ex("wonk flows", "Detected entry points"),
+            ex("wonk flows main --depth 5", "Execution flow from main"),
+        ],
+    ),
+    (
+        "blast",
+        &[ex(
+            "wonk blast parse_config --depth 2",
+            "Symbols affected by changing parse_config",
+        )],
+    ),
+    (
+        "changes",
+        &[
+            ex("wonk changes --blast", "Unstaged changes with blast radius"),
+            ex(
+                "wonk changes --scope compare --base main --flows",
+                "Flows touched by the current branch",
+            ),
+        ],
+    ),
+    (
+        "context",
+        &[ex(
+            "wonk context parse_config --budget 4000 --format json",
+            "Everything about a symbol in one bounded document",
+        )],
+    ),
+    (
+        "ask",
+        &[ex(
+            "wonk ask \"where are retries configured\" --from src/main.rs",
+            "Semantic search limited to code reachable from src/main.rs",
+        )],
+    ),
+    (
+        "report",
+        &[ex("wonk report unsafe src", "Unsafe code under src/")],
+    ),
+    (
+        "snapshot",
+        &[ex(
+            "wonk snapshot deprecated -- ref old_api",
+            "Record every use of old_api as .wonk/snapshots/deprecated.json",
+        )],
+    ),
+    (
+        "compare",
+        &[ex(
+            "wonk compare deprecated --ignore-positions --check",
+            "Fail CI when new uses of old_api appear since the snapshot",
+        )],
+    ),
+    (
+        "gate",
+        &[
+            ex("wonk gate", "Run the [gate] checks from .wonk/config.toml"),
+            ex(
+                "wonk gate --format github",
+                "Annotate new findings inline on a pull request",
+            ),
+            ex(
+                "wonk gate --update-baseline",
+                "Accept the current findings as the new baseline",
+            ),
+        ],
+    ),
+    (
+        "tree-dump",
+        &[
+            ex(
+                "wonk tree-dump src/lib.rs --kind call_expression",
+                "Every call expression and its subtree, with ranges",
+            ),
+            ex(
+                "wonk tree-dump --lang py --sexp -",
+                "S-expression of a snippet piped on stdin",
+            ),
+        ],
+    ),
+    (
+        "schema",
+        &[
+            ex(
+                "wonk schema",
+                "One JSON Schema document covering every JSON output record",
+            ),
+            ex(
+                "wonk schema SymbolOutput",
+                "The schema of one record type, e.g. for `wonk sym --format json`",
+            ),
+        ],
+    ),
+    (
+        "parse",
+        &[
+            ex(
+                "wonk parse --lang rust -",
+                "Extract symbols and references from source piped on stdin",
+            ),
+            ex(
+                "wonk parse src/lib.rs --format json",
+                "One JSON record of what the indexer sees in a file",
+            ),
+        ],
+    ),
+];
+
+/// Examples for subcommand `name`, empty when it has none.
+pub fn for_command(name: &str) -> &'static [Example] {
+    COMMAND_EXAMPLES
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map_or(&[], |(_, examples)| examples)
+}
+
+/// Look up a recipe by topic, accepting an unambiguous prefix.
+pub fn find_recipe(topic: &str) -> Option<&'static Recipe> {
+    let topic = topic.to_lowercase();
+    if let Some(recipe) = RECIPES.iter().find(|r| r.topic == topic) {
+        return Some(recipe);
+    }
+    let mut matches = RECIPES.iter().filter(|r| r.topic.starts_with(&topic));
+    match (matches.next(), matches.next()) {
+        (Some(recipe), None) => Some(recipe),
+        _ => None,
+    }
+}
+
+/// Render examples as indented `# description` / command pairs.
+pub fn render(examples: &[Example]) -> String {
+    examples
+        .iter()
+        .map(|e| format!("  # {}\n  {}\n", e.description, e.command))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// After-help text for subcommand `name`, or `None` when it has no examples.
+pub fn after_help(name: &str) -> Option<String> {
+    let examples = for_command(name);
+    (!examples.is_empty()).then(|| format!("Examples:\n{}", render(examples)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Cli;
+    use clap::Parser;
+
+    /// Split a shell command line into arguments, honouring double and
+    /// single quotes and stopping at the first pipe.
+    fn shell_words(line: &str) -> Vec<String> {
+        let mut words = Vec::new();
+        let mut word = String::new();
+        let mut in_word = false;
+        let mut quote = None;
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some('"'), '\\') => {
+                    if let Some(next) = chars.next() {
+                        if next != '"' && next != '\\' {
+                            word.push('\\');
+                        }
+                        word.push(next);
+                    }
+                }
+                (Some(_), c) => word.push(c),
+                (None, '"' | '\'') => {
+                    quote = Some(c);
+                    in_word = true;
+                }
+                (None, '|') if !in_word => break,
+                (None, c) if c.is_whitespace() => {
+                    if in_word {
+                        words.push(std::mem::take(&mut word));
+                        in_word = false;
+                    }
+                }
+                (None, c) => {
+                    word.push(c);
+                    in_word = true;
+                }
+            }
+        }
+        if in_word {
+            words.push(word);
+        }
+        words
+    }
+
+    fn all_examples() -> impl Iterator<Item = &'static Example> {
+        RECIPES
+            .iter()
+            .flat_map(|r| r.steps.iter())
+            .chain(COMMAND_EXAMPLES.iter().flat_map(|(_, e)| e.iter()))
+    }
+
+    #[test]
+    fn shell_words_handles_quotes_and_pipes() {
+        assert_eq!(
+            shell_words("wonk search \"a b\" --regex \"x\\\\w\" | grep -q y"),
+            vec!["wonk", "search", "a b", "--regex", "x\\w"]
+        );
+    }
+
+    #[test]
+    fn every_example_parses() {
+        for example in all_examples() {
+            let args = shell_words(example.command);
+            if let Err(e) = Cli::try_parse_from(&args) {
+                panic!("example `{}` does not parse: {e}", example.command);
+            }
+        }
+    }
+
+    #[test]
+    fn command_examples_use_their_subcommand() {
+        use clap::CommandFactory;
+        let cli = Cli::command();
+        for (name, examples) in COMMAND_EXAMPLES {
+            assert!(
+                cli.find_subcommand(name).is_some(),
+                "no subcommand `{name}`"
+            );
+            for example in examples.iter() {
+                assert_eq!(shell_words(example.command)[1], *name);
+            }
+        }
+    }
+
+    #[test]
+    fn find_recipe_accepts_unique_prefixes() {
+        assert_eq!(find_recipe("callers").unwrap().topic, "callers");
+        assert_eq!(find_recipe("CALL").unwrap().topic, "callers");
+        assert!(find_recipe("c").is_none(), "ambiguous prefix");
+        assert!(find_recipe("nope").is_none());
+    }
+
+    #[test]
+    fn after_help_lists_examples() {
+        let help = after_help("callers").unwrap();
+        assert!(help.starts_with("Examples:\n"));
+        assert!(help.contains("  # Direct callers\n  wonk callers parse_config\n"));
+        assert!(after_help("status").is_none());
+    }
+}
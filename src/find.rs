@@ -0,0 +1,203 @@
+//! `wonk find`: everything known about a name in one report.
+//!
+//! Symbol lookup, signatures, references, and a word search for the name
+//! overlap heavily: a definition is also a signature and a text match, and a
+//! call is also a text match.  [`merge`] keeps each source line once, in the
+//! first section it belongs to, so the report reads definitions, then
+//! signatures, then usages, then the mentions the index does not explain
+//! (comments, strings, configuration files).
+
+use std::collections::HashSet;
+
+use crate::search::SearchResult;
+use crate::types::{Reference, Symbol};
+
+/// A section of the `wonk find` report, in report order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Section {
+    Definition,
+    Signature,
+    Usage,
+    Mention,
+}
+
+impl Section {
+    /// Name used in JSON output.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Section::Definition => "definition",
+            Section::Signature => "signature",
+            Section::Usage => "usage",
+            Section::Mention => "mention",
+        }
+    }
+
+    /// Heading shown above the section in grep output.
+    pub fn heading(self) -> &'static str {
+        match self {
+            Section::Definition => "definitions",
+            Section::Signature => "signatures",
+            Section::Usage => "usages",
+            Section::Mention => "other mentions",
+        }
+    }
+}
+
+/// One line of the report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FindHit {
+    pub section: Section,
+    pub file: String,
+    /// 1-based line number.
+    pub line: usize,
+    /// 0-based column.
+    pub col: usize,
+    /// Symbol kind of a definition or signature, reference kind of a usage.
+    pub kind: Option<String>,
+    /// The signature, or the source line of a usage or mention.
+    pub content: String,
+    /// Function enclosing a usage.
+    pub caller_name: Option<String>,
+}
+
+/// Merge the results of the four lookups into report order, dropping every
+/// hit on a line already reported by an earlier section (or earlier in the
+/// same one).  `mentions` must have paths relative to the repository root,
+/// like the index.
+pub fn merge(
+    definitions: Vec<Symbol>,
+    signatures: Vec<Symbol>,
+    usages: Vec<Reference>,
+    mentions: Vec<SearchResult>,
+) -> Vec<FindHit> {
+    let symbol_hit = |section, s: Symbol| FindHit {
+        section,
+        kind: Some(s.kind.to_string()),
+        content: s.signature.trim().to_string(),
+        file: s.file,
+        line: s.line,
+        col: s.col,
+        caller_name: None,
+    };
+    let hits = definitions
+        .into_iter()
+        .map(|s| symbol_hit(Section::Definition, s))
+        .chain(
+            signatures
+                .into_iter()
+                .map(|s| symbol_hit(Section::Signature, s)),
+        )
+        .chain(usages.into_iter().map(|r| FindHit {
+            section: Section::Usage,
+            kind: Some(r.kind.to_string()),
+            content: r.context.trim().to_string(),
+            file: r.file,
+            line: r.line,
+            col: r.col,
+            caller_name: r.caller_name,
+        }))
+        .chain(mentions.into_iter().map(|m| FindHit {
+            section: Section::Mention,
+            kind: None,
+            content: m.content.trim().to_string(),
+            file: m.file.to_string_lossy().into_owned(),
+            line: m.line as usize,
+            col: m.col.saturating_sub(1) as usize,
+            caller_name: None,
+        }));
+
+    let mut seen = HashSet::new();
+    hits.filter(|hit| seen.insert((hit.file.clone(), hit.line)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ReferenceKind, SymbolKind};
+    use std::path::PathBuf;
+
+    fn symbol(file: &str, line: usize, signature: &str) -> Symbol {
+        Symbol {
+            name: "parse".into(),
+            kind: SymbolKind::Function,
+            file: file.into(),
+            line,
+            col: 0,
+            end_line: None,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
+            scope: None,
+            signature: signature.into(),
+            language: "Rust".into(),
+            doc_comment: None,
+            condition: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
+        }
+    }
+
+    fn reference(file: &str, line: usize) -> Reference {
+        Reference {
+            name: "parse".into(),
+            kind: ReferenceKind::Call,
+            file: file.into(),
+            line,
+            col: 4,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
+            context: "    parse(input);".into(),
+            caller_name: Some("main".into()),
+            confidence: 1.0,
+        }
+    }
+
+    fn mention(file: &str, line: u64, content: &str) -> SearchResult {
+        SearchResult {
+            file: PathBuf::from(file),
+            line,
+            col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
+            content: content.into(),
+        }
+    }
+
+    #[test]
+    fn each_line_is_reported_once_in_its_first_section() {
+        let hits = merge(
+            vec![symbol("src/lib.rs", 3, "pub fn parse(s: &str)")],
+            vec![
+                symbol("src/lib.rs", 3, "pub fn parse(s: &str)"),
+                symbol("include/parse.h", 1, "int parse(const char *s);"),
+            ],
+            vec![reference("src/main.rs", 7), reference("src/main.rs", 7)],
+            vec![
+                mention("src/lib.rs", 3, "pub fn parse(s: &str) {"),
+                mention("src/main.rs", 7, "    parse(input);"),
+                mention("README.md", 12, "Call `parse` first."),
+            ],
+        );
+        let report: Vec<(Section, &str, usize)> = hits
+            .iter()
+            .map(|h| (h.section, h.file.as_str(), h.line))
+            .collect();
+        assert_eq!(
+            report,
+            [
+                (Section::Definition, "src/lib.rs", 3),
+                (Section::Signature, "include/parse.h", 1),
+                (Section::Usage, "src/main.rs", 7),
+                (Section::Mention, "README.md", 12),
+            ]
+        );
+        assert_eq!(hits[2].content, "parse(input);");
+        assert_eq!(hits[2].caller_name.as_deref(), Some("main"));
+        assert_eq!(hits[0].kind.as_deref(), Some("function"));
+        assert_eq!(hits[3].col, 0);
+    }
+}
@@ -0,0 +1,132 @@
+//! Threshold and regression checks for `wonk gate`.
+//!
+//! Each check runs a named query from `[gate.queries]`.  `max_<query> = N`
+//! fails when the query returns more than `N` results;
+//! `no_new_findings_of` fails when a query returns records its committed
+//! baseline does not have.  Baselines are ordinary snapshots (see
+//! [`crate::snapshot`]) named after the query, so `wonk compare <query>`
+//! shows the same diff in full.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use serde_json::Value;
+
+use crate::config::GateConfig;
+use crate::output::GateCheckOutput;
+use crate::snapshot::{self, Snapshot};
+
+/// Query names the configured checks refer to, failing on undefined ones.
+fn referenced_queries(config: &GateConfig) -> Result<Vec<&str>> {
+    let mut names: Vec<&str> = config.max.keys().map(String::as_str).collect();
+    for name in &config.no_new_findings_of {
+        if !names.contains(&name.as_str()) {
+            names.push(name);
+        }
+    }
+    if names.is_empty() {
+        bail!(
+            "no gate checks configured; add `max_<query>` or `no_new_findings_of` under [gate] in .wonk/config.toml"
+        );
+    }
+    if let Some(missing) = names.iter().find(|n| !config.queries.contains_key(**n)) {
+        bail!("gate check refers to unknown query {missing:?}; define it under [gate.queries]");
+    }
+    Ok(names)
+}
+
+/// Evaluate every configured check from `repo_root`.
+///
+/// With `update_baseline`, the baselines of `no_new_findings_of` queries
+/// are rewritten from the current results first, so those checks pass.
+pub fn run(
+    repo_root: &Path,
+    config: &GateConfig,
+    update_baseline: bool,
+) -> Result<Vec<GateCheckOutput>> {
+    let mut results: BTreeMap<&str, Vec<Value>> = BTreeMap::new();
+    for name in referenced_queries(config)? {
+        results.insert(name, snapshot::run_query(repo_root, &config.queries[name])?);
+    }
+
+    let mut checks = Vec::new();
+    for (name, &limit) in &config.max {
+        let count = results[name.as_str()].len();
+        checks.push(GateCheckOutput {
+            name: name.clone(),
+            rule: "max".to_string(),
+            count,
+            limit: Some(limit),
+            baseline: None,
+            added: Vec::new(),
+            passed: count <= limit,
+        });
+    }
+
+    let ignore: Vec<String> = snapshot::POSITION_FIELDS
+        .iter()
+        .map(|f| f.to_string())
+        .collect();
+    for name in &config.no_new_findings_of {
+        let current = &results[name.as_str()];
+        let path = snapshot::path_for(repo_root, name)?;
+        if update_baseline {
+            let query = &config.queries[name];
+            snapshot::save(&path, &Snapshot::new(name, query, current.clone()))?;
+        }
+        let baseline = path.exists().then(|| snapshot::load(&path)).transpose()?;
+        let added = baseline
+            .as_ref()
+            .map(|b| snapshot::diff(&b.entries, current, &ignore).added)
+            .unwrap_or_default();
+        checks.push(GateCheckOutput {
+            name: name.clone(),
+            rule: "no_new".to_string(),
+            count: current.len(),
+            limit: None,
+            baseline: baseline.as_ref().map(|b| b.entries.len()),
+            passed: baseline.is_some() && added.is_empty(),
+            added,
+        });
+    }
+    Ok(checks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(max: &[(&str, usize)], no_new: &[&str], defined: &[&str]) -> GateConfig {
+        GateConfig {
+            queries: defined
+                .iter()
+                .map(|n| (n.to_string(), vec!["ref".to_string(), n.to_string()]))
+                .collect(),
+            max: max.iter().map(|(n, l)| (n.to_string(), *l)).collect(),
+            no_new_findings_of: no_new.iter().map(|n| n.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn referenced_queries_are_deduplicated_in_check_order() {
+        let cfg = config(
+            &[("todo", 1), ("unwrap", 2)],
+            &["unwrap", "audit"],
+            &["todo", "unwrap", "audit"],
+        );
+        assert_eq!(
+            referenced_queries(&cfg).unwrap(),
+            vec!["todo", "unwrap", "audit"]
+        );
+    }
+
+    #[test]
+    fn undefined_or_missing_checks_are_errors() {
+        let err = referenced_queries(&config(&[], &[], &["todo"])).unwrap_err();
+        assert!(err.to_string().contains("no gate checks configured"));
+
+        let err = referenced_queries(&config(&[("todo", 1)], &["audit"], &["todo"])).unwrap_err();
+        assert!(err.to_string().contains("\"audit\""), "{err}");
+    }
+}
@@ -0,0 +1,279 @@
+//! Man page and markdown reference generation for `wonk gen-docs`.
+//!
+//! Both outputs are derived from the clap command tree (see
+//! [`crate::cli::command`]), so packaged docs always match the binary.  Each
+//! visible command gets its own page, named after its display name
+//! (`wonk-search.1`, `wonk-daemon-start.md`, ...); hidden commands such as
+//! `gen-docs` itself are skipped.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::{Arg, Command};
+
+/// Prepare `cmd` for introspection: resolve bin/display names and global
+/// arguments, and drop the implicit `help` subcommand.
+fn built(cmd: &Command) -> Command {
+    let mut cmd = cmd.clone().disable_help_subcommand(true);
+    cmd.build();
+    cmd
+}
+
+/// Visible subcommands of `cmd` in `--help` order.
+fn subcommands(cmd: &Command) -> Vec<&Command> {
+    let mut subs: Vec<&Command> = cmd.get_subcommands().filter(|s| !s.is_hide_set()).collect();
+    subs.sort_by_key(|s| s.get_display_order());
+    subs
+}
+
+/// Visit `cmd` and its visible subcommands depth-first, parents first.
+fn visit<'a>(cmd: &'a Command, out: &mut Vec<&'a Command>) {
+    out.push(cmd);
+    for sub in subcommands(cmd) {
+        visit(sub, out);
+    }
+}
+
+/// File stem for `cmd`'s page (`wonk`, `wonk-search`, ...).
+fn page_name(cmd: &Command) -> String {
+    cmd.get_display_name()
+        .unwrap_or_else(|| cmd.get_name())
+        .to_string()
+}
+
+/// Write one man page (section 1) per command into `dir`, returning the
+/// paths written.
+pub fn write_man_pages(cmd: &Command, dir: &Path) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    let root = built(cmd);
+    let mut commands = Vec::new();
+    visit(&root, &mut commands);
+    commands
+        .into_iter()
+        .map(|c| {
+            clap_mangen::Man::new(c.clone())
+                .generate_to(dir)
+                .with_context(|| format!("writing man page for {}", page_name(c)))
+        })
+        .collect()
+}
+
+/// Write one markdown page per command into `dir`, returning the paths
+/// written.  The root page (`wonk.md`) lists the global options and links
+/// to every command.
+pub fn write_markdown(cmd: &Command, dir: &Path) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    let root = built(cmd);
+    let mut commands = Vec::new();
+    visit(&root, &mut commands);
+    let mut written = Vec::with_capacity(commands.len());
+    for c in commands {
+        let path = dir.join(format!("{}.md", page_name(c)));
+        std::fs::write(&path, render_markdown(c, c.get_name() == root.get_name()))
+            .with_context(|| format!("writing {}", path.display()))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Render the markdown page for a single (built) command.
+pub fn render_markdown(cmd: &Command, is_root: bool) -> String {
+    let mut md = String::new();
+    let title = cmd.get_bin_name().unwrap_or_else(|| cmd.get_name());
+    let _ = writeln!(md, "# {title}\n");
+    if let Some(about) = cmd.get_long_about().or_else(|| cmd.get_about()) {
+        let _ = writeln!(md, "{about}\n");
+    }
+
+    let usage = cmd.clone().render_usage().to_string();
+    let usage = usage.strip_prefix("Usage: ").unwrap_or(&usage);
+    let _ = writeln!(md, "## Usage\n\n```\n{usage}\n```\n");
+
+    let positionals: Vec<&Arg> = cmd.get_positionals().filter(|a| !a.is_hide_set()).collect();
+    if !positionals.is_empty() {
+        let _ = writeln!(md, "## Arguments\n\n| Argument | Description |\n|---|---|");
+        for arg in positionals {
+            let _ = writeln!(md, "| `{}` | {} |", arg_usage(arg), arg_help(arg));
+        }
+        md.push('\n');
+    }
+
+    // Global options are documented once, on the root page.
+    let options: Vec<&Arg> = cmd
+        .get_arguments()
+        .filter(|a| !a.is_positional() && !a.is_hide_set())
+        .filter(|a| is_root || !a.is_global_set())
+        .filter(|a| !matches!(a.get_id().as_str(), "help" | "version"))
+        .collect();
+    if !options.is_empty() {
+        let heading = if is_root { "Global options" } else { "Options" };
+        let _ = writeln!(md, "## {heading}\n\n| Option | Description |\n|---|---|");
+        for arg in options {
+            let _ = writeln!(md, "| `{}` | {} |", arg_usage(arg), arg_help(arg));
+        }
+        md.push('\n');
+    }
+
+    let subs = subcommands(cmd);
+    if !subs.is_empty() {
+        let _ = writeln!(md, "## Commands\n\n| Command | Description |\n|---|---|");
+        for sub in subs {
+            let _ = writeln!(
+                md,
+                "| [`{}`]({}.md) | {} |",
+                sub.get_name(),
+                page_name(sub),
+                escape_cell(&sub.get_about().map(|a| a.to_string()).unwrap_or_default())
+            );
+        }
+        md.push('\n');
+    }
+
+    let examples = crate::examples::for_command(cmd.get_name());
+    if !is_root && !examples.is_empty() {
+        let _ = writeln!(md, "## Examples\n\n```sh");
+        for (i, example) in examples.iter().enumerate() {
+            if i > 0 {
+                md.push('\n');
+            }
+            let _ = writeln!(md, "# {}\n{}", example.description, example.command);
+        }
+        let _ = writeln!(md, "```\n");
+    } else if let Some(after) = cmd.get_after_help().filter(|_| is_root) {
+        let _ = writeln!(md, "{after}\n");
+    }
+
+    md.truncate(md.trim_end().len());
+    md.push('\n');
+    md
+}
+
+/// Usage form of an argument: `<NAME>`/`[NAME]` for positionals,
+/// `-s, --long <VALUE>` for options.
+fn arg_usage(arg: &Arg) -> String {
+    let value_name = || {
+        arg.get_value_names()
+            .and_then(|names| names.first())
+            .map(|n| n.to_string())
+            .unwrap_or_else(|| arg.get_id().as_str().to_uppercase())
+    };
+    let many = arg.get_num_args().is_some_and(|n| n.max_values() > 1);
+    if arg.is_positional() {
+        let ellipsis = if many { "..." } else { "" };
+        return if arg.is_required_set() {
+            format!("<{}>{ellipsis}", value_name())
+        } else {
+            format!("[{}]{ellipsis}", value_name())
+        };
+    }
+    let mut parts = Vec::new();
+    if let Some(short) = arg.get_short() {
+        parts.push(format!("-{short}"));
+    }
+    if let Some(long) = arg.get_long() {
+        parts.push(format!("--{long}"));
+    }
+    let mut usage = parts.join(", ");
+    if arg.get_action().takes_values() {
+        let _ = write!(usage, " <{}>", value_name());
+    }
+    usage
+}
+
+/// Help text for an argument, with possible and default values appended
+/// when the help does not already mention them.
+fn arg_help(arg: &Arg) -> String {
+    let mut help = arg
+        .get_long_help()
+        .or_else(|| arg.get_help())
+        .map(|h| h.to_string())
+        .unwrap_or_default();
+    let possible: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .filter(|v| !v.is_hide_set())
+        .map(|v| format!("`{}`", v.get_name()))
+        .collect();
+    if !possible.is_empty() && arg.get_action().takes_values() {
+        let _ = write!(help, " (one of {})", possible.join(", "));
+    }
+    let defaults: Vec<String> = arg
+        .get_default_values()
+        .iter()
+        .map(|v| v.to_string_lossy().into_owned())
+        .collect();
+    if !defaults.is_empty() && arg.get_action().takes_values() && !help.contains("default") {
+        let _ = write!(help, " (default: `{}`)", defaults.join(", "));
+    }
+    escape_cell(help.trim())
+}
+
+/// Escape a string for use in a markdown table cell.
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn man_pages_cover_visible_commands() {
+        let dir = TempDir::new().unwrap();
+        let written = write_man_pages(&crate::cli::command(), dir.path()).unwrap();
+        let names: Vec<String> = written
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert!(names.contains(&"wonk.1".to_string()));
+        assert!(names.contains(&"wonk-search.1".to_string()));
+        assert!(names.contains(&"wonk-daemon-start.1".to_string()));
+        assert!(!names.iter().any(|n| n.contains("gen-docs")));
+
+        let page = std::fs::read_to_string(dir.path().join("wonk-search.1")).unwrap();
+        assert!(page.contains("ignore\\-case"));
+    }
+
+    #[test]
+    fn markdown_pages_link_and_document_options() {
+        let dir = TempDir::new().unwrap();
+        write_markdown(&crate::cli::command(), dir.path()).unwrap();
+
+        let root = std::fs::read_to_string(dir.path().join("wonk.md")).unwrap();
+        assert!(root.starts_with("# wonk\n"));
+        assert!(root.contains("## Global options"));
+        assert!(root.contains("| `--format <FORMAT>` |"));
+        assert!(root.contains("[`search`](wonk-search.md)"));
+        let search_at = root.find("wonk-search.md").unwrap();
+        assert!(
+            search_at < root.find("wonk-status.md").unwrap(),
+            "help order"
+        );
+        assert!(!root.contains("gen-docs"));
+
+        let search = std::fs::read_to_string(dir.path().join("wonk-search.md")).unwrap();
+        assert!(search.starts_with("# wonk search\n"));
+        assert!(search.contains("| `-i, --ignore-case` | Case-insensitive search |"));
+        assert!(search.contains("| `[PATTERN]` |"));
+        assert!(search.contains("## Examples"));
+        assert!(search.contains("wonk search parse_config"));
+        // Global options live on the root page only.
+        assert!(!search.contains("--budget"));
+    }
+
+    #[test]
+    fn arg_help_appends_possible_values_and_defaults() {
+        let arg = Arg::new("mode")
+            .long("mode")
+            .help("Output mode")
+            .value_parser(["full", "files"])
+            .default_value("full");
+        assert_eq!(
+            arg_help(&arg),
+            "Output mode (one of `full`, `files`) (default: `full`)"
+        );
+        assert_eq!(escape_cell("a | b\nc"), "a \\| b c");
+    }
+}
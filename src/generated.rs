@@ -0,0 +1,209 @@
+//! Generated-code provenance.
+//!
+//! Code generators stamp their output with a header comment ("Code generated
+//! by protoc-gen-go. DO NOT EDIT.", "Autogenerated by Thrift Compiler",
+//! `@generated`, ...).  The indexer records the generator per file in
+//! `files.generated_by`; queries use it to label, filter, or collapse hits
+//! in generated code.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::LazyLock;
+
+use regex::Regex;
+use rusqlite::Connection;
+
+/// Number of leading lines searched for a generator header.
+const HEADER_LINES: usize = 20;
+
+/// Well-known header phrases and the generator they identify, checked
+/// before the generic "generated by X" pattern.
+const KNOWN_GENERATORS: &[(&str, &str)] = &[
+    ("generated by the protocol buffer compiler", "protoc"),
+    ("autogenerated by thrift", "thrift"),
+    ("openapi-generator", "openapi-generator"),
+    ("openapi generator", "openapi-generator"),
+    ("swagger-codegen", "swagger-codegen"),
+    ("automatically generated by flatc", "flatbuffers"),
+    ("generated by the grpc", "grpc"),
+];
+
+static GENERATED_BY_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\bgenerated (?:automatically )?by (?:the )?([A-Za-z0-9_@/.+-]*[A-Za-z0-9_])")
+        .expect("valid regex")
+});
+
+/// Return the generator named in the file's header comments, if any.
+///
+/// Only comment lines among the first few lines are considered, so prose
+/// such as "an ID generated by the database" in ordinary code does not
+/// count.  Files that are clearly generated but do not name a tool
+/// (`@generated`, `DO NOT EDIT`) report `"generated"`.
+pub fn detect_generator(content: &str) -> Option<String> {
+    let mut anonymous = false;
+    for line in content.lines().take(HEADER_LINES) {
+        let trimmed = line.trim_start();
+        if !is_comment(trimmed) {
+            continue;
+        }
+        let lower = trimmed.to_ascii_lowercase();
+        if let Some((_, name)) = KNOWN_GENERATORS.iter().find(|(p, _)| lower.contains(p)) {
+            return Some((*name).to_string());
+        }
+        if let Some(caps) = GENERATED_BY_RE.captures(trimmed) {
+            return Some(caps[1].to_ascii_lowercase());
+        }
+        if lower.contains("@generated")
+            || lower.contains("do not edit")
+            || lower.contains("auto-generated")
+            || lower.contains("autogenerated")
+        {
+            anonymous = true;
+        }
+    }
+    anonymous.then(|| "generated".to_string())
+}
+
+fn is_comment(line: &str) -> bool {
+    [
+        "//", "#", "/*", "*", "--", "<!--", ";", "%", "{-", "(*", "'",
+    ]
+    .iter()
+    .any(|p| line.starts_with(p))
+}
+
+/// Generator lookup for indexed files.
+#[derive(Debug, Default)]
+pub struct GeneratedFiles {
+    by_file: HashMap<String, String>,
+}
+
+impl GeneratedFiles {
+    /// Load every file with a recorded generator.  Returns an empty map when
+    /// the index predates provenance tracking.
+    pub fn load(conn: &Connection) -> Self {
+        let by_file = conn
+            .prepare("SELECT path, generated_by FROM files WHERE generated_by IS NOT NULL")
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .collect::<rusqlite::Result<HashMap<_, _>>>()
+            })
+            .unwrap_or_default();
+        Self { by_file }
+    }
+
+    /// The generator of `file` (repo-relative), if it is generated code.
+    pub fn generator(&self, file: &str) -> Option<&str> {
+        self.by_file.get(file).map(String::as_str)
+    }
+}
+
+/// Hits in generated code summarised per generator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedGroup {
+    pub generator: String,
+    pub files: usize,
+    pub hits: usize,
+}
+
+/// Split `items` into hand-written hits and one [`GeneratedGroup`] per
+/// generator, preserving the order of the hand-written hits.
+pub fn collapse<T>(
+    items: Vec<T>,
+    generated: &GeneratedFiles,
+    file_of: impl Fn(&T) -> &str,
+) -> (Vec<T>, Vec<GeneratedGroup>) {
+    let mut kept = Vec::with_capacity(items.len());
+    let mut groups: BTreeMap<String, (HashSet<String>, usize)> = BTreeMap::new();
+    for item in items {
+        match generated.generator(file_of(&item)) {
+            Some(generator) => {
+                let entry = groups.entry(generator.to_string()).or_default();
+                entry.0.insert(file_of(&item).to_string());
+                entry.1 += 1;
+            }
+            None => kept.push(item),
+        }
+    }
+    let groups = groups
+        .into_iter()
+        .map(|(generator, (files, hits))| GeneratedGroup {
+            generator,
+            files: files.len(),
+            hits,
+        })
+        .collect();
+    (kept, groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_go_convention() {
+        let src = "// Code generated by protoc-gen-go. DO NOT EDIT.\npackage pb\n";
+        assert_eq!(detect_generator(src).as_deref(), Some("protoc-gen-go"));
+    }
+
+    #[test]
+    fn detects_known_generators() {
+        let protoc =
+            "# -*- coding: utf-8 -*-\n# Generated by the protocol buffer compiler.  DO NOT EDIT!\n";
+        assert_eq!(detect_generator(protoc).as_deref(), Some("protoc"));
+        let thrift = "/**\n * Autogenerated by Thrift Compiler (0.16.0)\n */\n";
+        assert_eq!(detect_generator(thrift).as_deref(), Some("thrift"));
+        let openapi = "/* tslint:disable */\n/**\n * NOTE: This class is auto generated by OpenAPI Generator (https://openapi-generator.tech).\n */\n";
+        assert_eq!(
+            detect_generator(openapi).as_deref(),
+            Some("openapi-generator")
+        );
+    }
+
+    #[test]
+    fn anonymous_marker_reports_generic_generator() {
+        let src = "// @generated\nexport const x = 1;\n";
+        assert_eq!(detect_generator(src).as_deref(), Some("generated"));
+    }
+
+    #[test]
+    fn ignores_prose_in_code() {
+        let src = "fn main() {\n    let id = 1; let s = \"generated by the database\";\n}\n";
+        assert_eq!(detect_generator(src), None);
+        assert_eq!(detect_generator("fn main() {}\n"), None);
+    }
+
+    #[test]
+    fn collapse_groups_per_generator() {
+        let generated = GeneratedFiles {
+            by_file: HashMap::from([
+                ("pb/a.pb.go".to_string(), "protoc-gen-go".to_string()),
+                ("pb/b.pb.go".to_string(), "protoc-gen-go".to_string()),
+                ("api/client.ts".to_string(), "openapi-generator".to_string()),
+            ]),
+        };
+        let hits = vec![
+            "main.go",
+            "pb/a.pb.go",
+            "pb/a.pb.go",
+            "pb/b.pb.go",
+            "api/client.ts",
+        ];
+        let (kept, groups) = collapse(hits, &generated, |f| f);
+        assert_eq!(kept, ["main.go"]);
+        assert_eq!(
+            groups,
+            [
+                GeneratedGroup {
+                    generator: "openapi-generator".into(),
+                    files: 1,
+                    hits: 1
+                },
+                GeneratedGroup {
+                    generator: "protoc-gen-go".into(),
+                    files: 2,
+                    hits: 3
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! GitHub Actions workflow commands for `--format github`.
+//!
+//! Every record with a `file` and `line` becomes a `::warning` annotation,
+//! which GitHub shows inline on the pull request diff.  Records a check
+//! reports as `added` since a baseline (`wonk compare`, `wonk gate`) are
+//! annotated too.  Hints, warnings, and errors become `::notice`,
+//! `::warning`, and `::error` commands without a location.  Anything else
+//! without a location is printed as a JSON line, so it still shows up in the
+//! job log.  Columns are left out because result
+//! types do not agree on whether they are 0- or 1-based.
+
+use std::fmt::Write as _;
+
+use serde_json::Value;
+
+use crate::output::{MessageKind, message_kind, record_text};
+
+/// Workflow commands for one output record, one per line (no trailing
+/// newline).
+pub fn render(record: &Value) -> String {
+    if let Some(kind) = message_kind(record) {
+        let command = match kind {
+            MessageKind::Hint => "notice",
+            MessageKind::Warning => "warning",
+            MessageKind::Error => "error",
+        };
+        let mut message = record["message"].as_str().unwrap_or_default().to_string();
+        if let Some(hint) = record.get("hint").and_then(Value::as_str) {
+            message = format!("{message} (hint: {hint})");
+        }
+        return format!("::{command}::{}", escape_data(&message));
+    }
+    if let Some(annotation) = annotation(record, None) {
+        return annotation;
+    }
+    let mut out = String::new();
+    if let Some(added) = record.get("added").and_then(Value::as_array) {
+        let name = record.get("name").and_then(Value::as_str);
+        for entry in added {
+            if let Some(annotation) = annotation(entry, name) {
+                let _ = writeln!(out, "{annotation}");
+            }
+        }
+    }
+    out.push_str(&record.to_string());
+    out
+}
+
+/// A `::warning` command for `record` if it has a location, with the
+/// message prefixed by `source` (the check or snapshot that reported it).
+fn annotation(record: &Value, source: Option<&str>) -> Option<String> {
+    let file = record.get("file")?.as_str()?;
+    let line = record.get("line")?.as_u64()?;
+    let text = record_text(record).unwrap_or("match");
+    let message = match source {
+        Some(source) => format!("{source}: {text}"),
+        None => text.to_string(),
+    };
+    Some(format!(
+        "::warning file={},line={line}::{}",
+        escape_property(file),
+        escape_data(&message)
+    ))
+}
+
+/// Escape a command message (`%`, CR, and LF), as the Actions toolkit does.
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a command property value, which additionally may not contain
+/// `:` or `,`.
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn located_records_become_warnings() {
+        let record =
+            json!({"file": "src/a,b.rs", "line": 7, "col": 3, "content": "  x = 100% // TODO\n"});
+        assert_eq!(
+            render(&record),
+            "::warning file=src/a%2Cb.rs,line=7::x = 100%25 // TODO"
+        );
+    }
+
+    #[test]
+    fn records_without_location_are_printed_as_json() {
+        let record = json!({"files": 3, "lines": 120});
+        assert_eq!(render(&record), record.to_string());
+    }
+
+    #[test]
+    fn messages_become_unlocated_commands() {
+        let hint = json!({"type": "hint", "message": "no references found"});
+        assert_eq!(render(&hint), "::notice::no references found");
+        let error = json!({"type": "error", "message": "no index", "hint": "run `wonk init`"});
+        assert_eq!(render(&error), "::error::no index (hint: run `wonk init`)");
+        // A summary's `type` is not a message kind.
+        let summary = json!({"type": "directory", "path": "src"});
+        assert_eq!(render(&summary), summary.to_string());
+    }
+
+    #[test]
+    fn added_entries_are_annotated_with_their_check() {
+        let record = json!({
+            "name": "deprecated",
+            "passed": false,
+            "added": [
+                {"file": "src/cli.rs", "line": 88, "context": "old_api(&args)?;"},
+                {"total": 1}
+            ],
+        });
+        let out = render(&record);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines[0],
+            "::warning file=src/cli.rs,line=88::deprecated: old_api(&args)?;"
+        );
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("{\""));
+    }
+}
@@ -0,0 +1,190 @@
+//! Queries across every repository indexed in the central store.
+//!
+//! Each index under `~/.wonk/repos` names its repository in `meta.json`
+//! (see [`discover_repos`]).  `wonk global sym` looks a symbol up in each
+//! of them in turn and merges the hits, labeled with the repository they
+//! came from, so a type defined in a shared library can be found from
+//! anywhere.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::db;
+use crate::router;
+use crate::types::Symbol;
+
+/// Metadata for a discovered indexed repository.
+#[derive(Debug, Clone)]
+pub struct RepoEntry {
+    /// Absolute path to the repository root.
+    pub repo_path: PathBuf,
+    /// Absolute path to the index.db file.
+    pub index_path: PathBuf,
+    /// Short name (last component of repo_path).
+    pub name: String,
+    /// Languages detected during indexing.
+    pub languages: Vec<String>,
+    /// Unix timestamp when the index was created.
+    pub created: u64,
+}
+
+/// Discover all indexed repositories under a repos directory.
+///
+/// Scans `repos_dir/*/index.db`, reads the adjacent `meta.json` for metadata,
+/// and validates that the claimed repo path still has a root marker.
+pub fn discover_repos(repos_dir: &Path) -> Vec<RepoEntry> {
+    let mut entries = Vec::new();
+
+    if repos_dir.is_dir()
+        && let Ok(read_dir) = std::fs::read_dir(repos_dir)
+    {
+        for dir_entry in read_dir.flatten() {
+            let index_dir = dir_entry.path();
+            if !index_dir.is_dir() {
+                continue;
+            }
+            let index_path = index_dir.join("index.db");
+            if !index_path.exists() {
+                continue;
+            }
+            if let Ok(meta) = db::read_meta(&index_path) {
+                let repo_path = PathBuf::from(&meta.repo_path);
+                // Validate the claimed repo path still has a root marker.
+                if !db::is_repo_root(&repo_path) {
+                    continue;
+                }
+                let name = repo_path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| "unknown".to_string());
+                entries.push(RepoEntry {
+                    repo_path,
+                    index_path,
+                    name,
+                    languages: meta.languages,
+                    created: meta.created,
+                });
+            }
+        }
+    }
+
+    entries
+}
+
+/// A symbol found by a global query, with the repository it is defined in.
+#[derive(Debug, Clone)]
+pub struct GlobalSymbol {
+    /// Short name of the repository.
+    pub repo: String,
+    /// Absolute path to the repository root.
+    pub repo_path: PathBuf,
+    /// The symbol; its `file` is relative to `repo_path`.
+    pub symbol: Symbol,
+}
+
+/// Look `name` up in every repository of `repos`, as `wonk sym` would in
+/// each, and merge the hits ordered by repository name.
+///
+/// A repository indexed under more than one key is queried once.  Indexes
+/// that cannot be opened or queried, e.g. ones written by an older wonk,
+/// are reported in the second list as `(repo, error)` and skipped.
+pub fn search_symbols(
+    repos: &[RepoEntry],
+    name: &str,
+    kind: Option<&str>,
+    exact: bool,
+) -> (Vec<GlobalSymbol>, Vec<(String, String)>) {
+    let mut repos: Vec<&RepoEntry> = repos.iter().collect();
+    repos.sort_by(|a, b| a.name.cmp(&b.name).then(a.repo_path.cmp(&b.repo_path)));
+    let mut seen = HashSet::new();
+
+    let mut hits = Vec::new();
+    let mut failed = Vec::new();
+    for repo in repos {
+        if !seen.insert(&repo.repo_path) {
+            continue;
+        }
+        let symbols = db::open_existing(&repo.index_path)
+            .and_then(|conn| Ok(router::query_symbols_db(&conn, name, kind, exact)?));
+        match symbols {
+            Ok(symbols) => hits.extend(symbols.into_iter().map(|symbol| GlobalSymbol {
+                repo: repo.name.clone(),
+                repo_path: repo.repo_path.clone(),
+                symbol,
+            })),
+            Err(e) => failed.push((repo.name.clone(), format!("{e:#}"))),
+        }
+    }
+    (hits, failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Index a repository with the given files into its own central store
+    /// entry under `repos_dir`.
+    fn index_repo(root: &Path, repos_dir: &Path, files: &[(&str, &str)]) {
+        fs::create_dir_all(root.join(".git")).unwrap();
+        for (path, content) in files {
+            fs::write(root.join(path), content).unwrap();
+        }
+        crate::pipeline::build_index(root, true).unwrap();
+        let store = repos_dir.join(root.file_name().unwrap());
+        fs::create_dir_all(&store).unwrap();
+        let local = root.join(".wonk");
+        for file in ["index.db", "meta.json"] {
+            fs::copy(local.join(file), store.join(file)).unwrap();
+        }
+        fs::remove_dir_all(local).unwrap();
+    }
+
+    #[test]
+    fn search_merges_hits_across_repos() {
+        let dir = tempfile::tempdir().unwrap();
+        let repos_dir = dir.path().join("repos");
+        index_repo(
+            &dir.path().join("client"),
+            &repos_dir,
+            &[("types.rs", "pub struct Invoice {}\nfn render() {}\n")],
+        );
+        index_repo(
+            &dir.path().join("billing"),
+            &repos_dir,
+            &[("lib.rs", "fn load(i: Invoice) {}\nstruct InvoiceLine {}\n")],
+        );
+
+        let repos = discover_repos(&repos_dir);
+        assert_eq!(repos.len(), 2);
+
+        let (hits, failed) = search_symbols(&repos, "Invoice", None, false);
+        assert!(failed.is_empty());
+        let found: Vec<(&str, &str)> = hits
+            .iter()
+            .map(|h| (h.repo.as_str(), h.symbol.name.as_str()))
+            .collect();
+        assert_eq!(found, [("billing", "InvoiceLine"), ("client", "Invoice")]);
+
+        let (hits, _) = search_symbols(&repos, "Invoice", Some("struct"), true);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].repo, "client");
+        assert_eq!(hits[0].symbol.file, "types.rs");
+    }
+
+    #[test]
+    fn unreadable_indexes_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = RepoEntry {
+            repo_path: dir.path().to_path_buf(),
+            index_path: dir.path().join("missing/index.db"),
+            name: "gone".to_string(),
+            languages: Vec::new(),
+            created: 0,
+        };
+        let (hits, failed) = search_symbols(&[repo], "x", None, false);
+        assert!(hits.is_empty());
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].0, "gone");
+    }
+}
@@ -0,0 +1,87 @@
+//! Non-ASCII glyphs in human-readable output and their ASCII stand-ins.
+//!
+//! Some terminals and CI logs mangle Unicode, so `--ascii` (or
+//! `[output].ascii = true`) switches the whole process to ASCII glyphs with
+//! [`set_ascii`].  Formatters take their glyph set from [`current`] when
+//! they are created; free-form messages (hints, errors) go through
+//! [`asciify`].  Source text quoted in results is never rewritten.
+
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set when output must be ASCII-only.
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+/// The glyphs one rendering uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glyphs {
+    /// Separates a name from its description (`struct Config — pub struct ...`).
+    pub dash: &'static str,
+    /// Points from one item to the next (`main.rs → config.rs`).
+    pub arrow: &'static str,
+    /// One cell of a proportional bar (`wonk langs`).
+    pub bar: &'static str,
+}
+
+impl Glyphs {
+    pub const UNICODE: Glyphs = Glyphs {
+        dash: "—",
+        arrow: "→",
+        bar: "█",
+    };
+    pub const ASCII: Glyphs = Glyphs {
+        dash: "--",
+        arrow: "->",
+        bar: "#",
+    };
+
+    /// Rewrite the Unicode glyphs in `text` with this set.
+    pub fn apply<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if *self == Glyphs::UNICODE || text.is_ascii() {
+            return Cow::Borrowed(text);
+        }
+        Cow::Owned(
+            text.replace(Glyphs::UNICODE.dash, self.dash)
+                .replace(Glyphs::UNICODE.arrow, self.arrow)
+                .replace(Glyphs::UNICODE.bar, self.bar),
+        )
+    }
+}
+
+/// Use ASCII glyphs for the rest of the process.
+pub fn set_ascii() {
+    ASCII.store(true, Ordering::Relaxed);
+}
+
+/// The glyph set selected for this process.
+pub fn current() -> Glyphs {
+    if ASCII.load(Ordering::Relaxed) {
+        Glyphs::ASCII
+    } else {
+        Glyphs::UNICODE
+    }
+}
+
+/// `text` with the process glyph set applied (for hints and errors).
+pub fn asciify(text: &str) -> Cow<'_, str> {
+    current().apply(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_set_is_ascii() {
+        let g = Glyphs::ASCII;
+        assert!(g.dash.is_ascii() && g.arrow.is_ascii() && g.bar.is_ascii());
+    }
+
+    #[test]
+    fn apply_rewrites_only_known_glyphs() {
+        let text = "a → b — ███ naïve";
+        assert_eq!(Glyphs::ASCII.apply(text), "a -> b -- ### naïve");
+        assert!(matches!(Glyphs::UNICODE.apply(text), Cow::Borrowed(_)));
+        assert!(matches!(Glyphs::ASCII.apply("plain"), Cow::Borrowed(_)));
+    }
+}
@@ -0,0 +1,295 @@
+//! Golden tests for extraction rules (hidden `wonk golden`).
+//!
+//! A fixture is any source file under a golden directory (by default
+//! `tests/golden/<language>/`); its expectation is the file next to it with
+//! `.yaml` appended.  The expectation lists the symbols, references,
+//! imports, and type edges [`crate::pipeline::extract`] produces, in a fixed
+//! YAML layout, so a change to an extraction rule shows up as a line diff.
+//! `--bless` rewrites expectations from the current output.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::indexer;
+use crate::pipeline::Extraction;
+
+/// Golden directory used when `wonk golden` is given none.
+pub const DEFAULT_DIR: &str = "tests/golden";
+
+/// Header written at the top of every expectation file.
+const HEADER: &str = "# Generated by `wonk golden --bless`; review changes like code.\n";
+
+/// How a fixture compared with its expectation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    /// Expected and actual output differ; holds a line diff.
+    Failed(String),
+    /// The fixture has no expectation file yet.
+    Missing,
+    /// The expectation was (re)written by `--bless`.
+    Blessed,
+}
+
+impl Outcome {
+    /// Short status used in output (`ok`, `FAIL`, ...).
+    pub fn label(&self) -> &'static str {
+        match self {
+            Outcome::Passed => "ok",
+            Outcome::Failed(_) => "FAIL",
+            Outcome::Missing => "MISSING",
+            Outcome::Blessed => "blessed",
+        }
+    }
+
+    /// Returns `true` when the case needs attention.
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Outcome::Failed(_) | Outcome::Missing)
+    }
+}
+
+/// The outcome for one fixture.
+#[derive(Debug, Clone)]
+pub struct CaseResult {
+    /// Fixture path relative to the golden directory.
+    pub fixture: String,
+    pub outcome: Outcome,
+}
+
+/// The expectation file for `fixture`.
+pub fn expected_path(fixture: &Path) -> PathBuf {
+    let mut name = fixture.as_os_str().to_owned();
+    name.push(".yaml");
+    PathBuf::from(name)
+}
+
+/// Source files with a supported language under `dir`, sorted.
+pub fn discover(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut fixtures = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let entries = std::fs::read_dir(&current)
+            .with_context(|| format!("reading golden directory {}", current.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if indexer::detect_language(&path).is_some() {
+                fixtures.push(path);
+            }
+        }
+    }
+    fixtures.sort();
+    Ok(fixtures)
+}
+
+/// Run every fixture under `dir`, rewriting expectations when `bless` is set.
+pub fn run(dir: &Path, bless: bool) -> Result<Vec<CaseResult>> {
+    let mut results = Vec::new();
+    for fixture in discover(dir)? {
+        let rel = fixture
+            .strip_prefix(dir)
+            .unwrap_or(&fixture)
+            .to_string_lossy()
+            .into_owned();
+        let actual = render_fixture(&fixture, &rel)?;
+        let expected_file = expected_path(&fixture);
+        let expected = std::fs::read_to_string(&expected_file).ok();
+
+        let outcome = if expected.as_deref() == Some(actual.as_str()) {
+            Outcome::Passed
+        } else if bless {
+            std::fs::write(&expected_file, &actual)
+                .with_context(|| format!("writing {}", expected_file.display()))?;
+            Outcome::Blessed
+        } else if let Some(expected) = expected {
+            Outcome::Failed(line_diff(&expected, &actual))
+        } else {
+            Outcome::Missing
+        };
+        results.push(CaseResult {
+            fixture: rel,
+            outcome,
+        });
+    }
+    Ok(results)
+}
+
+/// Extract `fixture` and render it as an expectation.
+fn render_fixture(fixture: &Path, rel: &str) -> Result<String> {
+    let lang = indexer::detect_language(fixture).context("fixture language")?;
+    let source = std::fs::read_to_string(fixture)
+        .with_context(|| format!("reading fixture {}", fixture.display()))?;
+    let extraction = crate::pipeline::extract(lang, rel, &source, false)
+        .with_context(|| format!("{} grammar is unavailable", lang.name()))?;
+    Ok(render(&extraction))
+}
+
+/// YAML double-quoted scalar (JSON string syntax is valid YAML).
+fn quote(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_default()
+}
+
+/// Render `extraction` in the expectation layout.
+///
+/// Only what extraction rules decide is recorded: names, kinds, positions,
+/// scopes, signatures, and callers.  Confidence scores and stable IDs are
+/// left out so ranking tweaks do not churn every expectation.
+pub fn render(extraction: &Extraction) -> String {
+    let mut out = String::from(HEADER);
+
+    out.push_str("symbols:");
+    if extraction.symbols.is_empty() {
+        out.push_str(" []");
+    }
+    out.push('\n');
+    for sym in &extraction.symbols {
+        let _ = writeln!(out, "  - name: {}", quote(&sym.name));
+        let _ = writeln!(out, "    kind: {}", sym.kind);
+        let _ = writeln!(out, "    line: {}", sym.line);
+        if let Some(end) = sym.end_line {
+            let _ = writeln!(out, "    end_line: {end}");
+        }
+        if let Some(scope) = &sym.scope {
+            let _ = writeln!(out, "    scope: {}", quote(scope));
+        }
+        let _ = writeln!(out, "    signature: {}", quote(&sym.signature));
+    }
+
+    out.push_str("references:");
+    if extraction.refs.is_empty() {
+        out.push_str(" []");
+    }
+    out.push('\n');
+    for r in &extraction.refs {
+        let _ = writeln!(out, "  - name: {}", quote(&r.name));
+        let _ = writeln!(out, "    kind: {}", r.kind);
+        let _ = writeln!(out, "    at: \"{}:{}\"", r.line, r.col);
+        if let Some(caller) = &r.caller_name {
+            let _ = writeln!(out, "    caller: {}", quote(caller));
+        }
+    }
+
+    out.push_str("imports:");
+    if extraction.imports.is_empty() {
+        out.push_str(" []");
+    }
+    out.push('\n');
+    for import in &extraction.imports {
+        let _ = writeln!(out, "  - {}", quote(import));
+    }
+
+    out.push_str("type_edges:");
+    if extraction.type_edges.is_empty() {
+        out.push_str(" []");
+    }
+    out.push('\n');
+    for edge in &extraction.type_edges {
+        let _ = writeln!(out, "  - child: {}", quote(&edge.child_name));
+        let _ = writeln!(out, "    parent: {}", quote(&edge.parent_name));
+        let _ = writeln!(out, "    relationship: {}", edge.relationship);
+    }
+    out
+}
+
+/// Changed lines between `expected` and `actual`, as `-N: line` / `+N: line`
+/// (numbered in their own file), from a longest-common-subsequence match.
+pub fn line_diff(expected: &str, actual: &str) -> String {
+    let a: Vec<&str> = expected.lines().collect();
+    let b: Vec<&str> = actual.lines().collect();
+
+    // lcs[i][j] = LCS length of a[i..] and b[j..].
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            let _ = writeln!(out, "-{}: {}", i + 1, a[i]);
+            i += 1;
+        } else {
+            let _ = writeln!(out, "+{}: {}", j + 1, b[j]);
+            j += 1;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// The shipped fixtures must match their expectations; run
+    /// `wonk golden --bless` after an intended extraction change.
+    #[test]
+    fn shipped_fixtures_match_expectations() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(DEFAULT_DIR);
+        let results = run(&dir, false).unwrap();
+        assert!(!results.is_empty(), "no fixtures under {}", dir.display());
+        let failures: Vec<String> = results
+            .iter()
+            .filter(|r| r.outcome.is_failure())
+            .map(|r| match &r.outcome {
+                Outcome::Failed(diff) => format!("{}:\n{diff}", r.fixture),
+                _ => format!("{}: no expectation", r.fixture),
+            })
+            .collect();
+        assert!(
+            failures.is_empty(),
+            "golden mismatches (rerun `wonk golden --bless` if intended):\n{}",
+            failures.join("\n")
+        );
+    }
+
+    #[test]
+    fn bless_writes_then_passes_then_detects_changes() {
+        let dir = TempDir::new().unwrap();
+        let fixture = dir.path().join("rust/basic.rs");
+        std::fs::create_dir_all(fixture.parent().unwrap()).unwrap();
+        std::fs::write(&fixture, "fn a() { b(); }\n").unwrap();
+        std::fs::write(dir.path().join("rust/notes.txt"), "ignored").unwrap();
+
+        let results = run(dir.path(), false).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].fixture, "rust/basic.rs");
+        assert_eq!(results[0].outcome, Outcome::Missing);
+
+        assert_eq!(run(dir.path(), true).unwrap()[0].outcome, Outcome::Blessed);
+        let expected = std::fs::read_to_string(expected_path(&fixture)).unwrap();
+        assert!(expected.starts_with(HEADER));
+        assert!(expected.contains("  - name: \"a\"\n    kind: function\n"));
+        assert!(expected.contains("    caller: \"a\"\n"));
+        assert_eq!(run(dir.path(), false).unwrap()[0].outcome, Outcome::Passed);
+
+        std::fs::write(&fixture, "fn a() { c(); }\n").unwrap();
+        match &run(dir.path(), false).unwrap()[0].outcome {
+            Outcome::Failed(diff) => {
+                assert!(diff.contains("-") && diff.contains("\"b\""), "{diff}");
+                assert!(diff.contains("\"c\""), "{diff}");
+            }
+            other => panic!("expected failure, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn line_diff_reports_changed_lines_only() {
+        let diff = line_diff("a\nb\nc\n", "a\nx\nc\nd\n");
+        assert_eq!(diff, "-2: b\n+2: x\n+4: d\n");
+        assert_eq!(line_diff("same\n", "same\n"), "");
+    }
+}
@@ -2,11 +2,11 @@
 //!
 //! Compares a fresh Tree-sitter parse of a file against the indexed version
 //! in SQLite to detect which symbols were added, modified, or removed.
-//! Also provides git-based file change detection for `--since` support.
+//! Also provides file change detection for `--since` and `wonk changes`,
+//! through git, Mercurial, or Jujutsu (see [`crate::vcs`]).
 
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
-use std::process::Command;
 use std::str::FromStr;
 
 use anyhow::{Context, Result, bail};
@@ -19,6 +19,7 @@ use crate::types::{
     ChangeAnalysis, ChangeScope, ChangeType, ChangedSymbol, ImpactResult, SemanticResult, Symbol,
     SymbolKind, SymbolRef,
 };
+use crate::vcs::Vcs;
 
 // ---------------------------------------------------------------------------
 // Internal helpers
@@ -65,10 +66,17 @@ fn query_indexed_symbols(conn: &Connection, file: &str) -> Result<Vec<Symbol>> {
             line: row.get::<_, i64>(3)? as usize,
             col: row.get::<_, i64>(4)? as usize,
             end_line: row.get::<_, Option<i64>>(5)?.map(|v| v as usize),
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             scope: row.get(6)?,
             signature: row.get(7)?,
             language: row.get(8)?,
             doc_comment: None,
+            condition: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
         })
     })?;
 
@@ -161,73 +169,40 @@ pub fn detect_changed_symbols(
     Ok(changes)
 }
 
-/// Return the list of files changed since a given git commit.
+/// Return the list of files changed since a given commit (or revision, in
+/// Mercurial and Jujutsu repositories).
 ///
-/// Shells out to `git diff --name-only <commit>` and parses the output.
-/// Returns a clear error if git is not installed (relevant only for `--since`).
+/// Shells out to `git diff --name-only <commit>` or its equivalent (see
+/// [`detect_scoped_files`]).  Returns a clear error if the tool is not
+/// installed (relevant only for `--since`).
 pub fn detect_changed_files_since(commit: &str, repo_root: &Path) -> Result<Vec<String>> {
     validate_git_ref(commit)?;
-
-    let output = Command::new("git")
-        .args(["diff", "--name-only", commit])
-        .current_dir(repo_root)
-        .output()
-        .context("failed to run git — is git installed? (--since requires git)")?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        bail!("git diff failed: {}", stderr.trim());
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let files: Vec<String> = stdout
-        .lines()
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty())
-        .map(|line| line.to_string())
-        .collect();
-
-    Ok(files)
-}
-
-/// Apply [`ChangeScope`] flags to a `git diff` command, validating refs.
-fn apply_scope_args(cmd: &mut Command, scope: &ChangeScope) -> Result<()> {
-    match scope {
-        ChangeScope::Unstaged => {} // default: working tree vs index
-        ChangeScope::Staged => {
-            cmd.arg("--cached");
-        }
-        ChangeScope::All => {
-            cmd.arg("HEAD");
-        }
-        ChangeScope::Compare(git_ref) => {
-            validate_git_ref(git_ref)?;
-            cmd.arg(git_ref.as_str());
-        }
-    }
-    Ok(())
+    detect_scoped_files(&ChangeScope::Compare(commit.to_string()), repo_root)
 }
 
 /// Return the list of files changed according to the given [`ChangeScope`].
 ///
-/// Maps each scope variant to the appropriate `git diff --name-only` invocation:
+/// In git repositories, maps each scope variant to the appropriate
+/// `git diff --name-only` invocation:
 /// - `Unstaged`: working tree vs index
 /// - `Staged`: index vs HEAD
 /// - `All`: working tree vs HEAD
 /// - `Compare(ref)`: working tree vs the given ref
+///
+/// Mercurial and Jujutsu repositories use `hg status` and `jj diff`; see
+/// [`Vcs::changed_files_command`].
 pub fn detect_scoped_files(scope: &ChangeScope, repo_root: &Path) -> Result<Vec<String>> {
-    let mut cmd = Command::new("git");
-    cmd.arg("diff").arg("--name-only");
-    apply_scope_args(&mut cmd, scope)?;
-
-    let output = cmd
+    let vcs = Vcs::detect(repo_root);
+    let program = vcs.program();
+    let output = vcs
+        .changed_files_command(scope)?
         .current_dir(repo_root)
         .output()
-        .context("failed to run git — is git installed?")?;
+        .with_context(|| format!("failed to run {program} — is {program} installed?"))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        bail!("git diff failed: {}", stderr.trim());
+        bail!("{program} diff failed: {}", stderr.trim());
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -284,26 +259,25 @@ pub fn map_hunks_to_symbols(
     result
 }
 
-/// Run `git diff --unified=0` for a single file under the given scope and
-/// return the parsed hunk ranges via [`parse_diff_hunks`].
+/// Run `git diff --unified=0` (or its Mercurial or Jujutsu equivalent) for
+/// a single file under the given scope and return the parsed hunk ranges via
+/// [`parse_diff_hunks`].
 pub fn get_diff_hunks_for_file(
     scope: &ChangeScope,
     file: &str,
     repo_root: &Path,
 ) -> Result<Vec<(usize, usize)>> {
-    let mut cmd = Command::new("git");
-    cmd.arg("diff").arg("--unified=0");
-    apply_scope_args(&mut cmd, scope)?;
-    cmd.arg("--").arg(file);
-
-    let output = cmd
+    let vcs = Vcs::detect(repo_root);
+    let program = vcs.program();
+    let output = vcs
+        .diff_command(scope, Some(file))?
         .current_dir(repo_root)
         .output()
-        .context("failed to run git diff for hunks")?;
+        .with_context(|| format!("failed to run {program} diff for hunks"))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        bail!("git diff failed for file {file}: {}", stderr.trim());
+        bail!("{program} diff failed for file {file}: {}", stderr.trim());
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -374,24 +348,24 @@ fn parse_all_diff_hunks(diff_output: &str) -> HashMap<String, Vec<(usize, usize)
     result
 }
 
-/// Run a single `git diff --unified=0` for all files under the given scope and
-/// return per-file hunk maps via [`parse_all_diff_hunks`].
+/// Run a single `git diff --unified=0` (or its Mercurial or Jujutsu
+/// equivalent) for all files under the given scope and return per-file hunk
+/// maps via [`parse_all_diff_hunks`].
 fn get_all_diff_hunks(
     scope: &ChangeScope,
     repo_root: &Path,
 ) -> Result<HashMap<String, Vec<(usize, usize)>>> {
-    let mut cmd = Command::new("git");
-    cmd.arg("diff").arg("--unified=0");
-    apply_scope_args(&mut cmd, scope)?;
-
-    let output = cmd
+    let vcs = Vcs::detect(repo_root);
+    let program = vcs.program();
+    let output = vcs
+        .diff_command(scope, None)?
         .current_dir(repo_root)
         .output()
-        .context("failed to run git diff for hunks")?;
+        .with_context(|| format!("failed to run {program} diff for hunks"))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        bail!("git diff failed: {}", stderr.trim());
+        bail!("{program} diff failed: {}", stderr.trim());
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -481,12 +455,15 @@ fn parse_file_to_symbols(file: &str, content: &str) -> Result<Vec<Symbol>> {
         None => bail!("unsupported language for file: {file}"),
     };
 
-    let mut parser = indexer::get_parser(lang);
+    let Some(mut parser) = indexer::get_parser(lang) else {
+        bail!("{} grammar unavailable for file: {file}", lang.name());
+    };
+    let source = indexer::parse_source(lang, content);
     let tree = parser
-        .parse(content.as_bytes(), None)
+        .parse(source.as_bytes(), None)
         .context("tree-sitter parse failed")?;
 
-    Ok(indexer::extract_symbols(&tree, content, file, lang))
+    Ok(indexer::extract_symbols(&tree, &source, file, lang))
 }
 
 /// Validate a git ref string to prevent argument injection (CWE-88).
@@ -557,7 +534,7 @@ pub fn detect_changes(
 
     // Single git subprocess for all hunks across all files.
     let all_hunks = get_all_diff_hunks(scope, repo_root).unwrap_or_else(|e| {
-        eprintln!("wonk: warning: failed to get diff hunks: {e}");
+        crate::output::print_warning(&format!("failed to get diff hunks: {e}"));
         HashMap::new()
     });
 
@@ -573,7 +550,7 @@ pub fn detect_changes(
         let indexed_symbols = match query_indexed_symbols(conn, file) {
             Ok(syms) => syms,
             Err(e) => {
-                eprintln!("wonk: warning: failed to query symbols for {file}: {e}");
+                crate::output::print_warning(&format!("failed to query symbols for {file}: {e}"));
                 continue;
             }
         };
@@ -586,7 +563,7 @@ pub fn detect_changes(
         // diff), reusing the already-loaded indexed symbols.
         let ts_changes = detect_changed_symbols_with(conn, file, repo_root, &indexed_symbols)
             .unwrap_or_else(|e| {
-                eprintln!("wonk: warning: failed to detect changes for {file}: {e}");
+                crate::output::print_warning(&format!("failed to detect changes for {file}: {e}"));
                 Vec::new()
             });
 
@@ -788,6 +765,7 @@ mod tests {
     use crate::pipeline;
     use crate::types::{ChangeType, SymbolKind, SymbolRef};
     use std::fs;
+    use std::process::Command;
     use tempfile::TempDir;
 
     /// Returns true if git is available on this system.
@@ -1658,7 +1636,7 @@ diff --git a/src/lib.rs b/src/lib.rs
         let result = parse_all_diff_hunks(diff);
         // Pure deletion produces no new-side hunks.
         assert!(
-            result.is_empty() || result.get("src/lib.rs").map_or(true, |h| h.is_empty()),
+            result.is_empty() || result.get("src/lib.rs").is_none_or(|h| h.is_empty()),
             "pure deletion should produce no hunks"
         );
     }
@@ -1674,10 +1652,17 @@ diff --git a/src/lib.rs b/src/lib.rs
             line: 10,
             col: 0,
             end_line: Some(15),
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             scope: None,
             signature: "fn foo()".into(),
             language: "Rust".into(),
             doc_comment: None,
+            condition: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
         }];
 
         // Hunk is on lines 1-5, symbol is on lines 10-15
@@ -1698,10 +1683,17 @@ diff --git a/src/lib.rs b/src/lib.rs
             line: 5,
             col: 0,
             end_line: Some(10),
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             scope: None,
             signature: "fn bar()".into(),
             language: "Rust".into(),
             doc_comment: None,
+            condition: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
         }];
 
         // Hunk covers lines 6-8, inside symbol 5-10
@@ -1721,10 +1713,17 @@ diff --git a/src/lib.rs b/src/lib.rs
             line: 5,
             col: 0,
             end_line: Some(10),
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             scope: None,
             signature: "fn baz()".into(),
             language: "Rust".into(),
             doc_comment: None,
+            condition: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
         }];
 
         // Hunk starts before symbol, ends inside it
@@ -1744,9 +1743,16 @@ diff --git a/src/lib.rs b/src/lib.rs
             col: 0,
             end_line: None, // single-line symbol
             scope: None,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             signature: "const VAL: i32 = 42".into(),
             language: "Rust".into(),
             doc_comment: None,
+            condition: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
         }];
 
         // Hunk covers line 3
@@ -1766,10 +1772,17 @@ diff --git a/src/lib.rs b/src/lib.rs
                 line: 1,
                 col: 0,
                 end_line: Some(5),
+                end_col: None,
+                start_byte: None,
+                end_byte: None,
                 scope: None,
                 signature: "fn alpha()".into(),
                 language: "Rust".into(),
                 doc_comment: None,
+                condition: None,
+                visibility: None,
+                modifiers: Vec::new(),
+                generics: Vec::new(),
             },
             Symbol {
                 name: "beta".into(),
@@ -1778,10 +1791,17 @@ diff --git a/src/lib.rs b/src/lib.rs
                 line: 7,
                 col: 0,
                 end_line: Some(12),
+                end_col: None,
+                start_byte: None,
+                end_byte: None,
                 scope: None,
                 signature: "fn beta()".into(),
                 language: "Rust".into(),
                 doc_comment: None,
+                condition: None,
+                visibility: None,
+                modifiers: Vec::new(),
+                generics: Vec::new(),
             },
             Symbol {
                 name: "gamma".into(),
@@ -1790,10 +1810,17 @@ diff --git a/src/lib.rs b/src/lib.rs
                 line: 14,
                 col: 0,
                 end_line: Some(20),
+                end_col: None,
+                start_byte: None,
+                end_byte: None,
                 scope: None,
                 signature: "fn gamma()".into(),
                 language: "Rust".into(),
                 doc_comment: None,
+                condition: None,
+                visibility: None,
+                modifiers: Vec::new(),
+                generics: Vec::new(),
             },
         ];
 
@@ -1816,10 +1843,17 @@ diff --git a/src/lib.rs b/src/lib.rs
             line: 1,
             col: 0,
             end_line: Some(10),
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             scope: None,
             signature: "fn foo()".into(),
             language: "Rust".into(),
             doc_comment: None,
+            condition: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
         }];
 
         // Two hunks both overlap the same symbol
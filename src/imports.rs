@@ -0,0 +1,2270 @@
+//! Import path resolution.
+//!
+//! The indexer records each import exactly as written (`./utils`,
+//! `../lib/config`, `foo/bar.h`).  This module maps those strings onto
+//! indexed files and stores the result in `file_imports.resolved_file`, so
+//! reverse-dependency lookups can match edges exactly instead of guessing
+//! from file stems.  Imports that point outside the repo (standard library,
+//! third-party packages) stay unresolved.
+//!
+//! Go imports name packages rather than files, so Go imports inside a module
+//! (per `go.mod`) resolve to the package directory instead.  Rust `use`
+//! paths and `mod` declarations follow the module tree (`foo.rs` or
+//! `foo/mod.rs`, `#[path]` overrides) from the importing file.  Python
+//! imports resolve against the repo's package roots, including `src`
+//! layouts declared in `pyproject.toml` or `setup.cfg`.  TypeScript and
+//! JavaScript bare imports, including those in Vue and Svelte components, go
+//! through `tsconfig.json`/`jsconfig.json` `paths` and `baseUrl` first.  With
+//! `[ruby] rails = true`, Ruby constants (recorded as imports at index time)
+//! resolve by Rails autoloading conventions.  PHP `use` statements resolve
+//! through the PSR-4 mappings in `composer.json`.  Java imports resolve to
+//! the file declaring the class in the named package, and wildcard imports
+//! are expanded to the package's classes the importing file actually
+//! references.  Dart `package:` URIs resolve into the `lib/` directory of the
+//! repo package whose `pubspec.yaml` declares that name.  Shell `source` and
+//! `.` paths that start with a variable or command substitution
+//! (`"$(dirname "$0")/env.sh"`, `"$ROOT/lib.sh"`) are tried relative to the
+//! script's directory and then the repo root.
+//!
+//! Imports that do not resolve are marked `unresolvable` and left alone until
+//! a file that could satisfy them is added ([`retry_for_file`]) or a build
+//! file changes ([`retry_all`]), so updates only resolve what changed.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use rusqlite::Connection;
+
+/// Extensions tried when an import omits one, after the importing file's own.
+const EXTENSIONS: &[&str] = &[
+    "ts", "tsx", "js", "jsx", "mjs", "cjs", "py", "rb", "php", "go", "rs", "h", "hpp", "hh", "c",
+    "cc", "cpp",
+];
+
+/// File names that stand for their directory when a directory is imported.
+const INDEX_FILES: &[&str] = &[
+    "index.ts",
+    "index.tsx",
+    "index.js",
+    "index.jsx",
+    "__init__.py",
+    "mod.rs",
+];
+
+/// JavaScript and TypeScript source extensions, plus Vue and Svelte
+/// components whose scripts import the same way.
+const JS_EXTENSIONS: &[&str] = &[
+    "ts", "tsx", "mts", "cts", "js", "jsx", "mjs", "cjs", "vue", "svelte",
+];
+
+/// Compiler config files that carry `paths`/`baseUrl` for JS and TS.
+const TS_CONFIG_FILES: &[&str] = &["tsconfig.json", "jsconfig.json"];
+
+/// Python packaging files that may declare a package root.
+const PYTHON_PROJECT_FILES: &[&str] = &["pyproject.toml", "setup.cfg", "setup.py"];
+
+/// File names that root a Rust crate.
+const RUST_CRATE_ROOTS: &[&str] = &["lib.rs", "main.rs"];
+
+/// C and C++ header extensions.
+const HEADER_EXTENSIONS: &[&str] = &["h", "hh", "hpp", "hxx", "h++"];
+
+/// Build files whose contents change how imports resolve, besides
+/// [`TS_CONFIG_FILES`] and [`PYTHON_PROJECT_FILES`].
+const BUILD_FILES: &[&str] = &["go.mod", "composer.json", "pubspec.yaml"];
+
+/// C and C++ implementation-file extensions.
+const SOURCE_EXTENSIONS: &[&str] = &["c", "cc", "cpp", "cxx", "c++", "m", "mm"];
+
+/// Maps import strings onto the set of indexed files.
+#[derive(Debug, Default)]
+pub struct ImportResolver {
+    files: HashSet<String>,
+    /// File name → every indexed path with that name, for include lookups.
+    by_name: HashMap<String, Vec<String>>,
+    /// Go modules in the repo: (module path, repo-relative module directory).
+    go_modules: Vec<(String, String)>,
+    /// Directories holding at least one indexed Go file.
+    go_packages: HashSet<String>,
+    /// Extra Python import roots (repo-relative), tried before the repo root.
+    python_roots: Vec<String>,
+    /// `tsconfig.json`/`jsconfig.json` settings, deepest directory first.
+    ts_configs: Vec<TsConfig>,
+    /// Resolve Ruby constants through Rails autoload paths.
+    rails: bool,
+    /// PSR-4 mappings: namespace prefix (with trailing `\\`) → repo-relative
+    /// directories, longest prefix first.
+    psr4: Vec<(String, Vec<String>)>,
+    /// Java, Kotlin, and Scala top-level types (and Kotlin and Scala
+    /// top-level functions) by fully qualified name (`com.acme.Foo`) → file.
+    java_classes: HashMap<String, String>,
+    /// Dart packages in the repo: (pubspec name, repo-relative package
+    /// directory).
+    dart_packages: Vec<(String, String)>,
+}
+
+/// Module resolution settings from a `tsconfig.json` or `jsconfig.json`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TsConfig {
+    /// Repo-relative directory holding the config file.
+    pub dir: String,
+    /// Repo-relative `baseUrl`, if set.
+    pub base_url: Option<String>,
+    /// `paths` entries: pattern (at most one `*`) → repo-relative targets.
+    pub paths: Vec<(String, Vec<String>)>,
+}
+
+impl ImportResolver {
+    pub fn new(files: impl IntoIterator<Item = String>) -> Self {
+        let files: HashSet<String> = files.into_iter().collect();
+        let mut by_name: HashMap<String, Vec<String>> = HashMap::new();
+        for path in &files {
+            by_name
+                .entry(file_name(path).to_string())
+                .or_default()
+                .push(path.clone());
+        }
+        let go_packages = files
+            .iter()
+            .filter(|p| extension(p) == "go")
+            .map(|p| package_dir(p).to_string())
+            .collect();
+        Self {
+            files,
+            by_name,
+            go_modules: Vec::new(),
+            go_packages,
+            python_roots: Vec::new(),
+            ts_configs: Vec::new(),
+            rails: false,
+            psr4: Vec::new(),
+            java_classes: HashMap::new(),
+            dart_packages: Vec::new(),
+        }
+    }
+
+    /// Load the resolver with every file in the index.  With `repo_root`,
+    /// build files on disk (`go.mod`) are read as well.
+    pub fn load(conn: &Connection, repo_root: Option<&Path>) -> rusqlite::Result<Self> {
+        let files: Vec<String> = conn
+            .prepare("SELECT path FROM files")?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        let mut resolver = Self::new(files);
+        // Top-level Java, Kotlin, and Scala declarations are scoped by their
+        // package declaration.
+        let mut stmt = conn.prepare(
+            "SELECT scope, name, file FROM symbols \
+             WHERE language IN ('Java', 'Kotlin', 'Scala') \
+             AND kind IN ('class', 'interface', 'trait', 'enum', 'function') \
+             AND scope IS NOT NULL",
+        )?;
+        let classes = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+        for class in classes {
+            let (package, name, file) = class?;
+            resolver.add_java_class(&format!("{package}.{name}"), &file);
+        }
+        if let Some(root) = repo_root {
+            resolver.go_modules = discover_go_modules(root, &resolver.go_packages);
+            resolver.python_roots = discover_python_roots(root, &resolver.files);
+            for config in discover_ts_configs(root, &resolver.files) {
+                resolver.add_ts_config(config);
+            }
+            for (prefix, dirs) in discover_psr4(root, &resolver.files) {
+                resolver.add_psr4(&prefix, dirs);
+            }
+            for (name, dir) in discover_dart_packages(root, &resolver.files) {
+                resolver.add_dart_package(&name, &dir);
+            }
+            resolver.rails = crate::config::Config::load(Some(root))
+                .unwrap_or_default()
+                .ruby
+                .rails;
+        }
+        Ok(resolver)
+    }
+
+    /// Register a Go module rooted at `dir` (repo-relative, `.` for the root).
+    pub fn add_go_module(&mut self, module: &str, dir: &str) {
+        self.go_modules.push((module.to_string(), dir.to_string()));
+        // Longest module path first, so nested modules win.
+        self.go_modules
+            .sort_by_key(|m| std::cmp::Reverse(m.0.len()));
+    }
+
+    /// Register a Python import root (repo-relative), such as `src`.
+    pub fn add_python_root(&mut self, dir: &str) {
+        self.python_roots.push(dir.to_string());
+    }
+
+    /// Register a PSR-4 mapping of a namespace prefix (`App\\`) to
+    /// repo-relative directories.
+    pub fn add_psr4(&mut self, prefix: &str, dirs: Vec<String>) {
+        let prefix = format!("{}\\", prefix.trim_matches('\\'));
+        self.psr4.push((prefix, dirs));
+        self.psr4.sort_by_key(|m| std::cmp::Reverse(m.0.len()));
+    }
+
+    /// Register a Dart package named `name` rooted at `dir` (repo-relative,
+    /// empty for the root).
+    pub fn add_dart_package(&mut self, name: &str, dir: &str) {
+        self.dart_packages.push((name.to_string(), dir.to_string()));
+    }
+
+    /// Register the file declaring a Java type by its fully qualified name.
+    pub fn add_java_class(&mut self, name: &str, file: &str) {
+        self.java_classes.insert(name.to_string(), file.to_string());
+    }
+
+    /// Resolve Ruby constants by Rails autoloading conventions.
+    pub fn enable_rails(&mut self) {
+        self.rails = true;
+    }
+
+    /// Register the resolution settings of a `tsconfig.json`/`jsconfig.json`.
+    pub fn add_ts_config(&mut self, config: TsConfig) {
+        self.ts_configs.push(config);
+        self.ts_configs
+            .sort_by_key(|c| std::cmp::Reverse(c.dir.len()));
+    }
+
+    /// Resolve `import_path`, written in `source_file`, to an indexed file.
+    ///
+    /// Relative imports (`./x`, `../x`) resolve against the importing file's
+    /// directory.  Other imports are tried relative to the importing file's
+    /// directory and then to the repo root.  Each candidate is tried as
+    /// written, with an extension added, and as a directory index file.
+    /// C/C++ includes that still do not resolve are matched against include
+    /// directories anywhere in the repo (`net/socket.h` → `include/net/socket.h`)
+    /// when exactly one file fits.
+    pub fn resolve(&self, source_file: &str, import_path: &str) -> Option<String> {
+        match extension(source_file) {
+            "go" => return self.resolve_go(import_path),
+            "rs" => return self.resolve_rust(source_file, import_path),
+            "py" | "pyi" => return self.resolve_python(source_file, import_path),
+            "php" => return self.resolve_psr4(import_path),
+            "lua" => return self.resolve_lua(source_file, import_path),
+            "ex" | "exs" => return self.resolve_elixir(source_file, import_path),
+            "dart" => return self.resolve_dart(source_file, import_path),
+            // Extensionless files are only indexed as shebang shell scripts.
+            "" | "sh" | "bash" | "zsh" => return self.resolve_shell(source_file, import_path),
+            "java" | "kt" | "kts" | "scala" | "sc" => {
+                return self.resolve_java(source_file, import_path);
+            }
+            "rb" if self.rails && is_ruby_constant(import_path) => {
+                return self.resolve_rails_constant(source_file, import_path);
+            }
+            ext if JS_EXTENSIONS.contains(&ext) => {
+                if let Some(found) = self.resolve_ts_config(source_file, import_path) {
+                    return Some(found);
+                }
+            }
+            _ => {}
+        }
+        let source_dir = parent_dir(source_file);
+        let bases: Vec<String> = if import_path.starts_with("./") || import_path.starts_with("../")
+        {
+            vec![join(source_dir, import_path)?]
+        } else if import_path.starts_with('/') || import_path.is_empty() {
+            return None;
+        } else {
+            [join(source_dir, import_path), normalize(import_path)]
+                .into_iter()
+                .flatten()
+                .collect()
+        };
+
+        let own_ext = extension(source_file);
+        bases
+            .iter()
+            .find_map(|base| {
+                if JS_EXTENSIONS.contains(&own_ext) {
+                    self.lookup_js(base, own_ext)
+                } else {
+                    self.lookup(base, own_ext)
+                }
+            })
+            .or_else(|| {
+                is_c_family(own_ext)
+                    .then(|| self.include_suffix_match(import_path))
+                    .flatten()
+            })
+    }
+
+    /// Resolve a bare JS/TS import through the `paths` and `baseUrl` of the
+    /// nearest config above `source_file`.  `paths` patterns match exactly or
+    /// through their one `*`, longest prefix first.
+    fn resolve_ts_config(&self, source_file: &str, import_path: &str) -> Option<String> {
+        if import_path.starts_with('.') || import_path.starts_with('/') {
+            return None;
+        }
+        let config = self
+            .ts_configs
+            .iter()
+            .find(|c| c.dir.is_empty() || source_file.starts_with(&format!("{}/", c.dir)))?;
+
+        let mut matches: Vec<(usize, &str, &Vec<String>)> = config
+            .paths
+            .iter()
+            .filter_map(|(pattern, targets)| match pattern.split_once('*') {
+                None => (pattern == import_path).then_some((usize::MAX, "", targets)),
+                Some((prefix, suffix)) => import_path
+                    .strip_prefix(prefix)
+                    .and_then(|rest| rest.strip_suffix(suffix))
+                    .map(|star| (prefix.len(), star, targets)),
+            })
+            .collect();
+        matches.sort_by_key(|(len, _, _)| std::cmp::Reverse(*len));
+        let own_ext = extension(source_file);
+        for (_, star, targets) in matches {
+            for target in targets {
+                let candidate = normalize(&target.replacen('*', star, 1))?;
+                if let Some(found) = self.lookup_js(&candidate, own_ext) {
+                    return Some(found);
+                }
+            }
+        }
+        let base = config.base_url.as_deref()?;
+        self.lookup_js(&dir_join(base, import_path), own_ext)
+    }
+
+    /// [`Self::lookup`], also mapping compiled `.js` specifiers back to their
+    /// TypeScript sources (`./util.js` → `util.ts`).
+    fn lookup_js(&self, base: &str, own_ext: &str) -> Option<String> {
+        self.lookup(base, own_ext).or_else(|| {
+            let stem = base
+                .strip_suffix(".js")
+                .or_else(|| base.strip_suffix(".jsx"))
+                .or_else(|| base.strip_suffix(".mjs"))?;
+            ["ts", "tsx", "mts"]
+                .iter()
+                .map(|ext| format!("{stem}.{ext}"))
+                .find(|c| self.files.contains(c))
+        })
+    }
+
+    /// Resolve a fully qualified PHP class name to its file through the PSR-4
+    /// mappings: `App\\Service\\Mailer` with `App\\` → `src/` is
+    /// `src/Service/Mailer.php`.  Longer prefixes win.
+    fn resolve_psr4(&self, class: &str) -> Option<String> {
+        let class = class.trim_start_matches('\\');
+        self.psr4.iter().find_map(|(prefix, dirs)| {
+            let rest = class.strip_prefix(prefix.as_str())?;
+            let rel = format!("{}.php", rest.replace('\\', "/"));
+            dirs.iter()
+                .filter_map(|dir| normalize(&dir_join(dir, &rel)))
+                .find(|c| self.files.contains(c))
+        })
+    }
+
+    /// Resolve a Java, Kotlin, or Scala import to the file declaring the
+    /// imported type (or Kotlin or Scala top-level function).
+    ///
+    /// Members and nested types (`com.acme.Util.helper`, `com.acme.Outer.Inner`)
+    /// resolve to the file of the longest prefix naming a type, and static
+    /// wildcards (`com.acme.Util.*`) to the type's file.  Package wildcards
+    /// are expanded by [`resolve_pending`] instead.
+    fn resolve_java(&self, source_file: &str, import_path: &str) -> Option<String> {
+        let path = import_path.trim_end_matches(".*");
+        let mut name = path;
+        loop {
+            if let Some(file) = self.java_classes.get(name) {
+                return (file != source_file).then(|| file.clone());
+            }
+            name = &name[..name.rfind('.')?];
+        }
+    }
+
+    /// The files declaring the types of Java `package` among `names`.
+    fn java_package_members<'a>(
+        &'a self,
+        package: &'a str,
+        names: &'a [String],
+    ) -> impl Iterator<Item = (String, &'a String)> + 'a {
+        names.iter().filter_map(move |name| {
+            let qualified = format!("{package}.{name}");
+            let file = self.java_classes.get(&qualified)?;
+            Some((qualified, file))
+        })
+    }
+
+    /// Resolve a Ruby constant to the file Rails would autoload it from:
+    /// `Admin::UserPolicy` → `admin/user_policy.rb` under an `app/*`
+    /// directory (including `app/*/concerns`) or `lib/`, possibly inside an
+    /// engine.  When several files fit, the one in the importing file's
+    /// app or engine tree wins, else the first by path.
+    fn resolve_rails_constant(&self, source_file: &str, constant: &str) -> Option<String> {
+        let rel = format!(
+            "{}.rb",
+            constant
+                .split("::")
+                .map(underscore)
+                .collect::<Vec<_>>()
+                .join("/")
+        );
+        let suffix = format!("/{rel}");
+        let mut matches: Vec<&String> = self
+            .by_name
+            .get(file_name(&rel))?
+            .iter()
+            .filter(|p| {
+                p.strip_suffix(&suffix)
+                    .is_some_and(|prefix| is_rails_autoload_dir(&format!("{prefix}/")))
+            })
+            .collect();
+        matches.sort();
+        let local = matches.iter().find(|p| {
+            let root = p.split("app/").next().unwrap_or("");
+            source_file.starts_with(root)
+        });
+        let target = local.or(matches.first())?.to_string();
+        (target != source_file).then_some(target)
+    }
+
+    /// Resolve a Go import path to the package directory it names, when the
+    /// path lies inside one of the repo's modules.
+    fn resolve_go(&self, import_path: &str) -> Option<String> {
+        self.go_modules.iter().find_map(|(module, dir)| {
+            let rest = import_path.strip_prefix(module.as_str())?;
+            let rest = match rest {
+                "" => "",
+                r => r.strip_prefix('/')?,
+            };
+            let pkg = match (dir.as_str(), rest) {
+                (".", "") => ".".to_string(),
+                (".", rest) => normalize(rest)?,
+                (dir, "") => dir.to_string(),
+                (dir, rest) => join(dir, rest)?,
+            };
+            self.go_packages.contains(&pkg).then_some(pkg)
+        })
+    }
+
+    /// Resolve a Rust `mod` declaration or `use` path to the file of the
+    /// module it names.
+    ///
+    /// `use` paths resolve to the deepest module with a file (`crate::a::b::C`
+    /// → `a/b.rs`), falling back to the file of the module the path starts
+    /// from (`super::Item` → the parent module).  Paths into other crates
+    /// stay unresolved.
+    fn resolve_rust(&self, source_file: &str, import_path: &str) -> Option<String> {
+        let module_dir = rust_module_dir(source_file);
+
+        if let Some(rest) = import_path.strip_prefix("#[path = \"") {
+            // Path attributes are relative to the declaring file's directory.
+            let (path, _) = rest.split_once('"')?;
+            let target = join(parent_dir(source_file), path)?;
+            return self.files.contains(&target).then_some(target);
+        }
+        if let Some(name) = import_path.strip_prefix("mod ") {
+            return self.rust_module_file(&dir_join(&module_dir, name.trim()));
+        }
+
+        let mut segments = import_path.split("::").peekable();
+        let mut base = match *segments.peek()? {
+            "crate" => {
+                segments.next();
+                parent_dir(&self.rust_crate_root(source_file)?).to_string()
+            }
+            "self" => {
+                segments.next();
+                module_dir.clone()
+            }
+            // `super::...`, or a child module of the current one (2018
+            // uniform paths).
+            _ => module_dir.clone(),
+        };
+        while segments.next_if_eq(&"super").is_some() {
+            base = parent_dir(&base).to_string();
+        }
+
+        let rest: Vec<&str> = segments.filter(|s| *s != "*").collect();
+        let deepest = (1..=rest.len()).rev().find_map(|n| {
+            let dir = rest[..n]
+                .iter()
+                .fold(base.clone(), |dir, s| dir_join(&dir, s));
+            self.rust_module_file(&dir)
+        });
+        let target = match deepest {
+            Some(file) => file,
+            None if base == module_dir && !import_path.starts_with("self::") => return None,
+            None => self.rust_dir_module_file(&base, source_file)?,
+        };
+        (target != source_file).then_some(target)
+    }
+
+    /// Resolve a dotted Python import to the deepest module with a file.
+    ///
+    /// Relative imports (`.x`, `..pkg.x`) climb from the importing file's
+    /// package.  Absolute imports are tried under each package root, then
+    /// the repo root, then the importing file's directory (scripts run in
+    /// place).  Packages without `__init__.py` (namespace packages) are
+    /// walked through like any other directory.
+    fn resolve_python(&self, source_file: &str, import_path: &str) -> Option<String> {
+        let dots = import_path.len() - import_path.trim_start_matches('.').len();
+        let rest: Vec<&str> = import_path[dots..]
+            .split('.')
+            .filter(|s| !s.is_empty())
+            .collect();
+        let bases: Vec<String> = if dots > 0 {
+            let mut dir = parent_dir(source_file);
+            for _ in 1..dots {
+                if dir.is_empty() {
+                    return None;
+                }
+                dir = parent_dir(dir);
+            }
+            vec![dir.to_string()]
+        } else {
+            self.python_roots
+                .iter()
+                .map(String::as_str)
+                .chain(["", parent_dir(source_file)])
+                .map(str::to_string)
+                .collect()
+        };
+        let target = bases.iter().find_map(|base| {
+            (1..=rest.len()).rev().find_map(|n| {
+                let dir = rest[..n]
+                    .iter()
+                    .fold(base.clone(), |dir, s| dir_join(&dir, s));
+                self.python_module_file(&dir)
+            })
+        });
+        // `from . import x` where x is a name in the package's `__init__.py`.
+        let target = target.or_else(|| {
+            (dots > 0)
+                .then(|| self.python_module_file(&bases[0]))
+                .flatten()
+        })?;
+        (target != source_file).then_some(target)
+    }
+
+    /// `path.py`, else `path/__init__.py`.
+    fn python_module_file(&self, path: &str) -> Option<String> {
+        [
+            format!("{path}.py"),
+            format!("{path}.pyi"),
+            format!("{path}/__init__.py"),
+        ]
+        .into_iter()
+        .find(|c| !c.starts_with('/') && self.files.contains(c))
+    }
+
+    /// Resolve a Lua `require("a.b")` to `a/b.lua` or `a/b/init.lua` under
+    /// the repo root or a conventional `lua/`, `src/`, or `lib/` directory
+    /// (Neovim plugins, LuaRocks packages).
+    fn resolve_lua(&self, source_file: &str, module: &str) -> Option<String> {
+        let path = module.replace('.', "/");
+        ["", "lua", "src", "lib"]
+            .iter()
+            .find_map(|root| {
+                let base = dir_join(root, &path);
+                [format!("{base}.lua"), format!("{base}/init.lua")]
+                    .into_iter()
+                    .find(|c| self.files.contains(c))
+            })
+            .filter(|target| target != source_file)
+    }
+
+    /// Resolve an Elixir module to the file Mix compiles it from by
+    /// convention: `MyApp.Accounts.User` → `my_app/accounts/user.ex` under a
+    /// `lib/` directory, including each app of an umbrella project.  When
+    /// several apps define it, the importing file's own app wins.
+    fn resolve_elixir(&self, source_file: &str, module: &str) -> Option<String> {
+        let rel = format!(
+            "{}.ex",
+            module
+                .split('.')
+                .map(underscore)
+                .collect::<Vec<_>>()
+                .join("/")
+        );
+        let suffix = format!("lib/{rel}");
+        let mut matches: Vec<(&str, &String)> = self
+            .by_name
+            .get(file_name(&rel))?
+            .iter()
+            .filter_map(|p| {
+                let prefix = p.strip_suffix(&suffix)?;
+                (prefix.is_empty() || prefix.ends_with('/')).then_some((prefix, p))
+            })
+            .collect();
+        matches.sort();
+        let local = matches
+            .iter()
+            .rev()
+            .find(|(prefix, _)| source_file.starts_with(prefix));
+        let target = local.or(matches.first())?.1.to_string();
+        (target != source_file).then_some(target)
+    }
+
+    /// Resolve a Dart `import`/`export`/`part` URI.  `package:name/x.dart`
+    /// names `lib/x.dart` of the repo package called `name`; `dart:` and
+    /// other schemes are external; anything else is relative to the
+    /// importing file.
+    fn resolve_dart(&self, source_file: &str, uri: &str) -> Option<String> {
+        let target = if let Some(rest) = uri.strip_prefix("package:") {
+            let (name, path) = rest.split_once('/')?;
+            let (_, dir) = self.dart_packages.iter().find(|(n, _)| n == name)?;
+            join(&dir_join(dir, "lib"), path)?
+        } else if uri.contains(':') {
+            return None;
+        } else {
+            join(parent_dir(source_file), uri)?
+        };
+        (self.files.contains(&target) && target != source_file).then_some(target)
+    }
+
+    /// Resolve a shell `source`/`.` path.  A leading `$VAR`, `${...}`, or
+    /// `$(...)` usually names the script's directory or the repo root, so
+    /// the rest of the path is tried against both; other expansions cannot
+    /// be resolved statically.
+    fn resolve_shell(&self, source_file: &str, path: &str) -> Option<String> {
+        let rest = strip_shell_expansion(path).unwrap_or(path);
+        if rest.contains('$') {
+            return None;
+        }
+        let rel = match rest.strip_prefix('/') {
+            Some(rel) if rest.len() < path.len() => rel,
+            Some(_) => return None,
+            None => rest,
+        };
+        [join(parent_dir(source_file), rel), normalize(rel)]
+            .into_iter()
+            .flatten()
+            .find(|c| self.files.contains(c) && c != source_file)
+    }
+
+    /// The file defining the module whose children live in `dir`: `dir.rs`
+    /// or `dir/mod.rs`.
+    fn rust_module_file(&self, dir: &str) -> Option<String> {
+        [format!("{dir}.rs"), format!("{dir}/mod.rs")]
+            .into_iter()
+            .find(|c| self.files.contains(c))
+    }
+
+    /// Like [`Self::rust_module_file`], but also accepts a crate root when
+    /// `dir` is the importing crate's source directory.
+    fn rust_dir_module_file(&self, dir: &str, source_file: &str) -> Option<String> {
+        self.rust_module_file(dir).or_else(|| {
+            self.rust_crate_root(source_file)
+                .filter(|root| parent_dir(root) == dir)
+        })
+    }
+
+    /// The crate root (`lib.rs`, else `main.rs`) nearest above `source_file`.
+    fn rust_crate_root(&self, source_file: &str) -> Option<String> {
+        let mut dir = parent_dir(source_file);
+        loop {
+            if let Some(root) = RUST_CRATE_ROOTS
+                .iter()
+                .map(|name| dir_join(dir, name))
+                .find(|c| self.files.contains(c))
+            {
+                return Some(root);
+            }
+            if dir.is_empty() {
+                return None;
+            }
+            dir = parent_dir(dir);
+        }
+    }
+
+    fn lookup(&self, base: &str, own_ext: &str) -> Option<String> {
+        if self.files.contains(base) {
+            return Some(base.to_string());
+        }
+        let with_ext = std::iter::once(own_ext)
+            .chain(EXTENSIONS.iter().copied())
+            .filter(|e| !e.is_empty())
+            .map(|ext| format!("{base}.{ext}"));
+        let as_dir = INDEX_FILES.iter().map(|index| {
+            if base.is_empty() {
+                index.to_string()
+            } else {
+                format!("{base}/{index}")
+            }
+        });
+        with_ext.chain(as_dir).find(|c| self.files.contains(c))
+    }
+
+    /// The single indexed file whose path ends with `/<include>`.
+    fn include_suffix_match(&self, include: &str) -> Option<String> {
+        let include = normalize(include)?;
+        let suffix = format!("/{include}");
+        let mut matches = self
+            .by_name
+            .get(file_name(&include))?
+            .iter()
+            .filter(|p| p.ends_with(&suffix));
+        let first = matches.next()?;
+        matches.next().is_none().then(|| first.clone())
+    }
+
+    /// Whether `path` is a C/C++ header.
+    pub fn is_header(path: &str) -> bool {
+        HEADER_EXTENSIONS.contains(&extension(path))
+    }
+
+    /// Implementation files paired with a C/C++ header by convention: same
+    /// stem in the same directory, or the only source file in the repo with
+    /// that stem (`include/foo.h` ↔ `src/foo.c`).
+    pub fn paired_sources(&self, header: &str) -> Vec<String> {
+        if !Self::is_header(header) {
+            return Vec::new();
+        }
+        let stem = file_stem(header);
+        let candidates: Vec<&String> = SOURCE_EXTENSIONS
+            .iter()
+            .filter_map(|ext| self.by_name.get(&format!("{stem}.{ext}")))
+            .flatten()
+            .collect();
+        let dir = parent_dir(header);
+        let same_dir: Vec<String> = candidates
+            .iter()
+            .filter(|p| parent_dir(p) == dir)
+            .map(|p| p.to_string())
+            .collect();
+        if !same_dir.is_empty() {
+            return same_dir;
+        }
+        match candidates.as_slice() {
+            [only] => vec![only.to_string()],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Resolve every import that has not been tried since it was recorded or
+/// marked for a retry.
+///
+/// Run after indexing changes.  Imports that still do not resolve (standard
+/// library and third-party imports, mostly) are marked `unresolvable` and
+/// not tried again until [`retry_for_file`] or [`retry_all`] clears the mark.
+/// The resolver, which reads every indexed path and the repo's build files,
+/// is only loaded when something is pending.
+/// Java, Kotlin, and Scala package wildcards (`com.acme.*`) stay unresolved
+/// themselves, but gain one resolved import per class of the package the
+/// file references.
+/// Returns the number of newly resolved imports.
+pub fn resolve_pending(conn: &Connection, repo_root: Option<&Path>) -> Result<usize> {
+    let pending: Vec<(i64, String, String)> = conn
+        .prepare(
+            "SELECT id, source_file, import_path FROM file_imports \
+             WHERE resolved_file IS NULL AND unresolvable = 0",
+        )?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    if pending.is_empty() {
+        return Ok(0);
+    }
+    let resolver = ImportResolver::load(conn, repo_root)?;
+
+    let tx = conn.unchecked_transaction()?;
+    let mut resolved = 0usize;
+    {
+        let mut stmt = tx.prepare("UPDATE file_imports SET resolved_file = ?1 WHERE id = ?2")?;
+        let mut mark_stmt = tx.prepare("UPDATE file_imports SET unresolvable = 1 WHERE id = ?1")?;
+        for (id, source, import) in &pending {
+            if let Some(target) = resolver.resolve(source, import) {
+                stmt.execute(rusqlite::params![target, id])?;
+                resolved += 1;
+            } else {
+                mark_stmt.execute([id])?;
+            }
+        }
+    }
+    {
+        let mut names_stmt =
+            tx.prepare("SELECT DISTINCT name FROM \"references\" WHERE file = ?1")?;
+        let mut exists_stmt =
+            tx.prepare("SELECT 1 FROM file_imports WHERE source_file = ?1 AND import_path = ?2")?;
+        let mut insert_stmt = tx.prepare(
+            "INSERT INTO file_imports (source_file, import_path, resolved_file) \
+             VALUES (?1, ?2, ?3)",
+        )?;
+        for (_, source, import) in &pending {
+            let Some(package) = import.strip_suffix(".*") else {
+                continue;
+            };
+            if !matches!(extension(source), "java" | "kt" | "kts" | "scala" | "sc")
+                || resolver.resolve(source, import).is_some()
+            {
+                continue;
+            }
+            let names: Vec<String> = names_stmt
+                .query_map([source], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?;
+            for (qualified, file) in resolver.java_package_members(package, &names) {
+                if file != source && !exists_stmt.exists(rusqlite::params![source, qualified])? {
+                    insert_stmt.execute(rusqlite::params![source, qualified, file])?;
+                    resolved += 1;
+                }
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(resolved)
+}
+
+/// Clear the `unresolvable` mark of the imports that could name `file`, a
+/// file just added to the index or one whose declarations may have changed,
+/// so [`resolve_pending`] tries them again.
+///
+/// An import is retried when it mentions the file's stem or its directory's
+/// name, compared without case, `_`, or `-` (`UserSession` matches
+/// `user_session.rb`, `com.acme.*` any file in `acme/`).  Returns the number
+/// of imports marked for a retry.
+pub fn retry_for_file(conn: &Connection, file: &str) -> rusqlite::Result<usize> {
+    let key = |name: &str| -> String {
+        name.chars()
+            .filter(|c| !matches!(c, '_' | '-'))
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    let mut stmt = conn.prepare_cached(
+        "UPDATE file_imports SET unresolvable = 0 WHERE unresolvable = 1 \
+         AND instr(lower(replace(replace(import_path, '_', ''), '-', '')), ?1) > 0",
+    )?;
+    let mut retried = 0;
+    for name in [file_stem(file), file_name(parent_dir(file))] {
+        let name = key(name);
+        if !name.is_empty() {
+            retried += stmt.execute([name])?;
+        }
+    }
+    Ok(retried)
+}
+
+/// Clear the `unresolvable` mark of every import, so [`resolve_pending`]
+/// tries them all again: after a build file changed (see
+/// [`affects_resolution`]) or on a full `wonk update`.
+pub fn retry_all(conn: &Connection) -> rusqlite::Result<usize> {
+    conn.execute(
+        "UPDATE file_imports SET unresolvable = 0 WHERE unresolvable = 1",
+        [],
+    )
+}
+
+/// Whether a change to `path` can change how imports resolve: `go.mod`,
+/// `tsconfig.json`, `composer.json`, and the other build files the resolver
+/// reads.
+pub fn affects_resolution(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| {
+            BUILD_FILES
+                .iter()
+                .chain(TS_CONFIG_FILES)
+                .chain(PYTHON_PROJECT_FILES)
+                .any(|f| *f == name)
+        })
+}
+
+/// Whether imports resolving to a file in `path` depend on what it declares
+/// rather than on its path alone: Java, Kotlin, and Scala, whose imports name
+/// the types a file declares.
+pub fn declares_importable_names(path: &str) -> bool {
+    matches!(extension(path), "java" | "kt" | "kts" | "scala" | "sc")
+}
+
+/// The Go package directory of a file (`.` for files at the repo root).
+pub fn package_dir(file: &str) -> &str {
+    match parent_dir(file) {
+        "" => ".",
+        dir => dir,
+    }
+}
+
+/// Find the `go.mod` governing each Go package directory and read its
+/// module path.
+fn discover_go_modules(root: &Path, packages: &HashSet<String>) -> Vec<(String, String)> {
+    let mut seen: HashSet<PathBuf> = HashSet::new();
+    let mut modules = Vec::new();
+    for pkg in packages {
+        let mut dir = if pkg == "." {
+            root.to_path_buf()
+        } else {
+            root.join(pkg)
+        };
+        loop {
+            let go_mod = dir.join("go.mod");
+            if go_mod.is_file() {
+                if seen.insert(dir.clone())
+                    && let Some(module) = std::fs::read_to_string(&go_mod)
+                        .ok()
+                        .as_deref()
+                        .and_then(parse_go_module)
+                {
+                    let rel = dir
+                        .strip_prefix(root)
+                        .map(|p| p.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                    let rel = if rel.is_empty() { ".".to_string() } else { rel };
+                    modules.push((module, rel));
+                }
+                break;
+            }
+            if dir == root || !dir.pop() {
+                break;
+            }
+        }
+    }
+    modules.sort_by_key(|m| std::cmp::Reverse(m.0.len()));
+    modules
+}
+
+/// Read every `tsconfig.json`/`jsconfig.json` in a directory holding (or
+/// above) indexed JS/TS files.
+fn discover_ts_configs(root: &Path, files: &HashSet<String>) -> Vec<TsConfig> {
+    let mut dirs: HashSet<&str> = HashSet::new();
+    for file in files
+        .iter()
+        .filter(|f| JS_EXTENSIONS.contains(&extension(f)))
+    {
+        let mut dir = parent_dir(file);
+        while dirs.insert(dir) && !dir.is_empty() {
+            dir = parent_dir(dir);
+        }
+    }
+    let mut configs = Vec::new();
+    for dir in dirs {
+        // tsconfig.json wins over jsconfig.json in the same directory.
+        if let Some(config) = TS_CONFIG_FILES
+            .iter()
+            .find_map(|name| read_ts_config(root, &dir_join(dir, name), 0))
+        {
+            configs.push(TsConfig {
+                dir: dir.to_string(),
+                ..config
+            });
+        }
+    }
+    configs
+}
+
+/// Read a config file's `baseUrl` and `paths`, following a relative
+/// `extends` chain for settings the file does not set itself.
+fn read_ts_config(root: &Path, path: &str, depth: usize) -> Option<TsConfig> {
+    let content = std::fs::read_to_string(root.join(path)).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&strip_jsonc(&content)).ok()?;
+    let dir = parent_dir(path);
+    let options = json.get("compilerOptions");
+
+    let base_url = options
+        .and_then(|o| o.get("baseUrl"))
+        .and_then(|v| v.as_str())
+        .and_then(|b| join(dir, b));
+    let paths: Option<Vec<(String, Vec<String>)>> = options
+        .and_then(|o| o.get("paths"))
+        .and_then(|v| v.as_object())
+        .map(|map| {
+            // Targets are relative to baseUrl, or to the config file without one.
+            let target_base = base_url.as_deref().unwrap_or(dir);
+            map.iter()
+                .map(|(pattern, targets)| {
+                    let targets = targets
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|t| t.as_str())
+                        .filter_map(|t| join(target_base, t))
+                        .collect();
+                    (pattern.clone(), targets)
+                })
+                .collect()
+        });
+
+    let parent = json
+        .get("extends")
+        .and_then(|v| v.as_str())
+        .filter(|e| e.starts_with('.') && depth < 8)
+        .and_then(|e| {
+            let e = if e.ends_with(".json") {
+                e.to_string()
+            } else {
+                format!("{e}.json")
+            };
+            read_ts_config(root, &join(dir, &e)?, depth + 1)
+        });
+    let parent = parent.unwrap_or_default();
+    Some(TsConfig {
+        dir: dir.to_string(),
+        base_url: base_url.or(parent.base_url),
+        paths: paths.unwrap_or(parent.paths),
+    })
+}
+
+/// Strip `//` and `/* */` comments and trailing commas, which tsconfig
+/// files allow but JSON does not.
+fn strip_jsonc(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('/', Some('/')) => while chars.next_if(|&n| n != '\n').is_some() {},
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for n in chars.by_ref() {
+                    if prev == '*' && n == '/' {
+                        break;
+                    }
+                    prev = n;
+                }
+            }
+            ('}' | ']', _) => {
+                // Drop a trailing comma before the closing bracket.
+                let kept = out.trim_end().len();
+                if out[..kept].ends_with(',') {
+                    out.truncate(kept - 1);
+                }
+                out.push(c);
+            }
+            _ => {
+                in_string = c == '"';
+                out.push(c);
+            }
+        }
+    }
+    out
+}
+
+/// Read the PSR-4 mappings (`autoload` and `autoload-dev`) of every
+/// `composer.json` in a directory holding (or above) indexed PHP files.
+fn discover_psr4(root: &Path, files: &HashSet<String>) -> Vec<(String, Vec<String>)> {
+    let mut dirs: HashSet<&str> = HashSet::new();
+    for file in files.iter().filter(|f| extension(f) == "php") {
+        let mut dir = parent_dir(file);
+        while dirs.insert(dir) && !dir.is_empty() {
+            dir = parent_dir(dir);
+        }
+    }
+    let mut mappings = Vec::new();
+    for dir in dirs {
+        let Ok(content) = std::fs::read_to_string(root.join(dir).join("composer.json")) else {
+            continue;
+        };
+        mappings.extend(parse_psr4(&content, dir));
+    }
+    mappings
+}
+
+/// Read the package name from every `pubspec.yaml` in a directory holding
+/// (or above) indexed Dart files.
+fn discover_dart_packages(root: &Path, files: &HashSet<String>) -> Vec<(String, String)> {
+    let mut dirs: HashSet<&str> = HashSet::new();
+    for file in files.iter().filter(|f| extension(f) == "dart") {
+        let mut dir = parent_dir(file);
+        while dirs.insert(dir) && !dir.is_empty() {
+            dir = parent_dir(dir);
+        }
+    }
+    let mut packages = Vec::new();
+    for dir in dirs {
+        let Ok(content) = std::fs::read_to_string(root.join(dir).join("pubspec.yaml")) else {
+            continue;
+        };
+        if let Some(name) = parse_pubspec_name(&content) {
+            packages.push((name, dir.to_string()));
+        }
+    }
+    packages
+}
+
+/// The top-level `name:` of a `pubspec.yaml`.
+fn parse_pubspec_name(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let name = line.strip_prefix("name:")?.trim();
+        let name = name.trim_matches(|c| c == '"' || c == '\'');
+        (!name.is_empty()).then(|| name.to_string())
+    })
+}
+
+/// PSR-4 mappings from a `composer.json` in `dir`.  Values may be a single
+/// directory or a list.
+fn parse_psr4(content: &str, dir: &str) -> Vec<(String, Vec<String>)> {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(content) else {
+        return Vec::new();
+    };
+    let mut mappings = Vec::new();
+    for section in ["autoload", "autoload-dev"] {
+        let Some(map) = json
+            .get(section)
+            .and_then(|a| a.get("psr-4"))
+            .and_then(|m| m.as_object())
+        else {
+            continue;
+        };
+        for (prefix, value) in map {
+            let targets: Vec<&str> = match value {
+                serde_json::Value::String(s) => vec![s.as_str()],
+                serde_json::Value::Array(items) => {
+                    items.iter().filter_map(|v| v.as_str()).collect()
+                }
+                _ => continue,
+            };
+            let dirs = targets
+                .into_iter()
+                .filter_map(|t| match t.trim_end_matches('/') {
+                    "" | "." => Some(dir.to_string()),
+                    t => join(dir, t),
+                })
+                .collect();
+            mappings.push((prefix.clone(), dirs));
+        }
+    }
+    mappings
+}
+
+/// Find Python package roots: for each packaging file next to indexed Python
+/// code, the directories it names (`where`, `package-dir`, poetry `from`)
+/// plus a conventional `src` directory.
+fn discover_python_roots(root: &Path, files: &HashSet<String>) -> Vec<String> {
+    let mut dirs: HashSet<&str> = HashSet::new();
+    for file in files.iter().filter(|f| extension(f) == "py") {
+        let mut dir = parent_dir(file);
+        while dirs.insert(dir) && !dir.is_empty() {
+            dir = parent_dir(dir);
+        }
+        dirs.insert("");
+    }
+    let mut roots = Vec::new();
+    let mut sorted: Vec<&str> = dirs.iter().copied().collect();
+    sorted.sort_unstable();
+    for dir in sorted {
+        let contents: Vec<String> = PYTHON_PROJECT_FILES
+            .iter()
+            .filter_map(|name| std::fs::read_to_string(root.join(dir).join(name)).ok())
+            .collect();
+        if contents.is_empty() {
+            continue;
+        }
+        let mut named: Vec<String> = contents
+            .iter()
+            .flat_map(|c| python_package_dirs(c))
+            .collect();
+        named.push("src".to_string());
+        for name in named {
+            let candidate = dir_join(dir, name.trim_matches('/'));
+            if dirs.contains(candidate.as_str()) && !roots.contains(&candidate) {
+                roots.push(candidate);
+            }
+        }
+    }
+    roots
+}
+
+/// Directory names a packaging file declares as package roots.
+fn python_package_dirs(content: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut lines = content.lines().peekable();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim().trim_start_matches('{').trim();
+        let mut value = value.trim().to_string();
+        match key {
+            "where" | "from" => {}
+            "package-dir" | "package_dir" => {
+                // `package_dir =` with the mapping on the next line
+                // (setup.cfg), or `{"" = "src"}` (pyproject).
+                if value.is_empty()
+                    && let Some(next) = lines.peek()
+                {
+                    value = next.trim().to_string();
+                }
+                value = value.rsplit('=').next().unwrap_or("").to_string();
+            }
+            _ => {
+                // Poetry: `packages = [{ include = "pkg", from = "src" }]`.
+                if let Some((_, from)) = line.split_once("from") {
+                    let from = from.trim_start();
+                    if let Some(v) = from.strip_prefix('=') {
+                        value = v.to_string();
+                    } else {
+                        continue;
+                    }
+                } else {
+                    continue;
+                }
+            }
+        }
+        for part in value.split([',', '[', ']', '{', '}']) {
+            let part = part.trim().trim_matches(['"', '\'']).trim();
+            if !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '/' | '.'))
+                && part != "."
+            {
+                found.push(part.to_string());
+            }
+        }
+    }
+    found
+}
+
+/// The module path declared by a `go.mod` file.
+fn parse_go_module(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let rest = line.trim().strip_prefix("module")?;
+        rest.starts_with(char::is_whitespace)
+            .then(|| rest.trim().trim_matches('"').to_string())
+    })
+}
+
+/// The directory holding a Rust file's child modules: its own directory for
+/// `mod.rs` and crate roots, else a directory named after the file.
+fn rust_module_dir(file: &str) -> String {
+    let name = file_name(file);
+    if name == "mod.rs" || RUST_CRATE_ROOTS.contains(&name) {
+        parent_dir(file).to_string()
+    } else {
+        dir_join(parent_dir(file), file_stem(file))
+    }
+}
+
+/// `dir/name`, or just `name` at the repo root.
+fn dir_join(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{dir}/{name}")
+    }
+}
+
+/// Whether a Ruby import is a constant recorded for Rails autoloading
+/// (`User`, `Admin::User`) rather than a `require` path.
+pub fn is_ruby_constant(import: &str) -> bool {
+    import.starts_with(|c: char| c.is_ascii_uppercase())
+}
+
+/// Whether `prefix` (ending in `/`) is a Rails autoload root: `app/<dir>/`,
+/// `app/<dir>/concerns/`, or `lib/`, at the repo root or inside an engine.
+fn is_rails_autoload_dir(prefix: &str) -> bool {
+    if prefix == "lib/" || prefix.ends_with("/lib/") {
+        return true;
+    }
+    let Some(idx) = prefix.rfind("app/") else {
+        return false;
+    };
+    if idx > 0 && !prefix[..idx].ends_with('/') {
+        return false;
+    }
+    let rest = &prefix[idx + "app/".len()..];
+    let segments: Vec<&str> = rest.trim_end_matches('/').split('/').collect();
+    match segments.as_slice() {
+        [dir] => !dir.is_empty(),
+        [_, "concerns"] => true,
+        _ => false,
+    }
+}
+
+/// Rails' `underscore`: `UserPolicy` → `user_policy`, `HTMLParser` →
+/// `html_parser`.
+fn underscore(camel: &str) -> String {
+    let chars: Vec<char> = camel.chars().collect();
+    let mut out = String::with_capacity(camel.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() {
+            let prev = i.checked_sub(1).map(|p| chars[p]);
+            let next = chars.get(i + 1);
+            let boundary = match prev {
+                Some(p) if p.is_ascii_lowercase() || p.is_ascii_digit() => true,
+                Some(p) if p.is_ascii_uppercase() => next.is_some_and(|n| n.is_ascii_lowercase()),
+                _ => false,
+            };
+            if boundary {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn is_c_family(ext: &str) -> bool {
+    HEADER_EXTENSIONS.contains(&ext) || SOURCE_EXTENSIONS.contains(&ext)
+}
+
+/// The rest of a shell path after a leading `$NAME`, `${...}`, or `$(...)`.
+fn strip_shell_expansion(path: &str) -> Option<&str> {
+    let rest = path.strip_prefix('$')?;
+    let (open, close) = match rest.chars().next()? {
+        '(' => ('(', ')'),
+        '{' => ('{', '}'),
+        _ => {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            return (end > 0).then(|| &rest[end..]);
+        }
+    };
+    let mut depth = 0usize;
+    for (i, c) in rest.char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some(&rest[i + 1..]);
+            }
+        }
+    }
+    None
+}
+
+fn extension(path: &str) -> &str {
+    Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+}
+
+fn file_stem(path: &str) -> &str {
+    Path::new(path)
+        .file_stem()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn parent_dir(file: &str) -> &str {
+    file.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+/// Join a relative import onto a directory, returning `None` when `..`
+/// climbs out of the repo.
+fn join(dir: &str, path: &str) -> Option<String> {
+    if dir.is_empty() {
+        normalize(path)
+    } else {
+        normalize(&format!("{dir}/{path}"))
+    }
+}
+
+/// Collapse `.` and `..` segments of a repo-relative path.
+fn normalize(path: &str) -> Option<String> {
+    let mut parts: Vec<&str> = Vec::new();
+    for seg in path.split('/') {
+        match seg {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            s => parts.push(s),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use tempfile::TempDir;
+
+    fn files(paths: &[&str]) -> ImportResolver {
+        ImportResolver::new(paths.iter().map(|p| p.to_string()))
+    }
+
+    fn resolve(source: &str, import: &str, files: &ImportResolver) -> Option<String> {
+        files.resolve(source, import)
+    }
+
+    #[test]
+    fn resolves_relative_imports_with_extensions() {
+        let files = files(&["src/utils.ts", "src/lib/config.ts", "src/app.ts"]);
+        assert_eq!(
+            resolve("src/app.ts", "./utils", &files).as_deref(),
+            Some("src/utils.ts")
+        );
+        assert_eq!(
+            resolve("src/lib/config.ts", "../utils", &files).as_deref(),
+            Some("src/utils.ts")
+        );
+        assert_eq!(resolve("src/app.ts", "./missing", &files), None);
+        assert_eq!(resolve("app.ts", "../../etc/passwd", &files), None);
+    }
+
+    #[test]
+    fn resolves_directory_index_files() {
+        let files = files(&["src/components/index.tsx", "pkg/__init__.py"]);
+        assert_eq!(
+            resolve("src/app.tsx", "./components", &files).as_deref(),
+            Some("src/components/index.tsx")
+        );
+        assert_eq!(
+            resolve("main.py", "pkg", &files).as_deref(),
+            Some("pkg/__init__.py")
+        );
+    }
+
+    #[test]
+    fn bare_imports_try_source_dir_then_root() {
+        let files = files(&["src/util.h", "include/net/socket.h"]);
+        assert_eq!(
+            resolve("src/main.c", "util.h", &files).as_deref(),
+            Some("src/util.h")
+        );
+        assert_eq!(
+            resolve("src/main.c", "include/net/socket.h", &files).as_deref(),
+            Some("include/net/socket.h")
+        );
+        // Same stem but not the imported path.
+        assert_eq!(resolve("src/main.c", "stdio.h", &files), None);
+        assert_eq!(resolve("src/app.ts", "react", &files), None);
+    }
+
+    #[test]
+    fn c_includes_match_include_directories() {
+        let files = files(&[
+            "include/net/socket.h",
+            "src/net.c",
+            "lib/a/util.h",
+            "lib/b/util.h",
+        ]);
+        assert_eq!(
+            resolve("src/net.c", "net/socket.h", &files).as_deref(),
+            Some("include/net/socket.h")
+        );
+        assert_eq!(
+            resolve("src/net.c", "socket.h", &files).as_deref(),
+            Some("include/net/socket.h")
+        );
+        // Ambiguous: two util.h files.
+        assert_eq!(resolve("src/net.c", "util.h", &files), None);
+        // Not applied to other languages.
+        assert_eq!(resolve("src/app.ts", "socket.h", &files), None);
+    }
+
+    #[test]
+    fn pairs_headers_with_sources() {
+        let files = files(&[
+            "src/foo.h",
+            "src/foo.c",
+            "include/bar.hpp",
+            "src/bar.cpp",
+            "include/baz.h",
+            "a/baz.c",
+            "b/baz.c",
+        ]);
+        assert_eq!(files.paired_sources("src/foo.h"), ["src/foo.c"]);
+        assert_eq!(files.paired_sources("include/bar.hpp"), ["src/bar.cpp"]);
+        assert!(files.paired_sources("include/baz.h").is_empty());
+        assert!(files.paired_sources("src/foo.c").is_empty());
+    }
+
+    #[test]
+    fn rust_mod_declarations_follow_the_module_tree() {
+        let files = files(&[
+            "src/lib.rs",
+            "src/db.rs",
+            "src/net/mod.rs",
+            "src/net/tcp.rs",
+            "src/sys/unix.rs",
+        ]);
+        assert_eq!(
+            resolve("src/lib.rs", "mod db", &files).as_deref(),
+            Some("src/db.rs")
+        );
+        assert_eq!(
+            resolve("src/lib.rs", "mod net", &files).as_deref(),
+            Some("src/net/mod.rs")
+        );
+        assert_eq!(
+            resolve("src/net/mod.rs", "mod tcp", &files).as_deref(),
+            Some("src/net/tcp.rs")
+        );
+        assert_eq!(
+            resolve("src/lib.rs", "#[path = \"sys/unix.rs\"] mod sys", &files).as_deref(),
+            Some("src/sys/unix.rs")
+        );
+        assert_eq!(resolve("src/lib.rs", "mod missing", &files), None);
+    }
+
+    #[test]
+    fn rust_use_paths_resolve_to_module_files() {
+        let files = files(&[
+            "src/main.rs",
+            "src/types.rs",
+            "src/net.rs",
+            "src/net/tcp.rs",
+            "src/net/tcp/frame.rs",
+        ]);
+        let r = |source, import| resolve(source, import, &files);
+        assert_eq!(
+            r("src/net/tcp.rs", "crate::types::Symbol").as_deref(),
+            Some("src/types.rs")
+        );
+        assert_eq!(
+            r("src/main.rs", "crate::net::tcp::Stream").as_deref(),
+            Some("src/net/tcp.rs")
+        );
+        // Child modules by bare name, and via self.
+        assert_eq!(
+            r("src/net/tcp.rs", "frame::Frame").as_deref(),
+            Some("src/net/tcp/frame.rs")
+        );
+        assert_eq!(
+            r("src/net.rs", "self::tcp::*").as_deref(),
+            Some("src/net/tcp.rs")
+        );
+        // super:: walks up the module tree; items land on the module file.
+        assert_eq!(
+            r("src/net/tcp/frame.rs", "super::Stream").as_deref(),
+            Some("src/net/tcp.rs")
+        );
+        assert_eq!(
+            r("src/net/tcp/frame.rs", "super::super::super::types::Kind").as_deref(),
+            Some("src/types.rs")
+        );
+        assert_eq!(
+            r("src/net/tcp.rs", "super::super::Config").as_deref(),
+            Some("src/main.rs")
+        );
+        // Other crates and items of the importing crate root stay unresolved.
+        assert_eq!(r("src/main.rs", "std::collections::HashMap"), None);
+        assert_eq!(r("src/main.rs", "serde::Serialize"), None);
+        assert_eq!(r("src/main.rs", "crate::Config"), None);
+    }
+
+    #[test]
+    fn python_imports_resolve_to_module_files() {
+        let mut files = files(&[
+            "src/app/__init__.py",
+            "src/app/models.py",
+            "src/app/views/__init__.py",
+            "src/app/views/home.py",
+            "src/nspkg/tool.py",
+            "scripts/run.py",
+            "scripts/helpers.py",
+        ]);
+        files.add_python_root("src");
+        let r = |source, import| resolve(source, import, &files);
+
+        // Submodule import, then a name inside a module.
+        assert_eq!(
+            r("scripts/run.py", "app.views.home").as_deref(),
+            Some("src/app/views/home.py")
+        );
+        assert_eq!(
+            r("scripts/run.py", "app.models.User").as_deref(),
+            Some("src/app/models.py")
+        );
+        assert_eq!(
+            r("scripts/run.py", "app.views").as_deref(),
+            Some("src/app/views/__init__.py")
+        );
+        // Namespace package (no __init__.py).
+        assert_eq!(
+            r("scripts/run.py", "nspkg.tool.main").as_deref(),
+            Some("src/nspkg/tool.py")
+        );
+        // Sibling scripts, relative imports, and names from __init__.py.
+        assert_eq!(
+            r("scripts/run.py", "helpers.setup").as_deref(),
+            Some("scripts/helpers.py")
+        );
+        assert_eq!(
+            r("src/app/views/home.py", "..models.User").as_deref(),
+            Some("src/app/models.py")
+        );
+        assert_eq!(
+            r("src/app/views/home.py", ".Base").as_deref(),
+            Some("src/app/views/__init__.py")
+        );
+        assert_eq!(r("src/app/models.py", "os.path.join"), None);
+        assert_eq!(r("src/app/models.py", "....too.far"), None);
+    }
+
+    #[test]
+    fn lua_requires_resolve_to_module_files() {
+        let files = files(&[
+            "lua/plugin/init.lua",
+            "lua/plugin/config.lua",
+            "app/util.lua",
+            "main.lua",
+        ]);
+        let r = |source, import| resolve(source, import, &files);
+
+        assert_eq!(r("main.lua", "app.util").as_deref(), Some("app/util.lua"));
+        assert_eq!(
+            r("main.lua", "plugin.config").as_deref(),
+            Some("lua/plugin/config.lua")
+        );
+        assert_eq!(
+            r("app/util.lua", "plugin").as_deref(),
+            Some("lua/plugin/init.lua")
+        );
+        assert_eq!(r("main.lua", "cjson"), None);
+    }
+
+    #[test]
+    fn elixir_modules_resolve_under_lib() {
+        let files = files(&[
+            "lib/my_app/accounts/user.ex",
+            "lib/my_app/web.ex",
+            "apps/billing/lib/my_app/web.ex",
+            "apps/billing/lib/billing/invoice.ex",
+            "test/my_app/accounts_test.exs",
+        ]);
+        let r = |source, import| resolve(source, import, &files);
+
+        assert_eq!(
+            r("test/my_app/accounts_test.exs", "MyApp.Accounts.User").as_deref(),
+            Some("lib/my_app/accounts/user.ex")
+        );
+        assert_eq!(
+            r("lib/my_app/accounts/user.ex", "MyApp.Web").as_deref(),
+            Some("lib/my_app/web.ex")
+        );
+        assert_eq!(
+            r("apps/billing/lib/billing/invoice.ex", "MyApp.Web").as_deref(),
+            Some("apps/billing/lib/my_app/web.ex")
+        );
+        assert_eq!(r("lib/my_app/web.ex", "Ecto.Query"), None);
+    }
+
+    #[test]
+    fn shell_sources_resolve_from_script_dir_or_root() {
+        let files = files(&[
+            "scripts/deploy.sh",
+            "scripts/env.sh",
+            "scripts/lib/common.sh",
+            "lib/log.sh",
+            "bin/release",
+        ]);
+        let r = |source, import| resolve(source, import, &files);
+
+        assert_eq!(
+            r("scripts/deploy.sh", "./lib/common.sh").as_deref(),
+            Some("scripts/lib/common.sh")
+        );
+        assert_eq!(
+            r("scripts/deploy.sh", "$(dirname \"$0\")/env.sh").as_deref(),
+            Some("scripts/env.sh")
+        );
+        assert_eq!(
+            r("scripts/deploy.sh", "${BASH_SOURCE%/*}/lib/common.sh").as_deref(),
+            Some("scripts/lib/common.sh")
+        );
+        assert_eq!(
+            r("scripts/deploy.sh", "$ROOT/lib/log.sh").as_deref(),
+            Some("lib/log.sh")
+        );
+        // Shebang scripts have no extension.
+        assert_eq!(
+            r("bin/release", "scripts/env.sh").as_deref(),
+            Some("scripts/env.sh")
+        );
+        assert_eq!(r("scripts/deploy.sh", "/etc/profile"), None);
+        assert_eq!(r("scripts/deploy.sh", "$HOME/$PROFILE.sh"), None);
+    }
+
+    #[test]
+    fn dart_package_uris_resolve_under_lib() {
+        let mut files = files(&[
+            "app/lib/main.dart",
+            "app/lib/src/home.dart",
+            "app/lib/src/home.g.dart",
+            "packages/ui/lib/button.dart",
+        ]);
+        files.add_dart_package("my_app", "app");
+        files.add_dart_package("ui", "packages/ui");
+        let r = |source, import| resolve(source, import, &files);
+
+        assert_eq!(
+            r("app/lib/main.dart", "package:my_app/src/home.dart").as_deref(),
+            Some("app/lib/src/home.dart")
+        );
+        assert_eq!(
+            r("app/lib/main.dart", "package:ui/button.dart").as_deref(),
+            Some("packages/ui/lib/button.dart")
+        );
+        assert_eq!(
+            r("app/lib/src/home.dart", "home.g.dart").as_deref(),
+            Some("app/lib/src/home.g.dart")
+        );
+        assert_eq!(
+            r("app/lib/src/home.dart", "../main.dart").as_deref(),
+            Some("app/lib/main.dart")
+        );
+        assert_eq!(
+            r("app/lib/main.dart", "package:flutter/material.dart"),
+            None
+        );
+        assert_eq!(r("app/lib/main.dart", "dart:async"), None);
+    }
+
+    #[test]
+    fn reads_pubspec_name() {
+        let pubspec = "name: my_app\ndescription: A Flutter app.\ndependencies:\n  name: nested\n";
+        assert_eq!(parse_pubspec_name(pubspec).as_deref(), Some("my_app"));
+        assert_eq!(parse_pubspec_name("description: none\n"), None);
+    }
+
+    #[test]
+    fn reads_python_package_roots() {
+        assert_eq!(
+            python_package_dirs("[tool.setuptools.packages.find]\nwhere = [\"lib\"]\n"),
+            ["lib"]
+        );
+        assert_eq!(
+            python_package_dirs("[options]\npackage_dir =\n    =source\n"),
+            ["source"]
+        );
+        assert_eq!(
+            python_package_dirs("[tool.setuptools]\npackage-dir = {\"\" = \"code\"}\n"),
+            ["code"]
+        );
+        assert_eq!(
+            python_package_dirs("packages = [{ include = \"app\", from = \"pysrc\" }]\n"),
+            ["pysrc"]
+        );
+
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("pyproject.toml"),
+            "[project]\nname = \"x\"\n",
+        )
+        .unwrap();
+        let files: HashSet<String> = ["src/app/__init__.py".to_string()].into();
+        assert_eq!(discover_python_roots(dir.path(), &files), ["src"]);
+    }
+
+    #[test]
+    fn ts_imports_use_config_paths_and_base_url() {
+        let mut files = files(&[
+            "web/src/app.ts",
+            "web/src/utils/index.ts",
+            "web/src/lib/format.ts",
+            "web/src/components/Button.tsx",
+            "web/types/env.d.ts",
+            "shared/date.ts",
+        ]);
+        files.add_ts_config(TsConfig {
+            dir: "web".to_string(),
+            base_url: Some("web/src".to_string()),
+            paths: vec![
+                ("@app/*".to_string(), vec!["web/src/*".to_string()]),
+                (
+                    "@shared/*".to_string(),
+                    vec!["web/missing/*".to_string(), "shared/*".to_string()],
+                ),
+                ("env".to_string(), vec!["web/types/env.d.ts".to_string()]),
+            ],
+        });
+        let r = |import| resolve("web/src/app.ts", import, &files);
+
+        assert_eq!(r("@app/utils").as_deref(), Some("web/src/utils/index.ts"));
+        assert_eq!(
+            r("@app/components/Button").as_deref(),
+            Some("web/src/components/Button.tsx")
+        );
+        // Later targets are tried when earlier ones do not exist.
+        assert_eq!(r("@shared/date").as_deref(), Some("shared/date.ts"));
+        assert_eq!(r("env").as_deref(), Some("web/types/env.d.ts"));
+        // baseUrl, and `.js` specifiers of TypeScript sources.
+        assert_eq!(r("lib/format").as_deref(), Some("web/src/lib/format.ts"));
+        assert_eq!(
+            r("./lib/format.js").as_deref(),
+            Some("web/src/lib/format.ts")
+        );
+        assert_eq!(r("react"), None);
+        // Files outside the config's directory ignore it.
+        assert_eq!(resolve("shared/date.ts", "@app/utils", &files), None);
+    }
+
+    #[test]
+    fn components_import_like_typescript() {
+        let mut files = files(&[
+            "src/App.vue",
+            "src/components/UserCard.vue",
+            "src/stores/user.ts",
+            "src/routes/+page.svelte",
+            "src/lib/Counter.svelte",
+        ]);
+        files.add_ts_config(TsConfig {
+            dir: String::new(),
+            base_url: None,
+            paths: vec![
+                ("@/*".to_string(), vec!["src/*".to_string()]),
+                ("$lib/*".to_string(), vec!["src/lib/*".to_string()]),
+            ],
+        });
+        let r = |source, import| resolve(source, import, &files);
+
+        assert_eq!(
+            r("src/App.vue", "@/components/UserCard.vue").as_deref(),
+            Some("src/components/UserCard.vue")
+        );
+        assert_eq!(
+            r("src/App.vue", "./stores/user").as_deref(),
+            Some("src/stores/user.ts")
+        );
+        assert_eq!(
+            r("src/stores/user.ts", "../components/UserCard.vue").as_deref(),
+            Some("src/components/UserCard.vue")
+        );
+        assert_eq!(
+            r("src/routes/+page.svelte", "$lib/Counter.svelte").as_deref(),
+            Some("src/lib/Counter.svelte")
+        );
+        assert_eq!(
+            r("src/routes/+page.svelte", "../stores/user").as_deref(),
+            Some("src/stores/user.ts")
+        );
+    }
+
+    #[test]
+    fn reads_tsconfig_with_comments_and_extends() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("app")).unwrap();
+        std::fs::write(
+            dir.path().join("tsconfig.base.json"),
+            "{\n  // shared\n  \"compilerOptions\": {\n    \"baseUrl\": \".\",\n    \"paths\": { \"@lib/*\": [\"libs/*\",], },\n  },\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("app/tsconfig.json"),
+            "/* app */ { \"extends\": \"../tsconfig.base\", \"compilerOptions\": { \"strict\": true } }",
+        )
+        .unwrap();
+        let files: HashSet<String> = ["app/main.ts".to_string()].into();
+        assert_eq!(
+            discover_ts_configs(dir.path(), &files),
+            [TsConfig {
+                dir: "app".to_string(),
+                base_url: Some(String::new()),
+                paths: vec![("@lib/*".to_string(), vec!["libs/*".to_string()])],
+            }]
+        );
+        assert_eq!(strip_jsonc("{\"a\": \"//x,}\",}"), "{\"a\": \"//x,}\"}");
+    }
+
+    #[test]
+    fn rails_constants_resolve_to_autoload_paths() {
+        let mut files = files(&[
+            "app/controllers/admin/reports_controller.rb",
+            "app/models/user.rb",
+            "app/models/concerns/searchable.rb",
+            "app/services/admin/html_exporter.rb",
+            "lib/tasks/cleanup.rb",
+            "engines/billing/app/models/invoice.rb",
+            "vendor/gems/foo/user.rb",
+        ]);
+        let source = "app/controllers/admin/reports_controller.rb";
+        assert_eq!(resolve(source, "User", &files), None, "off by default");
+
+        files.enable_rails();
+        let r = |import| resolve(source, import, &files);
+        assert_eq!(r("User").as_deref(), Some("app/models/user.rb"));
+        assert_eq!(
+            r("Searchable").as_deref(),
+            Some("app/models/concerns/searchable.rb")
+        );
+        assert_eq!(
+            r("Admin::HTMLExporter").as_deref(),
+            Some("app/services/admin/html_exporter.rb")
+        );
+        assert_eq!(r("Tasks::Cleanup").as_deref(), Some("lib/tasks/cleanup.rb"));
+        assert_eq!(
+            r("Invoice").as_deref(),
+            Some("engines/billing/app/models/invoice.rb")
+        );
+        assert_eq!(r("ActiveRecord::Base"), None);
+    }
+
+    #[test]
+    fn rails_underscore() {
+        assert_eq!(underscore("User"), "user");
+        assert_eq!(underscore("UserPolicy"), "user_policy");
+        assert_eq!(underscore("HTMLParser"), "html_parser");
+        assert_eq!(underscore("OAuth2Client"), "o_auth2_client");
+    }
+
+    #[test]
+    fn php_uses_resolve_through_psr4() {
+        let mut files = files(&[
+            "src/Service/Mailer.php",
+            "src/Http/Controller.php",
+            "modules/billing/src/Invoice.php",
+            "tests/Unit/MailerTest.php",
+        ]);
+        files.add_psr4("App\\", vec!["src".to_string()]);
+        files.add_psr4(
+            "App\\Billing\\",
+            vec!["missing".to_string(), "modules/billing/src".to_string()],
+        );
+        files.add_psr4("Tests\\", vec![String::new()]);
+        let r = |import| resolve("src/Http/Controller.php", import, &files);
+
+        assert_eq!(
+            r("App\\Service\\Mailer").as_deref(),
+            Some("src/Service/Mailer.php")
+        );
+        assert_eq!(
+            r("\\App\\Billing\\Invoice").as_deref(),
+            Some("modules/billing/src/Invoice.php")
+        );
+        assert_eq!(
+            r("Tests\\tests\\Unit\\MailerTest").as_deref(),
+            Some("tests/Unit/MailerTest.php")
+        );
+        assert_eq!(r("Psr\\Log\\LoggerInterface"), None);
+    }
+
+    #[test]
+    fn reads_composer_psr4() {
+        let json = r#"{
+            "autoload": {"psr-4": {"App\\": "src/", "Lib\\": ["lib", "vendor-lib/"]}},
+            "autoload-dev": {"psr-4": {"Tests\\": "tests/"}}
+        }"#;
+        let mut mappings = parse_psr4(json, "api");
+        mappings.sort();
+        assert_eq!(
+            mappings,
+            [
+                ("App\\".to_string(), vec!["api/src".to_string()]),
+                (
+                    "Lib\\".to_string(),
+                    vec!["api/lib".to_string(), "api/vendor-lib".to_string()]
+                ),
+                ("Tests\\".to_string(), vec!["api/tests".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn java_imports_resolve_to_declaring_files() {
+        let mut files = files(&[
+            "src/main/java/com/acme/Foo.java",
+            "src/main/java/com/acme/Util.java",
+            "legacy/Bar.java",
+            "src/main/java/com/acme/app/App.java",
+        ]);
+        files.add_java_class("com.acme.Foo", "src/main/java/com/acme/Foo.java");
+        files.add_java_class("com.acme.Util", "src/main/java/com/acme/Util.java");
+        // Declared package, not directory layout, decides.
+        files.add_java_class("com.acme.Bar", "legacy/Bar.java");
+        let r = |import| resolve("src/main/java/com/acme/app/App.java", import, &files);
+
+        assert_eq!(
+            r("com.acme.Foo").as_deref(),
+            Some("src/main/java/com/acme/Foo.java")
+        );
+        assert_eq!(r("com.acme.Bar").as_deref(), Some("legacy/Bar.java"));
+        assert_eq!(
+            r("com.acme.Util.helper").as_deref(),
+            Some("src/main/java/com/acme/Util.java")
+        );
+        assert_eq!(
+            r("com.acme.Util.*").as_deref(),
+            Some("src/main/java/com/acme/Util.java")
+        );
+        assert_eq!(r("com.acme.*"), None);
+        assert_eq!(r("java.util.List"), None);
+    }
+
+    #[test]
+    fn go_imports_resolve_to_package_directories() {
+        let mut files = files(&[
+            "main.go",
+            "internal/store/store.go",
+            "internal/store/store_test.go",
+            "tools/gen/main.go",
+        ]);
+        files.add_go_module("example.com/app", ".");
+        files.add_go_module("example.com/app/tools", "tools");
+
+        assert_eq!(
+            resolve("main.go", "example.com/app/internal/store", &files).as_deref(),
+            Some("internal/store")
+        );
+        assert_eq!(
+            resolve("internal/store/store.go", "example.com/app", &files).as_deref(),
+            Some(".")
+        );
+        assert_eq!(
+            resolve("main.go", "example.com/app/tools/gen", &files).as_deref(),
+            Some("tools/gen")
+        );
+        assert_eq!(resolve("main.go", "fmt", &files), None);
+        assert_eq!(resolve("main.go", "example.com/app/missing", &files), None);
+        assert_eq!(resolve("main.go", "example.com/application", &files), None);
+    }
+
+    #[test]
+    fn reads_go_mod_module_paths() {
+        assert_eq!(
+            parse_go_module("// comment\nmodule example.com/app\n\ngo 1.22\n").as_deref(),
+            Some("example.com/app")
+        );
+        assert_eq!(parse_go_module("go 1.22\n"), None);
+
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("go.mod"), "module example.com/app\n").unwrap();
+        std::fs::create_dir_all(dir.path().join("pkg/a")).unwrap();
+        let packages = HashSet::from(["pkg/a".to_string(), ".".to_string()]);
+        assert_eq!(
+            discover_go_modules(dir.path(), &packages),
+            [("example.com/app".to_string(), ".".to_string())]
+        );
+    }
+
+    #[test]
+    fn resolve_pending_updates_unresolved_rows() {
+        let dir = TempDir::new().unwrap();
+        let conn = db::open(&dir.path().join("index.db")).unwrap();
+        for path in ["src/app.ts", "src/utils.ts"] {
+            conn.execute(
+                "INSERT INTO files (path, language, hash, last_indexed) VALUES (?1, 'TypeScript', 'h', 0)",
+                [path],
+            )
+            .unwrap();
+        }
+        for import in ["./utils", "react"] {
+            conn.execute(
+                "INSERT INTO file_imports (source_file, import_path) VALUES ('src/app.ts', ?1)",
+                [import],
+            )
+            .unwrap();
+        }
+
+        assert_eq!(resolve_pending(&conn, None).unwrap(), 1);
+        let resolved: Vec<Option<String>> = conn
+            .prepare("SELECT resolved_file FROM file_imports ORDER BY import_path")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(resolved, [Some("src/utils.ts".to_string()), None]);
+
+        // Already-resolved rows are not revisited.
+        assert_eq!(resolve_pending(&conn, None).unwrap(), 0);
+    }
+
+    #[test]
+    fn resolve_pending_expands_java_wildcards_to_referenced_classes() {
+        let dir = TempDir::new().unwrap();
+        let conn = db::open(&dir.path().join("index.db")).unwrap();
+        for (name, file) in [("Foo", "acme/Foo.java"), ("Bar", "acme/Bar.java")] {
+            conn.execute(
+                "INSERT INTO symbols (name, kind, file, line, col, scope, language) \
+                 VALUES (?1, 'class', ?2, 1, 0, 'com.acme', 'Java')",
+                [name, file],
+            )
+            .unwrap();
+        }
+        for name in ["Foo", "String"] {
+            conn.execute(
+                "INSERT INTO \"references\" (name, file, line, col) VALUES (?1, 'App.java', 3, 0)",
+                [name],
+            )
+            .unwrap();
+        }
+        conn.execute(
+            "INSERT INTO file_imports (source_file, import_path) VALUES ('App.java', 'com.acme.*')",
+            [],
+        )
+        .unwrap();
+
+        assert_eq!(resolve_pending(&conn, None).unwrap(), 1);
+        let rows: Vec<(String, Option<String>)> = conn
+            .prepare("SELECT import_path, resolved_file FROM file_imports ORDER BY import_path")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("com.acme.*".to_string(), None),
+                (
+                    "com.acme.Foo".to_string(),
+                    Some("acme/Foo.java".to_string())
+                ),
+            ]
+        );
+
+        // Retrying the wildcard does not duplicate its expansions.
+        assert_eq!(retry_for_file(&conn, "acme/Baz.java").unwrap(), 1);
+        assert_eq!(resolve_pending(&conn, None).unwrap(), 0);
+    }
+
+    #[test]
+    fn unresolvable_imports_wait_for_a_file_that_could_satisfy_them() {
+        let dir = TempDir::new().unwrap();
+        let conn = db::open(&dir.path().join("index.db")).unwrap();
+        let add_file = |path: &str| {
+            conn.execute(
+                "INSERT INTO files (path, language, hash, last_indexed) VALUES (?1, 'TypeScript', 'h', 0)",
+                [path],
+            )
+            .unwrap();
+        };
+        add_file("src/app.ts");
+        for import in ["./utils", "react", "Admin::UserSession"] {
+            conn.execute(
+                "INSERT INTO file_imports (source_file, import_path) VALUES ('src/app.ts', ?1)",
+                [import],
+            )
+            .unwrap();
+        }
+        let marked = || -> i64 {
+            conn.query_row(
+                "SELECT COUNT(*) FROM file_imports WHERE unresolvable = 1",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+
+        assert_eq!(resolve_pending(&conn, None).unwrap(), 0);
+        assert_eq!(marked(), 3);
+
+        // An unrelated file leaves them marked; one they could name does not.
+        add_file("lib/helpers.ts");
+        assert_eq!(retry_for_file(&conn, "lib/helpers.ts").unwrap(), 0);
+        add_file("src/utils.ts");
+        assert_eq!(retry_for_file(&conn, "src/utils.ts").unwrap(), 1);
+        assert_eq!(resolve_pending(&conn, None).unwrap(), 1);
+        assert_eq!(marked(), 2);
+        // Names match without case, `_`, or `-`.
+        assert_eq!(
+            retry_for_file(&conn, "app/models/admin/user_session.rb").unwrap(),
+            1
+        );
+
+        assert_eq!(retry_all(&conn).unwrap(), 1);
+        assert_eq!(resolve_pending(&conn, None).unwrap(), 0);
+        assert_eq!(marked(), 2);
+    }
+
+    #[test]
+    fn build_files_affect_resolution() {
+        for path in [
+            "go.mod",
+            "web/tsconfig.json",
+            "pyproject.toml",
+            "composer.json",
+        ] {
+            assert!(affects_resolution(Path::new(path)), "{path}");
+        }
+        assert!(!affects_resolution(Path::new("src/go.rs")));
+        assert!(!affects_resolution(Path::new("package.json")));
+    }
+
+    #[test]
+    fn resolve_pending_resolves_kotlin_imports_by_package() {
+        let dir = TempDir::new().unwrap();
+        let conn = db::open(&dir.path().join("index.db")).unwrap();
+        conn.execute_batch(
+            "INSERT INTO symbols (name, kind, file, line, col, scope, language) \
+                 VALUES ('checksum', 'function', 'util/Hash.kt', 3, 0, 'com.acme.util', 'Kotlin'); \
+             INSERT INTO symbols (name, kind, file, line, col, scope, language) \
+                 VALUES ('Entry', 'class', 'model/Entry.java', 1, 0, 'com.acme', 'Java'); \
+             INSERT INTO file_imports (source_file, import_path) \
+                 VALUES ('app/Main.kt', 'com.acme.util.checksum'); \
+             INSERT INTO file_imports (source_file, import_path) \
+                 VALUES ('app/Main.kt', 'com.acme.Entry'); \
+             INSERT INTO file_imports (source_file, import_path) \
+                 VALUES ('app/Main.kt', 'kotlin.math.max');",
+        )
+        .unwrap();
+
+        assert_eq!(resolve_pending(&conn, None).unwrap(), 2);
+        let rows: Vec<(String, Option<String>)> = conn
+            .prepare("SELECT import_path, resolved_file FROM file_imports ORDER BY import_path")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                (
+                    "com.acme.Entry".to_string(),
+                    Some("model/Entry.java".to_string())
+                ),
+                (
+                    "com.acme.util.checksum".to_string(),
+                    Some("util/Hash.kt".to_string())
+                ),
+                ("kotlin.math.max".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve_pending_resolves_scala_imports_by_package() {
+        let dir = TempDir::new().unwrap();
+        let conn = db::open(&dir.path().join("index.db")).unwrap();
+        conn.execute_batch(
+            "INSERT INTO symbols (name, kind, file, line, col, scope, language) \
+                 VALUES ('Codec', 'trait', 'core/Codec.scala', 3, 0, 'com.acme.core', 'Scala'); \
+             INSERT INTO symbols (name, kind, file, line, col, scope, language) \
+                 VALUES ('Registry', 'class', 'core/Registry.scala', 3, 0, 'com.acme.core', 'Scala'); \
+             INSERT INTO file_imports (source_file, import_path) \
+                 VALUES ('app/Main.scala', 'com.acme.core.Codec'); \
+             INSERT INTO file_imports (source_file, import_path) \
+                 VALUES ('app/Main.scala', 'com.acme.core.Registry.lookup'); \
+             INSERT INTO file_imports (source_file, import_path) \
+                 VALUES ('app/Main.scala', 'scala.util.Try');",
+        )
+        .unwrap();
+
+        assert_eq!(resolve_pending(&conn, None).unwrap(), 2);
+        let rows: Vec<(String, Option<String>)> = conn
+            .prepare("SELECT import_path, resolved_file FROM file_imports ORDER BY import_path")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                (
+                    "com.acme.core.Codec".to_string(),
+                    Some("core/Codec.scala".to_string())
+                ),
+                (
+                    "com.acme.core.Registry.lookup".to_string(),
+                    Some("core/Registry.scala".to_string())
+                ),
+                ("scala.util.Try".to_string(), None),
+            ]
+        );
+    }
+}
@@ -5,7 +5,9 @@
 //! Also extracts symbol definitions (functions, classes, types, etc.) from
 //! parsed syntax trees across all supported languages.
 
+use std::collections::HashSet;
 use std::path::Path;
+use std::sync::Mutex;
 
 use tree_sitter::{Language, Node, Parser, Tree};
 
@@ -21,11 +23,20 @@ pub enum Lang {
     Rust,
     Go,
     Java,
+    Kotlin,
+    Scala,
     C,
     Cpp,
     Ruby,
     Php,
     CSharp,
+    Lua,
+    Elixir,
+    Dart,
+    Shell,
+    Sql,
+    Vue,
+    Svelte,
 }
 
 impl Lang {
@@ -39,15 +50,64 @@ impl Lang {
             Lang::Rust => "Rust",
             Lang::Go => "Go",
             Lang::Java => "Java",
+            Lang::Kotlin => "Kotlin",
+            Lang::Scala => "Scala",
             Lang::C => "C",
             Lang::Cpp => "C++",
             Lang::Ruby => "Ruby",
             Lang::Php => "PHP",
             Lang::CSharp => "C#",
+            Lang::Lua => "Lua",
+            Lang::Elixir => "Elixir",
+            Lang::Dart => "Dart",
+            Lang::Shell => "Shell",
+            Lang::Sql => "SQL",
+            Lang::Vue => "Vue",
+            Lang::Svelte => "Svelte",
         }
     }
 }
 
+impl Lang {
+    /// Characters besides Unicode letters, digits, and `_` that can be part
+    /// of an identifier: `$` in the JVM and JavaScript families, and the
+    /// `?` and `!` that end Ruby and Elixir function names.
+    pub fn identifier_chars(self) -> &'static str {
+        match self {
+            Lang::TypeScript
+            | Lang::Tsx
+            | Lang::JavaScript
+            | Lang::Vue
+            | Lang::Svelte
+            | Lang::Java
+            | Lang::Scala
+            | Lang::Dart => "$",
+            Lang::Ruby | Lang::Elixir => "?!",
+            _ => "",
+        }
+    }
+}
+
+/// Look up a language by name (`rust`, `C++`, `csharp`, ...) or file
+/// extension (`rs`, `py`, ...), ignoring case.
+pub fn lang_from_name(name: &str) -> Option<Lang> {
+    let lower = name.to_ascii_lowercase();
+    let by_name = match lower.as_str() {
+        "typescript" => Some(Lang::TypeScript),
+        "javascript" => Some(Lang::JavaScript),
+        "python" => Some(Lang::Python),
+        "rust" => Some(Lang::Rust),
+        "golang" => Some(Lang::Go),
+        "c++" => Some(Lang::Cpp),
+        "ruby" => Some(Lang::Ruby),
+        "c#" | "csharp" => Some(Lang::CSharp),
+        "elixir" => Some(Lang::Elixir),
+        "shell" => Some(Lang::Shell),
+        _ => None,
+    };
+    by_name.or_else(|| detect_language(Path::new(&format!("source.{lower}"))))
+}
+
 /// Detect the programming language of a file based on its extension.
 ///
 /// Returns `None` for unsupported or missing extensions.
@@ -61,11 +121,45 @@ pub fn detect_language(path: &Path) -> Option<Lang> {
         "rs" => Some(Lang::Rust),
         "go" => Some(Lang::Go),
         "java" => Some(Lang::Java),
+        "kt" | "kts" => Some(Lang::Kotlin),
+        "scala" | "sc" => Some(Lang::Scala),
         "c" | "h" => Some(Lang::C),
         "cpp" | "cc" | "cxx" | "hpp" | "hh" | "hxx" => Some(Lang::Cpp),
         "rb" => Some(Lang::Ruby),
         "php" => Some(Lang::Php),
         "cs" => Some(Lang::CSharp),
+        "lua" => Some(Lang::Lua),
+        "ex" | "exs" => Some(Lang::Elixir),
+        "dart" => Some(Lang::Dart),
+        "sh" | "bash" | "zsh" => Some(Lang::Shell),
+        "sql" => Some(Lang::Sql),
+        "vue" => Some(Lang::Vue),
+        "svelte" => Some(Lang::Svelte),
+        _ => None,
+    }
+}
+
+/// Detect the language of `source` at `path`: by extension, or for files
+/// without one, by a shell shebang (`#!/bin/sh`, `#!/usr/bin/env bash`).
+pub fn detect_language_in(path: &Path, source: &str) -> Option<Lang> {
+    detect_language(path).or_else(|| {
+        if path.extension().is_some() {
+            return None;
+        }
+        shebang_language(source)
+    })
+}
+
+/// The language a `#!` line names, if wonk indexes it.
+fn shebang_language(source: &str) -> Option<Lang> {
+    let line = source.lines().next()?.strip_prefix("#!")?;
+    let mut words = line.split_whitespace();
+    let mut interpreter = words.next()?.rsplit('/').next()?;
+    if interpreter == "env" {
+        interpreter = words.find(|w| !w.starts_with('-'))?;
+    }
+    match interpreter {
+        "sh" | "bash" | "zsh" | "dash" | "ksh" => Some(Lang::Shell),
         _ => None,
     }
 }
@@ -80,21 +174,82 @@ fn grammar_for(lang: Lang) -> Language {
         Lang::Rust => tree_sitter_rust::LANGUAGE.into(),
         Lang::Go => tree_sitter_go::LANGUAGE.into(),
         Lang::Java => tree_sitter_java::LANGUAGE.into(),
+        Lang::Kotlin => tree_sitter_kotlin_ng::LANGUAGE.into(),
+        Lang::Scala => tree_sitter_scala::LANGUAGE.into(),
         Lang::C => tree_sitter_c::LANGUAGE.into(),
         Lang::Cpp => tree_sitter_cpp::LANGUAGE.into(),
         Lang::Ruby => tree_sitter_ruby::LANGUAGE.into(),
         Lang::Php => tree_sitter_php::LANGUAGE_PHP.into(),
         Lang::CSharp => tree_sitter_c_sharp::LANGUAGE.into(),
+        Lang::Lua => tree_sitter_lua::LANGUAGE.into(),
+        Lang::Elixir => tree_sitter_elixir::LANGUAGE.into(),
+        Lang::Dart => tree_sitter_dart::LANGUAGE.into(),
+        Lang::Shell => tree_sitter_bash::LANGUAGE.into(),
+        Lang::Sql => tree_sitter_sequel::LANGUAGE.into(),
+        // Only the `<script>` blocks are parsed; see `component_script_source`.
+        Lang::Vue | Lang::Svelte => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
     }
 }
 
+/// Languages whose grammar failed to load during this process.
+///
+/// A grammar that fails once (e.g. tree-sitter ABI mismatch) is never retried;
+/// files in that language are skipped by the indexer for the rest of the
+/// session, and queries carry a [`crate::caveats::Caveat::DisabledLanguages`]
+/// warning.
+static DISABLED_LANGUAGES: Mutex<Vec<Lang>> = Mutex::new(Vec::new());
+
 /// Create a new [`Parser`] configured for the given language.
-pub fn get_parser(lang: Lang) -> Parser {
+///
+/// Returns `None` when the grammar cannot be loaded.  The first failure for a
+/// language prints a warning and disables that language for the session (see
+/// [`disabled_languages`]) instead of aborting the whole process.
+pub fn get_parser(lang: Lang) -> Option<Parser> {
+    if is_language_disabled(lang) {
+        return None;
+    }
     let mut parser = Parser::new();
-    parser
-        .set_language(&grammar_for(lang))
-        .expect("Error loading grammar — ABI version mismatch");
-    parser
+    match parser.set_language(&grammar_for(lang)) {
+        Ok(()) => Some(parser),
+        Err(e) => {
+            disable_language(lang, &e.to_string());
+            None
+        }
+    }
+}
+
+/// Returns `true` if `lang` was disabled after a grammar load failure.
+pub fn is_language_disabled(lang: Lang) -> bool {
+    DISABLED_LANGUAGES
+        .lock()
+        .map(|d| d.contains(&lang))
+        .unwrap_or(false)
+}
+
+/// Names of all languages disabled during this session, sorted.
+pub fn disabled_languages() -> Vec<String> {
+    let mut names: Vec<String> = DISABLED_LANGUAGES
+        .lock()
+        .map(|d| d.iter().map(|l| l.name().to_string()).collect())
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+/// Mark `lang` as disabled and warn once on stderr.
+fn disable_language(lang: Lang, reason: &str) {
+    let Ok(mut disabled) = DISABLED_LANGUAGES.lock() else {
+        return;
+    };
+    if disabled.contains(&lang) {
+        return;
+    }
+    disabled.push(lang);
+    crate::output::print_warning(&format!(
+        "failed to load {} grammar ({reason}); {} files will use grep fallback",
+        lang.name(),
+        lang.name()
+    ));
 }
 
 /// Parse a source file, returning the syntax tree and detected language.
@@ -102,15 +257,31 @@ pub fn get_parser(lang: Lang) -> Parser {
 /// Returns `None` when:
 /// - the file extension is unsupported
 /// - the file cannot be read
+/// - the language's grammar failed to load
 /// - the parser fails to produce a tree
 pub fn parse_file(path: &Path) -> Option<(Tree, Lang)> {
-    let lang = detect_language(path)?;
     let source = std::fs::read(path).ok()?;
-    let mut parser = get_parser(lang);
-    let tree = parser.parse(&source, None)?;
+    let source = String::from_utf8_lossy(&source);
+    let lang = detect_language_in(path, &source)?;
+    let mut parser = get_parser(lang)?;
+    let tree = parser.parse(parse_source(lang, &source), None)?;
     Some((tree, lang))
 }
 
+/// The text the indexer parses for `lang`: Rust with `cfg_*!` wrappers
+/// stripped, Vue and Svelte components with everything outside `<script>`
+/// blanked.
+///
+/// Byte offsets and line numbers match `source`, so positions extracted from
+/// the tree point into the original file.
+pub fn parse_source(lang: Lang, source: &str) -> String {
+    match lang {
+        Lang::Rust => preprocess_rust_macros(source),
+        Lang::Vue | Lang::Svelte => component_script_source(source),
+        _ => source.to_string(),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Rust macro pre-expansion
 // ---------------------------------------------------------------------------
@@ -121,9 +292,8 @@ pub fn parse_file(path: &Path) -> Option<(Tree, Lang)> {
 /// Replaces the macro invocation line and its matching closing brace with blank
 /// lines so that all original line numbers are preserved.
 pub fn preprocess_rust_macros(source: &str) -> String {
-    let mut lines: Vec<String> = source.lines().map(String::from).collect();
-    // Preserve trailing newline if original had one.
-    let trailing_newline = source.ends_with('\n');
+    // Lines keep their terminators so the output has the same byte length.
+    let mut lines: Vec<String> = source.split_inclusive('\n').map(String::from).collect();
 
     let mut i = 0;
     while i < lines.len() {
@@ -133,19 +303,24 @@ pub fn preprocess_rust_macros(source: &str) -> String {
             // Find the matching closing brace by counting depth.
             if let Some(close_idx) = find_matching_brace(&lines, i) {
                 // Blank out the macro invocation line(s).
-                lines[i] = String::new();
+                lines[i] = blank_line(&lines[i]);
                 // Blank out the closing brace line.
-                lines[close_idx] = String::new();
+                lines[close_idx] = blank_line(&lines[close_idx]);
             }
         }
         i += 1;
     }
 
-    let mut result = lines.join("\n");
-    if trailing_newline {
-        result.push('\n');
-    }
-    result
+    lines.concat()
+}
+
+/// Replace a line's content with spaces, keeping its byte length and line
+/// terminator so byte offsets after it are unchanged.
+fn blank_line(line: &str) -> String {
+    let content = line.trim_end_matches(['\n', '\r']);
+    let mut blank = " ".repeat(content.len());
+    blank.push_str(&line[content.len()..]);
+    blank
 }
 
 /// Returns true if a trimmed line looks like a `cfg_*! {` macro invocation.
@@ -182,6 +357,99 @@ fn find_matching_brace(lines: &[String], start_line: usize) -> Option<usize> {
     None
 }
 
+// ---------------------------------------------------------------------------
+// Vue and Svelte components
+// ---------------------------------------------------------------------------
+
+/// Keep only the contents of a component's `<script>` blocks (Vue's
+/// `<script setup>`, Svelte's `<script context="module">`), replacing every
+/// other byte except line terminators with a space.
+fn component_script_source(source: &str) -> String {
+    let mut out = blank_text(source).into_bytes();
+    let mut rest = 0;
+    while let Some(open) = find_script_tag(&source[rest..]) {
+        let start = rest + open;
+        let Some(body) = source[start..].find('>').map(|i| start + i + 1) else {
+            break;
+        };
+        let end = source[body..]
+            .find("</script>")
+            .map_or(source.len(), |i| body + i);
+        out[body..end].copy_from_slice(&source.as_bytes()[body..end]);
+        rest = end;
+    }
+    // Only ASCII spaces replaced whole lines and tags, so this cannot fail.
+    String::from_utf8(out).unwrap_or_default()
+}
+
+/// Offset of the next `<script` opening tag (not `<scripts` or similar).
+fn find_script_tag(text: &str) -> Option<usize> {
+    let mut from = 0;
+    while let Some(i) = text[from..].find("<script") {
+        let at = from + i;
+        match text.as_bytes().get(at + "<script".len()) {
+            Some(b'>' | b' ' | b'\t' | b'\n' | b'\r') => return Some(at),
+            _ => from = at + 1,
+        }
+    }
+    None
+}
+
+/// `text` with every byte except `\n` and `\r` replaced by a space.
+fn blank_text(text: &str) -> String {
+    text.bytes()
+        .map(|b| {
+            if b == b'\n' || b == b'\r' {
+                b as char
+            } else {
+                ' '
+            }
+        })
+        .collect()
+}
+
+/// The component a `.vue` or `.svelte` file defines, named from the file as
+/// Vue infers it: `user-card.vue` is `UserCard`.  The symbol spans the whole
+/// file.
+fn component_symbol(source: &str, file: &str, lang: Lang) -> Option<Symbol> {
+    let path = Path::new(file);
+    let stem = path.file_stem()?.to_str()?;
+    let name: String = stem
+        .split(['-', '_'])
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|c| c.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect();
+    if name.is_empty() {
+        return None;
+    }
+    let end_line = source.lines().count().max(1);
+    let end_col = source.lines().last().map_or(0, str::len);
+    Some(Symbol {
+        name,
+        kind: SymbolKind::Class,
+        file: file.to_string(),
+        line: 1,
+        col: 0,
+        end_line: Some(end_line),
+        end_col: Some(end_col),
+        start_byte: Some(0),
+        end_byte: Some(source.len()),
+        scope: None,
+        signature: path.file_name()?.to_str()?.to_string(),
+        language: lang.name().to_string(),
+        doc_comment: None,
+        condition: None,
+        visibility: Some("public".to_string()),
+        modifiers: Vec::new(),
+        generics: Vec::new(),
+    })
+}
+
 // ---------------------------------------------------------------------------
 // Symbol extraction
 // ---------------------------------------------------------------------------
@@ -194,6 +462,9 @@ fn find_matching_brace(lines: &[String], start_line: usize) -> Option<usize> {
 pub fn extract_symbols(tree: &Tree, source: &str, file: &str, lang: Lang) -> Vec<Symbol> {
     let src = source.as_bytes();
     let mut symbols = Vec::new();
+    if matches!(lang, Lang::Vue | Lang::Svelte) {
+        symbols.extend(component_symbol(source, file, lang));
+    }
     let root = tree.root_node();
 
     walk_node(root, src, file, lang, &mut symbols);
@@ -226,12 +497,37 @@ fn walk_node(root: Node, src: &[u8], file: &str, lang: Lang, symbols: &mut Vec<S
             }
         }
 
-        // Default: descend into children with same scope.
-        for i in (0..node.child_count()).rev() {
+        // Default: descend into children with same scope.  A PHP
+        // `namespace Foo;` or Java/Kotlin/Scala `package foo` statement
+        // scopes the siblings that follow it.
+        let mut children = Vec::with_capacity(node.child_count());
+        let mut child_scope = scope;
+        for i in 0..node.child_count() {
             if let Some(child) = node.child(i as u32) {
-                stack.push((child, scope.clone()));
+                children.push((child, child_scope.clone()));
+                if lang == Lang::Php
+                    && child.kind() == "namespace_definition"
+                    && child.child_by_field_name("body").is_none()
+                {
+                    child_scope = field_text(child, "name", src).map(str::to_string);
+                } else if lang == Lang::Java && child.kind() == "package_declaration" {
+                    child_scope = (0..child.named_child_count())
+                        .filter_map(|i| child.named_child(i as u32))
+                        .find(|c| matches!(c.kind(), "scoped_identifier" | "identifier"))
+                        .map(|c| node_text(c, src).to_string());
+                } else if lang == Lang::Kotlin && child.kind() == "package_header" {
+                    child_scope = child
+                        .named_child(0u32)
+                        .map(|c| node_text(c, src).to_string());
+                } else if lang == Lang::Scala
+                    && child.kind() == "package_clause"
+                    && child.child_by_field_name("body").is_none()
+                {
+                    child_scope = field_text(child, "name", src).map(str::to_string);
+                }
             }
         }
+        stack.extend(children.into_iter().rev());
     }
 }
 
@@ -244,7 +540,7 @@ fn is_container(kind: &str, lang: Lang) -> bool {
             "impl_item" | "trait_item" | "mod_item" | "struct_item" | "enum_item"
         ),
         Lang::Python => matches!(kind, "class_definition"),
-        Lang::JavaScript | Lang::TypeScript | Lang::Tsx => {
+        Lang::JavaScript | Lang::TypeScript | Lang::Tsx | Lang::Vue | Lang::Svelte => {
             matches!(
                 kind,
                 "class_declaration" | "class" | "interface_declaration"
@@ -254,6 +550,11 @@ fn is_container(kind: &str, lang: Lang) -> bool {
             kind,
             "class_declaration" | "interface_declaration" | "enum_declaration"
         ),
+        Lang::Kotlin => matches!(kind, "class_declaration" | "object_declaration"),
+        Lang::Scala => matches!(
+            kind,
+            "class_definition" | "object_definition" | "trait_definition" | "enum_definition"
+        ),
         Lang::Go => false, // Go has no nested containers
         Lang::C | Lang::Cpp => matches!(
             kind,
@@ -262,7 +563,10 @@ fn is_container(kind: &str, lang: Lang) -> bool {
         Lang::Ruby => matches!(kind, "class" | "module"),
         Lang::Php => matches!(
             kind,
-            "class_declaration" | "interface_declaration" | "trait_declaration"
+            "class_declaration"
+                | "interface_declaration"
+                | "trait_declaration"
+                | "namespace_definition"
         ),
         Lang::CSharp => matches!(
             kind,
@@ -273,6 +577,18 @@ fn is_container(kind: &str, lang: Lang) -> bool {
                 | "namespace_declaration"
                 | "record_declaration"
         ),
+        // Lua scopes come from the table a function is defined on.
+        Lang::Lua | Lang::Shell | Lang::Sql => false,
+        // Every Elixir form is a `call`; of the extracted ones only
+        // `defmodule` has a body that can hold definitions.
+        Lang::Elixir => kind == "call",
+        Lang::Dart => matches!(
+            kind,
+            "class_declaration"
+                | "mixin_declaration"
+                | "extension_declaration"
+                | "enum_declaration"
+        ),
     }
 }
 
@@ -289,12 +605,21 @@ fn match_node(
         Lang::Rust => extract_rust(node, kind, src, file, scope),
         Lang::Python => extract_python(node, kind, src, file, scope),
         Lang::JavaScript => extract_javascript(node, kind, src, file, scope),
-        Lang::TypeScript | Lang::Tsx => extract_typescript(node, kind, src, file, lang, scope),
+        Lang::TypeScript | Lang::Tsx | Lang::Vue | Lang::Svelte => {
+            extract_typescript(node, kind, src, file, lang, scope)
+        }
         Lang::Go => extract_go(node, kind, src, file, scope),
         Lang::Java => extract_java(node, kind, src, file, scope),
+        Lang::Kotlin => extract_kotlin(node, kind, src, file, scope),
+        Lang::Scala => extract_scala(node, kind, src, file, scope),
         Lang::C => extract_c(node, kind, src, file, scope),
         Lang::Cpp => extract_cpp(node, kind, src, file, scope),
         Lang::Ruby => extract_ruby(node, kind, src, file, scope),
+        Lang::Lua => extract_lua(node, kind, src, file, scope),
+        Lang::Shell => extract_shell(node, kind, src, file, scope),
+        Lang::Sql => extract_sql(node, kind, src, file, scope),
+        Lang::Elixir => extract_elixir(node, kind, src, file, scope),
+        Lang::Dart => extract_dart(node, kind, src, file, scope),
         Lang::Php => extract_php(node, kind, src, file, scope),
         Lang::CSharp => extract_csharp(node, kind, src, file, scope),
     }
@@ -342,123 +667,920 @@ fn make_symbol(
         line: node.start_position().row + 1,
         col: node.start_position().column,
         end_line: Some(node.end_position().row + 1),
+        end_col: Some(node.end_position().column),
+        start_byte: Some(node.start_byte()),
+        end_byte: Some(node.end_byte()),
         scope: scope.map(|s| s.to_string()),
         signature: first_line(node, src),
         language: lang.name().to_string(),
         doc_comment: extract_doc_comment(node, src, lang),
+        condition: match lang {
+            Lang::C | Lang::Cpp => preproc_condition(node, src),
+            _ => None,
+        },
+        visibility: symbol_visibility(node, name, kind, src, lang),
+        modifiers: symbol_modifiers(node, src, lang),
+        generics: symbol_generics(node, src, lang),
     }
 }
 
-/// Maximum length in Unicode scalar values for extracted doc comments.
-const MAX_DOC_COMMENT_LEN: usize = 200;
-
-/// Extract a doc comment for the given symbol node.
-fn extract_doc_comment(node: Node, src: &[u8], lang: Lang) -> Option<String> {
+/// Modifier keywords recorded on symbols, in the order they are reported.
+const MODIFIERS: &[&str] = &[
+    "static",
+    "async",
+    "unsafe",
+    "abstract",
+    "final",
+    "const",
+    "readonly",
+    "virtual",
+    "override",
+    "sealed",
+    "extern",
+    "inline",
+    "synchronized",
+];
+
+/// Node kinds that wrap modifier keywords in the various grammars.
+const MODIFIER_WRAPPERS: &[&str] = &[
+    "modifiers",
+    "modifier",
+    "function_modifiers",
+    "extern_modifier",
+    "storage_class_specifier",
+    "virtual_specifier",
+    "static_modifier",
+    "abstract_modifier",
+    "final_modifier",
+    "readonly_modifier",
+];
+
+/// Modifier keywords of a definition (`static`, `async`, `unsafe`, ...).
+///
+/// Keywords are read from the definition's own tokens and modifier lists;
+/// Python decorators (`@staticmethod`, `@abstractmethod`) and Ruby
+/// `def self.x` map onto `static` and `abstract`.
+fn symbol_modifiers(node: Node, src: &[u8], lang: Lang) -> Vec<String> {
+    let mut found: HashSet<&str> = HashSet::new();
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        let kind = child.kind();
+        if MODIFIER_WRAPPERS.contains(&kind) {
+            let mut words = Vec::new();
+            collect_leaf_text(child, src, &mut words);
+            found.extend(words.into_iter().filter(|w| MODIFIERS.contains(w)));
+        } else if child.child_count() == 0 && kind != "const" {
+            // Bare keywords: `async def`, `static x = 1`, `unsafe impl`.
+            found.extend(MODIFIERS.iter().find(|m| **m == kind));
+        }
+    }
+    // C++ `override`/`final` sit on the declarator.
+    if lang == Lang::Cpp
+        && let Some(declarator) = node.child_by_field_name("declarator")
+    {
+        let mut cursor = declarator.walk();
+        for child in declarator.children(&mut cursor) {
+            if child.kind() == "virtual_specifier" {
+                found.extend(MODIFIERS.iter().find(|m| **m == node_text(child, src)));
+            }
+        }
+    }
     match lang {
-        Lang::Python => extract_python_docstring(node, src),
-        _ => extract_preceding_comment(node, src, lang),
+        Lang::Python => {
+            if let Some(parent) = node.parent()
+                && parent.kind() == "decorated_definition"
+            {
+                let mut cursor = parent.walk();
+                for decorator in parent.children(&mut cursor) {
+                    if decorator.kind() != "decorator" {
+                        continue;
+                    }
+                    let text = node_text(decorator, src);
+                    let name = text
+                        .trim_start_matches('@')
+                        .rsplit('.')
+                        .next()
+                        .unwrap_or("");
+                    match name {
+                        "staticmethod" | "classmethod" => {
+                            found.insert("static");
+                        }
+                        "abstractmethod" => {
+                            found.insert("abstract");
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        Lang::Ruby if node.kind() == "singleton_method" => {
+            found.insert("static");
+        }
+        // `static` sits on a method's signature and `async` on its body.
+        Lang::Dart => {
+            for part in ["signature", "body"] {
+                if let Some(part) = node.child_by_field_name(part) {
+                    let mut cursor = part.walk();
+                    for child in part.children(&mut cursor) {
+                        found.extend(MODIFIERS.iter().find(|m| **m == child.kind()));
+                    }
+                }
+            }
+        }
+        // Members of a `companion object` belong to the class itself.
+        Lang::Kotlin
+            if node
+                .parent()
+                .and_then(|body| body.parent())
+                .is_some_and(|p| p.kind() == "companion_object") =>
+        {
+            found.insert("static");
+        }
+        // Members of an `object` belong to the singleton, not an instance.
+        Lang::Scala
+            if node
+                .parent()
+                .and_then(|body| body.parent())
+                .is_some_and(|p| p.kind() == "object_definition") =>
+        {
+            found.insert("static");
+        }
+        _ => {}
     }
+    MODIFIERS
+        .iter()
+        .filter(|m| found.contains(*m))
+        .map(|m| m.to_string())
+        .collect()
 }
 
-/// Extract a Python docstring: first `expression_statement > string` in the `body` field.
-fn extract_python_docstring(node: Node, src: &[u8]) -> Option<String> {
-    let body = node.child_by_field_name("body")?;
-    let first_stmt = body.named_child(0)?;
-    if first_stmt.kind() != "expression_statement" {
-        return None;
-    }
-    let string_node = first_stmt.named_child(0)?;
-    if string_node.kind() != "string" {
-        return None;
+/// Push the text of every leaf token under `node`.
+fn collect_leaf_text<'a>(node: Node, src: &'a [u8], out: &mut Vec<&'a str>) {
+    if node.child_count() == 0 {
+        out.push(node_text(node, src));
+        return;
     }
-    let text = node_text(string_node, src);
-    // Strip quotes: """...""", '''...''', "...", '...'
-    let stripped = text
-        .strip_prefix("\"\"\"")
-        .and_then(|s| s.strip_suffix("\"\"\""))
-        .or_else(|| text.strip_prefix("'''").and_then(|s| s.strip_suffix("'''")))
-        .or_else(|| text.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
-        .or_else(|| text.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')))
-        .unwrap_or(text);
-    let cleaned = strip_doc_prefix(stripped, Lang::Python);
-    if cleaned.is_empty() {
-        return None;
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_leaf_text(child, src, out);
     }
-    Some(truncate_doc(&cleaned))
 }
 
-/// Extract a doc comment from preceding sibling comment nodes.
-fn extract_preceding_comment(node: Node, src: &[u8], lang: Lang) -> Option<String> {
-    let mut current = node.prev_named_sibling()?;
-
-    // Skip attribute/decorator nodes
-    while let "attribute_item" | "decorator" | "annotation" | "attribute" | "attribute_list" =
-        current.kind()
-    {
-        current = current.prev_named_sibling()?;
-    }
+/// Visibility of a definition, normalised across languages: `public`,
+/// `private`, `protected`, `internal` (C#), `package` (Java package-private
+/// and unexported Go names), or a Rust restriction as written
+/// (`pub(crate)`).  Implicit defaults follow each language's rules; `None`
+/// where the language has no notion of it (C macros, local JS functions).
+fn symbol_visibility(
+    node: Node,
+    name: &str,
+    kind: SymbolKind,
+    src: &[u8],
+    lang: Lang,
+) -> Option<String> {
+    let parent_kind = node.parent().map(|p| p.kind()).unwrap_or("");
+    let keyword = explicit_visibility(node, src);
+    let visibility = match lang {
+        Lang::Rust => {
+            let mut cursor = node.walk();
+            let modifier = node
+                .children(&mut cursor)
+                .find(|c| c.kind() == "visibility_modifier");
+            match modifier.map(|m| node_text(m, src).split_whitespace().collect::<String>()) {
+                Some(v) if v == "pub" => "public".to_string(),
+                Some(v) => v,
+                None if kind == SymbolKind::Module && node.kind() == "impl_item" => return None,
+                // Trait items and trait impl methods are as visible as the trait.
+                None if rust_in_trait(node) => "public".to_string(),
+                None => "private".to_string(),
+            }
+        }
+        Lang::Python => {
+            let dunder = name.starts_with("__") && name.ends_with("__");
+            if name.starts_with('_') && !dunder {
+                "private".to_string()
+            } else {
+                "public".to_string()
+            }
+        }
+        Lang::Go => {
+            if name.starts_with(|c: char| c.is_uppercase()) {
+                "public".to_string()
+            } else {
+                "package".to_string()
+            }
+        }
+        Lang::Java => match keyword {
+            Some(v) => v,
+            None if parent_kind == "interface_body" => "public".to_string(),
+            None => "package".to_string(),
+        },
+        Lang::Kotlin | Lang::Scala => match keyword {
+            Some(v) => v,
+            // Local functions are not visible outside their body.
+            None if parent_kind == "block" => return None,
+            None => "public".to_string(),
+        },
+        Lang::CSharp => match keyword {
+            Some(v) => v,
+            None if node
+                .parent()
+                .and_then(|p| p.parent())
+                .is_some_and(|g| g.kind() == "interface_declaration") =>
+            {
+                "public".to_string()
+            }
+            None if matches!(
+                parent_kind,
+                "compilation_unit" | "file_scoped_namespace_declaration"
+            ) || node
+                .parent()
+                .and_then(|p| p.parent())
+                .is_some_and(|g| g.kind() == "namespace_declaration") =>
+            {
+                "internal".to_string()
+            }
+            None => "private".to_string(),
+        },
+        Lang::JavaScript | Lang::TypeScript | Lang::Tsx | Lang::Vue | Lang::Svelte => {
+            let is_member = matches!(parent_kind, "class_body" | "object_type" | "interface_body");
+            if let Some(v) = keyword {
+                v
+            } else if is_member {
+                let private_name = node
+                    .child_by_field_name("name")
+                    .is_some_and(|n| n.kind() == "private_property_identifier");
+                if private_name { "private" } else { "public" }.to_string()
+            } else if std::iter::successors(node.parent(), |n| n.parent())
+                .take(3)
+                .any(|n| n.kind() == "export_statement")
+            {
+                "public".to_string()
+            } else if std::iter::successors(node.parent(), |n| n.parent())
+                .take(2)
+                .any(|n| n.kind() == "program")
+            {
+                "private".to_string()
+            } else {
+                return None;
+            }
+        }
+        Lang::Php => keyword.unwrap_or_else(|| "public".to_string()),
+        Lang::Elixir => match elixir_call_target(node, src) {
+            Some("defp" | "defmacrop") => "private".to_string(),
+            _ => "public".to_string(),
+        },
+        // Names starting with `_` are private to their library.
+        Lang::Dart => {
+            if name.starts_with('_') {
+                "private".to_string()
+            } else {
+                "public".to_string()
+            }
+        }
+        // Functions are global once the script is run or sourced.
+        Lang::Shell => "public".to_string(),
+        // Access is granted per role, not declared with the object.
+        Lang::Sql => return None,
+        Lang::Lua => {
+            let mut cursor = node.walk();
+            let local = node.children(&mut cursor).any(|c| c.kind() == "local");
+            match (local, parent_kind) {
+                (false, _) => "public".to_string(),
+                (true, "chunk") => "private".to_string(),
+                // Locals inside a function or block.
+                (true, _) => return None,
+            }
+        }
+        Lang::Ruby => {
+            if !matches!(node.kind(), "method" | "singleton_method") {
+                "public".to_string()
+            } else {
+                ruby_section_visibility(node, src).unwrap_or_else(|| "public".to_string())
+            }
+        }
+        Lang::C | Lang::Cpp => {
+            if node.kind() == "preproc_def" || node.kind() == "preproc_function_def" {
+                return None;
+            }
+            if parent_kind == "field_declaration_list" {
+                cpp_member_access(node, src)
+            } else if symbol_modifiers(node, src, lang)
+                .iter()
+                .any(|m| m == "static")
+            {
+                // File-scope `static` has internal linkage.
+                "private".to_string()
+            } else {
+                "public".to_string()
+            }
+        }
+    };
+    Some(visibility)
+}
 
-    // Check if the node is a comment
-    let is_comment = matches!(current.kind(), "line_comment" | "block_comment" | "comment");
-    if !is_comment {
-        return None;
+/// An explicit `public`/`private`/`protected`/`internal` keyword (C# may
+/// combine two, as in `protected internal`).
+fn explicit_visibility(node: Node, src: &[u8]) -> Option<String> {
+    let mut words = Vec::new();
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if matches!(
+            child.kind(),
+            "modifiers" | "modifier" | "accessibility_modifier" | "visibility_modifier"
+        ) {
+            collect_leaf_text(child, src, &mut words);
+        }
     }
+    let keywords: Vec<&str> = words
+        .into_iter()
+        .filter(|w| matches!(*w, "public" | "private" | "protected" | "internal"))
+        .collect();
+    (!keywords.is_empty()).then(|| keywords.join(" "))
+}
 
-    let text = node_text(current, src);
-
-    // Check for doc comment prefix based on language
-    let is_doc = match lang {
-        Lang::Rust => text.starts_with("///") || text.starts_with("/**"),
-        Lang::TypeScript | Lang::Tsx | Lang::JavaScript => text.starts_with("/**"),
-        Lang::Go => text.starts_with("//"),
-        Lang::Java | Lang::CSharp => text.starts_with("/**") || text.starts_with("///"),
-        Lang::C | Lang::Cpp => text.starts_with("/**") || text.starts_with("///"),
-        Lang::Ruby => text.starts_with("#"),
-        Lang::Php => text.starts_with("/**"),
-        Lang::Python => false, // handled by docstring extractor
+/// Whether a Rust item sits in a trait definition or a trait impl.
+fn rust_in_trait(node: Node) -> bool {
+    let Some(container) = node.parent().and_then(|list| list.parent()) else {
+        return false;
     };
-
-    if !is_doc {
-        return None;
+    match container.kind() {
+        "trait_item" => true,
+        "impl_item" => container.child_by_field_name("trait").is_some(),
+        _ => false,
     }
+}
 
-    // For multi-line comments, also collect adjacent preceding comment siblings
-    let mut lines = vec![text.to_string()];
-    let mut prev = current.prev_named_sibling();
-    while let Some(p) = prev {
-        if !matches!(p.kind(), "line_comment" | "comment") {
-            break;
-        }
-        let pt = node_text(p, src);
-        // Must have the same doc prefix style
-        let same_style = match lang {
-            Lang::Rust => pt.starts_with("///"),
-            Lang::Go => pt.starts_with("//"),
-            Lang::Ruby => pt.starts_with("#"),
-            _ => false,
-        };
-        if !same_style {
-            break;
+/// The access section of a C++ member: the nearest `public:`/`private:`/
+/// `protected:` label above it, else the default for `class` (private) or
+/// `struct`/`union` (public).
+fn cpp_member_access(node: Node, src: &[u8]) -> String {
+    let mut prev = node.prev_sibling();
+    while let Some(sibling) = prev {
+        if sibling.kind() == "access_specifier" {
+            return node_text(sibling, src).trim().to_string();
         }
-        // Must be on the immediately preceding line
-        if current.start_position().row != p.end_position().row + 1 {
-            break;
-        }
-        lines.push(pt.to_string());
-        current = p;
-        prev = p.prev_named_sibling();
+        prev = sibling.prev_sibling();
     }
-
-    lines.reverse();
-    let joined = lines.join("\n");
-    let cleaned = strip_doc_prefix(&joined, lang);
-    if cleaned.is_empty() {
-        return None;
+    let class_like = node.parent().and_then(|p| p.parent()).map(|c| c.kind());
+    if class_like == Some("class_specifier") {
+        "private".to_string()
+    } else {
+        "public".to_string()
     }
-    Some(truncate_doc(&cleaned))
 }
 
-/// Strip language-specific doc comment prefixes and normalize whitespace.
+/// The visibility set by a bare `private`/`protected`/`public` line earlier
+/// in a Ruby class body.
+fn ruby_section_visibility(node: Node, src: &[u8]) -> Option<String> {
+    let mut prev = node.prev_named_sibling();
+    while let Some(sibling) = prev {
+        if sibling.kind() == "identifier" {
+            let text = node_text(sibling, src);
+            if matches!(text, "private" | "protected" | "public") {
+                return Some(text.to_string());
+            }
+        }
+        prev = sibling.prev_named_sibling();
+    }
+    None
+}
+
+/// Generic type parameters of a definition, normalised to `Name` or
+/// `Name: Bound`.
+///
+/// Bounds from the parameter list and from `where` clauses are merged and
+/// joined with ` + ` (`extends A & B` in Java and TypeScript becomes
+/// `T: A + B`).  Defaults are kept as `T = i32`; value parameters such as
+/// Rust `const N: usize` or C++ `int N` are kept as written.  C++ takes
+/// the parameters of an enclosing `template <...>`.
+fn symbol_generics(node: Node, src: &[u8], lang: Lang) -> Vec<String> {
+    let list = match lang {
+        Lang::Cpp => node
+            .parent()
+            .filter(|p| p.kind() == "template_declaration")
+            .and_then(|p| p.child_by_field_name("parameters")),
+        Lang::Rust
+        | Lang::TypeScript
+        | Lang::Tsx
+        | Lang::Vue
+        | Lang::Svelte
+        | Lang::Java
+        | Lang::Kotlin
+        | Lang::Scala
+        | Lang::CSharp
+        | Lang::Go
+        | Lang::Python => node.child_by_field_name("type_parameters").or_else(|| {
+            let mut cursor = node.walk();
+            node.children(&mut cursor)
+                .find(|c| matches!(c.kind(), "type_parameters" | "type_parameter_list"))
+        }),
+        // A function's type parameters are part of its signature.
+        Lang::Dart => {
+            let holder = dart_signature(node).unwrap_or(node);
+            let mut cursor = holder.walk();
+            holder
+                .children(&mut cursor)
+                .find(|c| c.kind() == "type_parameters")
+        }
+        _ => None,
+    };
+    let Some(list) = list else {
+        return Vec::new();
+    };
+
+    // (name, bounds, default) per parameter; value parameters and
+    // lifetimes keep their text as the name.
+    let mut params: Vec<(String, Vec<String>, Option<String>)> = Vec::new();
+    let mut cursor = list.walk();
+    for param in list.named_children(&mut cursor) {
+        match param.kind() {
+            // Rust, TypeScript, Java, Kotlin, C#.
+            "type_parameter" => {
+                let mut name = None;
+                let mut bounds = Vec::new();
+                let mut default = None;
+                let mut c = param.walk();
+                for child in param.named_children(&mut c) {
+                    match child.kind() {
+                        "type_identifier" | "identifier" if name.is_none() => {
+                            name = Some(node_text(child, src).to_string());
+                        }
+                        "trait_bounds" | "type_bound" | "constraint" => {
+                            bounds.extend(bound_terms(child, src));
+                        }
+                        // Kotlin: `T : Comparable<T>`.
+                        "user_type" | "nullable_type" if lang == Lang::Kotlin => {
+                            bounds.push(collapse_ws(node_text(child, src)));
+                        }
+                        "default_type" => {
+                            default =
+                                Some(collapse_ws(node_text(child, src).trim_start_matches('=')));
+                        }
+                        // Dart: `T extends num`.
+                        _ if param.child_by_field_name("bound") == Some(child) => {
+                            bounds.push(collapse_ws(node_text(child, src)));
+                        }
+                        _ if param.child_by_field_name("default_type") == Some(child) => {
+                            default = Some(collapse_ws(node_text(child, src)));
+                        }
+                        _ => {}
+                    }
+                }
+                if let Some(name) = name {
+                    params.push((name, bounds, default));
+                }
+            }
+            // Go: `K, V comparable`.
+            "type_parameter_declaration" if lang == Lang::Go => {
+                let bound = param
+                    .child_by_field_name("type")
+                    .map(|t| collapse_ws(node_text(t, src)));
+                let mut c = param.walk();
+                for name in param.children_by_field_name("name", &mut c) {
+                    params.push((
+                        node_text(name, src).to_string(),
+                        bound.iter().cloned().collect(),
+                        None,
+                    ));
+                }
+            }
+            // C++: `typename T`, `class U = int`, `typename... Ts`.
+            "type_parameter_declaration"
+            | "optional_type_parameter_declaration"
+            | "variadic_type_parameter_declaration" => {
+                let mut c = param.walk();
+                let name = param
+                    .named_children(&mut c)
+                    .find(|n| n.kind() == "type_identifier")
+                    .map(|n| node_text(n, src).to_string());
+                let default = param
+                    .child_by_field_name("default_type")
+                    .map(|d| collapse_ws(node_text(d, src)));
+                if let Some(name) = name {
+                    let name = if param.kind() == "variadic_type_parameter_declaration" {
+                        format!("...{name}")
+                    } else {
+                        name
+                    };
+                    params.push((name, Vec::new(), default));
+                }
+            }
+            // Scala: `A <: B : Ctx`, `+T`; a parameter's bounds follow its
+            // name as siblings.
+            "identifier" if lang == Lang::Scala => {
+                params.push((node_text(param, src).to_string(), Vec::new(), None));
+            }
+            "covariant_type_parameter" | "contravariant_type_parameter" if lang == Lang::Scala => {
+                let variance = if param.kind() == "covariant_type_parameter" {
+                    "+"
+                } else {
+                    "-"
+                };
+                let name = field_text(param, "name", src).unwrap_or_default();
+                let mut c = param.walk();
+                let bounds = param
+                    .named_children(&mut c)
+                    .filter_map(|b| scala_bound(b, src))
+                    .collect();
+                params.push((format!("{variance}{name}"), bounds, None));
+            }
+            "upper_bound" | "lower_bound" | "context_bound" | "view_bound"
+                if lang == Lang::Scala =>
+            {
+                if let Some(entry) = params.last_mut() {
+                    entry.1.extend(scala_bound(param, src));
+                }
+            }
+            // Python: `T`, `T: int`, `*Ts`.
+            "type" if lang == Lang::Python => {
+                let inner = param.named_child(0u32).unwrap_or(param);
+                if inner.kind() == "constrained_type" {
+                    let name = inner.named_child(0u32).map(|n| node_text(n, src));
+                    let bound = inner
+                        .named_child(1u32)
+                        .map(|n| collapse_ws(node_text(n, src)));
+                    if let Some(name) = name {
+                        params.push((name.to_string(), bound.into_iter().collect(), None));
+                    }
+                } else {
+                    params.push((collapse_ws(node_text(inner, src)), Vec::new(), None));
+                }
+            }
+            _ => params.push((collapse_ws(node_text(param, src)), Vec::new(), None)),
+        }
+    }
+
+    // `where` clauses (Rust, C#, Kotlin) add bounds to declared parameters.
+    let mut cursor = node.walk();
+    for clause in node.children(&mut cursor) {
+        let mut c = clause.walk();
+        let predicates: Vec<Node> = match clause.kind() {
+            "where_clause" => clause
+                .named_children(&mut c)
+                .filter(|p| p.kind() == "where_predicate")
+                .collect(),
+            "type_parameter_constraints_clause" => vec![clause],
+            // Kotlin: `where T : A, T : B`.
+            "type_constraints" => clause
+                .named_children(&mut c)
+                .filter(|p| p.kind() == "type_constraint")
+                .collect(),
+            _ => continue,
+        };
+        for predicate in predicates {
+            let target = predicate
+                .child_by_field_name("left")
+                .or_else(|| predicate.named_child(0u32))
+                .map(|n| node_text(n, src));
+            let mut terms = Vec::new();
+            let mut pc = predicate.walk();
+            for child in predicate.named_children(&mut pc) {
+                match child.kind() {
+                    "trait_bounds" => terms.extend(bound_terms(child, src)),
+                    "type_parameter_constraint" => {
+                        terms.push(collapse_ws(node_text(child, src)));
+                    }
+                    "user_type" | "nullable_type" if lang == Lang::Kotlin => {
+                        terms.push(collapse_ws(node_text(child, src)));
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(entry) = params.iter_mut().find(|(n, ..)| Some(n.as_str()) == target) {
+                entry.1.extend(terms);
+            }
+        }
+    }
+
+    params
+        .into_iter()
+        .map(|(name, bounds, default)| {
+            let mut out = name;
+            if !bounds.is_empty() {
+                out.push_str(": ");
+                out.push_str(&bounds.join(" + "));
+            }
+            if let Some(default) = default {
+                out.push_str(" = ");
+                out.push_str(&default);
+            }
+            out
+        })
+        .collect()
+}
+
+/// Split a bound list (`: Read + Send`, `extends A & B`) into its terms.
+fn bound_terms(node: Node, src: &[u8]) -> Vec<String> {
+    let mut cursor = node.walk();
+    let terms: Vec<String> = node
+        .named_children(&mut cursor)
+        .map(|t| collapse_ws(node_text(t, src)))
+        .collect();
+    if terms.is_empty() {
+        let text = node_text(node, src)
+            .trim_start_matches(':')
+            .trim_start()
+            .trim_start_matches("extends");
+        return vec![collapse_ws(text)];
+    }
+    terms
+}
+
+/// Collapse runs of whitespace to single spaces and trim the ends.
+fn collapse_ws(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The preprocessor condition under which a C/C++ definition is compiled,
+/// as a C expression joining every enclosing branch with `&&`.
+///
+/// `#ifdef X` is `defined(X)`, `#if EXPR` is `EXPR`, and `#elif`/`#else`
+/// branches add the negation of the branches before them.  Include guards
+/// (`#ifndef X` directly followed by `#define X`) are not conditions.
+fn preproc_condition(node: Node, src: &[u8]) -> Option<String> {
+    let mut terms: Vec<String> = Vec::new();
+    let mut child = node;
+    while let Some(parent) = child.parent() {
+        if !is_preproc_branch(parent.kind())
+            || parent.child_by_field_name("alternative") == Some(child)
+        {
+            child = parent;
+            continue;
+        }
+        // `child` is in the body of `parent`: take its own condition, then
+        // negate each earlier branch of the same `#if` chain.
+        let mut branch: Vec<String> = preproc_branch_condition(parent, src).into_iter().collect();
+        let mut head = parent;
+        while let Some(prev) = head.parent()
+            && is_preproc_branch(prev.kind())
+            && prev.child_by_field_name("alternative") == Some(head)
+        {
+            branch.extend(preproc_branch_condition(prev, src).map(|c| negate_condition(&c)));
+            head = prev;
+        }
+        if !is_include_guard(head, src) {
+            branch.reverse();
+            terms.splice(0..0, branch);
+        }
+        child = head;
+    }
+    match terms.len() {
+        0 => None,
+        1 => terms.pop(),
+        _ => Some(
+            terms
+                .iter()
+                .map(|t| {
+                    if t.contains(' ') && !is_negated_group(t) {
+                        format!("({t})")
+                    } else {
+                        t.clone()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" && "),
+        ),
+    }
+}
+
+fn is_preproc_branch(kind: &str) -> bool {
+    matches!(
+        kind,
+        "preproc_if" | "preproc_ifdef" | "preproc_elif" | "preproc_elifdef" | "preproc_else"
+    )
+}
+
+/// The condition a single `#if`/`#ifdef`/`#elif` branch tests (`None` for
+/// `#else`).
+fn preproc_branch_condition(node: Node, src: &[u8]) -> Option<String> {
+    match node.kind() {
+        "preproc_ifdef" | "preproc_elifdef" => {
+            let name = field_text(node, "name", src)?;
+            let directive = node.child(0).map(|d| node_text(d, src)).unwrap_or("");
+            if directive.ends_with("ndef") {
+                Some(format!("!defined({name})"))
+            } else {
+                Some(format!("defined({name})"))
+            }
+        }
+        "preproc_if" | "preproc_elif" => {
+            let condition = field_text(node, "condition", src)?;
+            Some(condition.split_whitespace().collect::<Vec<_>>().join(" "))
+        }
+        _ => None,
+    }
+}
+
+/// Whether `condition` is `!( ... )` with the group spanning the rest.
+fn is_negated_group(condition: &str) -> bool {
+    let Some(group) = condition.strip_prefix('!') else {
+        return false;
+    };
+    let mut depth = 0;
+    for (i, c) in group.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        if depth == 0 {
+            return i + 1 == group.len() && i > 0;
+        }
+    }
+    false
+}
+
+fn negate_condition(condition: &str) -> String {
+    match condition.strip_prefix('!') {
+        Some(rest) if !rest.contains(' ') => rest.to_string(),
+        _ if condition.contains(' ') => format!("!({condition})"),
+        _ => format!("!{condition}"),
+    }
+}
+
+/// Whether an `#ifndef X` wraps the file as an include guard: it defines `X`
+/// first and has no `#else`.
+fn is_include_guard(node: Node, src: &[u8]) -> bool {
+    if node.kind() != "preproc_ifdef" || node.child_by_field_name("alternative").is_some() {
+        return false;
+    }
+    let Some(name) = field_text(node, "name", src) else {
+        return false;
+    };
+    let is_ifndef = node
+        .child(0)
+        .is_some_and(|d| node_text(d, src).ends_with("ndef"));
+    let first = (0..node.named_child_count())
+        .filter_map(|i| node.named_child(i as u32))
+        .find(|c| c.kind() != "comment" && node.child_by_field_name("name") != Some(*c));
+    is_ifndef
+        && first
+            .is_some_and(|d| d.kind() == "preproc_def" && field_text(d, "name", src) == Some(name))
+}
+
+/// Maximum length in Unicode scalar values for extracted doc comments.
+const MAX_DOC_COMMENT_LEN: usize = 200;
+
+/// Extract a doc comment for the given symbol node.
+fn extract_doc_comment(node: Node, src: &[u8], lang: Lang) -> Option<String> {
+    match lang {
+        Lang::Python => extract_python_docstring(node, src),
+        Lang::Elixir => extract_elixir_doc(node, src),
+        // Docs precede the `statement` wrapping a `CREATE`.
+        Lang::Sql => {
+            let statement = node.parent().filter(|p| p.kind() == "statement");
+            extract_preceding_comment(statement.unwrap_or(node), src, lang)
+        }
+        // Member docs precede the `class_member` wrapping the declaration.
+        Lang::Dart => {
+            let member = node.parent().filter(|p| p.kind() == "class_member");
+            extract_preceding_comment(member.unwrap_or(node), src, lang)
+        }
+        _ => extract_preceding_comment(node, src, lang),
+    }
+}
+
+/// Extract a Python docstring: first `expression_statement > string` in the `body` field.
+fn extract_python_docstring(node: Node, src: &[u8]) -> Option<String> {
+    let body = node.child_by_field_name("body")?;
+    let first_stmt = body.named_child(0)?;
+    if first_stmt.kind() != "expression_statement" {
+        return None;
+    }
+    let string_node = first_stmt.named_child(0)?;
+    if string_node.kind() != "string" {
+        return None;
+    }
+    let text = node_text(string_node, src);
+    // Strip quotes: """...""", '''...''', "...", '...'
+    let stripped = text
+        .strip_prefix("\"\"\"")
+        .and_then(|s| s.strip_suffix("\"\"\""))
+        .or_else(|| text.strip_prefix("'''").and_then(|s| s.strip_suffix("'''")))
+        .or_else(|| text.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+        .or_else(|| text.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')))
+        .unwrap_or(text);
+    let cleaned = strip_doc_prefix(stripped, Lang::Python);
+    if cleaned.is_empty() {
+        return None;
+    }
+    Some(truncate_doc(&cleaned))
+}
+
+/// Extract an Elixir `@doc` attribute preceding a definition, skipping
+/// other attributes (`@spec`, `@impl`), or a module's leading `@moduledoc`.
+/// `@doc false` hides the definition from docs and yields nothing.
+fn extract_elixir_doc(node: Node, src: &[u8]) -> Option<String> {
+    let attribute = if elixir_call_target(node, src) == Some("defmodule") {
+        let body = elixir_child(node, "do_block")?;
+        let mut cursor = body.walk();
+        body.named_children(&mut cursor)
+            .find(|c| elixir_attribute(*c, src).is_some_and(|(name, _)| name == "moduledoc"))?
+    } else {
+        std::iter::successors(node.prev_named_sibling(), |n| n.prev_named_sibling())
+            .map_while(|n| elixir_attribute(n, src).map(|(name, _)| (n, name)))
+            .find(|(_, name)| *name == "doc")?
+            .0
+    };
+    let value = elixir_attribute(attribute, src)?.1?;
+    if value.kind() != "string" {
+        return None;
+    }
+    let text = node_text(value, src);
+    let stripped = text
+        .strip_prefix("\"\"\"")
+        .and_then(|s| s.strip_suffix("\"\"\""))
+        .or_else(|| text.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+        .unwrap_or(text);
+    let cleaned = strip_doc_prefix(stripped, Lang::Elixir);
+    if cleaned.is_empty() {
+        return None;
+    }
+    Some(truncate_doc(&cleaned))
+}
+
+/// Extract a doc comment from preceding sibling comment nodes.
+fn extract_preceding_comment(node: Node, src: &[u8], lang: Lang) -> Option<String> {
+    let mut current = node.prev_named_sibling()?;
+
+    // Skip attribute/decorator nodes
+    while let "attribute_item" | "decorator" | "annotation" | "attribute" | "attribute_list" =
+        current.kind()
+    {
+        current = current.prev_named_sibling()?;
+    }
+
+    // Check if the node is a comment
+    let is_comment = matches!(current.kind(), "line_comment" | "block_comment" | "comment");
+    if !is_comment {
+        return None;
+    }
+
+    let text = node_text(current, src);
+
+    // Check for doc comment prefix based on language
+    let is_doc = match lang {
+        Lang::Rust => text.starts_with("///") || text.starts_with("/**"),
+        Lang::TypeScript | Lang::Tsx | Lang::Vue | Lang::Svelte | Lang::JavaScript => {
+            text.starts_with("/**")
+        }
+        Lang::Go => text.starts_with("//"),
+        Lang::Java | Lang::CSharp => text.starts_with("/**") || text.starts_with("///"),
+        Lang::C | Lang::Cpp => text.starts_with("/**") || text.starts_with("///"),
+        Lang::Ruby => text.starts_with("#"),
+        Lang::Php | Lang::Kotlin | Lang::Scala => text.starts_with("/**"),
+        Lang::Lua => text.starts_with("---"),
+        Lang::Shell => text.starts_with('#') && !text.starts_with("#!"),
+        Lang::Sql => text.starts_with("--"),
+        Lang::Dart => text.starts_with("///") || text.starts_with("/**"),
+        Lang::Python | Lang::Elixir => false, // handled by docstring extractors
+    };
+
+    if !is_doc {
+        return None;
+    }
+
+    // For multi-line comments, also collect adjacent preceding comment siblings
+    let mut lines = vec![text.to_string()];
+    let mut prev = current.prev_named_sibling();
+    while let Some(p) = prev {
+        if !matches!(p.kind(), "line_comment" | "comment") {
+            break;
+        }
+        let pt = node_text(p, src);
+        // Must have the same doc prefix style
+        let same_style = match lang {
+            Lang::Rust | Lang::Dart => pt.starts_with("///"),
+            Lang::Go => pt.starts_with("//"),
+            Lang::Ruby => pt.starts_with("#"),
+            Lang::Lua => pt.starts_with("---"),
+            Lang::Shell => pt.starts_with('#') && !pt.starts_with("#!"),
+            Lang::Sql => pt.starts_with("--"),
+            _ => false,
+        };
+        if !same_style {
+            break;
+        }
+        // Must be on the immediately preceding line
+        if current.start_position().row != p.end_position().row + 1 {
+            break;
+        }
+        lines.push(pt.to_string());
+        current = p;
+        prev = p.prev_named_sibling();
+    }
+
+    lines.reverse();
+    let joined = lines.join("\n");
+    let cleaned = strip_doc_prefix(&joined, lang);
+    if cleaned.is_empty() {
+        return None;
+    }
+    Some(truncate_doc(&cleaned))
+}
+
+/// Strip language-specific doc comment prefixes and normalize whitespace.
 fn strip_doc_prefix(text: &str, lang: Lang) -> String {
     let lines: Vec<&str> = text.lines().collect();
     let cleaned: Vec<String> = lines
@@ -466,7 +1588,7 @@ fn strip_doc_prefix(text: &str, lang: Lang) -> String {
         .map(|line| {
             let trimmed = line.trim();
             match lang {
-                Lang::Rust => trimmed
+                Lang::Rust | Lang::Dart => trimmed
                     .strip_prefix("///")
                     .or_else(|| trimmed.strip_prefix("/**"))
                     .or_else(|| trimmed.strip_prefix("* "))
@@ -477,8 +1599,12 @@ fn strip_doc_prefix(text: &str, lang: Lang) -> String {
                     .to_string(),
                 Lang::TypeScript
                 | Lang::Tsx
+                | Lang::Vue
+                | Lang::Svelte
                 | Lang::JavaScript
                 | Lang::Java
+                | Lang::Kotlin
+                | Lang::Scala
                 | Lang::CSharp
                 | Lang::C
                 | Lang::Cpp
@@ -496,12 +1622,22 @@ fn strip_doc_prefix(text: &str, lang: Lang) -> String {
                     .unwrap_or(trimmed)
                     .trim()
                     .to_string(),
-                Lang::Ruby => trimmed
+                Lang::Ruby | Lang::Shell => trimmed
                     .strip_prefix('#')
                     .unwrap_or(trimmed)
                     .trim()
                     .to_string(),
-                Lang::Python => trimmed.to_string(),
+                Lang::Lua => trimmed
+                    .strip_prefix("---")
+                    .unwrap_or(trimmed)
+                    .trim()
+                    .to_string(),
+                Lang::Sql => trimmed
+                    .strip_prefix("--")
+                    .unwrap_or(trimmed)
+                    .trim()
+                    .to_string(),
+                Lang::Python | Lang::Elixir => trimmed.to_string(),
             }
         })
         .filter(|s| !s.is_empty() && s != "/")
@@ -1122,23 +2258,283 @@ fn extract_java(
 }
 
 // ---------------------------------------------------------------------------
-// C
+// Kotlin
 // ---------------------------------------------------------------------------
 
-fn extract_c(
+fn extract_kotlin(
     node: Node,
     kind: &str,
     src: &[u8],
     file: &str,
     scope: Option<&str>,
 ) -> Option<Symbol> {
+    let parent_kind = node.parent().map(|p| p.kind()).unwrap_or("");
+    let is_member = matches!(parent_kind, "class_body" | "enum_class_body");
     match kind {
-        "function_definition" => {
-            let declarator = node.child_by_field_name("declarator")?;
-            let name = find_identifier_in_declarator(declarator, src)?;
+        "class_declaration" => {
+            let name = field_text(node, "name", src)?;
+            let sk = if kotlin_is_interface(node) {
+                SymbolKind::Interface
+            } else if kotlin_has_modifier(node, src, "enum") {
+                SymbolKind::Enum
+            } else {
+                SymbolKind::Class
+            };
+            Some(make_symbol(name, sk, node, src, file, Lang::Kotlin, scope))
+        }
+        // `object Registry` is a singleton class.
+        "object_declaration" => {
+            let name = field_text(node, "name", src)?;
             Some(make_symbol(
                 name,
-                SymbolKind::Function,
+                SymbolKind::Class,
+                node,
+                src,
+                file,
+                Lang::Kotlin,
+                scope,
+            ))
+        }
+        "function_declaration" => {
+            let name_node = node.child_by_field_name("name")?;
+            let name = node_text(name_node, src);
+            // Extension functions (`fun String.shout()`) are scoped by their
+            // receiver type, written before the name.
+            let mut cursor = node.walk();
+            let receiver = node
+                .children(&mut cursor)
+                .take_while(|c| c.id() != name_node.id())
+                .find_map(|c| kotlin_type_name(c, src));
+            let sk = if is_member || receiver.is_some() {
+                SymbolKind::Method
+            } else {
+                SymbolKind::Function
+            };
+            Some(make_symbol(
+                name,
+                sk,
+                node,
+                src,
+                file,
+                Lang::Kotlin,
+                receiver.or(scope),
+            ))
+        }
+        // `constructor(...)` in a class body, named after its class.
+        "secondary_constructor" => {
+            let class = scope?;
+            Some(make_symbol(
+                class,
+                SymbolKind::Method,
+                node,
+                src,
+                file,
+                Lang::Kotlin,
+                scope,
+            ))
+        }
+        // Properties of classes and files; locals are not indexed.
+        "property_declaration" if is_member || parent_kind == "source_file" => {
+            let declaration = (0..node.named_child_count())
+                .filter_map(|i| node.named_child(i as u32))
+                .find(|c| c.kind() == "variable_declaration")?;
+            let name = declaration.named_child(0u32)?;
+            let sk = if kotlin_has_modifier(node, src, "const") {
+                SymbolKind::Constant
+            } else {
+                SymbolKind::Variable
+            };
+            Some(make_symbol(
+                node_text(name, src),
+                sk,
+                node,
+                src,
+                file,
+                Lang::Kotlin,
+                scope,
+            ))
+        }
+        "type_alias" => {
+            let name = field_text(node, "type", src)?;
+            Some(make_symbol(
+                name,
+                SymbolKind::TypeAlias,
+                node,
+                src,
+                file,
+                Lang::Kotlin,
+                scope,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Whether a Kotlin declaration's `modifiers` include `keyword`.
+fn kotlin_has_modifier(node: Node, src: &[u8], keyword: &str) -> bool {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|c| c.kind() == "modifiers")
+        .any(|m| {
+            let mut words = Vec::new();
+            collect_leaf_text(m, src, &mut words);
+            words.contains(&keyword)
+        })
+}
+
+// ---------------------------------------------------------------------------
+// Scala
+// ---------------------------------------------------------------------------
+
+fn extract_scala(
+    node: Node,
+    kind: &str,
+    src: &[u8],
+    file: &str,
+    scope: Option<&str>,
+) -> Option<Symbol> {
+    let parent_kind = node.parent().map(|p| p.kind()).unwrap_or("");
+    let is_member = parent_kind == "template_body";
+    match kind {
+        // `case class` included; `object Registry` is a singleton class.
+        "class_definition" | "object_definition" => {
+            let name = field_text(node, "name", src)?;
+            Some(make_symbol(
+                name,
+                SymbolKind::Class,
+                node,
+                src,
+                file,
+                Lang::Scala,
+                scope,
+            ))
+        }
+        "trait_definition" => {
+            let name = field_text(node, "name", src)?;
+            Some(make_symbol(
+                name,
+                SymbolKind::Trait,
+                node,
+                src,
+                file,
+                Lang::Scala,
+                scope,
+            ))
+        }
+        "enum_definition" => {
+            let name = field_text(node, "name", src)?;
+            Some(make_symbol(
+                name,
+                SymbolKind::Enum,
+                node,
+                src,
+                file,
+                Lang::Scala,
+                scope,
+            ))
+        }
+        "function_definition" | "function_declaration" => {
+            let name = field_text(node, "name", src)?;
+            // Extension methods (`extension (s: String) def shout`) are
+            // scoped by their receiver type.
+            let receiver = scala_extension_receiver(node, src);
+            let sk = if is_member || receiver.is_some() {
+                SymbolKind::Method
+            } else {
+                SymbolKind::Function
+            };
+            Some(make_symbol(
+                name,
+                sk,
+                node,
+                src,
+                file,
+                Lang::Scala,
+                receiver.or(scope),
+            ))
+        }
+        // Fields of classes, traits, and objects, and top-level values;
+        // locals and destructuring patterns are not indexed.
+        "val_definition" | "val_declaration" | "var_definition" | "var_declaration"
+            if is_member || parent_kind == "compilation_unit" =>
+        {
+            let name = node
+                .child_by_field_name("pattern")
+                .or_else(|| node.child_by_field_name("name"))
+                .filter(|n| n.kind() == "identifier")?;
+            let sk = if kind.starts_with("val") {
+                SymbolKind::Constant
+            } else {
+                SymbolKind::Variable
+            };
+            Some(make_symbol(
+                node_text(name, src),
+                sk,
+                node,
+                src,
+                file,
+                Lang::Scala,
+                scope,
+            ))
+        }
+        "type_definition" => {
+            let name = field_text(node, "name", src)?;
+            Some(make_symbol(
+                name,
+                SymbolKind::TypeAlias,
+                node,
+                src,
+                file,
+                Lang::Scala,
+                scope,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// The receiver type of a method declared in a Scala 3 `extension`.
+fn scala_extension_receiver<'a>(node: Node, src: &'a [u8]) -> Option<&'a str> {
+    let extension = node
+        .parent()
+        .filter(|p| p.kind() == "extension_definition")?;
+    let params = extension.child_by_field_name("parameters")?;
+    let mut cursor = params.walk();
+    let receiver = params
+        .named_children(&mut cursor)
+        .find(|p| p.kind() == "parameter")?;
+    scala_type_name(receiver.child_by_field_name("type")?, src)
+}
+
+/// A Scala type parameter bound as recorded in generics: the type of
+/// `<: B`, `: Ctx`, or `<% V`, and `>: L` with its operator.
+fn scala_bound(node: Node, src: &[u8]) -> Option<String> {
+    let ty = collapse_ws(node_text(node.child_by_field_name("type")?, src));
+    match node.kind() {
+        "upper_bound" | "context_bound" | "view_bound" => Some(ty),
+        "lower_bound" => Some(format!(">: {ty}")),
+        _ => None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// C
+// ---------------------------------------------------------------------------
+
+fn extract_c(
+    node: Node,
+    kind: &str,
+    src: &[u8],
+    file: &str,
+    scope: Option<&str>,
+) -> Option<Symbol> {
+    match kind {
+        "function_definition" => {
+            let declarator = node.child_by_field_name("declarator")?;
+            let name = find_identifier_in_declarator(declarator, src)?;
+            Some(make_symbol(
+                name,
+                SymbolKind::Function,
                 node,
                 src,
                 file,
@@ -1454,7 +2850,15 @@ fn extract_php(
     match kind {
         "function_definition" => {
             let name = field_text(node, "name", src)?;
-            let sk = if scope.is_some() {
+            // Namespaces scope functions too; only a class-like ancestor
+            // makes one a method.
+            let in_class = std::iter::successors(node.parent(), |n| n.parent()).any(|n| {
+                matches!(
+                    n.kind(),
+                    "class_declaration" | "interface_declaration" | "trait_declaration"
+                )
+            });
+            let sk = if in_class {
                 SymbolKind::Method
             } else {
                 SymbolKind::Function
@@ -1653,64 +3057,746 @@ fn extract_csharp(
                 scope,
             ))
         }
-        "method_declaration" | "constructor_declaration" => {
-            let name = field_text(node, "name", src)?;
-            Some(make_symbol(
+        "method_declaration" | "constructor_declaration" => {
+            let name = field_text(node, "name", src)?;
+            Some(make_symbol(
+                name,
+                SymbolKind::Method,
+                node,
+                src,
+                file,
+                Lang::CSharp,
+                scope,
+            ))
+        }
+        "property_declaration" => {
+            let name = field_text(node, "name", src)?;
+            Some(make_symbol(
+                name,
+                SymbolKind::Method,
+                node,
+                src,
+                file,
+                Lang::CSharp,
+                scope,
+            ))
+        }
+        // Positional record parameters: `record Person(string Name)` declares
+        // a `Name` property.
+        "parameter"
+            if node
+                .parent()
+                .and_then(|p| p.parent())
+                .is_some_and(|g| g.kind() == "record_declaration") =>
+        {
+            let name = field_text(node, "name", src)?;
+            Some(make_symbol(
+                name,
+                SymbolKind::Method,
+                node,
+                src,
+                file,
+                Lang::CSharp,
+                scope,
+            ))
+        }
+        "field_declaration" => {
+            // `public const int MAX = 100;` or `private int _count;`
+            for i in 0..node.named_child_count() {
+                if let Some(child) = node.named_child(i as u32)
+                    && child.kind() == "variable_declaration"
+                {
+                    for j in 0..child.named_child_count() {
+                        if let Some(decl) = child.named_child(j as u32)
+                            && decl.kind() == "variable_declarator"
+                            && let Some(name_node) = decl.child_by_field_name("name")
+                        {
+                            let name = node_text(name_node, src);
+                            let text = node_text(node, src);
+                            let sk = if text.contains("const") || text.contains("readonly") {
+                                SymbolKind::Constant
+                            } else {
+                                SymbolKind::Variable
+                            };
+                            return Some(make_symbol(
+                                name,
+                                sk,
+                                node,
+                                src,
+                                file,
+                                Lang::CSharp,
+                                scope,
+                            ));
+                        }
+                    }
+                }
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Lua
+// ---------------------------------------------------------------------------
+
+fn extract_lua(
+    node: Node,
+    kind: &str,
+    src: &[u8],
+    file: &str,
+    scope: Option<&str>,
+) -> Option<Symbol> {
+    match kind {
+        // `function f()`, `local function f()`, `function M.f()`, and
+        // `function M:f()`; the table the function is stored in is its scope.
+        "function_declaration" => {
+            let (table, name, method) = lua_function_name(node.child_by_field_name("name")?, src)?;
+            let sk = if method {
+                SymbolKind::Method
+            } else {
+                SymbolKind::Function
+            };
+            Some(lua_symbol(name, sk, node, src, file, table.or(scope)))
+        }
+        // `M.handler = function() end` and `local f = function() end` at
+        // the top level of a file.
+        "assignment_statement" => {
+            let outer = match node.parent() {
+                Some(p) if p.kind() == "variable_declaration" => p,
+                _ => node,
+            };
+            if outer.parent().is_none_or(|p| p.kind() != "chunk") {
+                return None;
+            }
+            let (table, name) = lua_assigned_function(node, src)?;
+            Some(lua_symbol(
+                name,
+                SymbolKind::Function,
+                outer,
+                src,
+                file,
+                table.or(scope),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// A Lua symbol whose signature is its first line: `{` opens a table
+/// constructor, not the body, so [`first_line`] would run into the body.
+fn lua_symbol(
+    name: &str,
+    kind: SymbolKind,
+    node: Node,
+    src: &[u8],
+    file: &str,
+    scope: Option<&str>,
+) -> Symbol {
+    let mut sym = make_symbol(name, kind, node, src, file, Lang::Lua, scope);
+    sym.signature = node_text(node, src)
+        .lines()
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_string();
+    sym
+}
+
+/// Split a Lua function name into its table, name, and whether it is a
+/// `:` method: `M.util.f` → (`M.util`, `f`, false), `M:f` → (`M`, `f`, true).
+fn lua_function_name<'a>(name: Node, src: &'a [u8]) -> Option<(Option<&'a str>, &'a str, bool)> {
+    match name.kind() {
+        "identifier" => Some((None, node_text(name, src), false)),
+        "dot_index_expression" => Some((
+            Some(field_text(name, "table", src)?),
+            field_text(name, "field", src)?,
+            false,
+        )),
+        "method_index_expression" => Some((
+            Some(field_text(name, "table", src)?),
+            field_text(name, "method", src)?,
+            true,
+        )),
+        _ => None,
+    }
+}
+
+/// The target of an assignment of a single function value, split like
+/// [`lua_function_name`].
+fn lua_assigned_function<'a>(
+    assignment: Node,
+    src: &'a [u8],
+) -> Option<(Option<&'a str>, &'a str)> {
+    let mut cursor = assignment.walk();
+    let mut children = assignment.named_children(&mut cursor);
+    let targets = children.find(|c| c.kind() == "variable_list")?;
+    let values = children.find(|c| c.kind() == "expression_list")?;
+    if targets.named_child_count() != 1
+        || values.named_child_count() != 1
+        || values.named_child(0)?.kind() != "function_definition"
+    {
+        return None;
+    }
+    let (table, name, _) = lua_function_name(targets.named_child(0)?, src)?;
+    Some((table, name))
+}
+
+/// The module named by a `require("mod")` or `require "mod"` call.
+fn lua_required_module<'a>(call: Node, src: &'a [u8]) -> Option<&'a str> {
+    let callee = call.child_by_field_name("name")?;
+    if callee.kind() != "identifier" || node_text(callee, src) != "require" {
+        return None;
+    }
+    let args = call.child_by_field_name("arguments")?;
+    let module = args.named_child(0).filter(|a| a.kind() == "string")?;
+    field_text(module, "content", src)
+}
+
+// ---------------------------------------------------------------------------
+// Elixir
+// ---------------------------------------------------------------------------
+
+/// Macros that define a named function.
+const ELIXIR_DEFS: &[&str] = &["def", "defp", "defmacro", "defmacrop"];
+
+/// Macros that bring another module into scope.
+const ELIXIR_DIRECTIVES: &[&str] = &["alias", "import", "use", "require"];
+
+/// Module attributes that hold typespecs rather than code.
+const ELIXIR_TYPESPECS: &[&str] = &[
+    "spec",
+    "type",
+    "typep",
+    "opaque",
+    "callback",
+    "macrocallback",
+];
+
+/// Special forms and definition macros that read as calls in the grammar
+/// but are language syntax rather than calls of a user function.
+const ELIXIR_SPECIAL_FORMS: &[&str] = &[
+    "def",
+    "defp",
+    "defmacro",
+    "defmacrop",
+    "defmodule",
+    "defstruct",
+    "defprotocol",
+    "defimpl",
+    "defdelegate",
+    "defguard",
+    "defguardp",
+    "defexception",
+    "alias",
+    "import",
+    "use",
+    "require",
+    "if",
+    "unless",
+    "case",
+    "cond",
+    "with",
+    "for",
+    "try",
+    "receive",
+    "quote",
+    "unquote",
+];
+
+fn extract_elixir(
+    node: Node,
+    kind: &str,
+    src: &[u8],
+    file: &str,
+    scope: Option<&str>,
+) -> Option<Symbol> {
+    if kind != "call" {
+        return None;
+    }
+    match elixir_call_target(node, src)? {
+        "defmodule" => {
+            let name = elixir_child(node, "arguments")?
+                .named_child(0)
+                .filter(|a| a.kind() == "alias")?;
+            Some(elixir_symbol(
+                node_text(name, src),
+                SymbolKind::Module,
+                node,
+                src,
+                file,
+                scope,
+            ))
+        }
+        // A struct takes the name of the module that defines it.
+        "defstruct" => Some(elixir_symbol(
+            scope?,
+            SymbolKind::Struct,
+            node,
+            src,
+            file,
+            scope,
+        )),
+        _ => {
+            let name = elixir_function_name(node, src)?;
+            Some(elixir_symbol(
+                name,
+                SymbolKind::Function,
+                node,
+                src,
+                file,
+                scope,
+            ))
+        }
+    }
+}
+
+/// An Elixir symbol whose signature is its first line without the trailing
+/// `do`: `def greet(name) do` → `def greet(name)`.
+fn elixir_symbol(
+    name: &str,
+    kind: SymbolKind,
+    node: Node,
+    src: &[u8],
+    file: &str,
+    scope: Option<&str>,
+) -> Symbol {
+    let mut sym = make_symbol(name, kind, node, src, file, Lang::Elixir, scope);
+    let line = node_text(node, src).lines().next().unwrap_or("").trim();
+    sym.signature = line.strip_suffix(" do").unwrap_or(line).to_string();
+    sym
+}
+
+/// The name of the macro or function a `call` invokes, when it is a plain
+/// identifier (`def`, `alias`, `helper`) rather than `Mod.fun`.
+fn elixir_call_target<'a>(call: Node, src: &'a [u8]) -> Option<&'a str> {
+    if call.kind() != "call" {
+        return None;
+    }
+    let target = call.child_by_field_name("target")?;
+    (target.kind() == "identifier").then(|| node_text(target, src))
+}
+
+/// The first child of `node` with the given kind (`arguments`, `do_block`).
+fn elixir_child<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor).find(|c| c.kind() == kind)
+}
+
+/// The head of a `def`-family call: `greet(name)` in `def greet(name) do`,
+/// with any `when` guard removed.
+fn elixir_function_head<'a>(call: Node<'a>, src: &[u8]) -> Option<Node<'a>> {
+    if !ELIXIR_DEFS.contains(&elixir_call_target(call, src)?) {
+        return None;
+    }
+    let head = elixir_child(call, "arguments")?.named_child(0)?;
+    if head.kind() == "binary_operator" && field_text(head, "operator", src) == Some("when") {
+        head.child_by_field_name("left")
+    } else {
+        Some(head)
+    }
+}
+
+/// The name defined by a `def`, `defp`, `defmacro`, or `defmacrop` call.
+fn elixir_function_name<'a>(call: Node, src: &'a [u8]) -> Option<&'a str> {
+    let head = elixir_function_head(call, src)?;
+    match head.kind() {
+        "call" => elixir_call_target(head, src),
+        // `def name, do: ...` without parentheses.
+        "identifier" => Some(node_text(head, src)),
+        _ => None,
+    }
+}
+
+/// The name and value of a module attribute: `@doc "..."` → (`doc`,
+/// the string); a bare `@attr` has no value.
+fn elixir_attribute<'a>(node: Node<'a>, src: &'a [u8]) -> Option<(&'a str, Option<Node<'a>>)> {
+    if node.kind() != "unary_operator" || field_text(node, "operator", src) != Some("@") {
+        return None;
+    }
+    let operand = node.child_by_field_name("operand")?;
+    match operand.kind() {
+        "identifier" => Some((node_text(operand, src), None)),
+        "call" => Some((
+            elixir_call_target(operand, src)?,
+            elixir_child(operand, "arguments").and_then(|a| a.named_child(0)),
+        )),
+        _ => None,
+    }
+}
+
+/// The modules named by an `alias`, `import`, `use`, or `require`
+/// directive; `alias MyApp.{User, Post}` names both `MyApp.User` and
+/// `MyApp.Post`.
+fn elixir_directive_modules(call: Node, src: &[u8]) -> Vec<String> {
+    let directive = elixir_call_target(call, src);
+    if !directive.is_some_and(|d| ELIXIR_DIRECTIVES.contains(&d)) {
+        return Vec::new();
+    }
+    let Some(target) = elixir_child(call, "arguments").and_then(|a| a.named_child(0)) else {
+        return Vec::new();
+    };
+    match target.kind() {
+        "alias" => vec![node_text(target, src).to_string()],
+        "dot" => {
+            let (Some(base), Some(group)) = (
+                target.child_by_field_name("left"),
+                target.child_by_field_name("right"),
+            ) else {
+                return Vec::new();
+            };
+            if base.kind() != "alias" || group.kind() != "tuple" {
+                return Vec::new();
+            }
+            let mut cursor = group.walk();
+            group
+                .named_children(&mut cursor)
+                .filter(|m| m.kind() == "alias")
+                .map(|m| format!("{}.{}", node_text(base, src), node_text(m, src)))
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Dart
+// ---------------------------------------------------------------------------
+
+fn extract_dart(
+    node: Node,
+    kind: &str,
+    src: &[u8],
+    file: &str,
+    scope: Option<&str>,
+) -> Option<Symbol> {
+    let parent_kind = node.parent().map(|p| p.kind()).unwrap_or("");
+    match kind {
+        "class_declaration" | "mixin_declaration" | "enum_declaration" => {
+            let name = field_text(node, "name", src)?;
+            let sk = match kind {
+                "mixin_declaration" => SymbolKind::Trait,
+                "enum_declaration" => SymbolKind::Enum,
+                _ => SymbolKind::Class,
+            };
+            Some(make_symbol(name, sk, node, src, file, Lang::Dart, scope))
+        }
+        // `extension on String` has no name; it is known by the type it extends.
+        "extension_declaration" => {
+            let name = node
+                .child_by_field_name("name")
+                .or_else(|| node.child_by_field_name("class"))?;
+            Some(make_symbol(
+                node_text(name, src),
+                SymbolKind::Module,
+                node,
+                src,
+                file,
+                Lang::Dart,
+                scope,
+            ))
+        }
+        // `typedef Predicate<T> = bool Function(T);` is named by its first
+        // type identifier.
+        "type_alias" => {
+            let mut cursor = node.walk();
+            let name = node
+                .named_children(&mut cursor)
+                .find(|c| c.kind() == "type_identifier")?;
+            Some(make_symbol(
+                node_text(name, src),
+                SymbolKind::TypeAlias,
+                node,
+                src,
+                file,
+                Lang::Dart,
+                scope,
+            ))
+        }
+        "function_declaration" | "getter_declaration" | "setter_declaration"
+            if parent_kind == "source_file" =>
+        {
+            dart_function(node, SymbolKind::Function, src, file, scope)
+        }
+        "method_declaration" => dart_function(node, SymbolKind::Method, src, file, scope),
+        // Members without a body: abstract methods, constructors, and fields.
+        "declaration" if parent_kind == "class_member" => match dart_signature(node) {
+            Some(_) => dart_function(node, SymbolKind::Method, src, file, scope),
+            None => dart_variable(node, src, file, scope),
+        },
+        "top_level_variable_declaration" => dart_variable(node, src, file, scope),
+        _ => None,
+    }
+}
+
+/// A Dart function, method, accessor, or constructor, with its signature
+/// (`static Shape parse(String s)`) as written before the body.
+fn dart_function(
+    node: Node,
+    kind: SymbolKind,
+    src: &[u8],
+    file: &str,
+    scope: Option<&str>,
+) -> Option<Symbol> {
+    let signature = dart_signature(node)?;
+    let name = dart_member_name(signature, src)?;
+    let mut sym = make_symbol(&name, kind, node, src, file, Lang::Dart, scope);
+    let written = node.child_by_field_name("signature").unwrap_or(signature);
+    sym.signature = collapse_ws(node_text(written, src));
+    Some(sym)
+}
+
+/// A Dart field or top-level variable, named by its first declared name;
+/// `const` declarations are constants.
+fn dart_variable(node: Node, src: &[u8], file: &str, scope: Option<&str>) -> Option<Symbol> {
+    let mut cursor = node.walk();
+    let mut is_const = false;
+    let mut list = None;
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "const" => is_const = true,
+            "initialized_identifier_list" | "static_final_declaration_list" => list = Some(child),
+            _ => {}
+        }
+    }
+    let name = field_text(list?.named_child(0u32)?, "name", src)?;
+    let sk = if is_const {
+        SymbolKind::Constant
+    } else {
+        SymbolKind::Variable
+    };
+    Some(make_symbol(name, sk, node, src, file, Lang::Dart, scope))
+}
+
+/// The signature node naming a Dart function or member: the
+/// `function_signature`, `getter_signature`, `constructor_signature`, ...
+/// under a declaration or its `method_signature`.
+fn dart_signature(node: Node) -> Option<Node> {
+    let outer = match node.kind() {
+        "declaration" => node,
+        _ => node.child_by_field_name("signature")?,
+    };
+    if outer.kind() != "method_signature" && outer.kind().ends_with("_signature") {
+        return Some(outer);
+    }
+    let mut cursor = outer.walk();
+    outer
+        .named_children(&mut cursor)
+        .find(|c| c.kind().ends_with("_signature"))
+}
+
+/// The name a Dart signature declares.  Constructors are named by their
+/// last identifier (`unit` for `Shape.unit()`, `Shape` for `Shape()`), and
+/// operators by their symbol (`operator ==`).
+fn dart_member_name(signature: Node, src: &[u8]) -> Option<String> {
+    if signature.kind() == "operator_signature" {
+        let op = field_text(signature, "operator", src)?;
+        return Some(format!("operator {op}"));
+    }
+    let mut cursor = signature.walk();
+    let name = signature
+        .children_by_field_name("name", &mut cursor)
+        .last()?;
+    Some(node_text(name, src).to_string())
+}
+
+/// The URI of a Dart `import`, `export`, or `part` directive.
+fn dart_directive_uri<'a>(node: Node, src: &'a [u8]) -> Option<&'a str> {
+    let uri = match node.kind() {
+        "import_specification" | "library_export" | "part_directive" => {
+            node.child_by_field_name("uri")?
+        }
+        _ => return None,
+    };
+    // `import 'a.dart' if (dart.library.io) 'b.dart'` imports its default.
+    let uri = match uri.kind() {
+        "configurable_uri" => uri.named_child(0u32)?,
+        _ => uri,
+    };
+    let text = node_text(uri, src).trim();
+    Some(text.trim_matches(|c| c == '\'' || c == '"'))
+}
+
+/// The simple name of a Dart supertype: the `Comparable` of
+/// `Comparable<T>`.
+fn dart_type_name<'a>(node: Node, src: &'a [u8]) -> Option<&'a str> {
+    let name = node.named_child(0u32)?;
+    (name.kind() == "type_identifier").then(|| node_text(name, src))
+}
+
+// ---------------------------------------------------------------------------
+// Shell
+// ---------------------------------------------------------------------------
+
+fn extract_shell(
+    node: Node,
+    kind: &str,
+    src: &[u8],
+    file: &str,
+    scope: Option<&str>,
+) -> Option<Symbol> {
+    // `name() { ... }` and `function name { ... }`.
+    if kind != "function_definition" {
+        return None;
+    }
+    let name = field_text(node, "name", src)?;
+    Some(make_symbol(
+        name,
+        SymbolKind::Function,
+        node,
+        src,
+        file,
+        Lang::Shell,
+        scope,
+    ))
+}
+
+/// The path a `source` or `.` command includes, unquoted and otherwise as
+/// written: `./lib/common.sh`, `$(dirname "$0")/env.sh`.
+fn shell_sourced_path<'a>(node: Node, src: &'a [u8]) -> Option<&'a str> {
+    if node.kind() != "command" {
+        return None;
+    }
+    let name = node_text(node.child_by_field_name("name")?, src);
+    if !matches!(name, "source" | ".") {
+        return None;
+    }
+    let arg = node.child_by_field_name("argument")?;
+    let text = node_text(arg, src);
+    let path = match arg.kind() {
+        "string" => text.strip_prefix('"')?.strip_suffix('"')?,
+        "raw_string" => text.strip_prefix('\'')?.strip_suffix('\'')?,
+        "word" | "concatenation" => text,
+        _ => return None,
+    };
+    (!path.is_empty()).then_some(path)
+}
+
+// ---------------------------------------------------------------------------
+// SQL
+// ---------------------------------------------------------------------------
+
+fn extract_sql(
+    node: Node,
+    kind: &str,
+    src: &[u8],
+    file: &str,
+    scope: Option<&str>,
+) -> Option<Symbol> {
+    let sk = match kind {
+        "create_table" | "create_view" | "create_materialized_view" => SymbolKind::Struct,
+        "create_function" => SymbolKind::Function,
+        "create_type" => {
+            let mut cursor = node.walk();
+            if node
+                .children(&mut cursor)
+                .any(|c| c.kind() == "keyword_enum")
+            {
+                SymbolKind::Enum
+            } else {
+                SymbolKind::TypeAlias
+            }
+        }
+        // Named after the `column` field; the object reference is the table.
+        "create_index" => {
+            let name = sql_identifier(node.child_by_field_name("column")?, src);
+            return Some(make_symbol(
                 name,
-                SymbolKind::Method,
+                SymbolKind::Variable,
                 node,
                 src,
                 file,
-                Lang::CSharp,
+                Lang::Sql,
                 scope,
-            ))
+            ));
         }
-        "property_declaration" => {
-            let name = field_text(node, "name", src)?;
-            Some(make_symbol(
+        // The grammar has no `CREATE PROCEDURE`; recover the name from the
+        // statement it failed to parse.
+        "ERROR" => {
+            let (schema, name) = sql_procedure_name(node_text(node, src))?;
+            return Some(make_symbol(
                 name,
-                SymbolKind::Method,
+                SymbolKind::Function,
                 node,
                 src,
                 file,
-                Lang::CSharp,
-                scope,
-            ))
-        }
-        "field_declaration" => {
-            // `public const int MAX = 100;` or `private int _count;`
-            for i in 0..node.named_child_count() {
-                if let Some(child) = node.named_child(i as u32)
-                    && child.kind() == "variable_declaration"
-                {
-                    for j in 0..child.named_child_count() {
-                        if let Some(decl) = child.named_child(j as u32)
-                            && decl.kind() == "variable_declarator"
-                            && let Some(name_node) = decl.child_by_field_name("name")
-                        {
-                            let name = node_text(name_node, src);
-                            let text = node_text(node, src);
-                            let sk = if text.contains("const") || text.contains("readonly") {
-                                SymbolKind::Constant
-                            } else {
-                                SymbolKind::Variable
-                            };
-                            return Some(make_symbol(
-                                name,
-                                sk,
-                                node,
-                                src,
-                                file,
-                                Lang::CSharp,
-                                scope,
-                            ));
-                        }
-                    }
-                }
-            }
-            None
+                Lang::Sql,
+                schema.or(scope),
+            ));
         }
-        _ => None,
+        _ => return None,
+    };
+    let (schema, name) = sql_object_name(node, src)?;
+    Some(make_symbol(
+        name,
+        sk,
+        node,
+        src,
+        file,
+        Lang::Sql,
+        schema.or(scope),
+    ))
+}
+
+/// The schema and name of the first object a statement names:
+/// `public.orders` → `(Some("public"), "orders")`.
+fn sql_object_name<'a>(node: Node, src: &'a [u8]) -> Option<(Option<&'a str>, &'a str)> {
+    let mut cursor = node.walk();
+    let reference = node
+        .named_children(&mut cursor)
+        .find(|c| c.kind() == "object_reference")?;
+    let name = sql_identifier(reference.child_by_field_name("name")?, src);
+    let schema = reference
+        .child_by_field_name("schema")
+        .map(|s| sql_identifier(s, src));
+    Some((schema, name))
+}
+
+/// An identifier without its quotes: `"Users"`, `` `users` ``, `[users]`.
+fn sql_identifier<'a>(node: Node, src: &'a [u8]) -> &'a str {
+    unquote_sql(node_text(node, src))
+}
+
+fn unquote_sql(text: &str) -> &str {
+    text.trim_matches(|c| matches!(c, '"' | '`' | '[' | ']'))
+}
+
+/// The schema and name of `CREATE [OR REPLACE|OR ALTER] PROCEDURE|PROC
+/// [schema.]name`, read from the statement text.
+fn sql_procedure_name(text: &str) -> Option<(Option<&str>, &str)> {
+    let mut words = text.split_whitespace();
+    if !words.next()?.eq_ignore_ascii_case("create") {
+        return None;
+    }
+    let mut word = words.next()?;
+    if word.eq_ignore_ascii_case("or") {
+        words.next()?;
+        word = words.next()?;
+    }
+    if !(word.eq_ignore_ascii_case("procedure") || word.eq_ignore_ascii_case("proc")) {
+        return None;
+    }
+    let qualified = words.next()?.split('(').next()?;
+    let (schema, name) = match qualified.rsplit_once('.') {
+        Some((schema, name)) => (Some(unquote_sql(schema)), unquote_sql(name)),
+        None => (None, unquote_sql(qualified)),
+    };
+    (!name.is_empty()).then_some((schema, name))
+}
+
+/// Whether an object reference names a table being used rather than the
+/// object a `CREATE` defines.
+fn sql_is_table_use(node: Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    match parent.kind() {
+        "relation" | "insert" | "from" | "constraint" | "column_definition" | "alter_table"
+        | "drop_table" => true,
+        // `CREATE INDEX name ON table`: the reference is the table.
+        "create_index" => true,
+        _ => false,
     }
 }
 
@@ -1821,6 +3907,12 @@ fn walk_refs(
             refs.push(r);
         }
 
+        // Check for JSX component usages (render sites)
+        if let Some(mut r) = match_component_ref(node, kind, src, file, lang, source_lines) {
+            r.caller_name = find_enclosing_function(node, src, lang);
+            refs.push(r);
+        }
+
         // Check for import references
         refs.extend(match_import_ref(node, kind, src, file, lang, source_lines));
 
@@ -1853,6 +3945,10 @@ fn make_ref(
         file: file.to_string(),
         line: row + 1,
         col: node.start_position().column,
+        // References have no end line, so a multi-line node has no end column.
+        end_col: (node.end_position().row == row).then(|| node.end_position().column),
+        start_byte: Some(node.start_byte()),
+        end_byte: Some(node.end_byte()),
         context: get_context_line(source_lines, row),
         caller_name: None,
         confidence: 0.5,
@@ -1869,7 +3965,7 @@ fn is_function_node(kind: &str, lang: Lang) -> bool {
     match lang {
         Lang::Rust => kind == "function_item",
         Lang::Python => kind == "function_definition",
-        Lang::JavaScript | Lang::TypeScript | Lang::Tsx => matches!(
+        Lang::JavaScript | Lang::TypeScript | Lang::Tsx | Lang::Vue | Lang::Svelte => matches!(
             kind,
             "function_declaration"
                 | "generator_function_declaration"
@@ -1878,10 +3974,24 @@ fn is_function_node(kind: &str, lang: Lang) -> bool {
         ),
         Lang::Go => matches!(kind, "function_declaration" | "method_declaration"),
         Lang::Java => matches!(kind, "method_declaration" | "constructor_declaration"),
+        Lang::Kotlin => kind == "function_declaration",
+        Lang::Scala => kind == "function_definition",
         Lang::C | Lang::Cpp => kind == "function_definition",
         Lang::Ruby => matches!(kind, "method" | "singleton_method"),
         Lang::Php => matches!(kind, "function_definition" | "method_declaration"),
         Lang::CSharp => matches!(kind, "method_declaration" | "constructor_declaration"),
+        Lang::Lua => matches!(kind, "function_declaration" | "function_definition"),
+        Lang::Shell => kind == "function_definition",
+        Lang::Sql => kind == "create_function",
+        // Narrowed to `def`-family calls by `find_enclosing_function`.
+        Lang::Elixir => kind == "call",
+        Lang::Dart => matches!(
+            kind,
+            "function_declaration"
+                | "getter_declaration"
+                | "setter_declaration"
+                | "method_declaration"
+        ),
     }
 }
 
@@ -1895,7 +4005,11 @@ fn function_name_from_node(node: Node, src: &[u8], lang: Lang) -> Option<String>
 
     // Arrow functions don't have a "name" field — look at the parent for
     // `variable_declarator` (e.g. `const foo = () => { ... }`).
-    if matches!(lang, Lang::JavaScript | Lang::TypeScript | Lang::Tsx) && kind == "arrow_function" {
+    if matches!(
+        lang,
+        Lang::JavaScript | Lang::TypeScript | Lang::Tsx | Lang::Vue | Lang::Svelte
+    ) && kind == "arrow_function"
+    {
         let parent = node.parent()?;
         if parent.kind() == "variable_declarator" {
             let name_node = parent.child_by_field_name("name")?;
@@ -1914,6 +4028,34 @@ fn function_name_from_node(node: Node, src: &[u8], lang: Lang) -> Option<String>
         return find_identifier_in_declarator(declarator, src).map(|s| s.to_string());
     }
 
+    // Lua names may be qualified (`M:f`), and function values take the
+    // name they are assigned to (`M.f = function() end`).
+    if lang == Lang::Lua {
+        let name = match kind {
+            "function_declaration" => lua_function_name(node.child_by_field_name("name")?, src)?.1,
+            _ => {
+                let assignment = node.parent()?.parent()?;
+                if assignment.kind() != "assignment_statement" {
+                    return None;
+                }
+                lua_assigned_function(assignment, src)?.1
+            }
+        };
+        return Some(name.to_string());
+    }
+
+    if lang == Lang::Elixir {
+        return elixir_function_name(node, src).map(str::to_string);
+    }
+
+    if lang == Lang::Dart {
+        return dart_member_name(dart_signature(node)?, src);
+    }
+
+    if lang == Lang::Sql {
+        return sql_object_name(node, src).map(|(_, name)| name.to_string());
+    }
+
     let name_node = node.child_by_field_name("name")?;
     let name = name_node.utf8_text(src).ok()?;
     if name.is_empty() {
@@ -1927,7 +4069,10 @@ fn function_name_from_node(node: Node, src: &[u8], lang: Lang) -> Option<String>
 fn find_enclosing_function(node: Node, src: &[u8], lang: Lang) -> Option<String> {
     let mut current = node.parent();
     while let Some(parent) = current {
-        if is_function_node(parent.kind(), lang) {
+        // Every Elixir form is a `call`; climb past those that are not `def`s.
+        if is_function_node(parent.kind(), lang)
+            && (lang != Lang::Elixir || elixir_function_name(parent, src).is_some())
+        {
             return function_name_from_node(parent, src, lang);
         }
         current = parent.parent();
@@ -1951,15 +4096,22 @@ fn match_call_ref(
     match lang {
         Lang::Rust => match_rust_call(node, kind, src, file, source_lines),
         Lang::Python => match_python_call(node, kind, src, file, source_lines),
-        Lang::JavaScript | Lang::TypeScript | Lang::Tsx => {
+        Lang::JavaScript | Lang::TypeScript | Lang::Tsx | Lang::Vue | Lang::Svelte => {
             match_js_call(node, kind, src, file, source_lines)
         }
         Lang::Go => match_go_call(node, kind, src, file, source_lines),
         Lang::Java => match_java_call(node, kind, src, file, source_lines),
+        Lang::Kotlin => match_kotlin_call(node, kind, src, file, source_lines),
+        Lang::Scala => match_scala_call(node, kind, src, file, source_lines),
         Lang::C | Lang::Cpp => match_c_call(node, kind, src, file, source_lines),
         Lang::Ruby => match_ruby_call(node, kind, src, file, source_lines),
         Lang::Php => match_php_call(node, kind, src, file, source_lines),
         Lang::CSharp => match_csharp_call(node, kind, src, file, source_lines),
+        Lang::Lua => match_lua_call(node, kind, src, file, source_lines),
+        Lang::Shell => match_shell_call(node, kind, src, file, source_lines),
+        Lang::Sql => match_sql_call(node, kind, src, file, source_lines),
+        Lang::Elixir => match_elixir_call(node, kind, src, file, source_lines),
+        Lang::Dart => match_dart_call(node, kind, src, file, source_lines),
     }
 }
 
@@ -2090,6 +4242,72 @@ fn match_java_call(
     ))
 }
 
+fn match_kotlin_call(
+    node: Node,
+    kind: &str,
+    src: &[u8],
+    file: &str,
+    source_lines: &[&str],
+) -> Option<Reference> {
+    if kind != "call_expression" {
+        return None;
+    }
+    // `helper(x)` or `a.b.create()`: the callee is the first child.
+    let callee = node.named_child(0u32)?;
+    let name_node = match callee.kind() {
+        "identifier" => callee,
+        "navigation_expression" => {
+            let last = callee.named_child(callee.named_child_count().saturating_sub(1) as u32)?;
+            (last.kind() == "identifier").then_some(last)?
+        }
+        _ => return None,
+    };
+    let name = node_text(name_node, src);
+    if name.is_empty() {
+        return None;
+    }
+    Some(make_ref(
+        name,
+        ReferenceKind::Call,
+        node,
+        file,
+        source_lines,
+    ))
+}
+
+fn match_scala_call(
+    node: Node,
+    kind: &str,
+    src: &[u8],
+    file: &str,
+    source_lines: &[&str],
+) -> Option<Reference> {
+    if kind != "call_expression" {
+        return None;
+    }
+    // `helper(x)`, `a.b.create()`, `parse[Int](s)`.
+    let mut callee = node.child_by_field_name("function")?;
+    if callee.kind() == "generic_function" {
+        callee = callee.child_by_field_name("function")?;
+    }
+    let name_node = match callee.kind() {
+        "identifier" => callee,
+        "field_expression" => callee.child_by_field_name("field")?,
+        _ => return None,
+    };
+    let name = node_text(name_node, src);
+    if name.is_empty() {
+        return None;
+    }
+    Some(make_ref(
+        name,
+        ReferenceKind::Call,
+        node,
+        file,
+        source_lines,
+    ))
+}
+
 fn match_c_call(
     node: Node,
     kind: &str,
@@ -2163,58 +4381,199 @@ fn match_ruby_call(
     }
 }
 
-fn match_php_call(
+fn match_php_call(
+    node: Node,
+    kind: &str,
+    src: &[u8],
+    file: &str,
+    source_lines: &[&str],
+) -> Option<Reference> {
+    match kind {
+        "function_call_expression" => {
+            let func = node.child_by_field_name("function")?;
+            let name = node_text(func, src);
+            if name.is_empty() {
+                return None;
+            }
+            Some(make_ref(
+                name,
+                ReferenceKind::Call,
+                node,
+                file,
+                source_lines,
+            ))
+        }
+        "member_call_expression" => {
+            let name_node = node.child_by_field_name("name")?;
+            let name = node_text(name_node, src);
+            if name.is_empty() {
+                return None;
+            }
+            Some(make_ref(
+                name,
+                ReferenceKind::Call,
+                node,
+                file,
+                source_lines,
+            ))
+        }
+        "scoped_call_expression" => {
+            let name_node = node.child_by_field_name("name")?;
+            let name = node_text(name_node, src);
+            if name.is_empty() {
+                return None;
+            }
+            Some(make_ref(
+                name,
+                ReferenceKind::Call,
+                node,
+                file,
+                source_lines,
+            ))
+        }
+        _ => None,
+    }
+}
+
+fn match_lua_call(
+    node: Node,
+    kind: &str,
+    src: &[u8],
+    file: &str,
+    source_lines: &[&str],
+) -> Option<Reference> {
+    if kind != "function_call" {
+        return None;
+    }
+    // `f()`, `M.f()`, and `obj:f()` are all calls of `f`.
+    let (_, name, _) = lua_function_name(node.child_by_field_name("name")?, src)?;
+    Some(make_ref(
+        name,
+        ReferenceKind::Call,
+        node,
+        file,
+        source_lines,
+    ))
+}
+
+/// A command naming a function or program: `build dist`, `make -C out`.
+/// `source` and `.` are includes, recorded as imports instead.
+fn match_shell_call(
+    node: Node,
+    kind: &str,
+    src: &[u8],
+    file: &str,
+    source_lines: &[&str],
+) -> Option<Reference> {
+    if kind != "command" {
+        return None;
+    }
+    let word = node.child_by_field_name("name")?.named_child(0)?;
+    if word.kind() != "word" {
+        return None;
+    }
+    let name = node_text(word, src);
+    if matches!(name, "source" | ".") {
+        return None;
+    }
+    Some(make_ref(
+        name,
+        ReferenceKind::Call,
+        node,
+        file,
+        source_lines,
+    ))
+}
+
+/// A function invocation: `count(*)`, `total_orders(id)`.
+fn match_sql_call(
+    node: Node,
+    kind: &str,
+    src: &[u8],
+    file: &str,
+    source_lines: &[&str],
+) -> Option<Reference> {
+    if kind != "invocation" {
+        return None;
+    }
+    let (_, name) = sql_object_name(node, src)?;
+    Some(make_ref(
+        name,
+        ReferenceKind::Call,
+        node,
+        file,
+        source_lines,
+    ))
+}
+
+fn match_elixir_call(
+    node: Node,
+    kind: &str,
+    src: &[u8],
+    file: &str,
+    source_lines: &[&str],
+) -> Option<Reference> {
+    if kind != "call" {
+        return None;
+    }
+    let target = node.child_by_field_name("target")?;
+    let name = match target.kind() {
+        "identifier" => node_text(target, src),
+        // `Mod.fun()`; `fun.()` invokes an anonymous function.
+        "dot" => field_text(target, "right", src)?,
+        _ => return None,
+    };
+    if target.kind() == "identifier" && ELIXIR_SPECIAL_FORMS.contains(&name) {
+        return None;
+    }
+    // Neither the name of an `@attribute` nor the head of a definition
+    // (`greet(name)` in `def greet(name) when ...`) is a call, and
+    // typespecs (`@spec get(id) :: t()`) name types.
+    let parent = node.parent()?;
+    let enclosing = std::iter::successors(Some(parent), |p| p.parent())
+        .take(3)
+        .find(|p| p.kind() == "call");
+    if elixir_attribute(parent, src).is_some()
+        || enclosing.and_then(|def| elixir_function_head(def, src)) == Some(node)
+        || std::iter::successors(Some(parent), |p| p.parent()).any(|p| {
+            elixir_attribute(p, src).is_some_and(|(name, _)| ELIXIR_TYPESPECS.contains(&name))
+        })
+    {
+        return None;
+    }
+    Some(make_ref(
+        name,
+        ReferenceKind::Call,
+        node,
+        file,
+        source_lines,
+    ))
+}
+
+fn match_dart_call(
     node: Node,
     kind: &str,
     src: &[u8],
     file: &str,
     source_lines: &[&str],
 ) -> Option<Reference> {
-    match kind {
-        "function_call_expression" => {
-            let func = node.child_by_field_name("function")?;
-            let name = node_text(func, src);
-            if name.is_empty() {
-                return None;
-            }
-            Some(make_ref(
-                name,
-                ReferenceKind::Call,
-                node,
-                file,
-                source_lines,
-            ))
-        }
-        "member_call_expression" => {
-            let name_node = node.child_by_field_name("name")?;
-            let name = node_text(name_node, src);
-            if name.is_empty() {
-                return None;
-            }
-            Some(make_ref(
-                name,
-                ReferenceKind::Call,
-                node,
-                file,
-                source_lines,
-            ))
-        }
-        "scoped_call_expression" => {
-            let name_node = node.child_by_field_name("name")?;
-            let name = node_text(name_node, src);
-            if name.is_empty() {
-                return None;
-            }
-            Some(make_ref(
-                name,
-                ReferenceKind::Call,
-                node,
-                file,
-                source_lines,
-            ))
-        }
-        _ => None,
+    if kind != "call_expression" {
+        return None;
     }
+    // `helper()`, `Square(4)`, and `Shape.of(n)`.
+    let function = node.child_by_field_name("function")?;
+    let name_node = match function.kind() {
+        "identifier" => function,
+        "member_expression" => function.child_by_field_name("property")?,
+        _ => return None,
+    };
+    Some(make_ref(
+        node_text(name_node, src),
+        ReferenceKind::Call,
+        node,
+        file,
+        source_lines,
+    ))
 }
 
 fn match_csharp_call(
@@ -2365,7 +4724,7 @@ fn match_type_ref(
             }
             _ => None,
         },
-        Lang::TypeScript | Lang::Tsx => match kind {
+        Lang::TypeScript | Lang::Tsx | Lang::Vue | Lang::Svelte => match kind {
             "type_identifier" => {
                 let parent_kind = node.parent().map(|p| p.kind()).unwrap_or("");
                 // Skip definition sites
@@ -2436,6 +4795,43 @@ fn match_type_ref(
             }
             _ => None,
         },
+        Lang::Kotlin => match kind {
+            // `a.b.Entry<T>`: the last identifier names the type.
+            "user_type" => {
+                let mut cursor = node.walk();
+                let name_node = node
+                    .named_children(&mut cursor)
+                    .filter(|c| c.kind() == "identifier")
+                    .last()?;
+                Some(make_ref(
+                    node_text(name_node, src),
+                    ReferenceKind::Type,
+                    name_node,
+                    file,
+                    source_lines,
+                ))
+            }
+            _ => None,
+        },
+        Lang::Scala => match kind {
+            "type_identifier" => {
+                // The name of `type Id = Long` is not a use.
+                if node
+                    .parent()
+                    .is_some_and(|p| p.child_by_field_name("name") == Some(node))
+                {
+                    return None;
+                }
+                Some(make_ref(
+                    node_text(node, src),
+                    ReferenceKind::Type,
+                    node,
+                    file,
+                    source_lines,
+                ))
+            }
+            _ => None,
+        },
         Lang::C | Lang::Cpp => match kind {
             "type_identifier" => {
                 let parent_kind = node.parent().map(|p| p.kind()).unwrap_or("");
@@ -2459,7 +4855,39 @@ fn match_type_ref(
             }
             _ => None,
         },
-        Lang::Ruby => None, // Ruby is dynamically typed, no type annotations
+        Lang::Ruby | Lang::Lua | Lang::Elixir | Lang::Shell => None, // dynamically typed, no type annotations
+        // Tables named by queries, constraints, and DDL.
+        Lang::Sql => {
+            if kind != "object_reference" || !sql_is_table_use(node) {
+                return None;
+            }
+            let name = sql_identifier(node.child_by_field_name("name")?, src);
+            Some(make_ref(
+                name,
+                ReferenceKind::Type,
+                node,
+                file,
+                source_lines,
+            ))
+        }
+        Lang::Dart => match kind {
+            "type_identifier" => {
+                // The name of a typedef or type parameter is not a use.
+                let parent = node.parent()?;
+                if parent.kind() == "type_alias" || parent.child_by_field_name("name") == Some(node)
+                {
+                    return None;
+                }
+                Some(make_ref(
+                    node_text(node, src),
+                    ReferenceKind::Type,
+                    node,
+                    file,
+                    source_lines,
+                ))
+            }
+            _ => None,
+        },
         Lang::Php => match kind {
             "named_type" => {
                 let name = node_text(node, src);
@@ -2535,6 +4963,47 @@ fn is_go_builtin_type(name: &str) -> bool {
     )
 }
 
+// ---------------------------------------------------------------------------
+// JSX component reference matching
+// ---------------------------------------------------------------------------
+
+/// Try to extract a component reference from a JSX element.
+///
+/// Only opening and self-closing tags count, so `<Foo>...</Foo>` is one
+/// usage.  Lowercase tags are intrinsic HTML elements and are skipped; for
+/// member tags (`<UI.Button>`) the last segment is the component.
+fn match_component_ref(
+    node: Node,
+    kind: &str,
+    src: &[u8],
+    file: &str,
+    lang: Lang,
+    source_lines: &[&str],
+) -> Option<Reference> {
+    if !matches!(lang, Lang::JavaScript | Lang::Tsx)
+        || !matches!(kind, "jsx_opening_element" | "jsx_self_closing_element")
+    {
+        return None;
+    }
+    let name_node = node.child_by_field_name("name")?;
+    let name_node = match name_node.kind() {
+        "member_expression" => name_node.child_by_field_name("property")?,
+        "identifier" => name_node,
+        _ => return None,
+    };
+    let name = node_text(name_node, src);
+    if !name.starts_with(|c: char| c.is_ascii_uppercase()) {
+        return None;
+    }
+    Some(make_ref(
+        name,
+        ReferenceKind::Component,
+        name_node,
+        file,
+        source_lines,
+    ))
+}
+
 // ---------------------------------------------------------------------------
 // Import reference matching
 // ---------------------------------------------------------------------------
@@ -2633,7 +5102,7 @@ fn match_import_ref(
             }
             _ => vec![],
         },
-        Lang::JavaScript | Lang::TypeScript | Lang::Tsx => {
+        Lang::JavaScript | Lang::TypeScript | Lang::Tsx | Lang::Vue | Lang::Svelte => {
             if kind != "import_statement" {
                 return vec![];
             }
@@ -2676,6 +5145,25 @@ fn match_import_ref(
                 source_lines,
             )]
         }
+        Lang::Kotlin | Lang::Scala => {
+            // The Kotlin `import` keyword token shares the statement's kind.
+            let statement = if lang == Lang::Kotlin {
+                "import"
+            } else {
+                "import_declaration"
+            };
+            if kind != statement || !node.is_named() {
+                return vec![];
+            }
+            let text = node_text(node, src).trim().to_string();
+            vec![make_ref(
+                &text,
+                ReferenceKind::Import,
+                node,
+                file,
+                source_lines,
+            )]
+        }
         Lang::C | Lang::Cpp => {
             if kind != "preproc_include" {
                 return vec![];
@@ -2762,7 +5250,86 @@ fn match_import_ref(
                 source_lines,
             )]
         }
+        Lang::Lua => {
+            if kind != "function_call" {
+                return vec![];
+            }
+            let Some(module) = lua_required_module(node, src) else {
+                return vec![];
+            };
+            vec![make_ref(
+                module,
+                ReferenceKind::Import,
+                node,
+                file,
+                source_lines,
+            )]
+        }
+        Lang::Elixir => {
+            if kind != "call" {
+                return vec![];
+            }
+            elixir_directive_modules(node, src)
+                .iter()
+                .map(|module| make_ref(module, ReferenceKind::Import, node, file, source_lines))
+                .collect()
+        }
+        Lang::Sql => vec![],
+        Lang::Shell => match shell_sourced_path(node, src) {
+            Some(path) => vec![make_ref(
+                path,
+                ReferenceKind::Import,
+                node,
+                file,
+                source_lines,
+            )],
+            None => vec![],
+        },
+        Lang::Dart => match dart_directive_uri(node, src) {
+            Some(uri) => vec![make_ref(
+                uri,
+                ReferenceKind::Import,
+                node,
+                file,
+                source_lines,
+            )],
+            None => vec![],
+        },
+    }
+}
+
+/// Find `unsafe { .. }` blocks (Rust) and `unsafe` statements (C#).
+///
+/// Returns the 1-based line of each block together with the name of the
+/// function enclosing it (`None` at file scope).  Other languages have no
+/// unsafe blocks and yield nothing.
+pub fn extract_unsafe_blocks(
+    tree: &Tree,
+    source: &str,
+    lang: Lang,
+) -> Vec<(usize, Option<String>)> {
+    let block_kind = match lang {
+        Lang::Rust => "unsafe_block",
+        Lang::CSharp => "unsafe_statement",
+        _ => return Vec::new(),
+    };
+    let src = source.as_bytes();
+    let mut blocks = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if node.kind() == block_kind {
+            blocks.push((
+                node.start_position().row + 1,
+                find_enclosing_function(node, src, lang),
+            ));
+        }
+        for i in (0..node.child_count()).rev() {
+            if let Some(child) = node.child(i as u32) {
+                stack.push(child);
+            }
+        }
     }
+    blocks
 }
 
 // ===========================================================================
@@ -2785,6 +5352,100 @@ pub fn extract_imports(tree: &Tree, source: &str, file: &str, lang: Lang) -> Fil
     }
 }
 
+/// Collect the constants a Ruby file uses (`User`, `Admin::Report`), in
+/// order of first use.
+///
+/// Rails autoloads constants instead of requiring files, so in Rails mode
+/// these stand in for imports.  Names being defined (`class Foo`,
+/// `FOO = 1`) are skipped; superclasses and mixins count as uses.
+pub fn extract_ruby_constants(tree: &Tree, source: &str) -> Vec<String> {
+    let src = source.as_bytes();
+    let mut seen = HashSet::new();
+    let mut constants = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if matches!(node.kind(), "constant" | "scope_resolution") {
+            let parent = node.parent();
+            let is_definition = parent.is_some_and(|p| {
+                (matches!(p.kind(), "class" | "module")
+                    && p.child_by_field_name("name") == Some(node))
+                    || (p.kind() == "assignment" && p.child_by_field_name("left") == Some(node))
+            });
+            if !is_definition {
+                let name = node_text(node, src).trim_start_matches("::");
+                if name.starts_with(|c: char| c.is_ascii_uppercase())
+                    && seen.insert(name.to_string())
+                {
+                    constants.push(name.to_string());
+                }
+            }
+            // The parts of `A::B` are not separate uses.
+            continue;
+        }
+        for i in (0..node.child_count()).rev() {
+            if let Some(child) = node.child(i as u32) {
+                stack.push(child);
+            }
+        }
+    }
+    constants
+}
+
+/// Flatten a Rust use tree into one path per imported item, so
+/// `crate::{a::B, c::*}` yields `crate::a::B` and `crate::c::*`.
+fn expand_use_tree(node: Node, prefix: &str, src: &[u8], imports: &mut Vec<String>) {
+    let join = |path: &str| {
+        if prefix.is_empty() {
+            path.to_string()
+        } else if path == "self" {
+            prefix.to_string()
+        } else {
+            format!("{prefix}::{path}")
+        }
+    };
+    match node.kind() {
+        "use_list" => {
+            let mut cursor = node.walk();
+            for child in node.named_children(&mut cursor) {
+                expand_use_tree(child, prefix, src, imports);
+            }
+        }
+        "scoped_use_list" => {
+            let path = field_text(node, "path", src).map(join);
+            let prefix = path.as_deref().unwrap_or(prefix);
+            if let Some(list) = node.child_by_field_name("list") {
+                expand_use_tree(list, prefix, src, imports);
+            }
+        }
+        "use_as_clause" => {
+            if let Some(path) = node.child_by_field_name("path") {
+                expand_use_tree(path, prefix, src, imports);
+            }
+        }
+        "line_comment" | "block_comment" => {}
+        _ => imports.push(join(node_text(node, src))),
+    }
+}
+
+/// The value of a `#[path = "..."]` attribute directly above an item.
+fn rust_path_attribute(node: Node, src: &[u8]) -> Option<String> {
+    let mut prev = node.prev_named_sibling();
+    while let Some(sibling) = prev {
+        if sibling.kind() != "attribute_item" {
+            break;
+        }
+        let text = node_text(sibling, src);
+        if let Some(rest) = text.strip_prefix("#[").and_then(|t| t.strip_suffix(']'))
+            && let Some((key, value)) = rest.split_once('=')
+            && key.trim() == "path"
+        {
+            return Some(value.trim().trim_matches('"').to_string());
+        }
+        prev = sibling.prev_named_sibling();
+    }
+    None
+}
+
 /// Recursively walk the tree collecting import paths and export names.
 ///
 /// Implemented with an explicit stack so deep trees cannot overflow the stack.
@@ -2804,7 +5465,18 @@ fn walk_imports(
                 if kind == "use_declaration"
                     && let Some(arg) = node.child_by_field_name("argument")
                 {
-                    imports.push(node_text(arg, src).to_string());
+                    expand_use_tree(arg, "", src, imports);
+                }
+                // Out-of-line `mod foo;` declarations pull in a file too.
+                if kind == "mod_item"
+                    && node.child_by_field_name("body").is_none()
+                    && node.parent().is_some_and(|p| p.kind() == "source_file")
+                    && let Some(name) = field_text(node, "name", src)
+                {
+                    imports.push(match rust_path_attribute(node, src) {
+                        Some(path) => format!("#[path = \"{path}\"] mod {name}"),
+                        None => format!("mod {name}"),
+                    });
                 }
                 // Rust pub items are exports (simplified: just look for `pub` visibility)
                 if kind == "visibility_modifier"
@@ -2843,15 +5515,39 @@ fn walk_imports(
                             }
                         }
                     }
-                    "import_from_statement" => {
-                        if let Some(module) = node.child_by_field_name("module_name") {
-                            imports.push(node_text(module, src).to_string());
-                        }
-                    }
+                    "import_from_statement" => {
+                        // One entry per imported name (`from a.b import c` →
+                        // `a.b.c`), since each may be a submodule of its own.
+                        if let Some(module) = node.child_by_field_name("module_name") {
+                            let module = node_text(module, src);
+                            let mut names = Vec::new();
+                            let mut cursor = node.walk();
+                            for child in node.children_by_field_name("name", &mut cursor) {
+                                let name = match child.kind() {
+                                    "aliased_import" => child.child_by_field_name("name"),
+                                    _ => Some(child),
+                                };
+                                if let Some(n) = name {
+                                    names.push(node_text(n, src));
+                                }
+                            }
+                            if names.is_empty() {
+                                // `from a.b import *`
+                                imports.push(module.to_string());
+                            }
+                            for name in names {
+                                if module.ends_with('.') {
+                                    imports.push(format!("{module}{name}"));
+                                } else {
+                                    imports.push(format!("{module}.{name}"));
+                                }
+                            }
+                        }
+                    }
                     _ => {}
                 }
             }
-            Lang::JavaScript | Lang::TypeScript | Lang::Tsx => {
+            Lang::JavaScript | Lang::TypeScript | Lang::Tsx | Lang::Vue | Lang::Svelte => {
                 if kind == "import_statement"
                     && let Some(source_node) = node.child_by_field_name("source")
                 {
@@ -2913,14 +5609,93 @@ fn walk_imports(
             }
             Lang::Java => {
                 if kind == "import_declaration" {
-                    // Extract the imported path (skip "import " and ";")
+                    // Extract the imported path (skip "import " and ";"),
+                    // keeping the `.*` of wildcard imports.
+                    let wildcard = (0..node.named_child_count())
+                        .filter_map(|i| node.named_child(i as u32))
+                        .any(|c| c.kind() == "asterisk");
                     for i in 0..node.named_child_count() {
                         if let Some(child) = node.named_child(i as u32)
                             && child.kind() == "scoped_identifier"
                         {
-                            imports.push(node_text(child, src).to_string());
+                            let path = node_text(child, src);
+                            imports.push(if wildcard {
+                                format!("{path}.*")
+                            } else {
+                                path.to_string()
+                            });
+                        }
+                    }
+                }
+            }
+            Lang::Kotlin => {
+                // `import a.b.C`, `import a.b.*`, `import a.b.C as D`; the
+                // alias is dropped.
+                if kind == "import"
+                    && let Some(path) = node.named_child(0u32)
+                    && path.kind() == "qualified_identifier"
+                {
+                    let path = node_text(path, src);
+                    let mut cursor = node.walk();
+                    let wildcard = node.children(&mut cursor).any(|c| c.kind() == "*");
+                    imports.push(if wildcard {
+                        format!("{path}.*")
+                    } else {
+                        path.to_string()
+                    });
+                }
+            }
+            Lang::Scala => {
+                // `import a.b.C`, `import a.b._`, `import a.b.{C, D => E}`,
+                // `import a.b.C as D`; renamed selectors keep their original
+                // name and hidden ones (`C => _`) are dropped.
+                if kind == "import_declaration" {
+                    let mut cursor = node.walk();
+                    let path = node
+                        .children_by_field_name("path", &mut cursor)
+                        .filter(|c| c.kind() == "identifier")
+                        .map(|c| node_text(c, src))
+                        .collect::<Vec<_>>()
+                        .join(".");
+                    let mut selected = false;
+                    let mut cursor = node.walk();
+                    for child in node.named_children(&mut cursor) {
+                        let selectors = match child.kind() {
+                            "namespace_wildcard" | "as_renamed_identifier" => vec![child],
+                            "namespace_selectors" => {
+                                let mut c = child.walk();
+                                child.named_children(&mut c).collect()
+                            }
+                            _ => continue,
+                        };
+                        selected = true;
+                        for selector in selectors {
+                            match selector.kind() {
+                                "identifier" => {
+                                    imports.push(format!("{path}.{}", node_text(selector, src)));
+                                }
+                                "arrow_renamed_identifier" | "as_renamed_identifier"
+                                    if selector
+                                        .child_by_field_name("alias")
+                                        .is_some_and(|a| a.kind() != "wildcard") =>
+                                {
+                                    if let Some(name) = field_text(selector, "name", src) {
+                                        imports.push(format!("{path}.{name}"));
+                                    }
+                                }
+                                // `_` and `*`, not `given`.
+                                "namespace_wildcard"
+                                    if matches!(node_text(selector, src), "_" | "*") =>
+                                {
+                                    imports.push(format!("{path}.*"));
+                                }
+                                _ => {}
+                            }
                         }
                     }
+                    if !selected && !path.is_empty() {
+                        imports.push(path);
+                    }
                 }
             }
             Lang::C | Lang::Cpp => {
@@ -2972,21 +5747,42 @@ fn walk_imports(
                 }
                 // PHP namespace use statements
                 if kind == "namespace_use_declaration" {
-                    for i in 0..node.named_child_count() {
-                        if let Some(child) = node.named_child(i as u32)
-                            && child.kind() == "namespace_use_clause"
+                    // `use A\B as C, D;` or grouped `use A\{B, C as D};`, one
+                    // fully qualified name per clause, aliases dropped.
+                    let (prefix, clauses) = match node.child_by_field_name("body") {
+                        Some(group) => (
+                            (0..node.named_child_count())
+                                .filter_map(|i| node.named_child(i as u32))
+                                .find(|c| c.kind() == "namespace_name")
+                                .map(|c| node_text(c, src)),
+                            group,
+                        ),
+                        None => (None, node),
+                    };
+                    for i in 0..clauses.named_child_count() {
+                        if let Some(clause) = clauses.named_child(i as u32)
+                            && clause.kind() == "namespace_use_clause"
+                            && let Some(name) = clause.named_child(0)
                         {
-                            imports.push(node_text(child, src).to_string());
+                            let name = node_text(name, src).trim_start_matches('\\');
+                            imports.push(match prefix {
+                                Some(prefix) => format!("{prefix}\\{name}"),
+                                None => name.to_string(),
+                            });
                         }
                     }
                 }
             }
             Lang::CSharp => {
                 if kind == "using_directive" {
-                    // `using System.Collections.Generic;` → extract the namespace
+                    // `using System.Collections.Generic;` → extract the namespace.
+                    // In `using Json = System.Text.Json;` the alias is the
+                    // `name` field; only the target is an import.
+                    let alias = node.child_by_field_name("name").map(|n| n.id());
                     for i in 0..node.named_child_count() {
                         if let Some(child) = node.named_child(i as u32)
                             && matches!(child.kind(), "qualified_name" | "identifier")
+                            && Some(child.id()) != alias
                         {
                             imports.push(node_text(child, src).to_string());
                         }
@@ -3010,6 +5806,30 @@ fn walk_imports(
                     }
                 }
             }
+            Lang::Lua => {
+                if kind == "function_call"
+                    && let Some(module) = lua_required_module(node, src)
+                {
+                    imports.push(module.to_string());
+                }
+            }
+            Lang::Elixir => {
+                if kind == "call" {
+                    imports.extend(elixir_directive_modules(node, src));
+                }
+            }
+            Lang::Sql => {}
+            Lang::Shell => {
+                if let Some(path) = shell_sourced_path(node, src) {
+                    imports.push(path.to_string());
+                }
+            }
+            // `import`, `export`, and `part` all pull in another file.
+            Lang::Dart => {
+                if let Some(uri) = dart_directive_uri(node, src) {
+                    imports.push(uri.to_string());
+                }
+            }
         }
 
         // Recurse into children
@@ -3030,10 +5850,14 @@ fn walk_imports(
 /// Returns a list of [`RawTypeEdge`] with unresolved names. The pipeline
 /// resolves these to symbol IDs before inserting into the `type_edges` table.
 ///
-/// C and Go are skipped (no class-based inheritance).
+/// C, Go, Lua, Elixir, shell, and SQL are skipped (no class-based
+/// inheritance).
 pub fn extract_type_edges(tree: &Tree, source: &str, _file: &str, lang: Lang) -> Vec<RawTypeEdge> {
-    // C and Go have no class-based inheritance.
-    if matches!(lang, Lang::C | Lang::Go) {
+    // C, Go, Lua, Elixir, shell, and SQL have no class-based inheritance.
+    if matches!(
+        lang,
+        Lang::C | Lang::Go | Lang::Lua | Lang::Elixir | Lang::Shell | Lang::Sql
+    ) {
         return Vec::new();
     }
 
@@ -3052,7 +5876,7 @@ fn walk_type_edges(root: Node, src: &[u8], lang: Lang, edges: &mut Vec<RawTypeEd
         let kind = node.kind();
 
         match lang {
-            Lang::TypeScript | Lang::Tsx => {
+            Lang::TypeScript | Lang::Tsx | Lang::Vue | Lang::Svelte => {
                 if kind == "class_declaration"
                     && let Some(class_name) = field_text(node, "name", src)
                 {
@@ -3139,10 +5963,43 @@ fn walk_type_edges(root: Node, src: &[u8], lang: Lang, edges: &mut Vec<RawTypeEd
                     }
                 }
             }
+            Lang::Kotlin => {
+                if matches!(kind, "class_declaration" | "object_declaration")
+                    && let Some(class_name) = field_text(node, "name", src)
+                {
+                    let is_interface = kotlin_is_interface(node);
+                    let mut cursor = node.walk();
+                    for specifiers in node
+                        .children(&mut cursor)
+                        .filter(|c| c.kind() == "delegation_specifiers")
+                    {
+                        extract_kotlin_supertypes(specifiers, src, class_name, is_interface, edges);
+                    }
+                }
+            }
+            Lang::Scala => {
+                if matches!(
+                    kind,
+                    "class_definition" | "object_definition" | "trait_definition"
+                ) && let Some(class_name) = field_text(node, "name", src)
+                    && let Some(clause) = node.child_by_field_name("extend")
+                {
+                    extract_scala_supertypes(
+                        clause,
+                        src,
+                        class_name,
+                        kind == "trait_definition",
+                        edges,
+                    );
+                }
+            }
             Lang::CSharp => {
                 if matches!(
                     kind,
-                    "class_declaration" | "struct_declaration" | "interface_declaration"
+                    "class_declaration"
+                        | "struct_declaration"
+                        | "record_declaration"
+                        | "interface_declaration"
                 ) && let Some(class_name) = field_text(node, "name", src)
                 {
                     let is_interface = kind == "interface_declaration";
@@ -3220,7 +6077,47 @@ fn walk_type_edges(root: Node, src: &[u8], lang: Lang, edges: &mut Vec<RawTypeEd
                     }
                 }
             }
-            Lang::C | Lang::Go => {} // handled by early return above
+            Lang::Dart => {
+                if kind == "class_declaration"
+                    && let Some(class_name) = field_text(node, "name", src)
+                {
+                    // `extends` a superclass, `with` mixins, and `implements`
+                    // interfaces.
+                    let mut parents = Vec::new();
+                    if let Some(superclass) = node.child_by_field_name("superclass") {
+                        if let Some(base) = superclass.child_by_field_name("type") {
+                            parents.push((base, "extends"));
+                        }
+                        let mut cursor = superclass.walk();
+                        for mixins in superclass
+                            .named_children(&mut cursor)
+                            .filter(|c| c.kind() == "mixins")
+                        {
+                            let mut c = mixins.walk();
+                            parents
+                                .extend(mixins.named_children(&mut c).map(|t| (t, "implements")));
+                        }
+                    }
+                    if let Some(interfaces) = node.child_by_field_name("interfaces") {
+                        let mut cursor = interfaces.walk();
+                        parents.extend(
+                            interfaces
+                                .named_children(&mut cursor)
+                                .map(|t| (t, "implements")),
+                        );
+                    }
+                    for (type_node, rel) in parents {
+                        if let Some(parent) = dart_type_name(type_node, src) {
+                            edges.push(RawTypeEdge {
+                                child_name: class_name.to_string(),
+                                parent_name: parent.to_string(),
+                                relationship: rel.to_string(),
+                            });
+                        }
+                    }
+                }
+            }
+            Lang::C | Lang::Go | Lang::Lua | Lang::Elixir | Lang::Shell | Lang::Sql => {} // handled by early return above
         }
 
         // Recurse into children.
@@ -3328,7 +6225,16 @@ fn extract_csharp_base_list(
 ) {
     let mut found_first = false;
     for i in 0..base_list.child_count() {
-        if let Some(child) = base_list.child(i as u32)
+        // A record's base class may take constructor arguments:
+        // `record Person(string Name) : Base(Name)`.
+        let child = base_list.child(i as u32).map(|c| {
+            if c.kind() == "primary_constructor_base_type" {
+                c.child_by_field_name("type").unwrap_or(c)
+            } else {
+                c
+            }
+        });
+        if let Some(child) = child
             && matches!(
                 child.kind(),
                 "identifier" | "generic_name" | "qualified_name"
@@ -3357,6 +6263,114 @@ fn extract_csharp_base_list(
     }
 }
 
+/// Whether a Kotlin `class_declaration` declares an interface.
+fn kotlin_is_interface(node: Node) -> bool {
+    let mut cursor = node.walk();
+    node.children(&mut cursor).any(|c| c.kind() == "interface")
+}
+
+/// The simple name of a Kotlin type: the last identifier of a `user_type`
+/// (`a.b.Entry<T>` → `Entry`), looking through nullable and parenthesized
+/// wrappers.
+fn kotlin_type_name<'a>(node: Node, src: &'a [u8]) -> Option<&'a str> {
+    match node.kind() {
+        "user_type" => {
+            let mut cursor = node.walk();
+            node.named_children(&mut cursor)
+                .filter(|c| c.kind() == "identifier")
+                .last()
+                .map(|n| node_text(n, src))
+        }
+        "nullable_type" | "parenthesized_type" => kotlin_type_name(node.named_child(0u32)?, src),
+        _ => None,
+    }
+}
+
+/// Extract supertypes from a Kotlin `delegation_specifiers` list.
+///
+/// A supertype called with constructor arguments (`Base()`) is the
+/// superclass; the others are implemented interfaces.  Everything an
+/// interface lists is extended.
+fn extract_kotlin_supertypes(
+    specifiers: Node,
+    src: &[u8],
+    class_name: &str,
+    is_interface: bool,
+    edges: &mut Vec<RawTypeEdge>,
+) {
+    let mut cursor = specifiers.walk();
+    for specifier in specifiers.named_children(&mut cursor) {
+        let Some(inner) = specifier.named_child(0u32) else {
+            continue;
+        };
+        let (type_node, is_call) = match inner.kind() {
+            "constructor_invocation" => (inner.named_child(0u32), true),
+            // `Foo by delegate`
+            "explicit_delegation" => (inner.named_child(0u32), false),
+            _ => (Some(inner), false),
+        };
+        let Some(parent) = type_node.and_then(|t| kotlin_type_name(t, src)) else {
+            continue;
+        };
+        let rel = if is_interface || is_call {
+            "extends"
+        } else {
+            "implements"
+        };
+        edges.push(RawTypeEdge {
+            child_name: class_name.to_string(),
+            parent_name: parent.to_string(),
+            relationship: rel.to_string(),
+        });
+    }
+}
+
+/// The simple name of a Scala type: `Base`, the `Shape` of `Shape[K]`, or
+/// the last segment of `a.b.Entry`.
+fn scala_type_name<'a>(node: Node, src: &'a [u8]) -> Option<&'a str> {
+    match node.kind() {
+        "type_identifier" => Some(node_text(node, src)),
+        "generic_type" => scala_type_name(node.child_by_field_name("type")?, src),
+        "stable_type_identifier" => {
+            let mut cursor = node.walk();
+            node.named_children(&mut cursor)
+                .filter(|c| c.kind() == "type_identifier")
+                .last()
+                .map(|n| node_text(n, src))
+        }
+        _ => None,
+    }
+}
+
+/// Extract supertypes from a Scala `extends_clause`.
+///
+/// The type after `extends` is extended and the `with` mixins are
+/// implemented; everything a trait lists is extended.
+fn extract_scala_supertypes(
+    clause: Node,
+    src: &[u8],
+    class_name: &str,
+    is_trait: bool,
+    edges: &mut Vec<RawTypeEdge>,
+) {
+    let mut cursor = clause.walk();
+    let parents = clause
+        .children_by_field_name("type", &mut cursor)
+        .filter_map(|t| scala_type_name(t, src));
+    for (i, parent) in parents.enumerate() {
+        let rel = if is_trait || i == 0 {
+            "extends"
+        } else {
+            "implements"
+        };
+        edges.push(RawTypeEdge {
+            child_name: class_name.to_string(),
+            parent_name: parent.to_string(),
+            relationship: rel.to_string(),
+        });
+    }
+}
+
 /// Extract base class identifiers from a C++ base_class_clause.
 fn extract_cpp_bases(
     base_clause: Node,
@@ -3454,6 +6468,95 @@ mod tests {
         assert_eq!(detect_language(Path::new("a.java")), Some(Lang::Java));
     }
 
+    #[test]
+    fn detect_kotlin() {
+        assert_eq!(detect_language(Path::new("a.kt")), Some(Lang::Kotlin));
+        assert_eq!(
+            detect_language(Path::new("build.gradle.kts")),
+            Some(Lang::Kotlin)
+        );
+    }
+
+    #[test]
+    fn detect_lua() {
+        assert_eq!(detect_language(Path::new("init.lua")), Some(Lang::Lua));
+        assert_eq!(lang_from_name("lua"), Some(Lang::Lua));
+    }
+
+    #[test]
+    fn detect_elixir() {
+        assert_eq!(detect_language(Path::new("lib/app.ex")), Some(Lang::Elixir));
+        assert_eq!(detect_language(Path::new("mix.exs")), Some(Lang::Elixir));
+        assert_eq!(lang_from_name("elixir"), Some(Lang::Elixir));
+    }
+
+    #[test]
+    fn detect_dart() {
+        assert_eq!(
+            detect_language(Path::new("lib/main.dart")),
+            Some(Lang::Dart)
+        );
+        assert_eq!(lang_from_name("dart"), Some(Lang::Dart));
+    }
+
+    #[test]
+    fn detect_shell() {
+        for path in ["deploy.sh", "lib.bash", ".zshrc.zsh"] {
+            assert_eq!(detect_language(Path::new(path)), Some(Lang::Shell));
+        }
+        assert_eq!(lang_from_name("shell"), Some(Lang::Shell));
+        assert_eq!(lang_from_name("bash"), Some(Lang::Shell));
+    }
+
+    #[test]
+    fn detect_shell_by_shebang() {
+        let bin = Path::new("bin/release");
+        assert_eq!(
+            detect_language_in(bin, "#!/bin/bash\nset -e\n"),
+            Some(Lang::Shell)
+        );
+        assert_eq!(
+            detect_language_in(bin, "#!/usr/bin/env -S zsh -f\n"),
+            Some(Lang::Shell)
+        );
+        assert_eq!(detect_language_in(bin, "#!/usr/bin/env perl\n"), None);
+        assert_eq!(detect_language_in(bin, "echo hi\n"), None);
+        // Only files without an extension are sniffed.
+        assert_eq!(
+            detect_language_in(Path::new("notes.txt"), "#!/bin/sh\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn detect_sql() {
+        assert_eq!(detect_language(Path::new("db/schema.sql")), Some(Lang::Sql));
+        assert_eq!(lang_from_name("SQL"), Some(Lang::Sql));
+    }
+
+    #[test]
+    fn detect_vue() {
+        assert_eq!(
+            detect_language(Path::new("src/components/UserCard.vue")),
+            Some(Lang::Vue)
+        );
+        assert_eq!(lang_from_name("vue"), Some(Lang::Vue));
+    }
+
+    #[test]
+    fn detect_svelte() {
+        assert_eq!(
+            detect_language(Path::new("src/lib/Counter.svelte")),
+            Some(Lang::Svelte)
+        );
+    }
+
+    #[test]
+    fn detect_scala() {
+        assert_eq!(detect_language(Path::new("a.scala")), Some(Lang::Scala));
+        assert_eq!(detect_language(Path::new("build.sc")), Some(Lang::Scala));
+    }
+
     #[test]
     fn detect_c() {
         assert_eq!(detect_language(Path::new("a.c")), Some(Lang::C));
@@ -3494,6 +6597,19 @@ mod tests {
         assert_eq!(detect_language(Path::new("Dockerfile")), None);
     }
 
+    #[test]
+    fn lang_from_name_accepts_names_and_extensions() {
+        assert_eq!(lang_from_name("rust"), Some(Lang::Rust));
+        assert_eq!(lang_from_name("RS"), Some(Lang::Rust));
+        assert_eq!(lang_from_name("TypeScript"), Some(Lang::TypeScript));
+        assert_eq!(lang_from_name("tsx"), Some(Lang::Tsx));
+        assert_eq!(lang_from_name("C++"), Some(Lang::Cpp));
+        assert_eq!(lang_from_name("cpp"), Some(Lang::Cpp));
+        assert_eq!(lang_from_name("c#"), Some(Lang::CSharp));
+        assert_eq!(lang_from_name("go"), Some(Lang::Go));
+        assert_eq!(lang_from_name("cobol"), None);
+    }
+
     // ---------- truncate_doc (UTF-8 / char limit) ----------
 
     #[test]
@@ -3557,8 +6673,14 @@ mod tests {
             Lang::CSharp,
         ];
         for lang in langs {
-            let _parser = get_parser(lang); // should not panic
+            assert!(
+                get_parser(lang).is_some(),
+                "{} grammar failed to load",
+                lang.name()
+            );
+            assert!(!is_language_disabled(lang));
         }
+        assert!(disabled_languages().is_empty());
     }
 
     // ---------- parse_file tests ----------
@@ -3626,6 +6748,69 @@ mod tests {
         assert!(!tree.root_node().has_error());
     }
 
+    #[test]
+    fn parse_kotlin_file() {
+        let src = "fun main() { println(\"hi\") }";
+        let (tree, lang) = parse_temp("kt", src).unwrap();
+        assert_eq!(lang, Lang::Kotlin);
+        assert!(!tree.root_node().has_error());
+    }
+
+    #[test]
+    fn parse_scala_file() {
+        let src = "object Main { def main(args: Array[String]): Unit = println(\"hi\") }";
+        let (tree, lang) = parse_temp("scala", src).unwrap();
+        assert_eq!(lang, Lang::Scala);
+        assert!(!tree.root_node().has_error());
+    }
+
+    #[test]
+    fn parse_lua_file() {
+        let src = "local function main() print(\"hi\") end";
+        let (tree, lang) = parse_temp("lua", src).unwrap();
+        assert_eq!(lang, Lang::Lua);
+        assert!(!tree.root_node().has_error());
+    }
+
+    #[test]
+    fn parse_elixir_file() {
+        let src = "defmodule App do\n  def main, do: IO.puts(\"hi\")\nend\n";
+        let (tree, lang) = parse_temp("ex", src).unwrap();
+        assert_eq!(lang, Lang::Elixir);
+        assert!(!tree.root_node().has_error());
+    }
+
+    #[test]
+    fn parse_dart_file() {
+        let src = "void main() {\n  print('hi');\n}\n";
+        let (tree, lang) = parse_temp("dart", src).unwrap();
+        assert_eq!(lang, Lang::Dart);
+        assert!(!tree.root_node().has_error());
+    }
+
+    #[test]
+    fn parse_shell_file() {
+        let src = "build() {\n  make all\n}\n";
+        let (tree, lang) = parse_temp("sh", src).unwrap();
+        assert_eq!(lang, Lang::Shell);
+        assert!(!tree.root_node().has_error());
+    }
+
+    #[test]
+    fn parse_sql_file() {
+        let (tree, lang) = parse_temp("sql", "CREATE TABLE users (id INT);\n").unwrap();
+        assert_eq!(lang, Lang::Sql);
+        assert!(!tree.root_node().has_error());
+    }
+
+    #[test]
+    fn parse_vue_file() {
+        let src = "<template>\n  <p>{{ msg }}</p>\n</template>\n<script setup lang=\"ts\">\nconst msg: string = 'hi'\n</script>\n<style scoped>\np { color: red; }\n</style>\n";
+        let (tree, lang) = parse_temp("vue", src).unwrap();
+        assert_eq!(lang, Lang::Vue);
+        assert!(!tree.root_node().has_error());
+    }
+
     #[test]
     fn parse_c_file() {
         let src = "int main() { return 0; }";
@@ -3675,7 +6860,7 @@ mod tests {
 
     /// Parse source code for a given language and extract symbols.
     fn extract_from(lang: Lang, source: &str) -> Vec<Symbol> {
-        let mut parser = get_parser(lang);
+        let mut parser = get_parser(lang).unwrap();
         let tree = parser.parse(source.as_bytes(), None).unwrap();
         extract_symbols(&tree, source, "test_file", lang)
     }
@@ -3971,14 +7156,363 @@ mod tests {
     }
 
     #[test]
-    fn java_final_field() {
-        let src = "class Config {\n    static final int MAX = 100;\n    int count;\n}";
-        let syms = extract_from(Lang::Java, src);
-        let c = find_sym(&syms, "MAX");
-        assert_eq!(c.kind, SymbolKind::Constant);
-        assert_eq!(c.scope.as_deref(), Some("Config"));
-        let v = find_sym(&syms, "count");
-        assert_eq!(v.kind, SymbolKind::Variable);
+    fn java_final_field() {
+        let src = "class Config {\n    static final int MAX = 100;\n    int count;\n}";
+        let syms = extract_from(Lang::Java, src);
+        let c = find_sym(&syms, "MAX");
+        assert_eq!(c.kind, SymbolKind::Constant);
+        assert_eq!(c.scope.as_deref(), Some("Config"));
+        let v = find_sym(&syms, "count");
+        assert_eq!(v.kind, SymbolKind::Variable);
+    }
+
+    #[test]
+    fn java_package_scopes_top_level_types() {
+        let src = "package com.acme.billing;\n\nimport java.util.List;\n\npublic class Invoice {\n    class Line {}\n    void total() {}\n}\n";
+        let syms = extract_from(Lang::Java, src);
+        assert_eq!(
+            find_sym(&syms, "Invoice").scope.as_deref(),
+            Some("com.acme.billing")
+        );
+        assert_eq!(find_sym(&syms, "Line").scope.as_deref(), Some("Invoice"));
+        assert_eq!(find_sym(&syms, "total").scope.as_deref(), Some("Invoice"));
+    }
+
+    // ---------- Kotlin symbol extraction ----------
+
+    #[test]
+    fn kotlin_classes_objects_and_members() {
+        let src = "package com.acme.store\n\n/**\n * One entry.\n */\ndata class Entry(val key: String) {\n    fun size(): Int = key.length\n    companion object {\n        const val MAX = 10\n        fun create(): Entry = Entry(\"a\")\n    }\n}\n\ninternal object Registry {\n    private fun clear() {}\n}\n\nsealed interface Shape\nenum class Color { RED, GREEN }\ntypealias Names = List<String>\n";
+        let syms = extract_from(Lang::Kotlin, src);
+        let entry = find_sym(&syms, "Entry");
+        assert_eq!(entry.kind, SymbolKind::Class);
+        assert_eq!(entry.scope.as_deref(), Some("com.acme.store"));
+        assert_eq!(entry.doc_comment.as_deref(), Some("One entry."));
+        let size = find_sym(&syms, "size");
+        assert_eq!(size.kind, SymbolKind::Method);
+        assert_eq!(size.scope.as_deref(), Some("Entry"));
+        // Companion members are scoped to the class and static.
+        let create = find_sym(&syms, "create");
+        assert_eq!(create.scope.as_deref(), Some("Entry"));
+        assert_eq!(vis_mods(&syms, "create"), vm("public", &["static"]));
+        assert_eq!(find_sym(&syms, "MAX").kind, SymbolKind::Constant);
+        assert_eq!(find_sym(&syms, "Registry").kind, SymbolKind::Class);
+        assert_eq!(vis_mods(&syms, "Registry"), vm("internal", &[]));
+        assert_eq!(vis_mods(&syms, "clear"), vm("private", &[]));
+        assert_eq!(find_sym(&syms, "Shape").kind, SymbolKind::Interface);
+        assert_eq!(vis_mods(&syms, "Shape"), vm("public", &["sealed"]));
+        assert_eq!(find_sym(&syms, "Color").kind, SymbolKind::Enum);
+        assert_eq!(find_sym(&syms, "Names").kind, SymbolKind::TypeAlias);
+    }
+
+    #[test]
+    fn kotlin_functions_and_extension_functions() {
+        let src = "fun String.shout(): String = uppercase()\n\nsuspend fun <T : Comparable<T>> load(path: String): T {\n    val e = 1\n    fun local() {}\n    return parse(path)\n}\n\nval greeting = \"hi\"\n\nclass Store(x: Int) {\n    constructor() : this(1)\n    var count: Int = 0\n}\n";
+        let syms = extract_from(Lang::Kotlin, src);
+        let shout = find_sym(&syms, "shout");
+        assert_eq!(shout.kind, SymbolKind::Method);
+        assert_eq!(shout.scope.as_deref(), Some("String"));
+        let load = find_sym(&syms, "load");
+        assert_eq!(load.kind, SymbolKind::Function);
+        assert_eq!(load.generics, ["T: Comparable<T>"]);
+        assert_eq!(find_sym(&syms, "local").visibility, None);
+        assert!(syms.iter().all(|s| s.name != "e"));
+        assert_eq!(find_sym(&syms, "greeting").kind, SymbolKind::Variable);
+        let ctors: Vec<_> = syms
+            .iter()
+            .filter(|s| s.name == "Store" && s.kind == SymbolKind::Method)
+            .collect();
+        assert_eq!(ctors.len(), 1);
+        assert_eq!(find_sym(&syms, "count").scope.as_deref(), Some("Store"));
+    }
+
+    #[test]
+    fn scala_classes_objects_traits_and_members() {
+        let src = "package com.acme.store\n\n/**\n * One entry.\n */\ncase class Entry(key: String) {\n  def size: Int = key.length\n  private val cache = 1\n}\n\nobject Entry {\n  val Max = 10\n  def create(): Entry = Entry(\"a\")\n}\n\nsealed trait Shape {\n  def area: Double\n}\ncase object Empty extends Shape\ntype Names = List[String]\n";
+        let syms = extract_from(Lang::Scala, src);
+        let entry = syms
+            .iter()
+            .find(|s| s.name == "Entry" && s.doc_comment.is_some())
+            .unwrap();
+        assert_eq!(entry.kind, SymbolKind::Class);
+        assert_eq!(entry.scope.as_deref(), Some("com.acme.store"));
+        assert_eq!(entry.doc_comment.as_deref(), Some("One entry."));
+        let size = find_sym(&syms, "size");
+        assert_eq!(size.kind, SymbolKind::Method);
+        assert_eq!(size.scope.as_deref(), Some("Entry"));
+        assert_eq!(vis_mods(&syms, "cache"), vm("private", &[]));
+        // Object members belong to the singleton and are static.
+        let create = find_sym(&syms, "create");
+        assert_eq!(create.scope.as_deref(), Some("Entry"));
+        assert_eq!(vis_mods(&syms, "create"), vm("public", &["static"]));
+        assert_eq!(find_sym(&syms, "Max").kind, SymbolKind::Constant);
+        assert_eq!(find_sym(&syms, "Shape").kind, SymbolKind::Trait);
+        assert_eq!(find_sym(&syms, "area").kind, SymbolKind::Method);
+        assert_eq!(find_sym(&syms, "Empty").kind, SymbolKind::Class);
+        assert_eq!(find_sym(&syms, "Names").kind, SymbolKind::TypeAlias);
+    }
+
+    #[test]
+    fn scala_functions_values_and_extensions() {
+        let src = "def load[T <: Entry : Codec](path: String): T = {\n  val e = 1\n  def local() = 2\n  parse(path)\n}\n\nval greeting = \"hi\"\nvar counter = 0\nval (a, b) = (1, 2)\n\nextension (s: String)\n  def shout: String = s.toUpperCase\n\nclass Box[+A, -B >: Null]\n";
+        let syms = extract_from(Lang::Scala, src);
+        let load = find_sym(&syms, "load");
+        assert_eq!(load.kind, SymbolKind::Function);
+        assert_eq!(load.generics, ["T: Entry + Codec"]);
+        assert_eq!(find_sym(&syms, "local").visibility, None);
+        assert!(syms.iter().all(|s| s.name != "e" && s.name != "a"));
+        assert_eq!(find_sym(&syms, "greeting").kind, SymbolKind::Constant);
+        assert_eq!(find_sym(&syms, "counter").kind, SymbolKind::Variable);
+        let shout = find_sym(&syms, "shout");
+        assert_eq!(shout.kind, SymbolKind::Method);
+        assert_eq!(shout.scope.as_deref(), Some("String"));
+        assert_eq!(find_sym(&syms, "Box").generics, ["+A", "-B: >: Null"]);
+    }
+
+    #[test]
+    fn lua_method_tables_populate_scope() {
+        let src = "local M = {}\n\n--- Greets someone.\n--- Returns a string.\nfunction M.greet(name)\n  return name\nend\n\nfunction M:reset() end\nfunction M.util.trim(s) end\nlocal function helper()\n  local function inner() end\nend\nfunction setup() end\nM.handler = function(req) end\nlocal cb = function() end\nM.VERSION = \"1.0\"\nreturn M\n";
+        let syms = extract_from(Lang::Lua, src);
+        let greet = find_sym(&syms, "greet");
+        assert_eq!(greet.kind, SymbolKind::Function);
+        assert_eq!(greet.scope.as_deref(), Some("M"));
+        assert_eq!(
+            greet.doc_comment.as_deref(),
+            Some("Greets someone. Returns a string.")
+        );
+        let reset = find_sym(&syms, "reset");
+        assert_eq!(reset.kind, SymbolKind::Method);
+        assert_eq!(reset.scope.as_deref(), Some("M"));
+        assert_eq!(find_sym(&syms, "trim").scope.as_deref(), Some("M.util"));
+        assert_eq!(vis_mods(&syms, "helper"), vm("private", &[]));
+        assert_eq!(find_sym(&syms, "inner").visibility, None);
+        let setup = find_sym(&syms, "setup");
+        assert_eq!(setup.scope, None);
+        assert_eq!(vis_mods(&syms, "setup"), vm("public", &[]));
+        let handler = find_sym(&syms, "handler");
+        assert_eq!(handler.kind, SymbolKind::Function);
+        assert_eq!(handler.scope.as_deref(), Some("M"));
+        let cb = find_sym(&syms, "cb");
+        assert_eq!(cb.signature, "local cb = function() end");
+        assert_eq!(vis_mods(&syms, "cb"), vm("private", &[]));
+        assert!(syms.iter().all(|s| s.name != "VERSION" && s.name != "M"));
+    }
+
+    #[test]
+    fn elixir_functions_are_scoped_by_module() {
+        let src = "defmodule MyApp.Accounts do\n  @moduledoc \"\"\"\n  Account management.\n  \"\"\"\n  defstruct [:name]\n\n  @doc \"Fetch a user.\"\n  @spec get(integer) :: t()\n  def get(id) when is_integer(id) do\n    id\n  end\n\n  def ping, do: :pong\n  defp wrap(x), do: {:ok, x}\n  defmacro debug(expr), do: expr\n  defmacrop trace(expr), do: expr\n\n  @doc false\n  def hidden, do: nil\n\n  defmodule Inner do\n    def hi, do: :hi\n  end\nend\n";
+        let syms = extract_from(Lang::Elixir, src);
+        let module = syms
+            .iter()
+            .find(|s| s.kind == SymbolKind::Module && s.name == "MyApp.Accounts")
+            .unwrap();
+        assert_eq!(module.scope, None);
+        assert_eq!(module.signature, "defmodule MyApp.Accounts");
+        assert_eq!(module.doc_comment.as_deref(), Some("Account management."));
+        let strukt = syms.iter().find(|s| s.kind == SymbolKind::Struct).unwrap();
+        assert_eq!(strukt.name, "MyApp.Accounts");
+        let get = find_sym(&syms, "get");
+        assert_eq!(get.kind, SymbolKind::Function);
+        assert_eq!(get.scope.as_deref(), Some("MyApp.Accounts"));
+        assert_eq!(get.signature, "def get(id) when is_integer(id)");
+        assert_eq!(get.doc_comment.as_deref(), Some("Fetch a user."));
+        assert_eq!(
+            find_sym(&syms, "ping").scope.as_deref(),
+            Some("MyApp.Accounts")
+        );
+        assert_eq!(find_sym(&syms, "hidden").doc_comment, None);
+        assert_eq!(vis_mods(&syms, "get"), vm("public", &[]));
+        assert_eq!(vis_mods(&syms, "wrap"), vm("private", &[]));
+        assert_eq!(vis_mods(&syms, "debug"), vm("public", &[]));
+        assert_eq!(vis_mods(&syms, "trace"), vm("private", &[]));
+        assert_eq!(
+            find_sym(&syms, "Inner").scope.as_deref(),
+            Some("MyApp.Accounts")
+        );
+        assert_eq!(find_sym(&syms, "hi").scope.as_deref(), Some("Inner"));
+    }
+
+    #[test]
+    fn dart_members_are_scoped_by_their_declaration() {
+        let src = "/// A shape.\nabstract class Shape<T extends num> {\n  Shape(this.sides);\n  Shape.unit() : sides = 1;\n  factory Shape.of(int n) => Square(n);\n\n  final int sides;\n  static const max = 12;\n\n  /// The area.\n  double area();\n\n  static Shape parse(String s) => Shape.of(int.parse(s));\n\n  Future<void> load() async {}\n  int get size => sides;\n  bool operator ==(Object other) => false;\n  void _reset() {}\n}\n\nmixin Named {\n  String describe() => 'named';\n}\n\nextension on String {\n  bool get isBlank => trim().isEmpty;\n}\n\nenum Color { red, green }\n\ntypedef Predicate<T> = bool Function(T value);\n\nT first<T>(List<T> items) => items[0];\nfinal _cache = <String, Shape>{};\n";
+        let syms = extract_from(Lang::Dart, src);
+        let shape = syms
+            .iter()
+            .find(|s| s.kind == SymbolKind::Class && s.name == "Shape")
+            .unwrap();
+        assert_eq!(shape.doc_comment.as_deref(), Some("A shape."));
+        assert_eq!(shape.generics, ["T: num"]);
+        assert_eq!(vis_mods(&syms, "Shape"), vm("public", &["abstract"]));
+        let ctors: Vec<&str> = syms
+            .iter()
+            .filter(|s| s.kind == SymbolKind::Method && s.scope.as_deref() == Some("Shape"))
+            .map(|s| s.name.as_str())
+            .take(3)
+            .collect();
+        assert_eq!(ctors, ["Shape", "unit", "of"]);
+        assert_eq!(find_sym(&syms, "of").signature, "factory Shape.of(int n)");
+        assert_eq!(find_sym(&syms, "sides").kind, SymbolKind::Variable);
+        assert_eq!(find_sym(&syms, "max").kind, SymbolKind::Constant);
+        let area = find_sym(&syms, "area");
+        assert_eq!(area.kind, SymbolKind::Method);
+        assert_eq!(area.doc_comment.as_deref(), Some("The area."));
+        assert_eq!(vis_mods(&syms, "parse"), vm("public", &["static"]));
+        assert_eq!(vis_mods(&syms, "load"), vm("public", &["async"]));
+        assert_eq!(find_sym(&syms, "size").scope.as_deref(), Some("Shape"));
+        assert_eq!(find_sym(&syms, "operator ==").kind, SymbolKind::Method);
+        assert_eq!(vis_mods(&syms, "_reset"), vm("private", &[]));
+        assert_eq!(find_sym(&syms, "Named").kind, SymbolKind::Trait);
+        assert_eq!(find_sym(&syms, "describe").scope.as_deref(), Some("Named"));
+        let extension = find_sym(&syms, "String");
+        assert_eq!(extension.kind, SymbolKind::Module);
+        assert_eq!(find_sym(&syms, "isBlank").scope.as_deref(), Some("String"));
+        assert_eq!(find_sym(&syms, "Color").kind, SymbolKind::Enum);
+        assert_eq!(find_sym(&syms, "Predicate").kind, SymbolKind::TypeAlias);
+        let first = find_sym(&syms, "first");
+        assert_eq!(first.kind, SymbolKind::Function);
+        assert_eq!(first.generics, ["T"]);
+        assert_eq!(vis_mods(&syms, "_cache"), vm("private", &["final"]));
+    }
+
+    #[test]
+    fn shell_functions() {
+        let src = "#!/usr/bin/env bash\n# Build the artifact.\n# Into dist/.\nbuild() {\n  make all\n}\n\nfunction deploy {\n  build\n}\n\nfunction cleanup() { rm -rf /tmp/x; }\nreadonly VERSION=1\n";
+        let syms = extract_from(Lang::Shell, src);
+        assert_eq!(syms.len(), 3);
+
+        let build = find_sym(&syms, "build");
+        assert_eq!(build.kind, SymbolKind::Function);
+        assert_eq!(build.line, 4);
+        assert_eq!(build.signature, "build()");
+        assert_eq!(
+            build.doc_comment.as_deref(),
+            Some("Build the artifact. Into dist/.")
+        );
+        assert_eq!(vis_mods(&syms, "build"), vm("public", &[]));
+
+        assert_eq!(find_sym(&syms, "deploy").kind, SymbolKind::Function);
+        assert_eq!(find_sym(&syms, "cleanup").kind, SymbolKind::Function);
+    }
+
+    #[test]
+    fn component_script_source_keeps_offsets() {
+        let src = "<template>\n  <div/>\n</template>\n<script>\nexport default {}\n</script>\n";
+        let kept = component_script_source(src);
+        assert_eq!(kept.len(), src.len());
+        assert_eq!(
+            kept,
+            "          \n        \n           \n        \nexport default {}\n         \n"
+        );
+        // Nothing to keep without a script block.
+        assert!(component_script_source("<template/>\n").trim().is_empty());
+    }
+
+    #[test]
+    fn vue_component_and_script_symbols() {
+        let src = "<script lang=\"ts\">\nexport interface Props { name: string }\n</script>\n\n<script setup lang=\"ts\">\nimport { ref } from 'vue'\nimport Avatar from './Avatar.vue'\n\n/**\n * Clicks so far.\n */\nconst count = ref(0)\nfunction increment() {\n  count.value++\n}\n</script>\n\n<template>\n  <button @click=\"increment\">{{ count }}</button>\n</template>\n";
+        let file = "src/components/user-card.vue";
+        let text = parse_source(Lang::Vue, src);
+        let tree = get_parser(Lang::Vue)
+            .unwrap()
+            .parse(text.as_bytes(), None)
+            .unwrap();
+        let syms = extract_symbols(&tree, &text, file, Lang::Vue);
+
+        let component = &syms[0];
+        assert_eq!(component.name, "UserCard");
+        assert_eq!(component.kind, SymbolKind::Class);
+        assert_eq!((component.line, component.end_line), (1, Some(20)));
+        assert_eq!(component.signature, "user-card.vue");
+
+        let props = find_sym(&syms, "Props");
+        assert_eq!(props.kind, SymbolKind::Interface);
+        assert_eq!(props.line, 2);
+        assert_eq!(props.language, "Vue");
+        let increment = find_sym(&syms, "increment");
+        assert_eq!(increment.kind, SymbolKind::Function);
+        assert_eq!(increment.line, 13);
+        let count = find_sym(&syms, "count");
+        assert_eq!(count.line, 12);
+        assert_eq!(count.doc_comment.as_deref(), Some("Clicks so far."));
+
+        let imports = extract_imports(&tree, &text, file, Lang::Vue);
+        assert_eq!(imports.imports, ["vue", "./Avatar.vue"]);
+        let refs = extract_references(&tree, &text, file, Lang::Vue);
+        let call = find_ref(&refs, "ref");
+        assert_eq!((call.line, call.kind), (12, ReferenceKind::Call));
+    }
+
+    #[test]
+    fn svelte_component_and_script_symbols() {
+        let src = "<script context=\"module\" lang=\"ts\">\n  export const prerender = true;\n</script>\n\n<script lang=\"ts\">\n  import { onMount } from 'svelte';\n  import Button from '$lib/Button.svelte';\n\n  export let start = 0;\n  let count = start;\n  $: doubled = count * 2;\n\n  function increment() {\n    count += 1;\n  }\n  onMount(increment);\n</script>\n\n<Button on:click={increment}>{count} / {doubled}</Button>\n";
+        let file = "src/lib/Counter.svelte";
+        let text = parse_source(Lang::Svelte, src);
+        let tree = get_parser(Lang::Svelte)
+            .unwrap()
+            .parse(text.as_bytes(), None)
+            .unwrap();
+        let syms = extract_symbols(&tree, &text, file, Lang::Svelte);
+
+        let component = &syms[0];
+        assert_eq!(component.name, "Counter");
+        assert_eq!(component.kind, SymbolKind::Class);
+        assert_eq!(component.language, "Svelte");
+
+        assert_eq!(find_sym(&syms, "prerender").line, 2);
+        assert_eq!(find_sym(&syms, "start").line, 9);
+        let increment = find_sym(&syms, "increment");
+        assert_eq!(increment.kind, SymbolKind::Function);
+        assert_eq!(increment.line, 13);
+
+        let imports = extract_imports(&tree, &text, file, Lang::Svelte);
+        assert_eq!(imports.imports, ["svelte", "$lib/Button.svelte"]);
+        let refs = extract_references(&tree, &text, file, Lang::Svelte);
+        assert_eq!(find_ref(&refs, "onMount").line, 16);
+    }
+
+    #[test]
+    fn sql_schema_objects() {
+        let src = "-- Registered accounts.\n-- One row per login.\nCREATE TABLE users (\n  id BIGINT PRIMARY KEY\n);\nCREATE TABLE IF NOT EXISTS billing.\"Invoices\" (id INT);\nCREATE VIEW active_users AS SELECT * FROM users;\nCREATE MATERIALIZED VIEW totals AS SELECT 1;\nCREATE UNIQUE INDEX idx_users_email ON users (email);\nCREATE INDEX ON users (id);\nCREATE OR REPLACE FUNCTION add_one(x INT) RETURNS INT AS $$ SELECT x + 1 $$ LANGUAGE sql;\nCREATE PROCEDURE dbo.archive_users\nAS\nBEGIN\n  SELECT 1;\nEND;\nCREATE TYPE mood AS ENUM ('sad', 'ok');\nINSERT INTO users (id) VALUES (1);\n";
+        let syms = extract_from(Lang::Sql, src);
+        let names: Vec<&str> = syms.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "users",
+                "Invoices",
+                "active_users",
+                "totals",
+                "idx_users_email",
+                "add_one",
+                "archive_users",
+                "mood"
+            ]
+        );
+
+        let users = find_sym(&syms, "users");
+        assert_eq!(users.kind, SymbolKind::Struct);
+        assert_eq!(users.line, 3);
+        assert_eq!(users.signature, "CREATE TABLE users (");
+        assert_eq!(
+            users.doc_comment.as_deref(),
+            Some("Registered accounts. One row per login.")
+        );
+        assert_eq!(users.visibility, None);
+
+        assert_eq!(
+            find_sym(&syms, "Invoices").scope.as_deref(),
+            Some("billing")
+        );
+        assert_eq!(find_sym(&syms, "active_users").kind, SymbolKind::Struct);
+        assert_eq!(
+            find_sym(&syms, "idx_users_email").kind,
+            SymbolKind::Variable
+        );
+        assert_eq!(find_sym(&syms, "add_one").kind, SymbolKind::Function);
+        let proc = find_sym(&syms, "archive_users");
+        assert_eq!(proc.kind, SymbolKind::Function);
+        assert_eq!(proc.scope.as_deref(), Some("dbo"));
+        assert_eq!(find_sym(&syms, "mood").kind, SymbolKind::Enum);
     }
 
     // ---------- C symbol extraction ----------
@@ -4010,8 +7544,216 @@ mod tests {
         assert_eq!(c.kind, SymbolKind::Constant);
     }
 
+    #[test]
+    fn c_symbols_record_preprocessor_conditions() {
+        let src = "#ifndef BOARD_H\n#define BOARD_H\n\nstruct board { int id; };\n\n#ifdef CONFIG_ARM\nvoid init(void) {}\n#elif defined(CONFIG_X86) || CONFIG_SIM\nvoid init(void) {}\n#else\nvoid init(void) {}\n#endif\n\n#if !NDEBUG\n#ifndef QUIET\n#define TRACE 1\n#endif\n#endif\n\n#endif\n";
+        let syms = extract_from(Lang::C, src);
+        assert_eq!(find_sym(&syms, "board").condition, None);
+        assert_eq!(find_sym(&syms, "BOARD_H").condition, None);
+        let inits: Vec<Option<&str>> = syms
+            .iter()
+            .filter(|s| s.name == "init")
+            .map(|s| s.condition.as_deref())
+            .collect();
+        assert_eq!(
+            inits,
+            [
+                Some("defined(CONFIG_ARM)"),
+                Some("!defined(CONFIG_ARM) && (defined(CONFIG_X86) || CONFIG_SIM)"),
+                Some("!defined(CONFIG_ARM) && !(defined(CONFIG_X86) || CONFIG_SIM)"),
+            ]
+        );
+        assert_eq!(
+            find_sym(&syms, "TRACE").condition.as_deref(),
+            Some("!NDEBUG && !defined(QUIET)")
+        );
+    }
+
+    #[test]
+    fn cpp_methods_inherit_class_condition() {
+        let src = "#ifdef WITH_NET\nclass Socket {\npublic:\n    void open() {}\n};\n#endif\n";
+        let syms = extract_from(Lang::Cpp, src);
+        assert_eq!(
+            find_sym(&syms, "open").condition.as_deref(),
+            Some("defined(WITH_NET)")
+        );
+    }
+
     // ---------- C++ symbol extraction ----------
 
+    /// (visibility, modifiers) of the first symbol named `name`.
+    fn vis_mods(syms: &[Symbol], name: &str) -> (Option<String>, Vec<String>) {
+        let sym = find_sym(syms, name);
+        (sym.visibility.clone(), sym.modifiers.clone())
+    }
+
+    fn vm(visibility: &str, modifiers: &[&str]) -> (Option<String>, Vec<String>) {
+        (
+            Some(visibility.to_string()),
+            modifiers.iter().map(|m| m.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn rust_visibility_and_modifiers() {
+        let src = "pub(crate) async unsafe fn load() {}\nfn helper() {}\npub static COUNT: u32 = 0;\npub const fn size() -> usize { 0 }\ntrait Run { fn run(&self); }\nimpl Run for X { fn run(&self) {} }\n";
+        let syms = extract_from(Lang::Rust, src);
+        assert_eq!(
+            vis_mods(&syms, "load"),
+            vm("pub(crate)", &["async", "unsafe"])
+        );
+        assert_eq!(vis_mods(&syms, "helper"), vm("private", &[]));
+        assert_eq!(vis_mods(&syms, "COUNT"), vm("public", &["static"]));
+        assert_eq!(vis_mods(&syms, "size"), vm("public", &["const"]));
+        for run in syms.iter().filter(|s| s.name == "run") {
+            assert_eq!(run.visibility.as_deref(), Some("public"));
+        }
+    }
+
+    #[test]
+    fn java_and_csharp_visibility_and_modifiers() {
+        let java = "public abstract class Shape {\n    protected static final int SIDES = 0;\n    abstract double area();\n}\ninterface Named { String name(); }\n";
+        let syms = extract_from(Lang::Java, java);
+        assert_eq!(vis_mods(&syms, "Shape"), vm("public", &["abstract"]));
+        assert_eq!(
+            vis_mods(&syms, "SIDES"),
+            vm("protected", &["static", "final"])
+        );
+        assert_eq!(vis_mods(&syms, "area"), vm("package", &["abstract"]));
+        assert_eq!(vis_mods(&syms, "name"), vm("public", &[]));
+
+        let cs = "class Store {\n    protected internal static async Task Save() {}\n    readonly int count;\n}\n";
+        let syms = extract_from(Lang::CSharp, cs);
+        assert_eq!(vis_mods(&syms, "Store"), vm("internal", &[]));
+        assert_eq!(
+            vis_mods(&syms, "Save"),
+            vm("protected internal", &["static", "async"])
+        );
+    }
+
+    #[test]
+    fn ts_python_go_visibility_and_modifiers() {
+        let ts = "export async function load() {}\nfunction local() {}\nclass Cache {\n    private static readonly size = 1;\n    async get() {}\n}\n";
+        let syms = extract_from(Lang::TypeScript, ts);
+        assert_eq!(vis_mods(&syms, "load"), vm("public", &["async"]));
+        assert_eq!(vis_mods(&syms, "local"), vm("private", &[]));
+        assert_eq!(vis_mods(&syms, "get"), vm("public", &["async"]));
+
+        let py = "class Repo:\n    @staticmethod\n    async def _fetch(): pass\n    def __init__(self): pass\n";
+        let syms = extract_from(Lang::Python, py);
+        assert_eq!(
+            vis_mods(&syms, "_fetch"),
+            vm("private", &["static", "async"])
+        );
+        assert_eq!(vis_mods(&syms, "__init__"), vm("public", &[]));
+
+        let go = "package main\nfunc Serve() {}\nfunc helper() {}\n";
+        let syms = extract_from(Lang::Go, go);
+        assert_eq!(vis_mods(&syms, "Serve"), vm("public", &[]));
+        assert_eq!(vis_mods(&syms, "helper"), vm("package", &[]));
+    }
+
+    #[test]
+    fn php_ruby_cpp_visibility_and_modifiers() {
+        let php = "<?php\nclass Repo {\n    private static function load() {}\n    function save() {}\n}\n";
+        let syms = extract_from(Lang::Php, php);
+        assert_eq!(vis_mods(&syms, "load"), vm("private", &["static"]));
+        assert_eq!(vis_mods(&syms, "save"), vm("public", &[]));
+
+        let rb = "class Repo\n  def load; end\n  private\n  def fetch; end\n  def self.build; end\nend\n";
+        let syms = extract_from(Lang::Ruby, rb);
+        assert_eq!(vis_mods(&syms, "load"), vm("public", &[]));
+        assert_eq!(vis_mods(&syms, "fetch"), vm("private", &[]));
+
+        let cpp = "class Widget {\n    void draw() {}\npublic:\n    virtual void paint() override {}\n};\nstatic inline int clamp(int x) { return x; }\n";
+        let syms = extract_from(Lang::Cpp, cpp);
+        assert_eq!(vis_mods(&syms, "draw"), vm("private", &[]));
+        assert_eq!(
+            vis_mods(&syms, "paint"),
+            vm("public", &["virtual", "override"])
+        );
+        assert_eq!(
+            vis_mods(&syms, "clamp"),
+            vm("private", &["static", "inline"])
+        );
+    }
+
+    fn generics_of(syms: &[Symbol], name: &str) -> Vec<String> {
+        find_sym(syms, name).generics.clone()
+    }
+
+    #[test]
+    fn rust_generics_merge_where_clauses() {
+        let src = "fn copy<'a, T: Read + Send, const N: usize>(x: &'a T) where T: Clone {}\nstruct Slot<T = i32> { v: T }\nfn plain() {}\n";
+        let syms = extract_from(Lang::Rust, src);
+        assert_eq!(
+            generics_of(&syms, "copy"),
+            ["'a", "T: Read + Send + Clone", "const N: usize"]
+        );
+        assert_eq!(generics_of(&syms, "Slot"), ["T = i32"]);
+        assert!(generics_of(&syms, "plain").is_empty());
+    }
+
+    #[test]
+    fn symbols_and_references_record_source_ranges() {
+        let src = "fn a() {}\nfn b() {\n    a();\n}\n";
+        let syms = extract_from(Lang::Rust, src);
+        let b = find_sym(&syms, "b");
+        let (start, end) = (b.start_byte.unwrap(), b.end_byte.unwrap());
+        assert_eq!(&src[start..end], "fn b() {\n    a();\n}");
+        assert_eq!((b.end_line, b.end_col), (Some(4), Some(1)));
+
+        let refs = refs_from(Lang::Rust, src);
+        let call = refs.iter().find(|r| r.name == "a").unwrap();
+        let (start, end) = (call.start_byte.unwrap(), call.end_byte.unwrap());
+        assert_eq!(&src[start..end], "a()");
+        assert_eq!((call.col, call.end_col), (4, Some(7)));
+    }
+
+    #[test]
+    fn ts_java_csharp_generics_normalise_bounds() {
+        let ts = "function pick<T extends Keyed, U = string>(x: T): U {}\nclass Box<T> {}\n";
+        let syms = extract_from(Lang::TypeScript, ts);
+        assert_eq!(generics_of(&syms, "pick"), ["T: Keyed", "U = string"]);
+        assert_eq!(generics_of(&syms, "Box"), ["T"]);
+
+        let java = "class Box<T extends Comparable<T> & Serializable, U> { <K> void put(K k) {} }";
+        let syms = extract_from(Lang::Java, java);
+        assert_eq!(
+            generics_of(&syms, "Box"),
+            ["T: Comparable<T> + Serializable", "U"]
+        );
+        assert_eq!(generics_of(&syms, "put"), ["K"]);
+
+        let cs =
+            "class Box<T, U> where T : IComparable, new() { void Put<K>() where K : class {} }";
+        let syms = extract_from(Lang::CSharp, cs);
+        assert_eq!(generics_of(&syms, "Box"), ["T: IComparable + new()", "U"]);
+        assert_eq!(generics_of(&syms, "Put"), ["K: class"]);
+    }
+
+    #[test]
+    fn go_cpp_python_generics() {
+        let go = "package m\nfunc Merge[K comparable, A, B any](m map[K]A) {}\ntype Set[T comparable] struct{}\n";
+        let syms = extract_from(Lang::Go, go);
+        assert_eq!(
+            generics_of(&syms, "Merge"),
+            ["K: comparable", "A: any", "B: any"]
+        );
+        assert_eq!(generics_of(&syms, "Set"), ["T: comparable"]);
+
+        let cpp = "template <typename T, class U = int, int N, typename... Ts> T max(T a) { return a; }\n";
+        let syms = extract_from(Lang::Cpp, cpp);
+        assert_eq!(
+            generics_of(&syms, "max"),
+            ["T", "U = int", "int N", "...Ts"]
+        );
+
+        let py = "def first[T: Sized, *Ts](xs): pass\n";
+        let syms = extract_from(Lang::Python, py);
+        assert_eq!(generics_of(&syms, "first"), ["T: Sized", "*Ts"]);
+    }
+
     #[test]
     fn cpp_class_with_method() {
         let src = "class Dog {\npublic:\n    void bark() { }\n};";
@@ -4084,6 +7826,40 @@ mod tests {
         assert_eq!(m.scope.as_deref(), Some("Dog"));
     }
 
+    #[test]
+    fn php_namespace_scopes_symbols() {
+        let src = "<?php\nnamespace App\\Service;\n\nclass Mailer {\n    public function send() {}\n}\nfunction helper() {}\n";
+        let syms = extract_from(Lang::Php, src);
+        assert_eq!(
+            find_sym(&syms, "Mailer").scope.as_deref(),
+            Some("App\\Service")
+        );
+        assert_eq!(find_sym(&syms, "send").scope.as_deref(), Some("Mailer"));
+        let helper = find_sym(&syms, "helper");
+        assert_eq!(helper.scope.as_deref(), Some("App\\Service"));
+        assert_eq!(helper.kind, SymbolKind::Function);
+
+        let braced = "<?php\nnamespace Lib {\n    class Box {}\n}\n";
+        let syms = extract_from(Lang::Php, braced);
+        assert_eq!(find_sym(&syms, "Box").scope.as_deref(), Some("Lib"));
+    }
+
+    #[test]
+    fn php_use_imports_are_qualified_names() {
+        let src = "<?php\nuse App\\Service\\Foo;\nuse App\\Models\\{User, Post as P};\nuse \\Other\\Bar as Baz, Qux;\n";
+        let fi = imports_from(Lang::Php, src);
+        assert_eq!(
+            fi.imports,
+            [
+                "App\\Service\\Foo",
+                "App\\Models\\User",
+                "App\\Models\\Post",
+                "Other\\Bar",
+                "Qux"
+            ]
+        );
+    }
+
     #[test]
     fn php_interface_and_trait() {
         let src = "<?php\ninterface Printable {\n    public function print();\n}\n\ntrait Loggable {\n    public function log() {}\n}\n?>";
@@ -4131,14 +7907,14 @@ mod tests {
 
     /// Parse source and extract references for a given language.
     fn refs_from(lang: Lang, source: &str) -> Vec<Reference> {
-        let mut parser = get_parser(lang);
+        let mut parser = get_parser(lang).unwrap();
         let tree = parser.parse(source.as_bytes(), None).unwrap();
         extract_references(&tree, source, "test_file", lang)
     }
 
     /// Parse source and extract imports for a given language.
     fn imports_from(lang: Lang, source: &str) -> FileImports {
-        let mut parser = get_parser(lang);
+        let mut parser = get_parser(lang).unwrap();
         let tree = parser.parse(source.as_bytes(), None).unwrap();
         extract_imports(&tree, source, "test_file", lang)
     }
@@ -4327,6 +8103,42 @@ mod tests {
         assert!(has_ref(&refs, "FC", ReferenceKind::Type));
     }
 
+    #[test]
+    fn jsx_component_references() {
+        let src = "function App() {\n  return (\n    <Layout title=\"x\">\n      <UI.Button onClick={go} />\n      <div><span>hi</span></div>\n    </Layout>\n  );\n}\n";
+        for lang in [Lang::Tsx, Lang::JavaScript] {
+            let refs = refs_from(lang, src);
+            let components: Vec<_> = refs
+                .iter()
+                .filter(|r| r.kind == ReferenceKind::Component)
+                .map(|r| (r.name.as_str(), r.line, r.caller_name.as_deref()))
+                .collect();
+            assert_eq!(
+                components,
+                [("Layout", 3, Some("App")), ("Button", 4, Some("App"))],
+                "{lang:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn ruby_constants_skip_definitions() {
+        let src = "module Admin\n  class ReportsController < ApplicationController\n    include Pagination\n    LIMIT = 10\n    def index\n      @reports = Report.where(owner: ::User.current)\n      Admin::Exporter.run(@reports, LIMIT)\n      Report.count\n    end\n  end\nend\n";
+        let mut parser = get_parser(Lang::Ruby).unwrap();
+        let tree = parser.parse(src.as_bytes(), None).unwrap();
+        assert_eq!(
+            extract_ruby_constants(&tree, src),
+            [
+                "ApplicationController",
+                "Pagination",
+                "Report",
+                "User",
+                "Admin::Exporter",
+                "LIMIT"
+            ]
+        );
+    }
+
     // ---------- Go reference extraction ----------
 
     #[test]
@@ -4351,6 +8163,143 @@ mod tests {
         assert!(has_ref(&refs, "os", ReferenceKind::Import));
     }
 
+    // ---------- Kotlin reference extraction ----------
+
+    #[test]
+    fn kotlin_call_type_and_import_references() {
+        let src = "import com.acme.Entry\n\nfun load(path: String): Entry {\n    helper(path)\n    return Entry.create()\n}\n";
+        let refs = refs_from(Lang::Kotlin, src);
+        assert!(has_ref(&refs, "helper", ReferenceKind::Call));
+        assert!(has_ref(&refs, "create", ReferenceKind::Call));
+        assert!(has_ref(&refs, "String", ReferenceKind::Type));
+        assert!(has_ref(&refs, "Entry", ReferenceKind::Type));
+        assert!(has_ref(
+            &refs,
+            "import com.acme.Entry",
+            ReferenceKind::Import
+        ));
+        assert_eq!(
+            find_ref(&refs, "helper").caller_name.as_deref(),
+            Some("load")
+        );
+    }
+
+    // ---------- Scala reference extraction ----------
+
+    #[test]
+    fn scala_call_type_and_import_references() {
+        let src = "import com.acme.Entry\n\ndef load(path: String): Entry = {\n  helper(path)\n  Entry.create()\n}\n";
+        let refs = refs_from(Lang::Scala, src);
+        assert!(has_ref(&refs, "helper", ReferenceKind::Call));
+        assert!(has_ref(&refs, "create", ReferenceKind::Call));
+        assert!(has_ref(&refs, "String", ReferenceKind::Type));
+        assert!(has_ref(&refs, "Entry", ReferenceKind::Type));
+        assert!(has_ref(
+            &refs,
+            "import com.acme.Entry",
+            ReferenceKind::Import
+        ));
+        assert_eq!(
+            find_ref(&refs, "helper").caller_name.as_deref(),
+            Some("load")
+        );
+    }
+
+    // ---------- Lua reference extraction ----------
+
+    #[test]
+    fn lua_call_and_require_references() {
+        let src = "local util = require(\"app.util\")\nlocal json = require 'cjson'\n\nfunction M:load(path)\n  local s = util.read(path)\n  self:parse(s)\n  M.handler = function() finish() end\nend\n";
+        let refs = refs_from(Lang::Lua, src);
+        assert!(has_ref(&refs, "app.util", ReferenceKind::Import));
+        assert!(has_ref(&refs, "cjson", ReferenceKind::Import));
+        assert!(has_ref(&refs, "read", ReferenceKind::Call));
+        assert_eq!(find_ref(&refs, "read").caller_name.as_deref(), Some("load"));
+        assert_eq!(
+            find_ref(&refs, "parse").caller_name.as_deref(),
+            Some("load")
+        );
+        assert_eq!(
+            find_ref(&refs, "finish").caller_name.as_deref(),
+            Some("handler")
+        );
+    }
+
+    // ---------- Elixir reference extraction ----------
+
+    #[test]
+    fn elixir_call_references_skip_definitions_and_specs() {
+        let src = "defmodule App do\n  @spec run(list) :: :ok\n  def run(items) when is_list(items) do\n    items |> Enum.map(&fmt/1) |> save()\n    if valid?(items), do: notify.(items)\n  end\nend\n";
+        let refs = refs_from(Lang::Elixir, src);
+        assert_eq!(find_ref(&refs, "map").caller_name.as_deref(), Some("run"));
+        assert_eq!(find_ref(&refs, "save").caller_name.as_deref(), Some("run"));
+        assert_eq!(
+            find_ref(&refs, "valid?").caller_name.as_deref(),
+            Some("run")
+        );
+        assert!(has_ref(&refs, "is_list", ReferenceKind::Call));
+        assert!(!has_ref(&refs, "run", ReferenceKind::Call));
+        assert!(!has_ref(&refs, "spec", ReferenceKind::Call));
+        assert!(!has_ref(&refs, "if", ReferenceKind::Call));
+        assert!(!has_ref(&refs, "defmodule", ReferenceKind::Call));
+    }
+
+    // ---------- Dart reference extraction ----------
+
+    #[test]
+    fn dart_references() {
+        let src = "class App extends StatelessWidget with Logging implements Comparable<App> {\n  Widget build(BuildContext context) {\n    log('build');\n    return Text(context.theme.title());\n  }\n}\n";
+        let refs = refs_from(Lang::Dart, src);
+        assert_eq!(find_ref(&refs, "log").caller_name.as_deref(), Some("build"));
+        assert!(has_ref(&refs, "Text", ReferenceKind::Call));
+        assert!(has_ref(&refs, "title", ReferenceKind::Call));
+        assert!(has_ref(&refs, "BuildContext", ReferenceKind::Type));
+        assert!(has_ref(&refs, "StatelessWidget", ReferenceKind::Type));
+    }
+
+    #[test]
+    fn shell_call_and_source_references() {
+        let src = "source ./lib/common.sh\n. \"$(dirname \"$0\")/env.sh\"\n\ndeploy() {\n  build dist\n  log_info done | tee out\n}\n";
+        let refs = refs_from(Lang::Shell, src);
+        assert!(has_ref(&refs, "./lib/common.sh", ReferenceKind::Import));
+        assert!(has_ref(
+            &refs,
+            "$(dirname \"$0\")/env.sh",
+            ReferenceKind::Import
+        ));
+        assert!(!has_ref(&refs, "source", ReferenceKind::Call));
+        assert!(!has_ref(&refs, ".", ReferenceKind::Call));
+        assert_eq!(
+            find_ref(&refs, "build").caller_name.as_deref(),
+            Some("deploy")
+        );
+        assert!(has_ref(&refs, "log_info", ReferenceKind::Call));
+        assert!(has_ref(&refs, "tee", ReferenceKind::Call));
+    }
+
+    #[test]
+    fn sql_table_and_call_references() {
+        let src = "CREATE TABLE orders (user_id INT REFERENCES users(id));\nCREATE FUNCTION n() RETURNS INT AS $$ SELECT count(*) FROM orders $$ LANGUAGE sql;\nUPDATE users SET name = 'x';\nDELETE FROM sessions;\nSELECT * FROM users u JOIN orders o ON o.user_id = u.id;\n";
+        let refs = refs_from(Lang::Sql, src);
+        let tables: Vec<(&str, usize)> = refs
+            .iter()
+            .filter(|r| r.kind == ReferenceKind::Type)
+            .map(|r| (r.name.as_str(), r.line))
+            .collect();
+        assert_eq!(
+            tables,
+            [
+                ("users", 1),
+                ("orders", 2),
+                ("users", 3),
+                ("sessions", 4),
+                ("users", 5),
+                ("orders", 5)
+            ]
+        );
+        assert_eq!(find_ref(&refs, "count").caller_name.as_deref(), Some("n"));
+    }
+
     // ---------- Java reference extraction ----------
 
     #[test]
@@ -4464,6 +8413,30 @@ mod tests {
         assert!(fi.imports.iter().any(|i| i.contains("Symbol")));
     }
 
+    #[test]
+    fn rust_use_trees_are_flattened() {
+        let src = "use crate::{types::{Symbol, Reference as Ref}, db};\nuse super::*;\nuse std::io::{self, Write};\n";
+        let fi = imports_from(Lang::Rust, src);
+        assert_eq!(
+            fi.imports,
+            [
+                "crate::types::Symbol",
+                "crate::types::Reference",
+                "crate::db",
+                "super::*",
+                "std::io",
+                "std::io::Write",
+            ]
+        );
+    }
+
+    #[test]
+    fn rust_mod_declarations_are_imports() {
+        let src = "mod db;\n#[cfg(unix)]\n#[path = \"sys/unix.rs\"]\nmod sys;\nmod inline { mod nested; }\n";
+        let fi = imports_from(Lang::Rust, src);
+        assert_eq!(fi.imports, ["mod db", "#[path = \"sys/unix.rs\"] mod sys"]);
+    }
+
     #[test]
     fn rust_exports() {
         let src = "pub fn hello() {}\npub struct Foo {}\nfn private() {}";
@@ -4484,6 +8457,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn python_from_imports_name_each_import() {
+        let src = "from a.b import c, d as e\nfrom . import sibling\nfrom ..pkg import mod\nfrom x import *\n";
+        let fi = imports_from(Lang::Python, src);
+        assert_eq!(fi.imports, ["a.b.c", "a.b.d", ".sibling", "..pkg.mod", "x"]);
+    }
+
     #[test]
     fn python_imports() {
         let src = "import os\nfrom pathlib import Path\ndef main(): pass\n";
@@ -4555,6 +8535,87 @@ mod tests {
         assert!(!fi.exports.contains(&"private".to_string()));
     }
 
+    #[test]
+    fn kotlin_imports() {
+        let src = "package app\n\nimport com.acme.util.checksum\nimport com.acme.io.*\nimport java.io.File as JFile\n";
+        let fi = imports_from(Lang::Kotlin, src);
+        assert_eq!(
+            fi.imports,
+            ["com.acme.util.checksum", "com.acme.io.*", "java.io.File"]
+        );
+    }
+
+    #[test]
+    fn scala_imports() {
+        let src = "package app\n\nimport com.acme.util.checksum\nimport com.acme.io._\nimport com.acme.model.{Entry, Shape => S, Hidden => _}\nimport java.io.File as JFile\n";
+        let fi = imports_from(Lang::Scala, src);
+        assert_eq!(
+            fi.imports,
+            [
+                "com.acme.util.checksum",
+                "com.acme.io.*",
+                "com.acme.model.Entry",
+                "com.acme.model.Shape",
+                "java.io.File",
+            ]
+        );
+    }
+
+    #[test]
+    fn lua_imports() {
+        let src = "local util = require(\"app.util\")\nlocal json = require 'cjson'\n\nlocal function lazy()\n  return require(\"app.heavy\")\nend\nlocal name = \"x\"\nrequire(name)\n";
+        let fi = imports_from(Lang::Lua, src);
+        assert_eq!(fi.imports, ["app.util", "cjson", "app.heavy"]);
+    }
+
+    #[test]
+    fn elixir_imports() {
+        let src = "defmodule App do\n  alias App.{User, Repo}\n  alias App.Accounts, as: Acc\n  import Ecto.Query, only: [from: 2]\n  use GenServer\n  require Logger\n  alias __MODULE__.Inner\nend\n";
+        let fi = imports_from(Lang::Elixir, src);
+        assert_eq!(
+            fi.imports,
+            [
+                "App.User",
+                "App.Repo",
+                "App.Accounts",
+                "Ecto.Query",
+                "GenServer",
+                "Logger"
+            ]
+        );
+    }
+
+    #[test]
+    fn dart_imports() {
+        let src = "library app;\n\nimport 'package:flutter/material.dart';\nimport 'src/util.dart' as util show helper;\nimport 'stub.dart' if (dart.library.io) 'io.dart';\nexport 'src/area.dart';\npart 'app.g.dart';\n";
+        let fi = imports_from(Lang::Dart, src);
+        assert_eq!(
+            fi.imports,
+            [
+                "package:flutter/material.dart",
+                "src/util.dart",
+                "stub.dart",
+                "src/area.dart",
+                "app.g.dart"
+            ]
+        );
+    }
+
+    #[test]
+    fn shell_imports() {
+        let src = "source ./lib/common.sh\n. 'env.sh'\nsource \"${ROOT}/x.sh\"\nsetup() {\n  source \"$HOME/.profile\"\n}\nsource\n";
+        let fi = imports_from(Lang::Shell, src);
+        assert_eq!(
+            fi.imports,
+            [
+                "./lib/common.sh",
+                "env.sh",
+                "${ROOT}/x.sh",
+                "$HOME/.profile"
+            ]
+        );
+    }
+
     #[test]
     fn java_imports() {
         let src = "import java.util.List;\nimport java.io.File;\nclass App {}";
@@ -4562,6 +8623,16 @@ mod tests {
         assert!(fi.imports.len() >= 2, "imports: {:?}", fi.imports);
     }
 
+    #[test]
+    fn java_wildcard_and_static_imports() {
+        let src = "import com.acme.*;\nimport static com.acme.Util.helper;\nimport static com.acme.Util.*;\nclass App {}";
+        let fi = imports_from(Lang::Java, src);
+        assert_eq!(
+            fi.imports,
+            ["com.acme.*", "com.acme.Util.helper", "com.acme.Util.*"]
+        );
+    }
+
     #[test]
     fn c_includes() {
         let src = "#include <stdio.h>\n#include \"myheader.h\"\nint main() { return 0; }";
@@ -4729,6 +8800,31 @@ mod tests {
         assert_eq!(p.scope.as_deref(), Some("User"));
     }
 
+    #[test]
+    fn csharp_record_positional_properties() {
+        let src = "public record Person(string Name, int Age);
+class Util {
+    void Run(int count) {}
+}";
+        let syms = extract_from(Lang::CSharp, src);
+        assert_eq!(find_sym(&syms, "Person").kind, SymbolKind::Class);
+        let name = find_sym(&syms, "Name");
+        assert_eq!(name.kind, SymbolKind::Method);
+        assert_eq!(name.scope.as_deref(), Some("Person"));
+        assert_eq!(find_sym(&syms, "Age").scope.as_deref(), Some("Person"));
+        // Ordinary method parameters are not symbols.
+        assert!(!syms.iter().any(|s| s.name == "count"));
+    }
+
+    #[test]
+    fn csharp_unsafe_statements_report_enclosing_method() {
+        let src = "class Buf {\n    void Copy() {\n        unsafe {\n            Poke();\n        }\n    }\n}";
+        let mut parser = get_parser(Lang::CSharp).unwrap();
+        let tree = parser.parse(src.as_bytes(), None).unwrap();
+        let blocks = extract_unsafe_blocks(&tree, src, Lang::CSharp);
+        assert_eq!(blocks, vec![(3, Some("Copy".to_string()))]);
+    }
+
     // ---------- C# reference extraction ----------
 
     #[test]
@@ -4759,6 +8855,14 @@ mod tests {
         assert!(fi.imports.iter().any(|i| i == "System.Linq"));
     }
 
+    #[test]
+    fn csharp_using_alias_imports_target_only() {
+        let src = "using Json = System.Text.Json;
+class Foo {}";
+        let fi = imports_from(Lang::CSharp, src);
+        assert_eq!(fi.imports, ["System.Text.Json"]);
+    }
+
     // ======================================================================
     // Enclosing function detection tests
     // ======================================================================
@@ -4924,7 +9028,7 @@ mod tests {
     #[ignore]
     fn debug_rust_trait_tree() {
         let src = "trait MyTrait { fn do_thing(&self); }";
-        let mut parser = get_parser(Lang::Rust);
+        let mut parser = get_parser(Lang::Rust).unwrap();
         let tree = parser.parse(src.as_bytes(), None).unwrap();
         dump_tree(tree.root_node(), src, 0);
     }
@@ -4933,7 +9037,7 @@ mod tests {
     #[ignore]
     fn debug_cpp_class_tree() {
         let src = "class Dog {\npublic:\n    void bark() { }\n};";
-        let mut parser = get_parser(Lang::Cpp);
+        let mut parser = get_parser(Lang::Cpp).unwrap();
         let tree = parser.parse(src.as_bytes(), None).unwrap();
         dump_tree(tree.root_node(), src, 0);
     }
@@ -4942,7 +9046,7 @@ mod tests {
     #[ignore]
     fn debug_cpp_namespace_tree() {
         let src = "namespace mylib {\n    void helper() {}\n}";
-        let mut parser = get_parser(Lang::Cpp);
+        let mut parser = get_parser(Lang::Cpp).unwrap();
         let tree = parser.parse(src.as_bytes(), None).unwrap();
         dump_tree(tree.root_node(), src, 0);
     }
@@ -4957,6 +9061,9 @@ mod tests {
             file: "a.rs".into(),
             line: 1,
             col: 0,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             context: "use std::collections::HashMap;".into(),
             caller_name: None,
             confidence: 0.5,
@@ -4978,6 +9085,9 @@ mod tests {
             file: "a.rs".into(),
             line: 10,
             col: 4,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             context: "let m = HashMap::new();".into(),
             caller_name: Some("main".into()),
             confidence: 0.5,
@@ -4999,6 +9109,9 @@ mod tests {
             file: "a.rs".into(),
             line: 10,
             col: 4,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             context: "helper();".into(),
             caller_name: Some("main".into()),
             confidence: 0.5,
@@ -5010,10 +9123,17 @@ mod tests {
             line: 1,
             col: 0,
             end_line: Some(5),
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             scope: None,
             signature: "fn helper()".into(),
             language: "Rust".into(),
             doc_comment: None,
+            condition: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
         }];
         let imports: Vec<String> = vec![];
         let score = compute_confidence(&r, &symbols, &imports);
@@ -5033,6 +9153,9 @@ mod tests {
             file: "a.rs".into(),
             line: 20,
             col: 8,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             context: "self.do_work();".into(),
             caller_name: Some("run".into()),
             confidence: 0.5,
@@ -5046,10 +9169,17 @@ mod tests {
                 line: 15,
                 col: 4,
                 end_line: Some(25),
+                end_col: None,
+                start_byte: None,
+                end_byte: None,
                 scope: Some("MyClass".into()),
                 signature: "fn run(&self)".into(),
                 language: "Rust".into(),
                 doc_comment: None,
+                condition: None,
+                visibility: None,
+                modifiers: Vec::new(),
+                generics: Vec::new(),
             },
             Symbol {
                 name: "do_work".into(),
@@ -5058,10 +9188,17 @@ mod tests {
                 line: 30,
                 col: 4,
                 end_line: Some(35),
+                end_col: None,
+                start_byte: None,
+                end_byte: None,
                 scope: Some("MyClass".into()),
                 signature: "fn do_work(&self)".into(),
                 language: "Rust".into(),
                 doc_comment: None,
+                condition: None,
+                visibility: None,
+                modifiers: Vec::new(),
+                generics: Vec::new(),
             },
         ];
         let imports: Vec<String> = vec![];
@@ -5082,6 +9219,9 @@ mod tests {
             file: "a.rs".into(),
             line: 10,
             col: 4,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             context: "external_func();".into(),
             caller_name: Some("main".into()),
             confidence: 0.5,
@@ -5094,10 +9234,17 @@ mod tests {
             line: 1,
             col: 0,
             end_line: Some(5),
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             scope: None,
             signature: "fn unrelated()".into(),
             language: "Rust".into(),
             doc_comment: None,
+            condition: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
         }];
         let imports: Vec<String> = vec![];
         let score = compute_confidence(&r, &symbols, &imports);
@@ -5117,6 +9264,9 @@ mod tests {
             file: "a.rs".into(),
             line: 20,
             col: 8,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             context: "self.helper();".into(),
             caller_name: Some("run".into()),
             confidence: 0.5,
@@ -5129,10 +9279,17 @@ mod tests {
                 line: 15,
                 col: 4,
                 end_line: Some(25),
+                end_col: None,
+                start_byte: None,
+                end_byte: None,
                 scope: Some("MyClass".into()),
                 signature: "fn run(&self)".into(),
                 language: "Rust".into(),
                 doc_comment: None,
+                condition: None,
+                visibility: None,
+                modifiers: Vec::new(),
+                generics: Vec::new(),
             },
             // helper is in scope "MyClass" but different file
             Symbol {
@@ -5142,10 +9299,17 @@ mod tests {
                 line: 5,
                 col: 4,
                 end_line: Some(10),
+                end_col: None,
+                start_byte: None,
+                end_byte: None,
                 scope: Some("MyClass".into()),
                 signature: "fn helper(&self)".into(),
                 language: "Rust".into(),
                 doc_comment: None,
+                condition: None,
+                visibility: None,
+                modifiers: Vec::new(),
+                generics: Vec::new(),
             },
         ];
         let imports: Vec<String> = vec![];
@@ -5160,7 +9324,7 @@ mod tests {
 
     /// Parse source code for a given language and extract type edges.
     fn edges_from(lang: Lang, source: &str) -> Vec<crate::types::RawTypeEdge> {
-        let mut parser = get_parser(lang);
+        let mut parser = get_parser(lang).unwrap();
         let tree = parser.parse(source.as_bytes(), None).unwrap();
         extract_type_edges(&tree, source, "test_file", lang)
     }
@@ -5289,6 +9453,90 @@ mod tests {
         assert_eq!(edges[0].relationship, "implements");
     }
 
+    // ---------- Kotlin type edges ----------
+
+    #[test]
+    fn kotlin_type_edges() {
+        let src = "interface Named : Base\nclass Dog(name: String) : Animal(name), Named, Comparable<Dog> {}\nobject Registry : Listener\n";
+        let edges = edges_from(Lang::Kotlin, src);
+        let found: Vec<(&str, &str, &str)> = edges
+            .iter()
+            .map(|e| {
+                (
+                    e.child_name.as_str(),
+                    e.parent_name.as_str(),
+                    e.relationship.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("Named", "Base", "extends"),
+                ("Dog", "Animal", "extends"),
+                ("Dog", "Named", "implements"),
+                ("Dog", "Comparable", "implements"),
+                ("Registry", "Listener", "implements"),
+            ]
+        );
+    }
+
+    // ---------- Scala type edges ----------
+
+    #[test]
+    fn scala_type_edges() {
+        let src = "trait Named extends Base with Serializable\nclass Dog(name: String) extends Animal(name) with Named with Ordered[Dog]\nobject Registry extends Listener\n";
+        let edges = edges_from(Lang::Scala, src);
+        let found: Vec<(&str, &str, &str)> = edges
+            .iter()
+            .map(|e| {
+                (
+                    e.child_name.as_str(),
+                    e.parent_name.as_str(),
+                    e.relationship.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("Named", "Base", "extends"),
+                ("Named", "Serializable", "extends"),
+                ("Dog", "Animal", "extends"),
+                ("Dog", "Named", "implements"),
+                ("Dog", "Ordered", "implements"),
+                ("Registry", "Listener", "extends"),
+            ]
+        );
+    }
+
+    // ---------- Dart type edges ----------
+
+    #[test]
+    fn dart_type_edges() {
+        let src = "class App extends StatelessWidget with Logging, Themed implements Comparable<App> {}\nmixin Logging {}\n";
+        let edges = edges_from(Lang::Dart, src);
+        let found: Vec<(&str, &str, &str)> = edges
+            .iter()
+            .map(|e| {
+                (
+                    e.child_name.as_str(),
+                    e.parent_name.as_str(),
+                    e.relationship.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("App", "StatelessWidget", "extends"),
+                ("App", "Logging", "implements"),
+                ("App", "Themed", "implements"),
+                ("App", "Comparable", "implements"),
+            ]
+        );
+    }
+
     // ---------- C# type edges ----------
 
     #[test]
@@ -5321,6 +9569,29 @@ mod tests {
         assert_eq!(implements[0].parent_name, "IRunnable");
     }
 
+    #[test]
+    fn csharp_type_edges_record_base() {
+        let src = "record Person(string Name) : Base(Name), IThing;";
+        let edges = edges_from(Lang::CSharp, src);
+        let found: Vec<(&str, &str, &str)> = edges
+            .iter()
+            .map(|e| {
+                (
+                    e.child_name.as_str(),
+                    e.parent_name.as_str(),
+                    e.relationship.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("Person", "Base", "extends"),
+                ("Person", "IThing", "implements"),
+            ]
+        );
+    }
+
     // ---------- C++ type edges ----------
 
     #[test]
@@ -5413,12 +9684,21 @@ mod tests {
         assert_eq!(lines.len(), 6, "line count should be preserved");
         assert_eq!(lines[0], "line1");
         assert_eq!(lines[1], "line2");
-        assert_eq!(lines[2], "", "macro line should be blanked");
+        assert_eq!(lines[2], "         ", "macro line should be blanked");
         assert!(lines[3].contains("pub fn spawn"), "inner content on line 4");
-        assert_eq!(lines[4], "", "closing brace should be blanked");
+        assert_eq!(lines[4], " ", "closing brace should be blanked");
         assert_eq!(lines[5], "line6");
     }
 
+    #[test]
+    fn preprocess_preserves_byte_offsets() {
+        let src = "cfg_rt! {\r\n    pub fn spawn() {}\r\n}\r\nfn après() {}";
+        let processed = preprocess_rust_macros(src);
+        assert_eq!(processed.len(), src.len());
+        assert_eq!(processed.find("spawn"), src.find("spawn"));
+        assert!(processed.ends_with(" \r\nfn après() {}"));
+    }
+
     #[test]
     fn preprocess_handles_nested_braces() {
         let src = "cfg_rt! {\n    fn foo() {\n        if true { bar(); }\n    }\n}\n";
@@ -5448,7 +9728,7 @@ mod tests {
     fn preprocess_expands_symbols_inside_cfg_macros() {
         let src = "cfg_rt! {\n    pub fn spawn<F>(future: F) -> JoinHandle<F::Output>\n    where\n        F: Future + Send + 'static,\n    {\n        spawn_inner(future)\n    }\n}\n";
         let processed = preprocess_rust_macros(src);
-        let mut parser = get_parser(Lang::Rust);
+        let mut parser = get_parser(Lang::Rust).unwrap();
         let tree = parser.parse(processed.as_bytes(), None).unwrap();
         let symbols = extract_symbols(&tree, &processed, "test.rs", Lang::Rust);
         assert!(
@@ -1,29 +1,62 @@
+pub mod access;
+pub mod archive;
 pub mod blast;
 pub mod budget;
 pub mod callgraph;
+pub mod caveats;
 pub mod cli;
 pub mod cluster;
 pub mod color;
+pub mod columns;
 pub mod config;
 pub mod context;
+pub mod crash;
 pub mod daemon;
 pub mod db;
+pub mod deadline;
+pub mod describe;
+pub mod diskspace;
 pub mod embedding;
 pub mod errors;
+pub mod examples;
+pub mod find;
 pub mod flows;
+pub mod gate;
+pub mod gendocs;
+pub mod generated;
+pub mod github;
+pub mod global;
+pub mod glyphs;
+pub mod golden;
 pub mod impact;
+pub mod imports;
 pub mod indexer;
 pub mod llm;
+pub mod markdown;
 pub mod mcp;
+pub mod near;
 pub mod output;
+pub mod pager;
 pub mod pipeline;
 pub mod progress;
+pub mod provenance;
 pub mod ranker;
+pub mod report;
+pub mod resolve;
 pub mod router;
+pub mod schema;
 pub mod search;
+#[cfg(feature = "self-update")]
+pub mod self_update;
 pub mod semantic;
 pub mod show;
+pub mod snapshot;
+pub mod stamp;
+pub mod storage;
 pub mod summary;
+pub mod treedump;
 pub mod types;
+pub mod vcs;
+pub mod verify;
 pub mod walker;
 pub mod watcher;
@@ -1,12 +1,22 @@
 use std::process;
 
 fn main() {
+    wonk::crash::install_panic_hook();
+
     // Parse CLI first so we know the output format.
     // clap handles its own usage errors (exit code 2) before we get here.
     let cli = wonk::cli::parse();
     let suppress = cli.format.is_some_and(|f| f.is_structured());
+    let strict = cli.strict;
+    if let Some(format) = cli.format {
+        wonk::output::set_structured_messages(format, cli.quiet);
+    }
 
     match wonk::router::dispatch(cli) {
+        Ok(()) if wonk::deadline::interrupted() => process::exit(wonk::errors::EXIT_INTERRUPTED),
+        Ok(()) if strict && !wonk::caveats::recorded().is_empty() => {
+            process::exit(wonk::errors::EXIT_INCOMPLETE)
+        }
         Ok(()) => process::exit(wonk::errors::EXIT_SUCCESS),
         Err(err) => {
             let wonk_err: wonk::errors::WonkError = err.into();
@@ -0,0 +1,263 @@
+//! Markdown headings as symbols.
+//!
+//! Markdown has no bundled Tree-sitter grammar, so headings are read line by
+//! line: ATX headings (`## Install`) and setext headings (a line underlined
+//! with `===` or `---`), skipping fenced code blocks and YAML front matter.
+//! Each heading becomes a [`SymbolKind::Module`] that spans its section, up
+//! to the next heading of the same or a higher level, with the enclosing
+//! heading as its scope, so a document's symbols form its outline.
+
+use std::path::Path;
+
+use crate::types::{Symbol, SymbolKind};
+
+/// Language name stored for Markdown files.
+pub const LANGUAGE: &str = "Markdown";
+
+/// Returns `true` for files indexed as Markdown (`.md`, `.markdown`).
+pub fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| matches!(e.to_ascii_lowercase().as_str(), "md" | "markdown"))
+}
+
+/// A heading found in the source, before its section end is known.
+struct Heading {
+    level: usize,
+    text: String,
+    /// 0-based index of the heading's first line.
+    line: usize,
+    /// The heading line as written.
+    signature: String,
+}
+
+/// Extract one symbol per heading in `source`, stored under `file`.
+pub fn extract_headings(source: &str, file: &str) -> Vec<Symbol> {
+    let lines: Vec<&str> = source.lines().collect();
+    let headings = find_headings(&lines);
+
+    // Byte offset of the start of each line, plus the end of the source.
+    let mut offsets = Vec::with_capacity(lines.len() + 1);
+    let mut offset = 0;
+    for line in source.split_inclusive('\n') {
+        offsets.push(offset);
+        offset += line.len();
+    }
+    offsets.push(source.len());
+
+    let mut symbols = Vec::with_capacity(headings.len());
+    // Open headings enclosing the current one, as (level, name).
+    let mut open: Vec<(usize, String)> = Vec::new();
+    for (i, heading) in headings.iter().enumerate() {
+        while open
+            .last()
+            .is_some_and(|(level, _)| *level >= heading.level)
+        {
+            open.pop();
+        }
+        let end = headings[i + 1..]
+            .iter()
+            .find(|h| h.level <= heading.level)
+            .map_or(lines.len(), |h| h.line);
+        // The section ends on its last non-blank line.
+        let last = (heading.line..end)
+            .rev()
+            .find(|&l| !lines[l].trim().is_empty())
+            .unwrap_or(heading.line);
+        symbols.push(Symbol {
+            name: heading.text.clone(),
+            kind: SymbolKind::Module,
+            file: file.to_string(),
+            line: heading.line + 1,
+            col: 0,
+            end_line: Some(last + 1),
+            end_col: Some(lines[last].len()),
+            start_byte: Some(offsets[heading.line]),
+            end_byte: Some(offsets[last] + lines[last].len()),
+            scope: open.last().map(|(_, name)| name.clone()),
+            signature: heading.signature.clone(),
+            language: LANGUAGE.to_string(),
+            doc_comment: None,
+            condition: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
+        });
+        open.push((heading.level, heading.text.clone()));
+    }
+    symbols
+}
+
+/// Headings in `lines`, in order.
+fn find_headings(lines: &[&str]) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    let mut fence: Option<(char, usize)> = None;
+    let mut i = front_matter_end(lines);
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        if let Some((ch, len)) = fence {
+            if indent < 4 && fence_marker(trimmed).is_some_and(|(c, l)| c == ch && l >= len) {
+                fence = None;
+            }
+            i += 1;
+            continue;
+        }
+        if indent < 4 {
+            if let Some(marker) = fence_marker(trimmed) {
+                fence = Some(marker);
+            } else if let Some((level, text)) = atx_heading(trimmed) {
+                headings.push(Heading {
+                    level,
+                    text,
+                    line: i,
+                    signature: line.trim_end().to_string(),
+                });
+            } else if let Some(level) = lines.get(i + 1).and_then(|next| setext_level(next))
+                && is_paragraph_line(trimmed)
+            {
+                headings.push(Heading {
+                    level,
+                    text: trimmed.trim_end().to_string(),
+                    line: i,
+                    signature: line.trim_end().to_string(),
+                });
+                i += 2;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    headings
+}
+
+/// Index of the first line after a YAML front matter block (`---` ... `---`
+/// at the top of the file), or 0 without one.
+fn front_matter_end(lines: &[&str]) -> usize {
+    if lines.first().map(|l| l.trim_end()) != Some("---") {
+        return 0;
+    }
+    lines
+        .iter()
+        .skip(1)
+        .position(|l| matches!(l.trim_end(), "---" | "..."))
+        .map_or(0, |p| p + 2)
+}
+
+/// The fence character and length when `trimmed` opens or closes a fenced
+/// code block (three or more backticks or tildes).
+fn fence_marker(trimmed: &str) -> Option<(char, usize)> {
+    let ch = trimmed.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let len = trimmed.chars().take_while(|&c| c == ch).count();
+    (len >= 3).then_some((ch, len))
+}
+
+/// Level and text of an ATX heading (`# Title`, `### Title ###`).
+fn atx_heading(trimmed: &str) -> Option<(usize, String)> {
+    let level = trimmed.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    // A closing run of `#` is dropped when separated from the text by a space.
+    let mut text = rest.trim();
+    let without_closing = text.trim_end_matches('#');
+    if without_closing.is_empty() || without_closing.ends_with([' ', '\t']) {
+        text = without_closing.trim_end();
+    }
+    (!text.is_empty()).then(|| (level, text.to_string()))
+}
+
+/// Level of a setext underline: 1 for `===`, 2 for `---`.
+fn setext_level(line: &str) -> Option<usize> {
+    let trimmed = line.trim();
+    if line.len() - line.trim_start().len() >= 4 || trimmed.is_empty() {
+        return None;
+    }
+    if trimmed.chars().all(|c| c == '=') {
+        Some(1)
+    } else if trimmed.chars().all(|c| c == '-') {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+/// Whether `trimmed` is plain paragraph text that a setext underline turns
+/// into a heading, rather than a list item, quote, table row, or HTML.
+fn is_paragraph_line(trimmed: &str) -> bool {
+    if trimmed.is_empty() || trimmed.starts_with(['>', '|', '<']) {
+        return false;
+    }
+    let list_marker = trimmed
+        .strip_prefix(['-', '*', '+'])
+        .or_else(|| {
+            let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+            (digits > 0)
+                .then(|| trimmed[digits..].strip_prefix(['.', ')']))
+                .flatten()
+        })
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t']));
+    !list_marker && setext_level(trimmed).is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outline(source: &str) -> Vec<(String, usize, Option<usize>, Option<String>)> {
+        extract_headings(source, "README.md")
+            .into_iter()
+            .map(|s| (s.name, s.line, s.end_line, s.scope))
+            .collect()
+    }
+
+    #[test]
+    fn detects_markdown_files() {
+        assert!(is_markdown(Path::new("docs/architecture.md")));
+        assert!(is_markdown(Path::new("NOTES.Markdown")));
+        assert!(!is_markdown(Path::new("src/main.rs")));
+        assert!(!is_markdown(Path::new("md")));
+    }
+
+    #[test]
+    fn headings_nest_by_level() {
+        let source = "# Wonk\n\nIntro.\n\n## Install\n\nRun it.\n\n### From source ###\n\nBuild.\n\n## Usage\n\nQuery.\n";
+        assert_eq!(
+            outline(source),
+            vec![
+                ("Wonk".to_string(), 1, Some(15), None),
+                ("Install".to_string(), 5, Some(11), Some("Wonk".to_string())),
+                (
+                    "From source".to_string(),
+                    9,
+                    Some(11),
+                    Some("Install".to_string())
+                ),
+                ("Usage".to_string(), 13, Some(15), Some("Wonk".to_string())),
+            ]
+        );
+        let symbols = extract_headings(source, "README.md");
+        assert_eq!(symbols[1].kind, SymbolKind::Module);
+        assert_eq!(symbols[1].signature, "## Install");
+        assert_eq!(symbols[1].language, "Markdown");
+        assert_eq!(
+            &source[symbols[1].start_byte.unwrap()..symbols[1].end_byte.unwrap()],
+            "## Install\n\nRun it.\n\n### From source ###\n\nBuild."
+        );
+    }
+
+    #[test]
+    fn setext_headings_and_skipped_blocks() {
+        let source = "---\ntitle: Notes\n---\n\nNotes\n=====\n\n```sh\n# not a heading\n```\n\nDetails\n-------\n\n- item\n---\n\n#hashtag\n";
+        let names: Vec<String> = outline(source).into_iter().map(|(n, ..)| n).collect();
+        assert_eq!(names, vec!["Notes", "Details"]);
+        let symbols = extract_headings(source, "README.md");
+        assert_eq!(symbols[1].scope.as_deref(), Some("Notes"));
+        assert_eq!(symbols[1].signature, "Details");
+    }
+}
@@ -18,9 +18,10 @@ use serde_json::Value;
 
 use crate::budget::TokenBudget;
 use crate::db;
+use crate::global::{RepoEntry, discover_repos};
 use crate::output::{
-    CallPathHopOutput, CalleeOutput, CallerOutput, DepOutput, OutputFormat, RefOutput,
-    SearchOutput, ShowOutput, SignatureOutput, SummaryOutput, SymbolOutput,
+    CallPathHopOutput, CalleeOutput, CallerOutput, DefinitionOutput, DepOutput, OutputFormat,
+    RefOutput, SearchOutput, ShowOutput, SignatureOutput, SummaryOutput, SymbolOutput,
 };
 use crate::pipeline;
 use crate::progress::Progress;
@@ -195,7 +196,7 @@ fn require_str(args: &Value, key: &str) -> Result<String, CallToolResult> {
 /// Serialize any `Serialize` value into a `CallToolResult` using the given format.
 fn format_result<T: Serialize>(data: &T, format: OutputFormat) -> CallToolResult {
     let text: Result<String, String> = match format {
-        OutputFormat::Json | OutputFormat::Grep => {
+        OutputFormat::Json | OutputFormat::Grep | OutputFormat::Github => {
             serde_json::to_string_pretty(data).map_err(|e| e.to_string())
         }
         OutputFormat::Toon => serde_toon2::to_string(data).map_err(|e| e.to_string()),
@@ -271,17 +272,7 @@ fn empty_show_hints(
 
 /// Convert a `Symbol` to the serializable `SymbolOutput`.
 fn symbol_to_output(sym: &Symbol) -> SymbolOutput {
-    SymbolOutput {
-        name: sym.name.clone(),
-        kind: sym.kind.to_string(),
-        file: sym.file.clone(),
-        line: sym.line,
-        col: sym.col,
-        end_line: sym.end_line,
-        scope: sym.scope.clone(),
-        signature: sym.signature.clone(),
-        language: sym.language.clone(),
-    }
+    SymbolOutput::from(sym)
 }
 
 /// Enrich a reference context line with ±1 surrounding lines from the source file.
@@ -1130,21 +1121,6 @@ fn tool_definitions() -> &'static Vec<Tool> {
 // Multi-repo registry
 // ---------------------------------------------------------------------------
 
-/// Metadata for a discovered indexed repository.
-#[derive(Debug, Clone)]
-struct RepoEntry {
-    /// Absolute path to the repository root.
-    repo_path: PathBuf,
-    /// Absolute path to the index.db file.
-    index_path: PathBuf,
-    /// Short name (last component of repo_path).
-    name: String,
-    /// Languages detected during indexing.
-    languages: Vec<String>,
-    /// Unix timestamp when the index was created.
-    created: u64,
-}
-
 /// Registry of all discovered indexed repositories.
 struct RepoRegistry {
     entries: Vec<RepoEntry>,
@@ -1220,49 +1196,6 @@ impl RepoRegistry {
     }
 }
 
-/// Discover all indexed repositories under a repos directory.
-///
-/// Scans `repos_dir/*/index.db`, reads the adjacent `meta.json` for metadata,
-/// and validates that the claimed repo path contains a `.git` or `.wonk` marker.
-fn discover_repos(repos_dir: &Path) -> Vec<RepoEntry> {
-    let mut entries = Vec::new();
-
-    if repos_dir.is_dir()
-        && let Ok(read_dir) = std::fs::read_dir(repos_dir)
-    {
-        for dir_entry in read_dir.flatten() {
-            let index_dir = dir_entry.path();
-            if !index_dir.is_dir() {
-                continue;
-            }
-            let index_path = index_dir.join("index.db");
-            if !index_path.exists() {
-                continue;
-            }
-            if let Ok(meta) = db::read_meta(&index_path) {
-                let repo_path = PathBuf::from(&meta.repo_path);
-                // Validate the claimed repo path has a git or wonk marker.
-                if !repo_path.join(".git").exists() && !repo_path.join(".wonk").exists() {
-                    continue;
-                }
-                let name = repo_path
-                    .file_name()
-                    .map(|n| n.to_string_lossy().into_owned())
-                    .unwrap_or_else(|| "unknown".to_string());
-                entries.push(RepoEntry {
-                    repo_path,
-                    index_path,
-                    name,
-                    languages: meta.languages,
-                    created: meta.created,
-                });
-            }
-        }
-    }
-
-    entries
-}
-
 // ---------------------------------------------------------------------------
 // MCP Server
 // ---------------------------------------------------------------------------
@@ -1481,13 +1414,9 @@ impl McpServer {
 
         for (_category, items) in &groups {
             for item in items {
-                let mut out = SearchOutput::from_search_result(
-                    &item.result.file,
-                    item.result.line,
-                    item.result.col,
-                    &item.result.content,
-                );
+                let mut out = SearchOutput::from_result(&item.result);
                 out.annotation = item.annotation.clone();
+                out.category = Some(item.category.to_string());
 
                 if let Some(ref mut b) = budget {
                     let estimate = (out.file.len() + out.content.len() + 20) / 4;
@@ -1669,9 +1598,16 @@ impl McpServer {
                 file: sym.file.clone(),
                 line: sym.line,
                 col: sym.col,
+                end_col: None,
+                start_byte: sym.start_byte,
+                end_byte: sym.end_byte,
                 context: sym.signature.clone(),
                 caller_name: None,
                 confidence: 1.0,
+                freshness: None,
+                definition: None,
+                generated_by: None,
+                stamp: None,
             });
         }
 
@@ -1684,9 +1620,16 @@ impl McpServer {
                 file: r.file.clone(),
                 line: r.line,
                 col: r.col,
+                end_col: r.end_col,
+                start_byte: r.start_byte,
+                end_byte: r.end_byte,
                 context,
                 caller_name: r.caller_name.clone(),
                 confidence: r.confidence,
+                freshness: None,
+                definition: None,
+                generated_by: None,
+                stamp: None,
             });
         }
 
@@ -1717,6 +1660,8 @@ impl McpServer {
                 line: sym.line,
                 signature: sym.signature.clone(),
                 language: sym.language.clone(),
+                freshness: None,
+                stamp: None,
             })
             .collect();
 
@@ -1747,6 +1692,7 @@ impl McpServer {
             .map(|dep| DepOutput {
                 file: file.clone(),
                 depends_on: dep.clone(),
+                generated_by: None,
             })
             .collect();
 
@@ -1767,7 +1713,7 @@ impl McpServer {
         if validate_path(Path::new(&file), &repo_root).is_err() {
             return CallToolResult::error("path is outside the repository".into());
         }
-        let results = match crate::router::query_rdeps_db(conn, &file) {
+        let results = match crate::router::query_rdeps_db(conn, &file, false) {
             Ok(r) => r,
             Err(e) => {
                 return CallToolResult::error(format!("reverse dependency query failed: {e}"));
@@ -1779,6 +1725,7 @@ impl McpServer {
             .map(|source| DepOutput {
                 file: source.clone(),
                 depends_on: file.clone(),
+                generated_by: None,
             })
             .collect();
 
@@ -2094,8 +2041,8 @@ impl McpServer {
                     let source_lines: Vec<&str> = sr.source.lines().collect();
                     if !source_lines.is_empty() {
                         let avg_chars = sr.source.len() / source_lines.len();
-                        if avg_chars > 0 {
-                            let max_lines = (remaining_chars / avg_chars)
+                        if let Some(lines_fit) = remaining_chars.checked_div(avg_chars) {
+                            let max_lines = lines_fit
                                 .clamp(ShowOutput::MIN_SOURCE_LINES, ShowOutput::MAX_SOURCE_LINES);
                             let fresh_out = ShowOutput::from(sr);
                             if let Some(t) = fresh_out.truncated(max_lines) {
@@ -2306,16 +2253,16 @@ impl McpServer {
             results.retain(|r| !crate::ranker::is_test_file(std::path::Path::new(&r.file)));
         }
 
+        let definitions = match crate::callgraph::resolve_callees(conn, &results) {
+            Ok(d) => d,
+            Err(e) => return CallToolResult::error(format!("callees query failed: {e}")),
+        };
         let outputs: Vec<CalleeOutput> = results
             .iter()
-            .map(|cr| CalleeOutput {
-                callee_name: cr.callee_name.clone(),
-                file: cr.file.clone(),
-                line: cr.line,
-                context: cr.context.clone(),
-                depth: cr.depth,
-                source_file: cr.source_file.clone(),
-                confidence: cr.confidence,
+            .zip(&definitions)
+            .map(|(cr, def)| CalleeOutput {
+                definition: def.as_ref().map(DefinitionOutput::from),
+                ..CalleeOutput::from(cr)
             })
             .collect();
 
@@ -2817,12 +2764,17 @@ impl McpServer {
             let mut outputs: Vec<SearchOutput> = Vec::new();
             let mut truncated = 0usize;
             for item in &fused {
-                let mut out = SearchOutput::from_search_result(
-                    Path::new(&item.file),
-                    item.line,
-                    item.col,
-                    &item.content,
-                );
+                let mut out = SearchOutput {
+                    end_col: item.end_col,
+                    start_byte: item.start_byte,
+                    end_byte: item.end_byte,
+                    ..SearchOutput::from_search_result(
+                        Path::new(&item.file),
+                        item.line,
+                        item.col,
+                        &item.content,
+                    )
+                };
                 out.annotation = item.annotation.clone();
 
                 if let Some(ref mut b) = budget {
@@ -3221,9 +3173,7 @@ pub fn serve() -> Result<()> {
     }
 
     // Discover all indexed repos at startup.
-    let repos_dir = std::env::var("HOME")
-        .map(|h| PathBuf::from(h).join(".wonk").join("repos"))
-        .unwrap_or_default();
+    let repos_dir = db::central_repos_dir().unwrap_or_default();
     let registry = RepoRegistry::new(discover_repos(&repos_dir));
 
     let mut server = McpServer::new(repo_root, registry);
@@ -4191,14 +4141,14 @@ mod tests {
             if tool.name == "wonk_repos" || tool.name == "wonk_init" || tool.name == "wonk_update" {
                 continue;
             }
-            if let Some(required) = tool.input_schema.get("required") {
-                if let Some(arr) = required.as_array() {
-                    assert!(
-                        !arr.contains(&serde_json::json!("repo")),
-                        "tool {} should not require 'repo' param",
-                        tool.name
-                    );
-                }
+            if let Some(required) = tool.input_schema.get("required")
+                && let Some(arr) = required.as_array()
+            {
+                assert!(
+                    !arr.contains(&serde_json::json!("repo")),
+                    "tool {} should not require 'repo' param",
+                    tool.name
+                );
             }
         }
     }
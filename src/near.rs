@@ -0,0 +1,214 @@
+//! Structural neighbourhood of a source location for `wonk near`.
+//!
+//! Given `file:line`, reports the innermost symbol enclosing the line, the
+//! symbols next to it at the same nesting level, the file's direct
+//! dependencies and dependents, and the references made from inside the
+//! enclosing symbol, all from the index.
+
+use anyhow::{Context, Result, bail};
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::output::{NearOutput, NearRefOutput, NearSymbolOutput};
+use crate::router;
+
+/// Split a `file:line` location into its parts.
+pub fn parse_location(location: &str) -> Result<(String, usize)> {
+    let Some((file, line)) = location.rsplit_once(':') else {
+        bail!("expected a location like src/main.rs:42, got `{location}`");
+    };
+    let line: usize = line
+        .parse()
+        .with_context(|| format!("invalid line number `{line}` in `{location}`"))?;
+    if file.is_empty() || line == 0 {
+        bail!("expected a location like src/main.rs:42, got `{location}`");
+    }
+    Ok((file.trim_start_matches("./").to_string(), line))
+}
+
+/// Describe what surrounds `line` of the indexed file `file` (repo-relative),
+/// listing up to `radius` sibling symbols on each side.  Returns `None` when
+/// the file is not indexed.
+pub fn neighborhood(
+    conn: &Connection,
+    file: &str,
+    line: usize,
+    radius: usize,
+) -> Result<Option<NearOutput>> {
+    let indexed: Option<i64> = conn
+        .query_row(
+            "SELECT 1 FROM files WHERE path = ?1",
+            rusqlite::params![file],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if indexed.is_none() {
+        return Ok(None);
+    }
+
+    // Innermost symbol whose span covers the line: the latest one to start.
+    let enclosing = conn
+        .query_row(
+            "SELECT id, name, kind, line, end_line, scope, COALESCE(signature, '') FROM symbols \
+             WHERE file = ?1 AND line <= ?2 AND COALESCE(end_line, line) >= ?2 \
+             ORDER BY line DESC, col DESC LIMIT 1",
+            rusqlite::params![file, line as i64],
+            |row| Ok((row.get::<_, i64>(0)?, symbol_from_row(row, 1)?)),
+        )
+        .optional()?;
+
+    // Siblings share the enclosing symbol's scope; without one, the line is
+    // at top level.
+    let scope = enclosing.as_ref().and_then(|(_, s)| s.scope.clone());
+    let exclude = enclosing.as_ref().map_or(-1, |(id, _)| *id);
+    let mut stmt = conn.prepare_cached(
+        "SELECT name, kind, line, end_line, scope, COALESCE(signature, '') FROM symbols \
+         WHERE file = ?1 AND scope IS ?2 AND id != ?3 ORDER BY line",
+    )?;
+    let same_level = stmt
+        .query_map(rusqlite::params![file, scope, exclude], |row| {
+            symbol_from_row(row, 0)
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let split = same_level.partition_point(|s| s.line <= line);
+    let before = &same_level[split.saturating_sub(radius)..split];
+    let after = &same_level[split..same_level.len().min(split + radius)];
+    let siblings = before.iter().chain(after).cloned().collect();
+
+    let references = match &enclosing {
+        Some((id, _)) => outgoing_references(conn, *id)?,
+        None => Vec::new(),
+    };
+
+    Ok(Some(NearOutput {
+        file: file.to_string(),
+        line,
+        enclosing: enclosing.map(|(_, s)| s),
+        siblings,
+        references,
+        deps: router::query_deps_db(conn, file)?,
+        rdeps: router::query_rdeps_db(conn, file, false)?,
+    }))
+}
+
+/// Read a symbol from `name, kind, line, end_line, scope, signature` columns
+/// starting at `offset`.
+fn symbol_from_row(row: &rusqlite::Row<'_>, offset: usize) -> rusqlite::Result<NearSymbolOutput> {
+    Ok(NearSymbolOutput {
+        name: row.get(offset)?,
+        kind: row.get(offset + 1)?,
+        line: row.get::<_, i64>(offset + 2)? as usize,
+        end_line: row.get::<_, Option<i64>>(offset + 3)?.map(|v| v as usize),
+        scope: row.get(offset + 4)?,
+        signature: row.get(offset + 5)?,
+    })
+}
+
+/// Names referenced from inside the symbol `caller_id`, first use of each,
+/// with the definition the index resolved it to.
+fn outgoing_references(conn: &Connection, caller_id: i64) -> Result<Vec<NearRefOutput>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT r.name, MIN(r.line), t.file, t.line FROM \"references\" r \
+         LEFT JOIN symbols t ON t.id = r.target_id \
+         WHERE r.caller_id = ?1 GROUP BY r.name ORDER BY MIN(r.line), r.name",
+    )?;
+    let refs = stmt
+        .query_map(rusqlite::params![caller_id], |row| {
+            let target_file: Option<String> = row.get(2)?;
+            let target_line: Option<i64> = row.get(3)?;
+            Ok(NearRefOutput {
+                name: row.get(0)?,
+                line: row.get::<_, i64>(1)? as usize,
+                definition: target_file
+                    .zip(target_line)
+                    .map(|(file, line)| format!("{file}:{line}")),
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(refs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use tempfile::TempDir;
+
+    fn fixture() -> (TempDir, Connection) {
+        let dir = TempDir::new().unwrap();
+        let conn = db::open(&dir.path().join("index.db")).unwrap();
+        conn.execute_batch(
+            "INSERT INTO files (path, language, hash, last_indexed) VALUES ('src/store.rs', 'Rust', 'h', 0); \
+             INSERT INTO symbols (id, name, kind, file, line, col, end_line, signature, language) \
+                 VALUES (1, 'Store', 'struct', 'src/store.rs', 1, 0, 3, 'pub struct Store', 'Rust'); \
+             INSERT INTO symbols (id, name, kind, file, line, col, end_line, signature, language) \
+                 VALUES (2, 'Store', 'module', 'src/store.rs', 5, 0, 30, 'impl Store', 'Rust'); \
+             INSERT INTO symbols (id, name, kind, file, line, col, end_line, scope, signature, language) \
+                 VALUES (3, 'open', 'method', 'src/store.rs', 6, 4, 10, 'Store', 'pub fn open()', 'Rust'); \
+             INSERT INTO symbols (id, name, kind, file, line, col, end_line, scope, signature, language) \
+                 VALUES (4, 'get', 'method', 'src/store.rs', 12, 4, 20, 'Store', 'pub fn get()', 'Rust'); \
+             INSERT INTO symbols (id, name, kind, file, line, col, end_line, scope, signature, language) \
+                 VALUES (5, 'put', 'method', 'src/store.rs', 22, 4, 29, 'Store', 'pub fn put()', 'Rust'); \
+             INSERT INTO symbols (id, name, kind, file, line, col, end_line, signature, language) \
+                 VALUES (6, 'checksum', 'function', 'src/store.rs', 32, 0, 40, 'fn checksum()', 'Rust'); \
+             INSERT INTO \"references\" (name, file, line, col, caller_id, target_id) \
+                 VALUES ('checksum', 'src/store.rs', 15, 8, 4, 6); \
+             INSERT INTO \"references\" (name, file, line, col, caller_id, target_id) \
+                 VALUES ('checksum', 'src/store.rs', 18, 8, 4, 6); \
+             INSERT INTO \"references\" (name, file, line, col, caller_id) \
+                 VALUES ('read_to_end', 'src/store.rs', 14, 8, 4); \
+             INSERT INTO file_imports (source_file, import_path) VALUES ('src/main.rs', 'src/store.rs'); \
+             INSERT INTO file_imports (source_file, import_path) VALUES ('src/store.rs', 'std::fs');",
+        )
+        .unwrap();
+        (dir, conn)
+    }
+
+    #[test]
+    fn parse_location_splits_file_and_line() {
+        assert_eq!(
+            parse_location("./src/a.rs:42").unwrap(),
+            ("src/a.rs".to_string(), 42)
+        );
+        assert!(parse_location("src/a.rs").is_err());
+        assert!(parse_location("src/a.rs:x").is_err());
+        assert!(parse_location("src/a.rs:0").is_err());
+    }
+
+    #[test]
+    fn neighborhood_reports_enclosing_siblings_and_references() {
+        let (_dir, conn) = fixture();
+        let near = neighborhood(&conn, "src/store.rs", 15, 1).unwrap().unwrap();
+        let enclosing = near.enclosing.unwrap();
+        assert_eq!(enclosing.name, "get");
+        let siblings: Vec<&str> = near.siblings.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(siblings, ["open", "put"]);
+        let refs: Vec<(&str, usize, Option<&str>)> = near
+            .references
+            .iter()
+            .map(|r| (r.name.as_str(), r.line, r.definition.as_deref()))
+            .collect();
+        assert_eq!(
+            refs,
+            [
+                ("read_to_end", 14, None),
+                ("checksum", 15, Some("src/store.rs:32"))
+            ]
+        );
+        assert!(near.deps.contains(&"std::fs".to_string()));
+    }
+
+    #[test]
+    fn neighborhood_between_symbols_lists_top_level_siblings() {
+        let (_dir, conn) = fixture();
+        let near = neighborhood(&conn, "src/store.rs", 31, 5).unwrap().unwrap();
+        assert!(near.enclosing.is_none());
+        let siblings: Vec<&str> = near.siblings.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(siblings, ["Store", "Store", "checksum"]);
+        assert!(near.references.is_empty());
+        assert!(
+            neighborhood(&conn, "src/missing.rs", 1, 5)
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -4,15 +4,20 @@
 //! arbitrary [`std::io::Write`] destination (typically stdout).
 //! Hints and errors always go to stderr via [`print_hint`] and [`print_error`].
 
+use std::borrow::Cow;
 use std::fmt::Display;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use regex::Regex;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::budget::TokenBudget;
 use crate::color;
+use crate::columns::{ColUnit, ColumnMapper, convert_col};
+use crate::glyphs::Glyphs;
+use crate::stamp::Stamper;
 use crate::types::ShowResult;
 
 // ---------------------------------------------------------------------------
@@ -26,13 +31,18 @@ pub enum OutputFormat {
     Grep,
     Json,
     Toon,
+    /// GitHub Actions annotations for located results (see [`crate::github`]).
+    Github,
 }
 
 impl OutputFormat {
     /// Returns `true` for structured (non-grep) formats that should suppress
     /// stderr hints and disable color.
     pub fn is_structured(&self) -> bool {
-        matches!(self, OutputFormat::Json | OutputFormat::Toon)
+        matches!(
+            self,
+            OutputFormat::Json | OutputFormat::Toon | OutputFormat::Github
+        )
     }
 }
 
@@ -43,7 +53,10 @@ impl std::str::FromStr for OutputFormat {
             "grep" => Ok(Self::Grep),
             "json" => Ok(Self::Json),
             "toon" => Ok(Self::Toon),
-            _ => Err(format!("unknown format '{s}' (expected: grep, json, toon)")),
+            "github" => Ok(Self::Github),
+            _ => Err(format!(
+                "unknown format '{s}' (expected: grep, json, toon, github)"
+            )),
         }
     }
 }
@@ -53,23 +66,59 @@ impl std::str::FromStr for OutputFormat {
 // ---------------------------------------------------------------------------
 
 /// A single text search match (corresponds to `SearchResult` in `search.rs`).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SearchOutput {
     pub file: String,
     pub line: u64,
     pub col: u64,
+    /// 1-based column just past the end of the first match.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_col: Option<u64>,
+    /// Byte offset of the first match in the file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_byte: Option<u64>,
+    /// Byte offset just past the end of the first match.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_byte: Option<u64>,
     pub content: String,
     /// Optional annotation from ranking/dedup (e.g. "(+3 other locations)").
     #[serde(skip_serializing_if = "Option::is_none")]
     pub annotation: Option<String>,
+    /// Ranking category in smart mode ("definition", "call_site", ...).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
     /// Optional source indicator for blended search ("structural" or "semantic").
     #[serde(skip_serializing_if = "Option::is_none")]
     pub source: Option<String>,
+    /// Patterns this line matched, for multi-pattern search (`-e`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patterns: Vec<String>,
+    /// Indexed content hash and index generation (structured output).
+    #[serde(flatten, default, skip_serializing_if = "Option::is_none")]
+    pub stamp: Option<SourceStamp>,
+}
+
+/// Where a result came from in the index: the hash of the file content as
+/// indexed, and the index generation it was read at.  A consumer holding a
+/// cached result can compare either against a fresh query to tell whether
+/// the file changed since.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SourceStamp {
+    /// Hash of the file's content when it was indexed; absent for files
+    /// outside the index.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    /// Index generation, advanced by every write that changes indexed
+    /// content.
+    pub index_generation: u64,
 }
 
 /// A symbol definition result.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SymbolOutput {
+    /// Name this result answers in a bulk lookup (`sym --stdin-names`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
     pub name: String,
     pub kind: String,
     pub file: String,
@@ -77,52 +126,263 @@ pub struct SymbolOutput {
     pub col: usize,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub end_line: Option<usize>,
+    /// 0-based column where the definition ends on `end_line` (exclusive).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_col: Option<usize>,
+    /// Byte range of the definition in the file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_byte: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_byte: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub scope: Option<String>,
     pub signature: String,
     pub language: String,
+    /// Stable symbol ID that survives reindexing (see [`Symbol::stable_id`]).
+    ///
+    /// [`Symbol::stable_id`]: crate::types::Symbol::stable_id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Code generator of the file, when the hit is in generated code.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generated_by: Option<String>,
+    /// Preprocessor condition guarding the definition (C/C++).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    /// Normalised visibility (`public`, `private`, `pub(crate)`, ...).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<String>,
+    /// Modifier keywords (`static`, `async`, `unsafe`, ...).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modifiers: Vec<String>,
+    /// Generic type parameters, normalised to `T` or `T: Bound`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub generics: Vec<String>,
+    /// `--verify` outcome: fresh, relocated, stale, or missing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub freshness: Option<String>,
+    /// Indexed content hash and index generation (structured output).
+    #[serde(flatten, default, skip_serializing_if = "Option::is_none")]
+    pub stamp: Option<SourceStamp>,
+}
+
+impl From<&crate::types::Symbol> for SymbolOutput {
+    fn from(sym: &crate::types::Symbol) -> Self {
+        Self {
+            query: None,
+            name: sym.name.clone(),
+            kind: sym.kind.to_string(),
+            file: sym.file.clone(),
+            line: sym.line,
+            col: sym.col,
+            end_line: sym.end_line,
+            end_col: sym.end_col,
+            start_byte: sym.start_byte,
+            end_byte: sym.end_byte,
+            scope: sym.scope.clone(),
+            signature: sym.signature.clone(),
+            language: sym.language.clone(),
+            id: (!sym.language.is_empty()).then(|| sym.stable_id()),
+            freshness: None,
+            generated_by: None,
+            condition: sym.condition.clone(),
+            visibility: sym.visibility.clone(),
+            modifiers: sym.modifiers.clone(),
+            generics: sym.generics.clone(),
+            stamp: None,
+        }
+    }
+}
+
+/// A `wonk global sym` result: a symbol labeled with its repository.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GlobalSymbolOutput {
+    /// Short name of the repository.
+    pub repo: String,
+    /// Absolute path to the repository root; `file` is relative to it.
+    pub repo_path: String,
+    #[serde(flatten)]
+    pub symbol: SymbolOutput,
+}
+
+impl From<&crate::global::GlobalSymbol> for GlobalSymbolOutput {
+    fn from(hit: &crate::global::GlobalSymbol) -> Self {
+        Self {
+            repo: hit.repo.clone(),
+            repo_path: hit.repo_path.display().to_string(),
+            symbol: SymbolOutput::from(&hit.symbol),
+        }
+    }
+}
+
+/// A `wonk find` result: one line of the report and the section it is in.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FindOutput {
+    /// `definition`, `signature`, `usage`, or `mention`.
+    pub section: String,
+    pub file: String,
+    pub line: usize,
+    pub col: usize,
+    /// Symbol kind of a definition or signature, reference kind of a usage.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// The signature, or the source line of a usage or mention.
+    pub content: String,
+    /// Name of the function enclosing a usage.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caller_name: Option<String>,
+    /// Indexed content hash and index generation (structured output).
+    #[serde(flatten, default, skip_serializing_if = "Option::is_none")]
+    pub stamp: Option<SourceStamp>,
+}
+
+impl From<&crate::find::FindHit> for FindOutput {
+    fn from(hit: &crate::find::FindHit) -> Self {
+        Self {
+            section: hit.section.as_str().to_string(),
+            file: hit.file.clone(),
+            line: hit.line,
+            col: hit.col,
+            kind: hit.kind.clone(),
+            content: hit.content.clone(),
+            caller_name: hit.caller_name.clone(),
+            stamp: None,
+        }
+    }
 }
 
 /// A reference (usage site) result.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RefOutput {
     pub name: String,
     pub kind: String,
     pub file: String,
     pub line: usize,
     pub col: usize,
+    /// 0-based column where the reference ends (exclusive), when it ends
+    /// on the same line.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_col: Option<usize>,
+    /// Byte range of the reference in the file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_byte: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_byte: Option<usize>,
     pub context: String,
     /// Name of the enclosing function/method (from call graph).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub caller_name: Option<String>,
     pub confidence: f64,
+    /// Resolved definition of the referenced name (`ref --resolve`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub definition: Option<DefinitionOutput>,
+    /// Code generator of the file, when the hit is in generated code.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generated_by: Option<String>,
+    /// `--verify` outcome: fresh, relocated, stale, or missing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub freshness: Option<String>,
+    /// Indexed content hash and index generation (structured output).
+    #[serde(flatten, default, skip_serializing_if = "Option::is_none")]
+    pub stamp: Option<SourceStamp>,
+}
+
+impl From<&crate::types::Reference> for RefOutput {
+    fn from(r: &crate::types::Reference) -> Self {
+        Self {
+            name: r.name.clone(),
+            kind: r.kind.to_string(),
+            file: r.file.clone(),
+            line: r.line,
+            col: r.col,
+            end_col: r.end_col,
+            start_byte: r.start_byte,
+            end_byte: r.end_byte,
+            context: r.context.clone(),
+            caller_name: r.caller_name.clone(),
+            confidence: r.confidence,
+            definition: None,
+            generated_by: None,
+            freshness: None,
+            stamp: None,
+        }
+    }
+}
+
+/// The definition a reference resolved to.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DefinitionOutput {
+    pub file: String,
+    pub line: usize,
+    pub kind: String,
+    /// How the definition was chosen: same-file, import, unique, or same-dir.
+    pub via: String,
+}
+
+impl From<&crate::resolve::Definition> for DefinitionOutput {
+    fn from(def: &crate::resolve::Definition) -> Self {
+        Self {
+            file: def.file.clone(),
+            line: def.line,
+            kind: def.kind.to_string(),
+            via: def.via.label().to_string(),
+        }
+    }
 }
 
 /// A function/method signature result.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SignatureOutput {
     pub name: String,
     pub file: String,
     pub line: usize,
     pub signature: String,
     pub language: String,
+    /// `--verify` outcome: fresh, relocated, stale, or missing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub freshness: Option<String>,
+    /// Indexed content hash and index generation (structured output).
+    #[serde(flatten, default, skip_serializing_if = "Option::is_none")]
+    pub stamp: Option<SourceStamp>,
 }
 
 /// A single file entry for `ls` results.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FileEntry {
     pub path: String,
 }
 
 /// A dependency edge for `deps` / `rdeps` results.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DepOutput {
     pub file: String,
     pub depends_on: String,
+    /// Code generator of the file, when the hit is in generated code.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generated_by: Option<String>,
+}
+
+/// Hits in generated code collapsed into one entry per generator
+/// (`--collapse-generated`).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GeneratedGroupOutput {
+    pub generated_by: String,
+    pub files: usize,
+    pub hits: usize,
+}
+
+impl From<&crate::generated::GeneratedGroup> for GeneratedGroupOutput {
+    fn from(group: &crate::generated::GeneratedGroup) -> Self {
+        Self {
+            generated_by: group.generator.clone(),
+            files: group.files,
+            hits: group.hits,
+        }
+    }
 }
 
 /// A single member of a cluster, shown as a representative symbol.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ClusterMemberOutput {
     pub file: String,
     pub line: usize,
@@ -132,7 +392,7 @@ pub struct ClusterMemberOutput {
 }
 
 /// A cluster of semantically similar symbols.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ClusterOutput {
     pub cluster_id: usize,
     pub total_members: usize,
@@ -140,7 +400,7 @@ pub struct ClusterOutput {
 }
 
 /// A semantic search result.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SemanticOutput {
     pub file: String,
     pub line: usize,
@@ -152,7 +412,7 @@ pub struct SemanticOutput {
 }
 
 /// A symbol reference in impact analysis output (the changed or impacted symbol).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ImpactSymbolOutput {
     pub name: String,
     pub kind: String,
@@ -161,7 +421,7 @@ pub struct ImpactSymbolOutput {
 }
 
 /// A single impacted symbol entry with similarity score.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ImpactEntryOutput {
     pub file: String,
     pub line: usize,
@@ -171,14 +431,14 @@ pub struct ImpactEntryOutput {
 }
 
 /// Full impact output for JSON: groups impacted symbols under their changed symbol.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ImpactOutput {
     pub changed_symbol: ImpactSymbolOutput,
     pub impacted: Vec<ImpactEntryOutput>,
 }
 
 /// A symbol with its full source body, returned by `wonk show`.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ShowOutput {
     pub name: String,
     pub kind: String,
@@ -194,6 +454,9 @@ pub struct ShowOutput {
     pub truncated_at_line: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub total_lines: Option<usize>,
+    /// Indexed content hash and index generation (structured output).
+    #[serde(flatten, default, skip_serializing_if = "Option::is_none")]
+    pub stamp: Option<SourceStamp>,
 }
 
 impl ShowOutput {
@@ -218,7 +481,8 @@ impl ShowOutput {
         let remaining = total - max_lines;
         let mut truncated_source: String = lines[..max_lines].join("\n");
         truncated_source.push_str(&format!(
-            "\n// ... {remaining} more lines (truncated — use --page 2 for continuation)",
+            "\n// ... {remaining} more lines (truncated {} use --page 2 for continuation)",
+            crate::glyphs::current().dash
         ));
         Some(Self {
             name: self.name.clone(),
@@ -231,6 +495,7 @@ impl ShowOutput {
             auto_shallow: self.auto_shallow,
             truncated_at_line: Some(self.line + max_lines - 1),
             total_lines: Some(total),
+            stamp: self.stamp.clone(),
         })
     }
 }
@@ -248,12 +513,13 @@ impl From<&ShowResult> for ShowOutput {
             auto_shallow: None,
             truncated_at_line: None,
             total_lines: None,
+            stamp: None,
         }
     }
 }
 
 /// A caller of a symbol, for `wonk callers` output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CallerOutput {
     pub caller_name: String,
     pub caller_kind: String,
@@ -267,21 +533,436 @@ pub struct CallerOutput {
 }
 
 /// A symbol count entry for summary output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SymbolCountEntry {
     pub kind: String,
     pub count: usize,
 }
 
 /// A language entry for summary output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LanguageEntry {
     pub language: String,
     pub count: usize,
 }
 
+/// One command line of a `wonk examples` recipe.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RecipeStepOutput {
+    pub command: String,
+    pub description: String,
+}
+
+/// A `wonk examples` recipe.  Steps are left out when listing topics.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RecipeOutput {
+    pub topic: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<RecipeStepOutput>,
+}
+
+impl RecipeOutput {
+    /// Build output for `recipe`, with its steps when `with_steps` is set.
+    pub fn from_recipe(recipe: &crate::examples::Recipe, with_steps: bool) -> Self {
+        let steps = if with_steps {
+            recipe
+                .steps
+                .iter()
+                .map(|e| RecipeStepOutput {
+                    command: e.command.to_string(),
+                    description: e.description.to_string(),
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        Self {
+            topic: recipe.topic.to_string(),
+            title: recipe.title.to_string(),
+            steps,
+        }
+    }
+}
+
+/// Per-language parse totals in `wonk init --profile` output.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct LanguageProfileOutput {
+    pub language: String,
+    pub files: usize,
+    pub lines: usize,
+    pub parse_ms: f64,
+    /// Share of all parse time, in percent (0.0-100.0).
+    pub percent: f64,
+}
+
+/// One of the slowest files in `wonk init --profile` output.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct FileProfileOutput {
+    pub path: String,
+    pub language: String,
+    pub lines: usize,
+    pub parse_ms: f64,
+}
+
+/// Timing report printed by `wonk init --profile`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct IndexProfileOutput {
+    pub files: usize,
+    pub total_ms: f64,
+    pub walk_ms: f64,
+    /// Wall-clock time of the parallel parse phase.
+    pub parse_ms: f64,
+    /// Parse time summed over all files (across threads).
+    pub parse_cpu_ms: f64,
+    pub insert_ms: f64,
+    pub resolve_ms: f64,
+    pub languages: Vec<LanguageProfileOutput>,
+    pub slowest_files: Vec<FileProfileOutput>,
+}
+
+impl IndexProfileOutput {
+    /// Build the report from `profile`, listing the `top` slowest files.
+    pub fn from_profile(profile: &crate::pipeline::IndexProfile, top: usize) -> Self {
+        let ms = |d: std::time::Duration| d.as_secs_f64() * 1000.0;
+        let cpu = ms(profile.parse_cpu());
+        Self {
+            files: profile.files.len(),
+            total_ms: ms(profile.total),
+            walk_ms: ms(profile.walk),
+            parse_ms: ms(profile.parse),
+            parse_cpu_ms: cpu,
+            insert_ms: ms(profile.insert),
+            resolve_ms: ms(profile.resolve),
+            languages: profile
+                .by_language()
+                .into_iter()
+                .map(|l| LanguageProfileOutput {
+                    percent: if cpu == 0.0 {
+                        0.0
+                    } else {
+                        ms(l.parse) * 100.0 / cpu
+                    },
+                    language: l.language,
+                    files: l.files,
+                    lines: l.lines,
+                    parse_ms: ms(l.parse),
+                })
+                .collect(),
+            slowest_files: profile
+                .slowest_files(top)
+                .into_iter()
+                .map(|f| FileProfileOutput {
+                    path: f.path.clone(),
+                    language: f.language.clone(),
+                    lines: f.lines,
+                    parse_ms: ms(f.parse),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// One crash report included by `wonk diag`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CrashReportOutput {
+    pub path: String,
+    pub contents: String,
+}
+
+/// The bundle printed by `wonk diag`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DiagOutput {
+    pub version: String,
+    pub platform: String,
+    /// Stats for the current repository's index; `None` when it has none.
+    pub index: Option<crate::crash::RepoStats>,
+    /// Total number of crash reports on disk.
+    pub crash_count: usize,
+    pub crash_reports: Vec<CrashReportOutput>,
+}
+
+/// A type hierarchy edge extracted by `wonk parse`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TypeEdgeOutput {
+    pub child: String,
+    pub parent: String,
+    /// `extends` or `implements`.
+    pub relationship: String,
+}
+
+/// Everything `wonk parse` extracted from one source text.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ParseOutput {
+    pub language: String,
+    pub path: String,
+    pub symbols: Vec<SymbolOutput>,
+    pub references: Vec<RefOutput>,
+    pub imports: Vec<String>,
+    pub type_edges: Vec<TypeEdgeOutput>,
+}
+
+impl ParseOutput {
+    pub fn new(language: &str, path: &str, extraction: &crate::pipeline::Extraction) -> Self {
+        Self {
+            language: language.to_string(),
+            path: path.to_string(),
+            symbols: extraction.symbols.iter().map(SymbolOutput::from).collect(),
+            references: extraction.refs.iter().map(RefOutput::from).collect(),
+            imports: extraction.imports.clone(),
+            type_edges: extraction
+                .type_edges
+                .iter()
+                .map(|e| TypeEdgeOutput {
+                    child: e.child_name.clone(),
+                    parent: e.parent_name.clone(),
+                    relationship: e.relationship.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// One syntax tree node listed by `wonk tree-dump`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TreeNodeOutput {
+    pub kind: String,
+    /// Field name under the parent node (`name`, `body`, ...).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    /// Nesting level below the root (or below the matched `--kind` node).
+    pub depth: usize,
+    /// `false` for anonymous nodes such as punctuation and keywords.
+    pub named: bool,
+    /// Inserted by tree-sitter's error recovery; absent from the source.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub missing: bool,
+    pub start_line: usize,
+    pub start_col: usize,
+    pub end_line: usize,
+    pub end_col: usize,
+    pub start_byte: usize,
+    pub end_byte: usize,
+    /// Source text of leaf nodes, truncated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+/// The syntax tree printed by `wonk tree-dump`: either `nodes` or, with
+/// `--sexp`, s-expressions.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TreeDumpOutput {
+    pub language: String,
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub nodes: Vec<TreeNodeOutput>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sexp: Option<Vec<String>>,
+}
+
+/// Result of `wonk snapshot`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SnapshotOutput {
+    pub name: String,
+    /// Snapshot file, relative to the repository root.
+    pub path: String,
+    pub query: Vec<String>,
+    pub entries: usize,
+}
+
+/// Result of `wonk compare`: records added and removed since a snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CompareOutput {
+    pub name: String,
+    pub query: Vec<String>,
+    /// Record counts in the snapshot and in the current results.
+    pub before: usize,
+    pub after: usize,
+    pub unchanged: usize,
+    pub added: Vec<serde_json::Value>,
+    pub removed: Vec<serde_json::Value>,
+}
+
+/// One check evaluated by `wonk gate`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GateCheckOutput {
+    /// Query name from `[gate.queries]`.
+    pub name: String,
+    /// `"max"` (result count limit) or `"no_new"` (no records beyond the
+    /// baseline).
+    pub rule: String,
+    /// Current number of results.
+    pub count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    /// Number of results in the baseline (`None` when it is missing).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub baseline: Option<usize>,
+    /// Records not present in the baseline.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added: Vec<serde_json::Value>,
+    pub passed: bool,
+}
+
+/// Result of `wonk which-index`: the index commands read and its provenance.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WhichIndexOutput {
+    pub repo: String,
+    /// Index read by most commands, `None` when none exists.
+    pub index: Option<String>,
+    /// `"local"` or `"central"`.
+    pub location: Option<String>,
+    /// Why `index` was chosen.
+    pub reason: String,
+    pub local_path: String,
+    pub local_exists: bool,
+    pub central_path: Option<String>,
+    pub central_exists: bool,
+    /// Index read by `sym`, `ref`, `sig`, `deps`, and `rdeps` (always the
+    /// central one; `None` when it does not exist and they use grep).
+    pub symbol_index: Option<String>,
+    /// Seconds since the Unix epoch when the index was last built or updated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub built: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wonk_version: Option<String>,
+    /// Git commit checked out when the index was last built or updated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// Git commit checked out now.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled_languages: Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool,
+}
+
+/// Result of `wonk describe`: a compact, index-sourced summary of one file.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DescribeOutput {
+    pub file: String,
+    pub language: String,
+    pub lines: usize,
+    /// `"test"`, `"generated"`, or `"source"`.
+    pub role: String,
+    /// Code generator of the file, when `role` is `"generated"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generated_by: Option<String>,
+    /// Files and modules the file imports.
+    pub imports: Vec<String>,
+    /// Public top-level symbols, in line order.
+    pub exports: Vec<String>,
+    /// Total number of symbols in the file, at any nesting level.
+    pub symbol_count: usize,
+    /// Top-level definitions, most used from other files first.
+    pub symbols: Vec<DescribeSymbolOutput>,
+}
+
+/// One top-level definition in a [`DescribeOutput`].
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DescribeSymbolOutput {
+    pub name: String,
+    pub kind: String,
+    pub line: usize,
+    pub signature: String,
+    /// References to the name from other files.
+    pub uses: usize,
+}
+
+/// Result of `wonk near`: the structural context around one location.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NearOutput {
+    pub file: String,
+    pub line: usize,
+    /// Innermost symbol whose span covers the line, if any.
+    pub enclosing: Option<NearSymbolOutput>,
+    /// Nearest symbols at the same nesting level, in line order.
+    pub siblings: Vec<NearSymbolOutput>,
+    /// Names referenced inside the enclosing symbol, first use of each.
+    pub references: Vec<NearRefOutput>,
+    /// Files and modules the file imports.
+    pub deps: Vec<String>,
+    /// Files importing the file.
+    pub rdeps: Vec<String>,
+}
+
+/// A symbol in a [`NearOutput`].
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NearSymbolOutput {
+    pub name: String,
+    pub kind: String,
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    pub signature: String,
+}
+
+/// A reference made from inside the enclosing symbol of a [`NearOutput`].
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NearRefOutput {
+    pub name: String,
+    pub line: usize,
+    /// `file:line` of the definition the index resolved the name to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub definition: Option<String>,
+}
+
+/// Result of `wonk self update`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SelfUpdateOutput {
+    pub current_version: String,
+    pub latest_version: String,
+    pub channel: String,
+    pub update_available: bool,
+    /// Whether the binary was replaced (always `false` with `--check`).
+    pub updated: bool,
+}
+
+/// One language row for `wonk langs` output.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LangStatsOutput {
+    pub language: String,
+    pub files: usize,
+    pub lines: usize,
+    pub symbols: usize,
+    pub references: usize,
+    /// Share of all indexed lines, in percent (0.0-100.0).
+    pub percent: f64,
+}
+
+impl LangStatsOutput {
+    /// Width of the text-mode bar chart at 100%.
+    pub const BAR_WIDTH: usize = 30;
+
+    /// Build output rows from index stats, computing each language's share of
+    /// the total line count.
+    pub fn from_stats(stats: &[crate::types::LanguageStats]) -> Vec<Self> {
+        let total_lines: usize = stats.iter().map(|s| s.line_count).sum();
+        stats
+            .iter()
+            .map(|s| LangStatsOutput {
+                language: s.language.clone(),
+                files: s.file_count,
+                lines: s.line_count,
+                symbols: s.symbol_count,
+                references: s.reference_count,
+                percent: if total_lines == 0 {
+                    0.0
+                } else {
+                    s.line_count as f64 * 100.0 / total_lines as f64
+                },
+            })
+            .collect()
+    }
+}
+
 /// Aggregated metrics for summary output, with optional fields for detail-level control.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SummaryMetricsOutput {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub file_count: Option<usize>,
@@ -296,7 +977,7 @@ pub struct SummaryMetricsOutput {
 }
 
 /// Structural summary output for a file or directory.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SummaryOutput {
     pub path: String,
     #[serde(rename = "type")]
@@ -314,7 +995,7 @@ pub struct SummaryOutput {
 }
 
 /// A symbol in a summary output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SummarySymbolOutput {
     pub name: String,
     pub kind: String,
@@ -333,7 +1014,7 @@ pub struct SummarySymbolOutput {
 }
 
 /// An intra-directory import edge in a summary output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ImportEdgeOutput {
     pub from: String,
     pub to: String,
@@ -435,8 +1116,51 @@ impl SummaryOutput {
     }
 }
 
+/// One file in a per-file directory listing (`wonk summary <dir> --files`).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FileListingOutput {
+    pub file: String,
+    pub language: String,
+    pub symbol_count: usize,
+    pub symbols: Vec<SummarySymbolOutput>,
+}
+
+impl From<&crate::types::FileListing> for FileListingOutput {
+    fn from(fl: &crate::types::FileListing) -> Self {
+        let parent_names: std::collections::HashSet<&str> = fl
+            .symbols
+            .iter()
+            .filter(|s| s.scope.is_none())
+            .map(|s| s.name.as_str())
+            .collect();
+        Self {
+            file: fl.path.clone(),
+            language: fl.language.clone(),
+            symbol_count: fl.symbol_count,
+            symbols: fl
+                .symbols
+                .iter()
+                .map(|s| SummarySymbolOutput {
+                    name: s.name.clone(),
+                    kind: s.kind.clone(),
+                    signature: s.signature.clone(),
+                    line: s.line,
+                    col: s.col,
+                    end_line: s.end_line,
+                    scope: s.scope.clone(),
+                    doc_comment: s.doc_comment.clone(),
+                    indent: match &s.scope {
+                        Some(scope) if parent_names.contains(scope.as_str()) => 1,
+                        _ => 0,
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
 /// A single hop in a call path, for `wonk callpath` output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CallPathHopOutput {
     pub symbol_name: String,
     pub symbol_kind: String,
@@ -445,7 +1169,7 @@ pub struct CallPathHopOutput {
 }
 
 /// A callee of a symbol, for `wonk callees` output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CalleeOutput {
     pub callee_name: String,
     pub file: String,
@@ -455,10 +1179,28 @@ pub struct CalleeOutput {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub source_file: Option<String>,
     pub confidence: f64,
+    /// Where the callee is defined, when the index can tell.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub definition: Option<DefinitionOutput>,
+}
+
+impl From<&crate::types::CalleeResult> for CalleeOutput {
+    fn from(cr: &crate::types::CalleeResult) -> Self {
+        Self {
+            callee_name: cr.callee_name.clone(),
+            file: cr.file.clone(),
+            line: cr.line,
+            context: cr.context.clone(),
+            depth: cr.depth,
+            source_file: cr.source_file.clone(),
+            confidence: cr.confidence,
+            definition: None,
+        }
+    }
 }
 
 /// A single step in an execution flow, for `wonk flows` output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FlowStepOutput {
     pub name: String,
     pub kind: String,
@@ -468,7 +1210,7 @@ pub struct FlowStepOutput {
 }
 
 /// A traced execution flow, for `wonk flows <entry>` output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FlowOutput {
     pub entry_point: FlowStepOutput,
     pub steps: Vec<FlowStepOutput>,
@@ -498,8 +1240,83 @@ impl From<&crate::types::ExecutionFlow> for FlowOutput {
     }
 }
 
+/// One category group of ranked search results, for `wonk search --group-json`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SearchGroupOutput {
+    /// Category name, or absent for the group of collapsed categories.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Header label (e.g. "definitions").
+    pub label: String,
+    pub results: Vec<SearchOutput>,
+}
+
+/// Search hits in one directory, for `wonk search --cluster dir`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SearchClusterOutput {
+    /// Parent directory of the hits, `.` for the top of the tree.
+    pub dir: String,
+    /// Hits in the directory, including those not shown.
+    pub count: usize,
+    /// The directory's top hits.
+    pub results: Vec<SearchOutput>,
+}
+
+/// An async function and its non-async callers, for `wonk report async` output.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AsyncBoundaryOutput {
+    pub function: CallPathHopOutput,
+    pub sync_callers: Vec<CallPathHopOutput>,
+}
+
+/// An unsafe declaration or block, for `wonk report unsafe` output.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UnsafeSiteOutput {
+    /// `"declaration"` or `"block"`.
+    pub kind: String,
+    pub file: String,
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+}
+
+impl From<&crate::types::CallPathHop> for CallPathHopOutput {
+    fn from(hop: &crate::types::CallPathHop) -> Self {
+        Self {
+            symbol_name: hop.symbol_name.clone(),
+            symbol_kind: hop.symbol_kind.to_string(),
+            file: hop.file.clone(),
+            line: hop.line,
+        }
+    }
+}
+
+impl From<&crate::types::AsyncBoundary> for AsyncBoundaryOutput {
+    fn from(boundary: &crate::types::AsyncBoundary) -> Self {
+        Self {
+            function: CallPathHopOutput::from(&boundary.function),
+            sync_callers: boundary
+                .sync_callers
+                .iter()
+                .map(CallPathHopOutput::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<&crate::types::UnsafeSite> for UnsafeSiteOutput {
+    fn from(site: &crate::types::UnsafeSite) -> Self {
+        Self {
+            kind: if site.block { "block" } else { "declaration" }.to_string(),
+            file: site.file.clone(),
+            line: site.line,
+            symbol: site.symbol.clone(),
+        }
+    }
+}
+
 /// A symbol affected by blast radius analysis, for output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BlastAffectedOutput {
     pub name: String,
     pub kind: String,
@@ -510,14 +1327,14 @@ pub struct BlastAffectedOutput {
 }
 
 /// A severity tier in blast radius output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BlastTierOutput {
     pub severity: String,
     pub symbols: Vec<BlastAffectedOutput>,
 }
 
 /// Complete blast radius analysis output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BlastOutput {
     pub target: String,
     pub direction: String,
@@ -561,7 +1378,7 @@ impl From<&crate::types::BlastAnalysis> for BlastOutput {
 }
 
 /// A changed symbol with optional per-symbol blast radius, for `wonk changes` output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ChangedSymbolOutput {
     pub name: String,
     pub kind: String,
@@ -573,7 +1390,7 @@ pub struct ChangedSymbolOutput {
 }
 
 /// An execution flow affected by changed symbols.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AffectedFlowOutput {
     pub entry_point: FlowStepOutput,
     pub steps: Vec<FlowStepOutput>,
@@ -582,7 +1399,7 @@ pub struct AffectedFlowOutput {
 }
 
 /// Complete `wonk changes` output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ChangesOutput {
     pub scope: String,
     pub changed_symbols: Vec<ChangedSymbolOutput>,
@@ -597,7 +1414,7 @@ pub struct ChangesOutput {
 // ---------------------------------------------------------------------------
 
 /// A caller of a symbol in context output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ContextCallerOutput {
     pub name: String,
     pub kind: String,
@@ -606,7 +1423,7 @@ pub struct ContextCallerOutput {
 }
 
 /// A type user reference in context output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ContextTypeUserOutput {
     pub file: String,
     pub line: usize,
@@ -614,13 +1431,13 @@ pub struct ContextTypeUserOutput {
 }
 
 /// An importer in context output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ContextImporterOutput {
     pub file: String,
 }
 
 /// A callee in context output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ContextCalleeOutput {
     pub name: String,
     pub kind: String,
@@ -629,20 +1446,20 @@ pub struct ContextCalleeOutput {
 }
 
 /// An import in context output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ContextImportOutput {
     pub path: String,
 }
 
 /// A flow participation in context output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ContextFlowOutput {
     pub entry_point: String,
     pub step_index: usize,
 }
 
 /// A child symbol in context output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ContextChildOutput {
     pub name: String,
     pub kind: String,
@@ -652,7 +1469,7 @@ pub struct ContextChildOutput {
 }
 
 /// Incoming references in context output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct IncomingRefsOutput {
     pub callers: Vec<ContextCallerOutput>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
@@ -662,7 +1479,7 @@ pub struct IncomingRefsOutput {
 }
 
 /// Outgoing references in context output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct OutgoingRefsOutput {
     pub callees: Vec<ContextCalleeOutput>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
@@ -670,7 +1487,7 @@ pub struct OutgoingRefsOutput {
 }
 
 /// Complete symbol context output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SymbolContextOutput {
     pub name: String,
     pub kind: String,
@@ -773,7 +1590,7 @@ impl From<&crate::types::SymbolContext> for SymbolContextOutput {
 
 /// Truncation metadata emitted as a final JSON line when `--budget` truncates
 /// output. In grep mode the summary goes to stderr instead.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct TruncationMeta {
     pub truncated_count: usize,
     pub budget_tokens: usize,
@@ -784,8 +1601,48 @@ pub struct TruncationMeta {
     pub has_more: bool,
 }
 
-/// Indicates whether a format call actually wrote data or was skipped due to
-/// budget exhaustion.
+/// Timeout metadata emitted as a final JSON line when `--timeout` stopped a
+/// command early. In grep mode the notice goes to stderr instead.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TimeoutMeta {
+    pub timed_out: bool,
+    pub timeout_secs: f64,
+}
+
+/// Interrupt metadata emitted as a final JSON line when Ctrl-C stopped a
+/// command early. In grep mode the notice goes to stderr instead.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct InterruptMeta {
+    pub interrupted: bool,
+}
+
+/// Kind of a [`MessageMeta`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageKind {
+    Hint,
+    Warning,
+    Error,
+}
+
+/// A hint, warning, or error emitted as a record on stdout when output is
+/// structured, so consumers never have to parse free text on stderr.  In
+/// grep mode the message goes to stderr as `<type>: <message>` instead.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct MessageMeta {
+    #[serde(rename = "type")]
+    pub kind: MessageKind,
+    pub message: String,
+    /// Suggested fix for an error.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+    /// Stable identifier of a warning (see [`crate::caveats::Caveat::code`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+/// Indicates whether a format call actually wrote data or was skipped due to
+/// budget exhaustion.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BudgetStatus {
     /// The result was formatted and written to the output.
@@ -805,9 +1662,35 @@ impl SearchOutput {
             file: file.to_string_lossy().into_owned(),
             line,
             col,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: content.to_string(),
             annotation: None,
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
+        }
+    }
+
+    /// Byte range of the recorded match within `content`, when it is known
+    /// and falls on character boundaries of the line.
+    fn match_span(&self) -> Option<std::ops::Range<usize>> {
+        let start = (self.col as usize).checked_sub(1)?;
+        let end = (self.end_col? as usize).checked_sub(1)?;
+        (start < end && self.content.is_char_boundary(start) && self.content.is_char_boundary(end))
+            .then_some(start..end)
+    }
+
+    /// Build a `SearchOutput` from a `search::SearchResult`, keeping its
+    /// match span.
+    pub fn from_result(result: &crate::search::SearchResult) -> Self {
+        Self {
+            end_col: result.end_col,
+            start_byte: result.start_byte,
+            end_byte: result.end_byte,
+            ..Self::from_search_result(&result.file, result.line, result.col, &result.content)
         }
     }
 }
@@ -836,6 +1719,26 @@ pub struct Formatter<W: Write> {
     /// Tracks the file path from the previous `emit()` call so that same-file
     /// results can be joined on one line in single-line mode.
     last_emit_file: Option<String>,
+    /// Converts byte columns to `[output].col_unit` in structured output.
+    columns: Option<ColumnMapper>,
+    /// Stamps results with their indexed content hash in structured output.
+    stamper: Option<Stamper>,
+    /// Dashes, arrows, and bars used in grep-format output.
+    glyphs: Glyphs,
+    /// Session budget file charged with the tokens used when dropped.  Only
+    /// output counted by `budget` is charged, so text written through
+    /// [`writer_mut`](Self::writer_mut) or to stderr is not.
+    session_budget: Option<PathBuf>,
+}
+
+impl<W: Write> Drop for Formatter<W> {
+    fn drop(&mut self) {
+        if let Some(path) = self.session_budget.take()
+            && let Err(e) = crate::budget::SessionBudget::charge(&path, self.budget_used())
+        {
+            print_warning(&format!("session budget not updated: {e:#}"));
+        }
+    }
 }
 
 impl<W: Write> Formatter<W> {
@@ -853,14 +1756,24 @@ impl<W: Write> Formatter<W> {
             budget: None,
             single_line: false,
             last_emit_file: None,
+            columns: None,
+            stamper: None,
+            glyphs: crate::glyphs::current(),
+            session_budget: None,
         }
     }
 
+    /// Override the glyph set taken from [`crate::glyphs::current`].
+    pub fn set_glyphs(&mut self, glyphs: Glyphs) {
+        self.glyphs = glyphs;
+    }
+
     /// Enable single-line mode: piped output emits one line per file group.
     /// Results from the same file are joined with ` ; `, results from
-    /// different files get separate lines.
+    /// different files get separate lines.  Has no effect on JSON and TOON,
+    /// which are already one record per line.
     pub fn set_single_line(&mut self, enabled: bool) {
-        self.single_line = enabled;
+        self.single_line = enabled && !self.format.is_structured();
     }
 
     /// Write data to the underlying writer. In single-line mode, collapses
@@ -896,6 +1809,41 @@ impl<W: Write> Formatter<W> {
         }
     }
 
+    /// Report columns of search, symbol, and reference results in the
+    /// mapper's unit instead of bytes.
+    ///
+    /// Only structured output carries columns, so grep-style output is
+    /// unaffected.
+    pub fn set_columns(&mut self, mapper: ColumnMapper) {
+        self.columns = (mapper.unit() != ColUnit::Byte).then_some(mapper);
+    }
+
+    /// The column mapper, when columns need converting for this format.
+    fn column_mapper(&mut self) -> Option<&mut ColumnMapper> {
+        if self.format.is_structured() {
+            self.columns.as_mut()
+        } else {
+            None
+        }
+    }
+
+    /// Stamp search, symbol, reference, signature, and show results with
+    /// their indexed content hash and the index generation.
+    ///
+    /// Only structured output carries stamps, so grep-style output is
+    /// unaffected.
+    pub fn set_stamper(&mut self, stamper: Stamper) {
+        self.stamper = Some(stamper);
+    }
+
+    /// The stamp for a result in `file`, when this format carries one.
+    fn stamp(&mut self, file: &str) -> Option<SourceStamp> {
+        if !self.format.is_structured() {
+            return None;
+        }
+        self.stamper.as_mut()?.stamp(file)
+    }
+
     /// Set a highlight pattern for match highlighting in search results.
     ///
     /// When color is enabled and a highlight pattern is set, matching portions
@@ -929,6 +1877,13 @@ impl<W: Write> Formatter<W> {
         self.budget = Some(TokenBudget::new_with_skip(limit, skip));
     }
 
+    /// Charge the tokens used by this command to the session budget at
+    /// `path` once the formatter is dropped (see
+    /// [`crate::budget::SessionBudget`]).
+    pub fn set_session_budget(&mut self, path: PathBuf) {
+        self.session_budget = Some(path);
+    }
+
     /// Borrow the underlying writer for direct output (e.g. table headers).
     pub fn writer_mut(&mut self) -> &mut W {
         &mut self.writer
@@ -962,6 +1917,30 @@ impl<W: Write> Formatter<W> {
         self.budget.is_some()
     }
 
+    /// Tokens left in the budget, or `None` without a budget or while an
+    /// earlier `--page` is still being skipped.
+    pub fn budget_remaining(&self) -> Option<usize> {
+        self.budget
+            .as_ref()
+            .filter(|b| b.skip_remaining() == 0)
+            .map(TokenBudget::remaining)
+    }
+
+    /// Stop writing results once `ceiling` tokens of the budget are used in
+    /// total, until cleared with `None` (see [`TokenBudget::set_ceiling`]).
+    pub fn set_budget_ceiling(&mut self, ceiling: Option<usize>) {
+        if let Some(budget) = self.budget.as_mut() {
+            budget.set_ceiling(ceiling);
+        }
+    }
+
+    /// Tokens `result` would take from the budget if formatted now.
+    pub fn search_result_tokens(&mut self, result: &SearchOutput) -> std::io::Result<usize> {
+        let result = self.convert_search_cols(result).into_owned();
+        let buf = self.render_to_buffer(|fmt| Self::render_search_result(fmt, &result))?;
+        Ok(crate::budget::estimate_tokens_from_len(buf.len()))
+    }
+
     /// Approximate remaining budget in characters (tokens × 4).
     /// Returns `None` when no budget is set.
     pub fn remaining_budget_chars(&self) -> Option<usize> {
@@ -977,6 +1956,20 @@ impl<W: Write> Formatter<W> {
     /// **Note:** This is only called when a budget is active. When no budget is
     /// set, callers should use the fast path that writes directly to `self`.
     fn budgeted_write<F>(&mut self, render: F) -> std::io::Result<BudgetStatus>
+    where
+        F: FnOnce(&mut Formatter<&mut Vec<u8>>) -> std::io::Result<()>,
+    {
+        let buf = self.render_to_buffer(render)?;
+        let status = self.check_budget_bytes(&buf);
+        if status == BudgetStatus::Written {
+            self.emit(&buf)?;
+        }
+        Ok(status)
+    }
+
+    /// Render a formatting closure to a new buffer with the same settings
+    /// as `self`, without writing or consuming budget.
+    fn render_to_buffer<F>(&mut self, render: F) -> std::io::Result<Vec<u8>>
     where
         F: FnOnce(&mut Formatter<&mut Vec<u8>>) -> std::io::Result<()>,
     {
@@ -990,6 +1983,10 @@ impl<W: Write> Formatter<W> {
                 budget: None,
                 single_line: false, // render normally; collapsing happens in emit()
                 last_emit_file: None,
+                columns: None,
+                stamper: None,
+                glyphs: self.glyphs,
+                session_budget: None,
             };
             // Transfer highlight pattern temporarily.
             std::mem::swap(&mut tmp.highlight, &mut self.highlight);
@@ -997,12 +1994,7 @@ impl<W: Write> Formatter<W> {
             std::mem::swap(&mut tmp.highlight, &mut self.highlight);
             result?;
         }
-
-        let status = self.check_budget_bytes(&buf);
-        if status == BudgetStatus::Written {
-            self.emit(&buf)?;
-        }
-        Ok(status)
+        Ok(buf)
     }
 
     /// Serialize a value to the active structured format (JSON or TOON).
@@ -1017,6 +2009,10 @@ impl<W: Write> Formatter<W> {
             OutputFormat::Toon => {
                 serde_toon2::to_string(value).map_err(|e| std::io::Error::other(e.to_string()))
             }
+            OutputFormat::Github => {
+                let record = serde_json::to_value(value).map_err(std::io::Error::other)?;
+                Ok(crate::github::render(&record))
+            }
             OutputFormat::Grep => unreachable!("serialize_structured called in grep mode"),
         }
     }
@@ -1051,17 +2047,48 @@ impl<W: Write> Formatter<W> {
     }
 
     /// Write content with match highlighting if a highlight pattern is set.
-    fn write_content(&mut self, content: &str) -> std::io::Result<()> {
-        if self.color
-            && let Some(ref hl) = self.highlight
-        {
-            return write_highlighted(&mut self.writer, content, &hl.re);
+    ///
+    /// `span` is the byte range of the recorded match within `content`; it
+    /// is highlighted even when re-running the pattern on the line alone
+    /// misses it.
+    fn write_content(
+        &mut self,
+        content: &str,
+        span: Option<std::ops::Range<usize>>,
+    ) -> std::io::Result<()> {
+        if self.color && (self.highlight.is_some() || span.is_some()) {
+            let re = self.highlight.as_ref().map(|hl| &hl.re);
+            return write_highlighted(&mut self.writer, content, re, span);
         }
         write!(self.writer, "{}", content)
     }
 
+    /// Convert the columns of a search result to the configured unit and
+    /// stamp it.
+    fn convert_search_cols<'r>(&mut self, result: &'r SearchOutput) -> Cow<'r, SearchOutput> {
+        let mut result = match self.column_mapper() {
+            Some(mapper) => {
+                let mut converted = result.clone();
+                let byte_col = (result.col as usize).saturating_sub(1);
+                converted.col = convert_col(&result.content, byte_col, mapper.unit()) as u64 + 1;
+                converted.end_col = result.end_col.map(|end| {
+                    let byte_col = (end as usize).saturating_sub(1);
+                    convert_col(&result.content, byte_col, mapper.unit()) as u64 + 1
+                });
+                Cow::Owned(converted)
+            }
+            None => Cow::Borrowed(result),
+        };
+        if let Some(stamp) = self.stamp(&result.file) {
+            result.to_mut().stamp = Some(stamp);
+        }
+        result
+    }
+
     /// Format a single text-search result.
     pub fn format_search_result(&mut self, result: &SearchOutput) -> std::io::Result<BudgetStatus> {
+        let result = self.convert_search_cols(result);
+        let result = result.as_ref();
         if !self.has_budget() {
             // Fast path: write directly, no temp buffer or clone needed.
             Self::render_search_result(self, result)?;
@@ -1084,16 +2111,120 @@ impl<W: Write> Formatter<W> {
             fmt.write_sep()?;
             fmt.write_line_no(result.line)?;
             fmt.write_sep()?;
-            fmt.write_content(&result.content)?;
+            fmt.write_content(&result.content, result.match_span())?;
             if let Some(ref ann) = result.annotation {
                 write!(fmt.writer, "  {ann}")?;
             }
+            if !result.patterns.is_empty() {
+                write!(fmt.writer, "  [{}]", result.patterns.join(", "))?;
+            }
             writeln!(fmt.writer)
         }
     }
 
+    /// Format a group of ranked search results.  Under a budget the group
+    /// is written whole or skipped.
+    pub fn format_search_group(
+        &mut self,
+        group: &SearchGroupOutput,
+    ) -> std::io::Result<BudgetStatus> {
+        let mut group = group.clone();
+        for result in &mut group.results {
+            if let Cow::Owned(converted) = self.convert_search_cols(result) {
+                *result = converted;
+            }
+        }
+        if !self.has_budget() {
+            Self::render_search_group(self, &group)?;
+            return Ok(BudgetStatus::Written);
+        }
+        self.budgeted_write(move |fmt| Self::render_search_group(fmt, &group))
+    }
+
+    /// Shared render logic for a search result group.
+    fn render_search_group<W2: Write>(
+        fmt: &mut Formatter<W2>,
+        group: &SearchGroupOutput,
+    ) -> std::io::Result<()> {
+        if fmt.format.is_structured() {
+            let line = Self::serialize_structured(fmt.format, group)?;
+            writeln!(fmt.writer, "{line}")
+        } else {
+            writeln!(fmt.writer, "-- {} --", group.label)?;
+            for result in &group.results {
+                Self::render_search_result(fmt, result)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Format one directory cluster of search results.
+    pub fn format_search_cluster(
+        &mut self,
+        cluster: &SearchClusterOutput,
+    ) -> std::io::Result<BudgetStatus> {
+        let mut cluster = cluster.clone();
+        for result in &mut cluster.results {
+            if let Cow::Owned(converted) = self.convert_search_cols(result) {
+                *result = converted;
+            }
+        }
+        if !self.has_budget() {
+            Self::render_search_cluster(self, &cluster)?;
+            return Ok(BudgetStatus::Written);
+        }
+        self.budgeted_write(move |fmt| Self::render_search_cluster(fmt, &cluster))
+    }
+
+    /// Shared render logic for a directory cluster: a `-- dir/ (N hits) --`
+    /// header in grep mode, noting how many are shown when cut.
+    fn render_search_cluster<W2: Write>(
+        fmt: &mut Formatter<W2>,
+        cluster: &SearchClusterOutput,
+    ) -> std::io::Result<()> {
+        if fmt.format.is_structured() {
+            let line = Self::serialize_structured(fmt.format, cluster)?;
+            return writeln!(fmt.writer, "{line}");
+        }
+        let dir = if cluster.dir == "." {
+            "./".to_string()
+        } else {
+            format!("{}/", cluster.dir)
+        };
+        let hits = if cluster.count == 1 { "hit" } else { "hits" };
+        if cluster.results.len() < cluster.count {
+            writeln!(
+                fmt.writer,
+                "-- {dir} ({} {hits}, showing {}) --",
+                cluster.count,
+                cluster.results.len()
+            )?;
+        } else {
+            writeln!(fmt.writer, "-- {dir} ({} {hits}) --", cluster.count)?;
+        }
+        for result in &cluster.results {
+            Self::render_search_result(fmt, result)?;
+        }
+        Ok(())
+    }
+
     /// Format a single symbol definition result.
     pub fn format_symbol(&mut self, sym: &SymbolOutput) -> std::io::Result<BudgetStatus> {
+        let mut sym = match self.column_mapper() {
+            Some(mapper) => {
+                let mut converted = sym.clone();
+                converted.col = mapper.map(&sym.file, sym.line, sym.col);
+                if let (Some(end_line), Some(end_col)) = (sym.end_line, sym.end_col) {
+                    converted.end_col = Some(mapper.map(&sym.file, end_line, end_col));
+                }
+                Cow::Owned(converted)
+            }
+            None => Cow::Borrowed(sym),
+        };
+        if let Some(stamp) = self.stamp(&sym.file) {
+            sym.to_mut().stamp = Some(stamp);
+        }
+        let sym = sym.as_ref();
         if !self.has_budget() {
             Self::render_symbol(self, sym)?;
             return Ok(BudgetStatus::Written);
@@ -1115,12 +2246,63 @@ impl<W: Write> Formatter<W> {
             fmt.write_sep()?;
             fmt.write_line_no(sym.line)?;
             fmt.write_sep()?;
-            writeln!(fmt.writer, "  {}", sym.signature)
+            write!(fmt.writer, "  {}", sym.signature)?;
+            if let Some(condition) = &sym.condition {
+                write!(fmt.writer, "  [#if {condition}]")?;
+            }
+            write_freshness(&mut fmt.writer, sym.freshness.as_deref())
+        }
+    }
+
+    /// Format a `wonk global sym` result.  Grep mode prints the absolute
+    /// path, so hits from every repository can be opened as they are, and
+    /// labels the line with the repository name.
+    pub fn format_global_symbol(
+        &mut self,
+        out: &GlobalSymbolOutput,
+    ) -> std::io::Result<BudgetStatus> {
+        if !self.has_budget() {
+            Self::render_global_symbol(self, out)?;
+            return Ok(BudgetStatus::Written);
+        }
+        let out = out.clone();
+        self.budgeted_write(move |fmt| Self::render_global_symbol(fmt, &out))
+    }
+
+    /// Shared render logic for a `wonk global sym` result.
+    fn render_global_symbol<W2: Write>(
+        fmt: &mut Formatter<W2>,
+        out: &GlobalSymbolOutput,
+    ) -> std::io::Result<()> {
+        if fmt.format.is_structured() {
+            let line = Self::serialize_structured(fmt.format, out)?;
+            return writeln!(fmt.writer, "{line}");
         }
+        let path = Path::new(&out.repo_path).join(&out.symbol.file);
+        fmt.write_file(&path.to_string_lossy())?;
+        fmt.write_sep()?;
+        fmt.write_line_no(out.symbol.line)?;
+        fmt.write_sep()?;
+        writeln!(fmt.writer, "  {}  [{}]", out.symbol.signature, out.repo)
     }
 
     /// Format a single reference result.
     pub fn format_reference(&mut self, reference: &RefOutput) -> std::io::Result<BudgetStatus> {
+        let mut reference = match self.column_mapper() {
+            Some(mapper) => {
+                let mut converted = reference.clone();
+                converted.col = mapper.map(&reference.file, reference.line, reference.col);
+                converted.end_col = reference
+                    .end_col
+                    .map(|end| mapper.map(&reference.file, reference.line, end));
+                Cow::Owned(converted)
+            }
+            None => Cow::Borrowed(reference),
+        };
+        if let Some(stamp) = self.stamp(&reference.file) {
+            reference.to_mut().stamp = Some(stamp);
+        }
+        let reference = reference.as_ref();
         if !self.has_budget() {
             Self::render_reference(self, reference)?;
             return Ok(BudgetStatus::Written);
@@ -1142,12 +2324,60 @@ impl<W: Write> Formatter<W> {
             fmt.write_sep()?;
             fmt.write_line_no(reference.line)?;
             fmt.write_sep()?;
-            writeln!(fmt.writer, "{}", reference.context)
+            write!(fmt.writer, "{}", reference.context)?;
+            if let Some(def) = &reference.definition {
+                write!(fmt.writer, "  -> {}:{}", def.file, def.line)?;
+            }
+            write_freshness(&mut fmt.writer, reference.freshness.as_deref())
         }
     }
 
+    /// Format a single `wonk find` result.
+    pub fn format_find(&mut self, out: &FindOutput) -> std::io::Result<BudgetStatus> {
+        let mut out = match self.column_mapper() {
+            Some(mapper) => {
+                let mut converted = out.clone();
+                converted.col = mapper.map(&out.file, out.line, out.col);
+                Cow::Owned(converted)
+            }
+            None => Cow::Borrowed(out),
+        };
+        if let Some(stamp) = self.stamp(&out.file) {
+            out.to_mut().stamp = Some(stamp);
+        }
+        let out = out.as_ref();
+        if !self.has_budget() {
+            Self::render_find(self, out)?;
+            return Ok(BudgetStatus::Written);
+        }
+        let out = out.clone();
+        self.budgeted_write(move |fmt| Self::render_find(fmt, &out))
+    }
+
+    /// Shared render logic for a `wonk find` result.
+    fn render_find<W2: Write>(fmt: &mut Formatter<W2>, out: &FindOutput) -> std::io::Result<()> {
+        if fmt.format.is_structured() {
+            let line = Self::serialize_structured(fmt.format, out)?;
+            return writeln!(fmt.writer, "{line}");
+        }
+        fmt.write_file(&out.file)?;
+        fmt.write_sep()?;
+        fmt.write_line_no(out.line)?;
+        fmt.write_sep()?;
+        write!(fmt.writer, "  {}", out.content)?;
+        if let Some(caller) = &out.caller_name {
+            write!(fmt.writer, "  (in {caller})")?;
+        }
+        writeln!(fmt.writer)
+    }
+
     /// Format a single signature result.
     pub fn format_signature(&mut self, sig: &SignatureOutput) -> std::io::Result<BudgetStatus> {
+        let mut sig = Cow::Borrowed(sig);
+        if let Some(stamp) = self.stamp(&sig.file) {
+            sig.to_mut().stamp = Some(stamp);
+        }
+        let sig = sig.as_ref();
         if !self.has_budget() {
             Self::render_signature(self, sig)?;
             return Ok(BudgetStatus::Written);
@@ -1169,7 +2399,8 @@ impl<W: Write> Formatter<W> {
             fmt.write_sep()?;
             fmt.write_line_no(sig.line)?;
             fmt.write_sep()?;
-            writeln!(fmt.writer, "  {}", sig.signature)
+            write!(fmt.writer, "  {}", sig.signature)?;
+            write_freshness(&mut fmt.writer, sig.freshness.as_deref())
         }
     }
 
@@ -1220,134 +2451,828 @@ impl<W: Write> Formatter<W> {
         }
     }
 
-    /// Format a single semantic search result.
-    pub fn format_semantic_result(
+    /// Format a collapsed generated-code summary entry.
+    pub fn format_generated_group(
         &mut self,
-        result: &SemanticOutput,
+        group: &GeneratedGroupOutput,
     ) -> std::io::Result<BudgetStatus> {
         if !self.has_budget() {
-            Self::render_semantic_result(self, result)?;
+            Self::render_generated_group(self, group)?;
             return Ok(BudgetStatus::Written);
         }
-        let result = result.clone();
-        self.budgeted_write(move |fmt| Self::render_semantic_result(fmt, &result))
+        let group = group.clone();
+        self.budgeted_write(move |fmt| Self::render_generated_group(fmt, &group))
     }
 
-    /// Shared render logic for a semantic search result.
-    fn render_semantic_result<W2: Write>(
+    fn render_generated_group<W2: Write>(
         fmt: &mut Formatter<W2>,
-        result: &SemanticOutput,
+        group: &GeneratedGroupOutput,
     ) -> std::io::Result<()> {
         if fmt.format.is_structured() {
-            let line = Self::serialize_structured(fmt.format, result)?;
+            let line = Self::serialize_structured(fmt.format, group)?;
             writeln!(fmt.writer, "{line}")
         } else {
-            fmt.write_file(&result.file)?;
-            fmt.write_sep()?;
-            fmt.write_line_no(result.line)?;
-            fmt.write_sep()?;
+            let files = if group.files == 1 { "file" } else { "files" };
             writeln!(
                 fmt.writer,
-                "  {} ({}) [{:.4}]",
-                result.symbol_name, result.symbol_kind, result.similarity_score
+                "[generated by {}] {} hits in {} {files}",
+                group.generated_by, group.hits, group.files
             )
         }
     }
 
-    /// Format a truncation metadata object (structured mode only).
-    ///
-    /// Emits a final line with truncation info when `--budget` truncates
-    /// output. In grep mode, callers should use [`print_budget_summary`] instead.
-    pub fn format_truncation_meta(&mut self, meta: &TruncationMeta) -> std::io::Result<()> {
-        let line = Self::serialize_structured(self.format, meta)?;
-        writeln!(self.writer, "{line}")
+    /// Format a single `wonk langs` row.
+    pub fn format_lang_stats(&mut self, out: &LangStatsOutput) -> std::io::Result<BudgetStatus> {
+        if !self.has_budget() {
+            Self::render_lang_stats(self, out)?;
+            return Ok(BudgetStatus::Written);
+        }
+        let out = out.clone();
+        self.budgeted_write(move |fmt| Self::render_lang_stats(fmt, &out))
     }
 
-    /// Format a single cluster member (representative symbol).
-    pub fn format_cluster_member(
-        &mut self,
-        member: &ClusterMemberOutput,
-    ) -> std::io::Result<BudgetStatus> {
+    /// Format a `wonk examples` recipe (or a topic line when it has no steps).
+    pub fn format_recipe(&mut self, out: &RecipeOutput) -> std::io::Result<BudgetStatus> {
         if !self.has_budget() {
-            Self::render_cluster_member(self, member)?;
+            Self::render_recipe(self, out)?;
             return Ok(BudgetStatus::Written);
         }
-        let member = member.clone();
-        self.budgeted_write(move |fmt| Self::render_cluster_member(fmt, &member))
+        let out = out.clone();
+        self.budgeted_write(move |fmt| Self::render_recipe(fmt, &out))
     }
 
-    /// Shared render logic for a cluster member.
-    fn render_cluster_member<W2: Write>(
+    /// Shared render logic for a `wonk examples` recipe.
+    ///
+    /// Grep mode lists `topic  title`, or the title followed by each step as
+    /// a `# description` comment over a copyable command line.
+    fn render_recipe<W2: Write>(
         fmt: &mut Formatter<W2>,
-        member: &ClusterMemberOutput,
+        out: &RecipeOutput,
     ) -> std::io::Result<()> {
         if fmt.format.is_structured() {
-            let line = Self::serialize_structured(fmt.format, member)?;
-            writeln!(fmt.writer, "{line}")
-        } else {
-            fmt.write_file(&member.file)?;
-            fmt.write_sep()?;
-            fmt.write_line_no(member.line)?;
-            fmt.write_sep()?;
-            writeln!(
-                fmt.writer,
-                "  {} ({}) [{:.4}]",
-                member.symbol_name, member.symbol_kind, member.distance_to_centroid
-            )
+            let line = Self::serialize_structured(fmt.format, out)?;
+            return writeln!(fmt.writer, "{line}");
         }
-    }
-
-    /// Format a full cluster (structured modes only: JSON/TOON).
-    ///
-    /// In grep mode, callers should use [`print_cluster_header`] for the
-    /// header and [`format_cluster_member`] for each representative.
-    pub fn format_cluster(&mut self, cluster: &ClusterOutput) -> std::io::Result<BudgetStatus> {
-        if !self.format.is_structured() {
-            return Ok(BudgetStatus::Written);
+        if out.steps.is_empty() {
+            return writeln!(fmt.writer, "{:<10} {}", out.topic, out.title);
         }
-        if !self.has_budget() {
-            let line = Self::serialize_structured(self.format, cluster)?;
-            writeln!(self.writer, "{line}")?;
-            return Ok(BudgetStatus::Written);
+        writeln!(fmt.writer, "{}", out.title)?;
+        for step in &out.steps {
+            writeln!(fmt.writer)?;
+            writeln!(fmt.writer, "  # {}", step.description)?;
+            writeln!(fmt.writer, "  {}", step.command)?;
         }
-        let cluster = cluster.clone();
-        self.budgeted_write(move |fmt| {
-            let line = Self::serialize_structured(fmt.format, &cluster)?;
-            writeln!(fmt.writer, "{line}")
-        })
+        Ok(())
     }
 
-    /// Format a single impact entry line (impacted symbol with similarity score).
-    pub fn format_impact_entry(
+    /// Format the `wonk init --profile` report.
+    pub fn format_index_profile(
         &mut self,
-        entry: &ImpactEntryOutput,
+        out: &IndexProfileOutput,
     ) -> std::io::Result<BudgetStatus> {
         if !self.has_budget() {
-            Self::render_impact_entry(self, entry)?;
-            return Ok(BudgetStatus::Written);
-        }
-        let entry = entry.clone();
-        self.budgeted_write(move |fmt| Self::render_impact_entry(fmt, &entry))
-    }
-
-    /// Format a full impact group (changed symbol + impacted entries) for structured output.
-    pub fn format_impact(&mut self, out: &ImpactOutput) -> std::io::Result<BudgetStatus> {
-        if !self.has_budget() {
-            let line = Self::serialize_structured(self.format, out)?;
-            writeln!(self.writer, "{line}")?;
+            Self::render_index_profile(self, out)?;
             return Ok(BudgetStatus::Written);
         }
         let out = out.clone();
-        self.budgeted_write(move |fmt| {
-            let line = Self::serialize_structured(fmt.format, &out)?;
-            writeln!(fmt.writer, "{line}")
-        })
+        self.budgeted_write(move |fmt| Self::render_index_profile(fmt, &out))
     }
 
-    /// Shared render logic for an impact entry.
-    fn render_impact_entry<W2: Write>(
+    /// Shared render logic for `wonk init --profile`.
+    ///
+    /// Grep mode prints the build phases, then a per-language table and the
+    /// slowest files, each sorted by parse time.
+    fn render_index_profile<W2: Write>(
         fmt: &mut Formatter<W2>,
-        entry: &ImpactEntryOutput,
+        out: &IndexProfileOutput,
+    ) -> std::io::Result<()> {
+        if fmt.format.is_structured() {
+            let line = Self::serialize_structured(fmt.format, out)?;
+            return writeln!(fmt.writer, "{line}");
+        }
+        let secs = |ms: f64| format!("{:.2}s", ms / 1000.0);
+        writeln!(
+            fmt.writer,
+            "Index profile: {} files in {}",
+            out.files,
+            secs(out.total_ms)
+        )?;
+        writeln!(fmt.writer, "  walk     {:>9}", secs(out.walk_ms))?;
+        writeln!(
+            fmt.writer,
+            "  parse    {:>9}  ({} summed across threads)",
+            secs(out.parse_ms),
+            secs(out.parse_cpu_ms)
+        )?;
+        writeln!(fmt.writer, "  insert   {:>9}", secs(out.insert_ms))?;
+        writeln!(fmt.writer, "  resolve  {:>9}", secs(out.resolve_ms))?;
+
+        if !out.languages.is_empty() {
+            writeln!(fmt.writer)?;
+            writeln!(fmt.writer, "Parse time by language:")?;
+            for l in &out.languages {
+                let avg = l.parse_ms / l.files.max(1) as f64;
+                writeln!(
+                    fmt.writer,
+                    "  {:<12} {:>9} {:>5.1}%  {:>6} files {:>9} lines  avg {avg:.1}ms",
+                    l.language,
+                    secs(l.parse_ms),
+                    l.percent,
+                    l.files,
+                    l.lines
+                )?;
+            }
+        }
+        if !out.slowest_files.is_empty() {
+            writeln!(fmt.writer)?;
+            writeln!(fmt.writer, "Slowest files:")?;
+            for f in &out.slowest_files {
+                writeln!(
+                    fmt.writer,
+                    "  {:>8.1}ms  {:<12} {} ({} lines)",
+                    f.parse_ms, f.language, f.path, f.lines
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Format the `wonk diag` bundle.
+    pub fn format_diag(&mut self, out: &DiagOutput) -> std::io::Result<BudgetStatus> {
+        if !self.has_budget() {
+            Self::render_diag(self, out)?;
+            return Ok(BudgetStatus::Written);
+        }
+        let out = out.clone();
+        self.budgeted_write(move |fmt| Self::render_diag(fmt, &out))
+    }
+
+    /// Shared render logic for `wonk diag`.
+    ///
+    /// Grep mode prints the environment as `key: value` lines, then each
+    /// crash report under a `==> path <==` header.
+    fn render_diag<W2: Write>(fmt: &mut Formatter<W2>, out: &DiagOutput) -> std::io::Result<()> {
+        if fmt.format.is_structured() {
+            let line = Self::serialize_structured(fmt.format, out)?;
+            return writeln!(fmt.writer, "{line}");
+        }
+        writeln!(fmt.writer, "version: {}", out.version)?;
+        writeln!(fmt.writer, "platform: {}", out.platform)?;
+        match &out.index {
+            Some(stats) => {
+                writeln!(fmt.writer, "index: {}", stats.summary())?;
+                writeln!(fmt.writer, "languages: {}", stats.languages.join(", "))?;
+            }
+            None => writeln!(fmt.writer, "index: none")?,
+        }
+        writeln!(
+            fmt.writer,
+            "crash reports: {} ({} included)",
+            out.crash_count,
+            out.crash_reports.len()
+        )?;
+        for report in &out.crash_reports {
+            writeln!(fmt.writer)?;
+            writeln!(fmt.writer, "==> {} <==", report.path)?;
+            write!(fmt.writer, "{}", report.contents)?;
+            if !report.contents.ends_with('\n') {
+                writeln!(fmt.writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Format the extraction results of `wonk parse`.
+    pub fn format_parse(&mut self, out: &ParseOutput) -> std::io::Result<BudgetStatus> {
+        if !self.has_budget() {
+            Self::render_parse(self, out)?;
+            return Ok(BudgetStatus::Written);
+        }
+        let out = out.clone();
+        self.budgeted_write(move |fmt| Self::render_parse(fmt, &out))
+    }
+
+    /// Shared render logic for `wonk parse`.
+    ///
+    /// The output is JSON in every mode: grep mode pretty-prints it for
+    /// reading, structured modes emit one record.
+    fn render_parse<W2: Write>(fmt: &mut Formatter<W2>, out: &ParseOutput) -> std::io::Result<()> {
+        let text = if fmt.format.is_structured() {
+            Self::serialize_structured(fmt.format, out)?
+        } else {
+            serde_json::to_string_pretty(out).map_err(std::io::Error::other)?
+        };
+        writeln!(fmt.writer, "{text}")
+    }
+
+    /// Format the syntax tree printed by `wonk tree-dump`.
+    pub fn format_tree_dump(&mut self, out: &TreeDumpOutput) -> std::io::Result<BudgetStatus> {
+        if !self.has_budget() {
+            Self::render_tree_dump(self, out)?;
+            return Ok(BudgetStatus::Written);
+        }
+        let out = out.clone();
+        self.budgeted_write(move |fmt| Self::render_tree_dump(fmt, &out))
+    }
+
+    /// Shared render logic for `wonk tree-dump`.
+    ///
+    /// Grep mode indents each node two spaces per level as
+    /// `field: kind [line:col - line:col] "text"`; `--sexp` output is one
+    /// s-expression per line.
+    fn render_tree_dump<W2: Write>(
+        fmt: &mut Formatter<W2>,
+        out: &TreeDumpOutput,
+    ) -> std::io::Result<()> {
+        if fmt.format.is_structured() {
+            let line = Self::serialize_structured(fmt.format, out)?;
+            return writeln!(fmt.writer, "{line}");
+        }
+        if let Some(sexps) = &out.sexp {
+            for sexp in sexps {
+                writeln!(fmt.writer, "{sexp}")?;
+            }
+            return Ok(());
+        }
+        for node in &out.nodes {
+            write!(fmt.writer, "{}", "  ".repeat(node.depth))?;
+            if let Some(field) = &node.field {
+                write!(fmt.writer, "{field}: ")?;
+            }
+            if node.named {
+                write!(fmt.writer, "{}", node.kind)?;
+            } else {
+                write!(fmt.writer, "{:?}", node.kind)?;
+            }
+            if node.missing {
+                write!(fmt.writer, " (missing)")?;
+            }
+            write!(
+                fmt.writer,
+                " [{}:{} - {}:{}]",
+                node.start_line, node.start_col, node.end_line, node.end_col
+            )?;
+            match &node.text {
+                Some(text) if node.named => writeln!(fmt.writer, " {text:?}")?,
+                _ => writeln!(fmt.writer)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Format the result of `wonk snapshot`.
+    pub fn format_snapshot(&mut self, out: &SnapshotOutput) -> std::io::Result<BudgetStatus> {
+        if !self.has_budget() {
+            Self::render_snapshot(self, out)?;
+            return Ok(BudgetStatus::Written);
+        }
+        let out = out.clone();
+        self.budgeted_write(move |fmt| Self::render_snapshot(fmt, &out))
+    }
+
+    /// Shared render logic for `wonk snapshot`.
+    fn render_snapshot<W2: Write>(
+        fmt: &mut Formatter<W2>,
+        out: &SnapshotOutput,
+    ) -> std::io::Result<()> {
+        if fmt.format.is_structured() {
+            let line = Self::serialize_structured(fmt.format, out)?;
+            return writeln!(fmt.writer, "{line}");
+        }
+        writeln!(
+            fmt.writer,
+            "Saved {} entries from `wonk {}` to {}",
+            out.entries,
+            out.query.join(" "),
+            out.path
+        )
+    }
+
+    /// Format the result of `wonk compare`.
+    pub fn format_compare(&mut self, out: &CompareOutput) -> std::io::Result<BudgetStatus> {
+        if !self.has_budget() {
+            Self::render_compare(self, out)?;
+            return Ok(BudgetStatus::Written);
+        }
+        let out = out.clone();
+        self.budgeted_write(move |fmt| Self::render_compare(fmt, &out))
+    }
+
+    /// Shared render logic for `wonk compare`.
+    ///
+    /// Grep mode prints a count summary, then one `+ record` / `- record`
+    /// line per added or removed record.
+    fn render_compare<W2: Write>(
+        fmt: &mut Formatter<W2>,
+        out: &CompareOutput,
+    ) -> std::io::Result<()> {
+        if fmt.format.is_structured() {
+            let line = Self::serialize_structured(fmt.format, out)?;
+            return writeln!(fmt.writer, "{line}");
+        }
+        writeln!(
+            fmt.writer,
+            "{}: {} -> {} entries (+{}, -{})",
+            out.name,
+            out.before,
+            out.after,
+            out.added.len(),
+            out.removed.len()
+        )?;
+        let changes = out
+            .added
+            .iter()
+            .map(|e| ('+', color::ADDED, e))
+            .chain(out.removed.iter().map(|e| ('-', color::REMOVED, e)));
+        for (marker, code, entry) in changes {
+            let text = crate::snapshot::describe(entry);
+            if fmt.color {
+                writeln!(fmt.writer, "{code}{marker} {text}{}", color::RESET)?;
+            } else {
+                writeln!(fmt.writer, "{marker} {text}")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Format one `wonk gate` check.
+    pub fn format_gate_check(&mut self, out: &GateCheckOutput) -> std::io::Result<BudgetStatus> {
+        if !self.has_budget() {
+            Self::render_gate_check(self, out)?;
+            return Ok(BudgetStatus::Written);
+        }
+        let out = out.clone();
+        self.budgeted_write(move |fmt| Self::render_gate_check(fmt, &out))
+    }
+
+    /// Shared render logic for a `wonk gate` check: an `ok`/`FAIL` line,
+    /// followed by the records a `no_new` check found beyond its baseline.
+    fn render_gate_check<W2: Write>(
+        fmt: &mut Formatter<W2>,
+        out: &GateCheckOutput,
+    ) -> std::io::Result<()> {
+        if fmt.format.is_structured() {
+            let line = Self::serialize_structured(fmt.format, out)?;
+            return writeln!(fmt.writer, "{line}");
+        }
+        let label = if out.passed { "ok" } else { "FAIL" };
+        match (out.limit, out.baseline) {
+            (Some(limit), _) => writeln!(
+                fmt.writer,
+                "{label} {}: {} results (max {limit})",
+                out.name, out.count
+            )?,
+            (None, Some(baseline)) => writeln!(
+                fmt.writer,
+                "{label} {}: {} new results (baseline {baseline}, now {})",
+                out.name,
+                out.added.len(),
+                out.count
+            )?,
+            (None, None) => writeln!(
+                fmt.writer,
+                "{label} {}: no baseline; run `wonk gate --update-baseline`",
+                out.name
+            )?,
+        }
+        for entry in &out.added {
+            let text = crate::snapshot::describe(entry);
+            if fmt.color {
+                writeln!(fmt.writer, "  {}+ {text}{}", color::ADDED, color::RESET)?;
+            } else {
+                writeln!(fmt.writer, "  + {text}")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Format the result of `wonk which-index`.
+    pub fn format_describe(&mut self, out: &DescribeOutput) -> std::io::Result<BudgetStatus> {
+        if !self.has_budget() {
+            Self::render_describe(self, out)?;
+            return Ok(BudgetStatus::Written);
+        }
+        let out = out.clone();
+        self.budgeted_write(move |fmt| Self::render_describe(fmt, &out))
+    }
+
+    /// Shared render logic for `wonk describe`.
+    ///
+    /// Grep mode prints a one-line overview, the imports and exports, then
+    /// one `file:line:  signature` line per listed symbol.
+    fn render_describe<W2: Write>(
+        fmt: &mut Formatter<W2>,
+        out: &DescribeOutput,
+    ) -> std::io::Result<()> {
+        if fmt.format.is_structured() {
+            let line = Self::serialize_structured(fmt.format, out)?;
+            return writeln!(fmt.writer, "{line}");
+        }
+        let role = match &out.generated_by {
+            Some(generator) => format!("generated by {generator}"),
+            None => out.role.clone(),
+        };
+        fmt.write_file(&out.file)?;
+        writeln!(
+            fmt.writer,
+            ": {}, {} lines, {} symbols, {role}",
+            out.language, out.lines, out.symbol_count
+        )?;
+        if !out.imports.is_empty() {
+            writeln!(fmt.writer, "imports: {}", out.imports.join(", "))?;
+        }
+        if !out.exports.is_empty() {
+            writeln!(fmt.writer, "exports: {}", out.exports.join(", "))?;
+        }
+        for sym in &out.symbols {
+            fmt.write_file(&out.file)?;
+            fmt.write_sep()?;
+            fmt.write_line_no(sym.line)?;
+            fmt.write_sep()?;
+            if sym.signature.is_empty() {
+                write!(fmt.writer, "  {} {}", sym.kind, sym.name)?;
+            } else {
+                write!(fmt.writer, "  {}", sym.signature)?;
+            }
+            match sym.uses {
+                0 => writeln!(fmt.writer)?,
+                1 => writeln!(fmt.writer, "  (1 use)")?,
+                n => writeln!(fmt.writer, "  ({n} uses)")?,
+            }
+        }
+        Ok(())
+    }
+
+    pub fn format_near(&mut self, out: &NearOutput) -> std::io::Result<BudgetStatus> {
+        if !self.has_budget() {
+            Self::render_near(self, out)?;
+            return Ok(BudgetStatus::Written);
+        }
+        let out = out.clone();
+        self.budgeted_write(move |fmt| Self::render_near(fmt, &out))
+    }
+
+    /// Shared render logic for `wonk near`.
+    ///
+    /// Grep mode prints the enclosing symbol and its siblings as
+    /// `file:line:  signature` lines, then references as `file:line:  name`
+    /// lines and the file's deps and rdeps as `key: a, b` lines.
+    fn render_near<W2: Write>(fmt: &mut Formatter<W2>, out: &NearOutput) -> std::io::Result<()> {
+        if fmt.format.is_structured() {
+            let line = Self::serialize_structured(fmt.format, out)?;
+            return writeln!(fmt.writer, "{line}");
+        }
+        let arrow = fmt.glyphs.arrow;
+        let write_symbol = |fmt: &mut Formatter<W2>, label: &str, sym: &NearSymbolOutput| {
+            fmt.write_file(&out.file)?;
+            fmt.write_sep()?;
+            fmt.write_line_no(sym.line)?;
+            fmt.write_sep()?;
+            let text = if sym.signature.is_empty() {
+                format!("{} {}", sym.kind, sym.name)
+            } else {
+                sym.signature.clone()
+            };
+            writeln!(fmt.writer, "  {text}  [{label}]")
+        };
+        match &out.enclosing {
+            Some(sym) => write_symbol(fmt, "enclosing", sym)?,
+            None => writeln!(fmt.writer, "{}:{}: top level", out.file, out.line)?,
+        }
+        for sym in &out.siblings {
+            write_symbol(fmt, "sibling", sym)?;
+        }
+        for r in &out.references {
+            fmt.write_file(&out.file)?;
+            fmt.write_sep()?;
+            fmt.write_line_no(r.line)?;
+            fmt.write_sep()?;
+            match &r.definition {
+                Some(def) => writeln!(fmt.writer, "  {} {arrow} {def}  [ref]", r.name)?,
+                None => writeln!(fmt.writer, "  {}  [ref]", r.name)?,
+            }
+        }
+        if !out.deps.is_empty() {
+            writeln!(fmt.writer, "deps: {}", out.deps.join(", "))?;
+        }
+        if !out.rdeps.is_empty() {
+            writeln!(fmt.writer, "rdeps: {}", out.rdeps.join(", "))?;
+        }
+        Ok(())
+    }
+
+    pub fn format_which_index(&mut self, out: &WhichIndexOutput) -> std::io::Result<BudgetStatus> {
+        if !self.has_budget() {
+            Self::render_which_index(self, out)?;
+            return Ok(BudgetStatus::Written);
+        }
+        let out = out.clone();
+        self.budgeted_write(move |fmt| Self::render_which_index(fmt, &out))
+    }
+
+    /// Shared render logic for `wonk which-index`.
+    ///
+    /// Grep mode prints one `key: value` line per known fact, then both
+    /// candidate locations.
+    fn render_which_index<W2: Write>(
+        fmt: &mut Formatter<W2>,
+        out: &WhichIndexOutput,
+    ) -> std::io::Result<()> {
+        if fmt.format.is_structured() {
+            let line = Self::serialize_structured(fmt.format, out)?;
+            return writeln!(fmt.writer, "{line}");
+        }
+        let short = |commit: &str| commit.chars().take(12).collect::<String>();
+        match (&out.index, &out.location) {
+            (Some(index), Some(location)) => writeln!(fmt.writer, "index: {index} ({location})")?,
+            _ => writeln!(fmt.writer, "index: none")?,
+        }
+        writeln!(fmt.writer, "reason: {}", out.reason)?;
+        if let Some(built) = out.built {
+            let age = crate::daemon::format_uptime(Some(built as i64));
+            match &out.wonk_version {
+                Some(version) => writeln!(fmt.writer, "built: {age} ago by wonk {version}")?,
+                None => writeln!(fmt.writer, "built: {age} ago")?,
+            }
+        }
+        match (&out.commit, &out.head) {
+            (Some(commit), Some(head)) if commit != head => writeln!(
+                fmt.writer,
+                "commit: {} (HEAD is now {}; run `wonk update` if results look stale)",
+                short(commit),
+                short(head)
+            )?,
+            (Some(commit), _) => writeln!(fmt.writer, "commit: {}", short(commit))?,
+            (None, _) if out.index.is_some() => {
+                writeln!(fmt.writer, "commit: unknown (not recorded by this index)")?
+            }
+            (None, _) => {}
+        }
+        if !out.languages.is_empty() {
+            writeln!(fmt.writer, "languages: {}", out.languages.join(", "))?;
+        }
+        if !out.disabled_languages.is_empty() {
+            writeln!(
+                fmt.writer,
+                "disabled grammars: {}",
+                out.disabled_languages.join(", ")
+            )?;
+        }
+        if out.partial {
+            writeln!(
+                fmt.writer,
+                "partial: yes (the build was interrupted or lazy; run `wonk update` to finish it)"
+            )?;
+        }
+        if out.location.as_deref() == Some("local") {
+            match &out.symbol_index {
+                Some(central) => writeln!(
+                    fmt.writer,
+                    "note: sym, ref, sig, deps, and rdeps read the central index {central}"
+                )?,
+                None => writeln!(
+                    fmt.writer,
+                    "note: sym, ref, sig, deps, and rdeps read the central index, which does not exist, so they use grep"
+                )?,
+            }
+        }
+        let state = |exists: bool| if exists { "exists" } else { "missing" };
+        writeln!(fmt.writer, "candidates:")?;
+        writeln!(
+            fmt.writer,
+            "  local    {} ({})",
+            out.local_path,
+            state(out.local_exists)
+        )?;
+        if let Some(central) = &out.central_path {
+            writeln!(
+                fmt.writer,
+                "  central  {central} ({})",
+                state(out.central_exists)
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Format the result of `wonk self update`.
+    pub fn format_self_update(&mut self, out: &SelfUpdateOutput) -> std::io::Result<BudgetStatus> {
+        if !self.has_budget() {
+            Self::render_self_update(self, out)?;
+            return Ok(BudgetStatus::Written);
+        }
+        let out = out.clone();
+        self.budgeted_write(move |fmt| Self::render_self_update(fmt, &out))
+    }
+
+    /// Shared render logic for `wonk self update`.
+    fn render_self_update<W2: Write>(
+        fmt: &mut Formatter<W2>,
+        out: &SelfUpdateOutput,
+    ) -> std::io::Result<()> {
+        if fmt.format.is_structured() {
+            let line = Self::serialize_structured(fmt.format, out)?;
+            return writeln!(fmt.writer, "{line}");
+        }
+        if out.updated {
+            writeln!(
+                fmt.writer,
+                "Updated wonk {} -> {}",
+                out.current_version, out.latest_version
+            )
+        } else if out.update_available {
+            writeln!(
+                fmt.writer,
+                "wonk {} is available (current: {}); run `wonk self update` to install it",
+                out.latest_version, out.current_version
+            )
+        } else {
+            writeln!(
+                fmt.writer,
+                "wonk {} is up to date ({} channel)",
+                out.current_version, out.channel
+            )
+        }
+    }
+
+    /// Shared render logic for a `wonk langs` row.
+    ///
+    /// Grep mode renders a fixed-width table row followed by a bar whose
+    /// length is proportional to the language's share of indexed lines.
+    fn render_lang_stats<W2: Write>(
+        fmt: &mut Formatter<W2>,
+        out: &LangStatsOutput,
+    ) -> std::io::Result<()> {
+        if fmt.format.is_structured() {
+            let line = Self::serialize_structured(fmt.format, out)?;
+            writeln!(fmt.writer, "{line}")
+        } else {
+            let filled =
+                ((out.percent / 100.0) * LangStatsOutput::BAR_WIDTH as f64).round() as usize;
+            let bar = fmt
+                .glyphs
+                .bar
+                .repeat(filled.min(LangStatsOutput::BAR_WIDTH));
+            writeln!(
+                fmt.writer,
+                "{:<12} {:>6} files {:>9} lines {:>7} symbols {:>8} refs  {:>5.1}% {}",
+                out.language, out.files, out.lines, out.symbols, out.references, out.percent, bar
+            )
+        }
+    }
+
+    /// Format a single semantic search result.
+    pub fn format_semantic_result(
+        &mut self,
+        result: &SemanticOutput,
+    ) -> std::io::Result<BudgetStatus> {
+        if !self.has_budget() {
+            Self::render_semantic_result(self, result)?;
+            return Ok(BudgetStatus::Written);
+        }
+        let result = result.clone();
+        self.budgeted_write(move |fmt| Self::render_semantic_result(fmt, &result))
+    }
+
+    /// Shared render logic for a semantic search result.
+    fn render_semantic_result<W2: Write>(
+        fmt: &mut Formatter<W2>,
+        result: &SemanticOutput,
+    ) -> std::io::Result<()> {
+        if fmt.format.is_structured() {
+            let line = Self::serialize_structured(fmt.format, result)?;
+            writeln!(fmt.writer, "{line}")
+        } else {
+            fmt.write_file(&result.file)?;
+            fmt.write_sep()?;
+            fmt.write_line_no(result.line)?;
+            fmt.write_sep()?;
+            writeln!(
+                fmt.writer,
+                "  {} ({}) [{:.4}]",
+                result.symbol_name, result.symbol_kind, result.similarity_score
+            )
+        }
+    }
+
+    /// Format a truncation metadata object (structured mode only).
+    ///
+    /// Emits a final line with truncation info when `--budget` truncates
+    /// output. In grep mode, callers should use [`print_budget_summary`] instead.
+    pub fn format_truncation_meta(&mut self, meta: &TruncationMeta) -> std::io::Result<()> {
+        let line = Self::serialize_structured(self.format, meta)?;
+        writeln!(self.writer, "{line}")
+    }
+
+    /// Format timeout metadata as a final line (JSON/TOON modes only).
+    ///
+    /// In grep mode, callers should use [`print_timeout_notice`] instead.
+    pub fn format_timeout_meta(&mut self, meta: &TimeoutMeta) -> std::io::Result<()> {
+        let line = Self::serialize_structured(self.format, meta)?;
+        writeln!(self.writer, "{line}")
+    }
+
+    /// Format interrupt metadata as a final line (JSON/TOON modes only).
+    ///
+    /// In grep mode, callers should use [`print_interrupt_notice`] instead.
+    pub fn format_interrupt_meta(&mut self, meta: &InterruptMeta) -> std::io::Result<()> {
+        let line = Self::serialize_structured(self.format, meta)?;
+        writeln!(self.writer, "{line}")
+    }
+
+    /// Format a hint, warning, or error record (JSON/TOON modes only).
+    ///
+    /// In grep mode, callers should use [`print_hint`], [`print_warning`],
+    /// or [`print_error`] instead.
+    pub fn format_message_meta(&mut self, meta: &MessageMeta) -> std::io::Result<()> {
+        let line = Self::serialize_structured(self.format, meta)?;
+        writeln!(self.writer, "{line}")
+    }
+
+    /// Format a single cluster member (representative symbol).
+    pub fn format_cluster_member(
+        &mut self,
+        member: &ClusterMemberOutput,
+    ) -> std::io::Result<BudgetStatus> {
+        if !self.has_budget() {
+            Self::render_cluster_member(self, member)?;
+            return Ok(BudgetStatus::Written);
+        }
+        let member = member.clone();
+        self.budgeted_write(move |fmt| Self::render_cluster_member(fmt, &member))
+    }
+
+    /// Shared render logic for a cluster member.
+    fn render_cluster_member<W2: Write>(
+        fmt: &mut Formatter<W2>,
+        member: &ClusterMemberOutput,
+    ) -> std::io::Result<()> {
+        if fmt.format.is_structured() {
+            let line = Self::serialize_structured(fmt.format, member)?;
+            writeln!(fmt.writer, "{line}")
+        } else {
+            fmt.write_file(&member.file)?;
+            fmt.write_sep()?;
+            fmt.write_line_no(member.line)?;
+            fmt.write_sep()?;
+            writeln!(
+                fmt.writer,
+                "  {} ({}) [{:.4}]",
+                member.symbol_name, member.symbol_kind, member.distance_to_centroid
+            )
+        }
+    }
+
+    /// Format a full cluster (structured modes only: JSON/TOON).
+    ///
+    /// In grep mode, callers should use [`print_cluster_header`] for the
+    /// header and [`format_cluster_member`] for each representative.
+    pub fn format_cluster(&mut self, cluster: &ClusterOutput) -> std::io::Result<BudgetStatus> {
+        if !self.format.is_structured() {
+            return Ok(BudgetStatus::Written);
+        }
+        if !self.has_budget() {
+            let line = Self::serialize_structured(self.format, cluster)?;
+            writeln!(self.writer, "{line}")?;
+            return Ok(BudgetStatus::Written);
+        }
+        let cluster = cluster.clone();
+        self.budgeted_write(move |fmt| {
+            let line = Self::serialize_structured(fmt.format, &cluster)?;
+            writeln!(fmt.writer, "{line}")
+        })
+    }
+
+    /// Format a single impact entry line (impacted symbol with similarity score).
+    pub fn format_impact_entry(
+        &mut self,
+        entry: &ImpactEntryOutput,
+    ) -> std::io::Result<BudgetStatus> {
+        if !self.has_budget() {
+            Self::render_impact_entry(self, entry)?;
+            return Ok(BudgetStatus::Written);
+        }
+        let entry = entry.clone();
+        self.budgeted_write(move |fmt| Self::render_impact_entry(fmt, &entry))
+    }
+
+    /// Format a full impact group (changed symbol + impacted entries) for structured output.
+    pub fn format_impact(&mut self, out: &ImpactOutput) -> std::io::Result<BudgetStatus> {
+        if !self.has_budget() {
+            let line = Self::serialize_structured(self.format, out)?;
+            writeln!(self.writer, "{line}")?;
+            return Ok(BudgetStatus::Written);
+        }
+        let out = out.clone();
+        self.budgeted_write(move |fmt| {
+            let line = Self::serialize_structured(fmt.format, &out)?;
+            writeln!(fmt.writer, "{line}")
+        })
+    }
+
+    /// Shared render logic for an impact entry.
+    fn render_impact_entry<W2: Write>(
+        fmt: &mut Formatter<W2>,
+        entry: &ImpactEntryOutput,
     ) -> std::io::Result<()> {
         if fmt.format.is_structured() {
             let line = Self::serialize_structured(fmt.format, entry)?;
@@ -1368,6 +3293,11 @@ impl<W: Write> Formatter<W> {
 
     /// Format a single `wonk show` result.
     pub fn format_show(&mut self, out: &ShowOutput) -> std::io::Result<BudgetStatus> {
+        let mut out = Cow::Borrowed(out);
+        if let Some(stamp) = self.stamp(&out.file) {
+            out.to_mut().stamp = Some(stamp);
+        }
+        let out = out.as_ref();
         if !self.has_budget() {
             Self::render_show(self, out)?;
             return Ok(BudgetStatus::Written);
@@ -1442,7 +3372,11 @@ impl<W: Write> Formatter<W> {
             fmt.write_sep()?;
             fmt.write_line_no(out.line)?;
             fmt.write_sep()?;
-            writeln!(fmt.writer, "{}", out.context)
+            write!(fmt.writer, "{}", out.context)?;
+            if let Some(def) = &out.definition {
+                write!(fmt.writer, "  -> {}:{}", def.file, def.line)?;
+            }
+            writeln!(fmt.writer)
         }
     }
 
@@ -1571,8 +3505,8 @@ impl<W: Write> Formatter<W> {
                 } else {
                     writeln!(
                         fmt.writer,
-                        "{prefix}    {} {} — {}",
-                        s.kind, s.name, s.signature
+                        "{prefix}    {} {} {} {}",
+                        s.kind, s.name, fmt.glyphs.dash, s.signature
                     )?;
                 }
             }
@@ -1580,32 +3514,122 @@ impl<W: Write> Formatter<W> {
         if !out.import_edges.is_empty() {
             writeln!(fmt.writer, "{prefix}  Imports:")?;
             for e in &out.import_edges {
-                writeln!(fmt.writer, "{prefix}    {} → {}", e.from, e.to)?;
+                writeln!(
+                    fmt.writer,
+                    "{prefix}    {} {} {}",
+                    e.from, fmt.glyphs.arrow, e.to
+                )?;
             }
         }
 
         Ok(())
     }
 
-    /// Shared render logic for a summary result (recursive with indent level).
-    fn render_summary<W2: Write>(
+    /// Shared render logic for a summary result (recursive with indent level).
+    fn render_summary<W2: Write>(
+        fmt: &mut Formatter<W2>,
+        out: &SummaryOutput,
+        indent: usize,
+    ) -> std::io::Result<()> {
+        if fmt.format.is_structured() {
+            let line = Self::serialize_structured(fmt.format, out)?;
+            writeln!(fmt.writer, "{line}")
+        } else {
+            Self::render_summary_grep_node(fmt, out, indent)?;
+
+            // Render children recursively.
+            for child in &out.children {
+                Self::render_summary(fmt, child, indent + 1)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Format one file of a per-file directory listing.
+    pub fn format_file_listing(
+        &mut self,
+        out: &FileListingOutput,
+    ) -> std::io::Result<BudgetStatus> {
+        if !self.has_budget() {
+            Self::render_file_listing(self, out)?;
+            return Ok(BudgetStatus::Written);
+        }
+        let out = out.clone();
+        self.budgeted_write(move |fmt| Self::render_file_listing(fmt, &out))
+    }
+
+    /// Shared render logic for a file listing line:
+    /// `path: Language, N symbols — kind name, kind name`.
+    fn render_file_listing<W2: Write>(
+        fmt: &mut Formatter<W2>,
+        out: &FileListingOutput,
+    ) -> std::io::Result<()> {
+        if fmt.format.is_structured() {
+            let line = Self::serialize_structured(fmt.format, out)?;
+            writeln!(fmt.writer, "{line}")
+        } else {
+            fmt.write_file(&out.file)?;
+            fmt.write_sep()?;
+            let noun = if out.symbol_count == 1 {
+                "symbol"
+            } else {
+                "symbols"
+            };
+            write!(fmt.writer, "{}, {} {noun}", out.language, out.symbol_count)?;
+            if !out.symbols.is_empty() {
+                let names: Vec<String> = out
+                    .symbols
+                    .iter()
+                    .map(|s| format!("{} {}", s.kind, s.name))
+                    .collect();
+                write!(fmt.writer, " {} {}", fmt.glyphs.dash, names.join(", "))?;
+            }
+            writeln!(fmt.writer)
+        }
+    }
+
+    /// Format one file of a grouped directory listing: a per-file header
+    /// followed by every symbol in line order.
+    pub fn format_file_symbols(
+        &mut self,
+        out: &FileListingOutput,
+    ) -> std::io::Result<BudgetStatus> {
+        if !self.has_budget() {
+            Self::render_file_symbols(self, out)?;
+            return Ok(BudgetStatus::Written);
+        }
+        let out = out.clone();
+        self.budgeted_write(move |fmt| Self::render_file_symbols(fmt, &out))
+    }
+
+    /// Shared render logic for a grouped file listing.
+    fn render_file_symbols<W2: Write>(
         fmt: &mut Formatter<W2>,
-        out: &SummaryOutput,
-        indent: usize,
+        out: &FileListingOutput,
     ) -> std::io::Result<()> {
         if fmt.format.is_structured() {
             let line = Self::serialize_structured(fmt.format, out)?;
-            writeln!(fmt.writer, "{line}")
-        } else {
-            Self::render_summary_grep_node(fmt, out, indent)?;
-
-            // Render children recursively.
-            for child in &out.children {
-                Self::render_summary(fmt, child, indent + 1)?;
+            return writeln!(fmt.writer, "{line}");
+        }
+        fmt.write_file(&out.file)?;
+        writeln!(fmt.writer, " ({})", out.language)?;
+        for s in &out.symbols {
+            let prefix = "  ".repeat(s.indent + 1);
+            write!(fmt.writer, "{prefix}")?;
+            fmt.write_line_no(s.line)?;
+            fmt.write_sep()?;
+            if s.signature.is_empty() {
+                writeln!(fmt.writer, "{} {}", s.kind, s.name)?;
+            } else {
+                writeln!(
+                    fmt.writer,
+                    "{} {} {} {}",
+                    s.kind, s.name, fmt.glyphs.dash, s.signature
+                )?;
             }
-
-            Ok(())
         }
+        Ok(())
     }
 
     /// Format a single flow entry point (list mode).
@@ -1693,6 +3717,86 @@ impl<W: Write> Formatter<W> {
         }
     }
 
+    /// Format one async function with its non-async callers.
+    pub fn format_async_boundary(
+        &mut self,
+        out: &AsyncBoundaryOutput,
+    ) -> std::io::Result<BudgetStatus> {
+        if !self.has_budget() {
+            Self::render_async_boundary(self, out)?;
+            return Ok(BudgetStatus::Written);
+        }
+        let out = out.clone();
+        self.budgeted_write(move |fmt| Self::render_async_boundary(fmt, &out))
+    }
+
+    /// Shared render logic for an async boundary.
+    fn render_async_boundary<W2: Write>(
+        fmt: &mut Formatter<W2>,
+        out: &AsyncBoundaryOutput,
+    ) -> std::io::Result<()> {
+        if fmt.format.is_structured() {
+            let line = Self::serialize_structured(fmt.format, out)?;
+            writeln!(fmt.writer, "{line}")
+        } else {
+            let func = &out.function;
+            fmt.write_file(&func.file)?;
+            fmt.write_sep()?;
+            fmt.write_line_no(func.line)?;
+            fmt.write_sep()?;
+            writeln!(
+                fmt.writer,
+                "async {} ({})",
+                func.symbol_name, func.symbol_kind
+            )?;
+            for caller in &out.sync_callers {
+                writeln!(
+                    fmt.writer,
+                    "  called from {} ({})\t{}:{}",
+                    caller.symbol_name, caller.symbol_kind, caller.file, caller.line
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Format one unsafe declaration or block.
+    pub fn format_unsafe_site(&mut self, out: &UnsafeSiteOutput) -> std::io::Result<BudgetStatus> {
+        if !self.has_budget() {
+            Self::render_unsafe_site(self, out)?;
+            return Ok(BudgetStatus::Written);
+        }
+        let out = out.clone();
+        self.budgeted_write(move |fmt| Self::render_unsafe_site(fmt, &out))
+    }
+
+    /// Shared render logic for an unsafe site.
+    fn render_unsafe_site<W2: Write>(
+        fmt: &mut Formatter<W2>,
+        out: &UnsafeSiteOutput,
+    ) -> std::io::Result<()> {
+        if fmt.format.is_structured() {
+            let line = Self::serialize_structured(fmt.format, out)?;
+            writeln!(fmt.writer, "{line}")
+        } else {
+            fmt.write_file(&out.file)?;
+            fmt.write_sep()?;
+            fmt.write_line_no(out.line)?;
+            fmt.write_sep()?;
+            match (out.kind.as_str(), &out.symbol) {
+                ("block", Some(symbol)) => writeln!(fmt.writer, "unsafe block in {symbol}"),
+                ("block", None) => writeln!(fmt.writer, "unsafe block"),
+                (_, symbol) => {
+                    writeln!(
+                        fmt.writer,
+                        "unsafe {}",
+                        symbol.as_deref().unwrap_or_default()
+                    )
+                }
+            }
+        }
+    }
+
     /// Format a blast radius analysis result.
     pub fn format_blast(&mut self, out: &BlastOutput) -> std::io::Result<BudgetStatus> {
         if !self.has_budget() {
@@ -1927,6 +4031,15 @@ impl<W: Write> Formatter<W> {
     }
 }
 
+/// The main text of a serialized result record: the matched line, signature,
+/// or reference context, falling back to its name.
+pub fn record_text(record: &serde_json::Value) -> Option<&str> {
+    ["content", "signature", "context", "name"]
+        .iter()
+        .find_map(|field| record.get(*field).and_then(serde_json::Value::as_str))
+        .map(str::trim)
+}
+
 /// Print a show header to stderr (grep mode): "file:start-end".
 pub fn print_show_header(file: &str, start_line: usize, end_line: Option<usize>, suppress: bool) {
     if !suppress {
@@ -1956,43 +4069,120 @@ pub fn print_cluster_header(cluster_id: usize, total_members: usize, suppress: b
 // Highlight helper (free function to avoid borrow conflicts)
 // ---------------------------------------------------------------------------
 
-/// Write content with regex matches highlighted in bold+underline+red ANSI codes.
+/// Write content with regex matches and the recorded match `span`
+/// highlighted in bold+underline+red ANSI codes.
 /// Bold and underline provide non-color indicators for color-blind accessibility.
-fn write_highlighted<W: Write>(writer: &mut W, content: &str, re: &Regex) -> std::io::Result<()> {
+fn write_highlighted<W: Write>(
+    writer: &mut W,
+    content: &str,
+    re: Option<&Regex>,
+    span: Option<std::ops::Range<usize>>,
+) -> std::io::Result<()> {
+    let mut ranges: Vec<std::ops::Range<usize>> = re
+        .map(|re| re.find_iter(content).map(|m| m.range()).collect())
+        .unwrap_or_default();
+    if let Some(span) = span
+        && !ranges
+            .iter()
+            .any(|r| r.start < span.end && span.start < r.end)
+    {
+        ranges.push(span);
+        ranges.sort_by_key(|r| r.start);
+    }
+
     let mut last_end = 0;
-    for mat in re.find_iter(content) {
-        write!(writer, "{}", &content[last_end..mat.start()])?;
+    for range in ranges {
+        if range.start < last_end {
+            continue;
+        }
+        write!(writer, "{}", &content[last_end..range.start])?;
         write!(
             writer,
             "{}{}{}",
             color::MATCH,
-            &content[mat.start()..mat.end()],
+            &content[range.clone()],
             color::RESET
         )?;
-        last_end = mat.end();
+        last_end = range.end;
     }
     write!(writer, "{}", &content[last_end..])
 }
 
+/// Terminate a grep-format result line, flagging non-fresh `--verify`
+/// outcomes with a trailing `[label]`.
+fn write_freshness<W: Write>(writer: &mut W, freshness: Option<&str>) -> std::io::Result<()> {
+    match freshness {
+        Some(label) if label != "fresh" => writeln!(writer, "  [{label}]"),
+        _ => writeln!(writer),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Stderr helpers
 // ---------------------------------------------------------------------------
 
+/// Structured format messages are written in, and whether hints are
+/// suppressed (`--quiet`).  Unset in grep mode.
+static STRUCTURED_MESSAGES: std::sync::OnceLock<(OutputFormat, bool)> = std::sync::OnceLock::new();
+
+/// Write hints, warnings, and errors as [`MessageMeta`] records on stdout in
+/// `format` for the rest of the process.  Only the first call takes effect.
+pub fn set_structured_messages(format: OutputFormat, quiet: bool) {
+    if format.is_structured() {
+        let _ = STRUCTURED_MESSAGES.set((format, quiet));
+    }
+}
+
+/// Write `meta` as a record on stdout if messages are structured.  Returns
+/// `false` (writing nothing) in grep mode.
+fn emit_message(meta: MessageMeta) -> bool {
+    let Some(&(format, quiet)) = STRUCTURED_MESSAGES.get() else {
+        return false;
+    };
+    if !(quiet && meta.kind == MessageKind::Hint) {
+        let mut fmt = Formatter::new(std::io::stdout().lock(), format, false);
+        let _ = fmt.format_message_meta(&meta);
+    }
+    true
+}
+
+/// The [`MessageKind`] of a record printed by [`Formatter::format_message_meta`].
+pub fn message_kind(record: &serde_json::Value) -> Option<MessageKind> {
+    record.get("message")?.as_str()?;
+    match record.get("type")?.as_str()? {
+        "hint" => Some(MessageKind::Hint),
+        "warning" => Some(MessageKind::Warning),
+        "error" => Some(MessageKind::Error),
+        _ => None,
+    }
+}
+
 /// Print a hint message to stderr (suppressed when `suppress` is true).
 ///
 /// When suppressed and stdout is a TTY, writes a comment line to stdout so
 /// that callers using `2>/dev/null` still see feedback. When stdout is piped
 /// (not a TTY), hints are suppressed entirely to avoid confusing LLM agents
 /// that parse stdout as structured results.
+///
+/// When output is structured (see [`set_structured_messages`]), the hint is
+/// a `{"type":"hint"}` record on stdout instead, omitted with `--quiet`.
 pub fn print_hint(msg: &str, suppress: bool) {
     use std::io::IsTerminal;
+    if emit_message(MessageMeta {
+        kind: MessageKind::Hint,
+        message: msg.to_string(),
+        hint: None,
+        code: None,
+    }) {
+        return;
+    }
     if suppress {
         // In piped mode, suppress entirely — agents parse stdout as results.
         if std::io::stdout().is_terminal() {
-            println!("# {msg}");
+            println!("# {}", crate::glyphs::asciify(msg));
         }
     } else {
-        eprintln!("hint: {msg}");
+        eprintln!("hint: {}", crate::glyphs::asciify(msg));
     }
 }
 
@@ -2003,6 +4193,16 @@ pub fn print_budget_summary(truncated: usize, budget: usize) {
     eprintln!("-- {truncated} more results truncated (budget: {budget} tokens) --");
 }
 
+/// Print a `--timeout` notice to stderr (grep mode).
+pub fn print_timeout_notice(secs: f64) {
+    eprintln!("-- timed out after {secs}s; results are partial --");
+}
+
+/// Print a Ctrl-C notice to stderr (grep mode).
+pub fn print_interrupt_notice() {
+    eprintln!("-- interrupted; results are partial --");
+}
+
 /// Print a budget truncation summary with page info to stderr (grep mode).
 pub fn print_budget_summary_with_page(truncated: usize, budget: usize, page: usize) {
     eprintln!(
@@ -2045,9 +4245,43 @@ pub fn print_category_header(header: &str) {
     }
 }
 
-/// Print an error message to stderr.
+/// Print a warning to stderr, or a `{"type":"warning"}` record on stdout
+/// when output is structured.
+pub fn print_warning(msg: &str) {
+    if !emit_message(MessageMeta {
+        kind: MessageKind::Warning,
+        message: msg.to_string(),
+        hint: None,
+        code: None,
+    }) {
+        eprintln!("warning: {}", crate::glyphs::asciify(msg));
+    }
+}
+
+/// Print a warning that the results may be incomplete, with the caveat's
+/// `code` in structured output.
+pub fn print_caveat(caveat: crate::caveats::Caveat) {
+    if !emit_message(MessageMeta {
+        kind: MessageKind::Warning,
+        message: caveat.message().to_string(),
+        hint: None,
+        code: Some(caveat.code().to_string()),
+    }) {
+        eprintln!("warning: {}", caveat.message());
+    }
+}
+
+/// Print an error message to stderr, or a `{"type":"error"}` record on
+/// stdout when output is structured.
 pub fn print_error(msg: &str) {
-    eprintln!("error: {msg}");
+    if !emit_message(MessageMeta {
+        kind: MessageKind::Error,
+        message: msg.to_string(),
+        hint: None,
+        code: None,
+    }) {
+        eprintln!("error: {}", crate::glyphs::asciify(msg));
+    }
 }
 
 /// Format a [`WonkError`] to stderr with structured `error:` / `hint:` lines.
@@ -2056,7 +4290,17 @@ pub fn print_error(msg: &str) {
 /// * When `suppress` is `false` and the error carries a contextual hint, also
 ///   prints `hint: <suggestion>` to stderr.
 /// * Returns the appropriate process exit code.
+///
+/// When output is structured, both go into one `{"type":"error"}` record.
 pub fn format_error(err: &crate::errors::WonkError, suppress: bool) -> i32 {
+    if emit_message(MessageMeta {
+        kind: MessageKind::Error,
+        message: format!("{err:#}"),
+        hint: err.hint().map(str::to_string),
+        code: None,
+    }) {
+        return err.exit_code();
+    }
     print_error(&format!("{err:#}"));
     if let Some(hint) = err.hint() {
         print_hint(hint, suppress);
@@ -2098,6 +4342,350 @@ mod tests {
         String::from_utf8(buf).unwrap()
     }
 
+    // -- --verify freshness -------------------------------------------------
+
+    #[test]
+    fn symbol_grep_format_flags_stale() {
+        let sym = SymbolOutput {
+            query: None,
+            name: "main".into(),
+            kind: "function".into(),
+            file: "src/main.rs".into(),
+            line: 10,
+            col: 1,
+            end_line: None,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
+            scope: None,
+            signature: "fn main()".into(),
+            language: "Rust".into(),
+            freshness: Some("stale".into()),
+            id: None,
+            generated_by: None,
+            condition: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
+            stamp: None,
+        };
+        let out = render(OutputFormat::Grep, |fmt| fmt.format_symbol(&sym));
+        assert_eq!(out, "src/main.rs:10:  fn main()  [stale]\n");
+    }
+
+    #[test]
+    fn symbol_json_format_leads_with_bulk_query() {
+        let sym = SymbolOutput {
+            query: Some("open".into()),
+            ..SymbolOutput::from(&crate::types::Symbol {
+                name: "open".into(),
+                kind: crate::types::SymbolKind::Function,
+                file: "src/db.rs".into(),
+                line: 3,
+                col: 1,
+                end_line: None,
+                end_col: None,
+                start_byte: None,
+                end_byte: None,
+                scope: None,
+                signature: "pub fn open()".into(),
+                language: String::new(),
+                doc_comment: None,
+                condition: None,
+                visibility: None,
+                modifiers: Vec::new(),
+                generics: Vec::new(),
+            })
+        };
+        let out = render(OutputFormat::Json, |fmt| fmt.format_symbol(&sym));
+        assert!(
+            out.starts_with(r#"{"query":"open","name":"open","#),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn symbol_grep_format_shows_condition() {
+        let sym = SymbolOutput {
+            query: None,
+            name: "init".into(),
+            kind: "function".into(),
+            file: "src/board.c".into(),
+            line: 4,
+            col: 0,
+            end_line: None,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
+            scope: None,
+            signature: "void init(void)".into(),
+            language: "C".into(),
+            freshness: None,
+            id: None,
+            generated_by: None,
+            condition: Some("defined(CONFIG_ARM)".into()),
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
+            stamp: None,
+        };
+        let out = render(OutputFormat::Grep, |fmt| fmt.format_symbol(&sym));
+        assert_eq!(
+            out,
+            "src/board.c:4:  void init(void)  [#if defined(CONFIG_ARM)]\n"
+        );
+        let out = render(OutputFormat::Json, |fmt| fmt.format_symbol(&sym));
+        let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
+        assert_eq!(v["condition"], "defined(CONFIG_ARM)");
+    }
+
+    #[test]
+    fn structured_columns_use_configured_unit() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.ts"), "const ñame = fetchAll();\n").unwrap();
+        let reference = RefOutput {
+            name: "fetchAll".into(),
+            kind: "call".into(),
+            file: "a.ts".into(),
+            line: 1,
+            col: 14,
+            end_col: Some(22),
+            start_byte: Some(14),
+            end_byte: Some(22),
+            context: "const ñame = fetchAll();".into(),
+            caller_name: None,
+            confidence: 0.5,
+            freshness: None,
+            definition: None,
+            generated_by: None,
+            stamp: None,
+        };
+        let search = SearchOutput {
+            end_col: Some(23),
+            ..SearchOutput::from_search_result(Path::new("a.ts"), 1, 15, "const ñame = fetchAll();")
+        };
+
+        let mut buf = Vec::new();
+        {
+            let mut fmt = Formatter::new(&mut buf, OutputFormat::Json, false);
+            fmt.set_columns(ColumnMapper::new(ColUnit::Utf16, dir.path()));
+            fmt.format_reference(&reference).unwrap();
+            fmt.format_search_result(&search).unwrap();
+        }
+        let out = String::from_utf8(buf).unwrap();
+        let lines: Vec<serde_json::Value> = out
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines[0]["col"], 13);
+        assert_eq!(lines[0]["end_col"], 21);
+        assert_eq!(lines[1]["col"], 14);
+        assert_eq!(lines[1]["end_col"], 22);
+        // Byte offsets are never converted.
+        assert_eq!(lines[0]["start_byte"], 14);
+        assert_eq!(lines[0]["end_byte"], 22);
+
+        // Byte columns are left alone.
+        let out = render(OutputFormat::Json, |fmt| fmt.format_reference(&reference));
+        let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
+        assert_eq!(v["col"], 14);
+        assert_eq!(v["end_col"], 22);
+    }
+
+    #[test]
+    fn structured_results_are_stamped_with_the_indexed_hash() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let index = dir.path().join("index.db");
+        let conn = crate::db::open(&index).unwrap();
+        conn.execute(
+            "INSERT INTO files (path, language, hash, last_indexed) VALUES ('a.rs', 'Rust', 'feedface', 0)",
+            [],
+        )
+        .unwrap();
+        crate::db::bump_generation(&conn).unwrap();
+        let search = SearchOutput::from_search_result(Path::new("a.rs"), 1, 1, "fn a() {}");
+        let stamped = |format| {
+            let mut buf = Vec::new();
+            {
+                let mut fmt = Formatter::new(&mut buf, format, false);
+                fmt.set_stamper(Stamper::new(dir.path(), index.clone()));
+                fmt.format_search_result(&search).unwrap();
+            }
+            String::from_utf8(buf).unwrap()
+        };
+
+        let v: serde_json::Value =
+            serde_json::from_str(stamped(OutputFormat::Json).trim()).unwrap();
+        assert_eq!(v["content_hash"], "feedface");
+        assert_eq!(v["index_generation"], 1);
+        assert!(v.get("stamp").is_none(), "stamp fields are flattened");
+        assert!(stamped(OutputFormat::Toon).contains("content_hash: feedface"));
+        assert_eq!(stamped(OutputFormat::Grep), "a.rs:1:fn a() {}\n");
+
+        // Without a stamper the fields are left out.
+        let out = render(OutputFormat::Json, |fmt| fmt.format_search_result(&search));
+        assert!(!out.contains("content_hash") && !out.contains("index_generation"));
+    }
+
+    #[test]
+    fn reference_grep_format_fresh_has_no_flag() {
+        let reference = RefOutput {
+            name: "foo".into(),
+            kind: "call".into(),
+            file: "src/lib.rs".into(),
+            line: 99,
+            col: 5,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
+            context: "    foo(42);".into(),
+            caller_name: None,
+            confidence: 0.5,
+            freshness: Some("fresh".into()),
+            definition: None,
+            generated_by: None,
+            stamp: None,
+        };
+        let out = render(OutputFormat::Grep, |fmt| fmt.format_reference(&reference));
+        assert_eq!(out, "src/lib.rs:99:    foo(42);\n");
+    }
+
+    #[test]
+    fn reference_with_definition_grep_and_json() {
+        let reference = RefOutput {
+            name: "foo".into(),
+            kind: "call".into(),
+            file: "src/main.rs".into(),
+            line: 4,
+            col: 5,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
+            context: "    foo();".into(),
+            caller_name: None,
+            confidence: 0.9,
+            definition: Some(DefinitionOutput {
+                file: "src/lib.rs".into(),
+                line: 12,
+                kind: "function".into(),
+                via: "import".into(),
+            }),
+            freshness: None,
+            generated_by: None,
+            stamp: None,
+        };
+        let out = render(OutputFormat::Grep, |fmt| fmt.format_reference(&reference));
+        assert_eq!(out, "src/main.rs:4:    foo();  -> src/lib.rs:12\n");
+
+        let out = render(OutputFormat::Json, |fmt| fmt.format_reference(&reference));
+        let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
+        assert_eq!(v["definition"]["file"], "src/lib.rs");
+        assert_eq!(v["definition"]["line"], 12);
+        assert_eq!(v["definition"]["via"], "import");
+    }
+
+    #[test]
+    fn generated_group_grep_and_json() {
+        let group = GeneratedGroupOutput {
+            generated_by: "protoc".into(),
+            files: 3,
+            hits: 12,
+        };
+        let out = render(OutputFormat::Grep, |fmt| fmt.format_generated_group(&group));
+        assert_eq!(out, "[generated by protoc] 12 hits in 3 files\n");
+
+        let out = render(OutputFormat::Json, |fmt| fmt.format_generated_group(&group));
+        let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
+        assert_eq!(v["generated_by"], "protoc");
+        assert_eq!(v["hits"], 12);
+    }
+
+    #[test]
+    fn signature_json_includes_freshness() {
+        let sig = SignatureOutput {
+            name: "process".into(),
+            file: "src/lib.rs".into(),
+            line: 15,
+            signature: "fn process()".into(),
+            language: "Rust".into(),
+            freshness: Some("relocated".into()),
+            stamp: None,
+        };
+        let out = render(OutputFormat::Json, |fmt| fmt.format_signature(&sig));
+        let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
+        assert_eq!(v["freshness"], "relocated");
+    }
+
+    // -- LangStatsOutput -----------------------------------------------------
+
+    fn lang_stats_fixture() -> Vec<crate::types::LanguageStats> {
+        vec![
+            crate::types::LanguageStats {
+                language: "Rust".into(),
+                file_count: 3,
+                line_count: 300,
+                symbol_count: 40,
+                reference_count: 120,
+            },
+            crate::types::LanguageStats {
+                language: "Python".into(),
+                file_count: 1,
+                line_count: 100,
+                symbol_count: 5,
+                reference_count: 9,
+            },
+        ]
+    }
+
+    #[test]
+    fn lang_stats_percent_of_total_lines() {
+        let rows = LangStatsOutput::from_stats(&lang_stats_fixture());
+        assert_eq!(rows[0].percent, 75.0);
+        assert_eq!(rows[1].percent, 25.0);
+    }
+
+    #[test]
+    fn lang_stats_zero_lines_has_zero_percent() {
+        let stats = vec![crate::types::LanguageStats {
+            language: "Go".into(),
+            ..Default::default()
+        }];
+        assert_eq!(LangStatsOutput::from_stats(&stats)[0].percent, 0.0);
+    }
+
+    #[test]
+    fn lang_stats_grep_format_has_bar() {
+        let rows = LangStatsOutput::from_stats(&lang_stats_fixture());
+        let out = render(OutputFormat::Grep, |fmt| fmt.format_lang_stats(&rows[0]));
+        assert!(out.starts_with("Rust"));
+        assert!(out.contains("300 lines"));
+        assert!(out.contains("75.0%"));
+        // 75% of a 30-wide bar.
+        assert_eq!(out.matches('█').count(), 23);
+    }
+
+    #[test]
+    fn lang_stats_ascii_glyphs_use_hash_bar() {
+        let rows = LangStatsOutput::from_stats(&lang_stats_fixture());
+        let out = render(OutputFormat::Grep, |fmt| {
+            fmt.set_glyphs(Glyphs::ASCII);
+            fmt.format_lang_stats(&rows[0])
+        });
+        assert!(out.is_ascii(), "{out}");
+        assert_eq!(out.matches('#').count(), 23);
+    }
+
+    #[test]
+    fn lang_stats_json_format() {
+        let rows = LangStatsOutput::from_stats(&lang_stats_fixture());
+        let out = render(OutputFormat::Json, |fmt| fmt.format_lang_stats(&rows[1]));
+        let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
+        assert_eq!(v["language"], "Python");
+        assert_eq!(v["files"], 1);
+        assert_eq!(v["references"], 9);
+        assert_eq!(v["percent"], 25.0);
+    }
+
     // -- SearchOutput --------------------------------------------------------
 
     #[test]
@@ -2106,12 +4694,61 @@ mod tests {
             file: "src/main.rs".into(),
             line: 42,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "fn main() {}".into(),
             annotation: None,
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
+        };
+        let out = render(OutputFormat::Grep, |fmt| fmt.format_search_result(&result));
+        assert_eq!(out, "src/main.rs:42:fn main() {}\n");
+    }
+
+    #[test]
+    fn single_line_joins_grep_but_not_json_results() {
+        let results: Vec<SearchOutput> = [3, 7]
+            .into_iter()
+            .map(|line| SearchOutput {
+                file: "src/main.rs".into(),
+                line,
+                col: 1,
+                end_col: None,
+                start_byte: None,
+                end_byte: None,
+                content: "fn main() {}".into(),
+                annotation: None,
+                category: None,
+                source: None,
+                patterns: Vec::new(),
+                stamp: None,
+            })
+            .collect();
+        let piped = |format| {
+            render(format, |fmt| {
+                // Piped output is budgeted, and single-line mode applies to
+                // budgeted writes.
+                fmt.set_budget(10_000);
+                fmt.set_single_line(true);
+                for result in &results {
+                    fmt.format_search_result(result)?;
+                }
+                Ok(())
+            })
         };
-        let out = render(OutputFormat::Grep, |fmt| fmt.format_search_result(&result));
-        assert_eq!(out, "src/main.rs:42:fn main() {}\n");
+
+        assert_eq!(
+            piped(OutputFormat::Grep).trim_end(),
+            "src/main.rs:3:fn main() {} ; src/main.rs:7:fn main() {}"
+        );
+        let json = piped(OutputFormat::Json);
+        assert_eq!(json.lines().count(), 2);
+        for line in json.lines() {
+            serde_json::from_str::<serde_json::Value>(line).unwrap();
+        }
     }
 
     #[test]
@@ -2120,9 +4757,15 @@ mod tests {
             file: "src/main.rs".into(),
             line: 42,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "fn main() {}".into(),
             annotation: None,
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
         };
         let out = render(OutputFormat::Json, |fmt| fmt.format_search_result(&result));
         let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
@@ -2137,15 +4780,27 @@ mod tests {
     #[test]
     fn symbol_grep_format() {
         let sym = SymbolOutput {
+            query: None,
             name: "main".into(),
             kind: "function".into(),
             file: "src/main.rs".into(),
             line: 10,
             col: 0,
             end_line: Some(20),
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             scope: None,
             signature: "fn main()".into(),
             language: "Rust".into(),
+            freshness: None,
+            id: None,
+            generated_by: None,
+            condition: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
+            stamp: None,
         };
         let out = render(OutputFormat::Grep, |fmt| fmt.format_symbol(&sym));
         assert_eq!(out, "src/main.rs:10:  fn main()\n");
@@ -2154,15 +4809,27 @@ mod tests {
     #[test]
     fn symbol_json_format() {
         let sym = SymbolOutput {
+            query: None,
             name: "main".into(),
             kind: "function".into(),
             file: "src/main.rs".into(),
             line: 10,
             col: 0,
             end_line: Some(20),
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             scope: Some("MyModule".into()),
             signature: "fn main()".into(),
             language: "Rust".into(),
+            freshness: None,
+            id: None,
+            generated_by: None,
+            condition: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
+            stamp: None,
         };
         let out = render(OutputFormat::Json, |fmt| fmt.format_symbol(&sym));
         let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
@@ -2177,15 +4844,27 @@ mod tests {
     #[test]
     fn symbol_json_skips_none_optional_fields() {
         let sym = SymbolOutput {
+            query: None,
             name: "Foo".into(),
             kind: "struct".into(),
             file: "lib.rs".into(),
             line: 5,
             col: 0,
             end_line: None,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             scope: None,
             signature: "struct Foo".into(),
             language: "Rust".into(),
+            freshness: None,
+            id: None,
+            generated_by: None,
+            condition: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
+            stamp: None,
         };
         let out = render(OutputFormat::Json, |fmt| fmt.format_symbol(&sym));
         // With skip_serializing_if = None, the JSON should not contain these keys.
@@ -2203,9 +4882,16 @@ mod tests {
             file: "src/lib.rs".into(),
             line: 99,
             col: 4,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             context: "    foo(42);".into(),
             caller_name: None,
             confidence: 0.5,
+            freshness: None,
+            definition: None,
+            generated_by: None,
+            stamp: None,
         };
         let out = render(OutputFormat::Grep, |fmt| fmt.format_reference(&reference));
         assert_eq!(out, "src/lib.rs:99:    foo(42);\n");
@@ -2219,9 +4905,16 @@ mod tests {
             file: "src/lib.rs".into(),
             line: 99,
             col: 4,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             context: "    foo(42);".into(),
             caller_name: None,
             confidence: 0.85,
+            freshness: None,
+            definition: None,
+            generated_by: None,
+            stamp: None,
         };
         let out = render(OutputFormat::Json, |fmt| fmt.format_reference(&reference));
         let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
@@ -2240,6 +4933,8 @@ mod tests {
             line: 15,
             signature: "fn process(input: &str) -> Result<()>".into(),
             language: "Rust".into(),
+            freshness: None,
+            stamp: None,
         };
         let out = render(OutputFormat::Grep, |fmt| fmt.format_signature(&sig));
         assert_eq!(
@@ -2256,6 +4951,8 @@ mod tests {
             line: 15,
             signature: "fn process(input: &str) -> Result<()>".into(),
             language: "Rust".into(),
+            freshness: None,
+            stamp: None,
         };
         let out = render(OutputFormat::Json, |fmt| fmt.format_signature(&sig));
         let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
@@ -2291,6 +4988,7 @@ mod tests {
         let dep = DepOutput {
             file: "src/main.rs".into(),
             depends_on: "src/lib.rs".into(),
+            generated_by: None,
         };
         let out = render(OutputFormat::Grep, |fmt| fmt.format_dep(&dep));
         assert_eq!(out, "src/main.rs -> src/lib.rs\n");
@@ -2301,6 +4999,7 @@ mod tests {
         let dep = DepOutput {
             file: "src/main.rs".into(),
             depends_on: "src/lib.rs".into(),
+            generated_by: None,
         };
         let out = render(OutputFormat::Json, |fmt| fmt.format_dep(&dep));
         let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
@@ -2317,17 +5016,29 @@ mod tests {
                 file: "a.rs".into(),
                 line: 1,
                 col: 1,
+                end_col: None,
+                start_byte: None,
+                end_byte: None,
                 content: "first".into(),
                 annotation: None,
+                category: None,
                 source: None,
+                patterns: Vec::new(),
+                stamp: None,
             },
             SearchOutput {
                 file: "b.rs".into(),
                 line: 2,
                 col: 1,
+                end_col: None,
+                start_byte: None,
+                end_byte: None,
                 content: "second".into(),
                 annotation: None,
+                category: None,
                 source: None,
+                patterns: Vec::new(),
+                stamp: None,
             },
         ];
         let out = render(OutputFormat::Json, |fmt| {
@@ -2351,17 +5062,29 @@ mod tests {
                 file: "a.rs".into(),
                 line: 1,
                 col: 1,
+                end_col: None,
+                start_byte: None,
+                end_byte: None,
                 content: "first".into(),
                 annotation: None,
+                category: None,
                 source: None,
+                patterns: Vec::new(),
+                stamp: None,
             },
             SearchOutput {
                 file: "b.rs".into(),
                 line: 2,
                 col: 1,
+                end_col: None,
+                start_byte: None,
+                end_byte: None,
                 content: "second".into(),
                 annotation: None,
+                category: None,
                 source: None,
+                patterns: Vec::new(),
+                stamp: None,
             },
         ];
         let out = render(OutputFormat::Grep, |fmt| {
@@ -2396,9 +5119,15 @@ mod tests {
             file: "src/lib.rs".into(),
             line: 10,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "pub fn foo() {}".into(),
             annotation: Some("(+3 other locations)".into()),
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
         };
         let out = render(OutputFormat::Grep, |fmt| fmt.format_search_result(&result));
         assert_eq!(out, "src/lib.rs:10:pub fn foo() {}  (+3 other locations)\n");
@@ -2410,9 +5139,15 @@ mod tests {
             file: "src/lib.rs".into(),
             line: 10,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "pub fn foo() {}".into(),
             annotation: None,
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
         };
         let out = render(OutputFormat::Grep, |fmt| fmt.format_search_result(&result));
         assert_eq!(out, "src/lib.rs:10:pub fn foo() {}\n");
@@ -2424,9 +5159,15 @@ mod tests {
             file: "src/lib.rs".into(),
             line: 10,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "pub fn foo() {}".into(),
             annotation: Some("(+2 other locations)".into()),
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
         };
         let out = render(OutputFormat::Json, |fmt| fmt.format_search_result(&result));
         let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
@@ -2439,9 +5180,15 @@ mod tests {
             file: "src/lib.rs".into(),
             line: 10,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "pub fn foo() {}".into(),
             annotation: None,
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
         };
         let out = render(OutputFormat::Json, |fmt| fmt.format_search_result(&result));
         assert!(!out.contains("annotation"));
@@ -2455,9 +5202,15 @@ mod tests {
             file: "src/lib.rs".into(),
             line: 10,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "pub fn foo() {}".into(),
             annotation: None,
+            category: None,
             source: Some("structural".into()),
+            patterns: Vec::new(),
+            stamp: None,
         };
         let out = render(OutputFormat::Json, |fmt| fmt.format_search_result(&result));
         let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
@@ -2470,14 +5223,39 @@ mod tests {
             file: "src/lib.rs".into(),
             line: 10,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "pub fn foo() {}".into(),
             annotation: None,
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
         };
         let out = render(OutputFormat::Json, |fmt| fmt.format_search_result(&result));
         assert!(!out.contains("source"));
     }
 
+    #[test]
+    fn search_result_with_patterns() {
+        let mut result = SearchOutput::from_search_result(
+            std::path::Path::new("src/a.rs"),
+            3,
+            1,
+            "old_api(); legacy.call();",
+        );
+        result.patterns = vec!["old_api".into(), "legacy.call".into()];
+        let out = render(OutputFormat::Grep, |fmt| fmt.format_search_result(&result));
+        assert_eq!(
+            out,
+            "src/a.rs:3:old_api(); legacy.call();  [old_api, legacy.call]\n"
+        );
+        let out = render(OutputFormat::Json, |fmt| fmt.format_search_result(&result));
+        let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
+        assert_eq!(v["patterns"][1], "legacy.call");
+    }
+
     #[test]
     fn from_search_result_sets_source_none() {
         let path = std::path::PathBuf::from("src/foo.rs");
@@ -2493,9 +5271,15 @@ mod tests {
             file: "cfg.toml".into(),
             line: 5,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "key: value".into(),
             annotation: None,
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
         };
         // Grep format: file:line:content (colons in content are fine)
         let out = render(OutputFormat::Grep, |fmt| fmt.format_search_result(&result));
@@ -2508,9 +5292,15 @@ mod tests {
             file: "test.rs".into(),
             line: 1,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "he said \"hello\"".into(),
             annotation: None,
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
         };
         let out = render(OutputFormat::Json, |fmt| fmt.format_search_result(&result));
         let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
@@ -2554,9 +5344,15 @@ mod tests {
             file: "src/main.rs".into(),
             line: 42,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "fn main() {}".into(),
             annotation: None,
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
         };
         let out = render(OutputFormat::Grep, |fmt| fmt.format_search_result(&result));
         assert_eq!(out, "src/main.rs:42:fn main() {}\n");
@@ -2568,9 +5364,15 @@ mod tests {
             file: "src/main.rs".into(),
             line: 42,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "fn main() {}".into(),
             annotation: None,
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
         };
         let out = render_color(|fmt| fmt.format_search_result(&result));
         // File path should be wrapped in magenta+bold
@@ -2590,9 +5392,15 @@ mod tests {
             file: "src/main.rs".into(),
             line: 42,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "fn main() {}".into(),
             annotation: None,
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
         };
         let out = render_color(|fmt| fmt.format_search_result(&result));
         // Line number should be wrapped in green
@@ -2612,9 +5420,15 @@ mod tests {
             file: "src/main.rs".into(),
             line: 42,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "fn main() {}".into(),
             annotation: None,
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
         };
         let out = render_color(|fmt| fmt.format_search_result(&result));
         // Separator should be wrapped in cyan
@@ -2630,9 +5444,15 @@ mod tests {
             file: "src/main.rs".into(),
             line: 42,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "fn main() {}".into(),
             annotation: None,
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
         };
         let mut buf = Vec::new();
         {
@@ -2652,9 +5472,15 @@ mod tests {
             file: "src/main.rs".into(),
             line: 42,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "fn main() {}".into(),
             annotation: None,
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
         };
         let mut buf = Vec::new();
         {
@@ -2676,9 +5502,15 @@ mod tests {
             file: "src/main.rs".into(),
             line: 42,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "fn main() {}".into(),
             annotation: None,
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
         };
         let mut buf = Vec::new();
         {
@@ -2694,15 +5526,126 @@ mod tests {
         );
     }
 
+    #[test]
+    fn match_highlighting_uses_recorded_span() {
+        // The pattern no longer matches the line on its own (e.g. it was
+        // anchored to the start of the file), but the span is still known.
+        let result = SearchOutput {
+            col: 4,
+            end_col: Some(8),
+            ..SearchOutput::from_search_result(Path::new("src/main.rs"), 42, 4, "fn main() {}")
+        };
+        let mut buf = Vec::new();
+        {
+            let mut fmt = Formatter::new(&mut buf, OutputFormat::Grep, true);
+            fmt.set_highlight("zzzzz", false, false);
+            fmt.format_search_result(&result).unwrap();
+        }
+        let out = String::from_utf8(buf).unwrap();
+        let expected_match = format!("{}main{}", crate::color::MATCH, crate::color::RESET);
+        assert!(out.contains(&expected_match), "got: {out:?}");
+        assert_eq!(out.matches(crate::color::MATCH).count(), 1);
+
+        // Overlapping regex matches and spans are highlighted once.
+        let mut buf = Vec::new();
+        {
+            let mut fmt = Formatter::new(&mut buf, OutputFormat::Grep, true);
+            fmt.set_highlight("ma", false, false);
+            fmt.format_search_result(&result).unwrap();
+        }
+        let out = String::from_utf8(buf).unwrap();
+        let expected_match = format!("{}ma{}in", crate::color::MATCH, crate::color::RESET);
+        assert!(out.contains(&expected_match), "got: {out:?}");
+    }
+
+    #[test]
+    fn search_group_json_nests_results() {
+        let mut result =
+            SearchOutput::from_search_result(Path::new("src/a.rs"), 3, 4, "fn foo() {}");
+        result.category = Some("definition".into());
+        let group = SearchGroupOutput {
+            category: Some("definition".into()),
+            label: "definitions".into(),
+            results: vec![result],
+        };
+
+        let out = render(OutputFormat::Json, |fmt| fmt.format_search_group(&group));
+        let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
+        assert_eq!(v["category"], "definition");
+        assert_eq!(v["label"], "definitions");
+        assert_eq!(v["results"][0]["file"], "src/a.rs");
+        assert_eq!(v["results"][0]["category"], "definition");
+
+        let collapsed = SearchGroupOutput {
+            category: None,
+            ..group
+        };
+        let out = render(OutputFormat::Json, |fmt| {
+            fmt.format_search_group(&collapsed)
+        });
+        let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
+        assert!(v.get("category").is_none());
+
+        let out = render(OutputFormat::Grep, |fmt| {
+            fmt.format_search_group(&collapsed)
+        });
+        assert_eq!(out, "-- definitions --\nsrc/a.rs:3:fn foo() {}\n");
+    }
+
+    #[test]
+    fn search_cluster_grep_header_notes_cut() {
+        let result = SearchOutput::from_search_result(Path::new("src/a.rs"), 3, 4, "log(x)");
+        let cluster = SearchClusterOutput {
+            dir: "src".into(),
+            count: 12,
+            results: vec![result],
+        };
+        let out = render(OutputFormat::Grep, |fmt| {
+            fmt.format_search_cluster(&cluster)
+        });
+        assert_eq!(out, "-- src/ (12 hits, showing 1) --\nsrc/a.rs:3:log(x)\n");
+
+        let all = SearchClusterOutput {
+            dir: ".".into(),
+            count: 1,
+            ..cluster
+        };
+        let out = render(OutputFormat::Grep, |fmt| fmt.format_search_cluster(&all));
+        assert!(out.starts_with("-- ./ (1 hit) --\n"), "{out}");
+
+        let out = render(OutputFormat::Json, |fmt| fmt.format_search_cluster(&all));
+        let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
+        assert_eq!(v["dir"], ".");
+        assert_eq!(v["count"], 1);
+        assert_eq!(v["results"][0]["line"], 3);
+    }
+
+    #[test]
+    fn match_span_rejects_out_of_range_columns() {
+        let mut result = SearchOutput::from_search_result(Path::new("a.rs"), 1, 2, "é = 1");
+        result.end_col = Some(4);
+        assert_eq!(result.match_span(), None);
+        result.col = 1;
+        assert_eq!(result.match_span(), Some(0..3));
+        result.end_col = Some(40);
+        assert_eq!(result.match_span(), None);
+    }
+
     #[test]
     fn no_highlighting_when_pattern_does_not_match() {
         let result = SearchOutput {
             file: "src/main.rs".into(),
             line: 42,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "fn main() {}".into(),
             annotation: None,
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
         };
         let mut buf = Vec::new();
         {
@@ -2721,15 +5664,27 @@ mod tests {
     #[test]
     fn color_symbol_format() {
         let sym = SymbolOutput {
+            query: None,
             name: "main".into(),
             kind: "function".into(),
             file: "src/main.rs".into(),
             line: 10,
             col: 0,
             end_line: Some(20),
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             scope: None,
             signature: "fn main()".into(),
             language: "Rust".into(),
+            freshness: None,
+            id: None,
+            generated_by: None,
+            condition: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
+            stamp: None,
         };
         let out = render_color(|fmt| fmt.format_symbol(&sym));
         assert!(out.contains(&format!(
@@ -2762,6 +5717,7 @@ mod tests {
         let dep = DepOutput {
             file: "src/main.rs".into(),
             depends_on: "src/lib.rs".into(),
+            generated_by: None,
         };
         let out = render_color(|fmt| fmt.format_dep(&dep));
         assert!(out.contains(&format!(
@@ -2794,6 +5750,449 @@ mod tests {
         assert_eq!(v["used_tokens"], 498);
     }
 
+    #[test]
+    fn timeout_meta_json_format() {
+        let meta = TimeoutMeta {
+            timed_out: true,
+            timeout_secs: 1.5,
+        };
+        let out = render(OutputFormat::Json, |fmt| fmt.format_timeout_meta(&meta));
+        let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
+        assert_eq!(v["timed_out"], true);
+        assert_eq!(v["timeout_secs"], 1.5);
+    }
+
+    #[test]
+    fn recipe_grep_lists_topic_or_steps() {
+        let recipe = crate::examples::find_recipe("callers").unwrap();
+        let listed = render(OutputFormat::Grep, |fmt| {
+            fmt.format_recipe(&RecipeOutput::from_recipe(recipe, false))
+        });
+        assert_eq!(listed, "callers    Find who calls a function\n");
+
+        let full = render(OutputFormat::Grep, |fmt| {
+            fmt.format_recipe(&RecipeOutput::from_recipe(recipe, true))
+        });
+        assert!(full.starts_with("Find who calls a function\n\n  # Direct callers\n"));
+        assert!(full.contains("  wonk callers parse_config\n"));
+    }
+
+    #[test]
+    fn recipe_json_includes_steps() {
+        let recipe = crate::examples::find_recipe("ci").unwrap();
+        let out = render(OutputFormat::Json, |fmt| {
+            fmt.format_recipe(&RecipeOutput::from_recipe(recipe, true))
+        });
+        let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
+        assert_eq!(v["topic"], "ci");
+        assert_eq!(v["steps"][0]["command"], "wonk update --skip-embed -q");
+    }
+
+    #[test]
+    fn index_profile_reports_phases_languages_and_slowest_files() {
+        use crate::pipeline::{FileTiming, IndexProfile};
+        use std::time::Duration;
+
+        let profile = IndexProfile {
+            walk: Duration::from_millis(100),
+            parse: Duration::from_millis(500),
+            insert: Duration::from_millis(200),
+            resolve: Duration::from_millis(50),
+            total: Duration::from_millis(900),
+            files: vec![
+                FileTiming {
+                    path: "gen/huge.ts".into(),
+                    language: "TypeScript".into(),
+                    lines: 50000,
+                    parse: Duration::from_millis(750),
+                },
+                FileTiming {
+                    path: "src/lib.rs".into(),
+                    language: "Rust".into(),
+                    lines: 100,
+                    parse: Duration::from_millis(250),
+                },
+            ],
+        };
+        let out = IndexProfileOutput::from_profile(&profile, 1);
+        assert_eq!(out.slowest_files.len(), 1);
+        assert!((out.languages[0].percent - 75.0).abs() < 1e-9);
+
+        let text = render(OutputFormat::Grep, |fmt| fmt.format_index_profile(&out));
+        assert!(text.starts_with("Index profile: 2 files in 0.90s\n"));
+        assert!(text.contains("  parse        0.50s  (1.00s summed across threads)\n"));
+        assert!(text.contains("  TypeScript       0.75s  75.0%"));
+        assert!(
+            text.contains("Slowest files:\n     750.0ms  TypeScript   gen/huge.ts (50000 lines)\n")
+        );
+        assert!(!text.contains("src/lib.rs"));
+
+        let json = render(OutputFormat::Json, |fmt| fmt.format_index_profile(&out));
+        let v: serde_json::Value = serde_json::from_str(json.trim()).unwrap();
+        assert_eq!(v["files"], 2);
+        assert_eq!(v["languages"][1]["language"], "Rust");
+        assert_eq!(v["slowest_files"][0]["path"], "gen/huge.ts");
+    }
+
+    #[test]
+    fn diag_grep_lists_environment_and_reports() {
+        let out = DiagOutput {
+            version: "4.14.1".into(),
+            platform: "linux-x86_64".into(),
+            index: None,
+            crash_count: 3,
+            crash_reports: vec![CrashReportOutput {
+                path: "/home/u/.wonk/crash/crash-1-2.txt".into(),
+                contents: "wonk crash report\npanic: boom".into(),
+            }],
+        };
+        let text = render(OutputFormat::Grep, |fmt| fmt.format_diag(&out));
+        assert_eq!(
+            text,
+            "version: 4.14.1\nplatform: linux-x86_64\nindex: none\ncrash reports: 3 (1 included)\n\n\
+             ==> /home/u/.wonk/crash/crash-1-2.txt <==\nwonk crash report\npanic: boom\n"
+        );
+
+        let json = render(OutputFormat::Json, |fmt| fmt.format_diag(&out));
+        let v: serde_json::Value = serde_json::from_str(json.trim()).unwrap();
+        assert_eq!(v["crash_count"], 3);
+        assert!(v["index"].is_null());
+        assert_eq!(
+            v["crash_reports"][0]["contents"],
+            "wonk crash report\npanic: boom"
+        );
+    }
+
+    #[test]
+    fn parse_output_is_json_in_every_mode() {
+        let extraction = crate::pipeline::Extraction {
+            symbols: Vec::new(),
+            refs: Vec::new(),
+            imports: vec!["./util".into()],
+            type_edges: vec![crate::types::RawTypeEdge {
+                child_name: "Child".into(),
+                parent_name: "Base".into(),
+                relationship: "extends".into(),
+            }],
+        };
+        let out = ParseOutput::new("TypeScript", "<stdin>", &extraction);
+
+        let pretty = render(OutputFormat::Grep, |fmt| fmt.format_parse(&out));
+        assert!(
+            pretty.starts_with("{\n  \"language\": \"TypeScript\""),
+            "{pretty}"
+        );
+        let json = render(OutputFormat::Json, |fmt| fmt.format_parse(&out));
+        assert_eq!(json.lines().count(), 1);
+        for text in [pretty, json] {
+            let v: serde_json::Value = serde_json::from_str(text.trim()).unwrap();
+            assert_eq!(v["path"], "<stdin>");
+            assert_eq!(v["imports"][0], "./util");
+            assert_eq!(v["type_edges"][0]["parent"], "Base");
+        }
+    }
+
+    #[test]
+    fn tree_dump_grep_indents_nodes() {
+        let node =
+            |kind: &str, field: Option<&str>, depth, named, text: Option<&str>| TreeNodeOutput {
+                kind: kind.into(),
+                field: field.map(Into::into),
+                depth,
+                named,
+                missing: false,
+                start_line: 1,
+                start_col: depth,
+                end_line: 2,
+                end_col: 1,
+                start_byte: 0,
+                end_byte: 10,
+                text: text.map(Into::into),
+            };
+        let mut out = TreeDumpOutput {
+            language: "Rust".into(),
+            path: "src/lib.rs".into(),
+            nodes: vec![
+                node("source_file", None, 0, true, None),
+                node("fn", None, 1, false, Some("fn")),
+                node("identifier", Some("name"), 1, true, Some("add")),
+            ],
+            sexp: None,
+        };
+        let text = render(OutputFormat::Grep, |fmt| fmt.format_tree_dump(&out));
+        assert_eq!(
+            text,
+            "source_file [1:0 - 2:1]\n  \"fn\" [1:1 - 2:1]\n  name: identifier [1:1 - 2:1] \"add\"\n"
+        );
+
+        let json = render(OutputFormat::Json, |fmt| fmt.format_tree_dump(&out));
+        let v: serde_json::Value = serde_json::from_str(json.trim()).unwrap();
+        assert_eq!(v["nodes"][2]["field"], "name");
+        assert!(v.get("sexp").is_none());
+
+        out.nodes.clear();
+        out.sexp = Some(vec!["(a)".into(), "(b)".into()]);
+        let text = render(OutputFormat::Grep, |fmt| fmt.format_tree_dump(&out));
+        assert_eq!(text, "(a)\n(b)\n");
+    }
+
+    #[test]
+    fn gate_check_grep_lines() {
+        let mut check = GateCheckOutput {
+            name: "todo".into(),
+            rule: "max".into(),
+            count: 12,
+            limit: Some(10),
+            baseline: None,
+            added: Vec::new(),
+            passed: false,
+        };
+        let text = render(OutputFormat::Grep, |fmt| fmt.format_gate_check(&check));
+        assert_eq!(text, "FAIL todo: 12 results (max 10)\n");
+
+        check.rule = "no_new".into();
+        check.limit = None;
+        check.baseline = Some(11);
+        check.added = vec![serde_json::json!({"file": "a.rs", "line": 2, "content": "// TODO"})];
+        let text = render(OutputFormat::Grep, |fmt| fmt.format_gate_check(&check));
+        assert_eq!(
+            text,
+            "FAIL todo: 1 new results (baseline 11, now 12)\n  + a.rs:2: // TODO\n"
+        );
+
+        check.baseline = None;
+        check.added.clear();
+        let json = render(OutputFormat::Json, |fmt| fmt.format_gate_check(&check));
+        let v: serde_json::Value = serde_json::from_str(json.trim()).unwrap();
+        assert_eq!(v["rule"], "no_new");
+        assert!(v.get("baseline").is_none() && v.get("added").is_none());
+    }
+
+    #[test]
+    fn compare_grep_marks_added_and_removed() {
+        let out = CompareOutput {
+            name: "deprecated".into(),
+            query: vec!["ref".into(), "old_api".into()],
+            before: 2,
+            after: 2,
+            unchanged: 1,
+            added: vec![serde_json::json!({"file": "b.rs", "line": 9, "context": "old_api()"})],
+            removed: vec![serde_json::json!({"file": "a.rs", "line": 3})],
+        };
+        let text = render(OutputFormat::Grep, |fmt| fmt.format_compare(&out));
+        assert_eq!(
+            text,
+            "deprecated: 2 -> 2 entries (+1, -1)\n+ b.rs:9: old_api()\n- a.rs:3\n"
+        );
+
+        let json = render(OutputFormat::Json, |fmt| fmt.format_compare(&out));
+        let v: serde_json::Value = serde_json::from_str(json.trim()).unwrap();
+        assert_eq!(v["added"][0]["file"], "b.rs");
+        assert_eq!(v["unchanged"], 1);
+
+        let github = render(OutputFormat::Github, |fmt| fmt.format_compare(&out));
+        let mut lines = github.lines();
+        assert_eq!(
+            lines.next(),
+            Some("::warning file=b.rs,line=9::deprecated: old_api()")
+        );
+        let summary: serde_json::Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+        assert_eq!(summary["name"], "deprecated");
+    }
+
+    #[test]
+    fn self_update_grep_describes_outcome() {
+        let mut out = SelfUpdateOutput {
+            current_version: "4.14.1".into(),
+            latest_version: "4.15.0".into(),
+            channel: "stable".into(),
+            update_available: true,
+            updated: false,
+        };
+        let available = render(OutputFormat::Grep, |fmt| fmt.format_self_update(&out));
+        assert_eq!(
+            available,
+            "wonk 4.15.0 is available (current: 4.14.1); run `wonk self update` to install it\n"
+        );
+
+        out.updated = true;
+        let updated = render(OutputFormat::Grep, |fmt| fmt.format_self_update(&out));
+        assert_eq!(updated, "Updated wonk 4.14.1 -> 4.15.0\n");
+
+        out.latest_version = "4.14.1".into();
+        out.update_available = false;
+        out.updated = false;
+        let current = render(OutputFormat::Grep, |fmt| fmt.format_self_update(&out));
+        assert_eq!(current, "wonk 4.14.1 is up to date (stable channel)\n");
+
+        let json = render(OutputFormat::Json, |fmt| fmt.format_self_update(&out));
+        let v: serde_json::Value = serde_json::from_str(json.trim()).unwrap();
+        assert_eq!(v["update_available"], false);
+        assert_eq!(v["latest_version"], "4.14.1");
+    }
+
+    fn which_index_fixture() -> WhichIndexOutput {
+        WhichIndexOutput {
+            repo: "/r".into(),
+            index: Some("/r/.wonk/index.db".into()),
+            location: Some("local".into()),
+            reason: "local first".into(),
+            local_path: "/r/.wonk/index.db".into(),
+            local_exists: true,
+            central_path: Some("/h/.wonk/repos/abc/index.db".into()),
+            central_exists: false,
+            symbol_index: None,
+            built: None,
+            wonk_version: None,
+            commit: Some("0123456789abcdef".into()),
+            head: Some("fedcba9876543210".into()),
+            languages: vec!["Rust".into()],
+            disabled_languages: Vec::new(),
+            partial: true,
+        }
+    }
+
+    #[test]
+    fn which_index_grep_format_flags_drift() {
+        let out = render(OutputFormat::Grep, |fmt| {
+            fmt.format_which_index(&which_index_fixture())
+        });
+        assert!(
+            out.starts_with("index: /r/.wonk/index.db (local)\n"),
+            "{out}"
+        );
+        assert!(out.contains("commit: 0123456789ab (HEAD is now fedcba987654;"));
+        assert!(out.contains("partial: yes"));
+        assert!(out.contains("which does not exist, so they use grep"));
+        assert!(out.contains("  central  /h/.wonk/repos/abc/index.db (missing)"));
+    }
+
+    #[test]
+    fn which_index_json_keeps_null_locations() {
+        let out = render(OutputFormat::Json, |fmt| {
+            fmt.format_which_index(&which_index_fixture())
+        });
+        let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
+        assert_eq!(v["location"], "local");
+        assert!(v["symbol_index"].is_null());
+        assert!(v.get("built").is_none());
+        assert_eq!(v["partial"], true);
+    }
+
+    #[test]
+    fn describe_grep_format_lists_overview_and_symbols() {
+        let out = DescribeOutput {
+            file: "src/store.rs".into(),
+            language: "Rust".into(),
+            lines: 120,
+            role: "source".into(),
+            generated_by: None,
+            imports: vec!["std::fs".into()],
+            exports: vec!["Store".into(), "open".into()],
+            symbol_count: 4,
+            symbols: vec![
+                DescribeSymbolOutput {
+                    name: "open".into(),
+                    kind: "function".into(),
+                    line: 10,
+                    signature: "pub fn open() -> Store".into(),
+                    uses: 2,
+                },
+                DescribeSymbolOutput {
+                    name: "LIMIT".into(),
+                    kind: "constant".into(),
+                    line: 1,
+                    signature: String::new(),
+                    uses: 0,
+                },
+            ],
+        };
+        let text = render(OutputFormat::Grep, |fmt| fmt.format_describe(&out));
+        assert_eq!(
+            text,
+            "src/store.rs: Rust, 120 lines, 4 symbols, source\n\
+             imports: std::fs\n\
+             exports: Store, open\n\
+             src/store.rs:10:  pub fn open() -> Store  (2 uses)\n\
+             src/store.rs:1:  constant LIMIT\n"
+        );
+
+        let json = render(OutputFormat::Json, |fmt| fmt.format_describe(&out));
+        let v: serde_json::Value = serde_json::from_str(json.trim()).unwrap();
+        assert_eq!(v["role"], "source");
+        assert_eq!(v["symbols"][0]["uses"], 2);
+        assert!(v.get("generated_by").is_none());
+    }
+
+    #[test]
+    fn near_grep_format_tags_each_line() {
+        let sym = |name: &str, line: usize, signature: &str| NearSymbolOutput {
+            name: name.into(),
+            kind: "method".into(),
+            line,
+            end_line: None,
+            scope: Some("Store".into()),
+            signature: signature.into(),
+        };
+        let out = NearOutput {
+            file: "src/store.rs".into(),
+            line: 15,
+            enclosing: Some(sym("get", 12, "pub fn get()")),
+            siblings: vec![sym("open", 6, "pub fn open()"), sym("put", 22, "")],
+            references: vec![NearRefOutput {
+                name: "checksum".into(),
+                line: 15,
+                definition: Some("src/store.rs:32".into()),
+            }],
+            deps: vec!["std::fs".into()],
+            rdeps: Vec::new(),
+        };
+        let text = render(OutputFormat::Grep, |fmt| fmt.format_near(&out));
+        assert_eq!(
+            text,
+            "src/store.rs:12:  pub fn get()  [enclosing]\n\
+             src/store.rs:6:  pub fn open()  [sibling]\n\
+             src/store.rs:22:  method put  [sibling]\n\
+             src/store.rs:15:  checksum \u{2192} src/store.rs:32  [ref]\n\
+             deps: std::fs\n"
+        );
+    }
+
+    #[test]
+    fn message_meta_json_format() {
+        let meta = MessageMeta {
+            kind: MessageKind::Error,
+            message: "no index found".into(),
+            hint: Some("run `wonk init`".into()),
+            code: None,
+        };
+        let out = render(OutputFormat::Json, |fmt| fmt.format_message_meta(&meta));
+        let v: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
+        assert_eq!(v["type"], "error");
+        assert_eq!(v["message"], "no index found");
+        assert_eq!(v["hint"], "run `wonk init`");
+        assert_eq!(message_kind(&v), Some(MessageKind::Error));
+
+        let meta = MessageMeta {
+            kind: MessageKind::Hint,
+            message: "no callers found".into(),
+            hint: None,
+            code: None,
+        };
+        let out = render(OutputFormat::Json, |fmt| fmt.format_message_meta(&meta));
+        assert_eq!(
+            out.trim(),
+            r#"{"type":"hint","message":"no callers found"}"#
+        );
+    }
+
+    #[test]
+    fn interrupt_meta_json_format() {
+        let meta = InterruptMeta { interrupted: true };
+        let out = render(OutputFormat::Json, |fmt| fmt.format_interrupt_meta(&meta));
+        assert_eq!(out.trim(), r#"{"interrupted":true}"#);
+    }
+
     #[test]
     fn truncation_meta_serializes_all_fields() {
         let meta = TruncationMeta {
@@ -2817,9 +6216,15 @@ mod tests {
                 file: "src/main.rs".into(),
                 line: i + 1,
                 col: 1,
+                end_col: None,
+                start_byte: None,
+                end_byte: None,
                 content: "fn some_function_here() {}".into(),
                 annotation: None,
+                category: None,
                 source: None,
+                patterns: Vec::new(),
+                stamp: None,
             })
             .collect();
 
@@ -2856,9 +6261,15 @@ mod tests {
                 file: "src/main.rs".into(),
                 line: i + 1,
                 col: 1,
+                end_col: None,
+                start_byte: None,
+                end_byte: None,
                 content: "fn some_function_here() {}".into(),
                 annotation: None,
+                category: None,
                 source: None,
+                patterns: Vec::new(),
+                stamp: None,
             })
             .collect();
 
@@ -2904,9 +6315,15 @@ mod tests {
                 file: "src/main.rs".into(),
                 line: i + 1,
                 col: 1,
+                end_col: None,
+                start_byte: None,
+                end_byte: None,
                 content: "fn main() {}".into(),
                 annotation: None,
+                category: None,
                 source: None,
+                patterns: Vec::new(),
+                stamp: None,
             })
             .collect();
 
@@ -2937,9 +6354,15 @@ mod tests {
             file: "src/main.rs".into(),
             line: 1,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "fn main() {}".into(),
             annotation: None,
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
         };
         fmt.format_search_result(&r).unwrap();
         assert!(fmt.budget_used() > 0);
@@ -2949,17 +6372,29 @@ mod tests {
     fn budget_applies_to_symbol_output() {
         let syms: Vec<SymbolOutput> = (0..10)
             .map(|i| SymbolOutput {
+                query: None,
                 name: "some_really_long_function_name".into(),
                 kind: "function".into(),
                 file: "src/very/deep/nested/module.rs".into(),
                 line: i + 1,
                 col: 0,
                 end_line: None,
+                end_col: None,
+                start_byte: None,
+                end_byte: None,
                 scope: None,
                 signature:
                     "fn some_really_long_function_name(arg1: Type1, arg2: Type2) -> ReturnType"
                         .into(),
                 language: "Rust".into(),
+                freshness: None,
+                id: None,
+                generated_by: None,
+                condition: None,
+                visibility: None,
+                modifiers: Vec::new(),
+                generics: Vec::new(),
+                stamp: None,
             })
             .collect();
 
@@ -2986,9 +6421,15 @@ mod tests {
             file: "src/main.rs".into(),
             line: 1,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "Hello WORLD hello".into(),
             annotation: None,
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
         };
         let mut buf = Vec::new();
         {
@@ -3038,9 +6479,15 @@ mod tests {
             file: "src/main.rs".into(),
             line: 42,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "fn main() {}".into(),
             annotation: None,
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
         };
         let out = render(OutputFormat::Toon, |fmt| fmt.format_search_result(&result));
         assert!(!out.is_empty());
@@ -3053,15 +6500,27 @@ mod tests {
     #[test]
     fn symbol_toon_format() {
         let sym = SymbolOutput {
+            query: None,
             name: "main".into(),
             kind: "function".into(),
             file: "src/main.rs".into(),
             line: 10,
             col: 0,
             end_line: None,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             scope: None,
             signature: "fn main()".into(),
             language: "Rust".into(),
+            freshness: None,
+            id: None,
+            generated_by: None,
+            condition: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
+            stamp: None,
         };
         let out = render(OutputFormat::Toon, |fmt| fmt.format_symbol(&sym));
         let parsed: SymbolOutput = serde_toon2::from_str(out.trim()).unwrap();
@@ -3077,9 +6536,16 @@ mod tests {
             file: "src/lib.rs".into(),
             line: 99,
             col: 4,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             context: "    foo(42);".into(),
             caller_name: None,
             confidence: 0.5,
+            freshness: None,
+            definition: None,
+            generated_by: None,
+            stamp: None,
         };
         let out = render(OutputFormat::Toon, |fmt| fmt.format_reference(&reference));
         let parsed: RefOutput = serde_toon2::from_str(out.trim()).unwrap();
@@ -3094,6 +6560,8 @@ mod tests {
             line: 15,
             signature: "fn process(input: &str) -> Result<()>".into(),
             language: "Rust".into(),
+            freshness: None,
+            stamp: None,
         };
         let out = render(OutputFormat::Toon, |fmt| fmt.format_signature(&sig));
         let parsed: SignatureOutput = serde_toon2::from_str(out.trim()).unwrap();
@@ -3115,6 +6583,7 @@ mod tests {
         let dep = DepOutput {
             file: "src/main.rs".into(),
             depends_on: "src/lib.rs".into(),
+            generated_by: None,
         };
         let out = render(OutputFormat::Toon, |fmt| fmt.format_dep(&dep));
         let parsed: DepOutput = serde_toon2::from_str(out.trim()).unwrap();
@@ -3128,9 +6597,15 @@ mod tests {
             file: "src/main.rs".into(),
             line: 42,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "fn main() {}".into(),
             annotation: None,
+            category: None,
             source: None,
+            patterns: Vec::new(),
+            stamp: None,
         };
         let mut buf = Vec::new();
         {
@@ -3403,6 +6878,7 @@ mod tests {
             auto_shallow: None,
             truncated_at_line: None,
             total_lines: None,
+            stamp: None,
         };
         let rendered = render(OutputFormat::Grep, |fmt| fmt.format_show(&out));
         assert!(rendered.contains("  10| function processPayment()"));
@@ -3423,6 +6899,7 @@ mod tests {
             auto_shallow: None,
             truncated_at_line: None,
             total_lines: None,
+            stamp: None,
         };
         let rendered = render_color(|fmt| fmt.format_show(&out));
         assert!(
@@ -3444,6 +6921,7 @@ mod tests {
             auto_shallow: None,
             truncated_at_line: None,
             total_lines: None,
+            stamp: None,
         };
         let rendered = render(OutputFormat::Json, |fmt| fmt.format_show(&out));
         let v: serde_json::Value = serde_json::from_str(rendered.trim()).unwrap();
@@ -3469,6 +6947,7 @@ mod tests {
             auto_shallow: None,
             truncated_at_line: None,
             total_lines: None,
+            stamp: None,
         };
         let rendered = render(OutputFormat::Json, |fmt| fmt.format_show(&out));
         assert!(!rendered.contains("end_line"));
@@ -3487,6 +6966,7 @@ mod tests {
             auto_shallow: None,
             truncated_at_line: None,
             total_lines: None,
+            stamp: None,
         };
         let rendered = render(OutputFormat::Grep, |fmt| fmt.format_show(&out));
         assert_eq!(rendered, "   3| const MAX: usize = 1024;\n");
@@ -3510,6 +6990,7 @@ mod tests {
             auto_shallow: None,
             truncated_at_line: None,
             total_lines: None,
+            stamp: None,
         }
     }
 
@@ -3639,6 +7120,7 @@ mod tests {
             depth: 1,
             source_file: None,
             confidence: 0.5,
+            definition: None,
         };
         let rendered = render(OutputFormat::Grep, |fmt| fmt.format_callee(&out));
         assert!(rendered.contains("src/db.rs"));
@@ -3656,6 +7138,7 @@ mod tests {
             depth: 1,
             source_file: Some("src/router.rs".into()),
             confidence: 0.5,
+            definition: None,
         };
         let rendered = render(OutputFormat::Json, |fmt| fmt.format_callee(&out));
         let v: serde_json::Value = serde_json::from_str(rendered.trim()).unwrap();
@@ -3676,6 +7159,7 @@ mod tests {
             depth: 1,
             source_file: None,
             confidence: 0.5,
+            definition: None,
         };
         let rendered = render(OutputFormat::Json, |fmt| fmt.format_callee(&out));
         let v: serde_json::Value = serde_json::from_str(rendered.trim()).unwrap();
@@ -3692,12 +7176,37 @@ mod tests {
             depth: 1,
             source_file: None,
             confidence: 0.95,
+            definition: None,
         };
         let rendered = render(OutputFormat::Json, |fmt| fmt.format_callee(&out));
         let v: serde_json::Value = serde_json::from_str(rendered.trim()).unwrap();
         assert_eq!(v["confidence"], 0.95);
     }
 
+    #[test]
+    fn callee_grep_format_shows_definition() {
+        let out = CalleeOutput {
+            callee_name: "open_db".into(),
+            file: "src/router.rs".into(),
+            line: 10,
+            context: "    let conn = open_db(&path);".into(),
+            depth: 1,
+            source_file: None,
+            confidence: 1.0,
+            definition: Some(DefinitionOutput {
+                file: "src/db.rs".into(),
+                line: 42,
+                kind: "function".into(),
+                via: "import".into(),
+            }),
+        };
+        let rendered = render(OutputFormat::Grep, |fmt| fmt.format_callee(&out));
+        assert!(rendered.ends_with("open_db(&path);  -> src/db.rs:42\n"));
+        let rendered = render(OutputFormat::Json, |fmt| fmt.format_callee(&out));
+        let v: serde_json::Value = serde_json::from_str(rendered.trim()).unwrap();
+        assert_eq!(v["definition"]["via"], "import");
+    }
+
     // -- CallPathHopOutput ---------------------------------------------------
 
     #[test]
@@ -3953,6 +7462,105 @@ mod tests {
         assert_eq!(v["depth"], 0);
     }
 
+    // -- AsyncBoundaryOutput / UnsafeSiteOutput -----------------------------
+
+    fn make_hop(name: &str, line: usize) -> CallPathHopOutput {
+        CallPathHopOutput {
+            symbol_name: name.into(),
+            symbol_kind: "function".into(),
+            file: "src/main.rs".into(),
+            line,
+        }
+    }
+
+    #[test]
+    fn async_boundary_grep_format() {
+        let out = AsyncBoundaryOutput {
+            function: make_hop("fetch", 2),
+            sync_callers: vec![make_hop("main", 8)],
+        };
+        let rendered = render(OutputFormat::Grep, |fmt| fmt.format_async_boundary(&out));
+        assert!(rendered.contains("src/main.rs:2:async fetch (function)"));
+        assert!(rendered.contains("  called from main (function)\tsrc/main.rs:8"));
+    }
+
+    #[test]
+    fn unsafe_site_formats() {
+        let block = UnsafeSiteOutput {
+            kind: "block".into(),
+            file: "src/ffi.rs".into(),
+            line: 7,
+            symbol: Some("read".into()),
+        };
+        let rendered = render(OutputFormat::Grep, |fmt| fmt.format_unsafe_site(&block));
+        assert!(rendered.contains("src/ffi.rs:7:unsafe block in read"));
+
+        let decl = UnsafeSiteOutput {
+            kind: "declaration".into(),
+            file: "src/ffi.rs".into(),
+            line: 2,
+            symbol: Some("raw_read".into()),
+        };
+        let rendered = render(OutputFormat::Json, |fmt| fmt.format_unsafe_site(&decl));
+        let v: serde_json::Value = serde_json::from_str(rendered.trim()).unwrap();
+        assert_eq!(v["kind"], "declaration");
+        assert_eq!(v["symbol"], "raw_read");
+    }
+
+    fn make_file_listing() -> FileListingOutput {
+        FileListingOutput {
+            file: "src/config.rs".into(),
+            language: "Rust".into(),
+            symbol_count: 4,
+            symbols: vec![SummarySymbolOutput {
+                name: "Config".into(),
+                kind: "struct".into(),
+                signature: "pub struct Config".into(),
+                line: 1,
+                col: 0,
+                end_line: None,
+                scope: None,
+                doc_comment: None,
+                indent: 0,
+            }],
+        }
+    }
+
+    #[test]
+    fn file_listing_grep_format() {
+        let listing = make_file_listing();
+        let rendered = render(OutputFormat::Grep, |fmt| fmt.format_file_listing(&listing));
+        assert_eq!(rendered, "src/config.rs:Rust, 4 symbols — struct Config\n");
+    }
+
+    #[test]
+    fn file_listing_json_nests_symbols() {
+        let listing = make_file_listing();
+        let rendered = render(OutputFormat::Json, |fmt| fmt.format_file_listing(&listing));
+        let v: serde_json::Value = serde_json::from_str(rendered.trim()).unwrap();
+        assert_eq!(v["file"], "src/config.rs");
+        assert_eq!(v["symbol_count"], 4);
+        assert_eq!(v["symbols"][0]["name"], "Config");
+        assert_eq!(v["symbols"][0]["line"], 1);
+    }
+
+    #[test]
+    fn file_symbols_grep_groups_under_header() {
+        let mut listing = make_file_listing();
+        let mut method = listing.symbols[0].clone();
+        method.name = "new".into();
+        method.kind = "method".into();
+        method.signature = String::new();
+        method.line = 3;
+        method.indent = 1;
+        listing.symbols.push(method);
+        let rendered = render(OutputFormat::Grep, |fmt| fmt.format_file_symbols(&listing));
+        assert_eq!(
+            rendered,
+            "src/config.rs (Rust)\n  1:struct Config — pub struct Config\n    3:method new\n"
+        );
+    }
+
     #[test]
     fn flow_grep_format() {
         let entry = make_flow_step("main", 0);
@@ -0,0 +1,219 @@
+//! Automatic paging of human-readable output, as git does.
+//!
+//! When stdout is a terminal, command output is piped through `$PAGER`
+//! (default `less`).  The pager starts on the first write, so commands that
+//! print nothing, or fail before printing, never open it.  `LESS` defaults
+//! to `FRX`, which makes `less` exit at once when the output fits on one
+//! screen and keeps colors.  While the pager runs, a terminal stderr is
+//! routed into it too, so hints and category headers stay in order with
+//! the results.
+
+use std::io::{self, LineWriter, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+/// The pager command to run: `$PAGER`, or `less` when unset.
+///
+/// Returns `None` when paging is effectively off (`PAGER=` or `PAGER=cat`).
+pub fn command() -> Option<String> {
+    command_from(std::env::var("PAGER").ok().as_deref())
+}
+
+fn command_from(pager: Option<&str>) -> Option<String> {
+    let cmd = pager.unwrap_or("less").trim();
+    (!cmd.is_empty() && cmd != "cat").then(|| cmd.to_string())
+}
+
+/// A writer that forwards to a pager process once written to, falling back
+/// to the wrapped writer when no pager is configured or it fails to start.
+pub struct Pager<W: Write> {
+    out: W,
+    /// Command still to start on the first write.
+    pending: Option<String>,
+    /// Route stderr into the pager while it runs.
+    capture_stderr: bool,
+    running: Option<Running>,
+}
+
+struct Running {
+    child: Child,
+    /// Line-buffered like stdout, so results interleave with stderr.
+    stdin: Option<LineWriter<ChildStdin>>,
+    /// The original stderr, restored before waiting for the pager.
+    saved_stderr: Option<libc::c_int>,
+    /// Set once the user quits the pager; later output is discarded.
+    closed: bool,
+}
+
+impl<W: Write> Pager<W> {
+    /// Wrap `out`, paging through `command` when it is `Some`.
+    pub fn new(out: W, command: Option<String>, capture_stderr: bool) -> Self {
+        Self {
+            out,
+            pending: command,
+            capture_stderr,
+            running: None,
+        }
+    }
+
+    /// Whether output is going to a pager process.
+    pub fn is_paging(&self) -> bool {
+        self.running.is_some()
+    }
+
+    fn start(&mut self, cmd: &str) {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(cmd).stdin(Stdio::piped());
+        if std::env::var_os("LESS").is_none() {
+            command.env("LESS", "FRX");
+        }
+        if std::env::var_os("LV").is_none() {
+            command.env("LV", "-c");
+        }
+        let Ok(mut child) = command.spawn() else {
+            return;
+        };
+        let stdin = child.stdin.take();
+        let saved_stderr = if self.capture_stderr {
+            redirect_stderr(stdin.as_ref())
+        } else {
+            None
+        };
+        let stdin = stdin.map(LineWriter::new);
+        self.running = Some(Running {
+            child,
+            stdin,
+            saved_stderr,
+            closed: false,
+        });
+    }
+
+    /// Close the pager's input and wait for the user to quit it.
+    fn finish(&mut self) {
+        if let Some(mut running) = self.running.take() {
+            if let Some(mut stdin) = running.stdin.take() {
+                let _ = stdin.flush();
+            }
+            if let Some(fd) = running.saved_stderr {
+                // SAFETY: `fd` is a descriptor we duplicated from stderr and
+                // still own; restoring it drops the last handle on the pipe.
+                unsafe {
+                    libc::dup2(fd, libc::STDERR_FILENO);
+                    libc::close(fd);
+                }
+            }
+            let _ = running.child.wait();
+        }
+    }
+}
+
+/// Point stderr at the pager's input, returning the saved original.
+fn redirect_stderr(stdin: Option<&ChildStdin>) -> Option<libc::c_int> {
+    use std::os::fd::AsRawFd;
+    let pipe = stdin?.as_raw_fd();
+    // SAFETY: plain descriptor duplication; failures leave stderr as is.
+    unsafe {
+        let saved = libc::dup(libc::STDERR_FILENO);
+        if saved < 0 {
+            return None;
+        }
+        if libc::dup2(pipe, libc::STDERR_FILENO) < 0 {
+            libc::close(saved);
+            return None;
+        }
+        Some(saved)
+    }
+}
+
+impl<W: Write> Write for Pager<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(cmd) = self.pending.take() {
+            self.start(&cmd);
+        }
+        let Some(running) = &mut self.running else {
+            return self.out.write(buf);
+        };
+        if running.closed {
+            return Ok(buf.len());
+        }
+        let Some(stdin) = &mut running.stdin else {
+            return Ok(buf.len());
+        };
+        match stdin.write(buf) {
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {
+                running.closed = true;
+                Ok(buf.len())
+            }
+            result => result,
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.running {
+            Some(Running {
+                stdin: Some(stdin),
+                closed: false,
+                ..
+            }) => match stdin.flush() {
+                Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+                result => result,
+            },
+            Some(_) => Ok(()),
+            None => self.out.flush(),
+        }
+    }
+}
+
+impl<W: Write> Drop for Pager<W> {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_defaults_to_less_and_cat_disables_paging() {
+        assert_eq!(command_from(None).as_deref(), Some("less"));
+        assert_eq!(command_from(Some("most -s")).as_deref(), Some("most -s"));
+        assert_eq!(command_from(Some("")), None);
+        assert_eq!(command_from(Some("cat")), None);
+    }
+
+    #[test]
+    fn without_command_writes_go_to_the_wrapped_writer() {
+        let mut buf = Vec::new();
+        {
+            let mut pager = Pager::new(&mut buf, None, false);
+            writeln!(pager, "hello").unwrap();
+            assert!(!pager.is_paging());
+        }
+        assert_eq!(buf, b"hello\n");
+    }
+
+    #[test]
+    fn output_is_piped_through_the_pager() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let target = dir.path().join("paged.txt");
+        let mut buf = Vec::new();
+        {
+            let cmd = format!("cat > '{}'", target.display());
+            let mut pager = Pager::new(&mut buf, Some(cmd), false);
+            writeln!(pager, "one").unwrap();
+            writeln!(pager, "two").unwrap();
+            assert!(pager.is_paging());
+        }
+        assert!(buf.is_empty());
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "one\ntwo\n");
+    }
+
+    #[test]
+    fn quitting_the_pager_early_discards_the_rest() {
+        let mut pager = Pager::new(Vec::new(), Some("true".to_string()), false);
+        for _ in 0..10_000 {
+            writeln!(pager, "line").unwrap();
+        }
+        pager.flush().unwrap();
+    }
+}
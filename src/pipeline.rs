@@ -12,20 +12,24 @@
 //! and [`process_events`].
 
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
-use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Once, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
 use rayon::prelude::*;
 use rusqlite::Connection;
 
 use crate::db;
+use crate::deadline;
+use crate::diskspace;
 use crate::embedding::{self, OllamaClient};
-use crate::errors::EmbeddingError;
+use crate::errors::{DiskSpaceError, EmbeddingError};
 use crate::indexer;
 use crate::progress::{Progress, ProgressMode};
 use crate::types::{RawTypeEdge, Reference, Symbol};
-use crate::walker::Walker;
+use crate::walker::{SourceFile, Walker};
 use crate::watcher::FileEvent;
 
 // ---------------------------------------------------------------------------
@@ -49,6 +53,194 @@ pub struct IndexStats {
     pub elapsed: std::time::Duration,
 }
 
+// ---------------------------------------------------------------------------
+// BuildLimits
+// ---------------------------------------------------------------------------
+
+/// Thread count set by `--jobs`, overriding `[index].jobs`.
+static JOBS_OVERRIDE: OnceLock<usize> = OnceLock::new();
+
+/// Override `[index].jobs` for every index build in this process
+/// (`--jobs`).  Only the first call has an effect.
+pub fn set_jobs(jobs: usize) {
+    let _ = JOBS_OVERRIDE.set(jobs);
+}
+
+/// CPU limits for index builds, from `[index].jobs` and `[index].nice`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BuildLimits {
+    /// Parser threads; `0` uses one per core.
+    pub jobs: usize,
+    /// Niceness (0-19) of the threads doing the build; `0` leaves it alone.
+    pub nice: i32,
+}
+
+impl BuildLimits {
+    /// Limits from `config`, with `--jobs` (see [`set_jobs`]) taking
+    /// precedence.
+    pub fn from_config(config: &crate::config::IndexConfig) -> Self {
+        Self {
+            jobs: JOBS_OVERRIDE.get().copied().unwrap_or(config.jobs),
+            nice: config.nice.clamp(0, 19),
+        }
+    }
+
+    /// Run `f` on a thread pool honouring these limits, so its parallel
+    /// iterators use at most `jobs` threads at priority `nice`.  Runs `f`
+    /// directly when there are no limits or the pool cannot be created.
+    pub fn install<T: Send>(&self, f: impl FnOnce() -> T + Send) -> T {
+        if *self == Self::default() {
+            return f();
+        }
+        let nice = self.nice;
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.jobs)
+            .thread_name(|i| format!("wonk-index-{i}"))
+            .start_handler(move |_| lower_priority(nice))
+            .build();
+        match pool {
+            Ok(pool) => pool.install(f),
+            Err(_) => f(),
+        }
+    }
+}
+
+/// How a query in a repository without an index builds one, from
+/// `[index].auto_init`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AutoInit {
+    /// Index the whole repository before answering.
+    #[default]
+    Full,
+    /// Answer from the grep fallback and add the files it finds definitions
+    /// in to the index (see [`promote_files`]), so repeated queries build it
+    /// up without a `wonk init`.
+    Lazy,
+}
+
+impl AutoInit {
+    pub const NAMES: &[&str] = &["full", "lazy"];
+
+    /// The policy in `config`.  An unknown value is reported once and
+    /// treated as the default.
+    pub fn from_config(config: &crate::config::IndexConfig) -> Self {
+        static WARNED: Once = Once::new();
+        config.auto_init.parse().unwrap_or_else(|e: String| {
+            WARNED.call_once(|| crate::output::print_warning(&format!("{e}; using full")));
+            AutoInit::default()
+        })
+    }
+}
+
+impl FromStr for AutoInit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(AutoInit::Full),
+            "lazy" => Ok(AutoInit::Lazy),
+            other => Err(format!(
+                "unknown [index].auto_init {other:?} (available: {})",
+                AutoInit::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// Raise the calling thread's niceness to `nice` (0-19).  Threads it spawns
+/// inherit the priority; on macOS the whole process is affected.  Never
+/// raises priority, and ignores failures.
+pub fn lower_priority(nice: i32) {
+    let nice = nice.clamp(0, 19);
+    if nice == 0 {
+        return;
+    }
+    // SAFETY: getpriority/setpriority only read and set the scheduling
+    // priority of the calling thread (Linux) or process; no memory is shared.
+    unsafe {
+        let current = libc::getpriority(libc::PRIO_PROCESS, 0);
+        if current < nice {
+            libc::setpriority(libc::PRIO_PROCESS, 0, nice);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// IndexProfile
+// ---------------------------------------------------------------------------
+
+/// Where the time of a full index build went (see [`build_index_profiled`]).
+#[derive(Debug, Clone, Default)]
+pub struct IndexProfile {
+    /// Walking the repository for candidate files.
+    pub walk: Duration,
+    /// Wall-clock time of the parallel parse phase.
+    pub parse: Duration,
+    /// Inserting symbols, references, and edges into SQLite.
+    pub insert: Duration,
+    /// Resolving import paths to indexed files.
+    pub resolve: Duration,
+    /// The whole build.
+    pub total: Duration,
+    /// Per-file parse times (read, parse, and extract), in walk order.
+    pub files: Vec<FileTiming>,
+}
+
+/// Parse time of one indexed file.
+#[derive(Debug, Clone)]
+pub struct FileTiming {
+    pub path: String,
+    pub language: String,
+    pub lines: usize,
+    pub parse: Duration,
+}
+
+/// Parse time summed over the files of one language.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanguageTiming {
+    pub language: String,
+    pub files: usize,
+    pub lines: usize,
+    pub parse: Duration,
+}
+
+impl IndexProfile {
+    /// Parse time summed over all files (CPU time across threads, so it
+    /// exceeds [`parse`](Self::parse) on multi-core machines).
+    pub fn parse_cpu(&self) -> Duration {
+        self.files.iter().map(|f| f.parse).sum()
+    }
+
+    /// Per-language totals, slowest language first.
+    pub fn by_language(&self) -> Vec<LanguageTiming> {
+        let mut totals: HashMap<&str, LanguageTiming> = HashMap::new();
+        for f in &self.files {
+            let entry = totals
+                .entry(f.language.as_str())
+                .or_insert_with(|| LanguageTiming {
+                    language: f.language.clone(),
+                    files: 0,
+                    lines: 0,
+                    parse: Duration::ZERO,
+                });
+            entry.files += 1;
+            entry.lines += f.lines;
+            entry.parse += f.parse;
+        }
+        let mut languages: Vec<LanguageTiming> = totals.into_values().collect();
+        languages.sort_by(|a, b| b.parse.cmp(&a.parse).then(a.language.cmp(&b.language)));
+        languages
+    }
+
+    /// The `n` files that took longest to parse, slowest first.
+    pub fn slowest_files(&self, n: usize) -> Vec<&FileTiming> {
+        let mut files: Vec<&FileTiming> = self.files.iter().collect();
+        files.sort_by(|a, b| b.parse.cmp(&a.parse).then(a.path.cmp(&b.path)));
+        files.truncate(n);
+        files
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Per-file parse result (collected from parallel phase)
 // ---------------------------------------------------------------------------
@@ -63,6 +255,10 @@ struct FileResult {
     content_hash: String,
     /// Line count.
     line_count: usize,
+    /// Whether the file is test code (path conventions or content markers).
+    is_test: bool,
+    /// Code generator named in the file header, if the file is generated.
+    generated_by: Option<String>,
     /// Extracted symbols.
     symbols: Vec<Symbol>,
     /// Extracted references.
@@ -81,13 +277,17 @@ struct FileResult {
 ///
 /// Steps:
 /// 1. Determine the index path (central or local).
-/// 2. Create the index directory and open/create the SQLite database.
-/// 3. Walk files using [`Walker`].
+/// 2. Walk files using [`Walker`] and check that the index will fit on disk
+///    (see [`crate::diskspace`]).
+/// 3. Create the index directory and open/create the SQLite database.
 /// 4. Parse files in parallel with rayon (detect language, parse with
 ///    tree-sitter, extract symbols + references, compute xxhash).
 /// 5. Batch-insert results into SQLite inside a transaction.
 /// 6. Write `meta.json`.
 /// 7. Return [`IndexStats`].
+///
+/// When the disk fills up during the build, the partial database is removed
+/// and a [`DiskSpaceError::FullDuringBuild`] is returned.
 pub fn build_index(repo_root: &Path, local: bool) -> Result<IndexStats> {
     build_index_with_progress(repo_root, local, &Progress::silent())
 }
@@ -101,38 +301,204 @@ pub fn build_index_with_progress(
     local: bool,
     progress: &Progress,
 ) -> Result<IndexStats> {
-    let start = Instant::now();
+    build_index_profiled(repo_root, local, progress).map(|(stats, _)| stats)
+}
 
-    // 1. Determine index path.
+/// Build a fresh index, also returning where the time went
+/// (`wonk init --profile`).
+///
+/// Same as [`build_index_with_progress`]; the timings are taken either way
+/// and cost one clock read per file.
+pub fn build_index_profiled(
+    repo_root: &Path,
+    local: bool,
+    progress: &Progress,
+) -> Result<(IndexStats, IndexProfile)> {
+    let config = crate::config::Config::load(Some(repo_root)).unwrap_or_default();
     let index_path = db::index_path_for(repo_root, local)?;
+    let result = BuildLimits::from_config(&config.index)
+        .install(|| build_index_inner(repo_root, &index_path, progress, &config));
+    clean_up_full_build(result, &index_path)
+}
 
-    // 2. Open (or create) the database.
-    let conn = db::open(&index_path)?;
+/// Build a fresh index for `root` from the contents of an archive or bare
+/// git repository (`wonk init --archive`).  `root/.wonk/` is created so that
+/// queries run under `root` find the index.
+///
+/// Members are read into memory (see [`crate::archive`]), the first `strip`
+/// path components are dropped, and the rest are filtered with the walker's
+/// rules and `root`'s config; nothing is unpacked to disk.
+pub fn build_index_from_archive(
+    archive: &Path,
+    strip: usize,
+    root: &Path,
+    progress: &Progress,
+) -> Result<IndexStats> {
+    let config = crate::config::Config::load(Some(root)).unwrap_or_default();
+    let index_path = db::index_path_for(root, false)?;
+    let marker = root.join(".wonk");
+    std::fs::create_dir_all(&marker).with_context(|| format!("creating {}", marker.display()))?;
+    let result = BuildLimits::from_config(&config.index).install(|| {
+        let start = Instant::now();
+        let walker = Walker::new(root).with_ignore_patterns(&config.ignore.patterns);
+        let files: Vec<SourceFile> = crate::archive::read(archive, strip)?
+            .into_iter()
+            .filter(|entry| walker.accepts(&entry.path))
+            .map(|entry| SourceFile::Memory {
+                path: entry.path,
+                contents: entry.contents,
+            })
+            .collect();
+        let walk_time = start.elapsed();
+        build_from_files(
+            root,
+            &index_path,
+            files,
+            (start, walk_time),
+            progress,
+            &config,
+        )
+    });
+    let stats = clean_up_full_build(result, &index_path).map(|(stats, _)| stats)?;
+    db::mark_archive(&index_path, archive)?;
+    Ok(stats)
+}
 
-    // 2b. Clear any existing data so fresh build is idempotent.
-    drop_all_data(&conn)?;
+/// Refuse to touch the index at `index_path` from the working tree when it
+/// was built from an archive (see [`db::archive_source`]).
+pub fn ensure_not_archive(index_path: &Path) -> Result<()> {
+    if let Some(source) = db::archive_source(index_path) {
+        anyhow::bail!(
+            "the index here was built from {source}; re-run `wonk init --archive` to refresh it \
+             (indexing the working tree would drop everything)"
+        );
+    }
+    Ok(())
+}
 
-    // 3. Walk files (respecting config ignore patterns).
-    let config = crate::config::Config::load(Some(repo_root)).unwrap_or_default();
-    let paths = Walker::new(repo_root)
+/// Turn a disk-full failure of a fresh build into a
+/// [`DiskSpaceError::FullDuringBuild`], removing the partial database.
+fn clean_up_full_build<T>(result: Result<T>, index_path: &Path) -> Result<T> {
+    match result {
+        Err(e) if diskspace::is_disk_full(&e) => {
+            diskspace::remove_index(index_path);
+            Err(DiskSpaceError::FullDuringBuild {
+                dir: parent_display(index_path),
+            }
+            .into())
+        }
+        other => other,
+    }
+}
+
+/// Display form of the directory holding `index_path`.
+fn parent_display(index_path: &Path) -> String {
+    index_path
+        .parent()
+        .unwrap_or(index_path)
+        .display()
+        .to_string()
+}
+
+/// Submodules of `repo_root` to index, as paths relative to it: the
+/// checked-out ones when `[index].submodules` is set, otherwise none.
+fn submodules_to_index(repo_root: &Path, config: &crate::config::Config) -> Vec<String> {
+    if config.index.submodules {
+        db::submodule_paths(repo_root)
+    } else {
+        Vec::new()
+    }
+}
+
+/// A walker over `root` (the repository root or a directory under it) with
+/// config ignore patterns applied and indexed submodules let through.
+fn walker_for(root: &Path, repo_root: &Path, config: &crate::config::Config) -> Walker {
+    let submodules = submodules_to_index(repo_root, config)
+        .iter()
+        .map(|sub| repo_root.join(sub))
+        .collect();
+    Walker::new(root)
         .with_ignore_patterns(&config.ignore.patterns)
-        .collect_paths();
+        .with_submodules(submodules)
+}
+
+fn build_index_inner(
+    repo_root: &Path,
+    index_path: &Path,
+    progress: &Progress,
+    config: &crate::config::Config,
+) -> Result<(IndexStats, IndexProfile)> {
+    let start = Instant::now();
+
+    // Walk files (respecting config ignore patterns).
+    let files: Vec<SourceFile> = walker_for(repo_root, repo_root, config)
+        .collect_paths()
+        .into_iter()
+        .map(SourceFile::Disk)
+        .collect();
+    let walk_time = start.elapsed();
+
+    build_from_files(
+        repo_root,
+        index_path,
+        files,
+        (start, walk_time),
+        progress,
+        config,
+    )
+}
+
+/// Index `paths` into a fresh database at `index_path`, given the build start
+/// and the time spent collecting `paths`.
+fn build_from_files(
+    repo_root: &Path,
+    index_path: &Path,
+    paths: Vec<SourceFile>,
+    (start, walk_time): (Instant, Duration),
+    progress: &Progress,
+    config: &crate::config::Config,
+) -> Result<(IndexStats, IndexProfile)> {
+    // An interrupt stops parsing below; the files parsed so far must still
+    // be committed, so SQL interrupts are held back for the whole build.
+    let _shield = deadline::shield();
+
+    // Refuse to start before touching an existing index when the new one
+    // will not fit.
+    let source_bytes: u64 = paths.iter().map(SourceFile::len).sum();
+    diskspace::check(index_path, source_bytes)?;
+
+    // 3. Open (or create) the database, clearing any existing data so a
+    //    fresh build is idempotent.
+    let conn = db::open(index_path)?;
+    drop_all_data(&conn)?;
 
     // Set total for progress reporting.
     progress.set_total(paths.len());
 
     // 4. Parse in parallel.
-    let results: Vec<FileResult> = paths
+    let phase = Instant::now();
+    let (results, parse_times): (Vec<FileResult>, Vec<Duration>) = paths
         .par_iter()
         .filter_map(|path| {
-            let result = parse_one_file(path, repo_root);
+            if deadline::build_interrupted() {
+                return None;
+            }
+            let file_start = Instant::now();
+            let result = parse_one_file(path, repo_root, config.ruby.rails);
             progress.inc();
-            result
+            result.map(|r| (r, file_start.elapsed()))
         })
-        .collect();
+        .unzip();
+    let parse_time = phase.elapsed();
 
     // 5. Batch insert.
+    let phase = Instant::now();
     let (sym_count, ref_count, caller_count, type_edge_count) = batch_insert(&conn, &results)?;
+    let insert_time = phase.elapsed();
+    let phase = Instant::now();
+    crate::imports::resolve_pending(&conn, Some(repo_root))?;
+    let resolve_time = phase.elapsed();
+    db::tag_submodules(&conn, &submodules_to_index(repo_root, config))?;
 
     // 6. Collect languages seen and write meta.json.
     let languages: Vec<String> = {
@@ -144,16 +510,34 @@ pub fn build_index_with_progress(
         v.sort();
         v
     };
-    db::write_meta(&index_path, repo_root, &languages)?;
+    db::write_meta(index_path, repo_root, &languages)?;
 
-    Ok(IndexStats {
+    let stats = IndexStats {
         file_count: results.len(),
         symbol_count: sym_count,
         ref_count,
         caller_count,
         type_edge_count,
         elapsed: start.elapsed(),
-    })
+    };
+    let profile = IndexProfile {
+        walk: walk_time,
+        parse: parse_time,
+        insert: insert_time,
+        resolve: resolve_time,
+        total: stats.elapsed,
+        files: results
+            .iter()
+            .zip(parse_times)
+            .map(|(r, parse)| FileTiming {
+                path: r.rel_path.clone(),
+                language: r.language.clone(),
+                lines: r.line_count,
+                parse,
+            })
+            .collect(),
+    };
+    Ok((stats, profile))
 }
 
 /// Drop all data and rebuild the index from scratch.
@@ -172,15 +556,8 @@ pub fn rebuild_index_with_progress(
     local: bool,
     progress: &Progress,
 ) -> Result<IndexStats> {
-    let index_path = db::index_path_for(repo_root, local)?;
-
-    // If the database exists, drop all data.
-    if index_path.exists() {
-        let conn = db::open(&index_path)?;
-        drop_all_data(&conn)?;
-        drop(conn);
-    }
-
+    // The build clears existing data itself, after the disk space check, so
+    // a refused rebuild leaves the current index usable.
     build_index_with_progress(repo_root, local, progress)
 }
 
@@ -192,29 +569,82 @@ pub fn rebuild_index_with_progress(
 ///
 /// Returns [`IndexStats`] reflecting what is now in the database.
 pub fn incremental_update(repo_root: &Path, local: bool) -> Result<IndexStats> {
+    update_paths(repo_root, local, &[])
+}
+
+/// Incrementally update only the files at or below `paths` (relative to
+/// `repo_root`): those on disk are re-indexed and indexed ones that are gone
+/// are removed, leaving the rest of the index untouched.  An empty `paths`
+/// updates the whole repository like [`incremental_update`].
+///
+/// Returns [`IndexStats`] reflecting what is now in the database.
+pub fn update_paths(repo_root: &Path, local: bool, paths: &[PathBuf]) -> Result<IndexStats> {
+    ensure_not_archive(&db::index_path_for(repo_root, local)?)?;
+    let config = crate::config::Config::load(Some(repo_root)).unwrap_or_default();
+    let result = BuildLimits::from_config(&config.index)
+        .install(|| incremental_update_inner(repo_root, local, &config, paths));
+    match result {
+        Err(e) if diskspace::is_disk_full(&e) => Err(DiskSpaceError::FullDuringUpdate {
+            dir: parent_display(&db::index_path_for(repo_root, local)?),
+        }
+        .into()),
+        other => other,
+    }
+}
+
+/// Add the files at `paths` (relative to `repo_root`) to the index, creating
+/// it when missing, and mark it partial: the rest of the repository is left
+/// for `wonk update` (see [`AutoInit::Lazy`]).
+pub fn promote_files(repo_root: &Path, local: bool, paths: &[PathBuf]) -> Result<IndexStats> {
+    let stats = update_paths(repo_root, local, paths)?;
+    db::mark_partial(&db::index_path_for(repo_root, local)?)?;
+    Ok(stats)
+}
+
+fn incremental_update_inner(
+    repo_root: &Path,
+    local: bool,
+    config: &crate::config::Config,
+    paths: &[PathBuf],
+) -> Result<IndexStats> {
     let start = Instant::now();
+    let _shield = deadline::shield();
 
     let index_path = db::index_path_for(repo_root, local)?;
+    // Updating some paths leaves the rest of a partial index as partial.
+    let was_partial = !paths.is_empty() && db::read_meta(&index_path).is_ok_and(|m| m.partial);
     let conn = db::open(&index_path)?;
 
-    // Walk current files on disk.
-    let config = crate::config::Config::load(Some(repo_root)).unwrap_or_default();
-    let on_disk: HashSet<String> = Walker::new(repo_root)
-        .with_ignore_patterns(&config.ignore.patterns)
-        .collect_paths()
-        .into_iter()
-        .filter_map(|p| {
-            p.strip_prefix(repo_root)
-                .ok()
-                .map(|r| r.to_string_lossy().into_owned())
-        })
-        .collect();
+    // Walk current files on disk: the whole repository, or each of `paths`
+    // with config ignore patterns still applied from the repository root.
+    let walk = |root: &Path| {
+        walker_for(root, repo_root, config)
+            .collect_paths()
+            .into_iter()
+            .filter_map(|p| {
+                p.strip_prefix(repo_root)
+                    .ok()
+                    .map(|r| r.to_string_lossy().into_owned())
+            })
+    };
+    let on_disk: HashSet<String> = if paths.is_empty() {
+        walk(repo_root).collect()
+    } else {
+        let root_walker = walker_for(repo_root, repo_root, config);
+        paths
+            .iter()
+            .filter(|p| repo_root.join(p).exists())
+            .flat_map(|p| walk(&repo_root.join(p)))
+            .filter(|rel| root_walker.accepts(Path::new(rel)))
+            .collect()
+    };
 
-    // Query indexed paths.
+    // Query indexed paths (those under `paths`, when given).
     let mut stmt = conn.prepare("SELECT path FROM files")?;
     let indexed: HashSet<String> = stmt
         .query_map([], |row| row.get::<_, String>(0))?
         .filter_map(|r| r.ok())
+        .filter(|rel| paths.is_empty() || paths.iter().any(|p| Path::new(rel).starts_with(p)))
         .collect();
 
     // Remove files no longer on disk.
@@ -227,9 +657,18 @@ pub fn incremental_update(repo_root: &Path, local: bool) -> Result<IndexStats> {
 
     // Re-index files on disk (reindex_file skips unchanged via hash).
     for rel in &on_disk {
+        if deadline::build_interrupted() {
+            break;
+        }
         let abs = repo_root.join(rel);
         let _ = reindex_file(&conn, &abs, repo_root);
     }
+    if paths.is_empty() {
+        // A full update also picks up build file changes the daemon missed.
+        crate::imports::retry_all(&conn)?;
+    }
+    crate::imports::resolve_pending(&conn, Some(repo_root))?;
+    db::tag_submodules(&conn, &submodules_to_index(repo_root, config))?;
 
     // Collect languages and rewrite meta.json.
     let mut lang_stmt = conn.prepare("SELECT DISTINCT language FROM files")?;
@@ -239,6 +678,9 @@ pub fn incremental_update(repo_root: &Path, local: bool) -> Result<IndexStats> {
         .collect();
     languages.sort();
     db::write_meta(&index_path, repo_root, &languages)?;
+    if was_partial {
+        db::mark_partial(&index_path)?;
+    }
 
     // Gather final stats from DB.
     let file_count = conn
@@ -277,6 +719,11 @@ pub fn incremental_update(repo_root: &Path, local: bool) -> Result<IndexStats> {
     })
 }
 
+/// Compute the content hash stored in the `files` table (xxh3, 16 hex digits).
+pub fn content_hash(content: &str) -> String {
+    format!("{:016x}", xxhash_rust::xxh3::xxh3_64(content.as_bytes()))
+}
+
 // ---------------------------------------------------------------------------
 // ProcessResult
 // ---------------------------------------------------------------------------
@@ -317,7 +764,7 @@ pub fn reindex_file(conn: &Connection, file_path: &Path, repo_root: &Path) -> Re
         .with_context(|| format!("reading file {}", file_path.display()))?;
 
     // Compute content hash.
-    let new_hash = format!("{:016x}", xxhash_rust::xxh3::xxh3_64(content.as_bytes()));
+    let new_hash = content_hash(&content);
 
     // Compare with stored hash — skip if unchanged.
     let stored_hash: Option<String> = conn
@@ -332,8 +779,13 @@ pub fn reindex_file(conn: &Connection, file_path: &Path, repo_root: &Path) -> Re
         return Ok(false);
     }
 
+    if crate::markdown::is_markdown(file_path) {
+        upsert_file_data(conn, &markdown_result(Path::new(&rel_path), &content))?;
+        return Ok(true);
+    }
+
     // Detect language — if unsupported, remove stale data and return.
-    let lang = match indexer::detect_language(file_path) {
+    let lang = match indexer::detect_language_in(file_path, &content) {
         Some(l) => l,
         None => {
             // File is not a supported language.  If it was previously indexed
@@ -343,23 +795,33 @@ pub fn reindex_file(conn: &Connection, file_path: &Path, repo_root: &Path) -> Re
         }
     };
 
-    // Pre-process Rust source to expand cfg_*! macros so tree-sitter can
-    // see the items they wrap.
-    let parse_source = if lang == indexer::Lang::Rust {
-        indexer::preprocess_rust_macros(&content)
-    } else {
-        content.clone()
-    };
+    // Expand Rust cfg_*! macros and cut Vue components down to their
+    // scripts so tree-sitter sees the items inside.
+    let parse_source = indexer::parse_source(lang, &content);
 
-    // Parse with tree-sitter.
-    let mut parser = indexer::get_parser(lang);
+    // Parse with tree-sitter.  A language whose grammar failed to load is
+    // dropped from the index so queries fall back to grep for its files.
+    let Some(mut parser) = indexer::get_parser(lang) else {
+        delete_file_data(conn, &rel_path)?;
+        return Ok(false);
+    };
     let tree = parser
         .parse(parse_source.as_bytes(), None)
         .context("tree-sitter parse failed")?;
 
     let symbols = indexer::extract_symbols(&tree, &parse_source, &rel_path, lang);
     let mut refs = indexer::extract_references(&tree, &parse_source, &rel_path, lang);
-    let file_imports = indexer::extract_imports(&tree, &parse_source, &rel_path, lang);
+    let mut file_imports = indexer::extract_imports(&tree, &parse_source, &rel_path, lang);
+    if lang == indexer::Lang::Ruby
+        && crate::config::Config::load(Some(repo_root))
+            .unwrap_or_default()
+            .ruby
+            .rails
+    {
+        file_imports
+            .imports
+            .extend(indexer::extract_ruby_constants(&tree, &parse_source));
+    }
     let type_edges = indexer::extract_type_edges(&tree, &parse_source, &rel_path, lang);
 
     // Compute confidence for each reference.
@@ -377,6 +839,8 @@ pub fn reindex_file(conn: &Connection, file_path: &Path, repo_root: &Path) -> Re
             language: lang.name().to_string(),
             content_hash: new_hash,
             line_count,
+            is_test: crate::ranker::detect_test_file(file_path, &content),
+            generated_by: crate::generated::detect_generator(&content),
             symbols,
             refs,
             imports: file_imports.imports,
@@ -429,6 +893,15 @@ pub fn process_events(
     let mut updated = 0usize;
     let mut changed_files = Vec::new();
 
+    // A changed build file (go.mod, tsconfig.json, ...) can make any import
+    // resolve differently.
+    let build_files_changed = events
+        .iter()
+        .any(|e| crate::imports::affects_resolution(e.path()));
+    if build_files_changed {
+        crate::imports::retry_all(conn)?;
+    }
+
     for event in events {
         let rel_path = event
             .path()
@@ -451,15 +924,19 @@ pub fn process_events(
             Ok(false) => {} // unchanged
             Err(e) => {
                 // Log the error but continue processing the batch.
-                eprintln!(
-                    "warn: failed to process {}: {:#}",
+                crate::output::print_warning(&format!(
+                    "failed to process {}: {:#}",
                     event.path().display(),
                     e
-                );
+                ));
             }
         }
     }
 
+    if updated > 0 || build_files_changed {
+        crate::imports::resolve_pending(conn, Some(repo_root))?;
+    }
+
     Ok(ProcessResult {
         updated_count: updated,
         changed_files,
@@ -494,11 +971,17 @@ fn delete_file_data(conn: &Connection, rel_path: &str) -> Result<()> {
         "DELETE FROM file_imports WHERE source_file = ?1",
         rusqlite::params![rel_path],
     )?;
+    // Imports that resolved to this file must be resolved again.
+    tx.execute(
+        "UPDATE file_imports SET resolved_file = NULL WHERE resolved_file = ?1",
+        rusqlite::params![rel_path],
+    )?;
     tx.execute(
         "DELETE FROM files WHERE path = ?1",
         rusqlite::params![rel_path],
     )?;
 
+    db::bump_generation(&tx)?;
     tx.commit().context("committing delete transaction")?;
     Ok(())
 }
@@ -530,15 +1013,34 @@ fn upsert_file_data(conn: &Connection, result: &FileResult) -> Result<()> {
         "DELETE FROM \"references\" WHERE file = ?1",
         rusqlite::params![result.rel_path],
     )?;
+    // While the set of files stays the same, an import written as before
+    // resolves as before, so its earlier result is kept rather than resolved
+    // again.  Package wildcards are redone: their expansion depends on the
+    // names the file references.
+    let is_new = !tx.query_row(
+        "SELECT EXISTS(SELECT 1 FROM files WHERE path = ?1)",
+        rusqlite::params![result.rel_path],
+        |row| row.get::<_, bool>(0),
+    )?;
+    let previous_imports: HashMap<String, (Option<String>, bool)> = tx
+        .prepare(
+            "SELECT import_path, resolved_file, unresolvable FROM file_imports \
+             WHERE source_file = ?1 AND (resolved_file IS NOT NULL OR unresolvable = 1) \
+             AND import_path NOT LIKE '%.*'",
+        )?
+        .query_map(rusqlite::params![result.rel_path], |row| {
+            Ok((row.get(0)?, (row.get(1)?, row.get(2)?)))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
     tx.execute(
         "DELETE FROM file_imports WHERE source_file = ?1",
         rusqlite::params![result.rel_path],
     )?;
 
-    // Upsert file metadata.
+    // Upsert file metadata, keeping the file's submodule tag.
     tx.execute(
-        "INSERT OR REPLACE INTO files (path, language, hash, last_indexed, line_count, symbols_count) \
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        "INSERT OR REPLACE INTO files (path, language, hash, last_indexed, line_count, symbols_count, is_test, generated_by, submodule) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, (SELECT submodule FROM files WHERE path = ?1))",
         rusqlite::params![
             result.rel_path,
             result.language,
@@ -546,6 +1048,8 @@ fn upsert_file_data(conn: &Connection, result: &FileResult) -> Result<()> {
             now,
             result.line_count as i64,
             result.symbols.len() as i64,
+            result.is_test,
+            result.generated_by,
         ],
     )?;
 
@@ -553,8 +1057,8 @@ fn upsert_file_data(conn: &Connection, result: &FileResult) -> Result<()> {
     let mut caller_map: HashMap<&str, i64> = HashMap::new();
     {
         let mut stmt = tx.prepare(
-            "INSERT INTO symbols (name, kind, file, line, col, end_line, scope, signature, language, doc_comment) \
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            "INSERT INTO symbols (name, kind, file, line, col, end_line, scope, signature, language, doc_comment, stable_id, condition, visibility, modifiers, generics, end_col, start_byte, end_byte) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
         )?;
         for sym in &result.symbols {
             stmt.execute(rusqlite::params![
@@ -568,6 +1072,14 @@ fn upsert_file_data(conn: &Connection, result: &FileResult) -> Result<()> {
                 sym.signature,
                 sym.language,
                 sym.doc_comment,
+                sym.stable_id(),
+                sym.condition,
+                sym.visibility,
+                (!sym.modifiers.is_empty()).then(|| sym.modifiers.join(" ")),
+                (!sym.generics.is_empty()).then(|| sym.generics.join("\n")),
+                sym.end_col.map(|v| v as i64),
+                sym.start_byte.map(|v| v as i64),
+                sym.end_byte.map(|v| v as i64),
             ])?;
             caller_map.insert(&sym.name, tx.last_insert_rowid());
         }
@@ -576,8 +1088,8 @@ fn upsert_file_data(conn: &Connection, result: &FileResult) -> Result<()> {
     // Insert new references, resolving caller_name to caller_id and target_id.
     {
         let mut stmt = tx.prepare(
-            "INSERT INTO \"references\" (name, file, line, col, context, caller_id, confidence, target_id) \
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            "INSERT INTO \"references\" (name, file, line, col, context, caller_id, confidence, target_id, end_col, start_byte, end_byte, kind) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
         )?;
         for reference in &result.refs {
             let caller_id = reference
@@ -595,6 +1107,10 @@ fn upsert_file_data(conn: &Connection, result: &FileResult) -> Result<()> {
                 caller_id,
                 reference.confidence,
                 target_id,
+                reference.end_col.map(|v| v as i64),
+                reference.start_byte.map(|v| v as i64),
+                reference.end_byte.map(|v| v as i64),
+                reference.kind.to_string(),
             ])?;
         }
     }
@@ -611,12 +1127,27 @@ fn upsert_file_data(conn: &Connection, result: &FileResult) -> Result<()> {
 
     // Insert new imports.
     {
-        let mut stmt =
-            tx.prepare("INSERT INTO file_imports (source_file, import_path) VALUES (?1, ?2)")?;
+        let mut stmt = tx.prepare(
+            "INSERT INTO file_imports (source_file, import_path, resolved_file, unresolvable) \
+             VALUES (?1, ?2, ?3, ?4)",
+        )?;
         for import in &result.imports {
-            stmt.execute(rusqlite::params![result.rel_path, import])?;
+            let (resolved, unresolvable) = previous_imports
+                .get(import)
+                .cloned()
+                .unwrap_or((None, false));
+            stmt.execute(rusqlite::params![
+                result.rel_path,
+                import,
+                resolved,
+                unresolvable
+            ])?;
         }
     }
+    // Imports elsewhere that could name this file are worth another try.
+    if is_new || crate::imports::declares_importable_names(&result.rel_path) {
+        crate::imports::retry_for_file(&tx, &result.rel_path)?;
+    }
 
     // Insert type hierarchy edges, resolving names to symbol IDs.
     {
@@ -652,6 +1183,7 @@ fn upsert_file_data(conn: &Connection, result: &FileResult) -> Result<()> {
         }
     }
 
+    db::bump_generation(&tx)?;
     tx.commit().context("committing upsert transaction")?;
     Ok(())
 }
@@ -660,58 +1192,58 @@ fn upsert_file_data(conn: &Connection, result: &FileResult) -> Result<()> {
 // Internals
 // ---------------------------------------------------------------------------
 
-/// Parse a single file and extract everything we need.
-///
-/// Returns `None` if the file is not a supported language or cannot be read.
-fn parse_one_file(path: &Path, repo_root: &Path) -> Option<FileResult> {
-    let lang = indexer::detect_language(path)?;
-    let content = std::fs::read_to_string(path).ok()?;
-
-    // Compute content hash.
-    let hash = format!("{:016x}", xxhash_rust::xxh3::xxh3_64(content.as_bytes()));
-
-    // Pre-process Rust source to expand cfg_*! macros.
-    let parse_source = if lang == indexer::Lang::Rust {
-        indexer::preprocess_rust_macros(&content)
-    } else {
-        content.clone()
-    };
+/// Symbols, references, imports, and type edges extracted from one source
+/// text.
+#[derive(Debug, Clone)]
+pub struct Extraction {
+    pub symbols: Vec<Symbol>,
+    pub refs: Vec<Reference>,
+    /// Import paths for the dependency graph.
+    pub imports: Vec<String>,
+    pub type_edges: Vec<RawTypeEdge>,
+}
 
-    // Parse with tree-sitter.
-    let mut parser = indexer::get_parser(lang);
+/// Parse `content` as `lang` and run the same extraction the indexer does,
+/// storing `rel_path` as the file of every result (`wonk parse`).
+///
+/// Returns `None` when the language's grammar failed to load or tree-sitter
+/// gives up on the input.
+pub fn extract(
+    lang: indexer::Lang,
+    rel_path: &str,
+    content: &str,
+    rails: bool,
+) -> Option<Extraction> {
+    // Expand Rust cfg_*! macros and cut Vue components down to their scripts.
+    let parse_source = indexer::parse_source(lang, content);
+
+    // Parse with tree-sitter (skipped when the grammar failed to load).
+    let mut parser = indexer::get_parser(lang)?;
     let tree = parser.parse(parse_source.as_bytes(), None)?;
 
-    // Relative path for storage.
-    let rel_path = path
-        .strip_prefix(repo_root)
-        .unwrap_or(path)
-        .to_string_lossy()
-        .into_owned();
-
     // Extract symbols.
-    let symbols = indexer::extract_symbols(&tree, &parse_source, &rel_path, lang);
+    let symbols = indexer::extract_symbols(&tree, &parse_source, rel_path, lang);
 
     // Extract references.
-    let mut refs = indexer::extract_references(&tree, &parse_source, &rel_path, lang);
+    let mut refs = indexer::extract_references(&tree, &parse_source, rel_path, lang);
 
     // Extract imports for dependency graph.
-    let file_imports = indexer::extract_imports(&tree, &parse_source, &rel_path, lang);
+    let mut file_imports = indexer::extract_imports(&tree, &parse_source, rel_path, lang);
+    if rails && lang == indexer::Lang::Ruby {
+        file_imports
+            .imports
+            .extend(indexer::extract_ruby_constants(&tree, &parse_source));
+    }
 
     // Extract type hierarchy edges (extends/implements).
-    let type_edges = indexer::extract_type_edges(&tree, &parse_source, &rel_path, lang);
+    let type_edges = indexer::extract_type_edges(&tree, &parse_source, rel_path, lang);
 
     // Compute confidence for each reference.
     for r in &mut refs {
         r.confidence = indexer::compute_confidence(r, &symbols, &file_imports.imports);
     }
 
-    let line_count = content.lines().count();
-
-    Some(FileResult {
-        rel_path,
-        language: lang.name().to_string(),
-        content_hash: hash,
-        line_count,
+    Some(Extraction {
         symbols,
         refs,
         imports: file_imports.imports,
@@ -719,6 +1251,66 @@ fn parse_one_file(path: &Path, repo_root: &Path) -> Option<FileResult> {
     })
 }
 
+/// Parse a single file and extract everything we need.
+///
+/// Returns `None` if the file is not a supported language, cannot be read, or
+/// its language was disabled after a grammar load failure.
+fn parse_one_file(file: &SourceFile, repo_root: &Path, rails: bool) -> Option<FileResult> {
+    let path = file.path();
+    if crate::markdown::is_markdown(path) {
+        let rel_path = path.strip_prefix(repo_root).unwrap_or(path);
+        let content = file.read_to_string()?;
+        return Some(markdown_result(rel_path, &content));
+    }
+    // Only files without an extension can be detected by their shebang.
+    let lang = indexer::detect_language(path);
+    if lang.is_none() && path.extension().is_some() {
+        return None;
+    }
+    let content = file.read_to_string()?;
+    let lang = lang.or_else(|| indexer::detect_language_in(path, &content))?;
+
+    // Relative path for storage.
+    let rel_path = path
+        .strip_prefix(repo_root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .into_owned();
+
+    let extraction = extract(lang, &rel_path, &content, rails)?;
+
+    Some(FileResult {
+        language: lang.name().to_string(),
+        content_hash: content_hash(&content),
+        line_count: content.lines().count(),
+        is_test: crate::ranker::detect_test_file(path, &content),
+        generated_by: crate::generated::detect_generator(&content),
+        rel_path,
+        symbols: extraction.symbols,
+        refs: extraction.refs,
+        imports: extraction.imports,
+        type_edges: extraction.type_edges,
+    })
+}
+
+/// The [`FileResult`] of a Markdown file: its headings (see
+/// [`crate::markdown`]) and nothing else.
+fn markdown_result(rel_path: &Path, content: &str) -> FileResult {
+    let rel_path = rel_path.to_string_lossy().into_owned();
+    FileResult {
+        language: crate::markdown::LANGUAGE.to_string(),
+        content_hash: content_hash(content),
+        line_count: content.lines().count(),
+        is_test: crate::ranker::detect_test_file(Path::new(&rel_path), content),
+        generated_by: crate::generated::detect_generator(content),
+        symbols: crate::markdown::extract_headings(content, &rel_path),
+        rel_path,
+        refs: Vec::new(),
+        imports: Vec::new(),
+        type_edges: Vec::new(),
+    }
+}
+
 /// Insert all results into the database in a single transaction.
 ///
 /// Returns (symbol_count, ref_count, caller_count, type_edge_count).
@@ -739,8 +1331,8 @@ fn batch_insert(conn: &Connection, results: &[FileResult]) -> Result<(usize, usi
     // Insert files.
     {
         let mut stmt = tx.prepare(
-            "INSERT OR REPLACE INTO files (path, language, hash, last_indexed, line_count, symbols_count) \
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            "INSERT OR REPLACE INTO files (path, language, hash, last_indexed, line_count, symbols_count, is_test, generated_by) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
         )?;
         for r in results {
             stmt.execute(rusqlite::params![
@@ -750,6 +1342,8 @@ fn batch_insert(conn: &Connection, results: &[FileResult]) -> Result<(usize, usi
                 now,
                 r.line_count as i64,
                 r.symbols.len() as i64,
+                r.is_test,
+                r.generated_by,
             ])?;
         }
     }
@@ -758,8 +1352,8 @@ fn batch_insert(conn: &Connection, results: &[FileResult]) -> Result<(usize, usi
     let mut file_caller_maps: HashMap<&str, HashMap<&str, i64>> = HashMap::new();
     {
         let mut stmt = tx.prepare(
-            "INSERT INTO symbols (name, kind, file, line, col, end_line, scope, signature, language, doc_comment) \
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            "INSERT INTO symbols (name, kind, file, line, col, end_line, scope, signature, language, doc_comment, stable_id, condition, visibility, modifiers, generics, end_col, start_byte, end_byte) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
         )?;
         for r in results {
             let file_map = file_caller_maps.entry(&r.rel_path).or_default();
@@ -775,6 +1369,14 @@ fn batch_insert(conn: &Connection, results: &[FileResult]) -> Result<(usize, usi
                     sym.signature,
                     sym.language,
                     sym.doc_comment,
+                    sym.stable_id(),
+                    sym.condition,
+                    sym.visibility,
+                    (!sym.modifiers.is_empty()).then(|| sym.modifiers.join(" ")),
+                    (!sym.generics.is_empty()).then(|| sym.generics.join("\n")),
+                    sym.end_col.map(|v| v as i64),
+                    sym.start_byte.map(|v| v as i64),
+                    sym.end_byte.map(|v| v as i64),
                 ])?;
                 file_map.insert(&sym.name, tx.last_insert_rowid());
                 total_syms += 1;
@@ -785,8 +1387,8 @@ fn batch_insert(conn: &Connection, results: &[FileResult]) -> Result<(usize, usi
     // Insert references, resolving caller_name to caller_id and target_id.
     {
         let mut stmt = tx.prepare(
-            "INSERT INTO \"references\" (name, file, line, col, context, caller_id, confidence, target_id) \
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            "INSERT INTO \"references\" (name, file, line, col, context, caller_id, confidence, target_id, end_col, start_byte, end_byte, kind) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
         )?;
         for r in results {
             let file_map = file_caller_maps.get(r.rel_path.as_str());
@@ -809,6 +1411,10 @@ fn batch_insert(conn: &Connection, results: &[FileResult]) -> Result<(usize, usi
                     caller_id,
                     reference.confidence,
                     target_id,
+                    reference.end_col.map(|v| v as i64),
+                    reference.start_byte.map(|v| v as i64),
+                    reference.end_byte.map(|v| v as i64),
+                    reference.kind.to_string(),
                 ])?;
                 total_refs += 1;
             }
@@ -904,6 +1510,7 @@ fn batch_insert(conn: &Connection, results: &[FileResult]) -> Result<(usize, usi
         }
     }
 
+    db::bump_generation(&tx)?;
     tx.commit().context("committing transaction")?;
     Ok((total_syms, total_refs, caller_count, type_edge_count))
 }
@@ -1110,8 +1717,11 @@ pub fn build_embeddings(
     if !client.is_healthy() {
         if progress_mode != ProgressMode::Silent {
             eprintln!(
-                "Ollama not available — skipping embedding generation. \
-                 Semantic search will not be available until embeddings are built."
+                "{}",
+                crate::glyphs::asciify(
+                    "Ollama not available — skipping embedding generation. \
+                     Semantic search will not be available until embeddings are built."
+                )
             );
         }
         return Ok(EmbeddingBuildStats {
@@ -1373,6 +1983,177 @@ class Component {
         dir
     }
 
+    #[test]
+    fn test_extract_matches_indexer_output() {
+        let source = "class Base {}\nclass Child extends Base {\n  run() { helper(); }\n}\n";
+        let extraction = extract(indexer::Lang::TypeScript, "<stdin>", source, false).unwrap();
+
+        let names: Vec<&str> = extraction.symbols.iter().map(|s| s.name.as_str()).collect();
+        assert!(names.contains(&"Child"), "{names:?}");
+        assert!(names.contains(&"run"), "{names:?}");
+        assert!(extraction.symbols.iter().all(|s| s.file == "<stdin>"));
+        assert!(
+            extraction
+                .refs
+                .iter()
+                .any(|r| r.name == "helper" && r.caller_name.as_deref() == Some("run"))
+        );
+        assert_eq!(extraction.type_edges.len(), 1);
+        assert_eq!(extraction.type_edges[0].parent_name, "Base");
+    }
+
+    #[test]
+    fn test_build_index_marks_test_files() {
+        let dir = make_test_repo();
+        fs::write(
+            dir.path().join("checks.py"),
+            "import pytest\n\ndef test_ok():\n    assert True\n",
+        )
+        .unwrap();
+        build_index(dir.path(), true).unwrap();
+
+        let conn = db::open_existing(&db::local_index_path(dir.path())).unwrap();
+        let is_test = |path: &str| -> bool {
+            conn.query_row(
+                "SELECT is_test FROM files WHERE path = ?1",
+                rusqlite::params![path],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert!(is_test("checks.py"));
+        assert!(!is_test("app.py"));
+    }
+
+    #[test]
+    fn test_update_paths_only_touches_given_paths() {
+        let dir = make_test_repo();
+        let root = dir.path();
+        fs::write(root.join("src/util.rs"), "fn util() {}\n").unwrap();
+        build_index(root, true).unwrap();
+
+        fs::write(root.join("src/main.rs"), "fn main_v2() {}\n").unwrap();
+        fs::write(root.join("app.py"), "def process_v2():\n    pass\n").unwrap();
+        fs::remove_file(root.join("src/util.rs")).unwrap();
+        update_paths(root, true, &[PathBuf::from("src")]).unwrap();
+
+        let conn = db::open_existing(&db::local_index_path(root)).unwrap();
+        let has_symbol = |name: &str| -> bool {
+            conn.query_row(
+                "SELECT COUNT(*) FROM symbols WHERE name = ?1",
+                rusqlite::params![name],
+                |row| row.get::<_, i64>(0),
+            )
+            .unwrap()
+                > 0
+        };
+        assert!(has_symbol("main_v2"), "src/main.rs is re-indexed");
+        assert!(!has_symbol("util"), "deleted src/util.rs is removed");
+        assert!(has_symbol("process"), "app.py is left as indexed");
+        assert!(!has_symbol("process_v2"));
+
+        update_paths(root, true, &[PathBuf::from("app.py")]).unwrap();
+        assert!(has_symbol("process_v2"));
+    }
+
+    #[test]
+    fn test_promote_files_builds_a_partial_index() {
+        let dir = make_test_repo();
+        let root = dir.path();
+        let index = db::local_index_path(root);
+
+        promote_files(root, true, &[PathBuf::from("app.py")]).unwrap();
+        let conn = db::open_existing(&index).unwrap();
+        let files: Vec<String> = conn
+            .prepare("SELECT path FROM files")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(files, vec!["app.py".to_string()]);
+        assert!(db::read_meta(&index).unwrap().partial);
+
+        // Updating some paths keeps it partial; a full update completes it.
+        update_paths(root, true, &[PathBuf::from("src")]).unwrap();
+        assert!(db::read_meta(&index).unwrap().partial);
+        incremental_update(root, true).unwrap();
+        assert!(!db::read_meta(&index).unwrap().partial);
+
+        "lazy".parse::<AutoInit>().unwrap();
+        assert!("eager".parse::<AutoInit>().is_err());
+    }
+
+    #[test]
+    fn test_build_index_records_generator() {
+        let dir = make_test_repo();
+        fs::write(
+            dir.path().join("api.py"),
+            "# Generated by the protocol buffer compiler.  DO NOT EDIT!\nclass Msg:\n    pass\n",
+        )
+        .unwrap();
+        build_index(dir.path(), true).unwrap();
+
+        let conn = db::open_existing(&db::local_index_path(dir.path())).unwrap();
+        let generated_by = |path: &str| -> Option<String> {
+            conn.query_row(
+                "SELECT generated_by FROM files WHERE path = ?1",
+                rusqlite::params![path],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(generated_by("api.py").as_deref(), Some("protoc"));
+        assert_eq!(generated_by("app.py"), None);
+    }
+
+    #[test]
+    fn test_build_index_markdown_headings() {
+        let dir = make_test_repo();
+        fs::write(
+            dir.path().join("GUIDE.md"),
+            "# Guide\n\n## Setup\n\nSteps.\n",
+        )
+        .unwrap();
+        build_index(dir.path(), true).unwrap();
+
+        let conn = db::open_existing(&db::local_index_path(dir.path())).unwrap();
+        let mut stmt = conn
+            .prepare("SELECT name, kind, scope, language FROM symbols WHERE file = 'GUIDE.md' ORDER BY line")
+            .unwrap();
+        let rows: Vec<(String, String, Option<String>, String)> = stmt
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("Guide".into(), "module".into(), None, "Markdown".into()),
+                (
+                    "Setup".into(),
+                    "module".into(),
+                    Some("Guide".into()),
+                    "Markdown".into()
+                ),
+            ]
+        );
+
+        // Edits are picked up by the single-file path as well.
+        fs::write(dir.path().join("GUIDE.md"), "# Guide\n\n## Usage\n").unwrap();
+        assert!(reindex_file(&conn, &dir.path().join("GUIDE.md"), dir.path()).unwrap());
+        let second: String = conn
+            .query_row(
+                "SELECT name FROM symbols WHERE file = 'GUIDE.md' AND scope = 'Guide'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(second, "Usage");
+    }
+
     #[test]
     fn test_build_index_basic() {
         let dir = make_test_repo();
@@ -1470,6 +2251,87 @@ class Component {
         assert!(meta.created > 0, "meta should have a timestamp");
     }
 
+    #[test]
+    fn test_build_index_profiled_times_every_file() {
+        let dir = make_test_repo();
+        let (stats, profile) = build_index_profiled(dir.path(), true, &Progress::silent()).unwrap();
+
+        assert_eq!(profile.files.len(), stats.file_count);
+        assert!(profile.total >= profile.parse);
+        assert_eq!(
+            profile.parse_cpu(),
+            profile.files.iter().map(|f| f.parse).sum::<Duration>()
+        );
+
+        let languages = profile.by_language();
+        assert_eq!(
+            languages.iter().map(|l| l.files).sum::<usize>(),
+            stats.file_count
+        );
+        assert!(languages.windows(2).all(|w| w[0].parse >= w[1].parse));
+
+        let slowest = profile.slowest_files(2);
+        assert_eq!(slowest.len(), 2);
+        assert!(slowest[0].parse >= slowest[1].parse);
+    }
+
+    #[test]
+    fn test_build_limits_size_pool_and_lower_priority() {
+        let limits = BuildLimits { jobs: 2, nice: 0 };
+        assert_eq!(limits.install(rayon::current_num_threads), 2);
+
+        let config = crate::config::IndexConfig {
+            nice: 40,
+            ..Default::default()
+        };
+        assert_eq!(BuildLimits::from_config(&config).nice, 19);
+
+        // Linux priorities are per thread, so this leaves the test runner alone.
+        #[cfg(target_os = "linux")]
+        {
+            let limits = BuildLimits { jobs: 1, nice: 7 };
+            let nice = limits.install(|| unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) });
+            assert!(nice >= 7, "worker niceness {nice}");
+        }
+    }
+
+    #[test]
+    fn test_index_profile_aggregates_by_language() {
+        let timing = |path: &str, language: &str, ms: u64| FileTiming {
+            path: path.into(),
+            language: language.into(),
+            lines: 10,
+            parse: Duration::from_millis(ms),
+        };
+        let profile = IndexProfile {
+            files: vec![
+                timing("a.py", "Python", 5),
+                timing("b.ts", "TypeScript", 40),
+                timing("c.py", "Python", 15),
+            ],
+            ..Default::default()
+        };
+
+        let languages = profile.by_language();
+        assert_eq!(languages[0].language, "TypeScript");
+        assert_eq!(
+            languages[1],
+            LanguageTiming {
+                language: "Python".into(),
+                files: 2,
+                lines: 20,
+                parse: Duration::from_millis(20),
+            }
+        );
+        let slowest: Vec<&str> = profile
+            .slowest_files(2)
+            .iter()
+            .map(|f| f.path.as_str())
+            .collect();
+        assert_eq!(slowest, vec!["b.ts", "c.py"]);
+        assert_eq!(profile.parse_cpu(), Duration::from_millis(60));
+    }
+
     #[test]
     fn test_rebuild_index() {
         let dir = make_test_repo();
@@ -1721,6 +2583,25 @@ class Component {
         assert!(!changed, "unchanged file should be skipped");
     }
 
+    #[test]
+    fn test_index_generation_advances_only_on_content_changes() {
+        let (dir, conn) = setup_indexed_repo();
+        let root = dir.path();
+        let built = db::index_generation(&conn);
+        assert!(built > 0, "a build starts a generation");
+
+        reindex_file(&conn, &root.join("lib.rs"), root).unwrap();
+        assert_eq!(db::index_generation(&conn), built, "unchanged file");
+
+        fs::write(root.join("lib.rs"), "fn hello() { 3 }").unwrap();
+        reindex_file(&conn, &root.join("lib.rs"), root).unwrap();
+        let changed = db::index_generation(&conn);
+        assert!(changed > built);
+
+        remove_file(&conn, &root.join("app.py"), root).unwrap();
+        assert!(db::index_generation(&conn) > changed);
+    }
+
     #[test]
     fn test_reindex_file_changed_updates() {
         let (dir, conn) = setup_indexed_repo();
@@ -2126,6 +3007,60 @@ class Component {
         assert!(result.changed_files.contains(&"app.py".to_string()));
     }
 
+    #[test]
+    fn test_process_events_resolves_only_changed_imports() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::write(
+            root.join("app.ts"),
+            "import { a } from './util';\nimport { b } from './helpers';\nimport React from 'react';\n",
+        )
+        .unwrap();
+        fs::write(root.join("util.ts"), "export const a = 1;\n").unwrap();
+        build_index(root, true).unwrap();
+        let conn = db::open_existing(&db::local_index_path(root)).unwrap();
+        let imports = || -> Vec<(String, Option<String>, bool)> {
+            conn.prepare(
+                "SELECT import_path, resolved_file, unresolvable FROM file_imports \
+                 WHERE source_file = 'app.ts' ORDER BY import_path",
+            )
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect()
+        };
+        let settled = vec![
+            ("./helpers".to_string(), None, true),
+            ("./util".to_string(), Some("util.ts".to_string()), false),
+            ("react".to_string(), None, true),
+        ];
+        assert_eq!(imports(), settled);
+
+        // Reindexing keeps what the unchanged imports resolved to.
+        fs::write(
+            root.join("app.ts"),
+            "import { a } from './util';\nimport { b } from './helpers';\nimport React from 'react';\nexport {};\n",
+        )
+        .unwrap();
+        assert!(reindex_file(&conn, &root.join("app.ts"), root).unwrap());
+        assert_eq!(imports(), settled);
+
+        // A new file the import names gets it resolved.
+        fs::write(root.join("helpers.ts"), "export const b = 2;\n").unwrap();
+        process_events(&conn, &[FileEvent::Created(root.join("helpers.ts"))], root).unwrap();
+        assert_eq!(
+            imports()[0],
+            (
+                "./helpers".to_string(),
+                Some("helpers.ts".to_string()),
+                false
+            )
+        );
+        assert_eq!(imports()[2], ("react".to_string(), None, true));
+    }
+
     #[test]
     fn test_process_events_mixed_batch() {
         let (dir, conn) = setup_indexed_repo();
@@ -0,0 +1,132 @@
+//! Which index a command reads and where it came from, for `wonk
+//! which-index`.
+//!
+//! Most commands read the first index that exists of the repository-local
+//! `.wonk/index.db` and the central `~/.wonk/repos/<hash>/index.db` that
+//! belongs to the current user (see [`db::find_existing_index`]).  `sym`,
+//! `ref`, `sig`, `deps`, and `rdeps` go through
+//! [`crate::router::QueryRouter`], which always reads the central one
+//! (the local one when the central store is not writable).  [`inspect`]
+//! reports both candidates, the one chosen and why, and the chosen index's
+//! `meta.json` next to the commit checked out now, so stale results can be
+//! traced to a stale or unexpected index.
+
+use std::path::Path;
+
+use crate::access;
+use crate::db;
+use crate::output::WhichIndexOutput;
+
+/// Describe the index selection for `repo_root`.
+pub fn inspect(repo_root: &Path) -> WhichIndexOutput {
+    let local = db::local_index_path(repo_root);
+    let central = db::central_index_path(repo_root).ok();
+    let local_exists = local.exists();
+    let central_exists = central.as_ref().is_some_and(|p| p.exists());
+    let local_foreign = local_exists && !access::is_own(&local);
+
+    let (index, location, reason) = if local_exists && !local_foreign {
+        (
+            Some(&local),
+            Some("local"),
+            "the repository has a local .wonk/index.db, which takes precedence \
+             over the central index",
+        )
+    } else if central_exists && local_foreign {
+        (
+            central.as_ref(),
+            Some("central"),
+            "the local .wonk/index.db belongs to another user, so your central \
+             index for this repository is used",
+        )
+    } else if central_exists {
+        (
+            central.as_ref(),
+            Some("central"),
+            "there is no local .wonk/index.db, so the central index for this \
+             repository is used",
+        )
+    } else if local_foreign {
+        (
+            None,
+            None,
+            "the local .wonk/index.db belongs to another user; the next query \
+             builds your own central index",
+        )
+    } else if !db::central_store_usable() {
+        (
+            None,
+            None,
+            "no index exists yet; ~/.wonk/repos is not writable, so the next \
+             query builds a local one",
+        )
+    } else {
+        (
+            None,
+            None,
+            "no index exists yet; the next query builds the central index \
+             (`wonk init --local` builds a local one)",
+        )
+    };
+
+    let meta = index.and_then(|p| db::read_meta(p).ok());
+    let display = |p: &Path| p.display().to_string();
+    WhichIndexOutput {
+        repo: display(repo_root),
+        index: index.map(|p| display(p)),
+        location: location.map(str::to_string),
+        reason: reason.to_string(),
+        local_path: display(&local),
+        local_exists,
+        central_path: central.as_deref().map(display),
+        central_exists,
+        symbol_index: central.as_deref().filter(|_| central_exists).map(display),
+        built: meta.as_ref().map(|m| m.created),
+        wonk_version: meta.as_ref().and_then(|m| m.wonk_version.clone()),
+        commit: meta.as_ref().and_then(|m| m.commit.clone()),
+        head: db::head_commit(repo_root),
+        languages: meta
+            .as_ref()
+            .map(|m| m.languages.clone())
+            .unwrap_or_default(),
+        disabled_languages: meta
+            .as_ref()
+            .map(|m| m.disabled_languages.clone())
+            .unwrap_or_default(),
+        partial: meta.as_ref().is_some_and(|m| m.partial),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn local_index_takes_precedence() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        let index = db::local_index_path(root);
+        std::fs::create_dir_all(index.parent().unwrap()).unwrap();
+        std::fs::write(&index, "").unwrap();
+        db::write_meta(&index, root, &["rust".to_string()]).unwrap();
+
+        let out = inspect(root);
+        assert_eq!(out.location.as_deref(), Some("local"));
+        assert_eq!(out.index, Some(index.display().to_string()));
+        assert!(out.local_exists);
+        assert_eq!(out.languages, vec!["rust"]);
+        assert!(out.built.is_some());
+        assert!(out.commit.is_none() && out.head.is_none());
+    }
+
+    #[test]
+    fn missing_index_is_reported() {
+        let dir = TempDir::new().unwrap();
+        let out = inspect(dir.path());
+        assert!(out.index.is_none() && out.location.is_none());
+        assert!(!out.local_exists);
+        assert!(out.reason.contains("no index"));
+        assert!(out.built.is_none());
+    }
+}
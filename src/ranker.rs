@@ -1,17 +1,20 @@
 //! Result classification, ranking, and deduplication engine.
 //!
 //! Classifies each search result line into a category (Definition, CallSite,
-//! Import, Comment, Test, Other) using index metadata and path/content
+//! Import, Comment, Doc, Test, Other) using index metadata and path/content
 //! heuristics, then ranks results by relevance tier, deduplicates re-exported
 //! symbols, and groups results by category for display with section headers.
 
-use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::sync::LazyLock;
 
 use regex::Regex;
 use rusqlite::Connection;
 
+use crate::config::{BudgetConfig, SearchConfig};
 use crate::search::SearchResult;
 
 /// Category assigned to a classified search result.
@@ -25,6 +28,8 @@ pub enum ResultCategory {
     Import,
     /// The line is inside a comment.
     Comment,
+    /// The file is documentation (Markdown).
+    Doc,
     /// The file is a test file (detected by path heuristics).
     Test,
     /// Unclassified.
@@ -35,7 +40,8 @@ impl ResultCategory {
     /// Return a numeric tier for sort ordering.
     ///
     /// Lower values appear first in ranked output:
-    /// Definition(0) > CallSite(1) > Import(2) > Other(3) > Comment(4) > Test(5)
+    /// Definition(0) > CallSite(1) > Import(2) > Other(3) > Comment(4) > Doc(5)
+    /// > Test(6)
     pub fn tier(&self) -> u8 {
         match self {
             ResultCategory::Definition => 0,
@@ -43,7 +49,26 @@ impl ResultCategory {
             ResultCategory::Import => 2,
             ResultCategory::Other => 3,
             ResultCategory::Comment => 4,
-            ResultCategory::Test => 5,
+            ResultCategory::Doc => 5,
+            ResultCategory::Test => 6,
+        }
+    }
+}
+
+impl std::str::FromStr for ResultCategory {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "definition" | "definitions" => Ok(Self::Definition),
+            "call_site" | "call_sites" | "callsite" => Ok(Self::CallSite),
+            "import" | "imports" => Ok(Self::Import),
+            "comment" | "comments" => Ok(Self::Comment),
+            "doc" | "docs" => Ok(Self::Doc),
+            "test" | "tests" => Ok(Self::Test),
+            "other" => Ok(Self::Other),
+            _ => Err(format!(
+                "unknown result category '{s}' (expected: definition, call_site, import, comment, doc, test, other)"
+            )),
         }
     }
 }
@@ -67,6 +92,7 @@ impl std::fmt::Display for ResultCategory {
             ResultCategory::CallSite => "call_site",
             ResultCategory::Import => "import",
             ResultCategory::Comment => "comment",
+            ResultCategory::Doc => "doc",
             ResultCategory::Test => "test",
             ResultCategory::Other => "other",
         };
@@ -227,28 +253,21 @@ pub fn is_comment_line(line: &str) -> bool {
         || trimmed.starts_with('#')
 }
 
-/// Check if a file path matches test directory/filename heuristics.
+/// Check if a file path is test code by directory/filename conventions.
 ///
 /// Matches:
 /// - `test/`, `tests/`, `__tests__/` in path components
 /// - `*_test.*` filename suffix (e.g. `foo_test.go`)
 /// - `*.test.*` filename (e.g. `foo.test.ts`)
 /// - `*.spec.*` filename (e.g. `foo.spec.js`)
-pub fn is_test_file(path: &Path) -> bool {
+///
+/// Backs `--no-tests`/`--tests-only`; see [`is_test_file`] for the wider
+/// set used to demote results.
+pub fn is_test_path(path: &Path) -> bool {
     // Directory-based heuristics: check path components
     for component in path.components() {
         let s = component.as_os_str().to_string_lossy();
-        if s == "test"
-            || s == "tests"
-            || s == "__tests__"
-            || s == "docs"
-            || s == "doc"
-            || s == "examples"
-            || s == "example"
-            || s == "fixtures"
-            || s == "benchmarks"
-            || s == "bench"
-        {
+        if s == "test" || s == "tests" || s == "__tests__" {
             return true;
         }
     }
@@ -274,6 +293,192 @@ pub fn is_test_file(path: &Path) -> bool {
     false
 }
 
+/// Check if a file path matches test directory/filename heuristics.
+///
+/// Matches everything [`is_test_path`] does plus `docs/`, `doc/`,
+/// `examples/`, `example/`, `fixtures/`, `benchmarks/` and `bench/` path
+/// components. Used to demote and hide non-production results by default.
+pub fn is_test_file(path: &Path) -> bool {
+    if is_test_path(path) {
+        return true;
+    }
+    path.components().any(|component| {
+        matches!(
+            component.as_os_str().to_str(),
+            Some("docs" | "doc" | "examples" | "example" | "fixtures" | "benchmarks" | "bench")
+        )
+    })
+}
+
+/// Test framework imports that identify test code regardless of where the
+/// file lives.  They are only looked for on import lines (see
+/// [`is_test_import_line`]), so a comment or string naming a framework does
+/// not count.  Go is left to the `_test.go` suffix `go test` requires.
+const TEST_IMPORT_MARKERS: &[&str] = &[
+    // Python
+    "import pytest",
+    "from pytest",
+    "import unittest",
+    "from unittest",
+    // JavaScript / TypeScript
+    "'vitest'",
+    "\"vitest\"",
+    "@jest/globals",
+    "'@testing-library/",
+    "\"@testing-library/",
+    // Java / Kotlin
+    "import org.junit",
+    "import org.testng",
+    "import kotlin.test",
+    // Scala
+    "import org.scalatest",
+    "import munit",
+    "import org.specs2",
+    // C#
+    "using Xunit",
+    "using NUnit.Framework",
+    "using Microsoft.VisualStudio.TestTools",
+    // Ruby
+    "require 'rspec'",
+    "require \"rspec\"",
+    "require 'minitest",
+    "require \"minitest",
+    "require 'spec_helper'",
+    "require \"spec_helper\"",
+    "require 'rails_helper'",
+    "require \"rails_helper\"",
+    // Lua
+    "require('busted",
+    "require(\"busted",
+    "require 'busted",
+    "require('luaunit')",
+    "require(\"luaunit\")",
+    "require 'luaunit'",
+    // PHP
+    "PHPUnit\\Framework",
+    // Elixir
+    "ExUnit.Case",
+    // Swift
+    "import XCTest",
+    // Dart
+    "package:test/",
+    "package:flutter_test/",
+];
+
+/// Whether a test framework could be imported on `line`: an
+/// [`is_import_line`], a C# `using`, or a Lua `local x = require(...)`.
+fn is_test_import_line(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    is_import_line(line)
+        || trimmed.starts_with("using ")
+        || trimmed.starts_with("@testable import ")
+        || (trimmed.starts_with("local ") && trimmed.contains("= require"))
+}
+
+/// Detect test code by path conventions and content markers.
+///
+/// Extends [`is_test_path`] with test framework imports (e.g. `import
+/// pytest`, `import org.junit`, `using Xunit`) and Rust's `#![cfg(test)]`.
+/// Used at index time to populate `files.is_test`.
+pub fn detect_test_file(path: &Path, content: &str) -> bool {
+    if is_test_path(path) {
+        return true;
+    }
+    content.lines().any(|line| {
+        line.trim() == "#![cfg(test)]"
+            || (is_test_import_line(line) && TEST_IMPORT_MARKERS.iter().any(|m| line.contains(m)))
+    })
+}
+
+/// Which results to keep with respect to test files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TestFilter {
+    /// Drop test files (the default).
+    #[default]
+    Exclude,
+    /// Keep everything (`--include-tests`).
+    Include,
+    /// Keep only test files (`--tests-only`).
+    Only,
+}
+
+impl TestFilter {
+    /// Build the filter from the global CLI flags.
+    pub fn from_flags(include_tests: bool, tests_only: bool) -> Self {
+        if tests_only {
+            TestFilter::Only
+        } else if include_tests {
+            TestFilter::Include
+        } else {
+            TestFilter::Exclude
+        }
+    }
+}
+
+/// Classifies result files as test code using both path conventions and the
+/// `files.is_test` flag recorded at index time.
+pub struct TestClassifier {
+    filter: TestFilter,
+    repo_root: std::path::PathBuf,
+    marked: HashSet<String>,
+}
+
+impl TestClassifier {
+    /// Load the indexed test-file set (empty without an index, or when the
+    /// filter keeps everything).
+    pub fn new(filter: TestFilter, conn: Option<&Connection>, repo_root: &Path) -> Self {
+        let marked = match (filter, conn) {
+            (TestFilter::Include, _) | (_, None) => HashSet::new(),
+            (_, Some(conn)) => conn
+                .prepare("SELECT path FROM files WHERE is_test = 1")
+                .and_then(|mut stmt| {
+                    stmt.query_map([], |row| row.get::<_, String>(0))?
+                        .collect::<rusqlite::Result<HashSet<_>>>()
+                })
+                .unwrap_or_default(),
+        };
+        Self {
+            filter,
+            repo_root: repo_root.to_path_buf(),
+            marked,
+        }
+    }
+
+    /// Whether `path` (repo-relative, cwd-relative, or absolute) is test code.
+    pub fn is_test(&self, path: &Path) -> bool {
+        if is_test_path(path) {
+            return true;
+        }
+        if self.marked.is_empty() {
+            return false;
+        }
+        let abs = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            std::env::current_dir()
+                .map(|cwd| cwd.join(path))
+                .unwrap_or_else(|_| path.to_path_buf())
+        };
+        let rel: std::path::PathBuf = abs
+            .strip_prefix(&self.repo_root)
+            .unwrap_or(path)
+            .components()
+            .filter(|c| !matches!(c, std::path::Component::CurDir))
+            .collect();
+        self.marked.contains(rel.to_string_lossy().as_ref())
+            || self.marked.contains(path.to_string_lossy().as_ref())
+    }
+
+    /// Whether a result in `path` passes the filter.
+    pub fn keep(&self, path: &Path) -> bool {
+        match self.filter {
+            TestFilter::Include => true,
+            TestFilter::Exclude => !self.is_test(path),
+            TestFilter::Only => self.is_test(path),
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Classification engine
 // ---------------------------------------------------------------------------
@@ -325,7 +530,10 @@ fn classify_one(
     file_path: &Path,
     index: Option<&IndexLookup>,
 ) -> ResultCategory {
-    // 1. Test (highest priority - path heuristic)
+    // 1. Doc (Markdown, wherever it lives) and Test (path heuristics)
+    if crate::markdown::is_markdown(file_path) {
+        return ResultCategory::Doc;
+    }
     if is_test_file(file_path) {
         return ResultCategory::Test;
     }
@@ -446,10 +654,500 @@ pub fn category_header(cat: ResultCategory) -> &'static str {
         ResultCategory::CallSite | ResultCategory::Other => "-- usages --",
         ResultCategory::Import => "-- imports --",
         ResultCategory::Comment => "-- comments --",
+        ResultCategory::Doc => "-- docs --",
         ResultCategory::Test => "-- tests --",
     }
 }
 
+// ---------------------------------------------------------------------------
+// Layout
+// ---------------------------------------------------------------------------
+
+/// A group of ranked results as displayed: one category, or the trailing
+/// group of collapsed categories (`category` is `None`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResultGroup {
+    pub category: Option<ResultCategory>,
+    /// Header text without the `-- ` decoration (e.g. "definitions").
+    pub label: String,
+    pub items: Vec<ClassifiedResult>,
+}
+
+impl ResultGroup {
+    /// The decorated header line, e.g. `-- definitions --`.
+    pub fn header(&self) -> String {
+        format!("-- {} --", self.label)
+    }
+}
+
+/// How ranked groups are ordered, labelled, and collapsed, from the
+/// `[search]` config section.
+#[derive(Debug, Clone, Default)]
+pub struct CategoryLayout {
+    /// Categories listed first, in this order; the rest follow by tier.
+    order: Vec<ResultCategory>,
+    labels: HashMap<ResultCategory, String>,
+    collapsed_label: Option<String>,
+    /// Categories with fewer results than this are merged into one
+    /// trailing group (0 disables collapsing).
+    collapse_below: usize,
+}
+
+impl CategoryLayout {
+    /// Build a layout from config.  Unknown category names are ignored.
+    pub fn from_config(config: &SearchConfig) -> Self {
+        let mut order = Vec::new();
+        for name in &config.category_order {
+            if let Ok(cat) = name.parse::<ResultCategory>()
+                && !order.contains(&cat)
+            {
+                order.push(cat);
+            }
+        }
+        let mut labels = HashMap::new();
+        let mut collapsed_label = None;
+        for (name, label) in &config.category_labels {
+            if name == "collapsed" {
+                collapsed_label = Some(label.clone());
+            } else if let Ok(cat) = name.parse::<ResultCategory>() {
+                labels.insert(cat, label.clone());
+            }
+        }
+        Self {
+            order,
+            labels,
+            collapsed_label,
+            collapse_below: config.collapse_below,
+        }
+    }
+
+    /// Display label for a category (configured, or the default header text).
+    pub fn label(&self, cat: ResultCategory) -> String {
+        match self.labels.get(&cat) {
+            Some(label) => label.clone(),
+            None => category_header(cat)
+                .trim_start_matches("-- ")
+                .trim_end_matches(" --")
+                .to_string(),
+        }
+    }
+
+    /// Sort key placing configured categories first, then by tier.
+    fn rank(&self, cat: ResultCategory) -> (usize, u8) {
+        let pos = self.order.iter().position(|c| *c == cat);
+        (pos.unwrap_or(self.order.len()), cat.tier())
+    }
+
+    /// Reorder, relabel, and collapse the groups from [`rank_and_dedup`].
+    ///
+    /// Collapsing only applies when more than one group is present, so a
+    /// single small category is still shown under its own header.
+    pub fn arrange(
+        &self,
+        groups: Vec<(ResultCategory, Vec<ClassifiedResult>)>,
+    ) -> Vec<ResultGroup> {
+        let mut groups = groups;
+        groups.sort_by_key(|(cat, _)| self.rank(*cat));
+
+        let collapse = self.collapse_below > 0 && groups.len() > 1;
+        let mut arranged = Vec::with_capacity(groups.len());
+        let mut collapsed = Vec::new();
+        for (cat, items) in groups {
+            if collapse && items.len() < self.collapse_below {
+                collapsed.extend(items);
+            } else {
+                arranged.push(ResultGroup {
+                    category: Some(cat),
+                    label: self.label(cat),
+                    items,
+                });
+            }
+        }
+        if !collapsed.is_empty() {
+            arranged.push(ResultGroup {
+                category: None,
+                label: self
+                    .collapsed_label
+                    .clone()
+                    .unwrap_or_else(|| "other".to_string()),
+                items: collapsed,
+            });
+        }
+        arranged
+    }
+}
+
+/// Share of a `--budget` each ranked group gets (see
+/// [`crate::budget::distribute`]), from `[budget].category_weights`.
+///
+/// By default definitions weigh 4 and call sites 2, against 1 for every
+/// other category and the collapsed group, so a truncated listing still
+/// shows the definitions before usages and tests crowd them out.
+#[derive(Debug, Clone)]
+pub struct CategoryWeights {
+    weights: HashMap<ResultCategory, u32>,
+    collapsed: u32,
+}
+
+impl CategoryWeights {
+    /// Build weights from config.  Unknown category names are ignored.
+    pub fn from_config(config: &BudgetConfig) -> Self {
+        let mut weights = HashMap::from([
+            (ResultCategory::Definition, 4),
+            (ResultCategory::CallSite, 2),
+        ]);
+        let mut collapsed = 1;
+        for (name, &weight) in &config.category_weights {
+            if name == "collapsed" {
+                collapsed = weight;
+            } else if let Ok(cat) = name.parse::<ResultCategory>() {
+                weights.insert(cat, weight);
+            }
+        }
+        Self { weights, collapsed }
+    }
+
+    /// The weight of `group`.
+    pub fn weight(&self, group: &ResultGroup) -> u32 {
+        match group.category {
+            Some(cat) => self.weights.get(&cat).copied().unwrap_or(1),
+            None => self.collapsed,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Directory clusters
+// ---------------------------------------------------------------------------
+
+/// Results sharing one parent directory, for `wonk search --cluster dir`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirCluster<T> {
+    /// Parent directory of the files, `.` for files at the top.
+    pub dir: String,
+    /// Number of results in the directory before truncation to `items`.
+    pub count: usize,
+    /// The first `top` results of the directory, in their original order.
+    pub items: Vec<T>,
+}
+
+/// Group `items` by the parent directory of `path(item)`, keeping the first
+/// `top` of each.  Clusters with the most results come first, ties by
+/// directory name.
+pub fn cluster_by_dir<T>(
+    items: Vec<T>,
+    top: usize,
+    path: impl Fn(&T) -> &Path,
+) -> Vec<DirCluster<T>> {
+    let mut clusters: Vec<DirCluster<T>> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for item in items {
+        let dir = path(&item)
+            .parent()
+            .map(|p| p.to_string_lossy().trim_start_matches("./").to_string())
+            .filter(|d| !d.is_empty() && d != ".")
+            .unwrap_or_else(|| ".".to_string());
+        let i = *index.entry(dir.clone()).or_insert_with(|| {
+            clusters.push(DirCluster {
+                dir,
+                count: 0,
+                items: Vec::new(),
+            });
+            clusters.len() - 1
+        });
+        let cluster = &mut clusters[i];
+        cluster.count += 1;
+        if cluster.items.len() < top {
+            cluster.items.push(item);
+        }
+    }
+    clusters.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.dir.cmp(&b.dir)));
+    clusters
+}
+
+// ---------------------------------------------------------------------------
+// Sampling
+// ---------------------------------------------------------------------------
+
+/// Pick `n` of `items` spread across directories and files rather than the
+/// first `n`.
+///
+/// Directories take turns, each giving one item from its next file in
+/// rotation, so a directory or file with thousands of hits cannot crowd out
+/// the rest.  The picked items keep their original relative order.
+pub fn stratified_sample<T>(items: Vec<T>, n: usize, path: impl Fn(&T) -> &Path) -> Vec<T> {
+    if items.len() <= n {
+        return items;
+    }
+
+    // Item indices per file, files per directory, in first-seen order.
+    let mut dirs: Vec<VecDeque<VecDeque<usize>>> = Vec::new();
+    let mut dir_index: HashMap<&Path, usize> = HashMap::new();
+    let mut file_index: HashMap<&Path, (usize, usize)> = HashMap::new();
+    for (i, item) in items.iter().enumerate() {
+        let file = path(item);
+        match file_index.get(file) {
+            Some(&(d, f)) => dirs[d][f].push_back(i),
+            None => {
+                let dir = file.parent().unwrap_or(Path::new(""));
+                let d = *dir_index.entry(dir).or_insert_with(|| {
+                    dirs.push(VecDeque::new());
+                    dirs.len() - 1
+                });
+                dirs[d].push_back(VecDeque::from([i]));
+                file_index.insert(file, (d, dirs[d].len() - 1));
+            }
+        }
+    }
+
+    let mut picked = Vec::with_capacity(n);
+    'rounds: while picked.len() < n {
+        for files in &mut dirs {
+            let Some(mut file) = files.pop_front() else {
+                continue;
+            };
+            if let Some(i) = file.pop_front() {
+                picked.push(i);
+            }
+            if !file.is_empty() {
+                files.push_back(file);
+            }
+            if picked.len() == n {
+                break 'rounds;
+            }
+        }
+    }
+    picked.sort_unstable();
+
+    let mut keep = vec![false; items.len()];
+    for i in picked {
+        keep[i] = true;
+    }
+    items
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(item, keep)| keep.then_some(item))
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Recency
+// ---------------------------------------------------------------------------
+
+/// Where `--recent` reads how recently a file changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecencySource {
+    /// Last commit touching the file; uncommitted and untracked files count
+    /// by modification time.  Falls back to modification times outside git.
+    Git,
+    /// File modification time only.
+    Mtime,
+}
+
+impl std::str::FromStr for RecencySource {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "git" => Ok(Self::Git),
+            "mtime" => Ok(Self::Mtime),
+            _ => Err(format!(
+                "unknown recency source '{s}' (expected: git, mtime)"
+            )),
+        }
+    }
+}
+
+/// When each of `files` (relative to `cwd`) last changed, as Unix seconds.
+///
+/// Files that cannot be dated (missing, outside the repository with
+/// [`RecencySource::Git`] unavailable) are left out.
+pub fn file_recency(
+    repo_root: &Path,
+    cwd: &Path,
+    files: &[&Path],
+    source: RecencySource,
+) -> HashMap<PathBuf, i64> {
+    let mut times = HashMap::new();
+    if source == RecencySource::Git
+        && let Some(dirty) = git_dirty_files(repo_root)
+    {
+        // Committed files are dated by git; the rest fall through to mtime.
+        let committed: HashMap<String, &Path> = files
+            .iter()
+            .filter_map(|f| {
+                let rel = cwd.join(f).strip_prefix(repo_root).ok()?.to_path_buf();
+                let rel = rel.to_str()?.to_string();
+                (!dirty.contains(&rel)).then_some((rel, *f))
+            })
+            .collect();
+        for (rel, time) in git_commit_times(repo_root, committed.keys()) {
+            times.insert(committed[rel.as_str()].to_path_buf(), time);
+        }
+    }
+    for file in files {
+        if times.contains_key(*file) {
+            continue;
+        }
+        let modified = std::fs::metadata(cwd.join(file))
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok());
+        if let Some(modified) = modified {
+            times.insert(file.to_path_buf(), modified.as_secs() as i64);
+        }
+    }
+    times
+}
+
+/// Repo-relative paths with uncommitted changes, untracked files included.
+/// `None` when git cannot report status (not a repository, git missing).
+fn git_dirty_files(repo_root: &Path) -> Option<HashSet<String>> {
+    let output = Command::new("git")
+        .args(["status", "--porcelain", "-z", "--untracked-files=all"])
+        .current_dir(repo_root)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut dirty = HashSet::new();
+    let mut entries = stdout.split('\0');
+    while let Some(entry) = entries.next() {
+        let Some(path) = entry.get(3..) else {
+            continue;
+        };
+        // Renames and copies are followed by their source path.
+        if entry.starts_with(['R', 'C']) {
+            entries.next();
+        }
+        dirty.insert(path.to_string());
+    }
+    Some(dirty)
+}
+
+/// Time of the last commit touching each of `files` (repo-relative).
+///
+/// Walks `git log` limited to the files, newest first, and stops as soon as
+/// every file has been dated.
+fn git_commit_times<'a>(
+    repo_root: &Path,
+    files: impl Iterator<Item = &'a String>,
+) -> HashMap<String, i64> {
+    let mut wanted: HashSet<&str> = files.map(String::as_str).collect();
+    let mut times = HashMap::new();
+    if wanted.is_empty() {
+        return times;
+    }
+    let child = Command::new("git")
+        .args([
+            "--literal-pathspecs",
+            "log",
+            "--format=%x00%ct",
+            "--name-only",
+            "--no-renames",
+            "--",
+        ])
+        .args(&wanted)
+        .current_dir(repo_root)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let Ok(mut child) = child else {
+        return times;
+    };
+    let Some(stdout) = child.stdout.take() else {
+        return times;
+    };
+    let mut commit_time = 0i64;
+    for line in BufReader::new(stdout).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if let Some(time) = line.strip_prefix('\0') {
+            commit_time = time.trim().parse().unwrap_or(0);
+        } else if wanted.remove(line.as_str()) {
+            times.insert(line, commit_time);
+            if wanted.is_empty() {
+                break;
+            }
+        }
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+    times
+}
+
+/// Stable-sort `items` so files that changed most recently come first;
+/// items of undated files go last.  Order within a file is kept.
+pub fn sort_by_recency<T>(
+    items: &mut [T],
+    times: &HashMap<PathBuf, i64>,
+    path: impl Fn(&T) -> &Path,
+) {
+    items.sort_by_key(|item| std::cmp::Reverse(times.get(path(item)).copied()));
+}
+
+// ---------------------------------------------------------------------------
+// Path boosting
+// ---------------------------------------------------------------------------
+
+/// Gitignore-style globs (`src/core/**`) from `[ranking].boost` and
+/// `--boost`; results in matching files are listed first.
+pub struct PathBoost {
+    matcher: ignore::gitignore::Gitignore,
+    /// Directory result paths are relative to, relative to the repo root.
+    base: PathBuf,
+}
+
+impl PathBoost {
+    /// Build a boost from `globs`, matched against repo-relative paths.
+    /// Result paths are taken to be relative to `cwd`.
+    pub fn new(repo_root: &Path, cwd: &Path, globs: &[String]) -> anyhow::Result<Self> {
+        let mut builder = ignore::gitignore::GitignoreBuilder::new(repo_root);
+        for glob in globs {
+            builder
+                .add_line(None, glob)
+                .map_err(|e| anyhow::anyhow!("invalid boost glob '{glob}': {e}"))?;
+        }
+        Ok(Self {
+            matcher: builder.build()?,
+            base: cwd
+                .strip_prefix(repo_root)
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Whether no globs were given.
+    pub fn is_empty(&self) -> bool {
+        self.matcher.is_empty()
+    }
+
+    /// Whether `path` (relative to the cwd given to [`Self::new`]) matches
+    /// a boost glob.
+    pub fn matches(&self, path: &Path) -> bool {
+        let rel: PathBuf = self
+            .base
+            .join(path)
+            .components()
+            .filter(|c| !matches!(c, std::path::Component::CurDir))
+            .collect();
+        !self.is_empty()
+            && self
+                .matcher
+                .matched_path_or_any_parents(&rel, false)
+                .is_ignore()
+    }
+
+    /// Stable-sort `items` so those in boosted files come first.
+    pub fn apply<T>(&self, items: &mut [T], path: impl Fn(&T) -> &Path) {
+        if !self.is_empty() {
+            items.sort_by_key(|item| !self.matches(path(item)));
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Full pipeline
 // ---------------------------------------------------------------------------
@@ -489,6 +1187,11 @@ pub struct FusedResult {
     pub line: u64,
     /// 1-based column.
     pub col: u64,
+    /// 1-based column just past the end of the structural match.
+    pub end_col: Option<u64>,
+    /// Byte range of the structural match in the file.
+    pub start_byte: Option<u64>,
+    pub end_byte: Option<u64>,
     /// Display content for this result.
     pub content: String,
     /// RRF score (higher = better).
@@ -531,6 +1234,9 @@ pub fn fuse_rrf(
                 file: r.file.to_string_lossy().into_owned(),
                 line: r.line,
                 col: r.col,
+                end_col: r.end_col,
+                start_byte: r.start_byte,
+                end_byte: r.end_byte,
                 content: r.content.clone(),
                 rrf_score: score,
                 source: FusedSource::Structural,
@@ -554,6 +1260,9 @@ pub fn fuse_rrf(
                 file: sr.file.clone(),
                 line: sr.line as u64,
                 col: 1,
+                end_col: None,
+                start_byte: None,
+                end_byte: None,
                 content: format!("{} ({})", sr.symbol_name, sr.symbol_kind),
                 rrf_score: score,
                 source: FusedSource::Semantic,
@@ -592,6 +1301,9 @@ mod tests {
             file: PathBuf::from(file),
             line,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: content.to_string(),
         }
     }
@@ -726,6 +1438,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn classify_markdown_as_doc() {
+        let results = vec![
+            make_result("README.md", 3, "## Install"),
+            make_result("docs/architecture.md", 12, "The router picks a backend."),
+            make_result("docs/api.rs", 1, "fn api()"),
+        ];
+
+        let classified = classify_results(&results, None);
+        assert_eq!(classified[0].category, ResultCategory::Doc);
+        assert_eq!(classified[1].category, ResultCategory::Doc);
+        assert_eq!(classified[2].category, ResultCategory::Test);
+        assert_eq!("docs".parse(), Ok(ResultCategory::Doc));
+        assert_eq!(category_header(ResultCategory::Doc), "-- docs --");
+    }
+
     #[test]
     fn classify_other_default() {
         let results = vec![make_result("src/main.rs", 5, "let x = 42;")];
@@ -795,6 +1523,108 @@ mod tests {
         assert!(!is_test_file(Path::new("src/main.rs")));
         assert!(!is_test_file(Path::new("src/testing.rs")));
         assert!(!is_test_file(Path::new("src/contest.rs")));
+        assert!(is_test_file(Path::new("docs/x.rs")));
+        assert!(is_test_file(Path::new("examples/demo.rs")));
+        assert!(is_test_path(Path::new("tests/foo.rs")));
+        assert!(is_test_path(Path::new("src/foo.spec.js")));
+        assert!(!is_test_path(Path::new("docs/x.rs")));
+        assert!(!is_test_path(Path::new("benchmarks/run.rs")));
+    }
+
+    #[test]
+    fn detect_test_file_by_content() {
+        assert!(detect_test_file(
+            Path::new("src/check.py"),
+            "import pytest\n"
+        ));
+        assert!(detect_test_file(
+            Path::new("src/Thing.java"),
+            "import org.junit.jupiter.api.Test;\n"
+        ));
+        assert!(detect_test_file(
+            Path::new("src/app.ts"),
+            "import { describe, expect } from 'vitest';\n"
+        ));
+        assert!(detect_test_file(
+            Path::new("spec/models/user_spec.rb"),
+            "require 'rails_helper'\n\nRSpec.describe User do\nend\n"
+        ));
+        assert!(detect_test_file(
+            Path::new("spec/support.lua"),
+            "local busted = require('busted')\n"
+        ));
+        assert!(detect_test_file(
+            Path::new("src/helpers.rs"),
+            "#![cfg(test)]\nfn x() {}"
+        ));
+        assert!(detect_test_file(Path::new("tests/util.rs"), "fn x() {}"));
+        assert!(!detect_test_file(Path::new("docs/x.rs"), "fn x() {}"));
+        assert!(!detect_test_file(
+            Path::new("src/lib.rs"),
+            "#[cfg(test)]\nmod tests {}"
+        ));
+        assert!(!detect_test_file(
+            Path::new("src/app.js"),
+            "describe(thing);"
+        ));
+    }
+
+    #[test]
+    fn detect_test_file_ignores_markers_outside_imports() {
+        // Go test files are recognised by their `_test.go` suffix alone.
+        assert!(!detect_test_file(
+            Path::new("cmd/main.go"),
+            "package main\n\nfunc main() {\n\tlog.Println(\"testing\")\n}\n"
+        ));
+        assert!(detect_test_file(
+            Path::new("cmd/main_test.go"),
+            "import \"testing\"\n"
+        ));
+        // Ordinary code that happens to call `describe` and `expect`.
+        assert!(!detect_test_file(
+            Path::new("src/schema.js"),
+            "const d = describe(model);\nconst e = expect(d, 'id');\n"
+        ));
+        // Frameworks named in comments and strings.
+        assert!(!detect_test_file(
+            Path::new("lib/runner.rb"),
+            "# Works like RSpec.describe\nputs \"require 'rspec' first\"\n"
+        ));
+        assert!(!detect_test_file(
+            Path::new("src/check.py"),
+            "# We used to import unittest here.\nHELP = \"import pytest to run\"\n"
+        ));
+        assert!(!detect_test_file(
+            Path::new("src/Doc.cs"),
+            "// using Xunit for the tests\nclass Doc {}\n"
+        ));
+    }
+
+    #[test]
+    fn test_classifier_uses_indexed_flag() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let conn = crate::db::open(&dir.path().join("index.db")).unwrap();
+        conn.execute(
+            "INSERT INTO files (path, hash, last_indexed, is_test) VALUES ('src/checks.py', 'h', 0, 1)",
+            [],
+        )
+        .unwrap();
+        let root = Path::new("/repo");
+
+        let exclude = TestClassifier::new(TestFilter::Exclude, Some(&conn), root);
+        assert!(!exclude.keep(Path::new("src/checks.py")));
+        assert!(!exclude.keep(Path::new("/repo/src/checks.py")));
+        assert!(!exclude.keep(Path::new("tests/a.py")));
+        assert!(exclude.keep(Path::new("src/app.py")));
+        assert!(exclude.keep(Path::new("docs/x.rs")));
+
+        let only = TestClassifier::new(TestFilter::Only, Some(&conn), root);
+        assert!(only.keep(Path::new("src/checks.py")));
+        assert!(!only.keep(Path::new("src/app.py")));
+        assert!(!only.keep(Path::new("docs/x.rs")));
+
+        let include = TestClassifier::new(TestFilter::Include, Some(&conn), root);
+        assert!(include.keep(Path::new("src/checks.py")));
     }
 
     #[test]
@@ -840,13 +1670,14 @@ mod tests {
 
     #[test]
     fn tier_ordering_full_sequence() {
-        // Definition < CallSite < Import < Other < Comment < Test
+        // Definition < CallSite < Import < Other < Comment < Doc < Test
         let tiers: Vec<u8> = vec![
             ResultCategory::Definition.tier(),
             ResultCategory::CallSite.tier(),
             ResultCategory::Import.tier(),
             ResultCategory::Other.tier(),
             ResultCategory::Comment.tier(),
+            ResultCategory::Doc.tier(),
             ResultCategory::Test.tier(),
         ];
         for i in 0..tiers.len() - 1 {
@@ -1072,6 +1903,326 @@ mod tests {
         assert_eq!(category_header(ResultCategory::Test), "-- tests --");
     }
 
+    // -----------------------------------------------------------------------
+    // CategoryLayout tests
+    // -----------------------------------------------------------------------
+
+    fn sample_groups() -> Vec<(ResultCategory, Vec<ClassifiedResult>)> {
+        group_by_category(rank_results(vec![
+            make_classified("src/a.rs", 5, "fn foo() {}", ResultCategory::Definition),
+            make_classified("src/b.rs", 10, "foo();", ResultCategory::CallSite),
+            make_classified("src/c.rs", 12, "foo();", ResultCategory::CallSite),
+            make_classified("src/d.rs", 1, "// foo", ResultCategory::Comment),
+            make_classified("tests/t.rs", 3, "foo();", ResultCategory::Test),
+        ]))
+    }
+
+    #[test]
+    fn result_category_round_trips_through_str() {
+        for cat in [
+            ResultCategory::Definition,
+            ResultCategory::CallSite,
+            ResultCategory::Import,
+            ResultCategory::Comment,
+            ResultCategory::Test,
+            ResultCategory::Other,
+        ] {
+            assert_eq!(cat.to_string().parse::<ResultCategory>().unwrap(), cat);
+        }
+        assert_eq!(
+            "Call-Site".parse::<ResultCategory>().unwrap(),
+            ResultCategory::CallSite
+        );
+        assert!("usages".parse::<ResultCategory>().is_err());
+    }
+
+    #[test]
+    fn default_layout_keeps_tier_order_and_headers() {
+        let groups = CategoryLayout::default().arrange(sample_groups());
+        let headers: Vec<String> = groups.iter().map(|g| g.header()).collect();
+        assert_eq!(
+            headers,
+            [
+                "-- definitions --",
+                "-- usages --",
+                "-- comments --",
+                "-- tests --"
+            ]
+        );
+        assert_eq!(groups[0].category, Some(ResultCategory::Definition));
+    }
+
+    #[test]
+    fn layout_applies_order_labels_and_collapsing() {
+        let config = SearchConfig {
+            category_order: vec!["test".into(), "bogus".into(), "call_site".into()],
+            category_labels: HashMap::from([
+                ("test".to_string(), "specs".to_string()),
+                ("collapsed".to_string(), "misc".to_string()),
+            ]),
+            collapse_below: 2,
+            ..SearchConfig::default()
+        };
+        let groups = CategoryLayout::from_config(&config).arrange(sample_groups());
+
+        let labels: Vec<&str> = groups.iter().map(|g| g.label.as_str()).collect();
+        assert_eq!(labels, ["usages", "misc"]);
+        assert_eq!(groups[0].category, Some(ResultCategory::CallSite));
+        // Collapsed groups keep their configured order: test, then definition, comment.
+        let collapsed = &groups[1];
+        assert_eq!(collapsed.category, None);
+        let cats: Vec<ResultCategory> = collapsed.items.iter().map(|i| i.category).collect();
+        assert_eq!(
+            cats,
+            [
+                ResultCategory::Test,
+                ResultCategory::Definition,
+                ResultCategory::Comment
+            ]
+        );
+
+        let config = SearchConfig {
+            category_order: vec!["test".into()],
+            category_labels: HashMap::from([("test".to_string(), "specs".to_string())]),
+            ..SearchConfig::default()
+        };
+        let groups = CategoryLayout::from_config(&config).arrange(sample_groups());
+        assert_eq!(groups[0].header(), "-- specs --");
+        assert_eq!(groups.len(), 4);
+    }
+
+    #[test]
+    fn category_weights_default_and_override() {
+        let groups = CategoryLayout::default().arrange(sample_groups());
+        let weights = CategoryWeights::from_config(&BudgetConfig::default());
+        let by_category: Vec<u32> = groups.iter().map(|g| weights.weight(g)).collect();
+        // definition, call site, comment, test
+        assert_eq!(by_category, [4, 2, 1, 1]);
+
+        let config = BudgetConfig {
+            category_weights: HashMap::from([
+                ("test".to_string(), 0),
+                ("call_sites".to_string(), 3),
+                ("usages".to_string(), 9),
+                ("collapsed".to_string(), 5),
+            ]),
+        };
+        let weights = CategoryWeights::from_config(&config);
+        let by_category: Vec<u32> = groups.iter().map(|g| weights.weight(g)).collect();
+        assert_eq!(by_category, [4, 3, 1, 0]);
+        let collapsed = ResultGroup {
+            category: None,
+            label: "other".to_string(),
+            items: Vec::new(),
+        };
+        assert_eq!(weights.weight(&collapsed), 5);
+    }
+
+    #[test]
+    fn layout_does_not_collapse_a_single_group() {
+        let config = SearchConfig {
+            collapse_below: 5,
+            ..SearchConfig::default()
+        };
+        let single = group_by_category(vec![make_classified(
+            "src/a.rs",
+            1,
+            "fn foo() {}",
+            ResultCategory::Definition,
+        )]);
+        let groups = CategoryLayout::from_config(&config).arrange(single);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].category, Some(ResultCategory::Definition));
+    }
+
+    // -----------------------------------------------------------------------
+    // Directory clusters
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn cluster_by_dir_counts_and_keeps_top_hits() {
+        let results = vec![
+            make_result("./src/auth/a.rs", 1, "log(1)"),
+            make_result("README.md", 2, "log"),
+            make_result("src/auth/b.rs", 3, "log(2)"),
+            make_result("src/db/c.rs", 4, "log(3)"),
+            make_result("src/auth/a.rs", 9, "log(4)"),
+            make_result("src/db/d.rs", 5, "log(5)"),
+        ];
+
+        let clusters = cluster_by_dir(results, 2, |r| r.file.as_path());
+        let summary: Vec<(&str, usize, Vec<u64>)> = clusters
+            .iter()
+            .map(|c| {
+                (
+                    c.dir.as_str(),
+                    c.count,
+                    c.items.iter().map(|r| r.line).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("src/auth", 3, vec![1, 3]),
+                ("src/db", 2, vec![4, 5]),
+                (".", 1, vec![2]),
+            ]
+        );
+    }
+
+    // -----------------------------------------------------------------------
+    // Sampling
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn stratified_sample_spreads_across_dirs_and_files() {
+        let mut results: Vec<SearchResult> = (1..=20)
+            .map(|line| make_result("src/hot/a.rs", line, "log"))
+            .collect();
+        results.push(make_result("src/hot/b.rs", 1, "log"));
+        results.push(make_result("src/cold/c.rs", 1, "log"));
+        results.push(make_result("README.md", 1, "log"));
+
+        let sample = stratified_sample(results, 5, |r| r.file.as_path());
+        let picked: Vec<(String, u64)> = sample
+            .iter()
+            .map(|r| (r.file.to_string_lossy().into_owned(), r.line))
+            .collect();
+        // One per directory first, then the next file of each directory.
+        assert_eq!(
+            picked,
+            [
+                ("src/hot/a.rs".to_string(), 1),
+                ("src/hot/a.rs".to_string(), 2),
+                ("src/hot/b.rs".to_string(), 1),
+                ("src/cold/c.rs".to_string(), 1),
+                ("README.md".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn stratified_sample_keeps_small_sets() {
+        let results = vec![make_result("a.rs", 1, "x"), make_result("a.rs", 2, "x")];
+        assert_eq!(stratified_sample(results, 5, |r| r.file.as_path()).len(), 2);
+    }
+
+    // -----------------------------------------------------------------------
+    // Recency
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn sort_by_recency_puts_newest_files_first() {
+        let mut results = vec![
+            make_result("old.rs", 1, "x"),
+            make_result("new.rs", 4, "x"),
+            make_result("undated.rs", 1, "x"),
+            make_result("new.rs", 2, "x"),
+        ];
+        let times = HashMap::from([
+            (PathBuf::from("old.rs"), 100),
+            (PathBuf::from("new.rs"), 200),
+        ]);
+        sort_by_recency(&mut results, &times, |r| r.file.as_path());
+        let order: Vec<(String, u64)> = results
+            .iter()
+            .map(|r| (r.file.to_string_lossy().into_owned(), r.line))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                ("new.rs".to_string(), 4),
+                ("new.rs".to_string(), 2),
+                ("old.rs".to_string(), 1),
+                ("undated.rs".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn file_recency_uses_commit_times_and_mtime_for_uncommitted_files() {
+        if Command::new("git").arg("--version").output().is_err() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let git = |args: &[&str], date: &str| {
+            Command::new("git")
+                .args(args)
+                .current_dir(root)
+                .env("GIT_AUTHOR_DATE", date)
+                .env("GIT_COMMITTER_DATE", date)
+                .env("GIT_AUTHOR_NAME", "Test")
+                .env("GIT_AUTHOR_EMAIL", "test@test.com")
+                .env("GIT_COMMITTER_NAME", "Test")
+                .env("GIT_COMMITTER_EMAIL", "test@test.com")
+                .output()
+                .unwrap();
+        };
+        git(&["init"], "");
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(root.join("src/old.rs"), "x").unwrap();
+        std::fs::write(root.join("src/new.rs"), "x").unwrap();
+        git(&["add", "."], "");
+        git(&["commit", "-m", "one"], "@1000000000 +0000");
+        std::fs::write(root.join("src/new.rs"), "y").unwrap();
+        git(&["commit", "-am", "two"], "@1100000000 +0000");
+        std::fs::write(root.join("src/draft.rs"), "x").unwrap();
+
+        // Checkout-fresh mtimes would make every file look new; commits win.
+        let cwd = root.join("src");
+        let files = [
+            Path::new("old.rs"),
+            Path::new("new.rs"),
+            Path::new("draft.rs"),
+        ];
+        let times = file_recency(root, &cwd, &files, RecencySource::Git);
+        assert_eq!(times[Path::new("old.rs")], 1_000_000_000);
+        assert_eq!(times[Path::new("new.rs")], 1_100_000_000);
+        assert!(times[Path::new("draft.rs")] > 1_100_000_000);
+
+        let times = file_recency(root, &cwd, &files, RecencySource::Mtime);
+        assert!(times[Path::new("old.rs")] > 1_100_000_000);
+        assert!("svn".parse::<RecencySource>().is_err());
+    }
+
+    #[test]
+    fn path_boost_puts_matching_files_first() {
+        let root = Path::new("/repo");
+        let globs = ["src/core/**".to_string(), "lib/".to_string()];
+        let boost = PathBoost::new(root, root, &globs).unwrap();
+        assert!(boost.matches(Path::new("src/core/store.rs")));
+        assert!(boost.matches(Path::new("./lib/a/b.rs")));
+        assert!(!boost.matches(Path::new("vendor/sdk/src/core/store.rs")));
+
+        let mut files = vec![
+            "vendor/x.rs",
+            "src/core/b.rs",
+            "src/main.rs",
+            "src/core/a.rs",
+        ];
+        boost.apply(&mut files, |f| Path::new(f));
+        assert_eq!(
+            files,
+            [
+                "src/core/b.rs",
+                "src/core/a.rs",
+                "vendor/x.rs",
+                "src/main.rs"
+            ]
+        );
+
+        // Result paths are relative to the working directory.
+        let boost = PathBoost::new(root, &root.join("src"), &globs).unwrap();
+        assert!(boost.matches(Path::new("core/store.rs")));
+        assert!(!boost.matches(Path::new("main.rs")));
+
+        let none = PathBoost::new(root, root, &[]).unwrap();
+        assert!(none.is_empty());
+        assert!(!none.matches(Path::new("src/core/store.rs")));
+    }
+
     // -----------------------------------------------------------------------
     // rank_and_dedup end-to-end test
     // -----------------------------------------------------------------------
@@ -1093,6 +2244,36 @@ mod tests {
         assert_eq!(groups[0].0, ResultCategory::Import);
     }
 
+    #[test]
+    fn rank_and_dedup_preserves_match_spans() {
+        let with_span = |file: &str, line: u64, content: &str, col: u64, offset: u64| {
+            let len = "foo".len() as u64;
+            SearchResult {
+                col,
+                end_col: Some(col + len),
+                start_byte: Some(offset),
+                end_byte: Some(offset + len),
+                ..make_result(file, line, content)
+            }
+        };
+        let results = vec![
+            with_span("src/lib.rs", 9, "    let x = foo();", 13, 120),
+            with_span("src/main.rs", 1, "use foo;", 5, 4),
+        ];
+
+        let groups = rank_and_dedup(&results, None, "foo");
+
+        let ranked: Vec<&SearchResult> = groups
+            .iter()
+            .flat_map(|(_, items)| items.iter().map(|i| &i.result))
+            .collect();
+        assert_eq!(ranked.len(), 2);
+        for original in &results {
+            let kept = ranked.iter().find(|r| r.file == original.file).unwrap();
+            assert_eq!(*kept, original);
+        }
+    }
+
     // -----------------------------------------------------------------------
     // FusedResult / FusedSource type tests
     // -----------------------------------------------------------------------
@@ -1110,6 +2291,9 @@ mod tests {
             file: "src/lib.rs".to_string(),
             line: 42,
             col: 1,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             content: "fn foo() {}".to_string(),
             rrf_score: 0.016,
             source: FusedSource::Structural,
@@ -1191,6 +2375,23 @@ mod tests {
         assert_eq!(fused[0].content, "fn foo()");
     }
 
+    #[test]
+    fn fuse_rrf_keeps_structural_match_span() {
+        let structural = vec![SearchResult {
+            col: 4,
+            end_col: Some(7),
+            start_byte: Some(103),
+            end_byte: Some(106),
+            ..make_result("src/a.rs", 10, "fn foo()")
+        }];
+        let semantic = vec![make_semantic("src/a.rs", 10, "foo", 0.9)];
+        let fused = fuse_rrf(&structural, &semantic, 60.0);
+        assert_eq!(
+            (fused[0].end_col, fused[0].start_byte, fused[0].end_byte),
+            (Some(7), Some(103), Some(106))
+        );
+    }
+
     #[test]
     fn fuse_rrf_semantic_outranks_structural() {
         // Semantic rank-1 result should outrank structural rank-3 result
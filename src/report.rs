@@ -0,0 +1,255 @@
+//! Codebase reports for `wonk report`.
+//!
+//! `async` lists async functions together with the non-async functions that
+//! call them -- the points where async code is entered from a synchronous
+//! context and may block.  `unsafe` lists unsafe declarations and unsafe
+//! blocks, each block labelled with the function that encloses it.
+
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::Result;
+use rusqlite::Connection;
+
+use crate::indexer;
+use crate::types::{AsyncBoundary, CallPathHop, SymbolKind, UnsafeSite};
+
+/// SQL predicate matching symbols whose space-separated `modifiers` column
+/// contains the modifier bound to `?1`.
+const HAS_MODIFIER: &str = "(' ' || COALESCE(modifiers, '') || ' ') LIKE '% ' || ?1 || ' %'";
+
+/// Returns `true` when `file` lies under `path` (or no path was given).
+fn in_path(file: &str, path: Option<&str>) -> bool {
+    match path.map(|p| p.trim_start_matches("./").trim_end_matches('/')) {
+        None | Some("") | Some(".") => true,
+        Some(p) => file == p || file.starts_with(&format!("{p}/")),
+    }
+}
+
+/// Build a [`CallPathHop`] from a `(name, kind, file, line)` row.
+fn hop(name: String, kind: String, file: String, line: i64) -> CallPathHop {
+    CallPathHop {
+        symbol_name: name,
+        symbol_kind: SymbolKind::from_str(&kind).unwrap_or(SymbolKind::Function),
+        file,
+        line: line as usize,
+    }
+}
+
+/// List async functions under `path` with their non-async callers.
+///
+/// Callers come from the call graph (`references.caller_id`); a call whose
+/// target was resolved to a different symbol of the same name is skipped.
+/// Results are ordered by file and line.
+pub fn async_boundaries(conn: &Connection, path: Option<&str>) -> Result<Vec<AsyncBoundary>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, name, kind, file, line FROM symbols \
+         WHERE {HAS_MODIFIER} ORDER BY file, line"
+    ))?;
+    let functions: Vec<(i64, String, String, String, i64)> = stmt
+        .query_map(["async"], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, i64>(4)?,
+            ))
+        })?
+        .filter_map(|r| r.ok())
+        .filter(|(_, _, _, file, _)| in_path(file, path))
+        .collect();
+
+    let mut callers_stmt = conn.prepare(
+        "SELECT DISTINCT c.name, c.kind, c.file, c.line, c.modifiers \
+         FROM \"references\" r \
+         JOIN symbols c ON c.id = r.caller_id \
+         WHERE r.name = ?1 AND (r.target_id IS NULL OR r.target_id = ?2) \
+         ORDER BY c.file, c.line",
+    )?;
+
+    let mut boundaries = Vec::with_capacity(functions.len());
+    for (id, name, kind, file, line) in functions {
+        let sync_callers = callers_stmt
+            .query_map(rusqlite::params![name, id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                ))
+            })?
+            .filter_map(|r| r.ok())
+            .filter(|(.., modifiers)| {
+                !modifiers
+                    .as_deref()
+                    .is_some_and(|m| m.split_whitespace().any(|m| m == "async"))
+            })
+            .map(|(name, kind, file, line, _)| hop(name, kind, file, line))
+            .collect();
+        boundaries.push(AsyncBoundary {
+            function: hop(name, kind, file, line),
+            sync_callers,
+        });
+    }
+    Ok(boundaries)
+}
+
+/// List unsafe declarations and unsafe blocks under `path`.
+///
+/// Declarations come from the index (symbols carrying the `unsafe`
+/// modifier).  Blocks are not indexed, so indexed Rust and C# files that
+/// mention `unsafe` are re-parsed from `repo_root`.  Results are ordered by
+/// file and line.
+pub fn unsafe_sites(
+    conn: &Connection,
+    repo_root: &Path,
+    path: Option<&str>,
+) -> Result<Vec<UnsafeSite>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT name, file, line FROM symbols WHERE {HAS_MODIFIER}"
+    ))?;
+    let mut sites: Vec<UnsafeSite> = stmt
+        .query_map(["unsafe"], |row| {
+            Ok(UnsafeSite {
+                block: false,
+                symbol: Some(row.get(0)?),
+                file: row.get(1)?,
+                line: row.get::<_, i64>(2)? as usize,
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .filter(|site| in_path(&site.file, path))
+        .collect();
+
+    let mut files_stmt = conn.prepare("SELECT path FROM files ORDER BY path")?;
+    let files: Vec<String> = files_stmt
+        .query_map([], |row| row.get(0))?
+        .filter_map(|r| r.ok())
+        .filter(|file: &String| in_path(file, path))
+        .collect();
+
+    for file in files {
+        let Some(lang) = indexer::detect_language(Path::new(&file)) else {
+            continue;
+        };
+        if !matches!(lang, indexer::Lang::Rust | indexer::Lang::CSharp) {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(repo_root.join(&file)) else {
+            continue;
+        };
+        if !content.contains("unsafe") {
+            continue;
+        }
+        let source = indexer::parse_source(lang, &content);
+        let Some(tree) = indexer::get_parser(lang).and_then(|mut p| p.parse(&source, None)) else {
+            continue;
+        };
+        for (line, symbol) in indexer::extract_unsafe_blocks(&tree, &source, lang) {
+            sites.push(UnsafeSite {
+                block: true,
+                file: file.clone(),
+                line,
+                symbol,
+            });
+        }
+    }
+
+    sites.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    Ok(sites)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::pipeline;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn make_indexed_repo(files: &[(&str, &str)]) -> (TempDir, Connection) {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        for (path, content) in files {
+            let full = root.join(path);
+            fs::create_dir_all(full.parent().unwrap()).unwrap();
+            fs::write(full, content).unwrap();
+        }
+        pipeline::build_index(root, true).unwrap();
+        let conn = db::open_existing(&db::local_index_path(root)).unwrap();
+        (dir, conn)
+    }
+
+    #[test]
+    fn in_path_matches_directory_prefixes() {
+        assert!(in_path("src/a.rs", None));
+        assert!(in_path("src/a.rs", Some("src")));
+        assert!(in_path("src/a.rs", Some("./src/")));
+        assert!(in_path("src/a.rs", Some("src/a.rs")));
+        assert!(!in_path("srcx/a.rs", Some("src")));
+    }
+
+    #[test]
+    fn async_boundaries_list_only_sync_callers() {
+        let source = r#"
+async fn fetch() {}
+
+async fn handler() {
+    fetch().await;
+}
+
+fn main() {
+    block_on(fetch());
+}
+"#;
+        let (_dir, conn) = make_indexed_repo(&[("src/main.rs", source)]);
+        let boundaries = async_boundaries(&conn, None).unwrap();
+
+        let names: Vec<&str> = boundaries
+            .iter()
+            .map(|b| b.function.symbol_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["fetch", "handler"]);
+
+        let callers: Vec<&str> = boundaries[0]
+            .sync_callers
+            .iter()
+            .map(|c| c.symbol_name.as_str())
+            .collect();
+        assert_eq!(callers, vec!["main"]);
+        assert!(boundaries[1].sync_callers.is_empty());
+    }
+
+    #[test]
+    fn unsafe_sites_include_declarations_and_blocks() {
+        let source = r#"
+unsafe fn raw_read(p: *const u8) -> u8 {
+    *p
+}
+
+fn read(p: *const u8) -> u8 {
+    unsafe { raw_read(p) }
+}
+"#;
+        let (dir, conn) =
+            make_indexed_repo(&[("src/lib.rs", source), ("other/safe.rs", "fn ok() {}\n")]);
+        let sites = unsafe_sites(&conn, dir.path(), None).unwrap();
+
+        assert_eq!(sites.len(), 2);
+        assert!(!sites[0].block);
+        assert_eq!(sites[0].symbol.as_deref(), Some("raw_read"));
+        assert_eq!(sites[0].line, 2);
+        assert!(sites[1].block);
+        assert_eq!(sites[1].symbol.as_deref(), Some("read"));
+        assert_eq!(sites[1].line, 7);
+
+        assert!(
+            unsafe_sites(&conn, dir.path(), Some("other"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
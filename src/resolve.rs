@@ -0,0 +1,443 @@
+//! Reference → definition resolution for `wonk ref --resolve`.
+//!
+//! For each reference, candidate definitions are the indexed symbols with
+//! the same name.  A single candidate resolves trivially; otherwise the
+//! candidates are ranked by how the referencing file can see them: a
+//! definition in the same file wins, then one in a file the referencing
+//! file imports, then one in the same directory.  References that remain
+//! ambiguous are left unresolved rather than guessed.
+//!
+//! The same visibility rules back the default `wonk ref` filtering, which
+//! drops references that cannot see any definition of the queried symbol
+//! (`--loose` disables it).
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use rusqlite::Connection;
+
+use crate::errors::DbError;
+use crate::types::{Reference, SymbolKind};
+
+/// How a reference was matched to its definition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// Defined in the same file as the reference.
+    SameFile,
+    /// Defined in a file imported by the referencing file.
+    Import,
+    /// The only definition with this name in the index.
+    Unique,
+    /// Defined in the same directory as the referencing file.
+    SameDirectory,
+}
+
+impl Resolution {
+    /// Short label used in output.
+    pub fn label(self) -> &'static str {
+        match self {
+            Resolution::SameFile => "same-file",
+            Resolution::Import => "import",
+            Resolution::Unique => "unique",
+            Resolution::SameDirectory => "same-dir",
+        }
+    }
+}
+
+/// The definition a reference resolved to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Definition {
+    pub file: String,
+    pub line: usize,
+    pub kind: SymbolKind,
+    pub via: Resolution,
+}
+
+/// A candidate definition loaded from the symbols table.
+#[derive(Debug, Clone)]
+struct Candidate {
+    file: String,
+    line: usize,
+    kind: SymbolKind,
+    scope: Option<String>,
+}
+
+/// Resolves references to definitions, caching symbol and import lookups
+/// across the references of a single query.
+pub struct Resolver<'a> {
+    conn: &'a Connection,
+    candidates: HashMap<String, Vec<Candidate>>,
+    imports: HashMap<String, Vec<String>>,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self {
+            conn,
+            candidates: HashMap::new(),
+            imports: HashMap::new(),
+        }
+    }
+
+    /// Resolve `reference` to its most likely definition, or `None` when no
+    /// definition is indexed or the candidates cannot be told apart.
+    pub fn resolve(&mut self, reference: &Reference) -> Result<Option<Definition>, DbError> {
+        let candidates = self.candidates_for(&reference.name)?.clone();
+        let pick = |c: &Candidate, via| Definition {
+            file: c.file.clone(),
+            line: c.line,
+            kind: c.kind,
+            via,
+        };
+
+        if let [only] = candidates.as_slice() {
+            let via = if only.file == reference.file {
+                Resolution::SameFile
+            } else {
+                Resolution::Unique
+            };
+            return Ok(Some(pick(only, via)));
+        }
+
+        let same_file: Vec<_> = candidates
+            .iter()
+            .filter(|c| c.file == reference.file)
+            .collect();
+        if !same_file.is_empty() {
+            // Nearest preceding definition, else the first one in the file.
+            let best = same_file
+                .iter()
+                .filter(|c| c.line <= reference.line)
+                .max_by_key(|c| c.line)
+                .or_else(|| same_file.iter().min_by_key(|c| c.line))
+                .expect("same_file is non-empty");
+            return Ok(Some(pick(best, Resolution::SameFile)));
+        }
+
+        let imports = self.imports_for(&reference.file)?.clone();
+        if let Some(c) = unique_match(&candidates, |c| {
+            imports.iter().any(|imp| import_matches_file(imp, &c.file))
+        }) {
+            return Ok(Some(pick(c, Resolution::Import)));
+        }
+
+        let ref_dir = parent_dir(&reference.file);
+        if let Some(c) = unique_match(&candidates, |c| parent_dir(&c.file) == ref_dir) {
+            return Ok(Some(pick(c, Resolution::SameDirectory)));
+        }
+
+        Ok(None)
+    }
+
+    /// Drop references that cannot see any definition of the queried symbol.
+    ///
+    /// The queried definitions are the indexed symbols named `name`, narrowed
+    /// by `scope` (`Client.open`) and `file_hint` (`io::open`) when given.  A
+    /// reference is kept when its file defines a queried symbol itself, or
+    /// defines no symbol of that name and either imports a queried
+    /// definition's module, sits in the same directory, or names the module
+    /// at the call site (`os.open(...)`).  Files with no recorded imports are
+    /// given the benefit of the doubt.  Nothing is filtered when no queried
+    /// definition is indexed.  Returns the number of references dropped.
+    pub fn retain_visible(
+        &mut self,
+        name: &str,
+        scope: Option<&str>,
+        file_hint: Option<&str>,
+        refs: &mut Vec<Reference>,
+    ) -> Result<usize, DbError> {
+        let candidates = self.candidates_for(name)?.clone();
+        let targets: Vec<&Candidate> = candidates
+            .iter()
+            .filter(|c| scope.is_none_or(|s| c.scope.as_deref() == Some(s)))
+            .filter(|c| file_hint.is_none_or(|h| c.file.contains(h)))
+            .collect();
+        if targets.is_empty() {
+            return Ok(0);
+        }
+
+        let before = refs.len();
+        let mut kept = Vec::with_capacity(before);
+        for r in refs.drain(..) {
+            let own: Vec<&Candidate> = candidates.iter().filter(|c| c.file == r.file).collect();
+            let visible = if !own.is_empty() {
+                own.iter()
+                    .any(|c| targets.iter().any(|t| std::ptr::eq(*c, *t)))
+            } else {
+                let imports = self.imports_for(&r.file)?;
+                imports.is_empty()
+                    || targets.iter().any(|t| {
+                        parent_dir(&t.file) == parent_dir(&r.file)
+                            || imports.iter().any(|imp| import_matches_file(imp, &t.file))
+                            || module_name(&t.file).is_some_and(|m| {
+                                r.context
+                                    .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                                    .any(|word| word == m)
+                            })
+                    })
+            };
+            if visible {
+                kept.push(r);
+            }
+        }
+        *refs = kept;
+        Ok(before - refs.len())
+    }
+
+    fn candidates_for(&mut self, name: &str) -> Result<&Vec<Candidate>, DbError> {
+        if !self.candidates.contains_key(name) {
+            let mut stmt = self.conn.prepare_cached(
+                "SELECT file, line, kind, scope FROM symbols WHERE name = ?1 ORDER BY file, line",
+            )?;
+            let rows = stmt.query_map(rusqlite::params![name], |row| {
+                let line: i64 = row.get(1)?;
+                let kind: String = row.get(2)?;
+                Ok(Candidate {
+                    file: row.get(0)?,
+                    line: line as usize,
+                    kind: kind.parse().unwrap_or(SymbolKind::Function),
+                    scope: row.get(3)?,
+                })
+            })?;
+            let mut found = Vec::new();
+            for row in rows {
+                found.push(row?);
+            }
+            self.candidates.insert(name.to_string(), found);
+        }
+        Ok(&self.candidates[name])
+    }
+
+    fn imports_for(&mut self, file: &str) -> Result<&Vec<String>, DbError> {
+        if !self.imports.contains_key(file) {
+            // Resolved targets are included so edges that name no module
+            // (Rails-autoloaded constants) still point at their file.
+            let mut stmt = self.conn.prepare_cached(
+                "SELECT import_path FROM file_imports WHERE source_file = ?1 \
+                 UNION SELECT resolved_file FROM file_imports \
+                 WHERE source_file = ?1 AND resolved_file IS NOT NULL",
+            )?;
+            let rows = stmt.query_map(rusqlite::params![file], |row| row.get::<_, String>(0))?;
+            let mut found = Vec::new();
+            for row in rows {
+                found.push(row?);
+            }
+            self.imports.insert(file.to_string(), found);
+        }
+        Ok(&self.imports[file])
+    }
+}
+
+/// Return the only candidate satisfying `pred`, or `None` when zero or
+/// several do.
+fn unique_match(candidates: &[Candidate], pred: impl Fn(&Candidate) -> bool) -> Option<&Candidate> {
+    let mut matches = candidates.iter().filter(|c| pred(c));
+    let first = matches.next()?;
+    if matches.any(|c| c.file != first.file) {
+        return None;
+    }
+    Some(first)
+}
+
+/// Whether an import path (in any language's syntax) plausibly refers to
+/// `file`.
+///
+/// The import is split on path and module separators and compared with the
+/// file's [`module_name`].
+fn import_matches_file(import: &str, file: &str) -> bool {
+    let Some(module) = module_name(file) else {
+        return false;
+    };
+    import
+        .split(['/', '\\', '.', ':', '"', '\'', '<', '>'])
+        .any(|segment| segment == module)
+}
+
+/// The module name a file is imported by: its stem, or the directory name
+/// for package entry files such as `mod.rs`, `index.ts`, and `__init__.py`.
+fn module_name(file: &str) -> Option<&str> {
+    let path = Path::new(file);
+    let stem = path.file_stem()?.to_str()?;
+    if matches!(stem, "mod" | "index" | "__init__" | "lib" | "main") {
+        path.parent()?.file_name()?.to_str()
+    } else {
+        Some(stem)
+    }
+}
+
+fn parent_dir(file: &str) -> &str {
+    file.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::types::ReferenceKind;
+    use tempfile::TempDir;
+
+    fn test_db() -> (TempDir, Connection) {
+        let dir = TempDir::new().unwrap();
+        let conn = db::open(&dir.path().join("index.db")).unwrap();
+        (dir, conn)
+    }
+
+    fn add_symbol(conn: &Connection, name: &str, file: &str, line: usize) {
+        conn.execute(
+            "INSERT INTO symbols (name, kind, file, line, col, language) VALUES (?1, 'function', ?2, ?3, 0, 'rust')",
+            rusqlite::params![name, file, line as i64],
+        )
+        .unwrap();
+    }
+
+    fn add_import(conn: &Connection, source: &str, import: &str) {
+        conn.execute(
+            "INSERT INTO file_imports (source_file, import_path) VALUES (?1, ?2)",
+            rusqlite::params![source, import],
+        )
+        .unwrap();
+    }
+
+    fn reference(name: &str, file: &str, line: usize) -> Reference {
+        Reference {
+            name: name.into(),
+            kind: ReferenceKind::Call,
+            file: file.into(),
+            line,
+            col: 0,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
+            context: String::new(),
+            caller_name: None,
+            confidence: 0.5,
+        }
+    }
+
+    #[test]
+    fn unique_definition_resolves() {
+        let (_dir, conn) = test_db();
+        add_symbol(&conn, "helper", "src/util.rs", 3);
+        let mut r = Resolver::new(&conn);
+        let def = r
+            .resolve(&reference("helper", "src/main.rs", 10))
+            .unwrap()
+            .unwrap();
+        assert_eq!(def.file, "src/util.rs");
+        assert_eq!(def.line, 3);
+        assert_eq!(def.via, Resolution::Unique);
+    }
+
+    #[test]
+    fn same_file_definition_wins() {
+        let (_dir, conn) = test_db();
+        add_symbol(&conn, "helper", "src/util.rs", 3);
+        add_symbol(&conn, "helper", "src/main.rs", 1);
+        let mut r = Resolver::new(&conn);
+        let def = r
+            .resolve(&reference("helper", "src/main.rs", 10))
+            .unwrap()
+            .unwrap();
+        assert_eq!(def.file, "src/main.rs");
+        assert_eq!(def.via, Resolution::SameFile);
+    }
+
+    #[test]
+    fn import_disambiguates() {
+        let (_dir, conn) = test_db();
+        add_symbol(&conn, "parse", "src/json.ts", 3);
+        add_symbol(&conn, "parse", "src/yaml.ts", 7);
+        add_import(&conn, "src/app.ts", "./yaml");
+        let mut r = Resolver::new(&conn);
+        let def = r
+            .resolve(&reference("parse", "src/app.ts", 2))
+            .unwrap()
+            .unwrap();
+        assert_eq!(def.file, "src/yaml.ts");
+        assert_eq!(def.via, Resolution::Import);
+    }
+
+    #[test]
+    fn ambiguous_reference_is_unresolved() {
+        let (_dir, conn) = test_db();
+        add_symbol(&conn, "parse", "a/json.ts", 3);
+        add_symbol(&conn, "parse", "b/yaml.ts", 7);
+        let mut r = Resolver::new(&conn);
+        assert!(
+            r.resolve(&reference("parse", "c/app.ts", 2))
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn retain_visible_drops_unrelated_files() {
+        let (_dir, conn) = test_db();
+        add_symbol(&conn, "open", "src/store/files.py", 3);
+        add_import(&conn, "src/app.py", "store.files");
+        add_import(&conn, "tools/cli.py", "os");
+        let mut refs = vec![
+            reference("open", "src/app.py", 5),
+            reference("open", "tools/cli.py", 9),
+            reference("open", "src/store/cache.py", 2),
+        ];
+        let mut r = Resolver::new(&conn);
+        let dropped = r.retain_visible("open", None, None, &mut refs).unwrap();
+        assert_eq!(dropped, 1);
+        let files: Vec<_> = refs.iter().map(|r| r.file.as_str()).collect();
+        // cache.py has no recorded imports and is kept.
+        assert_eq!(files, ["src/app.py", "src/store/cache.py"]);
+    }
+
+    #[test]
+    fn retain_visible_respects_local_shadowing_and_scope() {
+        let (_dir, conn) = test_db();
+        add_symbol(&conn, "open", "a/files.py", 3);
+        add_symbol(&conn, "open", "b/other.py", 3);
+        conn.execute(
+            "UPDATE symbols SET scope = 'Store' WHERE file = 'a/files.py'",
+            [],
+        )
+        .unwrap();
+        let mut refs = vec![
+            reference("open", "a/files.py", 10),
+            reference("open", "b/other.py", 10),
+        ];
+        let mut r = Resolver::new(&conn);
+        r.retain_visible("open", Some("Store"), None, &mut refs)
+            .unwrap();
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].file, "a/files.py");
+    }
+
+    #[test]
+    fn retain_visible_accepts_qualified_call_sites() {
+        let (_dir, conn) = test_db();
+        add_symbol(&conn, "helper", "src/util.rs", 3);
+        add_import(&conn, "app/main.rs", "std::fs");
+        let mut refs = vec![reference("helper", "app/main.rs", 4)];
+        refs[0].context = "    util::helper();".into();
+        let mut r = Resolver::new(&conn);
+        assert_eq!(
+            r.retain_visible("helper", None, None, &mut refs).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn retain_visible_without_definitions_keeps_everything() {
+        let (_dir, conn) = test_db();
+        let mut refs = vec![reference("print", "a.py", 1)];
+        let mut r = Resolver::new(&conn);
+        assert_eq!(r.retain_visible("print", None, None, &mut refs).unwrap(), 0);
+        assert_eq!(refs.len(), 1);
+    }
+
+    #[test]
+    fn import_matching_handles_package_entry_files() {
+        assert!(import_matches_file("crate::config", "src/config.rs"));
+        assert!(import_matches_file("./utils", "src/utils/index.ts"));
+        assert!(import_matches_file("pkg.models", "pkg/models/__init__.py"));
+        assert!(!import_matches_file("./utils", "src/util.ts"));
+    }
+}
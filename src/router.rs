@@ -8,25 +8,33 @@
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use rusqlite::Connection;
 
-use crate::cli::{Cli, Command, ContextArgs, DaemonCommand, McpCommand, ReposCommand};
+use crate::cli::{
+    BudgetCommand, Cli, Command, ContextArgs, DaemonCommand, GlobalCommand, McpCommand, ReportArgs,
+    ReportCommand, ReposCommand,
+};
 use crate::db;
 use crate::errors::DbError;
 #[cfg(test)]
 use crate::errors::SearchError;
 use crate::output::{
-    self, AffectedFlowOutput, BlastOutput, BudgetStatus, CallPathHopOutput, CalleeOutput,
-    CallerOutput, ChangedSymbolOutput, ChangesOutput, FlowOutput, FlowStepOutput, Formatter,
-    OutputFormat, RefOutput, SearchOutput, SemanticOutput, ShowOutput, SignatureOutput,
-    SummaryOutput, SymbolOutput,
+    self, AffectedFlowOutput, AsyncBoundaryOutput, BlastOutput, BudgetStatus, CallPathHopOutput,
+    CalleeOutput, CallerOutput, ChangedSymbolOutput, ChangesOutput, DefinitionOutput, FindOutput,
+    FlowOutput, FlowStepOutput, Formatter, GlobalSymbolOutput, IndexProfileOutput, LangStatsOutput,
+    OutputFormat, ParseOutput, RecipeOutput, RefOutput, SearchOutput, SemanticOutput, ShowOutput,
+    SignatureOutput, SummaryOutput, SymbolOutput, UnsafeSiteOutput,
 };
 use crate::pipeline;
 use crate::progress::{self, Progress};
 use crate::search;
+use crate::storage::{Backend, IndexStore};
 use crate::types::{Reference, ReferenceKind, Symbol, SymbolKind};
 
+/// Number of files listed under "slowest files" by `wonk init --profile`.
+const PROFILE_SLOWEST_FILES: usize = 20;
+
 // ---------------------------------------------------------------------------
 // Search mode detection
 // ---------------------------------------------------------------------------
@@ -56,6 +64,66 @@ pub fn detect_search_mode(raw: bool, smart: bool, symbol_count: u64) -> SearchMo
 // ---------------------------------------------------------------------------
 
 pub fn dispatch(cli: Cli) -> Result<()> {
+    // Long-running servers keep the default Ctrl-C behaviour; everything
+    // else stops cleanly and keeps what it has written.
+    if !matches!(cli.command, Command::Daemon(_) | Command::Mcp(_)) {
+        let _ = crate::deadline::cancel_on_interrupt();
+    }
+    let timeout = cli.timeout;
+    let cli_format = cli.format;
+    let cli_profile = cli.config_profile.clone();
+    let quiet = cli.quiet;
+    let query = is_query_command(&cli.command);
+    let result = dispatch_command(cli);
+    if result.is_ok() {
+        report_caveats(query, quiet);
+    }
+    // Only an expired token interrupts SQL, so an interrupted statement means
+    // the command was cut short even if nothing checked the deadline.
+    let sql_interrupted = result.as_ref().is_err_and(crate::deadline::is_interrupt);
+    let interrupted = crate::deadline::interrupted();
+    let timed_out = crate::deadline::timed_out() || (sql_interrupted && !interrupted);
+    if (!interrupted && !timed_out) || (result.is_err() && !sql_interrupted) {
+        return result;
+    }
+
+    // The command was cut short: whatever was written so far is the partial
+    // result, and an interrupted query is not an error.
+    let format = cli_format.unwrap_or_else(|| {
+        let repo_root = std::env::current_dir()
+            .ok()
+            .and_then(|cwd| db::find_repo_root(&cwd).ok());
+        match &cli_profile {
+            Some(name) => crate::config::Config::load_with_profile(repo_root.as_deref(), name),
+            None => crate::config::Config::load(repo_root.as_deref()),
+        }
+        .unwrap_or_default()
+        .output
+        .default_format
+        .parse()
+        .unwrap_or(OutputFormat::Grep)
+    });
+    if format.is_structured() {
+        let mut fmt = Formatter::new(io::stdout().lock(), format, false);
+        if interrupted {
+            fmt.format_interrupt_meta(&output::InterruptMeta { interrupted: true })?;
+        } else if let Some(secs) = timeout {
+            fmt.format_timeout_meta(&output::TimeoutMeta {
+                timed_out: true,
+                timeout_secs: secs,
+            })?;
+        }
+    } else if !quiet {
+        if interrupted {
+            output::print_interrupt_notice();
+        } else if let Some(secs) = timeout {
+            output::print_timeout_notice(secs);
+        }
+    }
+    Ok(())
+}
+
+fn dispatch_command(cli: Cli) -> Result<()> {
     let quiet = cli.quiet;
     let stdout = io::stdout().lock();
 
@@ -63,7 +131,17 @@ pub fn dispatch(cli: Cli) -> Result<()> {
     let repo_root_for_config = std::env::current_dir()
         .ok()
         .and_then(|cwd| db::find_repo_root(&cwd).ok());
-    let config = crate::config::Config::load(repo_root_for_config.as_deref()).unwrap_or_default();
+    // A selected profile must exist, so its errors are not swallowed.
+    let config = match &cli.config_profile {
+        Some(name) => {
+            crate::config::Config::load_with_profile(repo_root_for_config.as_deref(), name)?
+        }
+        None => crate::config::Config::load(repo_root_for_config.as_deref()).unwrap_or_default(),
+    };
+
+    if cli.ascii || config.output.ascii {
+        crate::glyphs::set_ascii();
+    }
 
     // Resolve format: CLI flag > config default_format > grep.
     let format = cli.format.unwrap_or_else(|| {
@@ -74,6 +152,7 @@ pub fn dispatch(cli: Cli) -> Result<()> {
             .unwrap_or(OutputFormat::Grep)
     });
     let suppress = format.is_structured() || quiet;
+    output::set_structured_messages(format, quiet);
 
     // Resolve color: disabled for structured formats.
     let color = if format.is_structured() {
@@ -84,14 +163,105 @@ pub fn dispatch(cli: Cli) -> Result<()> {
 
     // When stdout is piped (not a terminal), group output by file (one line
     // per file) so `| grep "path/"` filters correctly and `| head -N` limits
-    // by file count.  Auto-budget is applied in cli::parse().
+    // by file count.
     let is_piped = !std::io::IsTerminal::is_terminal(&stdout);
-    let budget_limit = cli.budget;
+
+    // Budget: --budget > profile budget (0 = none) > auto-budget for piped
+    // output.  GitHub annotations run in CI, where a silently truncated
+    // result would hide findings, so they get no auto-budget.
+    let budget_limit = match (cli.budget, config.profile.budget) {
+        (Some(limit), _) => Some(limit),
+        (None, Some(limit)) => (limit > 0).then_some(limit),
+        (None, None) if is_piped && format != OutputFormat::Github => Some(crate::cli::AUTO_BUDGET),
+        (None, None) => None,
+    };
+    // A session budget file caps each command at what is left of it and
+    // refuses commands once it is spent.
+    let session_budget = match &cli.budget_file {
+        Some(path) if uses_session_budget(&cli.command) => {
+            let session = crate::budget::SessionBudget::load(Path::new(path))?;
+            if session.remaining() == 0 {
+                anyhow::bail!(
+                    "session token budget exhausted ({} of {} tokens used, tracked in {path}); \
+                     run `wonk budget reset` to start over",
+                    session.used,
+                    session.limit
+                );
+            }
+            Some((path, session.remaining()))
+        }
+        _ => None,
+    };
+    let budget_limit = match session_budget {
+        Some((path, left)) if budget_limit.is_none_or(|limit| limit > left) => {
+            output::print_hint(
+                &format!("limited to the {left} tokens left in the session budget ({path})"),
+                suppress,
+            );
+            Some(left)
+        }
+        _ => budget_limit,
+    };
     let page = cli.page;
-    let include_tests = cli.include_tests;
+    let include_tests = cli.include_tests || (config.profile.include_tests && !cli.no_tests);
+    let test_filter = crate::ranker::TestFilter::from_flags(include_tests, cli.tests_only);
+    let verify = cli.verify || config.profile.verify;
+    let no_generated = cli.no_generated || config.profile.no_generated;
+    let no_submodules = cli.no_submodules;
+    let sample = cli.sample;
+    // Text search already walks the current directory; the scope narrows the
+    // index-backed sym and ref results to match.
+    let cwd_scope = if matches!(
+        cli.command,
+        Command::Search(_) | Command::Sym(_) | Command::Ref(_)
+    ) {
+        CwdScope::detect((cli.cwd_scope || config.search.cwd_scope) && !cli.no_cwd_scope)
+    } else {
+        None
+    };
+    if let Some(scope) = &cwd_scope {
+        output::print_hint(
+            &format!(
+                "limited to {}/ (--cwd-scope); use --no-cwd-scope for the whole repository",
+                scope.dir.display()
+            ),
+            suppress,
+        );
+    }
+
+    // Long human-readable output on a terminal goes through $PAGER, started
+    // on the first line written.
+    let pager = (!is_piped && !format.is_structured() && config.output.pager && !cli.no_pager)
+        .then(crate::pager::command)
+        .flatten();
+    let stdout = crate::pager::Pager::new(
+        stdout,
+        pager,
+        std::io::IsTerminal::is_terminal(&io::stderr()),
+    );
 
     let mut fmt = Formatter::new(stdout, format, color);
     fmt.set_single_line(is_piped);
+    if let Some(root) = &repo_root_for_config {
+        let unit = config.output.col_unit.parse().unwrap_or_default();
+        fmt.set_columns(crate::columns::ColumnMapper::new(unit, root));
+    }
+    if format.is_structured()
+        && let Some(root) = &repo_root_for_config
+    {
+        // `sym`, `ref`, and `sig` read the default index (see
+        // `QueryRouter::new`); other commands the first that exists, and a
+        // query's auto-init builds the default one.
+        let index = match &cli.command {
+            Command::Sym(_) | Command::Ref(_) | Command::Sig(_) => {
+                db::index_path_for(root, false).ok()
+            }
+            _ => db::find_existing_index(root).or_else(|| db::index_path_for(root, false).ok()),
+        };
+        if let Some(index) = index {
+            fmt.set_stamper(crate::stamp::Stamper::new(root, index));
+        }
+    }
     if let Some(limit) = budget_limit {
         if let Some(p) = page {
             fmt.set_budget_with_page(limit, p);
@@ -99,30 +269,203 @@ pub fn dispatch(cli: Cli) -> Result<()> {
             fmt.set_budget(limit);
         }
     }
+    if let Some((path, _)) = session_budget {
+        fmt.set_session_budget(PathBuf::from(path));
+    }
 
-    // Auto-init: if this is a query command and no index exists, build one.
+    if let Some(jobs) = cli.jobs {
+        pipeline::set_jobs(jobs);
+    }
+
+    // Auto-init: if this is a query command and no index exists, build one
+    // (unless it is built lazily from the grep fallback's answers; see
+    // `QueryRouter::new`).
     if is_query_command(&cli.command)
+        && pipeline::AutoInit::from_config(&config.index) == pipeline::AutoInit::Full
         && let Ok(cwd) = std::env::current_dir()
         && let Ok(repo_root) = db::find_repo_root(&cwd)
         && db::find_existing_index(&repo_root).is_none()
     {
+        note_local_fallback(false, suppress);
         let progress = Progress::new("Indexing", "Indexed", progress::detect_mode(suppress));
         let stats = pipeline::build_index_with_progress(&repo_root, false, &progress)?;
         progress.finish(&stats);
+        index_build_cut_short(suppress);
         // Signal daemon to build embeddings in background.
         if let Ok(index_path) = db::index_path_for(&repo_root, false)
             && let Ok(conn) = db::open(&index_path)
         {
             crate::daemon::request_embedding_build(&conn).ok();
         }
-        // Spawn daemon after auto-init (best-effort).
-        spawn_daemon_background(&repo_root);
+        if !cli.no_daemon {
+            spawn_daemon_background(&repo_root, config.daemon.auto_start, true);
+        }
+    } else if is_query_command(&cli.command)
+        && !cli.no_daemon
+        && let Some(root) = &repo_root_for_config
+        && db::find_existing_index(root).is_some()
+    {
+        spawn_daemon_background(root, config.daemon.auto_start, false);
+    }
+
+    // The deadline starts after auto-init so that building a missing index
+    // does not count against --timeout.
+    if let Some(secs) = cli.timeout {
+        crate::deadline::start(std::time::Duration::from_secs_f64(secs));
     }
 
     match cli.command {
+        Command::Search(mut args) if !args.patterns.is_empty() || args.patterns_file.is_some() => {
+            // Multi-pattern mode: one pass over the tree, plain output with
+            // the matching patterns listed per line.
+            if let Some(file) = &args.patterns_file {
+                args.patterns
+                    .extend(search::read_patterns_file(Path::new(file))?);
+            }
+            if args.patterns.is_empty() {
+                output::print_hint("no patterns to search for", suppress);
+                return Ok(());
+            }
+            let highlight = search::combined_pattern(&args.patterns, args.regex);
+            fmt.set_highlight(&highlight, true, args.ignore_case);
+
+            let mut paths = args.paths;
+            if let Some(f) = args.file {
+                paths.insert(0, f);
+            }
+
+            let mut results =
+                search::multi_search(&args.patterns, args.regex, args.ignore_case, &paths)?;
+
+            if test_filter != crate::ranker::TestFilter::Include {
+                let root = std::env::current_dir()
+                    .ok()
+                    .and_then(|cwd| db::find_repo_root(&cwd).ok())
+                    .unwrap_or_else(|| PathBuf::from("."));
+                let index = db::find_existing_index(&root).and_then(|p| db::open_existing(&p).ok());
+                let tests = crate::ranker::TestClassifier::new(test_filter, index.as_ref(), &root);
+                results.retain(|m| tests.keep(&m.result.file));
+            }
+            if args.recent {
+                let times = recency_times(
+                    results.iter().map(|m| m.result.file.as_path()),
+                    &config.search.recent_source,
+                )?;
+                crate::ranker::sort_by_recency(&mut results, &times, |m| &m.result.file);
+            }
+
+            let mut hits = vec![0usize; args.patterns.len()];
+            let mut truncated = 0usize;
+            for m in &results {
+                let mut out = SearchOutput::from_result(&m.result);
+                for &i in &m.patterns {
+                    hits[i] += 1;
+                    out.patterns.push(args.patterns[i].clone());
+                }
+                if fmt.format_search_result(&out)? == BudgetStatus::Skipped {
+                    truncated += 1;
+                }
+            }
+
+            let unmatched: Vec<&str> = args
+                .patterns
+                .iter()
+                .zip(&hits)
+                .filter(|(_, n)| **n == 0)
+                .map(|(p, _)| p.as_str())
+                .collect();
+            if !unmatched.is_empty() {
+                output::print_hint(
+                    &format!("no matches for: {}", unmatched.join(", ")),
+                    suppress,
+                );
+            }
+
+            emit_budget_summary_with_page(&mut fmt, truncated, budget_limit, format, page)?;
+        }
+        Command::Search(args) if args.stream => {
+            // Streaming mode: each file's matches are written as soon as the
+            // file has been searched, ranked within the file only.
+            let pattern = args.pattern.unwrap_or_default();
+            let auto_regex = !args.regex && search::looks_like_regex(&pattern);
+            let mut regex = if auto_regex {
+                output::print_hint("pattern looks like regex; auto-enabled --regex", suppress);
+                true
+            } else {
+                args.regex
+            };
+            fmt.set_highlight(&pattern, regex, args.ignore_case);
+
+            let mut paths = args.paths;
+            if let Some(f) = args.file {
+                paths.insert(0, f);
+            }
+
+            let root = std::env::current_dir()
+                .ok()
+                .and_then(|cwd| db::find_repo_root(&cwd).ok())
+                .unwrap_or_else(|| PathBuf::from("."));
+            let conn = if args.raw {
+                None
+            } else {
+                db::find_existing_index(&root).and_then(|path| db::open(&path).ok())
+            };
+            let symbol_count = conn
+                .as_ref()
+                .map(|c| db::count_matching_symbols(c, &pattern))
+                .unwrap_or(0);
+            let mode = detect_search_mode(args.raw, args.smart, symbol_count);
+            if !args.raw {
+                output::print_mode_indicator(symbol_count, suppress);
+            }
+            let index = if test_filter == crate::ranker::TestFilter::Include {
+                None
+            } else {
+                db::find_existing_index(&root).and_then(|p| db::open_existing(&p).ok())
+            };
+            let tests = crate::ranker::TestClassifier::new(test_filter, index.as_ref(), &root);
+            let ranked = matches!(mode, SearchMode::Smart(_));
+
+            let mut outcome = stream_search(
+                &mut fmt,
+                &pattern,
+                regex,
+                args.ignore_case,
+                &paths,
+                &tests,
+                ranked.then_some(conn.as_ref()),
+            );
+            if auto_regex && outcome.is_err() {
+                output::print_hint(
+                    "regex compilation failed; falling back to literal search",
+                    suppress,
+                );
+                regex = false;
+                fmt.set_highlight(&pattern, regex, args.ignore_case);
+                outcome = stream_search(
+                    &mut fmt,
+                    &pattern,
+                    regex,
+                    args.ignore_case,
+                    &paths,
+                    &tests,
+                    ranked.then_some(conn.as_ref()),
+                );
+            }
+            let (found, truncated) = outcome?;
+            if found == 0 {
+                output::print_hint(
+                    "no results found; try a broader pattern or different paths",
+                    suppress,
+                );
+            }
+
+            emit_budget_summary_with_page(&mut fmt, truncated, budget_limit, format, page)?;
+        }
         Command::Search(args) => {
+            let pattern = args.pattern.unwrap_or_default();
             // Auto-detect regex metacharacters and enable regex mode.
-            let auto_regex = !args.regex && search::looks_like_regex(&args.pattern);
+            let auto_regex = !args.regex && search::looks_like_regex(&pattern);
             let mut regex = if auto_regex {
                 output::print_hint("pattern looks like regex; auto-enabled --regex", suppress);
                 true
@@ -131,7 +474,7 @@ pub fn dispatch(cli: Cli) -> Result<()> {
             };
 
             // Set up match highlighting for search results.
-            fmt.set_highlight(&args.pattern, regex, args.ignore_case);
+            fmt.set_highlight(&pattern, regex, args.ignore_case);
 
             // Merge --file into paths list.
             let mut paths = args.paths;
@@ -139,7 +482,7 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                 paths.insert(0, f);
             }
 
-            let mut results = search::text_search(&args.pattern, regex, args.ignore_case, &paths);
+            let mut results = search::text_search(&pattern, regex, args.ignore_case, &paths);
 
             // When auto-regex detected the pattern but it fails to compile as
             // regex (e.g. unmatched parens), fall back to literal search.
@@ -149,16 +492,49 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                     suppress,
                 );
                 regex = false;
-                fmt.set_highlight(&args.pattern, regex, args.ignore_case);
-                results = search::text_search(&args.pattern, regex, args.ignore_case, &paths);
+                fmt.set_highlight(&pattern, regex, args.ignore_case);
+                results = search::text_search(&pattern, regex, args.ignore_case, &paths);
             }
 
             let mut results = results?;
 
-            // Exclude test/doc/example files unless --include-tests.
-            if !include_tests {
-                results.retain(|r| !crate::ranker::is_test_file(&r.file));
+            // Exclude test/doc/example files unless --include-tests, or keep
+            // only them with --tests-only.
+            if test_filter != crate::ranker::TestFilter::Include {
+                let root = std::env::current_dir()
+                    .ok()
+                    .and_then(|cwd| db::find_repo_root(&cwd).ok())
+                    .unwrap_or_else(|| PathBuf::from("."));
+                let index = db::find_existing_index(&root).and_then(|p| db::open_existing(&p).ok());
+                let tests = crate::ranker::TestClassifier::new(test_filter, index.as_ref(), &root);
+                results.retain(|r| tests.keep(&r.file));
             }
+            apply_sample(&mut results, sample, suppress, |r| r.file.as_path());
+
+            // --recent puts recently changed files first: across all plain
+            // results, and within each category when ranked.  Boosted files
+            // then move to the front of each category.
+            let boost = path_boost(&config, &args.boost, false)?;
+            let recency = if args.recent {
+                let times = recency_times(
+                    results.iter().map(|r| r.file.as_path()),
+                    &config.search.recent_source,
+                )?;
+                crate::ranker::sort_by_recency(&mut results, &times, |r| &r.file);
+                Some(times)
+            } else {
+                None
+            };
+            let reorder = |groups: &mut [crate::ranker::ResultGroup]| {
+                for group in groups {
+                    if let Some(times) = &recency {
+                        crate::ranker::sort_by_recency(&mut group.items, times, |item| {
+                            &item.result.file
+                        });
+                    }
+                    boost.apply(&mut group.items, |item| &item.result.file);
+                }
+            };
 
             if results.is_empty() {
                 output::print_hint(
@@ -182,10 +558,16 @@ pub fn dispatch(cli: Cli) -> Result<()> {
             // Count symbol matches for mode detection and indicator display.
             let symbol_count = conn
                 .as_ref()
-                .map(|c| db::count_matching_symbols(c, &args.pattern))
+                .map(|c| db::count_matching_symbols(c, &pattern))
                 .unwrap_or(0);
 
-            let mode = detect_search_mode(args.raw, args.smart, symbol_count);
+            let mode = detect_search_mode(args.raw, args.smart || args.group_json, symbol_count);
+            if args.group_json && !format.is_structured() {
+                output::print_hint(
+                    "--group-json only applies to --format json or toon",
+                    suppress,
+                );
+            }
 
             // Print mode indicator (skip for raw — user explicitly chose it).
             if !args.raw {
@@ -196,14 +578,56 @@ pub fn dispatch(cli: Cli) -> Result<()> {
 
             let mut truncated = 0usize;
 
+            if args.cluster.is_some() {
+                // Directory clusters: results in ranked (or plain) order,
+                // grouped by directory and cut to the top hits of each.
+                let ordered: Vec<SearchOutput> = match mode {
+                    SearchMode::Smart(_) => {
+                        let layout = crate::ranker::CategoryLayout::from_config(&config.search);
+                        let mut groups = layout.arrange(crate::ranker::rank_and_dedup(
+                            &results,
+                            conn.as_ref(),
+                            &pattern,
+                        ));
+                        reorder(&mut groups);
+                        groups
+                            .into_iter()
+                            .flat_map(|group| group.items)
+                            .map(|item| {
+                                let mut out = SearchOutput::from_result(&item.result);
+                                out.annotation = item.annotation;
+                                out.category = Some(item.category.to_string());
+                                out
+                            })
+                            .collect()
+                    }
+                    SearchMode::Plain => results.iter().map(SearchOutput::from_result).collect(),
+                };
+                let top = args.cluster_top.unwrap_or(config.search.cluster_top);
+                fmt.set_single_line(false);
+                let clusters =
+                    crate::ranker::cluster_by_dir(ordered, top, |out| Path::new(&out.file));
+                for cluster in clusters {
+                    let out = output::SearchClusterOutput {
+                        dir: cluster.dir,
+                        count: cluster.count,
+                        results: cluster.items,
+                    };
+                    if fmt.format_search_cluster(&out)? == BudgetStatus::Skipped {
+                        truncated += out.results.len();
+                    }
+                }
+                emit_budget_summary_with_page(&mut fmt, truncated, budget_limit, format, page)?;
+                return Ok(());
+            }
+
             if blend_semantic {
                 // RRF fusion mode: fetch semantic results, fuse with structural,
                 // output interleaved by descending RRF score.
                 use crate::ranker;
 
                 let rrf_k = config.search.rrf_k;
-                let semantic_results =
-                    fetch_semantic_results(&args.pattern, conn.as_ref(), suppress)?;
+                let semantic_results = fetch_semantic_results(&pattern, conn.as_ref(), suppress)?;
 
                 let fused = ranker::fuse_rrf(&results, &semantic_results, rrf_k);
 
@@ -212,9 +636,15 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                         file: fr.file.clone(),
                         line: fr.line,
                         col: fr.col,
+                        end_col: fr.end_col,
+                        start_byte: fr.start_byte,
+                        end_byte: fr.end_byte,
                         content: fr.content.clone(),
                         annotation: fr.annotation.clone(),
+                        category: None,
                         source: Some(fr.source.to_string()),
+                        patterns: Vec::new(),
+                        stamp: None,
                     };
                     if fmt.format_search_result(&out)? == BudgetStatus::Skipped {
                         truncated += 1;
@@ -226,32 +656,73 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                         // Ranked mode: classify, sort, dedup, and group with headers.
                         use crate::ranker;
 
-                        let groups = ranker::rank_and_dedup(&results, conn.as_ref(), &args.pattern);
+                        let layout = ranker::CategoryLayout::from_config(&config.search);
+                        let mut groups = layout.arrange(ranker::rank_and_dedup(
+                            &results,
+                            conn.as_ref(),
+                            &pattern,
+                        ));
+                        reorder(&mut groups);
+                        let grouped = args.group_json && format.is_structured();
 
-                        for (category, items) in &groups {
+                        let outputs: Vec<Vec<SearchOutput>> = groups
+                            .iter()
+                            .map(|group| {
+                                group
+                                    .items
+                                    .iter()
+                                    .map(|item| {
+                                        let mut out = SearchOutput::from_result(&item.result);
+                                        out.annotation = item.annotation.clone();
+                                        out.category = Some(item.category.to_string());
+                                        out
+                                    })
+                                    .collect()
+                            })
+                            .collect();
+                        // Grouped records are kept or dropped whole, so only
+                        // per-result output shares the budget by category.
+                        let ceilings = if grouped {
+                            None
+                        } else {
+                            category_budget_ceilings(
+                                &mut fmt,
+                                &groups,
+                                &outputs,
+                                &ranker::CategoryWeights::from_config(&config.budget),
+                            )?
+                        };
+
+                        for (i, (group, results)) in groups.iter().zip(outputs).enumerate() {
+                            if grouped {
+                                let out = output::SearchGroupOutput {
+                                    category: group.category.map(|c| c.to_string()),
+                                    label: group.label.clone(),
+                                    results,
+                                };
+                                if fmt.format_search_group(&out)? == BudgetStatus::Skipped {
+                                    truncated += out.results.len();
+                                }
+                                continue;
+                            }
+                            if let Some(ceilings) = &ceilings {
+                                fmt.set_budget_ceiling(Some(ceilings[i]));
+                            }
                             if !suppress {
-                                output::print_category_header(ranker::category_header(*category));
+                                output::print_category_header(&group.header());
                             }
-                            for item in items {
-                                let mut out = SearchOutput::from_search_result(
-                                    &item.result.file,
-                                    item.result.line,
-                                    item.result.col,
-                                    &item.result.content,
-                                );
-                                out.annotation = item.annotation.clone();
-                                if fmt.format_search_result(&out)? == BudgetStatus::Skipped {
+                            for out in &results {
+                                if fmt.format_search_result(out)? == BudgetStatus::Skipped {
                                     truncated += 1;
                                 }
                             }
                         }
+                        fmt.set_budget_ceiling(None);
                     }
                     SearchMode::Plain => {
                         // Plain text mode: output directly without ranking/dedup.
                         for r in &results {
-                            let out = SearchOutput::from_search_result(
-                                &r.file, r.line, r.col, &r.content,
-                            );
+                            let out = SearchOutput::from_result(r);
                             if fmt.format_search_result(&out)? == BudgetStatus::Skipped {
                                 truncated += 1;
                             }
@@ -275,12 +746,32 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                 );
             }
 
-            // Support qualified paths: `Client.get` → name="get", scope="Client".
-            let split = split_qualified_name(&args.name);
             let kind_str = args.kind.as_deref();
-            let file_str = args.file.as_deref().or(split.file_hint.as_deref());
-            let mut results =
-                if let (Some(conn), Some(scope)) = (router.conn(), split.scope_hint.as_deref()) {
+            // With --stdin-names each result group is tagged with the name it
+            // answers; a single lookup is one untagged group.
+            let mut groups: Vec<(Option<String>, Vec<Symbol>)> = if args.stdin_names {
+                let mut names = search::parse_patterns(&io::read_to_string(io::stdin())?);
+                let mut seen = std::collections::HashSet::new();
+                names.retain(|n| seen.insert(n.clone()));
+                let names: Vec<&str> = names.iter().map(String::as_str).collect();
+                router
+                    .query_symbols_named(&names, kind_str, args.file.as_deref())?
+                    .into_iter()
+                    .map(|(query, results)| (Some(query), results))
+                    .collect()
+            } else {
+                let name = args.name.as_deref().unwrap_or_default();
+                // Support qualified paths: `Client.get` → name="get", scope="Client".
+                let split = split_qualified_name(name);
+                let file_str = args.file.as_deref().or(split.file_hint.as_deref());
+                let results = if args.id {
+                    match router.conn() {
+                        Some(conn) => query_symbols_by_stable_id_db(conn, name)?,
+                        None => Vec::new(),
+                    }
+                } else if let (Some(conn), Some(scope)) =
+                    (router.conn(), split.scope_hint.as_deref())
+                {
                     query_symbols_db_with_filters(
                         conn,
                         split.name,
@@ -292,38 +783,106 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                 } else {
                     router.query_symbols_with_file(split.name, kind_str, file_str, args.exact)?
                 };
+                vec![(None, results)]
+            };
 
-            if !include_tests {
-                results.retain(|r| !crate::ranker::is_test_file(Path::new(&r.file)));
+            let tests =
+                crate::ranker::TestClassifier::new(test_filter, router.conn(), router.repo_root());
+            let generated = generated_files(router.conn());
+            let submodules = excluded_submodules(router.conn(), no_submodules);
+            let keep = |r: &Symbol| {
+                tests.keep(Path::new(&r.file))
+                    && cwd_scope.as_ref().is_none_or(|s| s.contains(&r.file))
+                    && (!no_generated || generated.generator(&r.file).is_none())
+                    && !in_submodule(&submodules, &r.file)
+                    && args
+                        .condition
+                        .as_deref()
+                        .is_none_or(|c| matches_condition(r.condition.as_deref(), c))
+                    && args
+                        .visibility
+                        .as_deref()
+                        .is_none_or(|v| r.visibility.as_deref() == Some(v))
+                    && args.modifiers.iter().all(|m| r.modifiers.contains(m))
+                    && args
+                        .generics
+                        .iter()
+                        .all(|g| matches_generic(&r.generics, g))
+            };
+            let boost = path_boost(&config, &args.boost, true)?;
+            for (_, results) in &mut groups {
+                results.retain(|r| keep(r));
+                apply_sample(results, sample, suppress, |r| Path::new(&r.file));
+                boost.apply(results, |r| Path::new(&r.file));
+                // Apply --limit after deduplication/sorting.
+                if let Some(limit) = args.limit {
+                    results.truncate(limit);
+                }
             }
 
-            if results.is_empty() {
+            if args.stdin_names {
+                let unmatched: Vec<&str> = groups
+                    .iter()
+                    .filter(|(_, results)| results.is_empty())
+                    .filter_map(|(query, _)| query.as_deref())
+                    .collect();
+                if !unmatched.is_empty() {
+                    output::print_hint(
+                        &format!("no symbols found for: {}", unmatched.join(", ")),
+                        suppress,
+                    );
+                }
+            } else if groups.iter().all(|(_, results)| results.is_empty()) {
                 output::print_hint(
                     "no symbols found; try a broader query or omit --exact",
                     suppress,
                 );
             }
 
-            // Apply --limit after deduplication/sorting.
-            if let Some(limit) = args.limit {
-                results.truncate(limit);
-            }
-
             let mut truncated = 0usize;
-            for sym in &results {
-                let out = SymbolOutput {
-                    name: sym.name.clone(),
-                    kind: sym.kind.to_string(),
-                    file: sym.file.clone(),
-                    line: sym.line,
-                    col: sym.col,
-                    end_line: sym.end_line,
-                    scope: sym.scope.clone(),
-                    signature: sym.signature.clone(),
-                    language: sym.language.clone(),
+            for (query, mut results) in groups {
+                if results.is_empty() {
+                    continue;
+                }
+                if let Some(query) = &query
+                    && !suppress
+                {
+                    output::print_category_header(&format!("-- {query} --"));
+                }
+
+                let freshness = if verify {
+                    verify_symbols(router.conn(), router.repo_root(), &mut results, suppress)
+                } else {
+                    vec![None; results.len()]
                 };
-                if fmt.format_symbol(&out)? == BudgetStatus::Skipped {
-                    truncated += 1;
+
+                for (sym, freshness) in results.iter().zip(freshness) {
+                    let out = SymbolOutput {
+                        query: query.clone(),
+                        name: sym.name.clone(),
+                        kind: sym.kind.to_string(),
+                        file: sym.file.clone(),
+                        line: sym.line,
+                        col: sym.col,
+                        end_line: sym.end_line,
+                        end_col: sym.end_col,
+                        start_byte: sym.start_byte,
+                        end_byte: sym.end_byte,
+                        scope: sym.scope.clone(),
+                        signature: sym.signature.clone(),
+                        language: sym.language.clone(),
+                        id: (!sym.language.is_empty()).then(|| sym.stable_id()),
+                        generated_by: generated.generator(&sym.file).map(str::to_string),
+                        condition: sym.condition.clone(),
+                        visibility: sym.visibility.clone(),
+                        modifiers: sym.modifiers.clone(),
+                        generics: sym.generics.clone(),
+                        freshness,
+                        stamp: None,
+                    };
+                    if fmt.format_symbol(&out)? == BudgetStatus::Skipped {
+                        truncated += 1;
+                    }
                 }
             }
             emit_budget_summary_with_page(&mut fmt, truncated, budget_limit, format, page)?;
@@ -344,73 +903,207 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                 paths.insert(0, f);
             }
 
-            let mut results = router.query_references(&args.name, &paths)?;
+            // With --patterns-file every listed name is reported under its
+            // own header; the references are looked up for all of them at
+            // once (see `QueryRouter::query_references_named`).
+            let names = match &args.patterns_file {
+                Some(file) => search::read_patterns_file(Path::new(file))?,
+                None => vec![args.name.clone().unwrap_or_default()],
+            };
+            let multi = args.patterns_file.is_some();
+            let mut bare_names: Vec<&str> = names
+                .iter()
+                .map(|query| split_qualified_name(query).name)
+                .collect();
+            bare_names.sort_unstable();
+            bare_names.dedup();
+            let found: std::collections::HashMap<String, Vec<Reference>> = router
+                .query_references_named(&bare_names, &paths)?
+                .into_iter()
+                .collect();
 
-            // Also query subclasses/implementors from type_edges.
-            let mut subclass_results = router
-                .conn()
-                .and_then(|conn| query_subclasses_db(conn, &args.name).ok())
-                .unwrap_or_default();
+            let tests =
+                crate::ranker::TestClassifier::new(test_filter, router.conn(), router.repo_root());
+            let generated = generated_files(router.conn());
+            let submodules = excluded_submodules(router.conn(), no_submodules);
+            let mut files: Vec<String> = Vec::new();
+            let mut unmatched: Vec<&str> = Vec::new();
+            let mut truncated = 0usize;
+            for query in &names {
+                // Support qualified names: `Store.open` narrows the queried
+                // definitions to scope `Store`.
+                let split = split_qualified_name(query);
+                let mut results = found.get(split.name).cloned().unwrap_or_default();
+
+                // Unless --loose, keep only references that can see the symbol.
+                if !args.loose
+                    && let Some(conn) = router.conn()
+                {
+                    let dropped = crate::resolve::Resolver::new(conn).retain_visible(
+                        split.name,
+                        split.scope_hint.as_deref(),
+                        split.file_hint.as_deref(),
+                        &mut results,
+                    )?;
+                    if dropped > 0 {
+                        output::print_hint(
+                            &format!(
+                                "{dropped} same-name references outside the symbol's imports omitted (use --loose to include)"
+                            ),
+                            suppress,
+                        );
+                    }
+                }
 
-            if !include_tests {
-                results.retain(|r| !crate::ranker::is_test_file(Path::new(&r.file)));
-                subclass_results.retain(|r| !crate::ranker::is_test_file(Path::new(&r.file)));
-            }
+                // Also query subclasses/implementors from type_edges.
+                let mut subclass_results = router
+                    .conn()
+                    .and_then(|conn| query_subclasses_db(conn, split.name).ok())
+                    .unwrap_or_default();
+
+                results.retain(|r| tests.keep(Path::new(&r.file)));
+                subclass_results.retain(|r| tests.keep(Path::new(&r.file)));
+                if let Some(scope) = &cwd_scope {
+                    results.retain(|r| scope.contains(&r.file));
+                    subclass_results.retain(|r| scope.contains(&r.file));
+                }
+                if no_generated {
+                    results.retain(|r| generated.generator(&r.file).is_none());
+                    subclass_results.retain(|r| generated.generator(&r.file).is_none());
+                }
+                results.retain(|r| !in_submodule(&submodules, &r.file));
+                subclass_results.retain(|r| !in_submodule(&submodules, &r.file));
+                let generated_groups = if args.collapse_generated {
+                    let (kept, groups) =
+                        crate::generated::collapse(results, &generated, |r| r.file.as_str());
+                    results = kept;
+                    groups
+                } else {
+                    Vec::new()
+                };
+                apply_sample(&mut results, sample, suppress, |r| Path::new(&r.file));
 
-            if results.is_empty() && subclass_results.is_empty() {
-                output::print_hint("no references found", suppress);
+                if results.is_empty() && subclass_results.is_empty() && generated_groups.is_empty()
+                {
+                    if multi {
+                        unmatched.push(query.as_str());
+                    } else {
+                        output::print_hint("no references found", suppress);
+                    }
+                    continue;
+                }
+
+                let (subclass_freshness, freshness) = if verify {
+                    verify_references(
+                        router.conn(),
+                        router.repo_root(),
+                        &mut subclass_results,
+                        &mut results,
+                        suppress,
+                    )
+                } else {
+                    (
+                        vec![None; subclass_results.len()],
+                        vec![None; results.len()],
+                    )
+                };
+
+                // Files-only mode: return just unique file paths.
+                if args.output == "files" {
+                    files.extend(results.iter().map(|r| r.file.clone()));
+                    files.extend(subclass_results.iter().map(|s| s.file.clone()));
+                } else {
+                    if multi && !suppress {
+                        output::print_category_header(&format!("-- {query} --"));
+                    }
+
+                    // Show subclasses first if present.
+                    if !subclass_results.is_empty() && !suppress {
+                        output::print_category_header("-- subclasses --");
+                    }
+                    for (sym, freshness) in subclass_results.iter().zip(subclass_freshness) {
+                        let out = RefOutput {
+                            name: sym.name.clone(),
+                            kind: "subclass".to_string(),
+                            file: sym.file.clone(),
+                            line: sym.line,
+                            col: sym.col,
+                            end_col: None,
+                            start_byte: sym.start_byte,
+                            end_byte: sym.end_byte,
+                            context: sym.signature.clone(),
+                            caller_name: None,
+                            confidence: 1.0,
+                            definition: None,
+                            generated_by: generated.generator(&sym.file).map(str::to_string),
+                            freshness,
+                            stamp: None,
+                        };
+                        if fmt.format_reference(&out)? == BudgetStatus::Skipped {
+                            truncated += 1;
+                        }
+                    }
+
+                    if !subclass_results.is_empty() && !results.is_empty() && !suppress {
+                        output::print_category_header("-- references --");
+                    }
+                    let mut resolver = router
+                        .conn()
+                        .filter(|_| args.resolve)
+                        .map(crate::resolve::Resolver::new);
+                    if args.resolve && resolver.is_none() {
+                        output::print_hint("--resolve needs an index (run `wonk init`)", suppress);
+                    }
+                    for (r, freshness) in results.iter().zip(freshness) {
+                        let definition = match resolver.as_mut() {
+                            Some(resolver) => resolver.resolve(r)?.as_ref().map(Into::into),
+                            None => None,
+                        };
+                        let out = RefOutput {
+                            name: r.name.clone(),
+                            kind: r.kind.to_string(),
+                            file: r.file.clone(),
+                            line: r.line,
+                            col: r.col,
+                            end_col: r.end_col,
+                            start_byte: r.start_byte,
+                            end_byte: r.end_byte,
+                            context: r.context.clone(),
+                            caller_name: r.caller_name.clone(),
+                            confidence: r.confidence,
+                            definition,
+                            generated_by: generated.generator(&r.file).map(str::to_string),
+                            freshness,
+                            stamp: None,
+                        };
+                        if fmt.format_reference(&out)? == BudgetStatus::Skipped {
+                            truncated += 1;
+                        }
+                    }
+                    if !generated_groups.is_empty() && !suppress {
+                        output::print_category_header("-- generated code --");
+                    }
+                    for group in &generated_groups {
+                        if fmt.format_generated_group(&group.into())? == BudgetStatus::Skipped {
+                            truncated += 1;
+                        }
+                    }
+                }
             }
 
-            // Files-only mode: return just unique file paths.
+            if !unmatched.is_empty() {
+                output::print_hint(
+                    &format!("no references found for: {}", unmatched.join(", ")),
+                    suppress,
+                );
+            }
             if args.output == "files" {
-                let mut files: Vec<String> = results.iter().map(|r| r.file.clone()).collect();
-                files.extend(subclass_results.iter().map(|s| s.file.clone()));
                 files.sort();
                 files.dedup();
                 for f in &files {
                     writeln!(fmt.writer_mut(), "{f}")?;
                 }
             } else {
-                let mut truncated = 0usize;
-
-                // Show subclasses first if present.
-                if !subclass_results.is_empty() && !suppress {
-                    output::print_category_header("-- subclasses --");
-                }
-                for sym in &subclass_results {
-                    let out = RefOutput {
-                        name: sym.name.clone(),
-                        kind: "subclass".to_string(),
-                        file: sym.file.clone(),
-                        line: sym.line,
-                        col: sym.col,
-                        context: sym.signature.clone(),
-                        caller_name: None,
-                        confidence: 1.0,
-                    };
-                    if fmt.format_reference(&out)? == BudgetStatus::Skipped {
-                        truncated += 1;
-                    }
-                }
-
-                if !subclass_results.is_empty() && !results.is_empty() && !suppress {
-                    output::print_category_header("-- references --");
-                }
-                for r in &results {
-                    let out = RefOutput {
-                        name: r.name.clone(),
-                        kind: r.kind.to_string(),
-                        file: r.file.clone(),
-                        line: r.line,
-                        col: r.col,
-                        context: r.context.clone(),
-                        caller_name: r.caller_name.clone(),
-                        confidence: r.confidence,
-                    };
-                    if fmt.format_reference(&out)? == BudgetStatus::Skipped {
-                        truncated += 1;
-                    }
-                }
                 emit_budget_summary_with_page(&mut fmt, truncated, budget_limit, format, page)?;
             }
         }
@@ -424,20 +1117,28 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                 );
             }
 
-            let results = router.query_signatures(&args.name)?;
+            let mut results = router.query_signatures(&args.name)?;
 
             if results.is_empty() {
                 output::print_hint("no signatures found", suppress);
             }
 
+            let freshness = if verify {
+                verify_symbols(router.conn(), router.repo_root(), &mut results, suppress)
+            } else {
+                vec![None; results.len()]
+            };
+
             let mut truncated = 0usize;
-            for sym in &results {
+            for (sym, freshness) in results.iter().zip(freshness) {
                 let out = SignatureOutput {
                     name: sym.name.clone(),
                     file: sym.file.clone(),
                     line: sym.line,
                     signature: sym.signature.clone(),
                     language: sym.language.clone(),
+                    freshness,
+                    stamp: None,
                 };
                 if fmt.format_signature(&out)? == BudgetStatus::Skipped {
                     truncated += 1;
@@ -469,6 +1170,7 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                 let out = output::DepOutput {
                     file: args.file.clone(),
                     depends_on: dep.clone(),
+                    generated_by: None,
                 };
                 if fmt.format_dep(&out)? == BudgetStatus::Skipped {
                     truncated += 1;
@@ -489,9 +1191,23 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                 );
             }
 
-            let results = router.query_rdeps(&args.file)?;
+            let mut results = router.query_rdeps(&args.file, args.fuzzy)?;
+            let generated = generated_files(router.conn());
+            if no_generated {
+                results.retain(|f| generated.generator(f).is_none());
+            }
+            let submodules = excluded_submodules(router.conn(), no_submodules);
+            results.retain(|f| !in_submodule(&submodules, f));
+            let generated_groups = if args.collapse_generated {
+                let (kept, groups) =
+                    crate::generated::collapse(results, &generated, String::as_str);
+                results = kept;
+                groups
+            } else {
+                Vec::new()
+            };
 
-            if results.is_empty() {
+            if results.is_empty() && generated_groups.is_empty() {
                 output::print_hint("no reverse dependencies found", suppress);
             }
 
@@ -500,20 +1216,54 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                 let out = output::DepOutput {
                     file: source.clone(),
                     depends_on: args.file.clone(),
+                    generated_by: generated.generator(source).map(str::to_string),
                 };
                 if fmt.format_dep(&out)? == BudgetStatus::Skipped {
                     truncated += 1;
                 }
             }
+            for group in &generated_groups {
+                if fmt.format_generated_group(&group.into())? == BudgetStatus::Skipped {
+                    truncated += 1;
+                }
+            }
             emit_budget_summary_with_page(&mut fmt, truncated, budget_limit, format, page)?;
         }
         Command::Init(args) => {
-            let repo_root = std::env::current_dir()?;
-            let repo_root = db::find_repo_root(&repo_root)?;
+            if args.skip_space_check {
+                crate::diskspace::skip_checks();
+            }
+            if let Some(archive) = &args.archive {
+                // The archive is indexed as if it were checked out in the
+                // current directory, so queries run from there find it.
+                let root = std::fs::canonicalize(std::env::current_dir()?)?;
+                if root.join(".git").exists() {
+                    anyhow::bail!(
+                        "{} is a git working tree; run `wonk init --archive` from an empty directory so the archive's index does not replace the repository's",
+                        root.display()
+                    );
+                }
+                let progress =
+                    Progress::new("Indexing", "Indexed", progress::detect_mode(suppress));
+                let stats = pipeline::build_index_from_archive(
+                    Path::new(archive),
+                    args.strip_components,
+                    &root,
+                    &progress,
+                )?;
+                progress.finish(&stats);
+                index_build_cut_short(suppress);
+                return Ok(());
+            }
+            let repo_root = match &args.root {
+                Some(dir) => mark_repo_root(Path::new(dir))?,
+                None => db::find_repo_root(&std::env::current_dir()?)?,
+            };
             let progress_mode = progress::detect_mode(suppress);
 
             // Check if we can do an incremental update instead of a full rebuild.
             let index_path = db::index_path_for(&repo_root, args.local)?;
+            pipeline::ensure_not_archive(&index_path)?;
             let needs_full_rebuild = !index_path.exists()
                 || db::read_meta(&index_path)
                     .ok()
@@ -521,10 +1271,26 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                     .as_deref()
                     != Some(env!("CARGO_PKG_VERSION"));
 
-            if needs_full_rebuild {
+            if needs_full_rebuild || args.profile {
+                note_local_fallback(args.local, suppress);
                 let progress = Progress::new("Indexing", "Indexed", progress_mode);
-                let stats = pipeline::build_index_with_progress(&repo_root, args.local, &progress)?;
+                let (stats, profile) =
+                    pipeline::build_index_profiled(&repo_root, args.local, &progress)?;
                 progress.finish(&stats);
+                if args.profile {
+                    fmt.set_single_line(false);
+                    fmt.format_index_profile(&IndexProfileOutput::from_profile(
+                        &profile,
+                        PROFILE_SLOWEST_FILES,
+                    ))?;
+                    output::print_hint(
+                        "exclude slow generated or vendored files with `[ignore].patterns` in .wonk/config.toml",
+                        suppress,
+                    );
+                }
+                if index_build_cut_short(suppress) {
+                    return Ok(());
+                }
 
                 // Full embedding build.
                 let index_path = db::index_path_for(&repo_root, args.local)?;
@@ -550,6 +1316,9 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                         stats.elapsed.as_secs_f64(),
                     );
                 }
+                if index_build_cut_short(suppress) {
+                    return Ok(());
+                }
 
                 // Incremental embedding update.
                 let index_path = db::index_path_for(&repo_root, args.local)?;
@@ -573,8 +1342,12 @@ pub fn dispatch(cli: Cli) -> Result<()> {
             }
         }
         Command::Update(args) => {
-            let repo_root = std::env::current_dir()?;
-            let repo_root = db::find_repo_root(&repo_root)?;
+            if args.skip_space_check {
+                crate::diskspace::skip_checks();
+            }
+            let cwd = std::env::current_dir()?;
+            let repo_root = db::find_repo_root(&cwd)?;
+            let paths = repo_relative_paths(&repo_root, &cwd, &args.paths)?;
             let progress_mode = progress::detect_mode(suppress);
 
             // Decide whether we need a full rebuild or can do incremental.
@@ -587,10 +1360,19 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                     .as_deref()
                     != Some(env!("CARGO_PKG_VERSION"));
 
+            if needs_full_rebuild && !paths.is_empty() {
+                output::print_hint(
+                    "the index must be rebuilt first, so every file is re-indexed",
+                    suppress,
+                );
+            }
             if needs_full_rebuild {
                 let progress = Progress::new("Re-indexing", "Re-indexed", progress_mode);
                 let stats = pipeline::rebuild_index_with_progress(&repo_root, false, &progress)?;
                 progress.finish(&stats);
+                if index_build_cut_short(suppress) {
+                    return Ok(());
+                }
 
                 if !args.skip_embed {
                     // Full embedding rebuild.
@@ -608,8 +1390,8 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                     }
                 }
             } else {
-                // Incremental structural update.
-                let stats = pipeline::incremental_update(&repo_root, false)?;
+                // Incremental structural update, of `paths` when given.
+                let stats = pipeline::update_paths(&repo_root, false, &paths)?;
                 if !suppress {
                     eprintln!(
                         "Updated index ({} files, {} symbols) in {:.1}s",
@@ -618,6 +1400,9 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                         stats.elapsed.as_secs_f64(),
                     );
                 }
+                if index_build_cut_short(suppress) {
+                    return Ok(());
+                }
 
                 if !args.skip_embed {
                     // Incremental embedding update (graceful skip if Ollama unavailable).
@@ -791,7 +1576,7 @@ pub fn dispatch(cli: Cli) -> Result<()> {
 
             emit_budget_summary_with_page(&mut fmt, truncated, budget_limit, format, page)?;
         }
-        Command::Status => {
+        Command::Status(args) => {
             let conn = std::env::current_dir()
                 .ok()
                 .and_then(|cwd| db::find_repo_root(&cwd).ok())
@@ -800,7 +1585,7 @@ pub fn dispatch(cli: Cli) -> Result<()> {
 
             let info = query_status_info(conn.as_ref());
 
-            if format.is_structured() {
+            if args.json || format.is_structured() {
                 let json =
                     serde_json::to_string_pretty(&serde_json::to_value(&info).unwrap_or_default())
                         .unwrap_or_default();
@@ -809,6 +1594,243 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                 eprintln!("{}", format_status_info(&info));
             }
         }
+        Command::WhichIndex => {
+            let repo_root = db::find_repo_root(&std::env::current_dir()?)?;
+            fmt.set_single_line(false);
+            fmt.format_which_index(&crate::provenance::inspect(&repo_root))?;
+        }
+        Command::Langs => {
+            let conn = match std::env::current_dir()
+                .ok()
+                .and_then(|cwd| db::find_repo_root(&cwd).ok())
+                .and_then(|root| db::find_existing_index(&root))
+                .and_then(|path| db::open(&path).ok())
+            {
+                Some(c) => c,
+                None => {
+                    output::print_error("no index found; run `wonk init` to build the index");
+                    return Ok(());
+                }
+            };
+
+            let stats = crate::summary::language_stats(&conn)?;
+            if stats.is_empty() {
+                output::print_hint("no indexed languages", suppress);
+            }
+
+            let mut truncated = 0usize;
+            for row in LangStatsOutput::from_stats(&stats) {
+                if fmt.format_lang_stats(&row)? == BudgetStatus::Skipped {
+                    truncated += 1;
+                }
+            }
+            emit_budget_summary_with_page(&mut fmt, truncated, budget_limit, format, page)?;
+        }
+        Command::Examples(args) => {
+            let recipes: Vec<RecipeOutput> = match args.topic.as_deref() {
+                None => crate::examples::RECIPES
+                    .iter()
+                    .map(|r| RecipeOutput::from_recipe(r, false))
+                    .collect(),
+                Some(topic) => match crate::examples::find_recipe(topic) {
+                    Some(recipe) => vec![RecipeOutput::from_recipe(recipe, true)],
+                    None => {
+                        let topics: Vec<&str> =
+                            crate::examples::RECIPES.iter().map(|r| r.topic).collect();
+                        output::print_error(&format!(
+                            "unknown example topic '{topic}' (topics: {})",
+                            topics.join(", ")
+                        ));
+                        return Ok(());
+                    }
+                },
+            };
+
+            // Recipes are read, not filtered: keep them multi-line when piped.
+            fmt.set_single_line(false);
+            let mut truncated = 0usize;
+            for recipe in &recipes {
+                if fmt.format_recipe(recipe)? == BudgetStatus::Skipped {
+                    truncated += 1;
+                }
+            }
+            emit_budget_summary_with_page(&mut fmt, truncated, budget_limit, format, page)?;
+        }
+        Command::Parse(args) => {
+            let (lang, path, source) =
+                read_source_arg(&args.file, args.lang.as_deref(), args.path.as_deref())?;
+            let extraction = pipeline::extract(lang, &path, &source, config.ruby.rails)
+                .ok_or_else(|| anyhow::anyhow!("{} grammar is unavailable", lang.name()))?;
+            fmt.set_single_line(false);
+            fmt.format_parse(&ParseOutput::new(lang.name(), &path, &extraction))?;
+        }
+        Command::TreeDump(args) => {
+            let (lang, path, source) =
+                read_source_arg(&args.file, args.lang.as_deref(), args.path.as_deref())?;
+            let mut out = output::TreeDumpOutput {
+                language: lang.name().to_string(),
+                path,
+                nodes: Vec::new(),
+                sexp: None,
+            };
+            if args.sexp {
+                out.sexp = Some(crate::treedump::sexp(lang, &source, &args.kind)?);
+            } else {
+                let opts = crate::treedump::DumpOptions {
+                    kinds: args.kind,
+                    all: args.all,
+                    max_depth: args.depth,
+                };
+                out.nodes = crate::treedump::dump(lang, &source, &opts)?;
+            }
+            if out.nodes.is_empty() && out.sexp.as_ref().is_none_or(Vec::is_empty) {
+                output::print_hint("no nodes of the requested kind", suppress);
+            }
+            fmt.set_single_line(false);
+            fmt.format_tree_dump(&out)?;
+        }
+        Command::Schema(args) => {
+            // The schema is JSON whatever the output format.
+            fmt.set_single_line(false);
+            if args.list {
+                for name in crate::schema::record_names() {
+                    writeln!(fmt.writer_mut(), "{name}")?;
+                }
+                return Ok(());
+            }
+            let schema = match &args.record {
+                Some(name) => crate::schema::record_schema(name).ok_or_else(|| {
+                    let names: Vec<&str> = crate::schema::record_names().collect();
+                    anyhow::anyhow!(
+                        "unknown record type '{name}' (records: {})",
+                        names.join(", ")
+                    )
+                })?,
+                None => crate::schema::bundle(),
+            };
+            writeln!(
+                fmt.writer_mut(),
+                "{}",
+                serde_json::to_string_pretty(&schema)?
+            )?;
+        }
+        Command::Diag(args) => {
+            let reports = crate::crash::crash_dir()
+                .map(|dir| crate::crash::list_reports(&dir))
+                .unwrap_or_default();
+            let included = if args.all { reports.len() } else { 1 };
+            let diag = output::DiagOutput {
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                platform: crate::crash::platform(),
+                index: std::env::current_dir()
+                    .ok()
+                    .and_then(|cwd| crate::crash::RepoStats::collect(&cwd)),
+                crash_count: reports.len(),
+                crash_reports: reports
+                    .iter()
+                    .take(included)
+                    .filter_map(|path| {
+                        Some(output::CrashReportOutput {
+                            path: path.display().to_string(),
+                            contents: std::fs::read_to_string(path).ok()?,
+                        })
+                    })
+                    .collect(),
+            };
+
+            fmt.set_single_line(false);
+            match &args.output {
+                Some(file) => {
+                    let writer = std::fs::File::create(file)
+                        .map_err(|e| anyhow::anyhow!("creating {file}: {e}"))?;
+                    Formatter::new(writer, format, false).format_diag(&diag)?;
+                    if !suppress {
+                        eprintln!(
+                            "Wrote diagnostics to {file}; attach it to an issue at {}",
+                            crate::crash::ISSUES_URL
+                        );
+                    }
+                }
+                None => {
+                    fmt.format_diag(&diag)?;
+                }
+            }
+        }
+        #[cfg(feature = "self-update")]
+        Command::SelfCmd(args) => match args.command {
+            crate::cli::SelfCommand::Update(update_args) => {
+                use crate::self_update::{self, Channel};
+
+                let channel_name = update_args
+                    .channel
+                    .unwrap_or_else(|| config.update.channel.clone());
+                let channel: Channel = channel_name.parse().map_err(|e| anyhow::anyhow!("{e}"))?;
+                let check = match self_update::check(channel) {
+                    Ok(check) => check,
+                    // `--check` runs from shell prompts and CI: being offline
+                    // is not a failure.
+                    Err(e) if update_args.check => {
+                        output::print_hint(
+                            &format!("could not check for updates: {e:#}"),
+                            suppress,
+                        );
+                        return Ok(());
+                    }
+                    Err(e) => return Err(e),
+                };
+
+                let mut updated = false;
+                if !update_args.check && check.update_available() {
+                    let exe = std::env::current_exe()?.canonicalize()?;
+                    self_update::install(&check, &exe)?;
+                    updated = true;
+                }
+                fmt.format_self_update(&output::SelfUpdateOutput {
+                    current_version: check.current.to_string(),
+                    latest_version: check.latest.to_string(),
+                    channel: channel_name,
+                    update_available: check.update_available(),
+                    updated,
+                })?;
+            }
+        },
+        Command::GenDocs(args) => {
+            let cmd = crate::cli::command();
+            if let Some(dir) = &args.man {
+                let pages = crate::gendocs::write_man_pages(&cmd, Path::new(dir))?;
+                if !suppress {
+                    eprintln!("Wrote {} man pages to {dir}", pages.len());
+                }
+            }
+            if let Some(dir) = &args.markdown {
+                let pages = crate::gendocs::write_markdown(&cmd, Path::new(dir))?;
+                if !suppress {
+                    eprintln!("Wrote {} markdown pages to {dir}", pages.len());
+                }
+            }
+        }
+        Command::Golden(args) => {
+            let results = crate::golden::run(Path::new(&args.dir), args.bless)?;
+            let out = fmt.writer_mut();
+            for case in &results {
+                writeln!(out, "{} {}", case.outcome.label(), case.fixture)?;
+                if let crate::golden::Outcome::Failed(diff) = &case.outcome {
+                    for line in diff.lines() {
+                        writeln!(out, "    {line}")?;
+                    }
+                }
+            }
+            let failed = results.iter().filter(|c| c.outcome.is_failure()).count();
+            if failed > 0 {
+                anyhow::bail!(
+                    "{failed} of {} golden fixtures do not match; rerun with --bless to accept the current output",
+                    results.len()
+                );
+            }
+            if !suppress {
+                eprintln!("{} golden fixtures match", results.len());
+            }
+        }
         Command::Daemon(args) => match args.command {
             DaemonCommand::Start => {
                 let repo_root = db::find_repo_root(&std::env::current_dir()?)?;
@@ -893,12 +1915,76 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                             serde_json::Value::String(last_activity.clone()),
                         );
                     }
+                    if let Some(files) = info
+                        .last_reindex_files
+                        .as_ref()
+                        .and_then(|f| f.parse::<u64>().ok())
+                    {
+                        status.insert(
+                            "last_reindex_files".to_string(),
+                            serde_json::Value::Number(files.into()),
+                        );
+                    }
+                    if let Some(events) = info
+                        .events_processed
+                        .as_ref()
+                        .and_then(|n| n.parse::<u64>().ok())
+                    {
+                        status.insert(
+                            "events_processed".to_string(),
+                            serde_json::Value::Number(events.into()),
+                        );
+                    }
+                    if let Some(age) = crate::daemon::heartbeat_age(&info) {
+                        status.insert(
+                            "heartbeat_age".to_string(),
+                            serde_json::Value::Number(age.into()),
+                        );
+                        status.insert(
+                            "stuck".to_string(),
+                            serde_json::Value::Bool(
+                                daemon_pid.is_some() && crate::daemon::is_stuck(&info),
+                            ),
+                        );
+                    }
+                    if let Some(ref last_event) = info.last_event {
+                        status.insert(
+                            "last_event".to_string(),
+                            serde_json::Value::String(last_event.clone()),
+                        );
+                    }
+                    if let Some(queued) = info
+                        .files_queued
+                        .as_ref()
+                        .and_then(|n| n.parse::<u64>().ok())
+                    {
+                        status.insert(
+                            "files_queued".to_string(),
+                            serde_json::Value::Number(queued.into()),
+                        );
+                    }
                     if let Some(ref last_error) = info.last_error {
                         status.insert(
                             "last_error".to_string(),
                             serde_json::Value::String(last_error.clone()),
                         );
                     }
+                    if let Some(ref last_flood) = info.last_flood {
+                        status.insert(
+                            "last_flood".to_string(),
+                            serde_json::Value::String(last_flood.clone()),
+                        );
+                    }
+                    if let Some(files) = info
+                        .last_flood_files
+                        .as_ref()
+                        .and_then(|f| f.parse::<u64>().ok())
+                    {
+                        status.insert(
+                            "last_flood_files".to_string(),
+                            serde_json::Value::Number(files.into()),
+                        );
+                    }
                     if let Some(ref ebr) = info.embedding_build_requested {
                         status.insert(
                             "embedding_build_requested".to_string(),
@@ -922,7 +2008,39 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                             .ok()
                             .map(|e| format!("{} ago", crate::daemon::format_uptime(Some(e))))
                             .unwrap_or_else(|| format!("epoch {last_activity}"));
-                        eprintln!("Last activity: {display}");
+                        match info.last_reindex_files {
+                            Some(ref files) => eprintln!("Last reindex: {files} files, {display}"),
+                            None => eprintln!("Last activity: {display}"),
+                        }
+                    }
+                    if let Some(ref events) = info.events_processed {
+                        eprintln!("Events processed: {events}");
+                    }
+                    if let Some(age) = crate::daemon::heartbeat_age(&info) {
+                        eprintln!("Heartbeat: {age}s ago");
+                    }
+                    if let Some(ref last_event) = info.last_event {
+                        eprintln!("Last event: {last_event}");
+                    }
+                    if let Some(ref queued) = info.files_queued {
+                        eprintln!("Queue depth: {queued}");
+                    }
+                    if crate::daemon::is_stuck(&info) {
+                        eprintln!(
+                            "State: stuck (no heartbeat); run `wonk daemon start` to replace it"
+                        );
+                    } else if info.state.as_deref() == Some("catching_up") {
+                        eprintln!("State: catching up on a burst of file changes");
+                    }
+                    if let (Some(at), Some(files)) = (&info.last_flood, &info.last_flood_files) {
+                        let ago = at
+                            .parse::<i64>()
+                            .ok()
+                            .map(|e| format!("{} ago", crate::daemon::format_uptime(Some(e))))
+                            .unwrap_or_else(|| format!("epoch {at}"));
+                        eprintln!(
+                            "Last flood: {files} changed files {ago}, handled by one incremental update"
+                        );
                     }
                     if let Some(ref last_error) = info.last_error {
                         eprintln!("Last error: {last_error}");
@@ -946,12 +2064,171 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                 }
             }
         },
+        Command::Budget(args) => {
+            let Some(path) = cli.budget_file.as_deref() else {
+                anyhow::bail!(
+                    "no session budget file; pass --budget-file PATH or set WONK_BUDGET_FILE"
+                );
+            };
+            let path = Path::new(path);
+            let session = match args.command {
+                BudgetCommand::Status => crate::budget::SessionBudget::load(path)?,
+                BudgetCommand::Reset { limit } => crate::budget::SessionBudget::reset(path, limit)?,
+            };
+            if format.is_structured() {
+                let json = serde_json::json!({
+                    "budget_file": path.display().to_string(),
+                    "limit": session.limit,
+                    "used": session.used,
+                    "remaining": session.remaining(),
+                    "commands": session.commands,
+                });
+                writeln!(fmt.writer_mut(), "{}", serde_json::to_string_pretty(&json)?)?;
+            } else {
+                eprintln!(
+                    "Session budget: {} of {} tokens used, {} left ({} commands)",
+                    session.used,
+                    session.limit,
+                    session.remaining(),
+                    session.commands
+                );
+                eprintln!("File: {}", path.display());
+            }
+        }
         Command::Repos(args) => match args.command {
             ReposCommand::List => {
                 output::print_hint("repos list: not yet implemented", suppress);
             }
-            ReposCommand::Clean => {
-                output::print_hint("repos clean: not yet implemented", suppress);
+            ReposCommand::Clean(args) => {
+                let repos_dir = db::central_repos_dir()?;
+                let cutoff = args
+                    .older_than
+                    .and_then(|age| std::time::SystemTime::now().checked_sub(age));
+                let verb = if args.dry_run {
+                    "would remove"
+                } else {
+                    "removed"
+                };
+                let mut removed = Vec::new();
+                let mut unknown = Vec::new();
+                let mut reclaimed = 0;
+                for index in db::list_central_indexes(&repos_dir)? {
+                    // Without meta.json the index may belong to a live
+                    // repository, so it is only removed when asked for.
+                    let reason = if args.all {
+                        "all"
+                    } else if index.is_unknown() {
+                        if !args.unknown {
+                            if !format.is_structured() {
+                                eprintln!("kept {} (unknown repository)", index.dir.display());
+                            }
+                            unknown.push(index.dir.display().to_string());
+                            continue;
+                        }
+                        "unknown"
+                    } else if index.is_orphaned() {
+                        "orphaned"
+                    } else if cutoff.is_some_and(|c| index.modified.is_none_or(|m| m < c)) {
+                        "stale"
+                    } else {
+                        continue;
+                    };
+                    if !args.dry_run
+                        && let Err(e) = db::remove_central_index(&repos_dir, &index)
+                    {
+                        output::print_warning(&format!("{e:#}"));
+                        continue;
+                    }
+                    let repo = index
+                        .repo_path
+                        .as_ref()
+                        .map_or("unknown repository".into(), |p| p.display().to_string());
+                    if !format.is_structured() {
+                        eprintln!(
+                            "{verb} {} ({repo}, {reason}, {:.1} MiB)",
+                            index.dir.display(),
+                            index.size as f64 / (1024.0 * 1024.0)
+                        );
+                    }
+                    reclaimed += index.size;
+                    removed.push(serde_json::json!({
+                        "dir": index.dir.display().to_string(),
+                        "repo_path": index.repo_path.map(|p| p.display().to_string()),
+                        "reason": reason,
+                        "bytes": index.size,
+                    }));
+                }
+                if !unknown.is_empty() {
+                    output::print_hint(
+                        &format!(
+                            "{} index(es) have no readable meta.json; use --unknown to remove them",
+                            unknown.len()
+                        ),
+                        suppress,
+                    );
+                }
+                if format.is_structured() {
+                    let json = serde_json::json!({
+                        "dry_run": args.dry_run,
+                        "removed": removed,
+                        "reclaimed_bytes": reclaimed,
+                        "unknown": unknown,
+                    });
+                    writeln!(fmt.writer_mut(), "{}", serde_json::to_string_pretty(&json)?)?;
+                } else if removed.is_empty() {
+                    output::print_hint("no central indexes to remove", suppress);
+                } else {
+                    let (count, mib) = (removed.len(), reclaimed as f64 / (1024.0 * 1024.0));
+                    if args.dry_run {
+                        eprintln!("Would remove {count} index(es), reclaiming {mib:.1} MiB");
+                    } else {
+                        eprintln!("Removed {count} index(es), reclaimed {mib:.1} MiB");
+                    }
+                }
+            }
+        },
+        Command::Global(args) => match args.command {
+            GlobalCommand::Sym(args) => {
+                let repos: Vec<_> = crate::global::discover_repos(&db::central_repos_dir()?)
+                    .into_iter()
+                    .filter(|r| args.repos.is_empty() || args.repos.contains(&r.name))
+                    .collect();
+                if repos.is_empty() {
+                    output::print_hint(
+                        "no repositories indexed in the central store; run `wonk init` in each",
+                        suppress,
+                    );
+                    return Ok(());
+                }
+
+                let (mut hits, failed) = crate::global::search_symbols(
+                    &repos,
+                    &args.name,
+                    args.kind.as_deref(),
+                    args.exact,
+                );
+                for (repo, err) in failed {
+                    output::print_warning(&format!("skipping {repo}: {err}"));
+                }
+                if let Some(limit) = args.limit {
+                    hits.truncate(limit);
+                }
+                if hits.is_empty() {
+                    output::print_hint(
+                        "no symbols found; try a broader query or omit --exact",
+                        suppress,
+                    );
+                }
+
+                let mut truncated = 0usize;
+                for hit in &hits {
+                    if fmt.format_global_symbol(&GlobalSymbolOutput::from(hit))?
+                        == BudgetStatus::Skipped
+                    {
+                        truncated += 1;
+                    }
+                }
+                emit_budget_summary_with_page(&mut fmt, truncated, budget_limit, format, page)?;
             }
         },
         Command::Mcp(args) => match args.command {
@@ -1427,8 +2704,8 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                         let source_lines: Vec<&str> = sr.source.lines().collect();
                         if !source_lines.is_empty() {
                             let avg_chars = sr.source.len() / source_lines.len();
-                            if avg_chars > 0 {
-                                let max_lines = (remaining_chars / avg_chars).clamp(
+                            if let Some(lines_fit) = remaining_chars.checked_div(avg_chars) {
+                                let max_lines = lines_fit.clamp(
                                     ShowOutput::MIN_SOURCE_LINES,
                                     ShowOutput::MAX_SOURCE_LINES,
                                 );
@@ -1531,16 +2808,12 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                 output::print_hint("no callees found", suppress);
             }
 
+            let definitions = crate::callgraph::resolve_callees(&conn, &results)?;
             let mut truncated = 0usize;
-            for cr in &results {
+            for (cr, def) in results.iter().zip(&definitions) {
                 let out = CalleeOutput {
-                    callee_name: cr.callee_name.clone(),
-                    file: cr.file.clone(),
-                    line: cr.line,
-                    context: cr.context.clone(),
-                    depth: cr.depth,
-                    source_file: cr.source_file.clone(),
-                    confidence: cr.confidence,
+                    definition: def.as_ref().map(DefinitionOutput::from),
+                    ..CalleeOutput::from(cr)
                 };
 
                 if fmt.format_callee(&out)? == BudgetStatus::Skipped {
@@ -1584,6 +2857,38 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                 }
             }
         }
+        Command::Describe(args) => {
+            let repo_root = db::find_repo_root(&std::env::current_dir()?)?;
+            let Some(conn) =
+                db::find_existing_index(&repo_root).and_then(|path| db::open(&path).ok())
+            else {
+                anyhow::bail!("no index found; run `wonk init` to build the index");
+            };
+            let Some(out) = crate::describe::describe_file(&conn, &args.file, args.limit)? else {
+                anyhow::bail!(
+                    "{} is not in the index (paths are relative to the repository root)",
+                    args.file
+                );
+            };
+            fmt.set_single_line(false);
+            fmt.format_describe(&out)?;
+        }
+        Command::Near(args) => {
+            let (file, line) = crate::near::parse_location(&args.location)?;
+            let repo_root = db::find_repo_root(&std::env::current_dir()?)?;
+            let Some(conn) =
+                db::find_existing_index(&repo_root).and_then(|path| db::open(&path).ok())
+            else {
+                anyhow::bail!("no index found; run `wonk init` to build the index");
+            };
+            let Some(out) = crate::near::neighborhood(&conn, &file, line, args.radius)? else {
+                anyhow::bail!(
+                    "{file} is not in the index (paths are relative to the repository root)"
+                );
+            };
+            fmt.set_single_line(false);
+            fmt.format_near(&out)?;
+        }
         Command::Summary(args) => {
             let repo_root = match std::env::current_dir()
                 .ok()
@@ -1613,6 +2918,27 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                 }
             };
 
+            if args.files {
+                let listing = crate::summary::list_files(&conn, &args.path, detail)?;
+                if listing.is_empty() {
+                    output::print_hint(&format!("no indexed files under {}", args.path), suppress);
+                }
+                let mut truncated = 0usize;
+                for file in &listing {
+                    let out = file.into();
+                    let status = if detail == crate::types::DetailLevel::Rich {
+                        fmt.format_file_symbols(&out)?
+                    } else {
+                        fmt.format_file_listing(&out)?
+                    };
+                    if status == BudgetStatus::Skipped {
+                        truncated += 1;
+                    }
+                }
+                emit_budget_summary_with_page(&mut fmt, truncated, budget_limit, format, page)?;
+                return Ok(());
+            }
+
             let depth = if args.recursive {
                 None // unlimited
             } else {
@@ -1742,15 +3068,163 @@ pub fn dispatch(cli: Cli) -> Result<()> {
                 output::print_hint("no affected symbols found", suppress);
             }
 
-            let out = BlastOutput::from(&result);
-            fmt.format_blast(&out)?;
-        }
-        Command::Changes(args) => {
-            dispatch_changes(args, &mut fmt, suppress)?;
+            let out = BlastOutput::from(&result);
+            fmt.format_blast(&out)?;
+        }
+        Command::Changes(args) => {
+            dispatch_changes(args, &mut fmt, suppress)?;
+        }
+        Command::Find(args) => {
+            let router = QueryRouter::new(None, false);
+
+            if !router.has_index() {
+                output::print_hint(
+                    "no index found; falling back to grep (run `wonk init` for faster results)",
+                    suppress,
+                );
+            }
+
+            let name = args.name.as_str();
+            let definitions = router.query_symbols(name, None, true)?;
+            let signatures = router.query_signatures(name)?;
+            let usages = router.query_references(name, &[])?;
+            // Whole-identifier matches anywhere, with paths relative to the
+            // root like the index's.
+            let root = router.repo_root().to_path_buf();
+            let mut mentions = search::text_search(
+                &reference_grep_pattern(name),
+                true,
+                false,
+                &[root.to_string_lossy().into_owned()],
+            )?;
+            retain_whole_identifiers(&mut mentions);
+            for mention in &mut mentions {
+                if let Ok(rel) = mention.file.strip_prefix(&root) {
+                    mention.file = rel.to_path_buf();
+                }
+            }
+            let mut hits = crate::find::merge(definitions, signatures, usages, mentions);
+
+            let tests =
+                crate::ranker::TestClassifier::new(test_filter, router.conn(), router.repo_root());
+            let generated = generated_files(router.conn());
+            let submodules = excluded_submodules(router.conn(), no_submodules);
+            hits.retain(|h| {
+                tests.keep(Path::new(&h.file))
+                    && (!no_generated || generated.generator(&h.file).is_none())
+                    && !in_submodule(&submodules, &h.file)
+            });
+            if let Some(limit) = args.limit {
+                let mut counts = std::collections::HashMap::new();
+                hits.retain(|h| {
+                    let count = counts.entry(h.section).or_insert(0);
+                    *count += 1;
+                    *count <= limit
+                });
+            }
+            if hits.is_empty() {
+                output::print_hint(&format!("nothing found for {name}"), suppress);
+            }
+
+            let mut truncated = 0usize;
+            let mut section = None;
+            for hit in &hits {
+                if section != Some(hit.section) {
+                    section = Some(hit.section);
+                    if !suppress {
+                        output::print_category_header(&format!("-- {} --", hit.section.heading()));
+                    }
+                }
+                if fmt.format_find(&FindOutput::from(hit))? == BudgetStatus::Skipped {
+                    truncated += 1;
+                }
+            }
+            emit_budget_summary_with_page(&mut fmt, truncated, budget_limit, format, page)?;
         }
         Command::Context(args) => {
             dispatch_context(args, &mut fmt, suppress, include_tests)?;
         }
+        Command::Report(args) => {
+            let truncated = dispatch_report(args, &mut fmt, suppress, include_tests)?;
+            emit_budget_summary_with_page(&mut fmt, truncated, budget_limit, format, page)?;
+        }
+        Command::Snapshot(args) => {
+            let repo_root = db::find_repo_root(&std::env::current_dir()?)?;
+            let path = crate::snapshot::path_for(&repo_root, &args.name)?;
+            let entries = crate::snapshot::run_query(&repo_root, &args.query)?;
+            let count = entries.len();
+            crate::snapshot::save(
+                &path,
+                &crate::snapshot::Snapshot::new(&args.name, &args.query, entries),
+            )?;
+            fmt.format_snapshot(&output::SnapshotOutput {
+                path: format!("{}/{}.json", crate::snapshot::DIR, args.name),
+                name: args.name,
+                query: args.query,
+                entries: count,
+            })?;
+        }
+        Command::Gate(args) => {
+            let repo_root = db::find_repo_root(&std::env::current_dir()?)?;
+            // Load again without the silent fallback: a typo in [gate] must
+            // fail CI rather than pass with no checks.
+            let gate_config = match &cli.config_profile {
+                Some(name) => crate::config::Config::load_with_profile(Some(&repo_root), name)?,
+                None => crate::config::Config::load(Some(&repo_root))?,
+            }
+            .gate;
+            let checks = crate::gate::run(&repo_root, &gate_config, args.update_baseline)?;
+            fmt.set_single_line(false);
+            for check in &checks {
+                fmt.format_gate_check(check)?;
+            }
+            let failed = checks.iter().filter(|c| !c.passed).count();
+            if failed > 0 {
+                anyhow::bail!("{failed} of {} gate checks failed", checks.len());
+            }
+            if !suppress {
+                eprintln!("{} gate checks passed", checks.len());
+            }
+        }
+        Command::Compare(args) => {
+            let repo_root = db::find_repo_root(&std::env::current_dir()?)?;
+            let path = crate::snapshot::path_for(&repo_root, &args.name)?;
+            let snapshot = crate::snapshot::load(&path)?;
+            let current = crate::snapshot::run_query(&repo_root, &snapshot.query)?;
+            let mut ignore = args.ignore.clone();
+            if args.ignore_positions {
+                ignore.extend(
+                    crate::snapshot::POSITION_FIELDS
+                        .iter()
+                        .map(|f| f.to_string()),
+                );
+            }
+            let diff = crate::snapshot::diff(&snapshot.entries, &current, &ignore);
+
+            fmt.set_single_line(false);
+            fmt.format_compare(&output::CompareOutput {
+                name: snapshot.name.clone(),
+                query: snapshot.query.clone(),
+                before: snapshot.entries.len(),
+                after: current.len(),
+                unchanged: diff.unchanged,
+                added: diff.added.clone(),
+                removed: diff.removed,
+            })?;
+
+            if args.update {
+                let updated =
+                    crate::snapshot::Snapshot::new(&snapshot.name, &snapshot.query, current);
+                crate::snapshot::save(&path, &updated)?;
+            }
+            if args.check && !diff.added.is_empty() {
+                anyhow::bail!(
+                    "{} entries added since snapshot {:?}",
+                    diff.added.len(),
+                    args.name
+                );
+            }
+        }
     }
 
     // In single-line (piped) mode, emit a final newline so the output is
@@ -1766,6 +3240,208 @@ pub fn dispatch(cli: Cli) -> Result<()> {
 // `wonk changes` dispatch (TASK-072)
 // ---------------------------------------------------------------------------
 
+/// Cut `results` to a `--sample` of `sample` results spread across
+/// directories and files, saying so in a hint.
+fn apply_sample<T>(
+    results: &mut Vec<T>,
+    sample: Option<usize>,
+    suppress: bool,
+    path: impl Fn(&T) -> &Path,
+) {
+    let Some(n) = sample else {
+        return;
+    };
+    let total = results.len();
+    if total <= n {
+        return;
+    }
+    *results = crate::ranker::stratified_sample(std::mem::take(results), n, path);
+    output::print_hint(
+        &format!("showing a sample of {n} of {total} results spread across files"),
+        suppress,
+    );
+}
+
+/// Date the files of search results for `--recent`; paths are relative to
+/// the current directory, as printed.
+fn recency_times<'a>(
+    files: impl Iterator<Item = &'a Path>,
+    source: &str,
+) -> Result<std::collections::HashMap<PathBuf, i64>> {
+    let source: crate::ranker::RecencySource = source
+        .parse()
+        .map_err(|e: String| anyhow::anyhow!("[search].recent_source: {e}"))?;
+    let cwd = std::env::current_dir()?;
+    let root = db::find_repo_root(&cwd).unwrap_or_else(|_| cwd.clone());
+    let mut files: Vec<&Path> = files.collect();
+    files.sort();
+    files.dedup();
+    Ok(crate::ranker::file_recency(&root, &cwd, &files, source))
+}
+
+/// The current directory of a `--cwd-scope` query, relative to the repo
+/// root.
+struct CwdScope {
+    root: PathBuf,
+    dir: PathBuf,
+}
+
+impl CwdScope {
+    /// The scope when `enabled` and the current directory is below the repo
+    /// root; at the root every result is in scope, so there is none.
+    fn detect(enabled: bool) -> Option<Self> {
+        if !enabled {
+            return None;
+        }
+        let cwd = std::env::current_dir().ok()?;
+        let root = db::find_repo_root(&cwd).ok()?;
+        // The root comes back canonical.
+        let cwd = std::fs::canonicalize(&cwd).unwrap_or(cwd);
+        let dir = cwd.strip_prefix(&root).ok()?.to_path_buf();
+        (!dir.as_os_str().is_empty()).then_some(Self { root, dir })
+    }
+
+    /// Whether `file` (repo-relative, or absolute as from grep fallbacks)
+    /// lies in the scoped directory.
+    fn contains(&self, file: &str) -> bool {
+        let file = Path::new(file);
+        file.strip_prefix(&self.root)
+            .unwrap_or(file)
+            .starts_with(&self.dir)
+    }
+}
+
+/// Resolve `wonk update` paths, given relative to `cwd`, to paths relative
+/// to the (canonical) repository root.  Paths that no longer exist resolve
+/// through their parent directory so deleted files can be dropped.
+fn repo_relative_paths(repo_root: &Path, cwd: &Path, paths: &[String]) -> Result<Vec<PathBuf>> {
+    paths
+        .iter()
+        .map(|path| {
+            let abs = cwd.join(path);
+            let canonical =
+                std::fs::canonicalize(&abs).or_else(|e| match (abs.parent(), abs.file_name()) {
+                    (Some(parent), Some(name)) => {
+                        std::fs::canonicalize(parent).map(|p| p.join(name))
+                    }
+                    _ => Err(e),
+                });
+            let canonical = canonical.with_context(|| format!("resolving {path}"))?;
+            match canonical.strip_prefix(repo_root) {
+                Ok(rel) => Ok(rel.to_path_buf()),
+                Err(_) => anyhow::bail!("{path} is outside the repository"),
+            }
+        })
+        .collect()
+}
+
+/// `[ranking].boost` plus the `--boost` globs.  Symbol results are
+/// `repo_relative`; search results are relative to the current directory.
+fn path_boost(
+    config: &crate::config::Config,
+    extra: &[String],
+    repo_relative: bool,
+) -> Result<crate::ranker::PathBoost> {
+    let cwd = std::env::current_dir()?;
+    let root = db::find_repo_root(&cwd).unwrap_or_else(|_| cwd.clone());
+    let globs: Vec<String> = config.ranking.boost.iter().chain(extra).cloned().collect();
+    crate::ranker::PathBoost::new(&root, if repo_relative { &root } else { &cwd }, &globs)
+}
+
+/// Load generated-code provenance from the index (empty without one).
+fn generated_files(conn: Option<&Connection>) -> crate::generated::GeneratedFiles {
+    conn.map(crate::generated::GeneratedFiles::load)
+        .unwrap_or_default()
+}
+
+/// Submodule paths whose results `--no-submodules` drops (none when the flag
+/// is off or there is no index).
+fn excluded_submodules(conn: Option<&Connection>, no_submodules: bool) -> Vec<String> {
+    match conn {
+        Some(conn) if no_submodules => db::indexed_submodules(conn),
+        _ => Vec::new(),
+    }
+}
+
+/// Returns `true` when `file` lies inside one of the `submodules` paths.
+fn in_submodule(submodules: &[String], file: &str) -> bool {
+    submodules
+        .iter()
+        .any(|sub| Path::new(file).starts_with(sub))
+}
+
+/// Apply `--verify` to symbol results, correcting drifted locations in place.
+///
+/// Returns one freshness label per result (all `None` when there is no
+/// index to verify against) and prints a summary hint for non-fresh results.
+fn verify_symbols(
+    conn: Option<&Connection>,
+    repo_root: &Path,
+    results: &mut [Symbol],
+    suppress: bool,
+) -> Vec<Option<String>> {
+    let Some(conn) = conn else {
+        return vec![None; results.len()];
+    };
+    let mut verifier = crate::verify::Verifier::new(conn, repo_root);
+    let outcomes: Vec<_> = results
+        .iter_mut()
+        .map(|sym| verifier.verify_symbol(sym))
+        .collect();
+    report_verify_outcomes(&outcomes, suppress)
+}
+
+/// Apply `--verify` to reference results and the subclasses listed with
+/// them (verified as symbols); see [`verify_symbols`].  Returns the labels for
+/// `subclasses` and for `results`.
+fn verify_references(
+    conn: Option<&Connection>,
+    repo_root: &Path,
+    subclasses: &mut [Symbol],
+    results: &mut [Reference],
+    suppress: bool,
+) -> (Vec<Option<String>>, Vec<Option<String>>) {
+    let Some(conn) = conn else {
+        return (vec![None; subclasses.len()], vec![None; results.len()]);
+    };
+    let mut verifier = crate::verify::Verifier::new(conn, repo_root);
+    let mut outcomes: Vec<_> = subclasses
+        .iter_mut()
+        .map(|sym| verifier.verify_symbol(sym))
+        .collect();
+    outcomes.extend(results.iter_mut().map(|r| verifier.verify_reference(r)));
+    let mut labels = report_verify_outcomes(&outcomes, suppress);
+    let freshness = labels.split_off(subclasses.len());
+    (labels, freshness)
+}
+
+fn report_verify_outcomes(
+    outcomes: &[crate::verify::Freshness],
+    suppress: bool,
+) -> Vec<Option<String>> {
+    use crate::verify::Freshness;
+    let relocated = outcomes
+        .iter()
+        .filter(|f| matches!(f, Freshness::Relocated { .. }))
+        .count();
+    let stale = outcomes
+        .iter()
+        .filter(|f| matches!(f, Freshness::Stale | Freshness::Missing))
+        .count();
+    if relocated > 0 || stale > 0 {
+        output::print_hint(
+            &format!(
+                "verify: {relocated} relocated, {stale} stale (run `wonk update` to refresh the index)"
+            ),
+            suppress,
+        );
+    }
+    outcomes
+        .iter()
+        .map(|f| Some(f.label().to_string()))
+        .collect()
+}
+
 fn dispatch_changes<W: io::Write>(
     args: crate::cli::ChangesArgs,
     fmt: &mut Formatter<W>,
@@ -1987,6 +3663,134 @@ pub(crate) fn build_changes_output(
     })
 }
 
+// ---------------------------------------------------------------------------
+// `wonk report` dispatch
+// ---------------------------------------------------------------------------
+
+/// Run a text search on a worker thread, writing each file's matches as
+/// they arrive.
+///
+/// With `ranking` set, matches are classified, ordered, and deduplicated
+/// within each file (each result carries its `category`); there is no
+/// ranking across files and no category headers.  Returns the number of
+/// matches found and the number dropped by the token budget.
+fn stream_search<W: io::Write>(
+    fmt: &mut Formatter<W>,
+    pattern: &str,
+    regex: bool,
+    ignore_case: bool,
+    paths: &[String],
+    tests: &crate::ranker::TestClassifier,
+    ranking: Option<Option<&Connection>>,
+) -> Result<(usize, usize)> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::scope(|scope| {
+        let worker = scope.spawn(move || {
+            search::text_search_streaming(pattern, regex, ignore_case, paths, &[], tx)
+        });
+
+        let mut found = 0usize;
+        let mut truncated = 0usize;
+        for mut chunk in rx {
+            chunk.retain(|r| tests.keep(&r.file));
+            found += chunk.len();
+            let outputs: Vec<SearchOutput> = match ranking {
+                Some(conn) => crate::ranker::rank_and_dedup(&chunk, conn, pattern)
+                    .into_iter()
+                    .flat_map(|(_, items)| items)
+                    .map(|item| {
+                        let mut out = SearchOutput::from_result(&item.result);
+                        out.annotation = item.annotation;
+                        out.category = Some(item.category.to_string());
+                        out
+                    })
+                    .collect(),
+                None => chunk.iter().map(SearchOutput::from_result).collect(),
+            };
+            for out in &outputs {
+                if fmt.format_search_result(out)? == BudgetStatus::Skipped {
+                    truncated += 1;
+                }
+            }
+        }
+
+        worker
+            .join()
+            .map_err(|_| anyhow::anyhow!("search worker panicked"))??;
+        Ok((found, truncated))
+    })
+}
+
+/// Run `wonk report async|unsafe`, returning the number of entries dropped
+/// by the token budget.
+fn dispatch_report<W: io::Write>(
+    args: ReportArgs,
+    fmt: &mut Formatter<W>,
+    suppress: bool,
+    include_tests: bool,
+) -> Result<usize> {
+    let keep = |file: &str| include_tests || !crate::ranker::is_test_file(Path::new(file));
+    let mut truncated = 0usize;
+
+    match args.command {
+        ReportCommand::Async(args) => {
+            let conn = match callgraph_conn(suppress) {
+                Some(c) => c,
+                None => return Ok(0),
+            };
+            let mut boundaries = crate::report::async_boundaries(&conn, args.path.as_deref())?;
+            boundaries.retain(|b| keep(&b.function.file));
+            for boundary in &mut boundaries {
+                boundary.sync_callers.retain(|c| keep(&c.file));
+            }
+
+            if boundaries.is_empty() {
+                output::print_hint("no async functions found", suppress);
+            }
+            for boundary in &boundaries {
+                let out = AsyncBoundaryOutput::from(boundary);
+                if fmt.format_async_boundary(&out)? == BudgetStatus::Skipped {
+                    truncated += 1;
+                }
+            }
+        }
+        ReportCommand::Unsafe(args) => {
+            let repo_root = match std::env::current_dir()
+                .ok()
+                .and_then(|cwd| db::find_repo_root(&cwd).ok())
+            {
+                Some(r) => r,
+                None => {
+                    output::print_error("no repository root found");
+                    return Ok(0);
+                }
+            };
+            let conn = match db::find_existing_index(&repo_root).and_then(|p| db::open(&p).ok()) {
+                Some(c) => c,
+                None => {
+                    output::print_error("no index found; run `wonk init` to build the index");
+                    return Ok(0);
+                }
+            };
+
+            let mut sites = crate::report::unsafe_sites(&conn, &repo_root, args.path.as_deref())?;
+            sites.retain(|s| keep(&s.file));
+
+            if sites.is_empty() {
+                output::print_hint("no unsafe code found", suppress);
+            }
+            for site in &sites {
+                let out = UnsafeSiteOutput::from(site);
+                if fmt.format_unsafe_site(&out)? == BudgetStatus::Skipped {
+                    truncated += 1;
+                }
+            }
+        }
+    }
+
+    Ok(truncated)
+}
+
 /// Open a call graph connection: resolve repo root, open index, check
 /// caller_id data. Returns `None` when an early-exit error/hint was emitted.
 fn callgraph_conn(suppress: bool) -> Option<Connection> {
@@ -2048,8 +3852,9 @@ pub fn looks_like_file_path(s: &str) -> bool {
     }
     let lower = s.to_lowercase();
     [
-        ".rs", ".py", ".js", ".ts", ".tsx", ".jsx", ".go", ".java", ".c", ".h", ".cpp", ".cc",
-        ".hpp", ".rb", ".php", ".cs",
+        ".rs", ".py", ".js", ".ts", ".tsx", ".jsx", ".go", ".java", ".kt", ".scala", ".c", ".h",
+        ".cpp", ".cc", ".hpp", ".rb", ".php", ".cs", ".lua", ".ex", ".exs", ".dart", ".sh",
+        ".bash", ".zsh", ".sql", ".vue", ".svelte",
     ]
     .iter()
     .any(|ext| lower.ends_with(ext))
@@ -2237,6 +4042,42 @@ fn camel_to_snake(s: &str) -> String {
     result
 }
 
+/// Warn about every reason the results just printed may be incomplete, and
+/// hint when some of them came from files modified since they were indexed.
+///
+/// A partial index and disabled grammars are only checked after query
+/// commands; grep fallbacks and stale files are recorded by [`QueryRouter`]
+/// itself, whatever the command.
+fn report_caveats(query: bool, quiet: bool) {
+    use crate::caveats::{self, Caveat};
+    if query {
+        let meta = std::env::current_dir()
+            .ok()
+            .and_then(|cwd| db::find_repo_root(&cwd).ok())
+            .and_then(|repo_root| db::find_existing_index(&repo_root))
+            .and_then(|index| db::read_meta(&index).ok());
+        if meta.as_ref().is_some_and(|meta| meta.partial) {
+            caveats::record(Caveat::PartialIndex);
+        }
+        if meta.is_some_and(|meta| !meta.disabled_languages.is_empty())
+            || !crate::indexer::disabled_languages().is_empty()
+        {
+            caveats::record(Caveat::DisabledLanguages);
+        }
+    }
+    for caveat in caveats::recorded() {
+        output::print_caveat(caveat);
+    }
+    let stale = caveats::stale_files().len();
+    if stale > 0 {
+        let plural = if stale == 1 { "" } else { "s" };
+        output::print_hint(
+            &format!("index is stale for {stale} result file{plural}; run `wonk update`"),
+            quiet,
+        );
+    }
+}
+
 fn is_query_command(cmd: &Command) -> bool {
     matches!(
         cmd,
@@ -2253,13 +4094,24 @@ fn is_query_command(cmd: &Command) -> bool {
             | Command::Callees(_)
             | Command::Callpath(_)
             | Command::Summary(_)
+            | Command::Describe(_)
+            | Command::Near(_)
+            | Command::Langs
             | Command::Flows(_)
             | Command::Blast(_)
             | Command::Changes(_)
             | Command::Context(_)
+            | Command::Find(_)
+            | Command::Report(_)
     )
 }
 
+/// Commands capped and charged by a session budget file: the queries, plus
+/// the other commands that print code or results for an agent to read.
+fn uses_session_budget(cmd: &Command) -> bool {
+    is_query_command(cmd) || matches!(cmd, Command::Show(_) | Command::Global(_))
+}
+
 // ---------------------------------------------------------------------------
 // Semantic blending helpers
 // ---------------------------------------------------------------------------
@@ -2316,6 +4168,46 @@ fn fetch_semantic_results(
     Ok(resolved)
 }
 
+/// After an index build: when it was cut short, tell the user the index is
+/// partial and return `true` so follow-up work (embeddings) is skipped.
+fn index_build_cut_short(suppress: bool) -> bool {
+    if !crate::deadline::cut_short() {
+        return false;
+    }
+    output::print_hint(
+        "indexing stopped early; the index is partial, run `wonk update` to finish it",
+        suppress,
+    );
+    true
+}
+
+/// `wonk init --root DIR`: make `DIR` a repository root by writing
+/// [`db::ROOT_MARKER`] into it (kept if already there) and return its
+/// canonical path.
+fn mark_repo_root(dir: &Path) -> Result<PathBuf> {
+    let root = std::fs::canonicalize(dir)
+        .with_context(|| format!("cannot use {} as the repository root", dir.display()))?;
+    if !root.is_dir() {
+        anyhow::bail!("{} is not a directory", root.display());
+    }
+    let marker = root.join(db::ROOT_MARKER);
+    if !marker.exists() {
+        std::fs::write(&marker, "").with_context(|| format!("writing {}", marker.display()))?;
+    }
+    Ok(root)
+}
+
+/// Before building a central index: when the central store cannot be
+/// written, say that the index goes into the repository instead.
+fn note_local_fallback(local: bool, suppress: bool) {
+    if !local && !db::central_store_usable() {
+        output::print_hint(
+            "~/.wonk/repos is not writable; keeping the index in the repository's .wonk/ instead",
+            suppress,
+        );
+    }
+}
+
 /// Emit a budget summary if any results were truncated.
 ///
 /// In grep mode, prints the summary to stderr. In structured mode (JSON/TOON),
@@ -2352,20 +4244,76 @@ fn emit_budget_summary_with_page<W: io::Write>(
     Ok(())
 }
 
+/// Budget ceilings for ranked search groups, one per group and cumulative,
+/// that split what is left of the budget across them by category weight
+/// (see [`crate::budget::distribute`]).  `None` without a budget.
+fn category_budget_ceilings<W: Write>(
+    fmt: &mut Formatter<W>,
+    groups: &[crate::ranker::ResultGroup],
+    outputs: &[Vec<SearchOutput>],
+    weights: &crate::ranker::CategoryWeights,
+) -> io::Result<Option<Vec<usize>>> {
+    let Some(remaining) = fmt.budget_remaining() else {
+        return Ok(None);
+    };
+    let mut demands = Vec::with_capacity(groups.len());
+    for (group, results) in groups.iter().zip(outputs) {
+        let mut tokens = 0;
+        for out in results {
+            // Past the whole budget the exact demand no longer matters.
+            if tokens > remaining {
+                break;
+            }
+            tokens += fmt.search_result_tokens(out)?;
+        }
+        demands.push((weights.weight(group), tokens));
+    }
+    let mut ceiling = fmt.budget_used();
+    Ok(Some(
+        crate::budget::distribute(remaining, &demands)
+            .into_iter()
+            .map(|share| {
+                ceiling += share;
+                ceiling
+            })
+            .collect(),
+    ))
+}
+
 // ---------------------------------------------------------------------------
 // Status
 // ---------------------------------------------------------------------------
 
 /// Structured status information for `wonk status`.
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
 pub struct StatusInfo {
     pub indexed: bool,
+    /// Path of the index database queries read.
+    pub index_path: Option<String>,
+    /// Schema version recorded in the index (see [`db::SCHEMA_VERSION`]).
+    pub schema_version: i64,
     pub file_count: i64,
     pub symbol_count: i64,
     pub reference_count: i64,
+    /// Languages of the indexed files, sorted.
+    pub languages: Vec<String>,
+    /// When a file was last indexed, in seconds since the Unix epoch.
+    pub last_indexed: Option<i64>,
+    /// Indexed files modified on disk since they were indexed.
+    pub stale_file_count: usize,
     pub embedding_count: usize,
     pub stale_embedding_count: usize,
     pub ollama_reachable: bool,
+    /// Languages whose grammar failed to load; their files use grep fallback.
+    pub disabled_languages: Vec<String>,
+    /// The last index build was interrupted before every file was indexed,
+    /// or the index was built lazily from grep-confirmed definitions.
+    pub partial: bool,
+    /// Index generation, as stamped on search, symbol, and reference
+    /// results; it advances whenever indexed content changes.
+    pub index_generation: u64,
+    /// The repository's daemon, when one is running.
+    pub daemon: Option<crate::daemon::DaemonHealth>,
 }
 
 /// Format status info as a human-readable string for stderr output.
@@ -2375,10 +4323,31 @@ pub fn format_status_info(info: &StatusInfo) -> String {
     }
 
     let mut lines = Vec::new();
+    if let Some(path) = &info.index_path {
+        lines.push(format!(
+            "Location: {path} (schema v{})",
+            info.schema_version
+        ));
+    }
     lines.push(format!(
         "Index: {} files, {} symbols, {} references",
         info.file_count, info.symbol_count, info.reference_count
     ));
+    if !info.languages.is_empty() {
+        lines.push(format!("Languages: {}", info.languages.join(", ")));
+    }
+    if let Some(at) = info.last_indexed {
+        lines.push(format!(
+            "Last indexed: {} ago",
+            crate::daemon::format_uptime(Some(at))
+        ));
+    }
+    if info.stale_file_count > 0 {
+        lines.push(format!(
+            "Stale files: {} modified since indexed; run `wonk update` to refresh them",
+            info.stale_file_count
+        ));
+    }
 
     if info.embedding_count > 0 {
         let mut emb_line = format!("Embeddings: {} embeddings", info.embedding_count);
@@ -2397,6 +4366,30 @@ pub fn format_status_info(info: &StatusInfo) -> String {
     };
     lines.push(format!("Ollama: {ollama_status}"));
 
+    match &info.daemon {
+        Some(d) if d.stuck => lines.push(format!(
+            "Daemon: stuck (PID {}, no heartbeat for {}s); run `wonk daemon start` to replace it",
+            d.pid,
+            d.heartbeat_age.unwrap_or_default()
+        )),
+        Some(d) => lines.push(format!("Daemon: {} (PID {})", d.state, d.pid)),
+        None => lines.push("Daemon: not running".to_string()),
+    }
+
+    if info.partial {
+        lines.push(
+            "Index is partial (build was interrupted or lazy); run `wonk update` to finish it"
+                .to_string(),
+        );
+    }
+
+    if !info.disabled_languages.is_empty() {
+        lines.push(format!(
+            "Disabled grammars: {} (using grep fallback)",
+            info.disabled_languages.join(", ")
+        ));
+    }
+
     lines.join("\n")
 }
 
@@ -2407,19 +4400,44 @@ pub fn format_status_info(info: &StatusInfo) -> String {
 pub fn query_status_info(conn: Option<&Connection>) -> StatusInfo {
     let client = crate::embedding::OllamaClient::new();
     let ollama_reachable = client.is_healthy_quick();
+    let mut disabled_languages = crate::indexer::disabled_languages();
 
     let Some(conn) = conn else {
         return StatusInfo {
             indexed: false,
+            index_path: None,
+            schema_version: 0,
             file_count: 0,
             symbol_count: 0,
             reference_count: 0,
+            languages: Vec::new(),
+            last_indexed: None,
+            stale_file_count: 0,
             embedding_count: 0,
             stale_embedding_count: 0,
             ollama_reachable,
+            disabled_languages,
+            partial: false,
+            index_generation: 0,
+            daemon: None,
         };
     };
 
+    // Languages disabled by the session that built the index.
+    let index_path = conn.path().filter(|p| !p.is_empty()).map(str::to_string);
+    let mut partial = false;
+    let mut repo_root = None;
+    if let Some(meta) = index_path
+        .as_deref()
+        .and_then(|p| db::read_meta(Path::new(p)).ok())
+    {
+        partial = meta.partial;
+        repo_root = Some(PathBuf::from(meta.repo_path));
+        disabled_languages.extend(meta.disabled_languages);
+        disabled_languages.sort();
+        disabled_languages.dedup();
+    }
+
     let file_count: i64 = conn
         .query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))
         .unwrap_or(0);
@@ -2431,23 +4449,102 @@ pub fn query_status_info(conn: Option<&Connection>) -> StatusInfo {
         .unwrap_or(0);
     let (embedding_count, stale_embedding_count) =
         crate::embedding::embedding_stats(conn).unwrap_or((0, 0));
+    let languages = conn
+        .prepare("SELECT DISTINCT language FROM files WHERE language IS NOT NULL ORDER BY language")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| row.get(0))?
+                .collect::<rusqlite::Result<Vec<String>>>()
+        })
+        .unwrap_or_default();
+    let last_indexed = conn
+        .query_row("SELECT MAX(last_indexed) FROM files", [], |row| row.get(0))
+        .unwrap_or(None);
 
     StatusInfo {
         indexed: true,
+        index_path,
+        schema_version: db::schema_version(conn),
         file_count,
         symbol_count,
         reference_count,
+        languages,
+        last_indexed,
+        stale_file_count: repo_root.map_or(0, |root| count_stale_files(conn, &root)),
         embedding_count,
         stale_embedding_count,
         ollama_reachable,
+        disabled_languages,
+        partial,
+        index_generation: db::index_generation(conn),
+        daemon: crate::daemon::read_health(conn),
     }
 }
 
-/// Spawn the daemon as a background subprocess (best-effort).
+/// Count indexed files whose mtime is newer than their `last_indexed` stamp.
+/// Deleted files are not counted.
+fn count_stale_files(conn: &Connection, repo_root: &Path) -> usize {
+    let Ok(mut stmt) = conn.prepare("SELECT path, last_indexed FROM files") else {
+        return 0;
+    };
+    let Ok(rows) = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+    }) else {
+        return 0;
+    };
+    rows.filter_map(Result::ok)
+        .filter(|(path, last_indexed)| {
+            std::fs::metadata(repo_root.join(path))
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .is_some_and(|d| d.as_secs() as i64 > *last_indexed)
+        })
+        .count()
+}
+
+/// Resolve the `FILE|-`, `--lang`, and `--path` arguments of `wonk parse`
+/// and `wonk tree-dump` into the language, display path, and source text.
+fn read_source_arg(
+    file: &str,
+    lang: Option<&str>,
+    path: Option<&str>,
+) -> Result<(crate::indexer::Lang, String, String)> {
+    let from_stdin = file == "-";
+    let path = match path {
+        Some(path) => path.to_string(),
+        None if from_stdin => "<stdin>".to_string(),
+        None => file.to_string(),
+    };
+    let lang = match lang {
+        Some(name) => crate::indexer::lang_from_name(name)
+            .ok_or_else(|| anyhow::anyhow!("unsupported language {name:?}"))?,
+        None => crate::indexer::detect_language(Path::new(&path))
+            .ok_or_else(|| anyhow::anyhow!("cannot detect the language of {path}; pass --lang"))?,
+    };
+    let source = if from_stdin {
+        io::read_to_string(io::stdin())
+            .map_err(|e| anyhow::anyhow!("reading source from stdin: {e}"))?
+    } else {
+        std::fs::read_to_string(file).map_err(|e| anyhow::anyhow!("reading {file}: {e}"))?
+    };
+    Ok((lang, path, source))
+}
+
+/// Spawn the daemon as a background subprocess (best-effort), if the
+/// `[daemon].auto_start` policy wants one and none is running.
 ///
 /// Uses `std::process::Command` to launch `wonk daemon start` as a detached
 /// child process.  Errors are silently ignored since the daemon is optional.
-fn spawn_daemon_background(repo_root: &Path) {
+fn spawn_daemon_background(repo_root: &Path, policy: crate::daemon::AutoStart, after_init: bool) {
+    if !policy.wants_start(after_init, std::env::var_os("CI").is_some()) {
+        return;
+    }
+    if let Some(index) = db::find_existing_index(repo_root)
+        && (index.parent().is_some_and(crate::daemon::is_running)
+            || db::archive_source(&index).is_some())
+    {
+        return;
+    }
     if let Ok(exe) = std::env::current_exe() {
         let _ = std::process::Command::new(exe)
             .args(["daemon", "start"])
@@ -2495,55 +4592,217 @@ fn dispatch_daemon_list<W: io::Write>(
 
 /// Build a regex pattern to find symbol definitions via grep.
 ///
-/// Covers all 11 supported languages:
+/// Covers all supported languages:
 ///   Rust:       `fn`, `pub fn`, `pub(crate) fn`, `struct`, `enum`, `trait`
 ///   Python:     `def`, `class`
 ///   Ruby:       `def`, `class`, `module`
 ///   JavaScript: `function`, `class`
 ///   TypeScript: `function`, `class`, `interface`, `enum`
 ///   Go:         `func`, `type ... struct`, `type ... interface`
-///   Java:       `class`, `interface`, `enum`
+///   Java:       `class`, `interface`, `enum`, members after an access modifier
+///   Kotlin:     `fun`, `class`, `object`, `interface`
+///   Scala:      `def`, `class`, `object`, `trait`, `val`, `var`
 ///   C:          function-like patterns (captured by generic regex)
 ///   C++:        `class`, `struct`, `enum`, function-like patterns
 ///   PHP:        `function`, `class`, `interface`, `trait`
-///   C#:         `class`, `struct`, `interface`, `enum`, `delegate`
+///   C#:         `class`, `struct`, `record`, `interface`, `enum`, `delegate`,
+///               `namespace`, members after an access modifier
+///   Lua:        `function`, `local function`
+///   Elixir:     `def`, `defp`, `defmacro`, `defmacrop`, `defmodule`
+///   Dart:       `class`, `mixin`, `extension`, `enum`, `typedef`
+///   Shell:      `function`, `name() {`
+///   SQL:        `CREATE TABLE`, `VIEW`, `INDEX`, `FUNCTION`, `PROCEDURE`,
+///               `TYPE` (any case)
 pub fn symbol_grep_pattern(name: &str) -> String {
-    // Use word boundary around the name to reduce false positives.
-    format!(
-        r"(fn|pub\s+fn|pub\(crate\)\s+fn|def|function|func|class|struct|enum|trait|interface|module|type|const|let|var|val|delegate)\s+{}\b",
-        regex_escape(name)
-    )
+    definition_pattern(None, &identifier_re(name), &[])
 }
 
 /// Build a regex pattern to find symbol definitions filtered by kind.
 pub fn symbol_kind_grep_pattern(name: &str, kind: &str) -> String {
+    definition_pattern(Some(kind), &identifier_re(name), &[])
+}
+
+/// Build one regex pattern matching the definition of any of `names`.
+///
+/// The second capture group holds the name that matched (the third for a
+/// shell `name() {`), so hits can be attributed back to the queried name.
+pub fn symbols_grep_pattern(names: &[&str], kind: Option<&str>) -> String {
+    definition_pattern(kind, &names_re(names), &[])
+}
+
+/// `names` as one capture group of alternatives, each bounded by
+/// [`identifier_re`].
+fn names_re(names: &[&str]) -> String {
+    let alternation: Vec<String> = names.iter().map(|n| identifier_re(n)).collect();
+    format!("({})", alternation.join("|"))
+}
+
+/// C# and Java methods and properties have no definition keyword: an access
+/// modifier, any further modifiers, and the type come before the name
+/// (`public static async Task<int> Load`).
+const MEMBER_PREFIX: &str = r"(?:public|private|protected|internal)(?:\s+[\w.<>\[\],?]+)+";
+
+/// SQL `CREATE` statements, in any case, up to the object name:
+/// `CREATE OR REPLACE VIEW`, `create unique index`, `CREATE TABLE IF NOT EXISTS`.
+const SQL_CREATE: &str = r"(?i:create(?:\s+(?:or\s+replace|unique|materialized))*\s+(?:table|view|index|procedure|function|type)(?:\s+if\s+not\s+exists)?)";
+
+/// Definition keywords for `kind` (all of them when `None` or unknown) and
+/// the `custom` keyword alternatives, followed by `name_re`, a name escaped
+/// and bounded by [`identifier_re`].
+fn definition_pattern(kind: Option<&str>, name_re: &str, custom: &[String]) -> String {
+    let kind = kind.unwrap_or_default();
     let keywords = match kind {
-        "function" | "method" => "fn|pub\\s+fn|pub\\(crate\\)\\s+fn|def|function|func",
-        "class" => "class",
-        "struct" => "struct",
-        "interface" => "interface",
-        "enum" => "enum",
-        "trait" => "trait",
-        "type_alias" => "type|delegate",
-        "constant" => "const",
-        "variable" => "let|var|val",
-        "module" => "module|mod",
-        _ => return symbol_grep_pattern(name),
+        "function" | "method" => {
+            format!(
+                "fn|pub\\s+fn|pub\\(crate\\)\\s+fn|def|defp|defmacrop?|function|func|fun|{MEMBER_PREFIX}"
+            )
+        }
+        "class" => "class|object|record".to_string(),
+        "struct" => "struct".to_string(),
+        "interface" => "interface".to_string(),
+        "enum" => "enum".to_string(),
+        "trait" => "trait|mixin".to_string(),
+        "type_alias" => "type|typedef|delegate".to_string(),
+        "constant" => "const|val".to_string(),
+        "variable" => "let|var|val".to_string(),
+        "module" => "module|mod|namespace|defmodule|extension".to_string(),
+        _ => format!(
+            "fn|pub\\s+fn|pub\\(crate\\)\\s+fn|def|defp|defmacrop?|function|func|fun|class|object|record|struct|enum|trait|mixin|interface|module|defmodule|extension|namespace|type|typedef|const|let|var|val|delegate|{MEMBER_PREFIX}|{SQL_CREATE}"
+        ),
     };
-    format!(r"({})\s+{}\b", keywords, regex_escape(name))
+    let keywords = std::iter::once(keywords.as_str())
+        .chain(custom.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join("|");
+    let pattern = format!(r"({})\s+{}", keywords, name_re);
+    // Shell functions need no keyword: `name() {`.
+    if matches!(
+        parse_symbol_kind(kind),
+        SymbolKind::Function | SymbolKind::Method
+    ) {
+        format!(r"{pattern}|^\s*{name_re}\s*\(\)\s*\{{")
+    } else {
+        pattern
+    }
+}
+
+/// Definition keywords for the grep fallback from `[fallback.<language>]`.
+///
+/// A language's keywords replace the built-in ones in its files.  The
+/// fallback still searches the repository once, with every configured
+/// keyword added to the built-in ones, and [`retain`](Self::retain) then
+/// holds each hit to the keywords of its own file.  Keys that name no
+/// supported language are file extensions, so a DSL's definitions can be
+/// found too.  A configured keyword does not say what it defines, so a
+/// `--kind` query searches the built-in keywords for that kind, and in a
+/// configured file keeps only the lines its own keywords match as well.
+#[derive(Debug, Clone, Default)]
+pub struct FallbackKeywords {
+    /// Keyword alternatives per supported language.
+    languages: Vec<(crate::indexer::Lang, Vec<String>)>,
+    /// Keyword alternatives per (lowercase) file extension.
+    extensions: Vec<(String, Vec<String>)>,
+}
+
+impl FallbackKeywords {
+    pub fn from_config(config: &crate::config::FallbackConfig) -> Self {
+        let mut keywords = Self::default();
+        for (key, language) in &config.languages {
+            let Some(words) = &language.symbol_keywords else {
+                continue;
+            };
+            let alternatives = words
+                .iter()
+                .map(|w| keyword_re(w))
+                .filter(|w| !w.is_empty())
+                .collect();
+            match crate::indexer::lang_from_name(key) {
+                Some(lang) => keywords.languages.push((lang, alternatives)),
+                None => keywords
+                    .extensions
+                    .push((key.to_ascii_lowercase(), alternatives)),
+            }
+        }
+        keywords
+    }
+
+    fn is_empty(&self) -> bool {
+        self.languages.is_empty() && self.extensions.is_empty()
+    }
+
+    /// The configured keyword alternatives for the file at `path`, if any.
+    fn for_file(&self, path: &Path) -> Option<&[String]> {
+        if let Some(lang) = crate::indexer::detect_language(path)
+            && let Some((_, words)) = self.languages.iter().find(|(l, _)| *l == lang)
+        {
+            return Some(words);
+        }
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        self.extensions
+            .iter()
+            .find(|(e, _)| *e == ext)
+            .map(|(_, words)| words.as_slice())
+    }
+
+    /// The pattern to search for definitions of `name_re`.
+    fn pattern(&self, kind: Option<&str>, name_re: &str) -> String {
+        let mut custom: Vec<String> = Vec::new();
+        if kind.is_none() {
+            let languages = self.languages.iter().map(|(_, words)| words);
+            for words in languages.chain(self.extensions.iter().map(|(_, words)| words)) {
+                for w in words {
+                    if !custom.contains(w) {
+                        custom.push(w.clone());
+                    }
+                }
+            }
+        }
+        definition_pattern(kind, name_re, &custom)
+    }
+
+    /// Drop hits of [`pattern`](Self::pattern) that do not define the name
+    /// with the keywords of their own file.
+    fn retain(&self, hits: &mut Vec<search::SearchResult>, kind: Option<&str>, name_re: &str) {
+        if self.is_empty() {
+            return;
+        }
+        let mut compiled: std::collections::HashMap<String, Option<regex::Regex>> =
+            std::collections::HashMap::new();
+        hits.retain(|hit| {
+            let pattern = match self.for_file(&hit.file) {
+                Some(words) => format!(r"({})\s+{}", words.join("|"), name_re),
+                None => definition_pattern(kind, name_re, &[]),
+            };
+            compiled
+                .entry(pattern)
+                .or_insert_with_key(|p| regex::Regex::new(p).ok())
+                .as_ref()
+                .is_some_and(|re| re.is_match(&hit.content))
+        });
+    }
+}
+
+/// A configured keyword as a regex alternative: each word escaped, with any
+/// run of whitespace between words.
+fn keyword_re(keyword: &str) -> String {
+    keyword
+        .split_whitespace()
+        .map(regex_escape)
+        .collect::<Vec<_>>()
+        .join(r"\s+")
 }
 
 /// Build a regex pattern to find references (usages) of a name via grep.
 ///
-/// This is a broad pattern that looks for the name as a word boundary match,
+/// This is a broad pattern that looks for the name as a whole identifier,
 /// which captures calls, type annotations, and other usages.
 pub fn reference_grep_pattern(name: &str) -> String {
-    format!(r"\b{}\b", regex_escape(name))
+    identifier_re(name)
 }
 
 /// Build a regex pattern to find import/use statements mentioning a name.
 ///
-/// Covers all 11 supported languages:
+/// Covers all supported languages:
 ///   Rust:       `use ... name`
 ///   Python:     `import name`, `from ... import name`
 ///   Ruby:       `require ... name`
@@ -2551,12 +4810,18 @@ pub fn reference_grep_pattern(name: &str) -> String {
 ///   TypeScript: `import ... name`
 ///   Go:         `import ... name`
 ///   Java:       `import ... name`
+///   Kotlin:     `import ... name`
+///   Scala:      `import ... name`
 ///   C/C++:      `#include ... name`
 ///   PHP:        `use ... name`, `require ... name`, `include ... name`
 ///   C#:         `using ... name`
+///   Lua:        `require ... name`
+///   Elixir:     `alias ... name`, `import ... name`, `use ... name`
+///   Dart:       `import ... name`, `part ... name`
+///   Shell:      `source ... name`
 pub fn import_grep_pattern(name: &str) -> String {
     format!(
-        r"(import|from|require|use|using|include)\s+.*{}",
+        r"(import|from|require|use|using|include|alias|part|source)[\s(]+.*{}",
         regex_escape(name)
     )
 }
@@ -2564,11 +4829,57 @@ pub fn import_grep_pattern(name: &str) -> String {
 /// Build a regex pattern to find signature lines (function/method declarations).
 pub fn signature_grep_pattern(name: &str) -> String {
     format!(
-        r"(fn|pub\s+fn|pub\(crate\)\s+fn|def|function|func)\s+{}\s*\(",
-        regex_escape(name)
+        r"(fn|pub\s+fn|pub\(crate\)\s+fn|def|function|func|fun)\s+{}\s*\(",
+        identifier_re(name)
+    )
+}
+
+/// `name` as a regex matching it only as a whole identifier.
+///
+/// Each end is bounded by `\b` when the name's character there is a word
+/// character (Unicode letters and digits included) and by `\B` when it is
+/// not, so names with a sigil or suffix (`$price`, `#secret`, `empty?`)
+/// still match after a space or before a `(`.  Characters that only some
+/// languages allow in identifiers are checked on the hits afterwards (see
+/// [`retain_whole_identifiers`]).
+fn identifier_re(name: &str) -> String {
+    let boundary = |c: Option<char>| match c {
+        Some(c) if is_word_char(c) => r"\b",
+        Some(_) => r"\B",
+        None => "",
+    };
+    format!(
+        "{}{}{}",
+        boundary(name.chars().next()),
+        regex_escape(name),
+        boundary(name.chars().last())
     )
 }
 
+/// Whether `c` is a regex word character (`\w`, Unicode-aware).
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Drop grep hits whose match is part of a longer identifier in the file's
+/// language, such as `price` inside the JavaScript name `a$price` or
+/// `empty` inside the Ruby method `empty?`.
+fn retain_whole_identifiers(hits: &mut Vec<search::SearchResult>) {
+    hits.retain(|hit| {
+        let extra = crate::indexer::detect_language(&hit.file).map_or("", |l| l.identifier_chars());
+        let (Some(start), Some(end)) = (
+            (hit.col as usize).checked_sub(1),
+            hit.end_col.and_then(|c| (c as usize).checked_sub(1)),
+        ) else {
+            return true;
+        };
+        let is_ident = |c: char| is_word_char(c) || extra.contains(c);
+        let before = hit.content.get(..start).and_then(|s| s.chars().next_back());
+        let after = hit.content.get(end..).and_then(|s| s.chars().next());
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    });
+}
+
 /// Escape special regex characters in a literal name.
 fn regex_escape(s: &str) -> String {
     let mut escaped = String::with_capacity(s.len());
@@ -2616,10 +4927,28 @@ fn parse_symbol_kind(s: &str) -> SymbolKind {
 pub struct QueryRouter {
     /// Open database connection, or `None` if no index was found.
     conn: Option<Connection>,
+    /// Store given with [`with_store`](Self::with_store), answering queries
+    /// instead of `conn`.
+    store: Option<Box<dyn IndexStore>>,
+    /// How `conn` is opened, from `[index].backend`.
+    backend: Backend,
     /// Repository root directory (used as the base for grep searches).
     repo_root: PathBuf,
+    /// Maximum number of modified files reindexed per query before answering.
+    refresh_limit: usize,
+    /// Definition keywords the grep fallback uses per language.
+    fallback_keywords: FallbackKeywords,
+    /// Add the files the grep fallback finds definitions in to the index
+    /// (`[index].auto_init = "lazy"` with no index, or a partial one).
+    promote: bool,
+    /// Whether the index promoted files go to is the local one.
+    local: bool,
 }
 
+/// Result files whose staleness a query checks beyond those it refreshes,
+/// so the check stays cheap on large result sets.
+pub const STALE_CHECK_LIMIT: usize = 20;
+
 impl QueryRouter {
     /// Create a new `QueryRouter`.
     ///
@@ -2639,14 +4968,47 @@ impl QueryRouter {
             })
             .unwrap_or_else(|| PathBuf::from("."));
 
-        let conn = db::index_path_for(&root, local)
-            .ok()
+        let config = crate::config::Config::load(Some(&root)).unwrap_or_default();
+        let index_config = config.index;
+        let backend = index_config.backend.parse().unwrap_or_else(|e: String| {
+            output::print_warning(&format!("{e}; using sqlite"));
+            Backend::Sqlite
+        });
+        let index_path = db::index_path_for(&root, local).ok();
+        let conn = index_path
+            .as_ref()
             .filter(|p| p.exists())
-            .and_then(|p| db::open_existing(&p).ok());
+            .and_then(|p| backend.open(p).ok());
+        // A complete index is never turned partial by promoting into it.
+        let promote = pipeline::AutoInit::from_config(&index_config) == pipeline::AutoInit::Lazy
+            && index_path
+                .as_deref()
+                .is_some_and(|p| !p.exists() || db::read_meta(p).is_ok_and(|m| m.partial));
 
         Self {
             conn,
+            store: None,
+            backend,
             repo_root: root,
+            refresh_limit: index_config.query_refresh_limit,
+            fallback_keywords: FallbackKeywords::from_config(&config.fallback),
+            promote,
+            local,
+        }
+    }
+
+    /// Create a `QueryRouter` answering from `store` instead of an index
+    /// database, falling back to grep under `repo_root` like any other.
+    pub fn with_store(store: Box<dyn IndexStore>, repo_root: PathBuf) -> Self {
+        Self {
+            conn: None,
+            store: Some(store),
+            backend: Backend::default(),
+            repo_root,
+            refresh_limit: 0,
+            fallback_keywords: FallbackKeywords::default(),
+            promote: false,
+            local: false,
         }
     }
 
@@ -2655,7 +5017,13 @@ impl QueryRouter {
     pub fn with_conn(conn: Connection, repo_root: PathBuf) -> Self {
         Self {
             conn: Some(conn),
+            store: None,
+            backend: Backend::default(),
             repo_root,
+            refresh_limit: 0,
+            fallback_keywords: FallbackKeywords::default(),
+            promote: false,
+            local: false,
         }
     }
 
@@ -2664,13 +5032,28 @@ impl QueryRouter {
     pub fn grep_only(repo_root: PathBuf) -> Self {
         Self {
             conn: None,
+            store: None,
+            backend: Backend::default(),
             repo_root,
+            refresh_limit: 0,
+            fallback_keywords: FallbackKeywords::default(),
+            promote: false,
+            local: false,
         }
     }
 
-    /// Returns `true` if the router has an open index database.
+    /// Returns `true` if the router has an index to answer from.
     pub fn has_index(&self) -> bool {
-        self.conn.is_some()
+        self.index_store().is_some()
+    }
+
+    /// The store queries are answered from: the one given to
+    /// [`with_store`](Self::with_store), else the index database.
+    fn index_store(&self) -> Option<&dyn IndexStore> {
+        match &self.store {
+            Some(store) => Some(store.as_ref()),
+            None => self.conn.as_ref().map(|conn| conn as &dyn IndexStore),
+        }
     }
 
     /// Returns a reference to the underlying database connection, if available.
@@ -2689,7 +5072,93 @@ impl QueryRouter {
         self.conn = db::index_path_for(&self.repo_root, false)
             .ok()
             .filter(|p| p.exists())
-            .and_then(|p| db::open_existing(&p).ok());
+            .and_then(|p| self.backend.open(&p).ok());
+    }
+
+    /// Set the per-query stale-file refresh limit (0 disables refresh).
+    pub fn set_refresh_limit(&mut self, limit: usize) {
+        self.refresh_limit = limit;
+    }
+
+    /// Reindex files among `files` that were modified since they were indexed.
+    ///
+    /// Only files whose mtime is not older than their `last_indexed` stamp are
+    /// hashed, and at most `refresh_limit` of them are reindexed, so a query
+    /// touching many edited files stays fast.  Returns the number of files
+    /// whose index data actually changed.
+    ///
+    /// Files past the refresh limit (all of them when refresh is disabled)
+    /// are still checked, up to [`STALE_CHECK_LIMIT`], and those modified
+    /// after they were indexed are recorded with
+    /// [`caveats::record_stale_file`](crate::caveats::record_stale_file) so
+    /// the command can hint that the index is out of date.
+    pub fn refresh_stale_files<'a>(&self, files: impl IntoIterator<Item = &'a str>) -> usize {
+        let Some(conn) = &self.conn else {
+            return 0;
+        };
+
+        let mut seen = std::collections::HashSet::new();
+        let mut attempted = 0usize;
+        let mut checked = 0usize;
+        let mut refreshed = 0usize;
+        for file in files {
+            if attempted >= self.refresh_limit && checked >= STALE_CHECK_LIMIT {
+                break;
+            }
+            if !seen.insert(file) {
+                continue;
+            }
+            let Ok(last_indexed) = conn.query_row(
+                "SELECT last_indexed FROM files WHERE path = ?1",
+                rusqlite::params![file],
+                |row| row.get::<_, i64>(0),
+            ) else {
+                continue;
+            };
+            let abs = self.repo_root.join(file);
+            let mtime = std::fs::metadata(&abs)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs() as i64);
+            // Deleted files are left for `wonk update`; results in them are
+            // still reported so the caller can see what went away.
+            let Some(mtime) = mtime else {
+                continue;
+            };
+            checked += 1;
+            if mtime < last_indexed {
+                continue;
+            }
+            if attempted >= self.refresh_limit {
+                // Modified in the second it was indexed is not conclusive
+                // without hashing, which only refresh does.
+                if mtime > last_indexed {
+                    crate::caveats::record_stale_file(file);
+                }
+                continue;
+            }
+            attempted += 1;
+            match pipeline::reindex_file(conn, &abs, &self.repo_root) {
+                Ok(true) => refreshed += 1,
+                Ok(false) => {}
+                Err(_) => crate::caveats::record_stale_file(file),
+            }
+        }
+        if refreshed > 0 {
+            let _ = crate::imports::resolve_pending(conn, Some(&self.repo_root));
+        }
+        refreshed
+    }
+
+    /// Pass grep fallback `results` through, recording a
+    /// [`Caveat::GrepFallback`](crate::caveats::Caveat::GrepFallback) when
+    /// they are the answer: there is no index, or grep found what it missed.
+    fn fell_back<T>(&self, results: Vec<T>) -> Vec<T> {
+        if !self.has_index() || !results.is_empty() {
+            crate::caveats::record(crate::caveats::Caveat::GrepFallback);
+        }
+        results
     }
 
     // -- Symbol queries -----------------------------------------------------
@@ -2719,45 +5188,162 @@ impl QueryRouter {
         exact: bool,
     ) -> Result<Vec<Symbol>, DbError> {
         // Try SQLite first.
-        if let Some(conn) = &self.conn {
-            let results = query_symbols_db_with_file(conn, name, kind, file, exact)?;
+        if let Some(store) = self.index_store() {
+            let mut results = store.symbols(name, kind, file, exact)?;
+            if self.refresh_stale_files(results.iter().map(|s| s.file.as_str())) > 0 {
+                results = store.symbols(name, kind, file, exact)?;
+            }
             if !results.is_empty() {
                 return Ok(results);
             }
         }
 
         // Fallback to grep.
-        Ok(self.query_symbols_grep(name, kind))
+        Ok(self.fell_back(self.query_symbols_grep(name, kind)))
     }
 
     /// Grep-based symbol search fallback.
     fn query_symbols_grep(&self, name: &str, kind: Option<&str>) -> Vec<Symbol> {
-        let pattern = match kind {
-            Some(k) => symbol_kind_grep_pattern(name, k),
-            None => symbol_grep_pattern(name),
-        };
+        let name_re = identifier_re(name);
+        let pattern = self.fallback_keywords.pattern(kind, &name_re);
 
         let root_str = self.repo_root.to_string_lossy().into_owned();
         let results = search::text_search(&pattern, true, false, &[root_str]);
 
         match results {
-            Ok(hits) => hits
-                .into_iter()
-                .map(|r| Symbol {
-                    name: name.to_string(),
+            Ok(mut hits) => {
+                self.fallback_keywords.retain(&mut hits, kind, &name_re);
+                self.promote_hits(&hits);
+                hits.into_iter()
+                    .map(|r| Symbol {
+                        name: name.to_string(),
+                        kind: kind.map(parse_symbol_kind).unwrap_or(SymbolKind::Function),
+                        file: r.file.to_string_lossy().into_owned(),
+                        line: r.line as usize,
+                        col: r.col as usize,
+                        end_line: None,
+                        end_col: None,
+                        start_byte: None,
+                        end_byte: None,
+                        scope: None,
+                        signature: r.content.clone(),
+                        language: String::new(),
+                        doc_comment: None,
+                        condition: None,
+                        visibility: None,
+                        modifiers: Vec::new(),
+                        generics: Vec::new(),
+                    })
+                    .collect()
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Add the files of grep-confirmed definitions `hits` to the index when
+    /// it is built lazily, so later queries about them are answered from it.
+    /// A failure is reported and leaves the answer as it is.
+    fn promote_hits(&self, hits: &[search::SearchResult]) {
+        if !self.promote || hits.is_empty() {
+            return;
+        }
+        let mut paths: Vec<PathBuf> = hits
+            .iter()
+            .map(|h| {
+                h.file
+                    .strip_prefix(&self.repo_root)
+                    .unwrap_or(&h.file)
+                    .to_path_buf()
+            })
+            .collect();
+        paths.sort();
+        paths.dedup();
+        if let Err(e) = pipeline::promote_files(&self.repo_root, self.local, &paths) {
+            output::print_warning(&format!("could not add files to the index: {e}"));
+        }
+    }
+
+    /// Look up symbols named exactly any of `names` in one pass.
+    ///
+    /// The index is asked once for the whole list; names it has no answer
+    /// for are searched with a single combined grep.  Results come back
+    /// grouped per queried name, in the order of `names`.
+    pub fn query_symbols_named(
+        &self,
+        names: &[&str],
+        kind: Option<&str>,
+        file: Option<&str>,
+    ) -> Result<Vec<(String, Vec<Symbol>)>, DbError> {
+        let mut found: std::collections::HashMap<String, Vec<Symbol>> =
+            std::collections::HashMap::new();
+        if let Some(store) = self.index_store() {
+            let mut results = store.symbols_named(names, kind, file)?;
+            if self.refresh_stale_files(results.iter().map(|s| s.file.as_str())) > 0 {
+                results = store.symbols_named(names, kind, file)?;
+            }
+            for sym in results {
+                found.entry(sym.name.clone()).or_default().push(sym);
+            }
+        }
+
+        let missing: Vec<&str> = names
+            .iter()
+            .copied()
+            .filter(|n| !found.contains_key(*n))
+            .collect();
+        if !missing.is_empty() {
+            let hits = self.fell_back(self.query_symbols_named_grep(&missing, kind));
+            for sym in hits {
+                found.entry(sym.name.clone()).or_default().push(sym);
+            }
+        }
+
+        Ok(names
+            .iter()
+            .map(|n| (n.to_string(), found.remove(*n).unwrap_or_default()))
+            .collect())
+    }
+
+    /// Grep fallback for [`query_symbols_named`](Self::query_symbols_named):
+    /// one search for all `names`, each hit named after the name it matched.
+    fn query_symbols_named_grep(&self, names: &[&str], kind: Option<&str>) -> Vec<Symbol> {
+        let name_re = names_re(names);
+        let pattern = self.fallback_keywords.pattern(kind, &name_re);
+        let Ok(re) = regex::Regex::new(&pattern) else {
+            return Vec::new();
+        };
+
+        let root_str = self.repo_root.to_string_lossy().into_owned();
+        let Ok(mut hits) = search::text_search(&pattern, true, false, &[root_str]) else {
+            return Vec::new();
+        };
+        self.fallback_keywords.retain(&mut hits, kind, &name_re);
+        self.promote_hits(&hits);
+        hits.into_iter()
+            .filter_map(|r| {
+                let caps = re.captures(&r.content)?;
+                let name = caps.get(2).or_else(|| caps.get(3))?.as_str().to_string();
+                Some(Symbol {
+                    name,
                     kind: kind.map(parse_symbol_kind).unwrap_or(SymbolKind::Function),
                     file: r.file.to_string_lossy().into_owned(),
                     line: r.line as usize,
                     col: r.col as usize,
                     end_line: None,
+                    end_col: None,
+                    start_byte: None,
+                    end_byte: None,
                     scope: None,
                     signature: r.content.clone(),
                     language: String::new(),
                     doc_comment: None,
+                    condition: None,
+                    visibility: None,
+                    modifiers: Vec::new(),
+                    generics: Vec::new(),
                 })
-                .collect(),
-            Err(_) => Vec::new(),
-        }
+            })
+            .collect()
     }
 
     // -- Reference queries --------------------------------------------------
@@ -2774,8 +5360,11 @@ impl QueryRouter {
         paths: &[String],
     ) -> Result<Vec<Reference>, DbError> {
         // Try SQLite first.
-        if let Some(conn) = &self.conn {
-            let mut results = query_references_db(conn, name)?;
+        if let Some(store) = self.index_store() {
+            let mut results = store.references(name)?;
+            if self.refresh_stale_files(results.iter().map(|r| r.file.as_str())) > 0 {
+                results = store.references(name)?;
+            }
             if !results.is_empty() {
                 if !paths.is_empty() {
                     results.retain(|r| paths.iter().any(|p| r.file.starts_with(p)));
@@ -2785,7 +5374,7 @@ impl QueryRouter {
         }
 
         // Fallback to grep.
-        Ok(self.query_references_grep(name, paths))
+        Ok(self.fell_back(self.query_references_grep(name, paths)))
     }
 
     /// Grep-based reference search fallback.
@@ -2800,21 +5389,144 @@ impl QueryRouter {
         let results = search::text_search(&pattern, true, false, &search_paths);
 
         match results {
-            Ok(hits) => hits
-                .into_iter()
-                .map(|r| Reference {
-                    name: name.to_string(),
+            Ok(mut hits) => {
+                retain_whole_identifiers(&mut hits);
+                hits.into_iter()
+                    .map(|r| Reference {
+                        name: name.to_string(),
+                        kind: ReferenceKind::Call,
+                        file: r.file.to_string_lossy().into_owned(),
+                        line: r.line as usize,
+                        col: r.col as usize,
+                        end_col: r.end_col.map(|v| v as usize),
+                        start_byte: r.start_byte.map(|v| v as usize),
+                        end_byte: r.end_byte.map(|v| v as usize),
+                        context: r.content.clone(),
+                        caller_name: None,
+                        confidence: 0.5,
+                    })
+                    .collect()
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Find references to each of `names`, as
+    /// [`query_references`](Self::query_references) does for one.
+    ///
+    /// Names the index has no references for are searched with a single
+    /// combined grep instead of one scan per name.  Results come back
+    /// grouped per queried name, in the order of `names`.
+    pub fn query_references_named(
+        &self,
+        names: &[&str],
+        paths: &[String],
+    ) -> Result<Vec<(String, Vec<Reference>)>, DbError> {
+        let mut found: std::collections::HashMap<String, Vec<Reference>> =
+            std::collections::HashMap::new();
+        if let Some(store) = self.index_store() {
+            let lookup = |found: &mut std::collections::HashMap<String, Vec<Reference>>| {
+                for name in names {
+                    let results = store.references(name)?;
+                    if !results.is_empty() {
+                        found.insert(name.to_string(), results);
+                    }
+                }
+                Ok::<_, DbError>(())
+            };
+            lookup(&mut found)?;
+            let files = found.values().flatten().map(|r| r.file.as_str());
+            if self.refresh_stale_files(files) > 0 {
+                found.clear();
+                lookup(&mut found)?;
+            }
+            if !paths.is_empty() {
+                for results in found.values_mut() {
+                    results.retain(|r| paths.iter().any(|p| r.file.starts_with(p)));
+                }
+            }
+        }
+
+        let missing: Vec<&str> = names
+            .iter()
+            .copied()
+            .filter(|n| !found.contains_key(*n))
+            .collect();
+        if !missing.is_empty() {
+            for reference in self.fell_back(self.query_references_named_grep(&missing, paths)) {
+                found
+                    .entry(reference.name.clone())
+                    .or_default()
+                    .push(reference);
+            }
+        }
+
+        Ok(names
+            .iter()
+            .map(|n| (n.to_string(), found.remove(*n).unwrap_or_default()))
+            .collect())
+    }
+
+    /// Grep fallback for
+    /// [`query_references_named`](Self::query_references_named): one search
+    /// for all `names`, with a reference for the first match of each name on
+    /// a line.
+    fn query_references_named_grep(&self, names: &[&str], paths: &[String]) -> Vec<Reference> {
+        let pattern = names_re(names);
+        let Ok(re) = regex::Regex::new(&pattern) else {
+            return Vec::new();
+        };
+        let search_paths = if paths.is_empty() {
+            vec![self.repo_root.to_string_lossy().into_owned()]
+        } else {
+            paths.to_vec()
+        };
+        let Ok(hits) = search::text_search(&pattern, true, false, &search_paths) else {
+            return Vec::new();
+        };
+
+        let mut references = Vec::new();
+        for hit in hits {
+            // The search reports the first match on a line; split the line
+            // into one hit per match.
+            let line_start = hit.start_byte.map(|b| b - (hit.col - 1));
+            let mut matches: Vec<search::SearchResult> = re
+                .find_iter(&hit.content)
+                .map(|m| search::SearchResult {
+                    col: m.start() as u64 + 1,
+                    end_col: Some(m.end() as u64 + 1),
+                    start_byte: line_start.map(|b| b + m.start() as u64),
+                    end_byte: line_start.map(|b| b + m.end() as u64),
+                    ..hit.clone()
+                })
+                .collect();
+            retain_whole_identifiers(&mut matches);
+            let mut seen: Vec<String> = Vec::new();
+            for m in matches {
+                let span = (m.col - 1) as usize..m.end_col.map_or(0, |c| c as usize - 1);
+                let Some(name) = m.content.get(span).map(str::to_string) else {
+                    continue;
+                };
+                if seen.contains(&name) {
+                    continue;
+                }
+                seen.push(name.clone());
+                references.push(Reference {
+                    name,
                     kind: ReferenceKind::Call,
-                    file: r.file.to_string_lossy().into_owned(),
-                    line: r.line as usize,
-                    col: r.col as usize,
-                    context: r.content.clone(),
+                    file: m.file.to_string_lossy().into_owned(),
+                    line: m.line as usize,
+                    col: m.col as usize,
+                    end_col: m.end_col.map(|v| v as usize),
+                    start_byte: m.start_byte.map(|v| v as usize),
+                    end_byte: m.end_byte.map(|v| v as usize),
+                    context: m.content,
                     caller_name: None,
                     confidence: 0.5,
-                })
-                .collect(),
-            Err(_) => Vec::new(),
+                });
+            }
         }
+        references
     }
 
     // -- Signature queries --------------------------------------------------
@@ -2824,15 +5536,18 @@ impl QueryRouter {
     /// Tries the SQLite index first; falls back to grep.
     pub fn query_signatures(&self, name: &str) -> Result<Vec<Symbol>, DbError> {
         // Try SQLite first (signatures are symbols with kind=function/method).
-        if let Some(conn) = &self.conn {
-            let results = query_signatures_db(conn, name)?;
+        if let Some(store) = self.index_store() {
+            let mut results = store.signatures(name)?;
+            if self.refresh_stale_files(results.iter().map(|s| s.file.as_str())) > 0 {
+                results = store.signatures(name)?;
+            }
             if !results.is_empty() {
                 return Ok(results);
             }
         }
 
         // Fallback to grep.
-        Ok(self.query_signatures_grep(name))
+        Ok(self.fell_back(self.query_signatures_grep(name)))
     }
 
     /// Grep-based signature search fallback.
@@ -2851,10 +5566,17 @@ impl QueryRouter {
                     line: r.line as usize,
                     col: r.col as usize,
                     end_line: None,
+                    end_col: None,
+                    start_byte: None,
+                    end_byte: None,
                     scope: None,
                     signature: r.content.clone(),
                     language: String::new(),
                     doc_comment: None,
+                    condition: None,
+                    visibility: None,
+                    modifiers: Vec::new(),
+                    generics: Vec::new(),
                 })
                 .collect(),
             Err(_) => Vec::new(),
@@ -2873,20 +5595,20 @@ impl QueryRouter {
     /// definitions.
     pub fn query_symbols_in_file(&self, path: &str, _tree: bool) -> Result<Vec<Symbol>, DbError> {
         // Try SQLite first.
-        if let Some(conn) = &self.conn {
-            let results = query_symbols_in_file_db(conn, path)?;
+        if let Some(store) = self.index_store() {
+            let results = store.symbols_in_file(path)?;
             if !results.is_empty() {
                 return Ok(results);
             }
         }
 
         // Fallback: grep for common definition patterns in the specific file.
-        Ok(self.query_symbols_in_file_grep(path))
+        Ok(self.fell_back(self.query_symbols_in_file_grep(path)))
     }
 
     /// Grep-based file symbol listing fallback.
     fn query_symbols_in_file_grep(&self, path: &str) -> Vec<Symbol> {
-        let pattern = r"(fn|pub\s+fn|pub\(crate\)\s+fn|def|function|func|class|struct|enum|trait|interface|module)\s+\w+".to_string();
+        let pattern = r"(fn|pub\s+fn|pub\(crate\)\s+fn|def|function|func|fun|class|object|record|struct|enum|trait|interface|module|namespace)\s+\w+".to_string();
         let results = search::text_search(&pattern, true, false, &[path.to_string()]);
 
         match results {
@@ -2899,10 +5621,17 @@ impl QueryRouter {
                     line: r.line as usize,
                     col: r.col as usize,
                     end_line: None,
+                    end_col: None,
+                    start_byte: None,
+                    end_byte: None,
                     scope: None,
                     signature: r.content.clone(),
                     language: String::new(),
                     doc_comment: None,
+                    condition: None,
+                    visibility: None,
+                    modifiers: Vec::new(),
+                    generics: Vec::new(),
                 })
                 .collect(),
             Err(_) => Vec::new(),
@@ -2916,15 +5645,15 @@ impl QueryRouter {
     /// Tries the SQLite index first; falls back to grep for import statements.
     pub fn query_deps(&self, file: &str) -> Result<Vec<String>, DbError> {
         // Try SQLite first.
-        if let Some(conn) = &self.conn {
-            let results = query_deps_db(conn, file)?;
+        if let Some(store) = self.index_store() {
+            let results = store.deps(file)?;
             if !results.is_empty() {
                 return Ok(results);
             }
         }
 
         // Fallback to grep for import patterns.
-        Ok(self.query_deps_grep(file))
+        Ok(self.fell_back(self.query_deps_grep(file)))
     }
 
     /// Grep-based dependency search fallback.
@@ -2947,17 +5676,19 @@ impl QueryRouter {
     ///
     /// Tries the SQLite index first; falls back to grep for import statements
     /// mentioning the file's name.
-    pub fn query_rdeps(&self, file: &str) -> Result<Vec<String>, DbError> {
-        // Try SQLite first.
-        if let Some(conn) = &self.conn {
-            let results = query_rdeps_db(conn, file)?;
-            if !results.is_empty() {
+    pub fn query_rdeps(&self, file: &str, fuzzy: bool) -> Result<Vec<String>, DbError> {
+        // Try SQLite first.  Resolved edges are authoritative: an empty
+        // result means nothing imports the file, so only fall back to grep
+        // when there is no index or a fuzzy match was asked for.
+        if let Some(store) = self.index_store() {
+            let results = store.rdeps(file, fuzzy)?;
+            if !results.is_empty() || !fuzzy {
                 return Ok(results);
             }
         }
 
         // Fallback to grep: search for imports mentioning this file's stem.
-        Ok(self.query_rdeps_grep(file))
+        Ok(self.fell_back(self.query_rdeps_grep(file)))
     }
 
     /// Grep-based reverse dependency search fallback.
@@ -3020,13 +5751,17 @@ pub fn query_symbols_db_with_filters(
     exact: bool,
 ) -> Result<Vec<Symbol>, DbError> {
     let mut sql = String::from(
-        "SELECT name, kind, file, line, col, end_line, scope, signature, language FROM symbols WHERE ",
+        "SELECT name, kind, file, line, col, end_line, scope, signature, language, condition, visibility, modifiers, generics, end_col, start_byte, end_byte FROM symbols WHERE ",
     );
     let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
 
     if exact {
         sql.push_str("name = ?");
         params.push(Box::new(name.to_string()));
+    } else if name.chars().count() >= db::NAME_TRIGRAM_MIN_LEN && db::has_symbol_name_index(conn) {
+        // Substring match answered by the trigram index rather than a scan.
+        sql.push_str("id IN (SELECT rowid FROM symbol_names WHERE name LIKE ?)");
+        params.push(Box::new(format!("%{}%", name)));
     } else {
         sql.push_str("name LIKE ?");
         params.push(Box::new(format!("%{}%", name)));
@@ -3066,9 +5801,57 @@ pub fn query_symbols_db_with_filters(
     Ok(results)
 }
 
+/// Maximum names bound into one `IN (...)` list, kept well under SQLite's
+/// host parameter limit.
+const NAMED_QUERY_CHUNK: usize = 500;
+
+/// Query symbols named exactly any of `names` with one `IN (...)` query per
+/// chunk of names.
+pub fn query_symbols_named_db(
+    conn: &Connection,
+    names: &[&str],
+    kind: Option<&str>,
+    file: Option<&str>,
+) -> Result<Vec<Symbol>, DbError> {
+    let mut results: Vec<Symbol> = Vec::new();
+    for chunk in names.chunks(NAMED_QUERY_CHUNK) {
+        let mut sql = format!(
+            "SELECT name, kind, file, line, col, end_line, scope, signature, language, condition, visibility, modifiers, generics, end_col, start_byte, end_byte FROM symbols WHERE name IN ({})",
+            vec!["?"; chunk.len()].join(", ")
+        );
+        let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = chunk
+            .iter()
+            .map(|n| Box::new(n.to_string()) as Box<dyn rusqlite::types::ToSql>)
+            .collect();
+
+        if let Some(k) = kind {
+            sql.push_str(" AND kind = ?");
+            params.push(Box::new(k.to_string()));
+        }
+
+        if let Some(f) = file {
+            sql.push_str(" AND file LIKE ?");
+            params.push(Box::new(format!("%{}%", f)));
+        }
+
+        let param_refs: Vec<&dyn rusqlite::types::ToSql> =
+            params.iter().map(|p| p.as_ref()).collect();
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(param_refs), row_to_symbol)?;
+        for row in rows {
+            results.push(row?);
+        }
+    }
+
+    // Deprioritize test/bench/spec files: sort them after production code.
+    results.sort_by_key(|s| is_test_path(&s.file));
+
+    Ok(results)
+}
+
 /// Query references from the SQLite index.
 pub fn query_references_db(conn: &Connection, name: &str) -> Result<Vec<Reference>, DbError> {
-    let sql = "SELECT r.name, r.file, r.line, r.col, r.context, s.name, r.confidence \
+    let sql = "SELECT r.name, r.file, r.line, r.col, r.context, s.name, r.confidence, r.end_col, r.start_byte, r.end_byte, r.kind \
                FROM \"references\" r \
                LEFT JOIN symbols s ON r.caller_id = s.id \
                WHERE r.name = ?1";
@@ -3079,10 +5862,17 @@ pub fn query_references_db(conn: &Connection, name: &str) -> Result<Vec<Referenc
         let col: i64 = row.get(3)?;
         Ok(Reference {
             name: row.get(0)?,
-            kind: ReferenceKind::Call,
+            // Indexes built before kinds were stored read as calls.
+            kind: row
+                .get::<_, Option<String>>(10)?
+                .and_then(|k| k.parse().ok())
+                .unwrap_or(ReferenceKind::Call),
             file: row.get(1)?,
             line: line as usize,
             col: col as usize,
+            end_col: row.get::<_, Option<i64>>(7)?.map(|v| v as usize),
+            start_byte: row.get::<_, Option<i64>>(8)?.map(|v| v as usize),
+            end_byte: row.get::<_, Option<i64>>(9)?.map(|v| v as usize),
             context: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
             caller_name: row.get(5)?,
             confidence: row.get::<_, Option<f64>>(6)?.unwrap_or(0.5),
@@ -3098,7 +5888,7 @@ pub fn query_references_db(conn: &Connection, name: &str) -> Result<Vec<Referenc
 
 /// Query subclasses/implementors of a symbol via the type_edges table.
 pub fn query_subclasses_db(conn: &Connection, name: &str) -> Result<Vec<Symbol>, DbError> {
-    let sql = "SELECT s.name, s.kind, s.file, s.line, s.col, s.end_line, s.scope, s.signature, s.language \
+    let sql = "SELECT s.name, s.kind, s.file, s.line, s.col, s.end_line, s.scope, s.signature, s.language, s.condition, s.visibility, s.modifiers, s.generics, s.end_col, s.start_byte, s.end_byte \
                FROM type_edges te \
                JOIN symbols parent ON te.parent_id = parent.id \
                JOIN symbols s ON te.child_id = s.id \
@@ -3117,7 +5907,7 @@ pub fn query_subclasses_db(conn: &Connection, name: &str) -> Result<Vec<Symbol>,
 
 /// Query function/method signatures from the SQLite index.
 pub fn query_signatures_db(conn: &Connection, name: &str) -> Result<Vec<Symbol>, DbError> {
-    let sql = "SELECT name, kind, file, line, col, end_line, scope, signature, language \
+    let sql = "SELECT name, kind, file, line, col, end_line, scope, signature, language, condition, visibility, modifiers, generics, end_col, start_byte, end_byte \
                FROM symbols WHERE name LIKE ?1 AND kind IN ('function', 'method')";
     let name_param = format!("%{}%", name);
     let mut stmt = conn.prepare_cached(sql)?;
@@ -3131,9 +5921,66 @@ pub fn query_signatures_db(conn: &Connection, name: &str) -> Result<Vec<Symbol>,
     Ok(results)
 }
 
+/// Whether a symbol's preprocessor condition satisfies a `--condition`
+/// filter: `none` keeps unconditional symbols, anything else is a substring
+/// of the condition (`CONFIG_ARM` matches `defined(CONFIG_ARM) && DEBUG`).
+fn matches_condition(condition: Option<&str>, filter: &str) -> bool {
+    match (condition, filter) {
+        (None, "none") => true,
+        (Some(c), f) => f != "none" && c.contains(f),
+        (None, _) => false,
+    }
+}
+
+/// Whether a symbol's generic parameters satisfy a `--generic` filter.
+///
+/// A number matches the parameter count; `T` matches a parameter named `T`;
+/// `T: Read + Send` additionally requires each bound term to appear among
+/// the parameter's bounds.  An empty name (`: Read`) matches any parameter.
+fn matches_generic(generics: &[String], filter: &str) -> bool {
+    let filter = filter.trim();
+    if let Ok(arity) = filter.parse::<usize>() {
+        return generics.len() == arity;
+    }
+    let (name, bound) = match filter.split_once(':') {
+        Some((name, bound)) => (name.trim(), Some(bound)),
+        None => (filter, None),
+    };
+    generics.iter().any(|param| {
+        let param = param.split(" = ").next().unwrap_or(param);
+        let (param_name, param_bounds) = param.split_once(": ").unwrap_or((param, ""));
+        // Value parameters (`const N`, `int N`) are named by their last word.
+        let param_name = param_name.rsplit(' ').next().unwrap_or(param_name);
+        (name.is_empty() || param_name == name)
+            && bound.is_none_or(|b| {
+                b.split('+')
+                    .map(str::trim)
+                    .all(|term| param_bounds.contains(term))
+            })
+    })
+}
+
+/// Query symbols by their stable ID (see [`Symbol::stable_id`]).
+///
+/// Several rows can share an ID when identical signatures are defined in
+/// more than one file (e.g. per-platform implementations).
+pub fn query_symbols_by_stable_id_db(conn: &Connection, id: &str) -> Result<Vec<Symbol>, DbError> {
+    let sql = "SELECT name, kind, file, line, col, end_line, scope, signature, language, condition, visibility, modifiers, generics, end_col, start_byte, end_byte \
+               FROM symbols WHERE stable_id = ?1 ORDER BY file, line";
+    let mut stmt = conn.prepare_cached(sql)?;
+
+    let rows = stmt.query_map(rusqlite::params![id], row_to_symbol)?;
+
+    let mut results = Vec::new();
+    for row in rows {
+        results.push(row?);
+    }
+    Ok(results)
+}
+
 /// Query all symbols in a specific file from the SQLite index.
 pub fn query_symbols_in_file_db(conn: &Connection, path: &str) -> Result<Vec<Symbol>, DbError> {
-    let sql = "SELECT name, kind, file, line, col, end_line, scope, signature, language \
+    let sql = "SELECT name, kind, file, line, col, end_line, scope, signature, language, condition, visibility, modifiers, generics, end_col, start_byte, end_byte \
                FROM symbols WHERE file = ?1 ORDER BY line";
     let mut stmt = conn.prepare_cached(sql)?;
 
@@ -3150,47 +5997,127 @@ pub fn query_symbols_in_file_db(conn: &Connection, path: &str) -> Result<Vec<Sym
 ///
 /// Returns the list of import paths for the given source file.
 pub fn query_deps_db(conn: &Connection, file: &str) -> Result<Vec<String>, DbError> {
-    let sql = "SELECT DISTINCT import_path FROM file_imports WHERE source_file = ?1";
+    if let Some((pkg, files)) = go_package(conn, file)? {
+        // Go dependencies are per package: the imports of all its files.
+        let mut results = Vec::new();
+        for source in &files {
+            results.extend(query_file_deps(conn, source)?);
+        }
+        results.retain(|dep| *dep != pkg);
+        results.sort();
+        results.dedup();
+        return Ok(results);
+    }
+    query_file_deps(conn, file)
+}
+
+fn query_file_deps(conn: &Connection, file: &str) -> Result<Vec<String>, DbError> {
+    let sql = "SELECT DISTINCT COALESCE(resolved_file, import_path) FROM file_imports \
+               WHERE source_file = ?1";
     let mut stmt = conn.prepare_cached(sql)?;
 
     let rows = stmt.query_map(rusqlite::params![file], |row| row.get::<_, String>(0))?;
 
+    // Ruby constants recorded for Rails autoloading only count when they
+    // resolved to a file; the rest (`String`, `ActiveRecord::Base`) are noise.
+    let rails_constants = file.ends_with(".rb");
     let mut results = Vec::new();
     for row in rows {
-        results.push(row?);
+        let dep = row?;
+        if rails_constants && crate::imports::is_ruby_constant(&dep) {
+            continue;
+        }
+        results.push(dep);
     }
     Ok(results)
 }
 
 /// Query reverse dependencies from the `file_imports` table.
 ///
-/// Finds all files whose import paths contain the target file's stem
-/// (e.g. searching for "utils.ts" matches imports like "./utils",
-/// "../utils", "utils" etc.).
-pub fn query_rdeps_db(conn: &Connection, file: &str) -> Result<Vec<String>, DbError> {
-    let stem = Path::new(file)
-        .file_stem()
-        .map(|s| s.to_string_lossy().into_owned())
-        .unwrap_or_else(|| file.to_string());
-
-    let sql = "SELECT DISTINCT source_file FROM file_imports \
-               WHERE import_path LIKE ?1 AND source_file != ?2";
-    let stem_param = format!("%{}", stem);
-    let mut stmt = conn.prepare_cached(sql)?;
-
-    let rows = stmt.query_map(rusqlite::params![stem_param, file], |row| {
-        row.get::<_, String>(0)
-    })?;
+/// Finds all files with an import resolved to `file`.  With `fuzzy`, also
+/// includes files whose *unresolved* import paths end in the target file's
+/// stem (e.g. "utils.ts" matches "./utils", "../utils", "utils"), for
+/// imports the resolver could not map to a file.
+pub fn query_rdeps_db(conn: &Connection, file: &str, fuzzy: bool) -> Result<Vec<String>, DbError> {
+    if let Some((pkg, _)) = go_package(conn, file)? {
+        // Go imports name packages, so importers are reported as packages.
+        let mut stmt = conn.prepare_cached(
+            "SELECT DISTINCT source_file FROM file_imports WHERE resolved_file = ?1",
+        )?;
+        let mut results = Vec::new();
+        for row in stmt.query_map(rusqlite::params![pkg], |row| row.get::<_, String>(0))? {
+            let importer = crate::imports::package_dir(&row?).to_string();
+            if importer != pkg {
+                results.push(importer);
+            }
+        }
+        results.sort();
+        results.dedup();
+        return Ok(results);
+    }
 
     let mut results = Vec::new();
-    for row in rows {
+
+    let mut stmt = conn.prepare_cached(
+        "SELECT DISTINCT source_file FROM file_imports \
+         WHERE resolved_file = ?1 AND source_file != ?1",
+    )?;
+    for row in stmt.query_map(rusqlite::params![file], |row| row.get::<_, String>(0))? {
         results.push(row?);
     }
+
+    // A C/C++ header's implementation files depend on it by convention,
+    // even when they reach it through another header.
+    if crate::imports::ImportResolver::is_header(file) {
+        results.extend(crate::imports::ImportResolver::load(conn, None)?.paired_sources(file));
+    }
+
+    if fuzzy {
+        let stem = Path::new(file)
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| file.to_string());
+        let mut stmt = conn.prepare_cached(
+            "SELECT DISTINCT source_file FROM file_imports \
+             WHERE resolved_file IS NULL AND import_path LIKE ?1 AND source_file != ?2",
+        )?;
+        let stem_param = format!("%{}", stem);
+        for row in stmt.query_map(rusqlite::params![stem_param, file], |row| {
+            row.get::<_, String>(0)
+        })? {
+            results.push(row?);
+        }
+    }
+
     results.sort();
     results.dedup();
     Ok(results)
 }
 
+/// The Go package `target` belongs to, with the package's indexed files.
+///
+/// `target` is either a `.go` file or a package directory (`.` for the repo
+/// root).  Returns `None` for anything that is not Go code.
+fn go_package(conn: &Connection, target: &str) -> Result<Option<(String, Vec<String>)>, DbError> {
+    let pkg = if target.ends_with(".go") {
+        crate::imports::package_dir(target).to_string()
+    } else {
+        match target.trim_end_matches('/') {
+            "" | "./" => ".".to_string(),
+            dir => dir.strip_prefix("./").unwrap_or(dir).to_string(),
+        }
+    };
+    let mut stmt = conn.prepare_cached("SELECT path FROM files WHERE path LIKE '%.go'")?;
+    let mut files = Vec::new();
+    for row in stmt.query_map([], |row| row.get::<_, String>(0))? {
+        let path = row?;
+        if crate::imports::package_dir(&path) == pkg {
+            files.push(path);
+        }
+    }
+    Ok((!files.is_empty()).then_some((pkg, files)))
+}
+
 /// Convert a rusqlite row to a `Symbol`.
 fn row_to_symbol(row: &rusqlite::Row) -> rusqlite::Result<Symbol> {
     let kind_str: String = row.get(1)?;
@@ -3204,10 +6131,23 @@ fn row_to_symbol(row: &rusqlite::Row) -> rusqlite::Result<Symbol> {
         line: line as usize,
         col: col as usize,
         end_line: end_line.map(|v| v as usize),
+        end_col: row.get::<_, Option<i64>>(13)?.map(|v| v as usize),
+        start_byte: row.get::<_, Option<i64>>(14)?.map(|v| v as usize),
+        end_byte: row.get::<_, Option<i64>>(15)?.map(|v| v as usize),
         scope: row.get(6)?,
         signature: row.get::<_, Option<String>>(7)?.unwrap_or_default(),
         language: row.get(8)?,
         doc_comment: None,
+        condition: row.get(9)?,
+        visibility: row.get(10)?,
+        modifiers: row
+            .get::<_, Option<String>>(11)?
+            .map(|m| m.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default(),
+        generics: row
+            .get::<_, Option<String>>(12)?
+            .map(|g| g.lines().map(str::to_string).collect())
+            .unwrap_or_default(),
     })
 }
 
@@ -3225,12 +6165,16 @@ fn extract_symbol_name(content: &str) -> String {
         "def",
         "function",
         "func",
+        "fun",
         "class",
+        "object",
+        "record",
         "struct",
         "enum",
         "trait",
         "interface",
         "module",
+        "namespace",
     ];
 
     let tokens: Vec<&str> = content.split_whitespace().collect();
@@ -3292,24 +6236,147 @@ mod tests {
     }
 
     #[test]
-    fn test_symbol_kind_grep_pattern_function() {
-        let pat = symbol_kind_grep_pattern("handler", "function");
-        assert!(pat.contains("fn"));
-        assert!(pat.contains("def"));
-        assert!(pat.contains("function"));
-        assert!(pat.contains("func"));
-        assert!(pat.contains("handler"));
-        // Should NOT contain class/struct etc.
-        assert!(!pat.contains("class"));
+    fn test_symbol_kind_grep_pattern_function() {
+        let pat = symbol_kind_grep_pattern("handler", "function");
+        assert!(pat.contains("fn"));
+        assert!(pat.contains("def"));
+        assert!(pat.contains("function"));
+        assert!(pat.contains("func"));
+        assert!(pat.contains("handler"));
+        // Should NOT contain class/struct etc.
+        assert!(!pat.contains("class"));
+    }
+
+    #[test]
+    fn test_symbol_kind_grep_pattern_class() {
+        let pat = symbol_kind_grep_pattern("MyClass", "class");
+        assert!(pat.contains("class"));
+        assert!(pat.contains("MyClass"));
+        // Should NOT contain function keywords.
+        assert!(!pat.contains("def"));
+    }
+
+    #[test]
+    fn test_symbol_grep_pattern_matches_elixir_definitions() {
+        let matches = |pat: String, line: &str| regex::Regex::new(&pat).unwrap().is_match(line);
+        assert!(matches(
+            symbol_grep_pattern("compute"),
+            "  defp compute(r) do"
+        ));
+        assert!(matches(
+            symbol_kind_grep_pattern("unit", "function"),
+            "  defmacrop unit do"
+        ));
+        assert!(matches(
+            symbol_kind_grep_pattern("Shapes.Circle", "module"),
+            "defmodule Shapes.Circle do"
+        ));
+        assert!(matches(
+            import_grep_pattern("Shapes.Point"),
+            "  alias Shapes.Point"
+        ));
+    }
+
+    #[test]
+    fn test_symbol_grep_pattern_matches_shell_functions() {
+        let matches = |pat: String, line: &str| regex::Regex::new(&pat).unwrap().is_match(line);
+        assert!(matches(symbol_grep_pattern("deploy"), "deploy() {"));
+        assert!(matches(
+            symbol_kind_grep_pattern("deploy", "function"),
+            "function deploy {"
+        ));
+        assert!(!matches(symbol_grep_pattern("deploy"), "  deploy"));
+        assert!(!matches(
+            symbol_kind_grep_pattern("deploy", "class"),
+            "deploy() {"
+        ));
+        assert!(matches(
+            import_grep_pattern("common.sh"),
+            "source ./lib/common.sh"
+        ));
+
+        let re = regex::Regex::new(&symbols_grep_pattern(&["build", "deploy"], None)).unwrap();
+        let caps = re.captures("  deploy() {").unwrap();
+        assert_eq!(
+            caps.get(2).or_else(|| caps.get(3)).unwrap().as_str(),
+            "deploy"
+        );
+    }
+
+    #[test]
+    fn test_symbol_grep_pattern_matches_sql_definitions() {
+        let matches = |pat: String, line: &str| regex::Regex::new(&pat).unwrap().is_match(line);
+        assert!(matches(
+            symbol_grep_pattern("users"),
+            "CREATE TABLE users ("
+        ));
+        assert!(matches(
+            symbol_grep_pattern("users"),
+            "create table if not exists users (id int);"
+        ));
+        assert!(matches(
+            symbol_grep_pattern("active_users"),
+            "CREATE OR REPLACE VIEW active_users AS"
+        ));
+        assert!(matches(
+            symbol_grep_pattern("idx_email"),
+            "CREATE UNIQUE INDEX idx_email ON users (email);"
+        ));
+        assert!(!matches(
+            symbol_grep_pattern("users"),
+            "SELECT * FROM users"
+        ));
     }
 
     #[test]
-    fn test_symbol_kind_grep_pattern_class() {
-        let pat = symbol_kind_grep_pattern("MyClass", "class");
-        assert!(pat.contains("class"));
-        assert!(pat.contains("MyClass"));
-        // Should NOT contain function keywords.
-        assert!(!pat.contains("def"));
+    fn test_symbol_grep_pattern_matches_dart_definitions() {
+        let matches = |pat: String, line: &str| regex::Regex::new(&pat).unwrap().is_match(line);
+        assert!(matches(
+            symbol_kind_grep_pattern("Named", "trait"),
+            "mixin Named on Base {"
+        ));
+        assert!(matches(
+            symbol_kind_grep_pattern("IntShapes", "module"),
+            "extension IntShapes on int {"
+        ));
+        assert!(matches(
+            symbol_grep_pattern("Predicate"),
+            "typedef Predicate<T> = bool Function(T value);"
+        ));
+        assert!(matches(
+            import_grep_pattern("shapes.g.dart"),
+            "part 'shapes.g.dart';"
+        ));
+    }
+
+    #[test]
+    fn test_symbol_grep_pattern_matches_csharp_definitions() {
+        let matches = |pat: String, line: &str| regex::Regex::new(&pat).unwrap().is_match(line);
+        assert!(matches(
+            symbol_kind_grep_pattern("Add", "method"),
+            "    public void Add(T item)"
+        ));
+        assert!(matches(
+            symbol_kind_grep_pattern("Load", "method"),
+            "    protected static async Task<List<int>> Load()"
+        ));
+        assert!(matches(
+            symbol_kind_grep_pattern("Person", "class"),
+            "public record Person(string Name);"
+        ));
+        assert!(matches(
+            symbol_kind_grep_pattern("App", "module"),
+            "namespace App.Services;"
+        ));
+        assert!(matches(
+            symbol_grep_pattern("Name"),
+            "    public string Name { get; set; }"
+        ));
+        // A call is not a definition.
+        assert!(!matches(
+            symbol_kind_grep_pattern("Add", "method"),
+            "        list.Add(item);"
+        ));
     }
 
     #[test]
@@ -3321,6 +6388,10 @@ mod tests {
         assert!(pat.contains("use"));
         assert!(pat.contains("include"));
         assert!(pat.contains("utils"));
+        // Call-style `require("utils")` (Lua, CommonJS) as well.
+        let re = regex::Regex::new(&pat).unwrap();
+        assert!(re.is_match(r#"local u = require("app.utils")"#));
+        assert!(re.is_match("require 'utils'"));
     }
 
     #[test]
@@ -3341,6 +6412,61 @@ mod tests {
         assert!(pat.contains(r"\("));
     }
 
+    #[test]
+    fn test_identifier_patterns_handle_unicode_and_sigils() {
+        let matches =
+            |pattern: String, line: &str| regex::Regex::new(&pattern).unwrap().is_match(line);
+        assert!(matches(reference_grep_pattern("größe"), "let g = größe;"));
+        assert!(!matches(reference_grep_pattern("größe"), "let g = größeX;"));
+        assert!(!matches(reference_grep_pattern("größe"), "let g = Xgröße;"));
+        assert!(matches(reference_grep_pattern("$price"), "echo $price;"));
+        assert!(!matches(
+            reference_grep_pattern("$price"),
+            "$priceTotal = 1;"
+        ));
+        assert!(matches(
+            reference_grep_pattern("#secret"),
+            "this.#secret = 1;"
+        ));
+        assert!(matches(symbol_grep_pattern("empty?"), "  def empty?"));
+        assert!(matches(symbol_grep_pattern("$total"), "var $total = 0;"));
+        assert!(matches(
+            symbol_kind_grep_pattern("größe", "function"),
+            "fn größe() {}"
+        ));
+        assert!(matches(signature_grep_pattern("größe"), "fn größe(x: i32)"));
+        assert!(!matches(
+            signature_grep_pattern("größe"),
+            "fn größer(x: i32)"
+        ));
+    }
+
+    #[test]
+    fn test_retain_whole_identifiers_uses_language_identifier_chars() {
+        let hit = |file: &str, content: &str, needle: &str| {
+            let start = content.find(needle).unwrap();
+            search::SearchResult {
+                file: PathBuf::from(file),
+                line: 1,
+                col: start as u64 + 1,
+                end_col: Some((start + needle.len()) as u64 + 1),
+                start_byte: None,
+                end_byte: None,
+                content: content.to_string(),
+            }
+        };
+        let mut hits = vec![
+            hit("app.js", "const a$price = 1;", "price"),
+            hit("app.js", "total(price);", "price"),
+            hit("shop.php", "$price = 1;", "price"),
+            hit("list.rb", "items.empty?", "empty"),
+            hit("list.py", "items.empty", "empty"),
+        ];
+        retain_whole_identifiers(&mut hits);
+        let kept: Vec<&str> = hits.iter().map(|h| h.content.as_str()).collect();
+        assert_eq!(kept, ["total(price);", "$price = 1;", "items.empty"]);
+    }
+
     #[test]
     fn test_regex_escape() {
         assert_eq!(regex_escape("hello"), "hello");
@@ -3383,6 +6509,167 @@ mod tests {
         assert!(!router.has_index());
     }
 
+    #[test]
+    fn test_fallback_keywords_replace_builtins_per_language() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("ops.rs"),
+            "define_op!  resize {}\nfn resize() {}\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("util.py"), "def resize():\n").unwrap();
+        fs::write(dir.path().join("build.dsl"), "task resize:\n").unwrap();
+
+        let mut config = crate::config::FallbackConfig::default();
+        for (key, words) in [("rust", &["define_op!"][..]), ("dsl", &["task"][..])] {
+            config.languages.insert(
+                key.to_string(),
+                crate::config::FallbackLanguageConfig {
+                    symbol_keywords: Some(words.iter().map(|w| w.to_string()).collect()),
+                },
+            );
+        }
+        let mut router = QueryRouter::grep_only(dir.path().to_path_buf());
+        router.fallback_keywords = FallbackKeywords::from_config(&config);
+
+        let found = |symbols: Vec<Symbol>| {
+            let mut found: Vec<String> = symbols
+                .iter()
+                .map(|s| {
+                    format!(
+                        "{}:{}",
+                        Path::new(&s.file).file_name().unwrap().display(),
+                        s.line
+                    )
+                })
+                .collect();
+            found.sort();
+            found
+        };
+        // Rust files take the configured keyword only, `.dsl` files (an
+        // extension wonk does not parse) theirs, Python the built-ins.
+        assert_eq!(
+            found(router.query_symbols_grep("resize", None)),
+            ["build.dsl:1", "ops.rs:1", "util.py:1"]
+        );
+        let named = router.query_symbols_named_grep(&["resize"], None);
+        assert_eq!(found(named), ["build.dsl:1", "ops.rs:1", "util.py:1"]);
+        // A kind query keeps to the built-in keywords for that kind.
+        assert_eq!(
+            found(router.query_symbols_grep("resize", Some("function"))),
+            ["util.py:1"]
+        );
+    }
+
+    #[test]
+    fn test_grep_fallback_promotes_definition_files_when_lazy() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join("ops.rs"), "fn resize() {}\n").unwrap();
+        fs::write(dir.path().join("other.rs"), "fn shrink() {}\n").unwrap();
+
+        let mut router = QueryRouter::grep_only(dir.path().to_path_buf());
+        router.promote = true;
+        router.local = true;
+        assert_eq!(router.query_symbols_grep("resize", None).len(), 1);
+
+        // Only the file the definition was found in is indexed, and the
+        // index is marked partial.
+        let index = db::local_index_path(dir.path());
+        let conn = db::open_existing(&index).unwrap();
+        let files: Vec<String> = conn
+            .prepare("SELECT path FROM files")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(files, vec!["ops.rs".to_string()]);
+        assert!(db::read_meta(&index).unwrap().partial);
+        let found = QueryRouter::with_conn(conn, dir.path().to_path_buf())
+            .query_symbols("resize", None, false)
+            .unwrap();
+        assert_eq!(found[0].end_line, Some(1), "answered from the index");
+    }
+
+    /// A store over a fixed list of symbols, standing in for a non-SQLite
+    /// backend.
+    struct FixedStore(Vec<Symbol>);
+
+    impl IndexStore for FixedStore {
+        fn symbols(
+            &self,
+            name: &str,
+            _kind: Option<&str>,
+            _file: Option<&str>,
+            exact: bool,
+        ) -> Result<Vec<Symbol>, DbError> {
+            Ok(self
+                .0
+                .iter()
+                .filter(|s| {
+                    if exact {
+                        s.name == name
+                    } else {
+                        s.name.contains(name)
+                    }
+                })
+                .cloned()
+                .collect())
+        }
+        fn references(&self, _name: &str) -> Result<Vec<Reference>, DbError> {
+            Ok(Vec::new())
+        }
+        fn signatures(&self, _name: &str) -> Result<Vec<Symbol>, DbError> {
+            Ok(Vec::new())
+        }
+        fn symbols_in_file(&self, _path: &str) -> Result<Vec<Symbol>, DbError> {
+            Ok(Vec::new())
+        }
+        fn deps(&self, _file: &str) -> Result<Vec<String>, DbError> {
+            Ok(vec!["src/lib.rs".to_string()])
+        }
+        fn rdeps(&self, _file: &str, _fuzzy: bool) -> Result<Vec<String>, DbError> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[test]
+    fn test_router_answers_from_custom_store() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("main.rs"), "fn grep_only() {}\n").unwrap();
+        let symbol = Symbol {
+            name: "from_store".into(),
+            kind: SymbolKind::Function,
+            file: "virtual.rs".into(),
+            line: 1,
+            col: 0,
+            end_line: None,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
+            scope: None,
+            signature: "fn from_store()".into(),
+            language: "Rust".into(),
+            doc_comment: None,
+            condition: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
+        };
+        let router =
+            QueryRouter::with_store(Box::new(FixedStore(vec![symbol])), dir.path().to_path_buf());
+        assert!(router.has_index());
+        assert!(router.conn().is_none());
+
+        let found = router.query_symbols("from_store", None, true).unwrap();
+        assert_eq!(found[0].file, "virtual.rs");
+        assert_eq!(router.query_deps("main.rs").unwrap(), vec!["src/lib.rs"]);
+        // Misses still fall back to grep.
+        let found = router.query_symbols("grep_only", None, false).unwrap();
+        assert!(found.iter().any(|s| s.file.ends_with("main.rs")));
+    }
+
     #[test]
     fn test_router_query_symbols_grep_fallback() {
         let dir = TempDir::new().unwrap();
@@ -3398,6 +6685,7 @@ mod tests {
         let results = router.query_symbols("main", None, false).unwrap();
         assert!(!results.is_empty(), "grep fallback should find 'fn main'");
         assert!(results.iter().any(|s| s.name == "main"));
+        assert!(crate::caveats::recorded().contains(&crate::caveats::Caveat::GrepFallback));
     }
 
     #[test]
@@ -3486,7 +6774,7 @@ mod tests {
         fs::write(dir.path().join("utils.py"), "def helper():\n    pass\n").unwrap();
 
         let router = QueryRouter::grep_only(dir.path().to_path_buf());
-        let results = router.query_rdeps("utils.py").unwrap();
+        let results = router.query_rdeps("utils.py", false).unwrap();
         assert!(
             !results.is_empty(),
             "grep fallback should find files that import 'utils'"
@@ -3569,6 +6857,121 @@ mod tests {
         assert_eq!(results.len(), 2);
     }
 
+    #[test]
+    fn test_query_symbols_named_tags_index_and_grep_hits() {
+        let dir = TempDir::new().unwrap();
+        let db_dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("extra.py"),
+            "def fallback_only():\n    pass\n",
+        )
+        .unwrap();
+        let conn = db::open(&db_dir.path().join("index.db")).unwrap();
+        for (name, line) in [("open", 1), ("open_all", 2), ("close", 3)] {
+            conn.execute(
+                "INSERT INTO symbols (name, kind, file, line, col, language) \
+                 VALUES (?1, 'function', 'lib.rs', ?2, 0, 'rust')",
+                rusqlite::params![name, line],
+            )
+            .unwrap();
+        }
+
+        let hits = query_symbols_named_db(&conn, &["open", "close"], None, None).unwrap();
+        assert_eq!(hits.len(), 2, "names match exactly, not as substrings");
+
+        let router = QueryRouter::with_conn(conn, dir.path().to_path_buf());
+        let groups = router
+            .query_symbols_named(&["close", "fallback_only", "missing", "open"], None, None)
+            .unwrap();
+        let names: Vec<&str> = groups.iter().map(|(q, _)| q.as_str()).collect();
+        assert_eq!(names, ["close", "fallback_only", "missing", "open"]);
+        assert_eq!(groups[0].1[0].line, 3);
+        assert_eq!(groups[1].1.len(), 1);
+        assert_eq!(groups[1].1[0].name, "fallback_only");
+        assert!(groups[1].1[0].file.ends_with("extra.py"));
+        assert!(groups[2].1.is_empty());
+        assert_eq!(groups[3].1[0].name, "open");
+    }
+
+    #[test]
+    fn test_query_references_named_greps_missing_names_once() {
+        let dir = TempDir::new().unwrap();
+        let db_dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("app.py"),
+            "total = price(1) + tax(price(2))\nprices = 3\n",
+        )
+        .unwrap();
+        let conn = db::open(&db_dir.path().join("index.db")).unwrap();
+        conn.execute(
+            "INSERT INTO \"references\" (name, file, line, col, context) \
+             VALUES ('open', 'lib.rs', 4, 1, 'open()')",
+            [],
+        )
+        .unwrap();
+
+        let router = QueryRouter::with_conn(conn, dir.path().to_path_buf());
+        let groups = router
+            .query_references_named(&["open", "price", "tax", "missing"], &[])
+            .unwrap();
+        let names: Vec<&str> = groups.iter().map(|(q, _)| q.as_str()).collect();
+        assert_eq!(names, ["open", "price", "tax", "missing"]);
+        assert_eq!(groups[0].1[0].file, "lib.rs");
+        // One reference per name and line, from the same line of grep output.
+        assert_eq!(groups[1].1.len(), 1);
+        assert_eq!(groups[1].1[0].col, 9);
+        assert_eq!(groups[2].1.len(), 1);
+        assert_eq!(groups[2].1[0].name, "tax");
+        assert_eq!(groups[2].1[0].col, 20);
+        assert_eq!(groups[2].1[0].start_byte, Some(19));
+        assert!(groups[3].1.is_empty());
+    }
+
+    #[test]
+    fn test_symbols_grep_pattern_captures_queried_name() {
+        let pattern = symbols_grep_pattern(&["get", "a.b"], Some("function"));
+        let re = regex::Regex::new(&pattern).unwrap();
+        let caps = re.captures("pub fn get(&self)").unwrap();
+        assert_eq!(&caps[2], "get");
+        assert!(re.captures("fn aXb()").is_none());
+        assert_eq!(
+            symbol_grep_pattern("x"),
+            definition_pattern(None, &identifier_re("x"), &[]),
+            "single-name patterns are unchanged"
+        );
+    }
+
+    #[test]
+    fn test_query_symbols_db_substring_uses_name_index() {
+        let dir = TempDir::new().unwrap();
+        let conn = db::open(&dir.path().join("index.db")).unwrap();
+        for (name, line) in [("parseConfig", 1), ("ConfigParser", 2), ("id", 3)] {
+            conn.execute(
+                "INSERT INTO symbols (name, kind, file, line, col, language) \
+                 VALUES (?1, 'function', 'lib.rs', ?2, 0, 'rust')",
+                rusqlite::params![name, line],
+            )
+            .unwrap();
+        }
+
+        // Case-insensitive substring, same as the LIKE scan it replaces.
+        let hits = query_symbols_db(&conn, "config", None, false).unwrap();
+        assert_eq!(hits.len(), 2);
+
+        // Patterns shorter than a trigram fall back to the scan.
+        let hits = query_symbols_db(&conn, "id", None, false).unwrap();
+        assert_eq!(hits.len(), 1);
+
+        let plan: Vec<String> = conn
+            .prepare("EXPLAIN QUERY PLAN SELECT rowid FROM symbol_names WHERE name LIKE '%config%'")
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(3))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert!(plan.iter().any(|d| d.contains("VIRTUAL TABLE INDEX")));
+    }
+
     #[test]
     fn test_router_query_symbols_from_db_with_kind() {
         let dir = TempDir::new().unwrap();
@@ -3832,6 +7235,303 @@ mod tests {
         );
     }
 
+    #[test]
+    fn query_refreshes_modified_file() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::write(root.join("lib.rs"), "fn alpha() {}\n").unwrap();
+        pipeline::build_index(root, true).unwrap();
+
+        fs::write(root.join("lib.rs"), "// moved\n\nfn alpha() {}\n").unwrap();
+
+        let conn = db::open_existing(&db::local_index_path(root)).unwrap();
+        let mut router = QueryRouter::with_conn(conn, root.to_path_buf());
+        router.set_refresh_limit(4);
+        let results = router.query_symbols("alpha", None, true).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].line, 3, "line should reflect the edited file");
+    }
+
+    #[test]
+    fn verify_relocates_subclasses_with_references() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::write(
+            root.join("shapes.py"),
+            "class Shape:\n    pass\n\nclass Square(Shape):\n    pass\n",
+        )
+        .unwrap();
+        pipeline::build_index(root, true).unwrap();
+        fs::write(
+            root.join("shapes.py"),
+            "# moved\n\nclass Shape:\n    pass\n\nclass Square(Shape):\n    pass\n",
+        )
+        .unwrap();
+
+        let conn = db::open_existing(&db::local_index_path(root)).unwrap();
+        let mut subclasses = query_subclasses_db(&conn, "Shape").unwrap();
+        let mut references = query_references_db(&conn, "Shape").unwrap();
+        assert_eq!(subclasses.len(), 1);
+        let (subclass_freshness, freshness) =
+            verify_references(Some(&conn), root, &mut subclasses, &mut references, true);
+        assert_eq!(subclass_freshness, [Some("relocated".to_string())]);
+        assert_eq!(subclasses[0].line, 6);
+        assert_eq!(freshness.len(), references.len());
+    }
+
+    #[test]
+    fn query_records_stale_files_it_does_not_refresh() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::write(root.join("stale_a.rs"), "fn beta() {}\n").unwrap();
+        fs::write(root.join("stale_b.rs"), "fn beta() {}\n").unwrap();
+        pipeline::build_index(root, true).unwrap();
+
+        let conn = db::open_existing(&db::local_index_path(root)).unwrap();
+        // As if both files were edited after the index was built.
+        conn.execute("UPDATE files SET last_indexed = 0", [])
+            .unwrap();
+        let mut router = QueryRouter::with_conn(conn, root.to_path_buf());
+        router.set_refresh_limit(1);
+        let results = router.query_symbols("beta", None, true).unwrap();
+        assert_eq!(results.len(), 2);
+        // One file is refreshed (unchanged content), the other is left stale.
+        let stale = crate::caveats::stale_files();
+        assert_eq!(stale.iter().filter(|f| f.starts_with("stale_")).count(), 1);
+    }
+
+    #[test]
+    fn matches_condition_filters() {
+        assert!(matches_condition(
+            Some("defined(CONFIG_ARM) && DEBUG"),
+            "CONFIG_ARM"
+        ));
+        assert!(!matches_condition(
+            Some("defined(CONFIG_X86)"),
+            "CONFIG_ARM"
+        ));
+        assert!(!matches_condition(None, "CONFIG_ARM"));
+        assert!(matches_condition(None, "none"));
+        assert!(!matches_condition(Some("defined(CONFIG_ARM)"), "none"));
+    }
+
+    #[test]
+    fn matches_generic_filters() {
+        let generics = vec!["T: Read + Send".to_string(), "U = i32".to_string()];
+        assert!(matches_generic(&generics, "2"));
+        assert!(!matches_generic(&generics, "1"));
+        assert!(matches_generic(&generics, "U"));
+        assert!(matches_generic(&generics, "T: Read"));
+        assert!(matches_generic(&generics, "T: Send + Read"));
+        assert!(matches_generic(&generics, ": Send"));
+        assert!(!matches_generic(&generics, "T: Write"));
+        assert!(!matches_generic(&generics, "U: Read"));
+        assert!(matches_generic(&["const N: usize".to_string()], "N"));
+        assert!(!matches_generic(&[], "T"));
+    }
+
+    #[test]
+    fn preprocessor_conditions_are_stored_and_queried() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::write(
+            root.join("board.c"),
+            "#ifdef CONFIG_ARM\nvoid board_init(void) {}\n#else\nvoid board_init(void) {}\n#endif\n",
+        )
+        .unwrap();
+        pipeline::build_index(root, true).unwrap();
+
+        let conn = db::open_existing(&db::local_index_path(root)).unwrap();
+        let found = query_symbols_db(&conn, "board_init", None, true).unwrap();
+        let conditions: Vec<Option<&str>> = found.iter().map(|s| s.condition.as_deref()).collect();
+        assert_eq!(
+            conditions,
+            [Some("defined(CONFIG_ARM)"), Some("!defined(CONFIG_ARM)")]
+        );
+    }
+
+    #[test]
+    fn visibility_and_modifiers_are_stored() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::write(
+            root.join("lib.rs"),
+            "pub async fn fetch_all() {}\nasync fn fetch_one() {}\npub fn fetch_sync() {}\n",
+        )
+        .unwrap();
+        pipeline::build_index(root, true).unwrap();
+
+        let conn = db::open_existing(&db::local_index_path(root)).unwrap();
+        let mut found: Vec<(String, Option<String>, Vec<String>)> =
+            query_symbols_db(&conn, "fetch", None, false)
+                .unwrap()
+                .into_iter()
+                .map(|s| (s.name, s.visibility, s.modifiers))
+                .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            [
+                (
+                    "fetch_all".to_string(),
+                    Some("public".to_string()),
+                    vec!["async".to_string()]
+                ),
+                (
+                    "fetch_one".to_string(),
+                    Some("private".to_string()),
+                    vec!["async".to_string()]
+                ),
+                ("fetch_sync".to_string(), Some("public".to_string()), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn generics_are_stored_and_filtered() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::write(
+            root.join("lib.rs"),
+            "fn read_all<R: Read, const N: usize>(r: R) {}\nfn read_one<R>(r: R) where R: Read + Send {}\nfn read_none() {}\n",
+        )
+        .unwrap();
+        pipeline::build_index(root, true).unwrap();
+
+        let conn = db::open_existing(&db::local_index_path(root)).unwrap();
+        let syms = query_symbols_db(&conn, "read", None, false).unwrap();
+        let generics = |name: &str| {
+            syms.iter()
+                .find(|s| s.name == name)
+                .map(|s| s.generics.clone())
+                .unwrap()
+        };
+        assert_eq!(generics("read_all"), ["R: Read", "const N: usize"]);
+        assert_eq!(generics("read_one"), ["R: Read + Send"]);
+        assert!(generics("read_none").is_empty());
+
+        let mut send: Vec<&str> = syms
+            .iter()
+            .filter(|s| matches_generic(&s.generics, ": Send"))
+            .map(|s| s.name.as_str())
+            .collect();
+        send.sort();
+        assert_eq!(send, ["read_one"]);
+    }
+
+    #[test]
+    fn stream_search_ranks_within_each_file() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("a.rs"),
+            "// mentions target\nfn go() { target(); }\nuse x::target;\n",
+        )
+        .unwrap();
+        fs::create_dir(dir.path().join("tests")).unwrap();
+        fs::write(dir.path().join("tests/t.rs"), "target();\n").unwrap();
+        let paths = [dir.path().to_string_lossy().into_owned()];
+        let tests = crate::ranker::TestClassifier::new(
+            crate::ranker::TestFilter::Exclude,
+            None,
+            dir.path(),
+        );
+
+        let mut buf = Vec::new();
+        let (found, truncated) = {
+            let mut fmt = Formatter::new(&mut buf, OutputFormat::Json, false);
+            stream_search(&mut fmt, "target", false, false, &paths, &tests, Some(None)).unwrap()
+        };
+        assert_eq!((found, truncated), (3, 0));
+        let lines: Vec<serde_json::Value> = String::from_utf8(buf)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        let order: Vec<(u64, &str)> = lines
+            .iter()
+            .map(|v| (v["line"].as_u64().unwrap(), v["category"].as_str().unwrap()))
+            .collect();
+        assert_eq!(order, [(3, "import"), (2, "other"), (1, "comment")]);
+
+        // Unranked streaming keeps line order and omits categories.
+        let mut buf = Vec::new();
+        {
+            let mut fmt = Formatter::new(&mut buf, OutputFormat::Json, false);
+            stream_search(&mut fmt, "target", false, false, &paths, &tests, None).unwrap();
+        }
+        let out = String::from_utf8(buf).unwrap();
+        assert_eq!(out.lines().count(), 3);
+        assert!(!out.contains("category"));
+    }
+
+    #[test]
+    fn source_ranges_are_stored() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        let src = "fn alpha() {}\nfn beta() {\n    alpha();\n}\n";
+        fs::write(root.join("lib.rs"), src).unwrap();
+        pipeline::build_index(root, true).unwrap();
+
+        let conn = db::open_existing(&db::local_index_path(root)).unwrap();
+        let syms = query_symbols_db(&conn, "beta", None, true).unwrap();
+        let beta = &syms[0];
+        assert_eq!(
+            &src[beta.start_byte.unwrap()..beta.end_byte.unwrap()],
+            "fn beta() {\n    alpha();\n}"
+        );
+        assert_eq!(beta.end_col, Some(1));
+
+        let refs = query_references_db(&conn, "alpha").unwrap();
+        assert_eq!(refs.len(), 1);
+        assert_eq!(
+            &src[refs[0].start_byte.unwrap()..refs[0].end_byte.unwrap()],
+            "alpha()"
+        );
+        assert_eq!(refs[0].end_col, Some(11));
+    }
+
+    #[test]
+    fn stable_id_lookup_survives_reindex() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::write(root.join("lib.rs"), "fn alpha(x: u32) {}\n").unwrap();
+        pipeline::build_index(root, true).unwrap();
+
+        let conn = db::open_existing(&db::local_index_path(root)).unwrap();
+        let id = query_symbols_db(&conn, "alpha", None, true).unwrap()[0].stable_id();
+
+        fs::write(root.join("lib.rs"), "// moved\n\nfn alpha(x: u32) {}\n").unwrap();
+        pipeline::reindex_file(&conn, &root.join("lib.rs"), root).unwrap();
+
+        let found = query_symbols_by_stable_id_db(&conn, &id).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].line, 3);
+    }
+
+    #[test]
+    fn query_refresh_disabled_keeps_indexed_lines() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::write(root.join("lib.rs"), "fn alpha() {}\n").unwrap();
+        pipeline::build_index(root, true).unwrap();
+
+        fs::write(root.join("lib.rs"), "// moved\n\nfn alpha() {}\n").unwrap();
+
+        let conn = db::open_existing(&db::local_index_path(root)).unwrap();
+        let router = QueryRouter::with_conn(conn, root.to_path_buf());
+        let results = router.query_symbols("alpha", None, true).unwrap();
+        assert_eq!(results[0].line, 1);
+    }
+
     #[test]
     fn test_rdeps_dispatch_from_db() {
         let dir = TempDir::new().unwrap();
@@ -3863,7 +7563,7 @@ mod tests {
         let router = QueryRouter::with_conn(conn, root.to_path_buf());
 
         // Query rdeps for src/utils.ts.
-        let results = router.query_rdeps("src/utils.ts").unwrap();
+        let results = router.query_rdeps("src/utils.ts", false).unwrap();
         assert!(
             results.len() >= 2,
             "should find at least 2 reverse deps, got {}",
@@ -3893,6 +7593,7 @@ mod tests {
                 let out = output::DepOutput {
                     file: "src/main.ts".to_string(),
                     depends_on: dep.clone(),
+                    generated_by: None,
                 };
                 fmt.format_dep(&out).unwrap();
             }
@@ -3926,6 +7627,7 @@ mod tests {
                 let out = output::DepOutput {
                     file: "src/main.ts".to_string(),
                     depends_on: dep.clone(),
+                    generated_by: None,
                 };
                 fmt.format_dep(&out).unwrap();
             }
@@ -3963,6 +7665,334 @@ mod tests {
         assert!(results.contains(&"./config".to_string()));
     }
 
+    fn add_indexed_files(conn: &Connection, paths: &[&str]) {
+        for path in paths {
+            conn.execute(
+                "INSERT INTO files (path, language, hash, last_indexed) VALUES (?1, 'TypeScript', 'h', 0)",
+                [path],
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_router_query_rdeps_ignores_same_stem_elsewhere() {
+        let dir = TempDir::new().unwrap();
+        let conn = db::open(&dir.path().join("index.db")).unwrap();
+        add_indexed_files(
+            &conn,
+            &[
+                "src/app.ts",
+                "src/config.ts",
+                "lib/other.ts",
+                "lib/config.ts",
+            ],
+        );
+        for (source, import) in [
+            ("src/app.ts", "./config"),
+            ("lib/other.ts", "./config"),
+            ("lib/other.ts", "@scope/config"),
+        ] {
+            conn.execute(
+                "INSERT INTO file_imports (source_file, import_path) VALUES (?1, ?2)",
+                rusqlite::params![source, import],
+            )
+            .unwrap();
+        }
+        crate::imports::resolve_pending(&conn, None).unwrap();
+
+        // Only the import that resolves to src/config.ts counts.
+        assert_eq!(
+            query_rdeps_db(&conn, "src/config.ts", false).unwrap(),
+            ["src/app.ts"]
+        );
+        // --fuzzy adds the unresolved package import by stem.
+        assert_eq!(
+            query_rdeps_db(&conn, "src/config.ts", true).unwrap(),
+            ["lib/other.ts", "src/app.ts"]
+        );
+    }
+
+    #[test]
+    fn test_c_header_deps_and_rdeps_use_repo_paths() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("include/util")).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("include/util/strbuf.h"), "void sb_init(void);\n").unwrap();
+        fs::write(root.join("include/util/log.h"), "void log_msg(void);\n").unwrap();
+        // strbuf.c reaches its header only through log.h.
+        fs::write(
+            root.join("src/strbuf.c"),
+            "#include \"util/log.h\"\nvoid sb_init(void) {}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/main.c"),
+            "#include <stdio.h>\n#include \"util/strbuf.h\"\nint main(void) { return 0; }\n",
+        )
+        .unwrap();
+
+        pipeline::build_index(root, true).unwrap();
+        let conn = db::open_existing(&db::local_index_path(root)).unwrap();
+
+        let deps = query_deps_db(&conn, "src/main.c").unwrap();
+        assert!(deps.contains(&"include/util/strbuf.h".to_string()));
+        assert!(deps.contains(&"stdio.h".to_string()));
+
+        let rdeps = query_rdeps_db(&conn, "include/util/strbuf.h", false).unwrap();
+        assert_eq!(rdeps, ["src/main.c", "src/strbuf.c"]);
+    }
+
+    #[test]
+    fn test_rust_deps_and_rdeps_follow_module_graph() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("src/net")).unwrap();
+        fs::write(root.join("src/lib.rs"), "pub mod net;\npub mod types;\n").unwrap();
+        fs::write(root.join("src/types.rs"), "pub struct Config;\n").unwrap();
+        fs::write(root.join("src/net/mod.rs"), "mod tcp;\n").unwrap();
+        fs::write(
+            root.join("src/net/tcp.rs"),
+            "use crate::types::{Config, Config as Cfg};\nuse std::io;\npub fn open(_: Config) {}\n",
+        )
+        .unwrap();
+        // Same stem elsewhere must not count as a dependent.
+        fs::write(root.join("src/net/types.rs"), "pub struct Local;\n").unwrap();
+
+        pipeline::build_index(root, true).unwrap();
+        let conn = db::open_existing(&db::local_index_path(root)).unwrap();
+
+        assert_eq!(
+            query_deps_db(&conn, "src/net/tcp.rs").unwrap(),
+            ["src/types.rs", "std::io"]
+        );
+        assert_eq!(
+            query_deps_db(&conn, "src/lib.rs").unwrap(),
+            ["src/net/mod.rs", "src/types.rs"]
+        );
+        assert_eq!(
+            query_rdeps_db(&conn, "src/types.rs", false).unwrap(),
+            ["src/lib.rs", "src/net/tcp.rs"]
+        );
+        assert_eq!(
+            query_rdeps_db(&conn, "src/net/tcp.rs", false).unwrap(),
+            ["src/net/mod.rs"]
+        );
+    }
+
+    #[test]
+    fn test_python_deps_and_rdeps_use_src_layout() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("src/shop/orders")).unwrap();
+        fs::create_dir_all(root.join("tests")).unwrap();
+        fs::write(
+            root.join("pyproject.toml"),
+            "[tool.setuptools.packages.find]\nwhere = [\"src\"]\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/shop/__init__.py"), "").unwrap();
+        fs::write(root.join("src/shop/models.py"), "class Order:\n    pass\n").unwrap();
+        fs::write(
+            root.join("src/shop/orders/api.py"),
+            "from ..models import Order\nimport json\n\ndef get():\n    return Order()\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("tests/test_api.py"),
+            "from shop.orders import api\nfrom shop.models import Order\n\ndef test_get():\n    api.get()\n",
+        )
+        .unwrap();
+
+        pipeline::build_index(root, true).unwrap();
+        let conn = db::open_existing(&db::local_index_path(root)).unwrap();
+
+        let mut deps = query_deps_db(&conn, "tests/test_api.py").unwrap();
+        deps.sort();
+        assert_eq!(deps, ["src/shop/models.py", "src/shop/orders/api.py"]);
+        assert_eq!(
+            query_rdeps_db(&conn, "src/shop/models.py", false).unwrap(),
+            ["src/shop/orders/api.py", "tests/test_api.py"]
+        );
+    }
+
+    #[test]
+    fn test_rails_autoloaded_constants_are_dependencies() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join(".wonk")).unwrap();
+        fs::write(root.join(".wonk/config.toml"), "[ruby]\nrails = true\n").unwrap();
+        fs::create_dir_all(root.join("app/models")).unwrap();
+        fs::create_dir_all(root.join("app/controllers")).unwrap();
+        fs::write(
+            root.join("app/models/user.rb"),
+            "class User < ApplicationRecord\n  def self.active\n  end\nend\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("app/controllers/users_controller.rb"),
+            "class UsersController < ApplicationController\n  def index\n    @users = User.active\n    @count = String.new\n  end\nend\n",
+        )
+        .unwrap();
+
+        pipeline::build_index(root, true).unwrap();
+        let conn = db::open_existing(&db::local_index_path(root)).unwrap();
+
+        assert_eq!(
+            query_deps_db(&conn, "app/controllers/users_controller.rb").unwrap(),
+            ["app/models/user.rb"]
+        );
+        assert_eq!(
+            query_rdeps_db(&conn, "app/models/user.rb", false).unwrap(),
+            ["app/controllers/users_controller.rb"]
+        );
+    }
+
+    #[test]
+    fn test_php_deps_and_rdeps_use_psr4() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("src/Service")).unwrap();
+        fs::create_dir_all(root.join("src/Http")).unwrap();
+        fs::write(
+            root.join("composer.json"),
+            r#"{"autoload": {"psr-4": {"App\\": "src/"}}}"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/Service/Mailer.php"),
+            "<?php\nnamespace App\\Service;\n\nclass Mailer {}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/Http/Controller.php"),
+            "<?php\nnamespace App\\Http;\n\nuse App\\Service\\Mailer;\nuse Psr\\Log\\LoggerInterface;\n\nclass Controller {}\n",
+        )
+        .unwrap();
+
+        pipeline::build_index(root, true).unwrap();
+        let conn = db::open_existing(&db::local_index_path(root)).unwrap();
+
+        assert_eq!(
+            query_deps_db(&conn, "src/Http/Controller.php").unwrap(),
+            ["src/Service/Mailer.php", "Psr\\Log\\LoggerInterface"]
+        );
+        assert_eq!(
+            query_rdeps_db(&conn, "src/Service/Mailer.php", false).unwrap(),
+            ["src/Http/Controller.php"]
+        );
+    }
+
+    #[test]
+    fn test_java_deps_and_rdeps_use_packages() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("src/com/acme/util")).unwrap();
+        fs::create_dir_all(root.join("src/com/acme/app")).unwrap();
+        fs::write(
+            root.join("src/com/acme/Mailer.java"),
+            "package com.acme;\n\npublic class Mailer {}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/com/acme/util/Strings.java"),
+            "package com.acme.util;\n\npublic class Strings {}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/com/acme/util/Unused.java"),
+            "package com.acme.util;\n\npublic class Unused {}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/com/acme/app/App.java"),
+            "package com.acme.app;\n\nimport com.acme.Mailer;\nimport com.acme.util.*;\nimport java.util.List;\n\nclass App {\n    Mailer mailer;\n    Strings strings;\n}\n",
+        )
+        .unwrap();
+
+        pipeline::build_index(root, true).unwrap();
+        let conn = db::open_existing(&db::local_index_path(root)).unwrap();
+
+        let mut deps = query_deps_db(&conn, "src/com/acme/app/App.java").unwrap();
+        deps.sort();
+        assert_eq!(
+            deps,
+            [
+                "com.acme.util.*",
+                "java.util.List",
+                "src/com/acme/Mailer.java",
+                "src/com/acme/util/Strings.java",
+            ]
+        );
+        assert_eq!(
+            query_rdeps_db(&conn, "src/com/acme/util/Strings.java", false).unwrap(),
+            ["src/com/acme/app/App.java"]
+        );
+        assert!(
+            query_rdeps_db(&conn, "src/com/acme/util/Unused.java", false)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_go_deps_and_rdeps_are_per_package() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("internal/store")).unwrap();
+        fs::create_dir_all(root.join("cmd/server")).unwrap();
+        fs::write(root.join("go.mod"), "module example.com/app\n\ngo 1.22\n").unwrap();
+        fs::write(
+            root.join("internal/store/store.go"),
+            "package store\n\nimport \"fmt\"\n\nfunc Open() { fmt.Println() }\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("internal/store/cache.go"),
+            "package store\n\nimport \"sync\"\n\nvar mu sync.Mutex\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("cmd/server/main.go"),
+            "package main\n\nimport \"example.com/app/internal/store\"\n\nfunc main() { store.Open() }\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("cmd/server/flags.go"),
+            "package main\n\nimport (\n\t\"flag\"\n\t\"example.com/app/internal/store\"\n)\n\nvar _ = flag.Parse\nvar _ = store.Open\n",
+        )
+        .unwrap();
+
+        pipeline::build_index(root, true).unwrap();
+        let conn = db::open_existing(&db::local_index_path(root)).unwrap();
+
+        // Any file of a package answers for the whole package.
+        assert_eq!(
+            query_deps_db(&conn, "internal/store/cache.go").unwrap(),
+            ["fmt", "sync"]
+        );
+        assert_eq!(
+            query_deps_db(&conn, "cmd/server").unwrap(),
+            ["flag", "internal/store"]
+        );
+        assert_eq!(
+            query_rdeps_db(&conn, "internal/store/store.go", false).unwrap(),
+            ["cmd/server"]
+        );
+        assert_eq!(
+            query_rdeps_db(&conn, "internal/store", false).unwrap(),
+            ["cmd/server"]
+        );
+    }
+
     #[test]
     fn test_router_query_rdeps_from_db() {
         let dir = TempDir::new().unwrap();
@@ -3988,9 +8018,12 @@ mod tests {
         )
         .unwrap();
 
+        add_indexed_files(&conn, &["src/app.ts", "src/main.ts", "src/utils.ts"]);
+        crate::imports::resolve_pending(&conn, None).unwrap();
+
         let router = QueryRouter::with_conn(conn, dir.path().to_path_buf());
-        let results = router.query_rdeps("src/utils.ts").unwrap();
-        // Both app.ts and main.ts import something matching "utils" stem.
+        let results = router.query_rdeps("src/utils.ts", false).unwrap();
+        // Both app.ts and main.ts import ./utils, which resolves to utils.ts.
         assert_eq!(results.len(), 2);
         assert!(results.contains(&"src/app.ts".to_string()));
         assert!(results.contains(&"src/main.ts".to_string()));
@@ -4015,8 +8048,11 @@ mod tests {
         )
         .unwrap();
 
+        add_indexed_files(&conn, &["src/app.ts", "src/helper.ts", "src/utils.ts"]);
+        crate::imports::resolve_pending(&conn, None).unwrap();
+
         let router = QueryRouter::with_conn(conn, dir.path().to_path_buf());
-        let results = router.query_rdeps("src/utils.ts").unwrap();
+        let results = router.query_rdeps("src/utils.ts", false).unwrap();
         // Should not include utils.ts itself.
         assert_eq!(results.len(), 1);
         assert_eq!(results[0], "src/app.ts");
@@ -4360,7 +8396,7 @@ mod tests {
 
         let router = QueryRouter::grep_only(dir.path().to_path_buf());
 
-        let results = router.query_rdeps("utils.py").unwrap();
+        let results = router.query_rdeps("utils.py", false).unwrap();
         // Should find at least the JS and Rust files that reference "utils"
         assert!(
             !results.is_empty(),
@@ -4395,6 +8431,8 @@ mod tests {
                     line: sym.line,
                     signature: sym.signature.clone(),
                     language: sym.language.clone(),
+                    freshness: None,
+                    stamp: None,
                 };
                 fmt.format_signature(&out).unwrap();
             }
@@ -4436,6 +8474,8 @@ mod tests {
                     line: sym.line,
                     signature: sym.signature.clone(),
                     language: sym.language.clone(),
+                    freshness: None,
+                    stamp: None,
                 };
                 fmt.format_signature(&out).unwrap();
             }
@@ -4488,6 +8528,8 @@ mod tests {
                 line: sym.line,
                 signature: sym.signature.clone(),
                 language: sym.language.clone(),
+                freshness: None,
+                stamp: None,
             };
             fmt.format_signature(&out).unwrap();
         }
@@ -4532,15 +8574,27 @@ mod tests {
             let mut fmt = Formatter::new(&mut buf, format, false);
             for sym in &results {
                 let out = SymbolOutput {
+                    query: None,
                     name: sym.name.clone(),
                     kind: sym.kind.to_string(),
                     file: sym.file.clone(),
                     line: sym.line,
                     col: sym.col,
                     end_line: sym.end_line,
+                    end_col: sym.end_col,
+                    start_byte: sym.start_byte,
+                    end_byte: sym.end_byte,
                     scope: sym.scope.clone(),
                     signature: sym.signature.clone(),
                     language: sym.language.clone(),
+                    freshness: None,
+                    id: None,
+                    generated_by: None,
+                    condition: None,
+                    visibility: None,
+                    modifiers: Vec::new(),
+                    generics: Vec::new(),
+                    stamp: None,
                 };
                 fmt.format_symbol(&out).unwrap();
             }
@@ -4883,9 +8937,16 @@ mod tests {
             file: "src/billing.rs".into(),
             line: 42,
             col: 8,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             context: "    processPayment(order);".into(),
             caller_name: None,
             confidence: 0.85,
+            freshness: None,
+            definition: None,
+            generated_by: None,
+            stamp: None,
         };
 
         let mut buf = Vec::new();
@@ -4907,9 +8968,16 @@ mod tests {
             file: "src/billing.rs".into(),
             line: 42,
             col: 8,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             context: "    processPayment(order);".into(),
             caller_name: None,
             confidence: 0.85,
+            freshness: None,
+            definition: None,
+            generated_by: None,
+            stamp: None,
         };
 
         let mut buf = Vec::new();
@@ -5027,12 +9095,20 @@ mod tests {
     #[test]
     fn test_is_query_command_search() {
         let cmd = Command::Search(SearchArgs {
-            pattern: "test".into(),
+            pattern: Some("test".into()),
+            patterns: vec![],
+            patterns_file: None,
             regex: false,
             ignore_case: false,
             raw: false,
             smart: false,
             semantic: false,
+            group_json: false,
+            stream: false,
+            cluster: None,
+            cluster_top: None,
+            recent: false,
+            boost: vec![],
             file: None,
             paths: vec![],
         });
@@ -5042,11 +9118,18 @@ mod tests {
     #[test]
     fn test_is_query_command_sym() {
         let cmd = Command::Sym(SymArgs {
-            name: "foo".into(),
+            name: Some("foo".into()),
+            stdin_names: false,
             kind: None,
             file: None,
             exact: false,
             limit: None,
+            id: false,
+            condition: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
+            boost: Vec::new(),
         });
         assert!(is_query_command(&cmd));
     }
@@ -5061,21 +9144,32 @@ mod tests {
 
     #[test]
     fn test_is_query_command_not_init() {
-        let cmd = Command::Init(InitArgs { local: false });
+        let cmd = Command::Init(InitArgs {
+            local: false,
+            profile: false,
+            archive: None,
+            root: None,
+            strip_components: 0,
+            skip_space_check: false,
+        });
         assert!(!is_query_command(&cmd));
     }
 
     #[test]
     fn test_is_query_command_not_update() {
         assert!(!is_query_command(&Command::Update(UpdateArgs {
+            paths: vec![],
             force: false,
             skip_embed: false,
+            skip_space_check: false,
         })));
     }
 
     #[test]
     fn test_is_query_command_not_status() {
-        assert!(!is_query_command(&Command::Status));
+        assert!(!is_query_command(&Command::Status(
+            crate::cli::StatusArgs { json: false }
+        )));
     }
 
     #[test]
@@ -5129,12 +9223,21 @@ mod tests {
     fn test_status_info_format_with_embeddings() {
         let info = StatusInfo {
             indexed: true,
+            index_path: None,
+            schema_version: 0,
             file_count: 100,
             symbol_count: 500,
             reference_count: 2000,
+            languages: vec![],
+            last_indexed: None,
+            stale_file_count: 0,
             embedding_count: 300,
             stale_embedding_count: 10,
             ollama_reachable: true,
+            disabled_languages: vec![],
+            partial: false,
+            index_generation: 0,
+            daemon: None,
         };
         let output = format_status_info(&info);
         assert!(output.contains("100 files"));
@@ -5145,16 +9248,110 @@ mod tests {
         assert!(output.contains("reachable"));
     }
 
+    #[test]
+    fn test_status_info_reports_index_health() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::write(root.join("a.rs"), "fn a() {}\n").unwrap();
+        fs::write(root.join("b.rs"), "fn b() {}\n").unwrap();
+        pipeline::build_index(root, true).unwrap();
+
+        let future = std::time::SystemTime::now() + std::time::Duration::from_secs(3600);
+        fs::File::options()
+            .write(true)
+            .open(root.join("b.rs"))
+            .unwrap()
+            .set_modified(future)
+            .unwrap();
+
+        let conn = db::open_existing(&db::local_index_path(root)).unwrap();
+        let info = query_status_info(Some(&conn));
+        assert!(info.index_path.unwrap().ends_with("index.db"));
+        assert_eq!(info.schema_version, db::SCHEMA_VERSION);
+        assert_eq!(info.file_count, 2);
+        assert_eq!(info.languages.len(), 1);
+        assert!(info.last_indexed.is_some());
+        assert_eq!(info.stale_file_count, 1);
+    }
+
+    #[test]
+    fn test_status_info_format_index_health() {
+        let info = StatusInfo {
+            indexed: true,
+            index_path: Some("/home/u/.wonk/repos/abc/index.db".to_string()),
+            schema_version: 1,
+            file_count: 2,
+            symbol_count: 2,
+            reference_count: 0,
+            languages: vec!["Python".to_string(), "Rust".to_string()],
+            last_indexed: Some(0),
+            stale_file_count: 3,
+            embedding_count: 0,
+            stale_embedding_count: 0,
+            ollama_reachable: false,
+            disabled_languages: vec![],
+            partial: false,
+            index_generation: 0,
+            daemon: None,
+        };
+        let output = format_status_info(&info);
+        assert!(output.contains("Location: /home/u/.wonk/repos/abc/index.db (schema v1)"));
+        assert!(output.contains("Languages: Python, Rust"));
+        assert!(output.contains("Last indexed: "));
+        assert!(output.contains("Stale files: 3"));
+    }
+
+    #[test]
+    fn test_status_info_format_stuck_daemon() {
+        let info = StatusInfo {
+            indexed: true,
+            index_path: None,
+            schema_version: 0,
+            file_count: 1,
+            symbol_count: 1,
+            reference_count: 1,
+            languages: vec![],
+            last_indexed: None,
+            stale_file_count: 0,
+            embedding_count: 0,
+            stale_embedding_count: 0,
+            ollama_reachable: false,
+            disabled_languages: vec![],
+            partial: false,
+            index_generation: 0,
+            daemon: Some(crate::daemon::DaemonHealth {
+                pid: 4242,
+                state: "running".to_string(),
+                heartbeat_age: Some(300),
+                last_event: None,
+                files_queued: 0,
+                stuck: true,
+            }),
+        };
+        let output = format_status_info(&info);
+        assert!(output.contains("Daemon: stuck (PID 4242, no heartbeat for 300s)"));
+    }
+
     #[test]
     fn test_status_info_format_no_index() {
         let info = StatusInfo {
             indexed: false,
+            index_path: None,
+            schema_version: 0,
             file_count: 0,
             symbol_count: 0,
             reference_count: 0,
+            languages: vec![],
+            last_indexed: None,
+            stale_file_count: 0,
             embedding_count: 0,
             stale_embedding_count: 0,
             ollama_reachable: false,
+            disabled_languages: vec![],
+            partial: false,
+            index_generation: 0,
+            daemon: None,
         };
         let output = format_status_info(&info);
         assert!(output.contains("No index"));
@@ -5164,17 +9361,96 @@ mod tests {
     fn test_status_info_format_ollama_unreachable() {
         let info = StatusInfo {
             indexed: true,
+            index_path: None,
+            schema_version: 0,
             file_count: 50,
             symbol_count: 200,
             reference_count: 800,
+            languages: vec![],
+            last_indexed: None,
+            stale_file_count: 0,
             embedding_count: 0,
             stale_embedding_count: 0,
             ollama_reachable: false,
+            disabled_languages: vec![],
+            partial: false,
+            index_generation: 0,
+            daemon: None,
         };
         let output = format_status_info(&info);
         assert!(output.contains("unreachable"));
     }
 
+    #[test]
+    fn test_status_info_format_disabled_languages() {
+        let info = StatusInfo {
+            indexed: true,
+            index_path: None,
+            schema_version: 0,
+            file_count: 10,
+            symbol_count: 20,
+            reference_count: 30,
+            languages: vec![],
+            last_indexed: None,
+            stale_file_count: 0,
+            embedding_count: 0,
+            stale_embedding_count: 0,
+            ollama_reachable: false,
+            disabled_languages: vec!["PHP".to_string(), "Ruby".to_string()],
+            partial: false,
+            index_generation: 0,
+            daemon: None,
+        };
+        let output = format_status_info(&info);
+        assert!(output.contains("Disabled grammars: PHP, Ruby"));
+    }
+
+    #[test]
+    fn test_status_info_format_omits_disabled_when_none() {
+        let info = StatusInfo {
+            indexed: true,
+            index_path: None,
+            schema_version: 0,
+            file_count: 10,
+            symbol_count: 20,
+            reference_count: 30,
+            languages: vec![],
+            last_indexed: None,
+            stale_file_count: 0,
+            embedding_count: 0,
+            stale_embedding_count: 0,
+            ollama_reachable: false,
+            disabled_languages: vec![],
+            partial: false,
+            index_generation: 0,
+            daemon: None,
+        };
+        assert!(!format_status_info(&info).contains("Disabled grammars"));
+    }
+
+    #[test]
+    fn test_status_info_format_partial_index() {
+        let info = StatusInfo {
+            indexed: true,
+            index_path: None,
+            schema_version: 0,
+            file_count: 10,
+            symbol_count: 20,
+            reference_count: 30,
+            languages: vec![],
+            last_indexed: None,
+            stale_file_count: 0,
+            embedding_count: 0,
+            stale_embedding_count: 0,
+            ollama_reachable: false,
+            disabled_languages: vec![],
+            partial: true,
+            index_generation: 0,
+            daemon: None,
+        };
+        assert!(format_status_info(&info).contains("Index is partial"));
+    }
+
     // -- Semantic fetch + RRF helpers -----------------------------------------
 
     #[test]
@@ -5339,6 +9615,21 @@ mod tests {
         assert!(is_query_command(&cmd));
     }
 
+    // -- CwdScope tests --------------------------------------------------------
+
+    #[test]
+    fn test_cwd_scope_contains_files_below_dir() {
+        let scope = CwdScope {
+            root: PathBuf::from("/repo"),
+            dir: PathBuf::from("services/billing"),
+        };
+        assert!(scope.contains("services/billing/src/invoice.rs"));
+        assert!(scope.contains("/repo/services/billing/lib.rs"));
+        assert!(!scope.contains("services/billing-v2/lib.rs"));
+        assert!(!scope.contains("services/auth/lib.rs"));
+        assert!(!scope.contains("/elsewhere/services/billing/lib.rs"));
+    }
+
     // -- split_qualified_name tests -------------------------------------------
 
     #[test]
@@ -0,0 +1,206 @@
+//! `wonk schema` — JSON Schema definitions of every `--format json` record.
+//!
+//! The schemas are generated from the serde output types in [`crate::output`]
+//! (and the few records emitted elsewhere), so they cannot drift from what
+//! wonk writes.  The bundle is a JSON Schema 2020-12 document whose `anyOf`
+//! accepts any single record of an NDJSON stream, with one `$defs` entry per
+//! record type.  [`SCHEMA_VERSION`] changes whenever a record changes in a
+//! way that breaks existing consumers.
+
+use schemars::generate::{SchemaGenerator, SchemaSettings};
+use schemars::{JsonSchema, Schema};
+use serde_json::{Map, Value, json};
+
+use crate::output::*;
+
+/// Version of the record schemas, bumped on incompatible changes (a removed
+/// or renamed field, a changed type).  Added optional fields do not bump it.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A top-level record type: its `$defs` name and schema.
+struct Record {
+    name: &'static str,
+    schema: fn(&mut SchemaGenerator) -> Schema,
+}
+
+const fn record<T: JsonSchema>(name: &'static str) -> Record {
+    Record {
+        name,
+        schema: |generator| generator.subschema_for::<T>(),
+    }
+}
+
+/// Every record type written as one line of JSON output.
+const RECORDS: &[Record] = &[
+    record::<SearchOutput>("SearchOutput"),
+    record::<SearchGroupOutput>("SearchGroupOutput"),
+    record::<SearchClusterOutput>("SearchClusterOutput"),
+    record::<SymbolOutput>("SymbolOutput"),
+    record::<GlobalSymbolOutput>("GlobalSymbolOutput"),
+    record::<FindOutput>("FindOutput"),
+    record::<RefOutput>("RefOutput"),
+    record::<SignatureOutput>("SignatureOutput"),
+    record::<FileEntry>("FileEntry"),
+    record::<DepOutput>("DepOutput"),
+    record::<GeneratedGroupOutput>("GeneratedGroupOutput"),
+    record::<ShowOutput>("ShowOutput"),
+    record::<CallerOutput>("CallerOutput"),
+    record::<CalleeOutput>("CalleeOutput"),
+    record::<Vec<CallPathHopOutput>>("CallPathOutput"),
+    record::<SummaryOutput>("SummaryOutput"),
+    record::<FileListingOutput>("FileListingOutput"),
+    record::<DescribeOutput>("DescribeOutput"),
+    record::<NearOutput>("NearOutput"),
+    record::<FlowStepOutput>("FlowStepOutput"),
+    record::<FlowOutput>("FlowOutput"),
+    record::<BlastOutput>("BlastOutput"),
+    record::<ChangesOutput>("ChangesOutput"),
+    record::<Vec<SymbolContextOutput>>("ContextOutput"),
+    record::<AsyncBoundaryOutput>("AsyncBoundaryOutput"),
+    record::<UnsafeSiteOutput>("UnsafeSiteOutput"),
+    record::<SemanticOutput>("SemanticOutput"),
+    record::<ClusterMemberOutput>("ClusterMemberOutput"),
+    record::<ClusterOutput>("ClusterOutput"),
+    record::<ImpactEntryOutput>("ImpactEntryOutput"),
+    record::<ImpactOutput>("ImpactOutput"),
+    record::<SnapshotOutput>("SnapshotOutput"),
+    record::<CompareOutput>("CompareOutput"),
+    record::<GateCheckOutput>("GateCheckOutput"),
+    record::<LangStatsOutput>("LangStatsOutput"),
+    record::<WhichIndexOutput>("WhichIndexOutput"),
+    record::<crate::router::StatusInfo>("StatusInfo"),
+    record::<crate::daemon::DaemonEntry>("DaemonEntry"),
+    record::<IndexProfileOutput>("IndexProfileOutput"),
+    record::<RecipeOutput>("RecipeOutput"),
+    record::<ParseOutput>("ParseOutput"),
+    record::<TreeDumpOutput>("TreeDumpOutput"),
+    record::<DiagOutput>("DiagOutput"),
+    record::<SelfUpdateOutput>("SelfUpdateOutput"),
+    record::<TruncationMeta>("TruncationMeta"),
+    record::<TimeoutMeta>("TimeoutMeta"),
+    record::<InterruptMeta>("InterruptMeta"),
+    record::<MessageMeta>("MessageMeta"),
+];
+
+/// Names of all record types, in the order of [`bundle`]'s `anyOf`.
+pub fn record_names() -> impl Iterator<Item = &'static str> {
+    RECORDS.iter().map(|r| r.name)
+}
+
+fn generator() -> SchemaGenerator {
+    SchemaSettings::draft2020_12()
+        .for_serialize()
+        .into_generator()
+}
+
+/// The full schema document: every record type under `$defs`, and an
+/// `anyOf` that validates any one line of JSON output.
+pub fn bundle() -> Value {
+    let mut generator = generator();
+    let mut any_of = Vec::with_capacity(RECORDS.len());
+    let mut records = Vec::with_capacity(RECORDS.len());
+    for record in RECORDS {
+        records.push((record.name, (record.schema)(&mut generator)));
+        any_of.push(json!({ "$ref": format!("#/$defs/{}", record.name) }));
+    }
+    let mut defs: Map<String, Value> = generator.take_definitions(true);
+    for (name, schema) in records {
+        // Structs are already defined under their type name; arrays of
+        // them are inline and get a definition of their own.
+        defs.entry(name).or_insert_with(|| schema.to_value());
+    }
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "wonk JSON output",
+        "description": "One record per line of `--format json` output.",
+        "version": SCHEMA_VERSION,
+        "wonk_version": env!("CARGO_PKG_VERSION"),
+        "anyOf": any_of,
+        "$defs": defs,
+    })
+}
+
+/// A standalone schema for the record type `name` (case-insensitive), with
+/// the types it uses under its own `$defs`.
+pub fn record_schema(name: &str) -> Option<Value> {
+    let record = RECORDS.iter().find(|r| r.name.eq_ignore_ascii_case(name))?;
+    let mut generator = generator();
+    let schema = (record.schema)(&mut generator);
+    let defs = generator.take_definitions(true);
+    let mut value = match schema.as_object().and_then(|o| o.get("$ref")) {
+        // Inline the record itself rather than pointing into `$defs`.
+        Some(_) => defs.get(record.name).cloned().unwrap_or(Value::Null),
+        None => schema.to_value(),
+    };
+    let object = value.as_object_mut()?;
+    let mut out = Map::new();
+    out.insert(
+        "$schema".into(),
+        json!("https://json-schema.org/draft/2020-12/schema"),
+    );
+    out.insert("title".into(), json!(record.name));
+    out.insert("version".into(), json!(SCHEMA_VERSION));
+    out.append(object);
+    let mut used: Map<String, Value> = defs;
+    used.remove(record.name);
+    if !used.is_empty() {
+        out.insert("$defs".into(), Value::Object(used));
+    }
+    Some(Value::Object(out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundle_defines_every_record() {
+        let bundle = bundle();
+        let defs = bundle["$defs"].as_object().unwrap();
+        for name in record_names() {
+            assert!(defs.contains_key(name), "missing $defs/{name}");
+        }
+        assert_eq!(
+            bundle["anyOf"].as_array().unwrap().len(),
+            record_names().count()
+        );
+        assert_eq!(bundle["version"], SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn symbol_schema_follows_serde_attributes() {
+        let schema = record_schema("symboloutput").unwrap();
+        assert_eq!(schema["title"], "SymbolOutput");
+        assert_eq!(schema["type"], "object");
+        let required: Vec<&str> = schema["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_str().unwrap())
+            .collect();
+        assert!(required.contains(&"name"));
+        assert!(required.contains(&"file"));
+        // `skip_serializing_if` fields are optional.
+        assert!(!required.contains(&"end_line"));
+    }
+
+    #[test]
+    fn message_schema_uses_the_serialized_field_name() {
+        let schema = record_schema("MessageMeta").unwrap();
+        let props = schema["properties"].as_object().unwrap();
+        assert!(props.contains_key("type"));
+        assert!(!props.contains_key("kind"));
+    }
+
+    #[test]
+    fn array_records_are_standalone() {
+        let schema = record_schema("CallPathOutput").unwrap();
+        assert_eq!(schema["type"], "array");
+        assert!(schema["$defs"]["CallPathHopOutput"].is_object());
+    }
+
+    #[test]
+    fn unknown_record_is_none() {
+        assert!(record_schema("NoSuchOutput").is_none());
+    }
+}
@@ -6,13 +6,19 @@
 //! - `Walker` from the `walker` module for file enumeration
 //!
 //! Supports case-insensitive matching, regex patterns, and path restriction.
+//! [`multi_search`] matches several patterns in a single pass over the tree.
 
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
 
 use anyhow::{Context, Result};
-use grep::regex::RegexMatcherBuilder;
+use grep::matcher::Matcher;
+use grep::regex::{RegexMatcher, RegexMatcherBuilder};
 use grep::searcher::{BinaryDetection, Searcher, SearcherBuilder, Sink, SinkMatch};
+use rayon::prelude::*;
+use regex::{RegexSet, RegexSetBuilder};
 
+use crate::deadline;
 use crate::walker::Walker;
 
 /// A single search hit: one matching line in a file.
@@ -22,9 +28,14 @@ pub struct SearchResult {
     pub file: PathBuf,
     /// 1-based line number within the file.
     pub line: u64,
-    /// 1-based column of the first byte of the match within the line (reserved
-    /// for future use; currently always 1).
+    /// 1-based byte column of the first match within the line.
     pub col: u64,
+    /// 1-based byte column just past the end of the first match.
+    pub end_col: Option<u64>,
+    /// 0-based byte offset of the first match within the file.
+    pub start_byte: Option<u64>,
+    /// 0-based byte offset just past the end of the first match.
+    pub end_byte: Option<u64>,
     /// The matched line content (with trailing newline stripped).
     pub content: String,
 }
@@ -65,20 +76,7 @@ pub fn text_search_with_ignores(
     paths: &[String],
     ignore_patterns: &[String],
 ) -> Result<Vec<SearchResult>> {
-    // Build the regex matcher.
-    let mut builder = RegexMatcherBuilder::new();
-    builder.case_insensitive(ignore_case);
-    builder.line_terminator(Some(b'\n'));
-
-    // When regex mode is off, treat the pattern as a fixed string so that
-    // metacharacters (e.g. `.`, `*`) are matched literally.
-    if !regex {
-        builder.fixed_strings(true);
-    }
-
-    let matcher = builder
-        .build(pattern)
-        .with_context(|| format!("invalid search pattern: {pattern}"))?;
+    let matcher = build_matcher(pattern, regex, ignore_case)?;
 
     // Build the searcher (line-oriented, with line numbers enabled).
     let mut searcher = SearcherBuilder::new()
@@ -103,6 +101,9 @@ pub fn text_search_with_ignores(
             .collect_paths();
 
         for file_path in files {
+            if deadline::expired() {
+                break;
+            }
             // Strip leading "./" so paths match index format (e.g. "src/main.rs" not "./src/main.rs").
             let normalized = file_path
                 .strip_prefix(".")
@@ -111,6 +112,7 @@ pub fn text_search_with_ignores(
 
             let mut sink = CollectSink {
                 file: normalized.clone(),
+                matcher: &matcher,
                 results: &mut results,
             };
             // Silently skip files that cannot be read (e.g. permission errors).
@@ -121,10 +123,209 @@ pub fn text_search_with_ignores(
     Ok(results)
 }
 
+/// Execute a text search, sending each file's matches to `tx` as soon as
+/// that file has been searched.
+///
+/// Files are searched in parallel, so chunks arrive in completion order
+/// rather than walk order; lines within a chunk are in line order.  Files
+/// without matches send nothing.  The search stops early once the receiver
+/// is dropped.  An invalid pattern is reported before anything is sent.
+pub fn text_search_streaming(
+    pattern: &str,
+    regex: bool,
+    ignore_case: bool,
+    paths: &[String],
+    ignore_patterns: &[String],
+    tx: Sender<Vec<SearchResult>>,
+) -> Result<()> {
+    let matcher = build_matcher(pattern, regex, ignore_case)?;
+
+    let roots: Vec<&Path> = if paths.is_empty() {
+        vec![Path::new(".")]
+    } else {
+        paths.iter().map(|p| Path::new(p.as_str())).collect()
+    };
+    let files: Vec<PathBuf> = roots
+        .into_iter()
+        .flat_map(|root| {
+            Walker::new(root)
+                .with_ignore_patterns(ignore_patterns)
+                .collect_paths()
+        })
+        .map(|file_path| {
+            file_path
+                .strip_prefix(".")
+                .map(|p| p.to_path_buf())
+                .unwrap_or(file_path)
+        })
+        .collect();
+
+    // A send error means the receiver hung up; stop searching.
+    let _ = files.into_par_iter().try_for_each(|file| {
+        if deadline::expired() {
+            return Ok(());
+        }
+        let mut searcher = SearcherBuilder::new()
+            .binary_detection(BinaryDetection::quit(b'\x00'))
+            .line_number(true)
+            .build();
+        let mut results = Vec::new();
+        let mut sink = CollectSink {
+            file: file.clone(),
+            matcher: &matcher,
+            results: &mut results,
+        };
+        // Silently skip files that cannot be read (e.g. permission errors).
+        let _ = searcher.search_path(&matcher, &file, &mut sink);
+        if results.is_empty() {
+            Ok(())
+        } else {
+            tx.send(results)
+        }
+    });
+    Ok(())
+}
+
+/// Build the line matcher for `pattern`.  Without `regex` the pattern is
+/// matched as a fixed string, so metacharacters (e.g. `.`, `*`) are literal.
+fn build_matcher(pattern: &str, regex: bool, ignore_case: bool) -> Result<RegexMatcher> {
+    let mut builder = RegexMatcherBuilder::new();
+    builder.case_insensitive(ignore_case);
+    builder.line_terminator(Some(b'\n'));
+    if !regex {
+        builder.fixed_strings(true);
+    }
+    builder
+        .build(pattern)
+        .with_context(|| format!("invalid search pattern: {pattern}"))
+}
+
+/// Read search patterns from a file: one per line, with blank lines and
+/// lines starting with `#` ignored.  Surrounding whitespace is trimmed; a
+/// pattern that needs a leading `#` can escape it as `\#`.
+pub fn read_patterns_file(path: &Path) -> Result<Vec<String>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("reading patterns file {}", path.display()))?;
+    Ok(parse_patterns(&text))
+}
+
+/// Split pattern-list text into one pattern per line, skipping blank lines
+/// and `#` comments (`\#` escapes a leading `#`).
+pub fn parse_patterns(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(|l| {
+            l.strip_prefix('\\')
+                .filter(|r| r.starts_with('#'))
+                .unwrap_or(l)
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// A line matched by [`multi_search`], with the patterns it matched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiMatch {
+    pub result: SearchResult,
+    /// Indices into the pattern list, ascending.
+    pub patterns: Vec<usize>,
+}
+
+/// Combine `patterns` into one regex alternation, escaping them first unless
+/// they are already regexes.
+pub fn combined_pattern(patterns: &[String], regex: bool) -> String {
+    patterns
+        .iter()
+        .map(|p| {
+            if regex {
+                format!("(?:{p})")
+            } else {
+                regex::escape(p)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+/// Search for several patterns at once.
+///
+/// All patterns are compiled into a single alternation so each file is read
+/// once; a [`RegexSet`] then attributes every matching line to the patterns
+/// it matched.  Files are searched in parallel and results are returned in
+/// walk order, then line order.
+pub fn multi_search(
+    patterns: &[String],
+    regex: bool,
+    ignore_case: bool,
+    paths: &[String],
+) -> Result<Vec<MultiMatch>> {
+    let sources: Vec<String> = patterns
+        .iter()
+        .map(|p| if regex { p.clone() } else { regex::escape(p) })
+        .collect();
+    let set: RegexSet = RegexSetBuilder::new(&sources)
+        .case_insensitive(ignore_case)
+        .build()
+        .context("invalid search pattern")?;
+    let matcher = RegexMatcherBuilder::new()
+        .case_insensitive(ignore_case)
+        .line_terminator(Some(b'\n'))
+        .build(&combined_pattern(patterns, regex))
+        .context("invalid search pattern")?;
+
+    let roots: Vec<&Path> = if paths.is_empty() {
+        vec![Path::new(".")]
+    } else {
+        paths.iter().map(|p| Path::new(p.as_str())).collect()
+    };
+    let files: Vec<PathBuf> = roots
+        .into_iter()
+        .flat_map(|root| Walker::new(root).collect_paths())
+        .map(|file_path| {
+            file_path
+                .strip_prefix(".")
+                .map(|p| p.to_path_buf())
+                .unwrap_or(file_path)
+        })
+        .collect();
+
+    let per_file: Vec<Vec<MultiMatch>> = files
+        .into_par_iter()
+        .map(|file| {
+            if deadline::expired() {
+                return Vec::new();
+            }
+            let mut searcher = SearcherBuilder::new()
+                .binary_detection(BinaryDetection::quit(b'\x00'))
+                .line_number(true)
+                .build();
+            let mut results = Vec::new();
+            let mut sink = CollectSink {
+                file: file.clone(),
+                matcher: &matcher,
+                results: &mut results,
+            };
+            // Silently skip files that cannot be read (e.g. permission errors).
+            let _ = searcher.search_path(&matcher, &file, &mut sink);
+            results
+                .into_iter()
+                .map(|result| {
+                    let patterns = set.matches(&result.content).into_iter().collect();
+                    MultiMatch { result, patterns }
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(per_file.into_iter().flatten().collect())
+}
+
 /// A [`Sink`] implementation that collects matching lines into a
 /// `Vec<SearchResult>`.
 struct CollectSink<'a> {
     file: PathBuf,
+    matcher: &'a RegexMatcher,
     results: &'a mut Vec<SearchResult>,
 }
 
@@ -132,6 +333,9 @@ impl<'a> Sink for CollectSink<'a> {
     type Error = std::io::Error;
 
     fn matched(&mut self, _searcher: &Searcher, mat: &SinkMatch<'_>) -> Result<bool, Self::Error> {
+        if deadline::expired() {
+            return Ok(false);
+        }
         let line_number = mat.line_number().unwrap_or(0);
 
         // Convert matched bytes to a string, stripping trailing newline(s).
@@ -145,10 +349,19 @@ impl<'a> Sink for CollectSink<'a> {
             }
         };
 
+        let span = match self.matcher.find(mat.bytes()) {
+            Ok(Some(m)) => Some((m.start() as u64, m.end() as u64)),
+            _ => None,
+        };
+        let offset = mat.absolute_byte_offset();
+
         self.results.push(SearchResult {
             file: self.file.clone(),
             line: line_number,
-            col: 1,
+            col: span.map_or(1, |(start, _)| start + 1),
+            end_col: span.map(|(_, end)| end + 1),
+            start_byte: span.map(|(start, _)| offset + start),
+            end_byte: span.map(|(_, end)| offset + end),
             content,
         });
 
@@ -220,6 +433,112 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_patterns_skips_comments_and_blanks() {
+        let text = "# deprecated APIs\n\nold_api\n  legacy.call  \n\\#pragma once\n# end\n";
+        assert_eq!(
+            parse_patterns(text),
+            ["old_api", "legacy.call", "#pragma once"]
+        );
+    }
+
+    #[test]
+    fn read_patterns_file_reports_missing_file() {
+        let err = read_patterns_file(Path::new("/nonexistent/patterns.txt")).unwrap_err();
+        assert!(err.to_string().contains("patterns file"));
+    }
+
+    #[test]
+    fn streaming_search_sends_one_chunk_per_matching_file() {
+        let td = TestDir::new();
+        td.create_file("a.rs", "fn target() {}\ntarget();\n");
+        td.create_file("sub/b.rs", "target();\n");
+        td.create_file("c.rs", "nothing here\n");
+        let paths = [td.path().to_string_lossy().into_owned()];
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        text_search_streaming("target", false, false, &paths, &[], tx).unwrap();
+        let mut chunks: Vec<Vec<SearchResult>> = rx.into_iter().collect();
+        chunks.sort_by(|a, b| a[0].file.cmp(&b[0].file));
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].len(), 2);
+        assert_eq!(chunks[0][1].line, 2);
+        assert!(chunks[1][0].file.ends_with("sub/b.rs"));
+
+        let mut all: Vec<SearchResult> = chunks.into_iter().flatten().collect();
+        let mut expected = text_search("target", false, false, &paths).unwrap();
+        all.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+        expected.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+        assert_eq!(all, expected);
+    }
+
+    #[test]
+    fn streaming_search_rejects_invalid_regex_before_sending() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        assert!(text_search_streaming("(", true, false, &[], &[], tx).is_err());
+        assert!(rx.recv().is_err());
+    }
+
+    #[test]
+    fn multi_search_reports_matching_patterns_per_line() {
+        let td = TestDir::new();
+        td.create_file(
+            "a.rs",
+            "old_api();\nnew_api();\nold_api(); legacy.call();\n",
+        );
+        td.create_file("b.rs", "legacy.call();\n");
+
+        let patterns = vec!["old_api".to_string(), "legacy.call".to_string()];
+        let mut results = multi_search(
+            &patterns,
+            false,
+            false,
+            &[td.path().to_string_lossy().into_owned()],
+        )
+        .unwrap();
+        results
+            .sort_by(|a, b| (&a.result.file, a.result.line).cmp(&(&b.result.file, b.result.line)));
+
+        let summary: Vec<(u64, Vec<usize>)> = results
+            .iter()
+            .map(|m| (m.result.line, m.patterns.clone()))
+            .collect();
+        assert_eq!(summary, [(1, vec![0]), (3, vec![0, 1]), (1, vec![1])]);
+    }
+
+    #[test]
+    fn multi_search_literal_escapes_metacharacters() {
+        let td = TestDir::new();
+        td.create_file("a.txt", "legacyXcall\nlegacy.call\n");
+        let patterns = vec!["legacy.call".to_string()];
+        let results = multi_search(
+            &patterns,
+            false,
+            false,
+            &[td.path().to_string_lossy().into_owned()],
+        )
+        .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].result.line, 2);
+    }
+
+    #[test]
+    fn multi_search_regex_and_ignore_case() {
+        let td = TestDir::new();
+        td.create_file("a.txt", "FOO1\nbar22\nbaz\n");
+        let patterns = vec![r"foo\d".to_string(), r"bar\d+".to_string()];
+        let results = multi_search(
+            &patterns,
+            true,
+            true,
+            &[td.path().to_string_lossy().into_owned()],
+        )
+        .unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(multi_search(&["(".to_string()], true, false, &[]).is_err());
+    }
+
     #[test]
     fn literal_search_finds_exact_match() {
         let td = TestDir::new();
@@ -423,6 +742,48 @@ mod tests {
         assert_eq!(r.content, "target line");
     }
 
+    #[test]
+    fn col_is_byte_offset_of_first_match() {
+        let td = TestDir::new();
+        td.create_file("data.txt", "let naïve = target;\n");
+
+        let results = text_search(
+            "target",
+            false,
+            false,
+            &[td.path().to_string_lossy().into_owned()],
+        )
+        .unwrap();
+
+        assert_eq!(results.len(), 1);
+        // "ï" is two bytes, so the match starts at byte 13 (1-based 14).
+        assert_eq!(results[0].col, 14);
+        assert_eq!(results[0].end_col, Some(20));
+    }
+
+    #[test]
+    fn byte_range_is_absolute_within_file() {
+        let td = TestDir::new();
+        td.create_file(
+            "data.txt",
+            "first line
+second target
+",
+        );
+
+        let results = text_search(
+            "target",
+            false,
+            false,
+            &[td.path().to_string_lossy().into_owned()],
+        )
+        .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].start_byte, Some(18));
+        assert_eq!(results[0].end_byte, Some(24));
+    }
+
     #[test]
     fn skips_binary_files() {
         let td = TestDir::new();
@@ -0,0 +1,467 @@
+//! `wonk self update` — replace the running binary with the latest release.
+//!
+//! Releases are read from the GitHub releases API.  The asset for the
+//! current target (`wonk-<version>-<target>`, as built by the release
+//! workflow) is checked against the release's `SHA256SUMS` asset before it
+//! is written next to the running executable and renamed over it, so an
+//! interrupted or corrupt download never leaves a broken binary behind.
+//! `[update].channel` selects whether pre-releases are considered.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use ureq::Agent;
+
+/// GitHub API endpoint listing wonk releases, newest first.
+const RELEASES_URL: &str = "https://api.github.com/repos/etr/wonk/releases?per_page=30";
+
+/// Name of the checksum asset attached to each release.
+const CHECKSUMS_ASSET: &str = "SHA256SUMS";
+
+/// Largest release asset we are willing to download.
+const MAX_ASSET_BYTES: u64 = 128 * 1024 * 1024;
+
+/// Which releases `wonk self update` considers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Channel {
+    /// Only full releases (the default).
+    #[default]
+    Stable,
+    /// Full releases and pre-releases.
+    Prerelease,
+}
+
+impl FromStr for Channel {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "stable" => Ok(Self::Stable),
+            "prerelease" | "pre-release" | "beta" => Ok(Self::Prerelease),
+            _ => Err(format!(
+                "unknown update channel '{s}' (expected: stable, prerelease)"
+            )),
+        }
+    }
+}
+
+/// A `major.minor.patch[-pre]` version, ordered by semver precedence
+/// (a pre-release sorts before its release).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre: Option<String>,
+}
+
+impl FromStr for Version {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().trim_start_matches('v');
+        let (core, pre) = match s.split_once('-') {
+            Some((core, pre)) => (core, Some(pre.to_string())),
+            None => (s, None),
+        };
+        let core = core.split('+').next().unwrap_or(core);
+        let parts: Vec<u64> = core
+            .split('.')
+            .map(|p| p.parse::<u64>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("invalid version '{s}'"))?;
+        let [major, minor, patch] = parts[..] else {
+            return Err(format!("invalid version '{s}'"));
+        };
+        Ok(Self {
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (&self.pre, &other.pre) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => cmp_pre(a, b),
+            })
+    }
+}
+
+/// Compare pre-release tags by semver precedence: dot-separated identifiers
+/// left to right, numeric ones as integers and before alphanumeric ones, and
+/// the shorter list first when every shared identifier is equal.
+fn cmp_pre(a: &str, b: &str) -> Ordering {
+    let mut left = a.split('.');
+    let mut right = b.split('.');
+    loop {
+        let ord = match (left.next(), right.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => match (x.parse::<u64>(), y.parse::<u64>()) {
+                (Ok(x), Ok(y)) => x.cmp(&y),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => x.cmp(y),
+            },
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{pre}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A release asset as returned by the GitHub API.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Asset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+/// A release as returned by the GitHub API.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+}
+
+impl Release {
+    /// The release's version, parsed from its tag.
+    pub fn version(&self) -> Option<Version> {
+        self.tag_name.parse().ok()
+    }
+
+    fn asset(&self, name: &str) -> Option<&Asset> {
+        self.assets.iter().find(|a| a.name == name)
+    }
+}
+
+/// Result of comparing the running binary with the newest release.
+#[derive(Debug, Clone)]
+pub struct UpdateCheck {
+    pub current: Version,
+    pub latest: Version,
+    pub release: Release,
+}
+
+impl UpdateCheck {
+    /// Returns `true` when the newest release is newer than this binary.
+    pub fn update_available(&self) -> bool {
+        self.latest > self.current
+    }
+}
+
+/// The version of the running binary.
+pub fn current_version() -> Version {
+    env!("CARGO_PKG_VERSION")
+        .parse()
+        .expect("CARGO_PKG_VERSION is a valid version")
+}
+
+/// Target triple of the release asset matching this build, or `None` on
+/// platforms without prebuilt releases.
+pub fn release_target() -> Option<&'static str> {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("macos", "aarch64") => Some("aarch64-apple-darwin"),
+        ("macos", "x86_64") => Some("x86_64-apple-darwin"),
+        ("linux", "x86_64") => Some("x86_64-unknown-linux-musl"),
+        ("linux", "aarch64") => Some("aarch64-unknown-linux-musl"),
+        _ => None,
+    }
+}
+
+/// Name of the binary asset for `version` on `target`.
+pub fn asset_name(version: &Version, target: &str) -> String {
+    format!("wonk-{version}-{target}")
+}
+
+/// Pick the newest non-draft release allowed by `channel`.
+pub fn select_release(releases: &[Release], channel: Channel) -> Option<(&Release, Version)> {
+    releases
+        .iter()
+        .filter(|r| !r.draft)
+        .filter(|r| channel == Channel::Prerelease || !r.prerelease)
+        .filter_map(|r| r.version().map(|v| (r, v)))
+        .filter(|(_, v)| channel == Channel::Prerelease || v.pre.is_none())
+        .max_by(|(_, a), (_, b)| a.cmp(b))
+}
+
+/// Parse `sha256sum` output (`<hex>  <name>` per line) into the hash for
+/// `name`.
+pub fn checksum_for(sums: &str, name: &str) -> Option<String> {
+    sums.lines().find_map(|line| {
+        let (hash, file) = line.trim().split_once(char::is_whitespace)?;
+        let file = file.trim_start().trim_start_matches('*');
+        (file == name).then(|| hash.to_lowercase())
+    })
+}
+
+/// Lowercase hex SHA-256 of `bytes`.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn agent() -> Agent {
+    Agent::config_builder()
+        .timeout_connect(Some(Duration::from_secs(5)))
+        .timeout_global(Some(Duration::from_secs(300)))
+        .build()
+        .into()
+}
+
+/// Fetch the release list from GitHub.
+fn fetch_releases(agent: &Agent) -> Result<Vec<Release>> {
+    agent
+        .get(RELEASES_URL)
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", concat!("wonk/", env!("CARGO_PKG_VERSION")))
+        .call()
+        .context("fetching the release list from GitHub")?
+        .body_mut()
+        .read_json()
+        .context("parsing the release list")
+}
+
+fn download(agent: &Agent, url: &str) -> Result<Vec<u8>> {
+    agent
+        .get(url)
+        .header("User-Agent", concat!("wonk/", env!("CARGO_PKG_VERSION")))
+        .call()
+        .with_context(|| format!("downloading {url}"))?
+        .body_mut()
+        .with_config()
+        .limit(MAX_ASSET_BYTES)
+        .read_to_vec()
+        .with_context(|| format!("downloading {url}"))
+}
+
+/// Compare the running binary with the newest release on `channel`.
+pub fn check(channel: Channel) -> Result<UpdateCheck> {
+    let releases = fetch_releases(&agent())?;
+    let (release, latest) =
+        select_release(&releases, channel).ok_or_else(|| anyhow!("no published release found"))?;
+    Ok(UpdateCheck {
+        current: current_version(),
+        latest,
+        release: release.clone(),
+    })
+}
+
+/// Download the binary for `check.release`, verify it against the
+/// release's checksums, and atomically replace `exe` with it.
+pub fn install(check: &UpdateCheck, exe: &Path) -> Result<()> {
+    let target =
+        release_target().ok_or_else(|| anyhow!("no prebuilt releases for this platform"))?;
+    let name = asset_name(&check.latest, target);
+    let release = &check.release;
+    let binary = release
+        .asset(&name)
+        .ok_or_else(|| anyhow!("release {} has no asset {name}", release.tag_name))?;
+    let sums = release.asset(CHECKSUMS_ASSET).ok_or_else(|| {
+        anyhow!(
+            "release {} has no {CHECKSUMS_ASSET}; refusing to install an unverified binary",
+            release.tag_name
+        )
+    })?;
+
+    let agent = agent();
+    let sums = String::from_utf8(download(&agent, &sums.browser_download_url)?)
+        .context("reading checksums")?;
+    let expected = checksum_for(&sums, &name)
+        .ok_or_else(|| anyhow!("{CHECKSUMS_ASSET} does not list {name}"))?;
+    let bytes = download(&agent, &binary.browser_download_url)?;
+    let actual = sha256_hex(&bytes);
+    if actual != expected {
+        bail!("checksum mismatch for {name}: expected {expected}, got {actual}");
+    }
+
+    replace_binary(exe, &bytes)
+}
+
+/// Write `bytes` to a temporary file next to `exe` and rename it over
+/// `exe`, keeping `exe`'s permissions.  The rename is atomic, so `exe` is
+/// either the old or the new binary, never a partial one.
+pub fn replace_binary(exe: &Path, bytes: &[u8]) -> Result<()> {
+    let dir = exe
+        .parent()
+        .ok_or_else(|| anyhow!("{} has no parent directory", exe.display()))?;
+    let file_name = exe
+        .file_name()
+        .ok_or_else(|| anyhow!("{} has no file name", exe.display()))?
+        .to_string_lossy();
+    let tmp = dir.join(format!(".{file_name}.update-{}", std::process::id()));
+
+    let result = (|| -> Result<()> {
+        let mut file =
+            std::fs::File::create(&tmp).with_context(|| format!("creating {}", tmp.display()))?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        let perms = std::fs::metadata(exe)
+            .with_context(|| format!("reading permissions of {}", exe.display()))?
+            .permissions();
+        std::fs::set_permissions(&tmp, perms)?;
+        std::fs::rename(&tmp, exe).with_context(|| format!("replacing {}", exe.display()))
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(tag: &str, prerelease: bool) -> Release {
+        Release {
+            tag_name: tag.to_string(),
+            draft: false,
+            prerelease,
+            assets: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn parse_and_order_versions() {
+        let v: Version = "v4.14.1".parse().unwrap();
+        assert_eq!((v.major, v.minor, v.patch), (4, 14, 1));
+        assert!(v.pre.is_none());
+        assert_eq!(v.to_string(), "4.14.1");
+
+        let pre: Version = "4.15.0-rc.1".parse().unwrap();
+        let release: Version = "4.15.0".parse().unwrap();
+        assert!(pre < release);
+        assert!(v < pre);
+        let parse = |s: &str| s.parse::<Version>().unwrap();
+        assert!(parse("1.0.0-rc.10") > parse("1.0.0-rc.9"));
+        assert!(parse("1.0.0-alpha") < parse("1.0.0-alpha.1"));
+        assert!(parse("1.0.0-alpha.1") < parse("1.0.0-alpha.beta"));
+        assert!(parse("1.0.0-beta.11") < parse("1.0.0-rc.1"));
+        assert!("4.9.0".parse::<Version>().unwrap() < "4.10.0".parse().unwrap());
+        assert!("4.15".parse::<Version>().is_err());
+        assert!("latest".parse::<Version>().is_err());
+    }
+
+    #[test]
+    fn parse_channel() {
+        assert_eq!("stable".parse::<Channel>().unwrap(), Channel::Stable);
+        assert_eq!(
+            "Prerelease".parse::<Channel>().unwrap(),
+            Channel::Prerelease
+        );
+        assert!("nightly".parse::<Channel>().is_err());
+    }
+
+    #[test]
+    fn select_release_respects_channel() {
+        let mut draft = release("v9.0.0", false);
+        draft.draft = true;
+        let releases = vec![
+            release("v4.16.0-rc.1", true),
+            release("v4.15.0", false),
+            draft,
+            release("not-a-version", false),
+            release("v4.14.1", false),
+        ];
+
+        let (r, v) = select_release(&releases, Channel::Stable).unwrap();
+        assert_eq!(r.tag_name, "v4.15.0");
+        assert_eq!(v.to_string(), "4.15.0");
+
+        let (r, _) = select_release(&releases, Channel::Prerelease).unwrap();
+        assert_eq!(r.tag_name, "v4.16.0-rc.1");
+
+        assert!(select_release(&[], Channel::Stable).is_none());
+    }
+
+    #[test]
+    fn checksum_lookup_and_hashing() {
+        let sums = "\
+e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  wonk-4.15.0-x86_64-unknown-linux-musl
+ABCDEF  *wonk-4.15.0-aarch64-apple-darwin
+";
+        assert_eq!(
+            checksum_for(sums, "wonk-4.15.0-x86_64-unknown-linux-musl").as_deref(),
+            Some("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+        );
+        assert_eq!(
+            checksum_for(sums, "wonk-4.15.0-aarch64-apple-darwin").as_deref(),
+            Some("abcdef")
+        );
+        assert!(checksum_for(sums, "wonk-4.15.0-x86_64-apple-darwin").is_none());
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn asset_names_match_release_workflow() {
+        let v: Version = "4.15.0".parse().unwrap();
+        assert_eq!(
+            asset_name(&v, "x86_64-apple-darwin"),
+            "wonk-4.15.0-x86_64-apple-darwin"
+        );
+    }
+
+    #[test]
+    fn replace_binary_swaps_contents_and_keeps_permissions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let exe = dir.path().join("wonk");
+        std::fs::write(&exe, b"old").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&exe, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        replace_binary(&exe, b"new").unwrap();
+
+        assert_eq!(std::fs::read(&exe).unwrap(), b"new");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&exe).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o755);
+        }
+        let leftovers: Vec<_> = std::fs::read_dir(dir.path()).unwrap().collect();
+        assert_eq!(leftovers.len(), 1, "temporary file removed");
+    }
+}
@@ -0,0 +1,306 @@
+//! Saved query results for `wonk snapshot` and `wonk compare`.
+//!
+//! A snapshot is the JSON output of one wonk query, stored with the query
+//! itself under `<repo>/.wonk/snapshots/<name>.json`.  Comparing re-runs the
+//! stored query and reports which records were added or removed, treating
+//! the records as a multiset so reordering is not a change.  Queries run as
+//! a child `wonk` process, so any query command can be snapshotted without
+//! routing its output through a second code path.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Directory, relative to the repository root, holding snapshots.
+pub const DIR: &str = ".wonk/snapshots";
+
+/// Budget passed to the child query: piped output otherwise gets the
+/// automatic budget and a snapshot would silently hold a truncated result.
+const UNBOUNDED_BUDGET: u32 = u32::MAX;
+
+/// Location fields dropped by `wonk compare --ignore-positions`, so code
+/// that only moved is not reported as a change.
+pub const POSITION_FIELDS: &[&str] = &[
+    "line",
+    "col",
+    "end_line",
+    "end_col",
+    "start_byte",
+    "end_byte",
+];
+
+/// Index provenance fields (see [`crate::stamp`]), which change with every
+/// reindex and so are never compared.
+const STAMP_FIELDS: &[&str] = &["content_hash", "index_generation"];
+
+/// A stored query result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub name: String,
+    /// Arguments of the query, as given after `--`.
+    pub query: Vec<String>,
+    /// Seconds since the Unix epoch when the snapshot was taken.
+    pub created: u64,
+    /// One JSON record per result, in the order the query printed them.
+    pub entries: Vec<Value>,
+}
+
+impl Snapshot {
+    /// A snapshot of `entries` taken now.
+    pub fn new(name: &str, query: &[String], entries: Vec<Value>) -> Self {
+        let created = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self {
+            name: name.to_string(),
+            query: query.to_vec(),
+            created,
+            entries,
+        }
+    }
+}
+
+/// Records that differ between a snapshot and the current results.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Diff {
+    pub added: Vec<Value>,
+    pub removed: Vec<Value>,
+    pub unchanged: usize,
+}
+
+/// The file for snapshot `name` in `repo_root`.
+///
+/// Names are restricted to ASCII letters, digits, `-`, `_`, and `.` (not
+/// leading) so a name can never point outside the snapshot directory.
+pub fn path_for(repo_root: &Path, name: &str) -> Result<PathBuf> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        bail!("invalid snapshot name {name:?}: use letters, digits, '-', '_', or '.'");
+    }
+    Ok(repo_root.join(DIR).join(format!("{name}.json")))
+}
+
+/// Run `query` with the current wonk binary from `repo_root` and collect its
+/// JSON records.
+///
+/// `WONK_PROFILE` is not passed on, so a snapshot depends only on its stored
+/// query; a query that needs a profile names it with `--profile`.
+pub fn run_query(repo_root: &Path, query: &[String]) -> Result<Vec<Value>> {
+    let exe = std::env::current_exe().context("locating the wonk executable")?;
+    let output = Command::new(exe)
+        .args(["--format", "json", "--quiet", "--budget"])
+        .arg(UNBOUNDED_BUDGET.to_string())
+        .args(query)
+        .current_dir(repo_root)
+        .env_remove("WONK_PROFILE")
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()
+        .context("running the snapshot query")?;
+    if !output.status.success() {
+        bail!(
+            "query `wonk {}` failed ({})",
+            query.join(" "),
+            output.status
+        );
+    }
+    parse_records(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parse one JSON record per non-empty line.
+///
+/// Message records are not results and are left out; warnings and errors
+/// are passed on through [`crate::output::print_warning`].
+fn parse_records(stdout: &str) -> Result<Vec<Value>> {
+    let mut records = Vec::new();
+    for line in stdout.lines().filter(|line| !line.trim().is_empty()) {
+        let record: Value = serde_json::from_str(line)
+            .with_context(|| format!("query printed a non-JSON line: {line}"))?;
+        if let Some(kind) = crate::output::message_kind(&record) {
+            if kind != crate::output::MessageKind::Hint {
+                crate::output::print_warning(record["message"].as_str().unwrap_or_default());
+            }
+            continue;
+        }
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Write `snapshot` to `path`, creating the snapshot directory if needed.
+pub fn save(path: &Path, snapshot: &Snapshot) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    }
+    let mut json = serde_json::to_string_pretty(snapshot)?;
+    json.push('\n');
+    std::fs::write(path, json).with_context(|| format!("writing {}", path.display()))
+}
+
+/// Read the snapshot stored at `path`.
+pub fn load(path: &Path) -> Result<Snapshot> {
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("no snapshot at {}", path.display()))?;
+    serde_json::from_str(&json).with_context(|| format!("reading snapshot {}", path.display()))
+}
+
+/// Compare `before` with `after`, ignoring the top-level fields in `ignore`
+/// (e.g. `line` so that code moving within a file is not a change) and the
+/// index provenance of each record.
+pub fn diff(before: &[Value], after: &[Value], ignore: &[String]) -> Diff {
+    let before_keys: Vec<String> = before.iter().map(|v| key(v, ignore)).collect();
+    let after_keys: Vec<String> = after.iter().map(|v| key(v, ignore)).collect();
+
+    let unmatched = |keys: &[String], against: &[String], values: &[Value]| {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for k in against {
+            *counts.entry(k.as_str()).or_default() += 1;
+        }
+        keys.iter()
+            .zip(values)
+            .filter(|(k, _)| match counts.get_mut(k.as_str()) {
+                Some(n) if *n > 0 => {
+                    *n -= 1;
+                    false
+                }
+                _ => true,
+            })
+            .map(|(_, v)| v.clone())
+            .collect::<Vec<_>>()
+    };
+
+    let removed = unmatched(&before_keys, &after_keys, before);
+    let added = unmatched(&after_keys, &before_keys, after);
+    Diff {
+        unchanged: before.len() - removed.len(),
+        added,
+        removed,
+    }
+}
+
+/// Comparison key for a record with the `ignore` and stamp fields dropped.
+fn key(value: &Value, ignore: &[String]) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut map = map.clone();
+            map.retain(|field, _| {
+                !ignore.contains(field) && !STAMP_FIELDS.contains(&field.as_str())
+            });
+            Value::Object(map).to_string()
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// One-line description of a record: `file:line: text` when the record has
+/// a location, otherwise its compact JSON.
+pub fn describe(entry: &Value) -> String {
+    let Some(file) = entry.get("file").and_then(Value::as_str) else {
+        return entry.to_string();
+    };
+    let mut out = file.to_string();
+    if let Some(line) = entry.get("line").and_then(Value::as_u64) {
+        out.push_str(&format!(":{line}"));
+    }
+    if let Some(text) = crate::output::record_text(entry) {
+        out.push_str(": ");
+        out.push_str(text);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn names_stay_inside_the_snapshot_directory() {
+        let root = Path::new("/repo");
+        assert_eq!(
+            path_for(root, "deprecated-v2").unwrap(),
+            PathBuf::from("/repo/.wonk/snapshots/deprecated-v2.json")
+        );
+        for bad in ["", "../x", "a/b", ".hidden", "a b"] {
+            assert!(path_for(root, bad).is_err(), "{bad:?} accepted");
+        }
+    }
+
+    #[test]
+    fn save_then_load_round_trips() {
+        let dir = TempDir::new().unwrap();
+        let path = path_for(dir.path(), "s").unwrap();
+        let query = ["ref".to_string(), "old_api".to_string()];
+        let snapshot = Snapshot::new("s", &query, vec![json!({"file": "a.rs", "line": 3})]);
+        assert!(snapshot.created > 0);
+        save(&path, &snapshot).unwrap();
+        let loaded = load(&path).unwrap();
+        assert_eq!(loaded.query, snapshot.query);
+        assert_eq!(loaded.entries, snapshot.entries);
+        assert!(load(&dir.path().join("missing.json")).is_err());
+    }
+
+    #[test]
+    fn parse_records_rejects_non_json_lines() {
+        let records = parse_records(
+            "{\"a\":1}\n\n{\"type\":\"warning\",\"message\":\"grammar\"}\n{\"a\":2}\n",
+        )
+        .unwrap();
+        assert_eq!(records, vec![json!({"a": 1}), json!({"a": 2})]);
+        assert!(parse_records("src/a.rs:1:x\n").is_err());
+    }
+
+    #[test]
+    fn diff_treats_records_as_a_multiset() {
+        let a = json!({"file": "a.rs", "line": 1});
+        let b = json!({"file": "b.rs", "line": 2});
+        let c = json!({"file": "c.rs", "line": 3});
+        let d = diff(
+            &[a.clone(), b.clone(), b.clone()],
+            &[b.clone(), c.clone(), a.clone()],
+            &[],
+        );
+        assert_eq!(d.added, vec![c]);
+        assert_eq!(d.removed, vec![b]);
+        assert_eq!(d.unchanged, 2);
+    }
+
+    #[test]
+    fn diff_ignores_listed_fields() {
+        let before = [json!({"file": "a.rs", "line": 1})];
+        let after = [json!({"file": "a.rs", "line": 7})];
+        assert_eq!(diff(&before, &after, &[]).added.len(), 1);
+        let d = diff(&before, &after, &["line".into()]);
+        assert!(d.added.is_empty() && d.removed.is_empty());
+        assert_eq!(d.unchanged, 1);
+    }
+
+    #[test]
+    fn diff_ignores_index_provenance() {
+        let before = [json!({"file": "a.rs", "content_hash": "h1", "index_generation": 3})];
+        let after = [json!({"file": "a.rs", "content_hash": "h2", "index_generation": 9})];
+        let d = diff(&before, &after, &[]);
+        assert!(d.added.is_empty() && d.removed.is_empty());
+        // Snapshots taken before results were stamped still match.
+        assert_eq!(diff(&[json!({"file": "a.rs"})], &after, &[]).unchanged, 1);
+    }
+
+    #[test]
+    fn describe_prefers_location_and_text() {
+        assert_eq!(
+            describe(&json!({"file": "a.rs", "line": 4, "content": "  old_api(x);"})),
+            "a.rs:4: old_api(x);"
+        );
+        assert_eq!(describe(&json!({"total": 3})), "{\"total\":3}");
+    }
+}
@@ -0,0 +1,139 @@
+//! Index provenance stamped on structured results.
+//!
+//! Search, symbol, reference, signature, and show records in JSON or TOON
+//! output carry a [`SourceStamp`]: the content hash their file had when it
+//! was indexed, and the generation of the index they were read from.  A
+//! consumer caching results can compare either against a fresh query to
+//! tell that the file, or the index as a whole, has moved on since.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use rusqlite::Connection;
+
+use crate::db;
+use crate::output::SourceStamp;
+
+/// Looks up the stamps of results, querying each file once.
+///
+/// The index is opened on the first lookup, after the command has run, so
+/// stamps reflect any reindexing the query did and an index built by the
+/// command itself.
+#[derive(Debug)]
+pub struct Stamper {
+    root: PathBuf,
+    index: PathBuf,
+    /// The open index and its generation; `None` until first used, and
+    /// `Some(None)` when the index cannot be opened.
+    conn: Option<Option<(Connection, u64)>>,
+    hashes: HashMap<String, Option<String>>,
+}
+
+impl Stamper {
+    /// Stamp results of the repository at `root` from the index at `index`.
+    pub fn new(root: &Path, index: PathBuf) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            index,
+            conn: None,
+            hashes: HashMap::new(),
+        }
+    }
+
+    /// The stamp for a result in `file`, given relative to the repository
+    /// root or the current directory.  `None` when there is no index.
+    pub fn stamp(&mut self, file: &str) -> Option<SourceStamp> {
+        let index = &self.index;
+        let (conn, generation) = self
+            .conn
+            .get_or_insert_with(|| {
+                let conn = db::open_existing(index).ok()?;
+                let generation = db::index_generation(&conn);
+                Some((conn, generation))
+            })
+            .as_ref()?;
+        let content_hash = match self.hashes.get(file) {
+            Some(hash) => hash.clone(),
+            None => {
+                let hash = db::indexed_hash(conn, file).or_else(|| {
+                    let key = index_key(&self.root, file)?;
+                    db::indexed_hash(conn, &key)
+                });
+                self.hashes.insert(file.to_string(), hash.clone());
+                hash
+            }
+        };
+        Some(SourceStamp {
+            content_hash,
+            index_generation: *generation,
+        })
+    }
+}
+
+/// `file` relative to `root`, the form the index stores paths in.
+fn index_key(root: &Path, file: &str) -> Option<String> {
+    let path = Path::new(file);
+    let abs = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().ok()?.join(path)
+    };
+    let rel = abs.strip_prefix(root).ok()?;
+    Some(rel.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn index_with(files: &[(&str, &str)]) -> (TempDir, PathBuf) {
+        let dir = TempDir::new().unwrap();
+        let index = dir.path().join("index.db");
+        let conn = db::open(&index).unwrap();
+        for (path, hash) in files {
+            conn.execute(
+                "INSERT INTO files (path, language, hash, last_indexed) VALUES (?1, 'Rust', ?2, 0)",
+                rusqlite::params![path, hash],
+            )
+            .unwrap();
+        }
+        db::bump_generation(&conn).unwrap();
+        db::bump_generation(&conn).unwrap();
+        (dir, index)
+    }
+
+    #[test]
+    fn stamps_indexed_files_with_their_hash_and_the_generation() {
+        let (dir, index) = index_with(&[("src/lib.rs", "00c0ffee00c0ffee")]);
+        let mut stamper = Stamper::new(dir.path(), index);
+        let stamp = stamper.stamp("src/lib.rs").unwrap();
+        assert_eq!(stamp.content_hash.as_deref(), Some("00c0ffee00c0ffee"));
+        assert_eq!(stamp.index_generation, 2);
+    }
+
+    #[test]
+    fn files_outside_the_index_have_no_hash() {
+        let (dir, index) = index_with(&[]);
+        let mut stamper = Stamper::new(dir.path(), index);
+        let stamp = stamper.stamp("README.md").unwrap();
+        assert_eq!(stamp.content_hash, None);
+        assert_eq!(stamp.index_generation, 2);
+    }
+
+    #[test]
+    fn absolute_paths_are_made_repo_relative() {
+        let (dir, index) = index_with(&[("src/lib.rs", "h1")]);
+        let mut stamper = Stamper::new(dir.path(), index);
+        let abs = dir.path().join("src/lib.rs");
+        let stamp = stamper.stamp(&abs.to_string_lossy()).unwrap();
+        assert_eq!(stamp.content_hash.as_deref(), Some("h1"));
+    }
+
+    #[test]
+    fn missing_index_gives_no_stamp() {
+        let dir = TempDir::new().unwrap();
+        let mut stamper = Stamper::new(dir.path(), dir.path().join("index.db"));
+        assert!(stamper.stamp("src/lib.rs").is_none());
+    }
+}
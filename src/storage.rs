@@ -0,0 +1,185 @@
+//! Index storage behind [`QueryRouter`](crate::router::QueryRouter).
+//!
+//! [`IndexStore`] is the query surface the router needs from an index.  The
+//! SQLite index implements it directly on [`Connection`], and
+//! `[index].backend` picks how that database is opened (see [`Backend`]).
+//! Library users can give the router any other implementation with
+//! [`QueryRouter::with_store`](crate::router::QueryRouter::with_store), e.g.
+//! a store over an analytical database or over plain vectors.
+
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::Result;
+use rusqlite::Connection;
+
+use crate::db;
+use crate::errors::DbError;
+use crate::router;
+use crate::types::{Reference, Symbol};
+
+/// Lookups the query router answers from an index.
+///
+/// An empty result means "not indexed here": the router then falls back to
+/// grep, so a store only needs to answer what it knows.
+pub trait IndexStore {
+    /// Symbols named `name` (a substring unless `exact`), optionally of one
+    /// `kind` and in files whose path contains `file`.
+    fn symbols(
+        &self,
+        name: &str,
+        kind: Option<&str>,
+        file: Option<&str>,
+        exact: bool,
+    ) -> Result<Vec<Symbol>, DbError>;
+
+    /// Symbols named exactly any of `names`, with the same `kind` and `file`
+    /// filters as [`symbols`](Self::symbols).  The default asks once per
+    /// name; stores that can match a whole list at once should override it.
+    fn symbols_named(
+        &self,
+        names: &[&str],
+        kind: Option<&str>,
+        file: Option<&str>,
+    ) -> Result<Vec<Symbol>, DbError> {
+        let mut results = Vec::new();
+        for name in names {
+            results.extend(self.symbols(name, kind, file, true)?);
+        }
+        Ok(results)
+    }
+
+    /// References to `name`.
+    fn references(&self, name: &str) -> Result<Vec<Reference>, DbError>;
+
+    /// Functions and methods whose name contains `name`.
+    fn signatures(&self, name: &str) -> Result<Vec<Symbol>, DbError>;
+
+    /// All symbols defined in `path`, in line order.
+    fn symbols_in_file(&self, path: &str) -> Result<Vec<Symbol>, DbError>;
+
+    /// Files and modules `file` imports.
+    fn deps(&self, file: &str) -> Result<Vec<String>, DbError>;
+
+    /// Files importing `file`; with `fuzzy`, also files with an unresolved
+    /// import that names it.
+    fn rdeps(&self, file: &str, fuzzy: bool) -> Result<Vec<String>, DbError>;
+}
+
+impl IndexStore for Connection {
+    fn symbols(
+        &self,
+        name: &str,
+        kind: Option<&str>,
+        file: Option<&str>,
+        exact: bool,
+    ) -> Result<Vec<Symbol>, DbError> {
+        router::query_symbols_db_with_file(self, name, kind, file, exact)
+    }
+
+    fn symbols_named(
+        &self,
+        names: &[&str],
+        kind: Option<&str>,
+        file: Option<&str>,
+    ) -> Result<Vec<Symbol>, DbError> {
+        router::query_symbols_named_db(self, names, kind, file)
+    }
+
+    fn references(&self, name: &str) -> Result<Vec<Reference>, DbError> {
+        router::query_references_db(self, name)
+    }
+
+    fn signatures(&self, name: &str) -> Result<Vec<Symbol>, DbError> {
+        router::query_signatures_db(self, name)
+    }
+
+    fn symbols_in_file(&self, path: &str) -> Result<Vec<Symbol>, DbError> {
+        router::query_symbols_in_file_db(self, path)
+    }
+
+    fn deps(&self, file: &str) -> Result<Vec<String>, DbError> {
+        router::query_deps_db(self, file)
+    }
+
+    fn rdeps(&self, file: &str, fuzzy: bool) -> Result<Vec<String>, DbError> {
+        router::query_rdeps_db(self, file, fuzzy)
+    }
+}
+
+/// How the query router opens the index, from `[index].backend`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// Query the index file in place.
+    #[default]
+    Sqlite,
+    /// Copy the index into memory when it is opened and query the copy.
+    /// Opening costs a full read of the index; in return a long-running
+    /// process (`wonk mcp serve`) never waits on the disk or on a writer.
+    /// Files refreshed at query time are updated in the copy only.
+    Memory,
+}
+
+impl Backend {
+    pub const NAMES: &[&str] = &["sqlite", "memory"];
+
+    /// Open the index at `path` with this backend.
+    pub fn open(self, path: &Path) -> Result<Connection> {
+        match self {
+            Backend::Sqlite => db::open_existing(path),
+            Backend::Memory => db::open_in_memory_copy(path),
+        }
+    }
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sqlite" => Ok(Backend::Sqlite),
+            "memory" => Ok(Backend::Memory),
+            other => Err(format!(
+                "unknown index backend {other:?} (available: {})",
+                Backend::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backend_names_parse() {
+        for name in Backend::NAMES {
+            assert!(name.parse::<Backend>().is_ok(), "{name}");
+        }
+        let err = "duckdb".parse::<Backend>().unwrap_err();
+        assert!(err.contains("available: sqlite, memory"), "{err}");
+    }
+
+    #[test]
+    fn memory_backend_copies_the_index() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("index.db");
+        let disk = db::open(&path).unwrap();
+        disk.execute(
+            "INSERT INTO symbols (name, kind, file, line, col, signature, language) \
+             VALUES ('parse', 'function', 'src/a.rs', 3, 0, 'fn parse()', 'Rust')",
+            [],
+        )
+        .unwrap();
+
+        let mem = Backend::Memory.open(&path).unwrap();
+        assert!(mem.path().is_none_or(str::is_empty));
+        let found = mem.symbols("parse", None, None, true).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].file, "src/a.rs");
+
+        // The copy is independent of the file.
+        disk.execute("DELETE FROM symbols", []).unwrap();
+        assert_eq!(mem.symbols("parse", None, None, true).unwrap().len(), 1);
+    }
+}
@@ -3,13 +3,15 @@
 //! Queries the SQLite index to aggregate structural metrics (file count, line
 //! count, symbol counts by kind, language breakdown, dependency count) for a
 //! given path. Supports three detail levels and recursive depth traversal.
+//! Also provides the repo-wide per-language totals behind `wonk langs`.
 
 use anyhow::Result;
 use rusqlite::Connection;
 
 use crate::config::LlmConfig;
 use crate::types::{
-    DetailLevel, ImportEdge, SummaryMetrics, SummaryPathType, SummaryResult, SummarySymbol,
+    DetailLevel, FileListing, ImportEdge, LanguageStats, SummaryMetrics, SummaryPathType,
+    SummaryResult, SummarySymbol,
 };
 
 /// Maximum recursion depth to prevent unbounded resource consumption.
@@ -173,6 +175,55 @@ pub fn summarize_path(
     })
 }
 
+/// List every indexed file under `path` with its language, symbol count, and
+/// symbols, ordered by path with each file's symbols ordered by line.
+///
+/// A file path yields a single-entry listing. Outline detail keeps only
+/// top-level types and functions (capped at 50 per file); rich detail keeps
+/// every symbol, including methods and nested items.
+pub fn list_files(conn: &Connection, path: &str, detail: DetailLevel) -> Result<Vec<FileListing>> {
+    let normalized = normalize_path(path);
+    let like_pattern = match detect_path_type(conn, &normalized)? {
+        SummaryPathType::File => normalized,
+        SummaryPathType::Directory if normalized == "." => "%".to_string(),
+        SummaryPathType::Directory => format!("{}/%", escape_like(&normalized)),
+    };
+
+    let mut file_stmt = conn.prepare_cached(
+        "SELECT f.path, f.language, \
+         (SELECT COUNT(*) FROM symbols s WHERE s.file = f.path) \
+         FROM files f WHERE f.path LIKE ?1 ESCAPE '\\' ORDER BY f.path",
+    )?;
+    let files = file_stmt
+        .query_map(rusqlite::params![like_pattern], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)? as usize,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let options = SummaryOptions {
+        detail,
+        depth: Some(0),
+        suppress: true,
+    };
+    files
+        .into_iter()
+        .map(|(path, language, symbol_count)| {
+            let mut symbols = symbols_for_file(conn, &path, &options)?;
+            symbols.sort_by_key(|s| (s.line, s.col));
+            Ok(FileListing {
+                path,
+                language,
+                symbol_count,
+                symbols,
+            })
+        })
+        .collect()
+}
+
 /// Get unique top-level directory names from indexed file paths.
 /// Used for the root "." summary to avoid loading the entire index.
 fn root_child_directories(conn: &Connection) -> Result<Vec<String>> {
@@ -739,6 +790,65 @@ fn generate_description(
     }
 }
 
+/// Aggregate file, line, symbol, and reference counts per language across
+/// the whole index.
+///
+/// References carry no language column, so they are attributed through the
+/// `files` row of the file they occur in.  Results are sorted by line count
+/// (descending), then by language name.
+pub fn language_stats(conn: &Connection) -> Result<Vec<LanguageStats>> {
+    let mut stats: Vec<LanguageStats> = Vec::new();
+
+    let mut stmt = conn.prepare(
+        "SELECT language, COUNT(*), COALESCE(SUM(line_count), 0) \
+         FROM files WHERE language IS NOT NULL GROUP BY language",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(LanguageStats {
+            language: row.get(0)?,
+            file_count: row.get::<_, i64>(1)? as usize,
+            line_count: row.get::<_, i64>(2)? as usize,
+            ..Default::default()
+        })
+    })?;
+    for row in rows {
+        stats.push(row?);
+    }
+
+    let mut sym_stmt = conn.prepare("SELECT language, COUNT(*) FROM symbols GROUP BY language")?;
+    let sym_rows = sym_stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize))
+    })?;
+    for row in sym_rows {
+        let (lang, count) = row?;
+        if let Some(entry) = stats.iter_mut().find(|s| s.language == lang) {
+            entry.symbol_count = count;
+        }
+    }
+
+    let mut ref_stmt = conn.prepare(
+        "SELECT f.language, COUNT(*) FROM \"references\" r \
+         JOIN files f ON f.path = r.file \
+         WHERE f.language IS NOT NULL GROUP BY f.language",
+    )?;
+    let ref_rows = ref_stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize))
+    })?;
+    for row in ref_rows {
+        let (lang, count) = row?;
+        if let Some(entry) = stats.iter_mut().find(|s| s.language == lang) {
+            entry.reference_count = count;
+        }
+    }
+
+    stats.sort_by(|a, b| {
+        b.line_count
+            .cmp(&a.line_count)
+            .then_with(|| a.language.cmp(&b.language))
+    });
+    Ok(stats)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -808,6 +918,65 @@ mod tests {
         assert!(total_syms >= 2);
     }
 
+    #[test]
+    fn list_files_one_entry_per_file_sorted() {
+        let (_dir, conn) = make_indexed_repo(&[
+            (
+                "src/b.rs",
+                "struct Beta;\nimpl Beta {\n    fn new() -> Self { Beta }\n}\n",
+            ),
+            ("src/a.rs", "fn zeta() {}\nfn alpha() {}\n"),
+            ("src_other.rs", "fn other() {}\n"),
+        ]);
+
+        let listing = list_files(&conn, "src", DetailLevel::Outline).unwrap();
+        let paths: Vec<&str> = listing.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["src/a.rs", "src/b.rs"]);
+
+        let a = &listing[0];
+        assert_eq!(a.language, "Rust");
+        assert_eq!(a.symbol_count, 2);
+        let names: Vec<&str> = a.symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["zeta", "alpha"]);
+
+        // Methods count towards the total but are not top-level.
+        let b = &listing[1];
+        assert!(b.symbol_count > b.symbols.len());
+        assert!(b.symbols.iter().all(|s| s.kind != "method"));
+    }
+
+    #[test]
+    fn list_files_rich_groups_all_symbols_by_line() {
+        let (_dir, conn) = make_indexed_repo(&[
+            (
+                "src/b.rs",
+                "fn later() {}\nstruct Beta;\nimpl Beta {\n    fn new() -> Self { Beta }\n}\n",
+            ),
+            ("src/a.rs", "fn alpha() {}\n"),
+        ]);
+
+        let listing = list_files(&conn, "src", DetailLevel::Rich).unwrap();
+        let paths: Vec<&str> = listing.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["src/a.rs", "src/b.rs"]);
+
+        let b = &listing[1];
+        assert_eq!(b.symbols.len(), b.symbol_count);
+        assert!(b.symbols.iter().any(|s| s.name == "new"));
+        assert!(b.symbols.windows(2).all(|w| w[0].line <= w[1].line));
+    }
+
+    #[test]
+    fn list_files_accepts_file_path() {
+        let (_dir, conn) = make_indexed_repo(&[("src/a.rs", "fn alpha() {}\n")]);
+        let listing = list_files(&conn, "src/a.rs", DetailLevel::Outline).unwrap();
+        assert_eq!(listing.len(), 1);
+        assert!(
+            list_files(&conn, "missing", DetailLevel::Outline)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
     #[test]
     fn summary_empty_path_returns_zero_metrics() {
         let (_dir, conn) = make_indexed_repo(&[("src/lib.rs", "fn hello() {}\n")]);
@@ -1170,4 +1339,46 @@ mod tests {
         assert!(names.contains(&"hello"));
         assert!(names.contains(&"world"));
     }
+
+    // -- language_stats ----------------------------------------------------
+
+    #[test]
+    fn language_stats_groups_by_language() {
+        let (_dir, conn) = make_indexed_repo(&[
+            ("src/a.rs", "fn alpha() {}\nfn beta() { alpha(); }\n"),
+            ("src/b.rs", "fn gamma() {}\n"),
+            ("app/main.py", "def run():\n    pass\n"),
+        ]);
+
+        let stats = language_stats(&conn).unwrap();
+        assert_eq!(stats.len(), 2);
+
+        let rust = stats.iter().find(|s| s.language == "Rust").unwrap();
+        assert_eq!(rust.file_count, 2);
+        assert_eq!(rust.line_count, 3);
+        assert_eq!(rust.symbol_count, 3);
+        assert!(rust.reference_count >= 1, "alpha() call should be counted");
+
+        let python = stats.iter().find(|s| s.language == "Python").unwrap();
+        assert_eq!(python.file_count, 1);
+        assert_eq!(python.symbol_count, 1);
+    }
+
+    #[test]
+    fn language_stats_sorted_by_lines_desc() {
+        let (_dir, conn) = make_indexed_repo(&[
+            ("a.py", "def one():\n    pass\n"),
+            ("b.rs", "fn a() {}\nfn b() {}\nfn c() {}\nfn d() {}\n"),
+        ]);
+
+        let stats = language_stats(&conn).unwrap();
+        let names: Vec<&str> = stats.iter().map(|s| s.language.as_str()).collect();
+        assert_eq!(names, vec!["Rust", "Python"]);
+    }
+
+    #[test]
+    fn language_stats_empty_index() {
+        let (_dir, conn) = make_indexed_repo(&[]);
+        assert!(language_stats(&conn).unwrap().is_empty());
+    }
 }
@@ -0,0 +1,232 @@
+//! Tree-sitter syntax tree dumps for `wonk tree-dump`.
+//!
+//! Parses a source text exactly as the indexer does (including the Rust
+//! `cfg_*!` macro expansion) and lists its nodes in pre-order with their
+//! field names and ranges, or as s-expressions.  Filtering by node kind
+//! keeps the outermost matching nodes and their subtrees, which is what
+//! query-pack authors need to see.
+
+use anyhow::{Result, anyhow};
+use tree_sitter::{Node, Tree};
+
+use crate::indexer::{self, Lang};
+use crate::output::TreeNodeOutput;
+
+/// Longest leaf text shown before it is cut with `...`.
+const MAX_TEXT_CHARS: usize = 40;
+
+/// What to include in a dump.
+#[derive(Debug, Clone, Default)]
+pub struct DumpOptions {
+    /// Only show nodes of these kinds (and their subtrees); empty shows all.
+    pub kinds: Vec<String>,
+    /// Include anonymous nodes (punctuation and keywords).
+    pub all: bool,
+    /// Deepest level shown, relative to the root or each matched node.
+    pub max_depth: Option<usize>,
+}
+
+/// Parse `source` as `lang`, returning the tree and the text it was built
+/// from.
+fn parse(lang: Lang, source: &str) -> Result<(Tree, String)> {
+    let text = indexer::parse_source(lang, source);
+    let mut parser = indexer::get_parser(lang)
+        .ok_or_else(|| anyhow!("{} grammar is unavailable", lang.name()))?;
+    let tree = parser
+        .parse(text.as_bytes(), None)
+        .ok_or_else(|| anyhow!("tree-sitter failed to parse the input"))?;
+    Ok((tree, text))
+}
+
+/// Nodes of `source` in pre-order, filtered by `opts`.
+pub fn dump(lang: Lang, source: &str, opts: &DumpOptions) -> Result<Vec<TreeNodeOutput>> {
+    let (tree, text) = parse(lang, source)?;
+    let mut nodes = Vec::new();
+    visit(&tree, &opts.kinds, |node, field, depth| {
+        if !opts.all && !node.is_named() {
+            return;
+        }
+        if opts.max_depth.is_some_and(|max| depth > max) {
+            return;
+        }
+        nodes.push(node_output(node, field, depth, &text));
+    });
+    Ok(nodes)
+}
+
+/// S-expressions for the whole tree, or for each outermost node whose kind
+/// is in `kinds`.
+pub fn sexp(lang: Lang, source: &str, kinds: &[String]) -> Result<Vec<String>> {
+    let (tree, _) = parse(lang, source)?;
+    if kinds.is_empty() {
+        return Ok(vec![tree.root_node().to_sexp()]);
+    }
+    let mut out = Vec::new();
+    visit(&tree, kinds, |node, _, depth| {
+        if depth == 0 {
+            out.push(node.to_sexp());
+        }
+    });
+    Ok(out)
+}
+
+/// Walk `tree` in pre-order, calling `f(node, field, depth)` for every node
+/// when `kinds` is empty, otherwise for every node inside an outermost node
+/// of a listed kind (with `depth` relative to that node).
+fn visit<'t>(tree: &'t Tree, kinds: &[String], mut f: impl FnMut(Node<'t>, Option<&str>, usize)) {
+    let mut cursor = tree.walk();
+    let mut depth = 0usize;
+    // Depth of the matched node whose subtree is being shown.
+    let mut inside: Option<usize> = None;
+    loop {
+        let node = cursor.node();
+        if inside.is_some_and(|d| depth <= d) {
+            inside = None;
+        }
+        if kinds.is_empty() {
+            f(node, cursor.field_name(), depth);
+        } else if let Some(d) = inside {
+            f(node, cursor.field_name(), depth - d);
+        } else if kinds.iter().any(|k| k == node.kind()) {
+            inside = Some(depth);
+            f(node, cursor.field_name(), 0);
+        }
+
+        if cursor.goto_first_child() {
+            depth += 1;
+            continue;
+        }
+        loop {
+            if cursor.goto_next_sibling() {
+                break;
+            }
+            if !cursor.goto_parent() {
+                return;
+            }
+            depth -= 1;
+        }
+    }
+}
+
+fn node_output(node: Node, field: Option<&str>, depth: usize, text: &str) -> TreeNodeOutput {
+    let start = node.start_position();
+    let end = node.end_position();
+    let leaf_text = (node.child_count() == 0 && !node.is_missing())
+        .then(|| node.utf8_text(text.as_bytes()).ok())
+        .flatten()
+        .map(truncate);
+    TreeNodeOutput {
+        kind: node.kind().to_string(),
+        field: field.map(str::to_string),
+        depth,
+        named: node.is_named(),
+        missing: node.is_missing(),
+        start_line: start.row + 1,
+        start_col: start.column,
+        end_line: end.row + 1,
+        end_col: end.column,
+        start_byte: node.start_byte(),
+        end_byte: node.end_byte(),
+        text: leaf_text,
+    }
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_TEXT_CHARS {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(MAX_TEXT_CHARS).collect();
+    format!("{cut}...")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "fn add(a: i32) -> i32 {\n    a + 1\n}\n";
+
+    fn kinds(nodes: &[TreeNodeOutput]) -> Vec<(usize, &str)> {
+        nodes.iter().map(|n| (n.depth, n.kind.as_str())).collect()
+    }
+
+    #[test]
+    fn dump_lists_named_nodes_in_pre_order() {
+        let nodes = dump(Lang::Rust, SOURCE, &DumpOptions::default()).unwrap();
+        assert_eq!(nodes[0].kind, "source_file");
+        assert_eq!(nodes[1].kind, "function_item");
+        assert_eq!((nodes[1].start_line, nodes[1].end_line), (1, 3));
+
+        let name = &nodes[2];
+        assert_eq!(name.kind, "identifier");
+        assert_eq!(name.field.as_deref(), Some("name"));
+        assert_eq!(name.depth, 2);
+        assert_eq!(name.text.as_deref(), Some("add"));
+        assert_eq!((name.start_col, name.end_col), (3, 6));
+        assert!(nodes.iter().all(|n| n.named));
+    }
+
+    #[test]
+    fn dump_all_includes_anonymous_nodes() {
+        let all = DumpOptions {
+            all: true,
+            ..DumpOptions::default()
+        };
+        let nodes = dump(Lang::Rust, SOURCE, &all).unwrap();
+        assert!(nodes.iter().any(|n| n.kind == "fn" && !n.named));
+        assert!(nodes.iter().any(|n| n.kind == "+"));
+    }
+
+    #[test]
+    fn dump_filters_by_kind_and_depth() {
+        let opts = DumpOptions {
+            kinds: vec!["binary_expression".into()],
+            ..DumpOptions::default()
+        };
+        let nodes = dump(Lang::Rust, SOURCE, &opts).unwrap();
+        assert_eq!(
+            kinds(&nodes),
+            vec![
+                (0, "binary_expression"),
+                (1, "identifier"),
+                (1, "integer_literal")
+            ]
+        );
+
+        let shallow = DumpOptions {
+            max_depth: Some(1),
+            ..DumpOptions::default()
+        };
+        let nodes = dump(Lang::Rust, SOURCE, &shallow).unwrap();
+        assert_eq!(
+            kinds(&nodes),
+            vec![(0, "source_file"), (1, "function_item")]
+        );
+    }
+
+    #[test]
+    fn sexp_for_tree_and_matches() {
+        let whole = sexp(Lang::Rust, SOURCE, &[]).unwrap();
+        assert_eq!(whole.len(), 1);
+        assert!(whole[0].starts_with("(source_file (function_item name: (identifier)"));
+
+        let params = sexp(Lang::Rust, SOURCE, &["parameter".into()]).unwrap();
+        assert_eq!(
+            params,
+            vec!["(parameter pattern: (identifier) type: (primitive_type))"]
+        );
+    }
+
+    #[test]
+    fn long_leaf_text_is_truncated() {
+        let source = format!("x = \"{}\"\n", "a".repeat(100));
+        let opts = DumpOptions {
+            kinds: vec!["string_content".into()],
+            ..DumpOptions::default()
+        };
+        let nodes = dump(Lang::Python, &source, &opts).unwrap();
+        assert_eq!(
+            nodes[0].text.as_deref(),
+            Some(&*format!("{}...", "a".repeat(40)))
+        );
+    }
+}
@@ -89,6 +89,12 @@ pub struct Symbol {
     pub col: usize,
     /// 1-based line number where the symbol ends (if applicable).
     pub end_line: Option<usize>,
+    /// 0-based column where the symbol ends on `end_line` (exclusive).
+    pub end_col: Option<usize>,
+    /// Byte offset of the start of the definition in the file.
+    pub start_byte: Option<usize>,
+    /// Byte offset just past the end of the definition.
+    pub end_byte: Option<usize>,
     /// Parent symbol name (e.g. class name for a method).
     pub scope: Option<String>,
     /// Full signature text for display (e.g. the function header).
@@ -97,6 +103,38 @@ pub struct Symbol {
     pub language: String,
     /// Doc comment extracted from source (first 200 chars).
     pub doc_comment: Option<String>,
+    /// Preprocessor condition guarding the definition in C/C++, e.g.
+    /// `defined(CONFIG_ARM) && !defined(NDEBUG)`.
+    pub condition: Option<String>,
+    /// Normalised visibility (`public`, `private`, `protected`, `internal`,
+    /// `package`, or a Rust restriction such as `pub(crate)`).
+    pub visibility: Option<String>,
+    /// Modifier keywords such as `static`, `async`, `unsafe`, `abstract`.
+    pub modifiers: Vec<String>,
+    /// Generic type parameters, normalised to `T` or `T: Bound`.
+    pub generics: Vec<String>,
+}
+
+impl Symbol {
+    /// Stable identifier that survives reindexing and line shifts.
+    ///
+    /// Built from the language, the scope-qualified name, and a hash of the
+    /// whitespace-normalised signature, e.g. `rust:Router::dispatch#9f3c2a1b`.
+    /// The ID changes only when the symbol is renamed, moved to another
+    /// scope, or its signature changes.
+    pub fn stable_id(&self) -> String {
+        let signature = self
+            .signature
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let hash = xxhash_rust::xxh3::xxh3_64(signature.as_bytes()) as u32;
+        let language = self.language.to_lowercase();
+        match &self.scope {
+            Some(scope) => format!("{language}:{scope}::{}#{hash:08x}", self.name),
+            None => format!("{language}:{}#{hash:08x}", self.name),
+        }
+    }
 }
 
 /// The kind of a reference (usage site, not a definition).
@@ -108,6 +146,8 @@ pub enum ReferenceKind {
     Type,
     /// An import / use statement.
     Import,
+    /// A JSX element rendering a component (`<MyComponent />`).
+    Component,
 }
 
 impl fmt::Display for ReferenceKind {
@@ -116,15 +156,30 @@ impl fmt::Display for ReferenceKind {
             ReferenceKind::Call => "call",
             ReferenceKind::Type => "type",
             ReferenceKind::Import => "import",
+            ReferenceKind::Component => "component",
         };
         write!(f, "{s}")
     }
 }
 
+impl FromStr for ReferenceKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "call" => Ok(ReferenceKind::Call),
+            "type" => Ok(ReferenceKind::Type),
+            "import" => Ok(ReferenceKind::Import),
+            "component" => Ok(ReferenceKind::Component),
+            other => Err(format!("unknown reference kind: {other}")),
+        }
+    }
+}
+
 /// A reference (usage site) extracted from a parsed syntax tree.
 ///
-/// References include function/method calls, type annotations, and import
-/// statements.
+/// References include function/method calls, type annotations, import
+/// statements, and JSX component usages.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Reference {
     /// The referenced name (e.g. function name, type name, imported module).
@@ -137,6 +192,12 @@ pub struct Reference {
     pub line: usize,
     /// 0-based column offset where the reference occurs.
     pub col: usize,
+    /// 0-based column where the referencing node ends (exclusive).
+    pub end_col: Option<usize>,
+    /// Byte offset of the referencing node in the file.
+    pub start_byte: Option<usize>,
+    /// Byte offset just past the end of the referencing node.
+    pub end_byte: Option<usize>,
     /// Full source line for display context.
     pub context: String,
     /// Name of the enclosing function/method for call-site references.
@@ -457,6 +518,18 @@ pub struct SummarySymbol {
     pub doc_comment: Option<String>,
 }
 
+/// One file in a per-file directory listing (`wonk summary <dir> --files`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileListing {
+    pub path: String,
+    pub language: String,
+    /// Total number of symbols in the file, at any nesting level.
+    pub symbol_count: usize,
+    /// Symbols ordered by line: top-level types and functions for outline
+    /// detail, every symbol for rich detail.
+    pub symbols: Vec<SummarySymbol>,
+}
+
 /// An intra-directory import edge (from file → to file).
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ImportEdge {
@@ -464,6 +537,21 @@ pub struct ImportEdge {
     pub to: String,
 }
 
+/// Per-language totals for `wonk langs`, aggregated over the whole index.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LanguageStats {
+    /// Language name as stored in the index (e.g. "Rust", "TypeScript").
+    pub language: String,
+    /// Number of indexed files.
+    pub file_count: usize,
+    /// Total line count across those files.
+    pub line_count: usize,
+    /// Number of symbol definitions.
+    pub symbol_count: usize,
+    /// Number of references (usage sites) found in those files.
+    pub reference_count: usize,
+}
+
 /// A raw (name-based) type hierarchy edge extracted from a syntax tree.
 ///
 /// Contains unresolved names (not database IDs).  The pipeline resolves
@@ -784,10 +872,74 @@ pub struct CallPathHop {
     pub line: usize,
 }
 
+/// An async function and the non-async functions that call it, returned by
+/// `wonk report async`.
+///
+/// Each sync caller is a point where async code is entered from a
+/// synchronous context, which usually means blocking on the result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsyncBoundary {
+    /// The async function.
+    pub function: CallPathHop,
+    /// Callers that are not themselves async.
+    pub sync_callers: Vec<CallPathHop>,
+}
+
+/// An unsafe function or block, returned by `wonk report unsafe`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsafeSite {
+    /// `true` for an `unsafe { .. }` block, `false` for an unsafe declaration.
+    pub block: bool,
+    /// Path of the source file.
+    pub file: String,
+    /// 1-based line number of the declaration or block.
+    pub line: usize,
+    /// The unsafe declaration itself, or the function enclosing the block
+    /// (`None` for blocks outside any function).
+    pub symbol: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn method(line: usize, signature: &str) -> Symbol {
+        Symbol {
+            name: "dispatch".into(),
+            kind: SymbolKind::Method,
+            file: "src/router.rs".into(),
+            line,
+            col: 4,
+            end_line: None,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
+            scope: Some("Router".into()),
+            signature: signature.into(),
+            language: "Rust".into(),
+            doc_comment: None,
+            condition: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            generics: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn stable_id_ignores_location_and_whitespace() {
+        let a = method(10, "fn dispatch(&self, cli: Cli)");
+        let b = method(42, "fn  dispatch(&self,\n    cli: Cli)");
+        assert_eq!(a.stable_id(), b.stable_id());
+        assert!(a.stable_id().starts_with("rust:Router::dispatch#"));
+    }
+
+    #[test]
+    fn stable_id_changes_with_signature() {
+        let a = method(10, "fn dispatch(&self, cli: Cli)");
+        let b = method(10, "fn dispatch(&self, cli: Cli, verbose: bool)");
+        assert_ne!(a.stable_id(), b.stable_id());
+    }
+
     #[test]
     fn symbol_ref_creation() {
         let sr = SymbolRef {
@@ -1011,6 +1163,9 @@ mod tests {
             file: "a.rs".into(),
             line: 10,
             col: 4,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             context: "foo()".into(),
             caller_name: Some("bar".into()),
             confidence: 0.85,
@@ -1026,6 +1181,9 @@ mod tests {
             file: "b.rs".into(),
             line: 1,
             col: 0,
+            end_col: None,
+            start_byte: None,
+            end_byte: None,
             context: "use baz;".into(),
             caller_name: None,
             confidence: 0.5,
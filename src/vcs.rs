@@ -0,0 +1,219 @@
+//! Version control systems that change detection reads from.
+//!
+//! Repositories are recognised by their marker directory (see
+//! [`Vcs::detect`]); `wonk changes` and `--since` then list changed files and
+//! diff hunks with that system's own commands.  Every diff is requested in
+//! git format, so [`crate::impact`] parses the output of all three the same
+//! way.
+
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Result, bail};
+
+use crate::types::ChangeScope;
+
+/// A version control system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vcs {
+    Git,
+    Mercurial,
+    Jujutsu,
+}
+
+impl Vcs {
+    /// Marker directories, in detection order: a Jujutsu repository backed
+    /// by a colocated `.git` is read through `jj`, which sees its working
+    /// copy as git does not.
+    pub const MARKERS: &[(&str, Vcs)] = &[
+        (".jj", Vcs::Jujutsu),
+        (".git", Vcs::Git),
+        (".hg", Vcs::Mercurial),
+    ];
+
+    /// The system managing `repo_root`; git when no marker is found.
+    pub fn detect(repo_root: &Path) -> Vcs {
+        Self::MARKERS
+            .iter()
+            .find(|(marker, _)| repo_root.join(marker).exists())
+            .map_or(Vcs::Git, |(_, vcs)| *vcs)
+    }
+
+    /// The command-line program.
+    pub fn program(self) -> &'static str {
+        match self {
+            Vcs::Git => "git",
+            Vcs::Mercurial => "hg",
+            Vcs::Jujutsu => "jj",
+        }
+    }
+
+    /// A command listing the files changed in `scope`, one path (relative
+    /// to the repository root) per line.
+    pub fn changed_files_command(self, scope: &ChangeScope) -> Result<Command> {
+        let mut cmd = Command::new(self.program());
+        match self {
+            Vcs::Git => cmd.args(["diff", "--name-only"]),
+            Vcs::Mercurial => cmd.args([
+                "status",
+                "--no-status",
+                "--modified",
+                "--added",
+                "--removed",
+            ]),
+            Vcs::Jujutsu => cmd.args(["diff", "--name-only"]),
+        };
+        self.scope_args(&mut cmd, scope)?;
+        Ok(cmd)
+    }
+
+    /// A command printing the diff of `scope` in git format with no context
+    /// lines, limited to `file` when given.
+    pub fn diff_command(self, scope: &ChangeScope, file: Option<&str>) -> Result<Command> {
+        let mut cmd = Command::new(self.program());
+        match self {
+            Vcs::Git => cmd.args(["diff", "--unified=0"]),
+            Vcs::Mercurial => cmd.args(["diff", "--git", "--unified", "0"]),
+            Vcs::Jujutsu => cmd.args(["diff", "--git", "--context", "0"]),
+        };
+        self.scope_args(&mut cmd, scope)?;
+        if let Some(file) = file {
+            // Patterns that take the path literally, relative to the root.
+            match self {
+                Vcs::Git => cmd.arg("--").arg(file),
+                Vcs::Mercurial => cmd.arg(format!("path:{file}")),
+                Vcs::Jujutsu => cmd.arg(format!("root-file:{file:?}")),
+            };
+        }
+        Ok(cmd)
+    }
+
+    /// Apply [`ChangeScope`] to a diff or status command, validating refs.
+    ///
+    /// Mercurial and Jujutsu have no staging area: their uncommitted changes
+    /// are the working copy against its parent, for both `Unstaged` and
+    /// `All`, and `Staged` is refused.
+    fn scope_args(self, cmd: &mut Command, scope: &ChangeScope) -> Result<()> {
+        if let ChangeScope::Compare(rev) = scope {
+            crate::impact::validate_git_ref(rev)?;
+        }
+        match (self, scope) {
+            (Vcs::Git, ChangeScope::Unstaged) => {} // default: working tree vs index
+            (Vcs::Git, ChangeScope::Staged) => {
+                cmd.arg("--cached");
+            }
+            (Vcs::Git, ChangeScope::All) => {
+                cmd.arg("HEAD");
+            }
+            (Vcs::Git, ChangeScope::Compare(rev)) => {
+                cmd.arg(rev.as_str());
+            }
+            (_, ChangeScope::Staged) => {
+                bail!(
+                    "{} has no staging area; use --scope all or --scope compare",
+                    self.program()
+                );
+            }
+            (_, ChangeScope::Unstaged | ChangeScope::All) => {}
+            (Vcs::Mercurial, ChangeScope::Compare(rev)) => {
+                cmd.args(["--rev", rev]);
+            }
+            (Vcs::Jujutsu, ChangeScope::Compare(rev)) => {
+                cmd.args(["--from", rev]);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn args(cmd: &Command) -> Vec<String> {
+        std::iter::once(cmd.get_program())
+            .chain(cmd.get_args())
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn detects_by_marker() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(Vcs::detect(dir.path()), Vcs::Git);
+        fs::create_dir(dir.path().join(".hg")).unwrap();
+        assert_eq!(Vcs::detect(dir.path()), Vcs::Mercurial);
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::create_dir(dir.path().join(".jj")).unwrap();
+        assert_eq!(Vcs::detect(dir.path()), Vcs::Jujutsu);
+    }
+
+    #[test]
+    fn commands_per_system() {
+        let compare = ChangeScope::Compare("main".into());
+        assert_eq!(
+            args(&Vcs::Git.changed_files_command(&ChangeScope::All).unwrap()),
+            ["git", "diff", "--name-only", "HEAD"]
+        );
+        assert_eq!(
+            args(&Vcs::Mercurial.changed_files_command(&compare).unwrap()),
+            [
+                "hg",
+                "status",
+                "--no-status",
+                "--modified",
+                "--added",
+                "--removed",
+                "--rev",
+                "main"
+            ]
+        );
+        assert_eq!(
+            args(
+                &Vcs::Jujutsu
+                    .diff_command(&compare, Some("src/a b.rs"))
+                    .unwrap()
+            ),
+            [
+                "jj",
+                "diff",
+                "--git",
+                "--context",
+                "0",
+                "--from",
+                "main",
+                "root-file:\"src/a b.rs\""
+            ]
+        );
+        assert_eq!(
+            args(
+                &Vcs::Mercurial
+                    .diff_command(&ChangeScope::Unstaged, Some("a.rs"))
+                    .unwrap()
+            ),
+            ["hg", "diff", "--git", "--unified", "0", "path:a.rs"]
+        );
+        assert_eq!(
+            args(
+                &Vcs::Git
+                    .diff_command(&ChangeScope::Staged, Some("a.rs"))
+                    .unwrap()
+            ),
+            ["git", "diff", "--unified=0", "--cached", "--", "a.rs"]
+        );
+    }
+
+    #[test]
+    fn staging_and_bad_refs_are_refused() {
+        let err = Vcs::Jujutsu
+            .changed_files_command(&ChangeScope::Staged)
+            .unwrap_err();
+        assert!(err.to_string().contains("jj has no staging area"));
+        assert!(
+            Vcs::Mercurial
+                .diff_command(&ChangeScope::Compare("--config=x".into()), None)
+                .is_err()
+        );
+    }
+}
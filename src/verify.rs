@@ -0,0 +1,297 @@
+//! Result verification against the working tree for `--verify`.
+//!
+//! Index-backed results carry the line numbers recorded at index time, which
+//! drift as soon as a file is edited.  [`Verifier`] rechecks each result's
+//! file against the stored xxhash; files that changed are re-parsed on the
+//! fly (once per file per query) and drifted symbols and references are
+//! relocated to their current position.  Nothing is written back to the
+//! index.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use rusqlite::Connection;
+
+use crate::indexer;
+use crate::pipeline;
+use crate::types::{Reference, Symbol};
+
+/// Outcome of verifying a single result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    /// The file is unchanged since indexing (or the result is still in place).
+    Fresh,
+    /// The file changed and the result was found at a different line.
+    Relocated { from: usize },
+    /// The file changed and the result could not be found any more.
+    Stale,
+    /// The file no longer exists.
+    Missing,
+}
+
+impl Freshness {
+    /// Short label used in JSON output and grep annotations.
+    pub fn label(self) -> &'static str {
+        match self {
+            Freshness::Fresh => "fresh",
+            Freshness::Relocated { .. } => "relocated",
+            Freshness::Stale => "stale",
+            Freshness::Missing => "missing",
+        }
+    }
+}
+
+/// Current on-disk state of a file, cached per query.
+enum FileCheck {
+    Unchanged,
+    Missing,
+    Changed {
+        symbols: Vec<Symbol>,
+        refs: Vec<Reference>,
+    },
+}
+
+/// Verifies index results against the files currently on disk.
+pub struct Verifier<'a> {
+    conn: &'a Connection,
+    repo_root: &'a Path,
+    files: HashMap<String, FileCheck>,
+}
+
+impl<'a> Verifier<'a> {
+    pub fn new(conn: &'a Connection, repo_root: &'a Path) -> Self {
+        Self {
+            conn,
+            repo_root,
+            files: HashMap::new(),
+        }
+    }
+
+    /// Verify a symbol, correcting its location in place when it moved.
+    ///
+    /// A drifted symbol is matched by name, kind, and scope; when several
+    /// candidates remain, the one closest to the indexed line wins.
+    pub fn verify_symbol(&mut self, sym: &mut Symbol) -> Freshness {
+        let candidates = match self.check(&sym.file) {
+            FileCheck::Unchanged => return Freshness::Fresh,
+            FileCheck::Missing => return Freshness::Missing,
+            FileCheck::Changed { symbols, .. } => symbols,
+        };
+        let found = candidates
+            .iter()
+            .filter(|c| c.name == sym.name && c.kind == sym.kind && c.scope == sym.scope)
+            .min_by_key(|c| c.line.abs_diff(sym.line));
+        let Some(found) = found else {
+            return Freshness::Stale;
+        };
+        let from = sym.line;
+        sym.line = found.line;
+        sym.col = found.col;
+        sym.end_line = found.end_line;
+        sym.signature = found.signature.clone();
+        if from == found.line {
+            Freshness::Fresh
+        } else {
+            Freshness::Relocated { from }
+        }
+    }
+
+    /// Verify a reference, correcting its location in place when it moved.
+    ///
+    /// A drifted reference is matched by name; the occurrence closest to the
+    /// indexed line wins.
+    pub fn verify_reference(&mut self, reference: &mut Reference) -> Freshness {
+        let candidates = match self.check(&reference.file) {
+            FileCheck::Unchanged => return Freshness::Fresh,
+            FileCheck::Missing => return Freshness::Missing,
+            FileCheck::Changed { refs, .. } => refs,
+        };
+        let found = candidates
+            .iter()
+            .filter(|c| c.name == reference.name)
+            .min_by_key(|c| c.line.abs_diff(reference.line));
+        let Some(found) = found else {
+            return Freshness::Stale;
+        };
+        let from = reference.line;
+        reference.line = found.line;
+        reference.col = found.col;
+        reference.context = found.context.clone();
+        if from == found.line {
+            Freshness::Fresh
+        } else {
+            Freshness::Relocated { from }
+        }
+    }
+
+    /// Look up (or compute) the on-disk state of `rel_path`.
+    fn check(&mut self, rel_path: &str) -> &FileCheck {
+        if !self.files.contains_key(rel_path) {
+            let state = self.load(rel_path);
+            self.files.insert(rel_path.to_string(), state);
+        }
+        &self.files[rel_path]
+    }
+
+    fn load(&self, rel_path: &str) -> FileCheck {
+        let abs = self.repo_root.join(rel_path);
+        let Ok(content) = std::fs::read_to_string(&abs) else {
+            return FileCheck::Missing;
+        };
+
+        // Files absent from the index produced grep-fallback results, which
+        // were read live and cannot be stale.
+        let Ok(stored) = self.conn.query_row(
+            "SELECT hash FROM files WHERE path = ?1",
+            rusqlite::params![rel_path],
+            |row| row.get::<_, String>(0),
+        ) else {
+            return FileCheck::Unchanged;
+        };
+        if stored == pipeline::content_hash(&content) {
+            return FileCheck::Unchanged;
+        }
+
+        let (symbols, refs) = parse_symbols_and_refs(&abs, rel_path, &content);
+        FileCheck::Changed { symbols, refs }
+    }
+}
+
+/// Re-parse `content` and extract symbols and references.
+///
+/// Returns empty vectors for unsupported languages or parse failures, which
+/// makes every result in that file verify as [`Freshness::Stale`].
+fn parse_symbols_and_refs(
+    path: &Path,
+    rel_path: &str,
+    content: &str,
+) -> (Vec<Symbol>, Vec<Reference>) {
+    if crate::markdown::is_markdown(path) {
+        return (
+            crate::markdown::extract_headings(content, rel_path),
+            Vec::new(),
+        );
+    }
+    let Some(lang) = indexer::detect_language(path) else {
+        return (Vec::new(), Vec::new());
+    };
+    let source = indexer::parse_source(lang, content);
+    let Some(tree) = indexer::get_parser(lang).and_then(|mut p| p.parse(source.as_bytes(), None))
+    else {
+        return (Vec::new(), Vec::new());
+    };
+    (
+        indexer::extract_symbols(&tree, &source, rel_path, lang),
+        indexer::extract_references(&tree, &source, rel_path, lang),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::types::SymbolKind;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn indexed_repo(files: &[(&str, &str)]) -> (TempDir, Connection) {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        for (path, content) in files {
+            fs::write(root.join(path), content).unwrap();
+        }
+        pipeline::build_index(root, true).unwrap();
+        let conn = db::open_existing(&db::local_index_path(root)).unwrap();
+        (dir, conn)
+    }
+
+    fn indexed_symbol(conn: &Connection, name: &str) -> Symbol {
+        crate::router::query_symbols_db(conn, name, None, true)
+            .unwrap()
+            .remove(0)
+    }
+
+    #[test]
+    fn unchanged_file_is_fresh() {
+        let (dir, conn) = indexed_repo(&[("lib.rs", "fn alpha() {}\n")]);
+        let mut sym = indexed_symbol(&conn, "alpha");
+        let mut v = Verifier::new(&conn, dir.path());
+        assert_eq!(v.verify_symbol(&mut sym), Freshness::Fresh);
+        assert_eq!(sym.line, 1);
+    }
+
+    #[test]
+    fn moved_symbol_is_relocated() {
+        let (dir, conn) = indexed_repo(&[("lib.rs", "fn alpha() {}\nfn beta() {}\n")]);
+        fs::write(
+            dir.path().join("lib.rs"),
+            "// new header\n\nfn alpha() {}\nfn beta() {}\n",
+        )
+        .unwrap();
+
+        let mut sym = indexed_symbol(&conn, "beta");
+        let mut v = Verifier::new(&conn, dir.path());
+        assert_eq!(v.verify_symbol(&mut sym), Freshness::Relocated { from: 2 });
+        assert_eq!(sym.line, 4);
+        assert_eq!(sym.kind, SymbolKind::Function);
+    }
+
+    #[test]
+    fn removed_symbol_is_stale() {
+        let (dir, conn) = indexed_repo(&[("lib.rs", "fn alpha() {}\nfn beta() {}\n")]);
+        fs::write(dir.path().join("lib.rs"), "fn alpha() {}\n").unwrap();
+
+        let mut sym = indexed_symbol(&conn, "beta");
+        let mut v = Verifier::new(&conn, dir.path());
+        assert_eq!(v.verify_symbol(&mut sym), Freshness::Stale);
+    }
+
+    #[test]
+    fn deleted_file_is_missing() {
+        let (dir, conn) = indexed_repo(&[("lib.rs", "fn alpha() {}\n")]);
+        fs::remove_file(dir.path().join("lib.rs")).unwrap();
+
+        let mut sym = indexed_symbol(&conn, "alpha");
+        let mut v = Verifier::new(&conn, dir.path());
+        assert_eq!(v.verify_symbol(&mut sym), Freshness::Missing);
+    }
+
+    #[test]
+    fn moved_reference_is_relocated() {
+        let (dir, conn) =
+            indexed_repo(&[("lib.rs", "fn helper() {}\nfn main() {\n    helper();\n}\n")]);
+        fs::write(
+            dir.path().join("lib.rs"),
+            "fn helper() {}\nfn main() {\n    let x = 1;\n    helper();\n}\n",
+        )
+        .unwrap();
+
+        let mut r = crate::router::query_references_db(&conn, "helper")
+            .unwrap()
+            .remove(0);
+        let mut v = Verifier::new(&conn, dir.path());
+        assert_eq!(v.verify_reference(&mut r), Freshness::Relocated { from: 3 });
+        assert_eq!(r.line, 4);
+        assert!(r.context.contains("helper()"));
+    }
+
+    #[test]
+    fn unindexed_file_is_fresh() {
+        let (dir, conn) = indexed_repo(&[("lib.rs", "fn alpha() {}\n")]);
+        fs::write(dir.path().join("notes.rs"), "fn beta() {}\n").unwrap();
+
+        let mut sym = indexed_symbol(&conn, "alpha");
+        sym.file = "notes.rs".to_string();
+        let mut v = Verifier::new(&conn, dir.path());
+        assert_eq!(v.verify_symbol(&mut sym), Freshness::Fresh);
+    }
+
+    #[test]
+    fn freshness_labels() {
+        assert_eq!(Freshness::Fresh.label(), "fresh");
+        assert_eq!(Freshness::Relocated { from: 1 }.label(), "relocated");
+        assert_eq!(Freshness::Stale.label(), "stale");
+        assert_eq!(Freshness::Missing.label(), "missing");
+    }
+}
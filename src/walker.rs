@@ -8,15 +8,21 @@
 //! - Skips common build/dependency directories by default
 //! - Skips hidden files/directories except `.github`
 //! - Skips nested repositories and linked worktrees (directories containing
-//!   a `.git` entry that are not the walk root) to prevent cross-worktree
-//!   contamination during indexing
+//!   a `.git`, `.hg`, or `.jj` entry that are not the walk root) to prevent cross-worktree
+//!   contamination during indexing, except git submodules the caller lets
+//!   through with [`Walker::with_submodules`]
 //! - Supports path restriction (walking from a subdirectory)
 //! - Supports parallel file enumeration via `WalkParallel`
+//!
+//! Files that do not live on disk (archive members, see [`crate::archive`])
+//! are represented as [`SourceFile::Memory`] and filtered with
+//! [`Walker::accepts`], which applies the same exclusions and config
+//! patterns as the walk.
 
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
-use ignore::overrides::OverrideBuilder;
+use ignore::overrides::{Override, OverrideBuilder};
 use ignore::{WalkBuilder, WalkState};
 
 /// Directories that are always excluded from walks, regardless of `.gitignore`.
@@ -34,6 +40,45 @@ const DEFAULT_EXCLUSIONS: &[&str] = &[
 /// even though hidden directories are otherwise skipped).
 const HIDDEN_ALLOWLIST: &[&str] = &[".github"];
 
+/// A file to index: a path on disk, or contents held in memory under a
+/// path relative to the index root.
+#[derive(Debug, Clone)]
+pub enum SourceFile {
+    Disk(PathBuf),
+    Memory { path: PathBuf, contents: Vec<u8> },
+}
+
+impl SourceFile {
+    /// The file's path (absolute for [`SourceFile::Disk`], relative for
+    /// [`SourceFile::Memory`]).
+    pub fn path(&self) -> &Path {
+        match self {
+            SourceFile::Disk(path) | SourceFile::Memory { path, .. } => path,
+        }
+    }
+
+    /// Size in bytes, or 0 when a disk file cannot be read.
+    pub fn len(&self) -> u64 {
+        match self {
+            SourceFile::Disk(path) => std::fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+            SourceFile::Memory { contents, .. } => contents.len() as u64,
+        }
+    }
+
+    /// Returns `true` when the file has no contents.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The contents as UTF-8, or `None` when unreadable or not UTF-8.
+    pub fn read_to_string(&self) -> Option<String> {
+        match self {
+            SourceFile::Disk(path) => std::fs::read_to_string(path).ok(),
+            SourceFile::Memory { contents, .. } => String::from_utf8(contents.clone()).ok(),
+        }
+    }
+}
+
 /// A file-system walker that respects `.gitignore`, `.wonkignore`, and
 /// applies default exclusions plus optional config-driven ignore patterns.
 pub struct Walker {
@@ -41,6 +86,8 @@ pub struct Walker {
     threads: usize,
     /// Additional ignore patterns (gitignore syntax) supplied via config.
     ignore_patterns: Vec<String>,
+    /// Submodule directories walked despite their `.git` boundary.
+    submodules: Arc<Vec<PathBuf>>,
 }
 
 impl Walker {
@@ -53,6 +100,7 @@ impl Walker {
             root: root.as_ref().to_path_buf(),
             threads: 0, // 0 means ignore crate picks a sensible default
             ignore_patterns: Vec::new(),
+            submodules: Arc::new(Vec::new()),
         }
     }
 
@@ -74,6 +122,13 @@ impl Walker {
         self
     }
 
+    /// Descend into these submodule directories (given as the walk root
+    /// joined with the submodule path) instead of stopping at their `.git`.
+    pub fn with_submodules(mut self, dirs: Vec<PathBuf>) -> Self {
+        self.submodules = Arc::new(dirs);
+        self
+    }
+
     /// Build the underlying `WalkBuilder` with all our configuration applied.
     fn make_builder(&self) -> WalkBuilder {
         let mut builder = WalkBuilder::new(&self.root);
@@ -90,29 +145,11 @@ impl Walker {
         // nuanced policy (skip hidden except for allowlisted names).
         builder.hidden(false);
 
-        // Build overrides that negate (exclude) the default directories
-        // and any additional config-supplied patterns.
-        let mut overrides = OverrideBuilder::new(&self.root);
-        for dir in DEFAULT_EXCLUSIONS {
-            // The `!` prefix in override globs means "exclude this pattern".
-            let pattern = format!("!{dir}/");
-            overrides
-                .add(&pattern)
-                .expect("default exclusion pattern should be valid");
-        }
-
-        // Add config-driven ignore patterns as exclusion overrides.
-        for pattern in &self.ignore_patterns {
-            let negated = format!("!{pattern}");
-            overrides
-                .add(&negated)
-                .expect("config ignore pattern should be valid");
-        }
-
-        builder.overrides(overrides.build().expect("override builder should succeed"));
+        builder.overrides(self.overrides());
 
         // Custom filter: skip hidden entries and worktree/nested-repo boundaries.
-        builder.filter_entry(|entry| {
+        let submodules = Arc::clone(&self.submodules);
+        builder.filter_entry(move |entry| {
             let name = entry.file_name().to_string_lossy();
 
             // Skip hidden entries (name starts with `.`) unless allowlisted.
@@ -126,11 +163,14 @@ impl Walker {
 
             // Worktree boundary: skip non-root directories that contain a
             // `.git` entry (either a directory for nested repos, or a file
-            // for linked worktrees).
+            // for linked worktrees), or a Mercurial or Jujutsu repository.
             if entry.depth() > 0
                 && let Some(ft) = entry.file_type()
                 && ft.is_dir()
-                && entry.path().join(".git").exists()
+                && crate::vcs::Vcs::MARKERS
+                    .iter()
+                    .any(|(marker, _)| entry.path().join(marker).exists())
+                && !submodules.iter().any(|dir| dir == entry.path())
             {
                 return false;
             }
@@ -145,6 +185,52 @@ impl Walker {
         builder
     }
 
+    /// Overrides that negate (exclude) the default directories and any
+    /// additional config-supplied patterns.
+    fn overrides(&self) -> Override {
+        let mut overrides = OverrideBuilder::new(&self.root);
+        for dir in DEFAULT_EXCLUSIONS {
+            // The `!` prefix in override globs means "exclude this pattern".
+            let pattern = format!("!{dir}/");
+            overrides
+                .add(&pattern)
+                .expect("default exclusion pattern should be valid");
+        }
+
+        // Add config-driven ignore patterns as exclusion overrides.
+        for pattern in &self.ignore_patterns {
+            let negated = format!("!{pattern}");
+            overrides
+                .add(&negated)
+                .expect("config ignore pattern should be valid");
+        }
+
+        overrides.build().expect("override builder should succeed")
+    }
+
+    /// Whether a file at `rel_path` (relative to the walk root) would be
+    /// walked: not hidden (except allowlisted names), not under a default
+    /// exclusion, and not matched by a config pattern.
+    ///
+    /// Used for files that are not on disk; `.gitignore` and `.wonkignore`
+    /// rules are not consulted.
+    pub fn accepts(&self, rel_path: &Path) -> bool {
+        let hidden = rel_path.components().any(|c| {
+            let name = c.as_os_str().to_string_lossy();
+            name.starts_with('.') && !HIDDEN_ALLOWLIST.iter().any(|a| *a == &*name)
+        });
+        if hidden {
+            return false;
+        }
+        let overrides = self.overrides();
+        let dirs_ok = rel_path
+            .ancestors()
+            .skip(1)
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .all(|dir| !overrides.matched(dir, true).is_ignore());
+        dirs_ok && !overrides.matched(rel_path, false).is_ignore()
+    }
+
     /// Walk the file tree sequentially and collect all matching file paths.
     pub fn collect_paths(&self) -> Vec<PathBuf> {
         let builder = self.make_builder();
@@ -637,6 +723,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn descends_into_allowed_submodules() {
+        let td = TestDir::new();
+        fs::create_dir(td.path().join(".git")).unwrap();
+        td.create_file("src/main.rs");
+        td.create_file("deps/sub/lib.rs");
+        fs::write(
+            td.path().join("deps/sub/.git"),
+            "gitdir: ../../.git/modules/sub",
+        )
+        .unwrap();
+        td.create_file("deps/other/lib.rs");
+        fs::create_dir(td.path().join("deps/other/.git")).unwrap();
+
+        let walker = Walker::new(td.path()).with_submodules(vec![td.path().join("deps/sub")]);
+        let rel = sorted_relative(td.path(), &walker.collect_paths());
+
+        assert!(rel.contains(&"deps/sub/lib.rs".to_string()), "got: {rel:?}");
+        assert!(
+            !rel.iter().any(|p| p.starts_with("deps/other")),
+            "nested repos that are not listed stay skipped, got: {rel:?}"
+        );
+    }
+
     #[test]
     fn skips_nested_git_file_worktree() {
         let td = TestDir::new();
@@ -742,4 +852,17 @@ mod tests {
             "nested repo should be excluded in both modes, got: {seq:?}"
         );
     }
+
+    #[test]
+    fn accepts_applies_walk_rules_to_virtual_paths() {
+        let walker = Walker::new("/virtual").with_ignore_patterns(&["*.gen.rs".into()]);
+        assert!(walker.accepts(Path::new("src/main.rs")));
+        assert!(walker.accepts(Path::new(".github/workflows/ci.yml")));
+        assert!(walker.accepts(Path::new("src/build.rs")));
+        assert!(!walker.accepts(Path::new("node_modules/pkg/index.js")));
+        assert!(!walker.accepts(Path::new("app/target/debug/out.rs")));
+        assert!(!walker.accepts(Path::new(".env")));
+        assert!(!walker.accepts(Path::new("src/.hidden/a.rs")));
+        assert!(!walker.accepts(Path::new("src/schema.gen.rs")));
+    }
 }
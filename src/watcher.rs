@@ -7,10 +7,11 @@
 //! [`IgnoreMatcher`]), classifies each event as Created / Modified / Deleted,
 //! and dispatches to a caller-supplied handler.
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
 use crossbeam_channel::{Receiver, Sender};
@@ -79,12 +80,28 @@ const DEFAULT_EXCLUSIONS: &[&str] = &[
 /// Hidden directory names that are allowed through the filter.
 const HIDDEN_ALLOWLIST: &[&str] = &[".github"];
 
+/// File name suffixes of editor swap and backup files and of SQLite
+/// journals, which churn while a file is edited or the index is written.
+const TEMP_FILE_SUFFIXES: &[&str] = &[
+    ".swp", ".swo", ".swx", "~", ".tmp", "-journal", "-wal", "-shm",
+];
+
+/// Whether `name` is an editor or database temporary file: a suffix in
+/// [`TEMP_FILE_SUFFIXES`], an Emacs autosave (`#main.rs#`), or the `4913`
+/// file Vim creates to probe directory permissions.
+fn is_temp_file(name: &str) -> bool {
+    TEMP_FILE_SUFFIXES.iter().any(|s| name.ends_with(s))
+        || (name.len() > 1 && name.starts_with('#') && name.ends_with('#'))
+        || name == "4913"
+}
+
 /// Determine whether a filesystem event for `path` should be processed.
 ///
 /// Returns `false` for paths that fall inside default-excluded directories,
-/// hidden directories (unless allowlisted), the `.git` directory itself,
-/// or nested worktree boundaries (directories containing a `.git` entry
-/// that are not the repo root).
+/// hidden directories (unless allowlisted), the `.git` and `.wonk`
+/// directories themselves, nested worktree boundaries (directories
+/// containing a `.git` entry that are not the repo root), and editor or
+/// database temporary files.
 ///
 /// `path` must be relative to `repo_root`.  `repo_root` is the absolute
 /// path to the repository root; it is used to probe the filesystem for
@@ -97,8 +114,9 @@ pub fn should_process(path: &Path, repo_root: &Path) -> bool {
     for component in path.components() {
         let name = component.as_os_str().to_string_lossy();
 
-        // Skip the `.git` directory itself (internal git data).
-        if name == ".git" {
+        // Skip the `.git` directory itself (internal git data) and a local
+        // index, so the daemon never reacts to its own database writes.
+        if name == ".git" || name == ".wonk" {
             return false;
         }
 
@@ -117,6 +135,13 @@ pub fn should_process(path: &Path, repo_root: &Path) -> bool {
         }
     }
 
+    if path
+        .file_name()
+        .is_some_and(|name| is_temp_file(&name.to_string_lossy()))
+    {
+        return false;
+    }
+
     // Worktree boundary check: walk ancestor directories between
     // repo_root and the event path.  If any directory along the way
     // contains a `.git` entry (file or directory), the path is inside a
@@ -368,17 +393,38 @@ impl FileWatcher {
 ///
 /// `handler` receives a slice of events per batch.  It is expected to perform
 /// incremental re-indexing (upsert for Created/Modified, removal for Deleted).
-pub fn run_event_loop<F>(rx: &Receiver<Vec<FileEvent>>, shutdown: &Arc<AtomicBool>, mut handler: F)
+pub fn run_event_loop<F>(rx: &Receiver<Vec<FileEvent>>, shutdown: &Arc<AtomicBool>, handler: F)
 where
     F: FnMut(&[FileEvent]),
+{
+    run_event_loop_with_tick(rx, shutdown, Duration::MAX, || {}, handler);
+}
+
+/// Like [`run_event_loop`], but also calls `on_tick` at least every `tick`
+/// while the loop is alive, whether or not events arrive. The daemon uses
+/// this for its heartbeat, so a tick that stops means the loop is stuck.
+pub fn run_event_loop_with_tick<T, F>(
+    rx: &Receiver<Vec<FileEvent>>,
+    shutdown: &Arc<AtomicBool>,
+    tick: Duration,
+    mut on_tick: T,
+    mut handler: F,
+) where
+    T: FnMut(),
+    F: FnMut(&[FileEvent]),
 {
     // Use a short timeout so we can check the shutdown flag periodically.
     let poll_timeout = Duration::from_millis(200);
+    let mut last_tick = Instant::now();
 
     loop {
         if shutdown.load(Ordering::Relaxed) {
             break;
         }
+        if last_tick.elapsed() >= tick {
+            on_tick();
+            last_tick = Instant::now();
+        }
 
         match rx.recv_timeout(poll_timeout) {
             Ok(events) => {
@@ -398,6 +444,53 @@ where
     }
 }
 
+/// Merge the batches already waiting in `rx` into `events`, keeping only
+/// the latest event for each path (in order of first appearance).
+pub fn coalesce_events(events: &[FileEvent], rx: &Receiver<Vec<FileEvent>>) -> Vec<FileEvent> {
+    let mut merged = events.to_vec();
+    while let Ok(batch) = rx.try_recv() {
+        merged.extend(batch);
+    }
+    dedup_events(merged)
+}
+
+/// Keep the latest event for each path, in order of first appearance.
+pub(crate) fn dedup_events(events: Vec<FileEvent>) -> Vec<FileEvent> {
+    let mut slot: HashMap<PathBuf, usize> = HashMap::new();
+    let mut out: Vec<FileEvent> = Vec::with_capacity(events.len());
+    for event in events {
+        match slot.get(event.path()) {
+            Some(&i) => out[i] = event,
+            None => {
+                slot.insert(event.path().to_path_buf(), out.len());
+                out.push(event);
+            }
+        }
+    }
+    out
+}
+
+/// Keep receiving events until none arrive for `quiet`, then return them
+/// coalesced as by [`coalesce_events`].  Returns early on shutdown or when
+/// the channel disconnects.
+///
+/// Used to let a flood of events (a checkout, a rebase) finish before
+/// acting on it once.
+pub fn wait_for_quiet(
+    rx: &Receiver<Vec<FileEvent>>,
+    shutdown: &Arc<AtomicBool>,
+    quiet: Duration,
+) -> Vec<FileEvent> {
+    let mut events = Vec::new();
+    while !shutdown.load(Ordering::Relaxed) {
+        match rx.recv_timeout(quiet) {
+            Ok(batch) => events.extend(batch),
+            Err(_) => break,
+        }
+    }
+    dedup_events(events)
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -490,6 +583,32 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_should_process_rejects_index_dir() {
+        assert!(!should_process(
+            Path::new(".wonk/index.db-wal"),
+            Path::new(NO_ROOT)
+        ));
+    }
+
+    #[test]
+    fn test_should_process_rejects_editor_temp_files() {
+        for path in [
+            "src/.main.rs.swp",
+            "src/main.rs.swo",
+            "src/main.rs~",
+            "src/#main.rs#",
+            "src/4913",
+            "data/cache.db-journal",
+        ] {
+            assert!(
+                !should_process(Path::new(path), Path::new(NO_ROOT)),
+                "{path}"
+            );
+        }
+        assert!(should_process(Path::new("src/swp.rs"), Path::new(NO_ROOT)));
+    }
+
     #[test]
     fn test_should_process_rejects_hidden_directory() {
         assert!(!should_process(
@@ -772,6 +891,72 @@ mod tests {
         assert_eq!(received, vec![FileEvent::Modified(PathBuf::from("a.rs"))]);
     }
 
+    #[test]
+    fn test_run_event_loop_with_tick_ticks_while_idle() {
+        let (_tx, rx) = crossbeam_channel::unbounded::<Vec<FileEvent>>();
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let shutdown_clone = Arc::clone(&shutdown);
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(700));
+            shutdown_clone.store(true, Ordering::Relaxed);
+        });
+
+        let mut ticks = 0;
+        run_event_loop_with_tick(
+            &rx,
+            &shutdown,
+            Duration::from_millis(150),
+            || ticks += 1,
+            |_| {},
+        );
+
+        assert!(ticks >= 2, "expected idle ticks, got {ticks}");
+    }
+
+    #[test]
+    fn test_coalesce_events_keeps_latest_event_per_path() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        tx.send(vec![
+            FileEvent::Deleted(PathBuf::from("a.rs")),
+            FileEvent::Modified(PathBuf::from("c.rs")),
+        ])
+        .unwrap();
+
+        let first = [
+            FileEvent::Modified(PathBuf::from("a.rs")),
+            FileEvent::Modified(PathBuf::from("b.rs")),
+        ];
+        assert_eq!(
+            coalesce_events(&first, &rx),
+            [
+                FileEvent::Deleted(PathBuf::from("a.rs")),
+                FileEvent::Modified(PathBuf::from("b.rs")),
+                FileEvent::Modified(PathBuf::from("c.rs")),
+            ]
+        );
+        assert!(rx.is_empty());
+    }
+
+    #[test]
+    fn test_wait_for_quiet_drains_until_idle() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let sender = std::thread::spawn(move || {
+            for i in 0..5 {
+                tx.send(vec![FileEvent::Modified(PathBuf::from(format!("{i}.rs")))])
+                    .unwrap();
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            // Keep the channel open past the quiet window.
+            std::thread::sleep(Duration::from_millis(300));
+        });
+
+        let events = wait_for_quiet(&rx, &shutdown, Duration::from_millis(100));
+        assert_eq!(events.len(), 5);
+        sender.join().unwrap();
+    }
+
     // ---- Integration: FileWatcher with real filesystem ----
 
     #[test]
@@ -0,0 +1,78 @@
+//! Integration tests for indexes built by `wonk init --archive`.
+//!
+//! The directory such an index belongs to holds no sources, so commands that
+//! index the working tree must leave it alone.
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// Build the binary path. In test mode, cargo puts it in target/debug/.
+fn wonk_bin() -> std::path::PathBuf {
+    let mut path = std::env::current_exe()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .to_path_buf();
+    path.push("wonk");
+    path
+}
+
+/// Run a git command in `dir`, panicking on failure.
+fn run_git(dir: &Path, args: &[&str]) {
+    let output = Command::new("git")
+        .args(["-c", "user.name=Test", "-c", "user.email=test@test.com"])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("git command failed to execute");
+    assert!(output.status.success(), "git {args:?} failed");
+}
+
+/// Run wonk in `dir` with `home` as `$HOME`, so the central store is private
+/// to the test.
+fn wonk(dir: &Path, home: &Path, args: &[&str]) -> Output {
+    Command::new(wonk_bin())
+        .arg("--no-daemon")
+        .args(args)
+        .current_dir(dir)
+        .env("HOME", home)
+        .output()
+        .expect("failed to run wonk")
+}
+
+#[test]
+fn update_leaves_archive_index_intact() {
+    let tmp = tempfile::tempdir().unwrap();
+    let home = tmp.path().join("home");
+    let work = tmp.path().join("work");
+    let review = tmp.path().join("review");
+    for dir in [&home, &work.join("src"), &review] {
+        std::fs::create_dir_all(dir).unwrap();
+    }
+    std::fs::write(work.join("src/lib.rs"), "pub fn archived_fn() {}\n").unwrap();
+    run_git(&work, &["init", "-q"]);
+    run_git(&work, &["add", "."]);
+    run_git(&work, &["commit", "-qm", "init"]);
+    run_git(tmp.path(), &["clone", "-q", "--bare", "work", "bare.git"]);
+
+    let bare = tmp.path().join("bare.git");
+    let init = wonk(
+        &review,
+        &home,
+        &["init", "--archive", bare.to_str().unwrap()],
+    );
+    assert!(init.status.success(), "{init:?}");
+
+    for args in [&["update"][..], &["init"][..]] {
+        let out = wonk(&review, &home, args);
+        assert!(!out.status.success(), "wonk {args:?} should refuse");
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        assert!(stderr.contains("archive:bare.git"), "{stderr}");
+    }
+
+    let sym = wonk(&review, &home, &["sym", "archived_fn", "--format", "json"]);
+    let stdout = String::from_utf8_lossy(&sym.stdout);
+    assert!(stdout.contains("\"src/lib.rs\""), "{stdout}");
+}
@@ -0,0 +1,23 @@
+#include <stdio.h>
+#include "store.h"
+
+#define LIMIT 10
+
+struct point {
+    int x;
+    int y;
+};
+
+typedef struct point point_t;
+
+enum mode { FAST, SAFE };
+
+static int square(int v) {
+    return v * v;
+}
+
+int main(void) {
+    point_t p = {1, 2};
+    printf("%d\n", square(p.x));
+    return 0;
+}
@@ -0,0 +1,28 @@
+#include <string>
+#include <vector>
+
+namespace geo {
+
+class Shape {
+public:
+    virtual double area() const = 0;
+};
+
+class Square : public Shape {
+public:
+    explicit Square(double side) : side_(side) {}
+    double area() const override { return side_ * side_; }
+
+private:
+    double side_;
+};
+
+double total(const std::vector<Shape*>& shapes) {
+    double sum = 0;
+    for (auto* s : shapes) {
+        sum += s->area();
+    }
+    return sum;
+}
+
+}  // namespace geo
@@ -0,0 +1,35 @@
+using System;
+using System.Collections.Generic;
+using Json = System.Text.Json;
+
+namespace Example
+{
+    public interface IShape
+    {
+        double Area();
+    }
+
+    public class Circle : IShape
+    {
+        private readonly double radius;
+
+        public Circle(double radius)
+        {
+            this.radius = radius;
+        }
+
+        public double Area()
+        {
+            return Math.PI * Square(radius);
+        }
+
+        private static double Square(double v) => v * v;
+    }
+
+    public enum Mode { Fast, Safe }
+
+    public record Point(double X, double Y) : IShape
+    {
+        public double Area() => 0;
+    }
+}
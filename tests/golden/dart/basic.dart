@@ -0,0 +1,44 @@
+import 'package:flutter/material.dart';
+import 'src/util.dart' as util;
+part 'basic.g.dart';
+
+/// Maximum number of sides.
+const int maxSides = 12;
+
+/// A geometric shape.
+abstract class Shape extends Base with Named implements Comparable<Shape> {
+  Shape(this.sides);
+  Shape.unit() : sides = 1;
+  factory Shape.of(int n) => Square(n);
+
+  final int sides;
+
+  /// The area.
+  double area();
+
+  static Shape parse(String s) {
+    return Shape.of(int.parse(s));
+  }
+
+  Future<void> load() async {
+    await util.fetch(sides);
+  }
+}
+
+mixin Named {
+  String describe() => runtimeType.toString();
+}
+
+extension IntShapes on int {
+  Shape toShape() => Square(this);
+}
+
+enum Color { red, green }
+
+typedef Predicate<T> = bool Function(T value);
+
+void main() {
+  final s = Square(4);
+  print(s.area());
+  runApp(const MyApp());
+}
@@ -0,0 +1,37 @@
+defmodule Shapes.Circle do
+  @moduledoc """
+  Circles and their areas.
+  """
+  alias Shapes.{Point, Format}
+  import Shapes.Math, only: [square: 1]
+  use Shapes.Shape
+  require Logger
+
+  defstruct [:center, radius: 1.0]
+
+  @doc "Create a circle with the given radius."
+  @spec new(number) :: t()
+  def new(radius) when radius > 0 do
+    %__MODULE__{center: Point.origin(), radius: radius}
+  end
+
+  @doc "Area of the circle."
+  def area(%__MODULE__{radius: r}), do: compute(r)
+
+  def describe(circle) do
+    circle |> area() |> Format.number()
+  end
+
+  defp compute(r) do
+    Logger.debug("computing")
+    :math.pi() * square(r)
+  end
+
+  defmacro unit do
+    quote do: new(1.0)
+  end
+
+  defmodule Error do
+    defstruct [:message]
+  end
+end
@@ -0,0 +1,29 @@
+package store
+
+import (
+	"fmt"
+	"strings"
+)
+
+type Store struct {
+	items map[string]int
+}
+
+type Getter interface {
+	Get(key string) (int, bool)
+}
+
+func New() *Store {
+	return &Store{items: map[string]int{}}
+}
+
+func (s *Store) Get(key string) (int, bool) {
+	v, ok := s.items[strings.ToLower(key)]
+	return v, ok
+}
+
+func Describe(s *Store) string {
+	return fmt.Sprintf("%d items", len(s.items))
+}
+
+const Limit = 10
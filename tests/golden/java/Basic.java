@@ -0,0 +1,28 @@
+package com.example;
+
+import java.util.List;
+import java.util.ArrayList;
+
+interface Shape {
+    double area();
+}
+
+abstract class Base {
+    abstract String name();
+}
+
+public class Basic extends Base implements Shape {
+    private final List<String> tags = new ArrayList<>();
+
+    public double area() {
+        return compute(2.0);
+    }
+
+    String name() {
+        return "basic";
+    }
+
+    private static double compute(double r) {
+        return Math.PI * r * r;
+    }
+}
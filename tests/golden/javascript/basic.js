@@ -0,0 +1,20 @@
+const fs = require("fs");
+import { format } from "./format";
+
+class Animal {
+  speak() {
+    return "...";
+  }
+}
+
+class Dog extends Animal {
+  speak() {
+    return format(super.speak());
+  }
+}
+
+function readConfig(file) {
+  return JSON.parse(fs.readFileSync(file, "utf8"));
+}
+
+export const VERSION = "1.0";
@@ -0,0 +1,38 @@
+package com.example
+
+import com.example.util.checksum
+import kotlin.math.*
+
+interface Shape {
+    fun area(): Double
+}
+
+abstract class Base {
+    abstract fun name(): String
+}
+
+data class Basic(val radius: Double) : Base(), Shape {
+    private val tags = mutableListOf<String>()
+
+    override fun area(): Double = compute(radius)
+
+    override fun name(): String {
+        return checksum("basic")
+    }
+
+    companion object {
+        const val SIDES = 0
+
+        fun unit(): Basic = Basic(1.0)
+    }
+}
+
+object Registry {
+    fun register(shape: Shape) {
+        println(shape.area())
+    }
+}
+
+fun Shape.describe(): String = "area " + area()
+
+private fun compute(r: Double): Double = PI * r * r
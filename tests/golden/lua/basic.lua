@@ -0,0 +1,33 @@
+local util = require("app.util")
+local json = require "cjson"
+
+local Shape = {}
+Shape.__index = Shape
+
+--- Create a shape with the given radius.
+function Shape.new(radius)
+  return setmetatable({ radius = radius }, Shape)
+end
+
+--- Area of the shape.
+function Shape:area()
+  return compute(self.radius)
+end
+
+function Shape:describe()
+  return util.format("area %d", self:area())
+end
+
+Shape.encode = function(shape)
+  return json.encode(shape)
+end
+
+local function compute(r)
+  return math.pi * r * r
+end
+
+function register(shape)
+  print(shape:describe())
+end
+
+return Shape
@@ -0,0 +1,27 @@
+<?php
+
+namespace App\Billing;
+
+use App\Support\Money;
+
+interface Payable
+{
+    public function total(): float;
+}
+
+class Invoice implements Payable
+{
+    const TAX = 0.2;
+
+    public function __construct(private float $amount) {}
+
+    public function total(): float
+    {
+        return Money::round($this->amount * (1 + self::TAX));
+    }
+}
+
+function make_invoice(float $amount): Invoice
+{
+    return new Invoice($amount);
+}
@@ -0,0 +1,25 @@
+import os
+from collections import OrderedDict
+
+
+class Base:
+    def describe(self):
+        return "base"
+
+
+class Worker(Base):
+    """Runs jobs."""
+
+    def __init__(self, name):
+        self.name = name
+
+    def run(self, job):
+        path = os.path.join("/tmp", job)
+        return helper(path)
+
+
+def helper(path):
+    return OrderedDict(path=path)
+
+
+LIMIT = 10
@@ -0,0 +1,26 @@
+require "json"
+require_relative "helpers"
+
+module Billing
+  class Base
+    def describe
+      "base"
+    end
+  end
+
+  class Invoice < Base
+    TAX = 0.2
+
+    def initialize(amount)
+      @amount = amount
+    end
+
+    def total
+      round(@amount * (1 + TAX))
+    end
+
+    def self.parse(text)
+      new(JSON.parse(text)["amount"])
+    end
+  end
+end
@@ -0,0 +1,32 @@
+use std::collections::HashMap;
+
+/// A key-value store.
+pub struct Store {
+    items: HashMap<String, u32>,
+}
+
+pub trait Named {
+    fn name(&self) -> &str;
+}
+
+impl Store {
+    pub fn new() -> Self {
+        Store { items: HashMap::new() }
+    }
+
+    pub fn get(&self, key: &str) -> Option<u32> {
+        self.items.get(key).copied()
+    }
+}
+
+pub enum Mode {
+    Fast,
+    Safe,
+}
+
+const LIMIT: usize = 10;
+
+fn main() {
+    let store = Store::new();
+    println!("{:?}", store.get("a"));
+}
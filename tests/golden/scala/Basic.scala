@@ -0,0 +1,40 @@
+package com.example
+
+import com.example.util.checksum
+import scala.math._
+import scala.collection.mutable.{ListBuffer, Map => MMap}
+
+trait Shape {
+  def area: Double
+}
+
+abstract class Base {
+  def name(): String
+}
+
+case class Basic(radius: Double) extends Base with Shape {
+  private val tags = ListBuffer[String]()
+
+  override def area: Double = compute(radius)
+
+  override def name(): String = {
+    checksum("basic")
+  }
+}
+
+object Basic {
+  val Sides = 0
+
+  def unit(): Basic = Basic(1.0)
+}
+
+object Registry {
+  def register[T <: Shape](shape: T): Unit = {
+    println(shape.area)
+  }
+}
+
+extension (s: Shape)
+  def describe: String = "area " + s.area
+
+def compute(r: Double): Double = Pi * r * r
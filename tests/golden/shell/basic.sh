@@ -0,0 +1,25 @@
+#!/usr/bin/env bash
+# Release helpers shared by the deploy scripts.
+set -euo pipefail
+
+source ./lib/common.sh
+. "$(dirname "$0")/env.sh"
+
+readonly VERSION="1.2.0"
+
+# Build the release artifact into dist/.
+build() {
+  local out="${1:-dist}"
+  make -C "$out" all
+  log_info "built $VERSION"
+}
+
+function deploy {
+  build dist
+  upload_artifact "dist/app-$VERSION.tar.gz" | tee deploy.log
+}
+
+function cleanup() { rm -rf dist; }
+
+trap cleanup EXIT
+deploy
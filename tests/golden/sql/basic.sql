@@ -0,0 +1,30 @@
+-- Registered accounts, one row per login.
+CREATE TABLE users (
+  id BIGINT PRIMARY KEY,
+  email TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE IF NOT EXISTS billing.invoices (
+  id BIGINT PRIMARY KEY,
+  user_id BIGINT NOT NULL REFERENCES users (id),
+  total NUMERIC(10, 2)
+);
+
+CREATE UNIQUE INDEX idx_users_email ON users (email);
+
+CREATE VIEW unpaid_invoices AS
+  SELECT i.id, u.email
+  FROM billing.invoices i
+  JOIN users u ON u.id = i.user_id
+  WHERE i.total > 0;
+
+CREATE TYPE invoice_state AS ENUM ('open', 'paid');
+
+-- Sum of a user's invoices.
+CREATE OR REPLACE FUNCTION user_total(uid BIGINT) RETURNS NUMERIC AS $$
+  SELECT coalesce(sum(total), 0) FROM billing.invoices WHERE user_id = uid;
+$$ LANGUAGE sql;
+
+CREATE PROCEDURE purge_users()
+LANGUAGE sql
+AS $$ DELETE FROM users WHERE email IS NULL $$;
@@ -0,0 +1,14 @@
+import React from "react";
+import { Button } from "./button";
+
+interface Props {
+  label: string;
+}
+
+export function Toolbar({ label }: Props) {
+  return <Button onClick={() => save(label)}>{label}</Button>;
+}
+
+function save(label: string) {
+  console.log(label);
+}
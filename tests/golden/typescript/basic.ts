@@ -0,0 +1,22 @@
+import { readFile } from "fs";
+import * as path from "path";
+
+export interface Shape {
+  area(): number;
+}
+
+export type Point = { x: number; y: number };
+
+export class Circle implements Shape {
+  constructor(private radius: number) {}
+
+  area(): number {
+    return Math.PI * this.radius ** 2;
+  }
+}
+
+export function load(name: string): void {
+  readFile(path.join("data", name), () => {});
+}
+
+const DEFAULT_RADIUS = 1;